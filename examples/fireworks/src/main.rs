@@ -37,14 +37,15 @@ impl Material for FireworksMaterial {
             },
             depth_test: DepthTest::LessOrEqual,
             write_mask: WriteMask::COLOR,
+            ..Default::default()
         }
     }
     fn material_type(&self) -> MaterialType {
         MaterialType::Transparent
     }
 
-    fn id(&self) -> u16 {
-        0b1u16
+    fn id(&self) -> u32 {
+        0b1u32
     }
 }
 // Entry point for non-wasm
@@ -110,6 +111,7 @@ pub fn run() {
         camera.set_viewport(frame_input.viewport);
 
         control.handle_events(&mut camera, &mut frame_input.events);
+        control.update(&mut camera, frame_input.elapsed_time);
         let elapsed_time = (frame_input.elapsed_time * 0.001) as f32;
 
         // Update the time in the particlesystem; this automatically integrates the velocity and
@@ -72,6 +72,7 @@ pub async fn run() {
         let mut change = frame_input.first_frame;
         change |= camera.set_viewport(frame_input.viewport);
         change |= control.handle_events(&mut camera, &mut frame_input.events);
+        change |= control.update(&mut camera, frame_input.elapsed_time);
 
         for event in frame_input.events.iter() {
             if let Event::KeyPress { kind, .. } = event {
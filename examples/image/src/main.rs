@@ -84,6 +84,7 @@ pub async fn run() {
                 texture: image.clone(),
                 transformation: Mat3::from_scale(texture_transform_scale)
                     * Mat3::from_translation(vec2(texture_transform_x, texture_transform_y)),
+                uv_channel: 0,
             }),
             ..Default::default()
         };
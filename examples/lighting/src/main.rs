@@ -174,6 +174,8 @@ pub async fn run() {
                             spot0.clear_shadow_map();
                             directional0.clear_shadow_map();
                             directional1.clear_shadow_map();
+                            point0.clear_shadow_map();
+                            point1.clear_shadow_map();
                         }
                     }
 
@@ -252,6 +254,8 @@ pub async fn run() {
             directional0.generate_shadow_map(1024, &model);
             directional1.generate_shadow_map(1024, &model);
             spot0.generate_shadow_map(1024, &model);
+            point0.generate_shadow_map(512, &model);
+            point1.generate_shadow_map(512, &model);
         }
 
         let lights = [
@@ -60,6 +60,12 @@ pub async fn run() {
     let mut quadratic = 0.5;
     let mut light_count = 20;
     let mut color = [1.0; 4];
+    let mut bloom = BloomEffect {
+        threshold: 0.8,
+        ..Default::default()
+    };
+    let mut color_texture = new_color_texture(&context, camera.viewport());
+    let mut depth_texture = new_depth_texture(&context, camera.viewport());
     window.render_loop(move |mut frame_input| {
         let mut panel_width = 0.0;
         gui.update(
@@ -82,12 +88,19 @@ pub async fn run() {
                             .text("Attenuation quadratic"),
                     );
                     ui.color_edit_button_rgba_unmultiplied(&mut color);
+                    ui.add(
+                        Slider::new::<f32>(&mut bloom.intensity, 0.0..=2.0).text("Bloom intensity"),
+                    );
+                    ui.add(
+                        Slider::new::<f32>(&mut bloom.threshold, 0.0..=2.0).text("Bloom threshold"),
+                    );
 
                     ui.label("Tone mapping");
                     ui.radio_value(&mut camera.tone_mapping, ToneMapping::None, "None");
                     ui.radio_value(&mut camera.tone_mapping, ToneMapping::Reinhard, "Reinhard");
                     ui.radio_value(&mut camera.tone_mapping, ToneMapping::Aces, "Aces");
                     ui.radio_value(&mut camera.tone_mapping, ToneMapping::Filmic, "Filmic");
+                    ui.add(Slider::new::<f32>(&mut camera.exposure, -4.0..=4.0).text("Exposure"));
                 });
                 panel_width = gui_context.used_rect().width();
             },
@@ -121,14 +134,37 @@ pub async fn run() {
         camera.set_viewport(viewport);
 
         control.handle_events(&mut camera, &mut frame_input.events);
+        control.update(&mut camera, frame_input.elapsed_time);
+
+        // Render the scene with high dynamic range to be able to apply bloom to the bright parts
+        if color_texture.width() != viewport.width || color_texture.height() != viewport.height {
+            color_texture = new_color_texture(&context, viewport);
+            depth_texture = new_depth_texture(&context, viewport);
+        }
+        let mut hdr_camera = camera.clone();
+        hdr_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        hdr_camera.disable_tone_and_color_mapping();
+        RenderTarget::new(
+            color_texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.2, 0.2, 0.8, 1.0, 1.0))
+        .render(
+            &hdr_camera,
+            lights.iter().map(|l| l.object()).chain(&model),
+            &lights.iter().map(|l| l.light()).collect::<Vec<_>>(),
+        );
+        bloom.apply(&context, &mut color_texture);
 
         frame_input
             .screen()
             .clear(ClearState::color_and_depth(0.2, 0.2, 0.8, 1.0, 1.0))
-            .render(
+            .apply_screen_effect(
+                &ScreenEffect::default(),
                 &camera,
-                lights.iter().map(|l| l.object()).chain(&model),
-                &lights.iter().map(|l| l.light()).collect::<Vec<_>>(),
+                &[],
+                Some(ColorTexture::Single(&color_texture)),
+                None,
             )
             .write(|| gui.render())
             .unwrap();
@@ -137,6 +173,29 @@ pub async fn run() {
     });
 }
 
+fn new_color_texture(context: &Context, viewport: Viewport) -> Texture2D {
+    Texture2D::new_empty::<[f16; 4]>(
+        context,
+        viewport.width,
+        viewport.height,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+fn new_depth_texture(context: &Context, viewport: Viewport) -> DepthTexture2D {
+    DepthTexture2D::new::<f32>(
+        context,
+        viewport.width,
+        viewport.height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
 struct Glow {
     light: PointLight,
     velocity: Vec3,
@@ -37,7 +37,9 @@ pub async fn run() {
         .await
         .expect("failed to download the necessary assets, to enable running this example offline, place the relevant assets in a folder called 'assets' next to the three-d source")
     };
-    let image = Texture2D::new(&context, &loaded.deserialize("").unwrap());
+    let logo: CpuTexture = loaded.deserialize("").unwrap();
+    window.set_icon(Some(&logo)).unwrap();
+    let image = Texture2D::new(&context, &logo);
 
     let positions = vec![
         vec3(0.55, -0.4, 0.0),  // bottom right
@@ -80,8 +82,8 @@ impl Material for LogoMaterial<'_> {
         include_str!("shader.frag").to_string()
     }
 
-    fn id(&self) -> u16 {
-        0b1u16
+    fn id(&self) -> u32 {
+        0b1u32
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
@@ -92,7 +94,7 @@ impl Material for LogoMaterial<'_> {
     }
 
     fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
-        program.use_texture("image", &self.image);
+        program.use_texture("image", self.image);
     }
 
     fn render_states(&self) -> RenderStates {
@@ -27,8 +27,8 @@ impl Material for MandelbrotMaterial {
         MaterialType::Opaque
     }
 
-    fn id(&self) -> u16 {
-        0b11u16
+    fn id(&self) -> u32 {
+        0b11u32
     }
 }
 
@@ -78,15 +78,17 @@ pub fn main() {
 
         for event in frame_input.events.iter() {
             match *event {
-                Event::MouseMotion { delta, button, .. } => {
-                    if button == Some(MouseButton::Left) {
-                        let speed = 0.003 * camera.position().z.abs();
-                        let right = camera.right_direction();
-                        let up = right.cross(camera.view_direction());
-                        let delta = -right * speed * delta.0 + up * speed * delta.1;
-                        camera.translate(&delta);
-                        redraw = true;
-                    }
+                Event::MouseMotion {
+                    delta,
+                    button: Some(MouseButton::Left),
+                    ..
+                } => {
+                    let speed = 0.003 * camera.position().z.abs();
+                    let right = camera.right_direction();
+                    let up = right.cross(camera.view_direction());
+                    let delta = -right * speed * delta.0 + up * speed * delta.1;
+                    camera.translate(&delta);
+                    redraw = true;
                 }
                 Event::MouseWheel {
                     delta, position, ..
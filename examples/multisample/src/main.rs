@@ -4,6 +4,8 @@ use three_d::*;
 enum RenderMethod {
     Direct,
     ToTexture,
+    ToTextureWithFxaa,
+    ToTextureWithSmaa,
     ToMultisampledTexture(u32),
 }
 
@@ -84,6 +86,16 @@ pub fn main() {
                         RenderMethod::ToTexture,
                         "To texture (No MSAA)",
                     );
+                    ui.radio_value(
+                        &mut render_steps,
+                        RenderMethod::ToTextureWithFxaa,
+                        "To texture (FXAA)",
+                    );
+                    ui.radio_value(
+                        &mut render_steps,
+                        RenderMethod::ToTextureWithSmaa,
+                        "To texture (SMAA)",
+                    );
                     ui.radio_value(
                         &mut render_steps,
                         RenderMethod::ToMultisampledTexture(1),
@@ -132,8 +144,11 @@ pub fn main() {
                     .render(&camera, renderable_things, &[]);
             }
 
-            RenderMethod::ToTexture => {
-                // Render the shapes to a non-multisample texture, and copy the color texture to the screen.
+            RenderMethod::ToTexture
+            | RenderMethod::ToTextureWithFxaa
+            | RenderMethod::ToTextureWithSmaa => {
+                // Render the shapes to a non-multisample texture, and copy the color texture to the screen,
+                // optionally applying a post-process anti-aliasing effect on the way.
                 let mut color_texture = Texture2D::new_empty::<[u8; 4]>(
                     &context,
                     frame_input.viewport.width,
@@ -159,13 +174,29 @@ pub fn main() {
                 .clear(clear_state)
                 .render(&camera, renderable_things, &[]);
 
-                frame_input.screen().apply_screen_effect(
-                    &CopyEffect::default(),
-                    &camera,
-                    &[],
-                    Some(ColorTexture::Single(&color_texture)),
-                    None,
-                );
+                match render_steps {
+                    RenderMethod::ToTextureWithFxaa => {
+                        frame_input.screen().apply_screen_effect(
+                            &FxaaEffect::default(),
+                            &camera,
+                            &[],
+                            Some(ColorTexture::Single(&color_texture)),
+                            None,
+                        );
+                    }
+                    RenderMethod::ToTextureWithSmaa => {
+                        SmaaEffect::default().apply(&frame_input.screen(), &camera, &color_texture);
+                    }
+                    _ => {
+                        frame_input.screen().apply_screen_effect(
+                            &CopyEffect::default(),
+                            &camera,
+                            &[],
+                            Some(ColorTexture::Single(&color_texture)),
+                            None,
+                        );
+                    }
+                }
             }
 
             RenderMethod::ToMultisampledTexture(sample_count) => {
@@ -1,10 +1,12 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use three_d::{renderer::*, WindowedContext};
 
 struct Scene {
     camera: Camera,
-    model: Gm<Mesh, ColorMaterial>,
+    model: Rc<RefCell<Gm<Mesh, ColorMaterial>>>,
 }
 
 pub fn main() {
@@ -27,7 +29,15 @@ pub fn main() {
         ..Default::default()
     };
 
-    let mut windows = HashMap::new();
+    let mut windows: HashMap<
+        _,
+        (
+            winit::window::Window,
+            WindowedContext,
+            three_d::FrameInputGenerator,
+            Scene,
+        ),
+    > = HashMap::new();
     for i in 0..2 {
         #[cfg(not(target_arch = "wasm32"))]
         let window_builder = winit::window::WindowBuilder::new()
@@ -55,13 +65,18 @@ pub fn main() {
                 .with_prevent_default(true)
         };
         let window = window_builder.build(&event_loop).unwrap();
-        let context = WindowedContext::from_winit_window(
-            &window,
-            three_d::SurfaceSettings {
-                vsync: false, // Wayland hangs in swap_buffers when one window is minimized or occluded
-                ..three_d::SurfaceSettings::default()
-            },
-        )
+        let settings = three_d::SurfaceSettings {
+            vsync: false, // Wayland hangs in swap_buffers when one window is minimized or occluded
+            ..three_d::SurfaceSettings::default()
+        };
+        let context = match windows.values().next() {
+            // Share the GPU resources with the first window, which is not supported on web
+            #[cfg(not(target_arch = "wasm32"))]
+            Some((_, shared_context, _, _)) => {
+                WindowedContext::from_winit_window_with_sharing(&window, settings, shared_context)
+            }
+            _ => WindowedContext::from_winit_window(&window, settings),
+        }
         .unwrap();
 
         let camera = Camera::new_perspective(
@@ -74,8 +89,16 @@ pub fn main() {
             10.0,
         );
 
-        let mut model = Gm::new(Mesh::new(&context, &cpu_mesh), ColorMaterial::default());
-        model.set_animation(|time| Mat4::from_angle_y(radians(time * 0.005)));
+        let model = match windows.values().next() {
+            // The model is only uploaded to the GPU once and then rendered in all windows
+            #[cfg(not(target_arch = "wasm32"))]
+            Some((_, _, _, scene)) => scene.model.clone(),
+            _ => {
+                let mut model = Gm::new(Mesh::new(&context, &cpu_mesh), ColorMaterial::default());
+                model.set_animation(|time| Mat4::from_angle_y(radians(time * 0.005)));
+                Rc::new(RefCell::new(model))
+            }
+        };
 
         let frame_input_generator = three_d::FrameInputGenerator::from_winit_window(&window);
         windows.insert(
@@ -103,11 +126,12 @@ pub fn main() {
                 let frame_input = frame_input_generator.generate(context);
 
                 scene.camera.set_viewport(frame_input.viewport);
-                scene.model.animate(frame_input.accumulated_time as f32);
+                let mut model = scene.model.borrow_mut();
+                model.animate(frame_input.accumulated_time as f32);
                 frame_input
                     .screen()
                     .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
-                    .render(&scene.camera, &scene.model, &[]);
+                    .render(&scene.camera, &*model, &[]);
 
                 context.swap_buffers().unwrap();
                 control_flow.set_poll();
@@ -137,12 +137,14 @@ pub async fn run() {
                     } else {
                         None
                     },
+                    lightmap_texture: model.material.lightmap_texture.clone(),
                     render_states: model.material.render_states,
                     is_transparent: model.material.is_transparent,
                     lighting_model: LightingModel::Cook(
                         NormalDistributionFunction::TrowbridgeReitzGGX,
                         GeometryFunction::SmithSchlickGGX,
                     ),
+                    ..model.material.clone()
                 };
                 model.render_with_material(&material, &camera, &[&light]);
                 gui.render()
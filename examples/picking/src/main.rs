@@ -53,6 +53,9 @@ pub async fn run() {
         .iter_mut()
         .for_each(|m| m.material.render_states.cull = Cull::Back);
 
+    let outline = OutlineEffect::default();
+    let mut selected = false;
+
     // main loop
     window.render_loop(move |mut frame_input| {
         let mut change = frame_input.first_frame;
@@ -64,10 +67,12 @@ pub async fn run() {
             } = *event
             {
                 if button == MouseButton::Left {
-                    if let Some(pick) = pick(&context, &camera, position, &monkey) {
+                    let picked = pick(&context, &camera, position, &monkey);
+                    if let Some(pick) = picked {
                         pick_mesh.set_transformation(Mat4::from_translation(pick));
-                        change = true;
                     }
+                    change |= selected || picked.is_some();
+                    selected = picked.is_some();
                 }
             }
         }
@@ -76,14 +81,18 @@ pub async fn run() {
 
         // draw
         if change {
-            frame_input
-                .screen()
+            let screen = frame_input.screen();
+            screen
                 .clear(ClearState::color_and_depth(1.0, 1.0, 1.0, 1.0, 1.0))
                 .render(
                     &camera,
                     monkey.into_iter().chain(&pick_mesh),
                     &[&ambient, &directional],
                 );
+            // Highlight the monkey when it is selected
+            if selected {
+                outline.apply(&screen, &camera, &monkey);
+            }
         }
 
         FrameOutput {
@@ -47,6 +47,50 @@ pub fn main() {
             ..Default::default()
         },
     );
+    let mut path = Path2D::new();
+    path.move_to(vec2(700.0, 100.0) * scale_factor)
+        .cubic_to(
+            vec2(800.0, 300.0) * scale_factor,
+            vec2(1000.0, 0.0) * scale_factor,
+            vec2(1100.0, 200.0) * scale_factor,
+        )
+        .line_to(vec2(1000.0, 350.0) * scale_factor)
+        .quadratic_to(
+            vec2(850.0, 150.0) * scale_factor,
+            vec2(700.0, 350.0) * scale_factor,
+        )
+        .close();
+    let shape = Gm::new(
+        Shape2D::new_filled(&context, &path),
+        ColorMaterial {
+            color: Srgba::new(255, 200, 0, 255),
+            ..Default::default()
+        },
+    );
+    let outline = Gm::new(
+        Shape2D::new_stroked(&context, &path, 8.0 * scale_factor),
+        ColorMaterial {
+            color: Srgba::BLACK,
+            ..Default::default()
+        },
+    );
+
+    // All the squares are rendered in a single draw call
+    let sprites = Gm::new(
+        SpriteBatch::new(
+            &context,
+            &(0..10)
+                .map(|i| Sprite2D {
+                    center: vec2(100.0 + 60.0 * i as f32, 650.0) * scale_factor,
+                    size: vec2(40.0, 40.0) * scale_factor,
+                    rotation: degrees(9.0 * i as f32).into(),
+                    color: Srgba::new(25 * i as u8, 0, 255 - 25 * i as u8, 255),
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>(),
+        ),
+        ColorMaterial::default(),
+    );
 
     window.render_loop(move |frame_input| {
         for event in frame_input.events.iter() {
@@ -78,7 +122,12 @@ pub fn main() {
             .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
             .render(
                 &Camera::new_2d(frame_input.viewport),
-                line.into_iter().chain(&rectangle).chain(&circle),
+                line.into_iter()
+                    .chain(&rectangle)
+                    .chain(&circle)
+                    .chain(&outline)
+                    .chain(&shape)
+                    .chain(&sprites),
                 &[],
             );
 
@@ -71,11 +71,27 @@ pub async fn run() {
         Some(vec3(1.0, 1.0, 0.0).normalize()),
     );
 
+    // Shows the texture as a sheet of 2x2 frames, animated at two frames per second
+    let mut animated = Sprites::new(
+        &context,
+        &[
+            vec3(-5.0, 5.0, -10.0),
+            vec3(0.0, 5.0, -10.0),
+            vec3(5.0, 5.0, -10.0),
+        ],
+        None,
+    );
+    animated.set_sizes(Some(&[vec2(1.0, 1.0), vec2(2.0, 2.0), vec2(3.0, 3.0)]));
+    animated.set_sprite_sheet(Some(SpriteSheet::new(2, 2, 2.0)));
+    animated.set_frame_offsets(Some(&[0.0, 1.0, 2.0]));
+
     let ambient = AmbientLight::new(&context, 1.0, Srgba::WHITE);
 
     window.render_loop(move |mut frame_input| {
         camera.set_viewport(frame_input.viewport);
         control.handle_events(&mut camera, &mut frame_input.events);
+        control.update(&mut camera, frame_input.elapsed_time);
+        animated.animate(0.001 * frame_input.accumulated_time as f32);
 
         frame_input
             .screen()
@@ -94,6 +110,10 @@ pub async fn run() {
                     .chain(&Gm {
                         geometry: &sprites,
                         material: &material,
+                    })
+                    .chain(&Gm {
+                        geometry: &animated,
+                        material: &material,
                     }),
                 &[&ambient],
             );
@@ -189,6 +189,7 @@ pub async fn run() {
         );
         change |= parameter_change;
         change |= control.handle_events(&mut camera, &mut frame_input.events);
+        change |= control.update(&mut camera, frame_input.elapsed_time);
 
         if parameter_change {
             let mut rng = rand::thread_rng();
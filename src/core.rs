@@ -32,6 +32,17 @@ mod scissor_box;
 #[doc(inline)]
 pub use scissor_box::*;
 
+mod render_stats;
+#[doc(inline)]
+pub use render_stats::*;
+
+mod gpu_timer;
+#[doc(inline)]
+pub use gpu_timer::*;
+
+pub mod bounding_volume;
+pub use bounding_volume::*;
+
 pub mod prelude {
 
     //!
@@ -59,6 +70,8 @@ pub enum CoreError {
     ShaderCompilation(String, String, String),
     #[error("failed to link shader program: {0}")]
     ShaderLink(String),
+    #[error("failed to resolve shader include {0}, the include has not been added to the context")]
+    ShaderInclude(String),
 }
 
 pub(crate) fn full_screen_draw(
@@ -67,7 +80,7 @@ pub(crate) fn full_screen_draw(
     render_states: RenderStates,
     viewport: Viewport,
 ) {
-    unsafe { context.bind_vertex_array(Some(context.vao)) };
+    unsafe { context.bind_vertex_array(Some(context.vertex_array())) };
     program.draw_arrays(render_states, viewport, 3);
 }
 
@@ -95,12 +108,7 @@ pub(crate) fn full_screen_vertex_shader_source() -> &'static str {
 mod data_type;
 use data_type::DataType;
 fn to_byte_slice<T: DataType>(data: &[T]) -> &[u8] {
-    unsafe {
-        std::slice::from_raw_parts(
-            data.as_ptr() as *const _,
-            data.len() * std::mem::size_of::<T>(),
-        )
-    }
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const _, std::mem::size_of_val(data)) }
 }
 
 fn from_byte_slice<T: DataType>(data: &[u8]) -> &[T] {
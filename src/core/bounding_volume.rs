@@ -0,0 +1,20 @@
+//!
+//! Bounding volumes and planes used for culling and for testing whether objects overlap,
+//! which complement the [AxisAlignedBoundingBox](crate::core::AxisAlignedBoundingBox) that bounds the geometries.
+//!
+
+mod plane;
+#[doc(inline)]
+pub use plane::*;
+
+mod bounding_sphere;
+#[doc(inline)]
+pub use bounding_sphere::*;
+
+mod obb;
+#[doc(inline)]
+pub use obb::*;
+
+mod frustum;
+#[doc(inline)]
+pub use frustum::*;
@@ -0,0 +1,128 @@
+use crate::core::*;
+
+///
+/// A sphere given by a center and a radius, which is cheaper to test than an [AxisAlignedBoundingBox] and does not change size when rotated.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    /// The center of the sphere.
+    pub center: Vec3,
+    /// The radius of the sphere.
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    ///
+    /// Creates a new sphere with the given center and radius.
+    ///
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    ///
+    /// Creates a sphere which contains all of the given positions.
+    /// The sphere is centered in the center of the bounding box of the positions, so it is not necessarily the smallest possible sphere.
+    ///
+    pub fn from_positions(positions: &[Vec3]) -> Self {
+        let center = AxisAlignedBoundingBox::new_with_positions(positions).center();
+        Self {
+            center,
+            radius: positions
+                .iter()
+                .map(|p| p.distance(center))
+                .fold(0.0, f32::max),
+        }
+    }
+
+    ///
+    /// Creates the smallest sphere which contains the given bounding box, which must not be empty or infinite.
+    ///
+    pub fn from_aabb(aabb: &AxisAlignedBoundingBox) -> Self {
+        Self {
+            center: aabb.center(),
+            radius: 0.5 * aabb.size().magnitude(),
+        }
+    }
+
+    ///
+    /// Returns whether the given position is inside the sphere.
+    ///
+    pub fn contains_point(&self, position: Vec3) -> bool {
+        self.center.distance2(position) <= self.radius * self.radius
+    }
+
+    ///
+    /// Returns whether the other sphere is completely inside this sphere.
+    ///
+    pub fn contains_sphere(&self, other: &BoundingSphere) -> bool {
+        self.center.distance(other.center) + other.radius <= self.radius
+    }
+
+    ///
+    /// Returns whether the other sphere is inside or intersects this sphere.
+    ///
+    pub fn intersects_sphere(&self, other: &BoundingSphere) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance2(other.center) <= radius * radius
+    }
+
+    ///
+    /// Returns whether the given bounding box is inside or intersects this sphere. An empty bounding box never intersects.
+    ///
+    pub fn intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        let closest = vec3(
+            self.center.x.clamp(min.x, max.x),
+            self.center.y.clamp(min.y, max.y),
+            self.center.z.clamp(min.z, max.z),
+        );
+        self.contains_point(closest)
+    }
+
+    ///
+    /// Returns whether the given oriented bounding box is inside or intersects this sphere.
+    ///
+    pub fn intersects_obb(&self, obb: &Obb) -> bool {
+        self.contains_point(obb.closest_point(self.center))
+    }
+
+    ///
+    /// Expands the sphere so that it contains the other sphere.
+    ///
+    pub fn expand_with_sphere(&mut self, other: &BoundingSphere) {
+        if self.contains_sphere(other) {
+            return;
+        }
+        if other.contains_sphere(self) {
+            *self = *other;
+            return;
+        }
+        let distance = self.center.distance(other.center);
+        let radius = 0.5 * (distance + self.radius + other.radius);
+        self.center += (other.center - self.center) * ((radius - self.radius) / distance);
+        self.radius = radius;
+    }
+
+    ///
+    /// Transforms the sphere by the given transformation.
+    /// The radius is scaled by the largest scale of the transformation, so the sphere contains the transformed sphere if the transformation contains a non-uniform scale.
+    ///
+    pub fn transform(&mut self, transformation: &Mat4) {
+        self.center = (transformation * self.center.extend(1.0)).truncate();
+        self.radius *= transformation
+            .x
+            .truncate()
+            .magnitude()
+            .max(transformation.y.truncate().magnitude())
+            .max(transformation.z.truncate().magnitude());
+    }
+}
+
+impl From<AxisAlignedBoundingBox> for BoundingSphere {
+    fn from(aabb: AxisAlignedBoundingBox) -> Self {
+        Self::from_aabb(&aabb)
+    }
+}
@@ -0,0 +1,181 @@
+use crate::core::*;
+
+///
+/// The volume which is visible through a camera, defined by six planes, see [Camera::frustum](crate::Camera::frustum).
+/// Used for culling objects which are not visible, which is done automatically when rendering, for example using [RenderTarget::render](crate::RenderTarget::render).
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    planes: [Plane; 6],
+    corners: [Vec3; 8],
+}
+
+impl Frustum {
+    ///
+    /// Creates the frustum of the given view projection matrix, ie. the projection matrix multiplied by the view matrix.
+    ///
+    pub fn new(view_projection: Mat4) -> Self {
+        let m = view_projection;
+        let planes = [
+            m.row(3) + m.row(0),
+            m.row(3) - m.row(0),
+            m.row(3) + m.row(1),
+            m.row(3) - m.row(1),
+            m.row(3) + m.row(2),
+            m.row(3) - m.row(2),
+        ]
+        .map(Plane::from);
+        let inverse = m.invert().unwrap_or(Mat4::identity());
+        let corners = [
+            vec3(-1.0, -1.0, -1.0),
+            vec3(1.0, -1.0, -1.0),
+            vec3(-1.0, 1.0, -1.0),
+            vec3(1.0, 1.0, -1.0),
+            vec3(-1.0, -1.0, 1.0),
+            vec3(1.0, -1.0, 1.0),
+            vec3(-1.0, 1.0, 1.0),
+            vec3(1.0, 1.0, 1.0),
+        ]
+        .map(|c| {
+            let p = inverse * c.extend(1.0);
+            p.truncate() / p.w
+        });
+        Self { planes, corners }
+    }
+
+    ///
+    /// Returns the left, right, bottom, top, near and far planes in world space, where the inside of the frustum is in front of all the planes.
+    ///
+    pub fn planes(&self) -> &[Plane; 6] {
+        &self.planes
+    }
+
+    ///
+    /// Returns the eight corners of the frustum in world space.
+    ///
+    pub fn corners(&self) -> &[Vec3; 8] {
+        &self.corners
+    }
+
+    ///
+    /// Returns whether the given position is inside the frustum.
+    ///
+    pub fn contains_point(&self, position: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.signed_distance(position) >= 0.0)
+    }
+
+    ///
+    /// Returns whether the given sphere is inside or intersects the frustum.
+    /// The test is conservative, so it can return true for a sphere close to a corner of the frustum, even though the sphere is outside.
+    ///
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    ///
+    /// Returns whether the given sphere is completely inside the frustum.
+    ///
+    pub fn contains_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.signed_distance(sphere.center) >= sphere.radius)
+    }
+
+    ///
+    /// Returns whether the given bounding box is inside or intersects the frustum.
+    /// An empty bounding box never intersects and an infinite bounding box always intersects.
+    /// The test is conservative, so it can return true for a box close to a corner of the frustum, even though the box is outside.
+    ///
+    pub fn intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        if aabb.is_infinite() {
+            return true;
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        // The box is outside if all its corners are outside one of the planes of the frustum
+        for plane in self.planes.iter() {
+            let farthest = vec3(
+                if plane.normal.x >= 0.0 { max.x } else { min.x },
+                if plane.normal.y >= 0.0 { max.y } else { min.y },
+                if plane.normal.z >= 0.0 { max.z } else { min.z },
+            );
+            if plane.signed_distance(farthest) < 0.0 {
+                return false;
+            }
+        }
+        // or if all the corners of the frustum are outside one of the planes of the box
+        for i in 0..3 {
+            if self.corners.iter().all(|c| c[i] > max[i])
+                || self.corners.iter().all(|c| c[i] < min[i])
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    ///
+    /// Returns whether the given bounding box is completely inside the frustum.
+    /// An empty bounding box is never inside and an infinite bounding box is never inside.
+    ///
+    pub fn contains_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        !aabb.is_empty()
+            && !aabb.is_infinite()
+            && self.planes.iter().all(|p| {
+                p.signed_distance(aabb.center()) >= super::plane::aabb_radius(aabb, p.normal)
+            })
+    }
+
+    ///
+    /// Returns whether the given oriented bounding box is inside or intersects the frustum.
+    /// This is a tighter test than [Frustum::intersects_aabb] for rotated objects, since the bounding box of a rotated object is larger than the object.
+    /// The test is conservative, so it can return true for a box close to a corner of the frustum, even though the box is outside.
+    ///
+    pub fn intersects_obb(&self, obb: &Obb) -> bool {
+        // The box is outside if it is completely behind one of the planes of the frustum
+        if self
+            .planes
+            .iter()
+            .any(|p| p.signed_distance(obb.center) < -obb.projected_radius(p.normal))
+        {
+            return false;
+        }
+        // or if all the corners of the frustum are outside one of the faces of the box
+        for i in 0..3 {
+            let axis = obb.axes[i];
+            let extent = obb.half_extents[i];
+            let distances = self.corners.map(|c| (c - obb.center).dot(axis));
+            if distances.iter().all(|d| *d > extent) || distances.iter().all(|d| *d < -extent) {
+                return false;
+            }
+        }
+        true
+    }
+
+    ///
+    /// Returns whether the given oriented bounding box is completely inside the frustum.
+    ///
+    pub fn contains_obb(&self, obb: &Obb) -> bool {
+        self.planes
+            .iter()
+            .all(|p| p.signed_distance(obb.center) >= obb.projected_radius(p.normal))
+    }
+
+    ///
+    /// Transforms the frustum by the given transformation, for example to get the frustum in the local space of an object by using the inverse of its transformation.
+    ///
+    pub fn transform(&mut self, transformation: &Mat4) {
+        for plane in self.planes.iter_mut() {
+            plane.transform(transformation);
+        }
+        for corner in self.corners.iter_mut() {
+            *corner = (transformation * corner.extend(1.0)).truncate();
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::core::*;
+
+///
+/// An oriented bounding box, ie. a box which can be rotated, given by a center, three orthonormal axes and the half size along each axis.
+/// It bounds a rotated object much tighter than an [AxisAlignedBoundingBox], especially if the object is long and thin.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    /// The center of the box.
+    pub center: Vec3,
+    /// The axes of the box as the columns of the matrix, which must be orthonormal.
+    pub axes: Mat3,
+    /// Half the size of the box along each of the axes.
+    pub half_extents: Vec3,
+}
+
+impl Obb {
+    ///
+    /// Creates a new box with the given center, axes given as the orthonormal columns of a matrix, and half the size along each axis.
+    ///
+    pub fn new(center: Vec3, axes: Mat3, half_extents: Vec3) -> Self {
+        Self {
+            center,
+            axes,
+            half_extents,
+        }
+    }
+
+    ///
+    /// Creates a box with the same extent as the given bounding box, which must not be empty or infinite.
+    ///
+    pub fn from_aabb(aabb: &AxisAlignedBoundingBox) -> Self {
+        Self {
+            center: aabb.center(),
+            axes: Mat3::identity(),
+            half_extents: 0.5 * aabb.size(),
+        }
+    }
+
+    ///
+    /// Creates the box which bounds the given bounding box transformed by the given transformation, for example the bounding box of a geometry in local space and its transformation.
+    /// See [Obb::transform] for the supported transformations.
+    ///
+    pub fn from_transformed_aabb(aabb: &AxisAlignedBoundingBox, transformation: &Mat4) -> Self {
+        let mut obb = Self::from_aabb(aabb);
+        obb.transform(transformation);
+        obb
+    }
+
+    ///
+    /// Returns the eight corners of the box.
+    ///
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (x, y, z) = (
+            self.axes.x * self.half_extents.x,
+            self.axes.y * self.half_extents.y,
+            self.axes.z * self.half_extents.z,
+        );
+        let c = self.center;
+        [
+            c - x - y - z,
+            c + x - y - z,
+            c - x + y - z,
+            c + x + y - z,
+            c - x - y + z,
+            c + x - y + z,
+            c - x + y + z,
+            c + x + y + z,
+        ]
+    }
+
+    ///
+    /// Returns the axis aligned bounding box which contains this box.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::new_with_positions(&self.corners())
+    }
+
+    ///
+    /// Returns the smallest sphere which contains this box.
+    ///
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center, self.half_extents.magnitude())
+    }
+
+    ///
+    /// Returns the position inside or on the surface of the box which is closest to the given position.
+    ///
+    pub fn closest_point(&self, position: Vec3) -> Vec3 {
+        let d = position - self.center;
+        let mut closest = self.center;
+        for i in 0..3 {
+            let axis = self.axes[i];
+            let extent = self.half_extents[i];
+            closest += axis * d.dot(axis).clamp(-extent, extent);
+        }
+        closest
+    }
+
+    ///
+    /// Returns whether the given position is inside the box.
+    ///
+    pub fn contains_point(&self, position: Vec3) -> bool {
+        let d = position - self.center;
+        (0..3).all(|i| d.dot(self.axes[i]).abs() <= self.half_extents[i])
+    }
+
+    ///
+    /// Returns whether the given sphere is inside or intersects this box.
+    ///
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        sphere.intersects_obb(self)
+    }
+
+    ///
+    /// Returns whether the given bounding box is inside or intersects this box. An empty bounding box never intersects.
+    ///
+    pub fn intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        !aabb.is_empty() && (aabb.is_infinite() || self.intersects_obb(&Obb::from_aabb(aabb)))
+    }
+
+    ///
+    /// Returns whether the other box is inside or intersects this box, using the separating axis theorem.
+    ///
+    pub fn intersects_obb(&self, other: &Obb) -> bool {
+        let d = other.center - self.center;
+        let separated = |axis: Vec3| {
+            // Skip the axes which are degenerate because two edges are parallel
+            axis.magnitude2() > 1e-12
+                && d.dot(axis).abs() > self.projected_radius(axis) + other.projected_radius(axis)
+        };
+        for i in 0..3 {
+            if separated(self.axes[i]) || separated(other.axes[i]) {
+                return false;
+            }
+        }
+        for i in 0..3 {
+            for j in 0..3 {
+                if separated(self.axes[i].cross(other.axes[j])) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    ///
+    /// Transforms the box by the given transformation.
+    /// The result is exact for transformations consisting of translation, rotation and a scale along the axes of the box,
+    /// which includes all transformations of a box created using [Obb::from_aabb] that do not contain shear.
+    ///
+    pub fn transform(&mut self, transformation: &Mat4) {
+        self.center = (transformation * self.center.extend(1.0)).truncate();
+        let linear = Mat3::from_cols(
+            transformation.x.truncate(),
+            transformation.y.truncate(),
+            transformation.z.truncate(),
+        );
+        for i in 0..3 {
+            let axis = linear * self.axes[i];
+            let length = axis.magnitude();
+            if length > 0.0 {
+                self.axes[i] = axis / length;
+            }
+            self.half_extents[i] *= length;
+        }
+    }
+
+    ///
+    /// Returns half the extent of the box along the given direction, scaled by the length of the direction.
+    ///
+    pub(crate) fn projected_radius(&self, direction: Vec3) -> f32 {
+        (0..3)
+            .map(|i| self.half_extents[i] * self.axes[i].dot(direction).abs())
+            .sum()
+    }
+}
+
+impl From<AxisAlignedBoundingBox> for Obb {
+    fn from(aabb: AxisAlignedBoundingBox) -> Self {
+        Self::from_aabb(&aabb)
+    }
+}
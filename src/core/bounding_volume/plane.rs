@@ -0,0 +1,129 @@
+use crate::core::*;
+
+///
+/// A plane given by the plane equation `normal.dot(p) + distance = 0`, where the normal has unit length.
+/// A position is in front of the plane if it is on the side the normal points towards.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// The normal of the plane, which has unit length.
+    pub normal: Vec3,
+    /// The signed distance from the plane to origo along the normal, ie. `-normal.dot(p)` for any position `p` on the plane.
+    pub distance: f32,
+}
+
+impl Plane {
+    ///
+    /// Creates a new plane from the plane equation `normal.dot(p) + distance = 0`, which is normalized so that the normal has unit length.
+    ///
+    pub fn new(normal: Vec3, distance: f32) -> Self {
+        let length = normal.magnitude();
+        Self {
+            normal: normal / length,
+            distance: distance / length,
+        }
+    }
+
+    ///
+    /// Creates a new plane which contains the given position and has the given normal.
+    ///
+    pub fn from_point_and_normal(position: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize();
+        Self {
+            normal,
+            distance: -normal.dot(position),
+        }
+    }
+
+    ///
+    /// Creates a new plane which contains the three given positions, with the front side where the positions are in counter clockwise order.
+    /// Returns `None` if the positions are on a line.
+    ///
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Option<Self> {
+        let normal = (b - a).cross(c - a);
+        (normal.magnitude2() > 0.0).then(|| Self::from_point_and_normal(a, normal))
+    }
+
+    ///
+    /// Returns the signed distance from the plane to the given position, which is positive in front of the plane and negative behind the plane.
+    ///
+    pub fn signed_distance(&self, position: Vec3) -> f32 {
+        self.normal.dot(position) + self.distance
+    }
+
+    ///
+    /// Returns the position on the plane closest to the given position.
+    ///
+    pub fn project_point(&self, position: Vec3) -> Vec3 {
+        position - self.normal * self.signed_distance(position)
+    }
+
+    ///
+    /// Returns the distance along the ray from the given origin in the given direction to the plane,
+    /// or `None` if the ray is parallel to the plane or points away from it.
+    /// The distance is given in units of the length of the direction.
+    ///
+    pub fn intersect_ray(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        let denominator = self.normal.dot(direction);
+        if denominator == 0.0 {
+            return None;
+        }
+        let t = -self.signed_distance(origin) / denominator;
+        (t >= 0.0).then_some(t)
+    }
+
+    ///
+    /// Returns whether the plane intersects the given sphere.
+    ///
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.signed_distance(sphere.center).abs() <= sphere.radius
+    }
+
+    ///
+    /// Returns whether the plane intersects the given bounding box. An empty bounding box never intersects.
+    ///
+    pub fn intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        !aabb.is_empty()
+            && (aabb.is_infinite()
+                || self.signed_distance(aabb.center()).abs() <= aabb_radius(aabb, self.normal))
+    }
+
+    ///
+    /// Returns whether the plane intersects the given oriented bounding box.
+    ///
+    pub fn intersects_obb(&self, obb: &Obb) -> bool {
+        self.signed_distance(obb.center).abs() <= obb.projected_radius(self.normal)
+    }
+
+    ///
+    /// Transforms the plane by the given transformation.
+    /// Nothing happens if the transformation is not invertible.
+    ///
+    pub fn transform(&mut self, transformation: &Mat4) {
+        if let Some(inverse) = transformation.invert() {
+            *self = (inverse.transpose() * Vec4::from(*self)).into();
+        }
+    }
+}
+
+impl From<Vec4> for Plane {
+    fn from(plane: Vec4) -> Self {
+        Self::new(plane.truncate(), plane.w)
+    }
+}
+
+impl From<Plane> for Vec4 {
+    fn from(plane: Plane) -> Self {
+        plane.normal.extend(plane.distance)
+    }
+}
+
+///
+/// Returns half the extent of the bounding box along the given direction.
+///
+pub(super) fn aabb_radius(aabb: &AxisAlignedBoundingBox, direction: Vec3) -> f32 {
+    let half_size = 0.5 * aabb.size();
+    half_size.x * direction.x.abs()
+        + half_size.y * direction.y.abs()
+        + half_size.z * direction.z.abs()
+}
@@ -40,6 +40,7 @@ impl<T: BufferDataType + PrimitiveDataType> BufferDataType for [T; 4] {}
 
 impl BufferDataType for Quat {}
 
+#[allow(clippy::needless_maybe_sized)]
 impl<T: BufferDataType + ?Sized> BufferDataType for &T {}
 
 struct Buffer {
@@ -49,10 +50,12 @@ struct Buffer {
     data_type: u32,
     data_size: u32,
     normalized: bool,
+    byte_size: u64,
 }
 
 impl Buffer {
     pub fn new(context: &Context) -> Self {
+        context.record_buffer_allocation();
         Self {
             context: context.clone(),
             id: unsafe { context.create_buffer().expect("Failed creating buffer") },
@@ -60,6 +63,7 @@ impl Buffer {
             data_type: 0,
             data_size: 0,
             normalized: false,
+            byte_size: 0,
         }
     }
 
@@ -89,12 +93,22 @@ impl Buffer {
         self.data_type = T::data_type();
         self.data_size = T::size();
         self.normalized = T::normalized();
+        let byte_size = std::mem::size_of_val(data) as u64;
+        self.context.record_buffer_resize(self.byte_size, byte_size);
+        self.byte_size = byte_size;
     }
 
     pub fn attribute_count(&self) -> u32 {
         self.attribute_count
     }
 
+    pub fn set_label(&self, label: &str) {
+        // The buffer must have been bound once before it can be labeled
+        self.bind();
+        self.context
+            .set_object_label(crate::context::BUFFER, self.id, label);
+    }
+
     pub fn bind(&self) {
         unsafe {
             self.context
@@ -108,5 +122,6 @@ impl Drop for Buffer {
         unsafe {
             self.context.delete_buffer(self.id);
         }
+        self.context.record_buffer_deallocation(self.byte_size);
     }
 }
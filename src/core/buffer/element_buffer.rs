@@ -33,6 +33,7 @@ pub struct ElementBuffer {
     id: crate::context::Buffer,
     count: usize,
     data_type: u32,
+    byte_size: u64,
 }
 
 impl ElementBuffer {
@@ -41,11 +42,13 @@ impl ElementBuffer {
     ///
     pub fn new(context: &Context) -> Self {
         let id = unsafe { context.create_buffer().expect("Failed creating buffer") };
+        context.record_buffer_allocation();
         Self {
             context: context.clone(),
             id,
             count: 0,
             data_type: 0,
+            byte_size: 0,
         }
     }
 
@@ -76,6 +79,9 @@ impl ElementBuffer {
         }
         self.count = data.len();
         self.data_type = T::data_type();
+        let byte_size = std::mem::size_of_val(data) as u64;
+        self.context.record_buffer_resize(self.byte_size, byte_size);
+        self.byte_size = byte_size;
     }
 
     ///
@@ -92,6 +98,16 @@ impl ElementBuffer {
         self.count / 3
     }
 
+    ///
+    /// Sets the label of this buffer, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        // The buffer must have been bound once before it can be labeled
+        self.bind();
+        self.context
+            .set_object_label(crate::context::BUFFER, self.id, label);
+    }
+
     pub(crate) fn bind(&self) {
         unsafe {
             self.context
@@ -109,5 +125,6 @@ impl Drop for ElementBuffer {
         unsafe {
             self.context.delete_buffer(self.id);
         }
+        self.context.record_buffer_deallocation(self.byte_size);
     }
 }
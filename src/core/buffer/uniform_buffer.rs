@@ -27,6 +27,8 @@ impl UniformBuffer {
             offsets.push(length);
             length += *size as usize;
         }
+        context.record_buffer_allocation();
+        context.record_buffer_resize(0, (length * std::mem::size_of::<f32>()) as u64);
         let buffer = UniformBuffer {
             context: context.clone(),
             id,
@@ -37,6 +39,14 @@ impl UniformBuffer {
         buffer
     }
 
+    ///
+    /// Sets the label of this buffer, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::BUFFER, self.id, label);
+    }
+
     pub(crate) fn bind(&self, id: u32) {
         unsafe {
             self.context
@@ -116,5 +126,7 @@ impl Drop for UniformBuffer {
         unsafe {
             self.context.delete_buffer(self.id);
         }
+        self.context
+            .record_buffer_deallocation((self.data.len() * std::mem::size_of::<f32>()) as u64);
     }
 }
@@ -51,6 +51,13 @@ impl VertexBuffer {
         self.buffer.attribute_count()
     }
 
+    ///
+    /// Sets the label of this buffer, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.buffer.set_label(label);
+    }
+
     pub(in crate::core) fn bind(&self) {
         self.buffer.bind();
     }
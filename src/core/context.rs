@@ -6,6 +6,9 @@ use std::sync::RwLock;
 #[doc(hidden)]
 pub use crate::context::HasContext;
 
+// The vertex shader source, fragment shader source and defines a program is created from
+type ProgramSource = (String, String, Vec<(String, String)>);
+
 ///
 /// Contains the low-level OpenGL/WebGL graphics context as well as other "global" variables.
 /// Implements Deref with the low-level graphics context as target, so you can call low-level functionality
@@ -14,9 +17,19 @@ pub use crate::context::HasContext;
 #[derive(Clone)]
 pub struct Context {
     context: Arc<crate::context::Context>,
-    pub(super) vao: crate::context::VertexArray,
+    vao: Arc<RwLock<crate::context::VertexArray>>,
     /// A cache of programs to avoid recompiling a [Program] every frame.
-    pub programs: Arc<RwLock<HashMap<Vec<u8>, Program>>>,
+    /// The programs are reference counted, since the same program is shared by all keys with identical shader sources, see [Context::program].
+    pub programs: Arc<RwLock<HashMap<Vec<u8>, Arc<Program>>>>,
+    program_sources: Arc<RwLock<HashMap<ProgramSource, Arc<Program>>>>,
+    /// A registry of named shader source snippets that can be included in the shader source given to [Program::from_source] using `#include "name"`.
+    /// Use [Context::add_shader_include] to add a snippet.
+    pub shader_includes: Arc<RwLock<HashMap<String, String>>>,
+    target_scissor_box: Arc<RwLock<Option<ScissorBox>>>,
+    pub(super) render_stats: Arc<RwLock<super::render_stats::RenderStatsState>>,
+    /// The texture the deferred lighting is accumulated in when there are more lights than can be applied in one lighting pass.
+    /// It is kept between frames and only allocated again when the size of the viewport changes.
+    pub(crate) deferred_lighting_texture: Arc<RwLock<Option<Texture2D>>>,
 }
 
 impl Context {
@@ -26,15 +39,9 @@ impl Context {
     /// Since the content in the [context](crate::context) module is just a re-export of [glow](https://crates.io/crates/glow),
     /// you can also call this method with a reference counter to a glow context created using glow and not the re-export in [context](crate::context).
     ///
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn from_gl_context(context: Arc<crate::context::Context>) -> Result<Self, CoreError> {
-        unsafe {
-            if !context.version().is_embedded {
-                // Enable seamless cube map textures - not available on OpenGL ES and WebGL
-                context.enable(crate::context::TEXTURE_CUBE_MAP_SEAMLESS);
-            }
-            context.pixel_store_i32(crate::context::UNPACK_ALIGNMENT, 1);
-            context.pixel_store_i32(crate::context::PACK_ALIGNMENT, 1);
-        };
+        Self::set_default_state(&context);
         let c = unsafe {
             // Create one Vertex Array Object which is then reused all the time.
             let vao = context
@@ -42,13 +49,183 @@ impl Context {
                 .map_err(CoreError::ContextCreation)?;
             Self {
                 context,
-                vao,
+                vao: Arc::new(RwLock::new(vao)),
                 programs: Arc::new(RwLock::new(HashMap::new())),
+                program_sources: Arc::new(RwLock::new(HashMap::new())),
+                shader_includes: Arc::new(RwLock::new(HashMap::new())),
+                target_scissor_box: Arc::new(RwLock::new(None)),
+                render_stats: Arc::new(RwLock::new(Default::default())),
+                deferred_lighting_texture: Arc::new(RwLock::new(None)),
             }
         };
+        c.add_shader_include("shared.frag", include_str!("shared.frag"));
         Ok(c)
     }
 
+    fn set_default_state(context: &crate::context::Context) {
+        unsafe {
+            if !context.version().is_embedded {
+                // Enable seamless cube map textures - not available on OpenGL ES and WebGL
+                context.enable(crate::context::TEXTURE_CUBE_MAP_SEAMLESS);
+            }
+            context.pixel_store_i32(crate::context::UNPACK_ALIGNMENT, 1);
+            context.pixel_store_i32(crate::context::PACK_ALIGNMENT, 1);
+        }
+    }
+
+    ///
+    /// Prepares this context for rendering again after the graphics context has been lost and restored,
+    /// which for example happens on web when the browser or operating system resets the GPU.
+    /// The cached programs are removed and the global state, like the vertex array object, is recreated.
+    ///
+    /// **Note:** All GPU resources, like textures, buffers and programs, created before the context was lost are invalid afterwards
+    /// and must be recreated, for example from the CPU-side data they were originally created from.
+    /// This is not done automatically, since the GPU resources, including the ones in the built-in objects like [Mesh](crate::renderer::Mesh) and [Texture2D],
+    /// do not keep a copy of the CPU-side data. Instead, keep the CPU-side data, for example the loaded [CpuModel](crate::CpuModel) and [CpuTexture](crate::CpuTexture),
+    /// and construct the objects again from that data.
+    /// When using the default [Window](crate::window::Window), this is called automatically and [FrameInput::context_restored](crate::window::FrameInput::context_restored) is true in the first frame after the context is restored.
+    ///
+    pub fn restore_after_context_loss(&self) -> Result<(), CoreError> {
+        Self::set_default_state(&self.context);
+        let vao =
+            unsafe { self.context.create_vertex_array() }.map_err(CoreError::ContextCreation)?;
+        *self.vao.write().unwrap() = vao;
+        self.programs.write().unwrap().clear();
+        self.program_sources.write().unwrap().clear();
+        *self.target_scissor_box.write().unwrap() = None;
+        *self.deferred_lighting_texture.write().unwrap() = None;
+        Ok(())
+    }
+
+    ///
+    /// Returns the vertex array object used for all draw calls.
+    ///
+    pub(crate) fn vertex_array(&self) -> crate::context::VertexArray {
+        *self.vao.read().unwrap()
+    }
+
+    ///
+    /// Sets the vertex array object used for all draw calls.
+    /// Vertex array objects are not shared between graphics contexts, so this is needed when switching between graphics contexts which share resources, see [WindowedContext::from_winit_window_with_sharing](crate::WindowedContext::from_winit_window_with_sharing).
+    ///
+    #[cfg(all(feature = "window", not(target_arch = "wasm32")))]
+    pub(crate) fn set_vertex_array(&self, vao: crate::context::VertexArray) {
+        *self.vao.write().unwrap() = vao;
+    }
+
+    ///
+    /// Returns a shader program created from the given vertex and fragment shader source and defines (see [Program::from_source_with_defines]).
+    /// If a program has previously been created from the exact same source and defines using this method, that program is returned instead of compiling a new one.
+    /// The source is compared after the `#include` lines are replaced by the snippets in [Context::shader_includes], so changing a snippet results in a new program.
+    ///
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn program(
+        &self,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Arc<Program>, CoreError> {
+        let key = (
+            super::program::resolve_includes(self, vertex_shader_source)?,
+            super::program::resolve_includes(self, fragment_shader_source)?,
+            defines
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>(),
+        );
+        if let Some(program) = self.program_sources.read().unwrap().get(&key) {
+            return Ok(program.clone());
+        }
+        let program = Arc::new(Program::from_source_with_defines(
+            self, &key.0, &key.1, defines,
+        )?);
+        self.program_sources
+            .write()
+            .unwrap()
+            .insert(key, program.clone());
+        Ok(program)
+    }
+
+    ///
+    /// Removes the programs created using [Context::program] which are no longer used anywhere else, for example after they are removed from [Context::programs].
+    ///
+    pub(crate) fn remove_unused_programs(&self) {
+        self.program_sources
+            .write()
+            .unwrap()
+            .retain(|_, program| Arc::strong_count(program) > 1);
+    }
+
+    ///
+    /// Adds a named shader source snippet which can then be included in the shader source given to [Program::from_source] using `#include "name"`.
+    /// If a snippet with the same name already exists, it is replaced
+    /// and the cached programs (see [Context::programs] and [Context::program]) are removed, so the programs that include it are compiled again.
+    ///
+    /// The snippet `shared.frag`, which contains the helper functions used by the built-in materials, is added by default.
+    /// The lighting helper functions in `light_shared.frag` can be added using [add_light_shader_include](crate::renderer::add_light_shader_include).
+    /// To use the lighting calculations of the built-in materials, add the result of [lights_shader_source](crate::renderer::lights_shader_source) as a snippet.
+    ///
+    pub fn add_shader_include(&self, name: impl Into<String>, source: impl Into<String>) {
+        let source = source.into();
+        let previous = self
+            .shader_includes
+            .write()
+            .unwrap()
+            .insert(name.into(), source.clone());
+        if previous.is_some_and(|previous| previous != source) {
+            self.programs.write().unwrap().clear();
+            self.program_sources.write().unwrap().clear();
+        }
+    }
+
+    ///
+    /// Returns whether or not a color texture with the format given by the generic parameter `T` can be rendered into on this context,
+    /// ie. whether it can be used in a [ColorTarget].
+    /// For example, on web and OpenGL ES, rendering into a float format requires the `EXT_color_buffer_float` extension.
+    ///
+    /// **Note:** This creates a small texture and framebuffer to test the format, so avoid calling this every frame.
+    ///
+    pub fn supports_format<T: TextureDataType>(&self) -> bool {
+        self.supports_attachment(T::internal_format(), crate::context::COLOR_ATTACHMENT0)
+    }
+
+    ///
+    /// Returns whether or not a depth texture with the format given by the generic parameter `T` can be rendered into on this context,
+    /// ie. whether it can be used in a [DepthTarget].
+    ///
+    /// **Note:** This creates a small texture and framebuffer to test the format, so avoid calling this every frame.
+    ///
+    pub fn supports_depth_format<T: DepthTextureDataType>(&self) -> bool {
+        self.supports_attachment(T::internal_format(), T::attachment())
+    }
+
+    fn supports_attachment(&self, internal_format: u32, attachment: u32) -> bool {
+        unsafe {
+            let (Ok(texture), Ok(framebuffer)) = (self.create_texture(), self.create_framebuffer())
+            else {
+                return false;
+            };
+            self.bind_texture(crate::context::TEXTURE_2D, Some(texture));
+            self.tex_storage_2d(crate::context::TEXTURE_2D, 1, internal_format, 1, 1);
+            let supported = self.get_error() == crate::context::NO_ERROR && {
+                self.bind_framebuffer(crate::context::FRAMEBUFFER, Some(framebuffer));
+                self.framebuffer_texture_2d(
+                    crate::context::FRAMEBUFFER,
+                    attachment,
+                    crate::context::TEXTURE_2D,
+                    Some(texture),
+                    0,
+                );
+                self.check_framebuffer_status(crate::context::FRAMEBUFFER)
+                    == crate::context::FRAMEBUFFER_COMPLETE
+            };
+            self.bind_framebuffer(crate::context::FRAMEBUFFER, None);
+            self.delete_framebuffer(framebuffer);
+            self.delete_texture(texture);
+            supported
+        }
+    }
+
     ///
     /// Set the scissor test for this context (see [ScissorBox]).
     ///
@@ -68,6 +245,51 @@ impl Context {
         }
     }
 
+    ///
+    /// Sets the scissor box of the render target that is written to while calling `f`.
+    /// The scissor box in the [RenderStates] of the render calls in `f` is intersected with this scissor box.
+    /// Afterwards, the scissor box of the enclosing write is restored, or the scissor test is disabled if there is none,
+    /// so a scissor box set using [Context::set_scissor] outside of a write is not overridden.
+    ///
+    pub(crate) fn with_target_scissor<T>(
+        &self,
+        scissor_box: ScissorBox,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        let previous = self
+            .target_scissor_box
+            .write()
+            .unwrap()
+            .replace(scissor_box);
+        self.set_scissor(scissor_box);
+        let result = f();
+        *self.target_scissor_box.write().unwrap() = previous;
+        match previous {
+            Some(scissor_box) => self.set_scissor(scissor_box),
+            None => unsafe { self.disable(crate::context::SCISSOR_TEST) },
+        }
+        result
+    }
+
+    ///
+    /// Enables the first `count` clip distances written to `gl_ClipDistance` in the vertex shader and disables the rest.
+    /// Only enable clip distances that are written by the vertex shader of the following draw calls.
+    ///
+    /// **Note:** Clip distances are not available on web and on OpenGL ES without the `GL_EXT_clip_cull_distance` extension.
+    ///
+    pub fn set_clip_distances(&self, count: u32) {
+        unsafe {
+            // At least 8 clip distances are guaranteed to be available
+            for i in 0..8 {
+                if i < count {
+                    self.enable(crate::context::CLIP_DISTANCE0 + i);
+                } else {
+                    self.disable(crate::context::CLIP_DISTANCE0 + i);
+                }
+            }
+        }
+    }
+
     ///
     /// Set the viewport for this context (See [Viewport]).
     ///
@@ -188,6 +410,71 @@ impl Context {
         }
     }
 
+    ///
+    /// Set the stencil test for this context (see [Stencil]).
+    ///
+    pub fn set_stencil(&self, stencil: Stencil) {
+        unsafe {
+            if let Stencil::Enabled {
+                test,
+                reference,
+                read_mask,
+                write_mask,
+                stencil_fail,
+                depth_fail,
+                pass,
+            } = stencil
+            {
+                self.enable(crate::context::STENCIL_TEST);
+                let function = match test {
+                    StencilTest::Never => crate::context::NEVER,
+                    StencilTest::Less => crate::context::LESS,
+                    StencilTest::Equal => crate::context::EQUAL,
+                    StencilTest::LessOrEqual => crate::context::LEQUAL,
+                    StencilTest::Greater => crate::context::GREATER,
+                    StencilTest::NotEqual => crate::context::NOTEQUAL,
+                    StencilTest::GreaterOrEqual => crate::context::GEQUAL,
+                    StencilTest::Always => crate::context::ALWAYS,
+                };
+                self.stencil_func(function, reference as i32, read_mask as u32);
+                self.stencil_mask(write_mask as u32);
+                self.stencil_op(
+                    Self::stencil_const_from_operation(stencil_fail),
+                    Self::stencil_const_from_operation(depth_fail),
+                    Self::stencil_const_from_operation(pass),
+                );
+            } else {
+                self.disable(crate::context::STENCIL_TEST);
+            }
+        }
+    }
+
+    ///
+    /// Enables or disables alpha to coverage for this context (see [RenderStates::alpha_to_coverage]).
+    ///
+    pub fn set_alpha_to_coverage(&self, alpha_to_coverage: bool) {
+        unsafe {
+            if alpha_to_coverage {
+                self.enable(crate::context::SAMPLE_ALPHA_TO_COVERAGE);
+            } else {
+                self.disable(crate::context::SAMPLE_ALPHA_TO_COVERAGE);
+            }
+        }
+    }
+
+    fn stencil_const_from_operation(operation: StencilOperation) -> u32 {
+        match operation {
+            StencilOperation::Keep => crate::context::KEEP,
+            StencilOperation::Zero => crate::context::ZERO,
+            StencilOperation::Replace => crate::context::REPLACE,
+            StencilOperation::Increment => crate::context::INCR,
+            StencilOperation::IncrementWrap => crate::context::INCR_WRAP,
+            StencilOperation::Decrement => crate::context::DECR,
+            StencilOperation::DecrementWrap => crate::context::DECR_WRAP,
+            StencilOperation::Invert => crate::context::INVERT,
+        }
+    }
+
     fn blend_const_from_multiplier(multiplier: BlendMultiplierType) -> u32 {
         match multiplier {
             BlendMultiplierType::Zero => crate::context::ZERO,
@@ -225,6 +512,63 @@ impl Context {
             self.set_depth_test(render_states.depth_test);
         }
         self.set_blend(render_states.blend);
+        self.set_stencil(render_states.stencil);
+        self.set_alpha_to_coverage(render_states.alpha_to_coverage);
+        let target_scissor_box = *self.target_scissor_box.read().unwrap();
+        match (render_states.scissor_box, target_scissor_box) {
+            (Some(scissor_box), Some(target_scissor_box)) => {
+                self.set_scissor(scissor_box.intersection(target_scissor_box))
+            }
+            (Some(scissor_box), None) => self.set_scissor(scissor_box),
+            (None, Some(target_scissor_box)) => self.set_scissor(target_scissor_box),
+            (None, None) => {}
+        }
+    }
+
+    ///
+    /// Starts a named group of commands, for example a render pass, which is shown as a group in graphics debuggers like [RenderDoc](https://renderdoc.org/) until [Context::pop_debug_group] is called.
+    /// Groups can be nested. Does nothing if debug groups are not supported, which is always the case on web.
+    /// The render passes of three-d, for example the shadow map and deferred passes, are also wrapped in debug groups.
+    ///
+    pub fn push_debug_group(&self, name: &str) {
+        if self.supports_debug() {
+            unsafe {
+                self.context
+                    .push_debug_group(crate::context::DEBUG_SOURCE_APPLICATION, 0, name);
+            }
+        }
+    }
+
+    ///
+    /// Ends the debug group started by the latest call to [Context::push_debug_group].
+    ///
+    pub fn pop_debug_group(&self) {
+        if self.supports_debug() {
+            unsafe {
+                self.context.pop_debug_group();
+            }
+        }
+    }
+
+    ///
+    /// Calls the callback inside a named debug group, see [Context::push_debug_group], and returns the result of the callback.
+    ///
+    pub fn debug_group<T>(&self, name: &str, callback: impl FnOnce() -> T) -> T {
+        self.push_debug_group(name);
+        let result = callback();
+        self.pop_debug_group();
+        result
+    }
+
+    ///
+    /// Sets the label of the given OpenGL object shown in graphics debuggers, if debug labels are supported.
+    ///
+    pub(crate) fn set_object_label(&self, identifier: u32, object: impl DebugObject, label: &str) {
+        if let Some(name) = object.debug_name().filter(|_| self.supports_debug()) {
+            unsafe {
+                self.context.object_label(identifier, name, Some(label));
+            }
+        }
     }
 
     ///
@@ -309,3 +653,34 @@ impl std::ops::Deref for Context {
         &self.context
     }
 }
+
+///
+/// An OpenGL object which can be given a label using [Context::set_object_label].
+///
+pub(crate) trait DebugObject {
+    /// Returns the OpenGL name of the object, or `None` if object labels are not supported on this platform.
+    fn debug_name(&self) -> Option<u32>;
+}
+
+macro_rules! impl_debug_object {
+    ($($object:ident),*) => {
+        $(
+            impl DebugObject for crate::context::$object {
+                #[cfg(not(target_arch = "wasm32"))]
+                fn debug_name(&self) -> Option<u32> {
+                    Some(self.0.get())
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                fn debug_name(&self) -> Option<u32> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl_debug_object!(NativeTexture, NativeBuffer, NativeProgram);
+#[cfg(target_arch = "wasm32")]
+impl_debug_object!(WebTextureKey, WebBufferKey, WebProgramKey);
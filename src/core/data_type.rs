@@ -229,6 +229,7 @@ pub trait DataType: std::fmt::Debug + Clone {
     fn send_uniform(context: &Context, location: &UniformLocation, data: &[Self]);
 }
 
+#[allow(clippy::needless_maybe_sized)]
 impl<T: DataType + ?Sized> DataType for &T {
     fn internal_format() -> u32 {
         T::internal_format()
@@ -394,6 +395,42 @@ impl DataType for f32 {
     }
 }
 
+impl DataType for PackedR11G11B10F {
+    fn internal_format() -> u32 {
+        crate::context::R11F_G11F_B10F
+    }
+
+    fn data_type() -> u32 {
+        crate::context::UNSIGNED_INT_10F_11F_11F_REV
+    }
+
+    fn size() -> u32 {
+        3
+    }
+
+    fn send_uniform(_context: &Context, _location: &UniformLocation, _data: &[Self]) {
+        unreachable!("packed data types cannot be used as uniforms")
+    }
+}
+
+impl DataType for PackedRgb9E5 {
+    fn internal_format() -> u32 {
+        crate::context::RGB9_E5
+    }
+
+    fn data_type() -> u32 {
+        crate::context::UNSIGNED_INT_5_9_9_9_REV
+    }
+
+    fn size() -> u32 {
+        3
+    }
+
+    fn send_uniform(_context: &Context, _location: &UniformLocation, _data: &[Self]) {
+        unreachable!("packed data types cannot be used as uniforms")
+    }
+}
+
 impl<T: PrimitiveDataType> DataType for Vector2<T> {
     fn internal_format() -> u32 {
         T::internal_format_with_size(Self::size())
@@ -611,6 +648,9 @@ impl<T: PrimitiveDataType> DataType for Matrix4<T> {
 
 pub trait DepthDataType {
     fn internal_format() -> u32;
+    fn attachment() -> u32 {
+        crate::context::DEPTH_ATTACHMENT
+    }
 }
 
 impl DepthDataType for f16 {
@@ -628,3 +668,19 @@ impl DepthDataType for f32 {
         crate::context::DEPTH_COMPONENT32F
     }
 }
+impl DepthDataType for f32s8 {
+    fn internal_format() -> u32 {
+        crate::context::DEPTH32F_STENCIL8
+    }
+    fn attachment() -> u32 {
+        crate::context::DEPTH_STENCIL_ATTACHMENT
+    }
+}
+impl DepthDataType for f24s8 {
+    fn internal_format() -> u32 {
+        crate::context::DEPTH24_STENCIL8
+    }
+    fn attachment() -> u32 {
+        crate::context::DEPTH_STENCIL_ATTACHMENT
+    }
+}
@@ -0,0 +1,112 @@
+use crate::core::*;
+use std::collections::VecDeque;
+
+///
+/// Measures the time the GPU spends executing the commands issued between [GpuTimer::begin] and [GpuTimer::end], for example the rendering of a frame.
+/// The result is available a few frames later, so the CPU does not wait for the GPU, see [GpuTimer::time].
+///
+/// Only one timer can measure at a time, ie. timers cannot be nested or overlap.
+/// Requires timer queries, which are supported by OpenGL 3.3 and by some OpenGL ES and WebGL2 implementations through the `EXT_disjoint_timer_query` extensions.
+///
+pub struct GpuTimer {
+    context: Context,
+    pending: VecDeque<crate::context::Query>,
+    unused: Vec<crate::context::Query>,
+    measuring: bool,
+    time: Option<f64>,
+}
+
+impl GpuTimer {
+    ///
+    /// Creates a new GPU timer or returns `None` if timer queries are not supported.
+    ///
+    pub fn new(context: &Context) -> Option<Self> {
+        let version = context.version();
+        let extensions = context.supported_extensions();
+        let supported = (!version.is_embedded && (version.major, version.minor) >= (3, 3))
+            || extensions.contains("GL_ARB_timer_query")
+            || extensions.contains("GL_EXT_disjoint_timer_query")
+            || extensions.contains("EXT_disjoint_timer_query_webgl2");
+        supported.then(|| Self {
+            context: context.clone(),
+            pending: VecDeque::new(),
+            unused: Vec::new(),
+            measuring: false,
+            time: None,
+        })
+    }
+
+    ///
+    /// Starts measuring the time of the commands issued until [GpuTimer::end] is called.
+    ///
+    pub fn begin(&mut self) {
+        if self.measuring {
+            return;
+        }
+        // Avoid creating more and more queries if the results are never read
+        if self.pending.len() > 4 {
+            self.poll();
+        }
+        let query = match self.unused.pop() {
+            Some(query) => query,
+            None => unsafe { self.context.create_query().expect("Failed creating query") },
+        };
+        unsafe {
+            self.context
+                .begin_query(crate::context::TIME_ELAPSED, query);
+        }
+        self.pending.push_back(query);
+        self.measuring = true;
+    }
+
+    ///
+    /// Stops measuring, see [GpuTimer::begin].
+    ///
+    pub fn end(&mut self) {
+        if self.measuring {
+            unsafe {
+                self.context.end_query(crate::context::TIME_ELAPSED);
+            }
+            self.measuring = false;
+        }
+    }
+
+    ///
+    /// Returns the time in milliseconds of the latest measurement the GPU has finished, or `None` if no measurement has finished yet.
+    ///
+    pub fn time(&mut self) -> Option<f64> {
+        self.poll();
+        self.time
+    }
+
+    fn poll(&mut self) {
+        let finished = self.pending.len() - if self.measuring { 1 } else { 0 };
+        for _ in 0..finished {
+            let query = self.pending[0];
+            let available = unsafe {
+                self.context
+                    .get_query_parameter_u32(query, crate::context::QUERY_RESULT_AVAILABLE)
+            };
+            if available == 0 {
+                break;
+            }
+            let nanoseconds = unsafe {
+                self.context
+                    .get_query_parameter_u32(query, crate::context::QUERY_RESULT)
+            };
+            self.time = Some(nanoseconds as f64 * 1e-6);
+            self.unused.push(self.pending.pop_front().unwrap());
+        }
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        self.end();
+        unsafe {
+            for query in self.pending.drain(..).chain(self.unused.drain(..)) {
+                self.context.delete_query(query);
+            }
+        }
+    }
+}
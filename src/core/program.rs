@@ -15,17 +15,119 @@ pub struct Program {
     textures: RwLock<HashMap<String, u32>>,
     uniforms: HashMap<String, crate::context::UniformLocation>,
     uniform_blocks: RwLock<HashMap<String, (u32, u32)>>,
+    active_attributes: Vec<ActiveAttributeInfo>,
+    active_uniforms: Vec<ActiveUniformInfo>,
+    active_uniform_blocks: Vec<ActiveUniformBlockInfo>,
+}
+
+///
+/// Information about an active vertex attribute in a [Program], ie. an attribute that is used by the shader code.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveAttributeInfo {
+    /// The name of the attribute in the shader source.
+    pub name: String,
+    /// The location of the attribute.
+    pub location: u32,
+    /// The data type of the attribute, for example [FLOAT_VEC3](crate::context::FLOAT_VEC3).
+    pub data_type: u32,
+    /// The number of elements, which is larger than one if the attribute is an array.
+    pub size: u32,
+}
+
+///
+/// Information about an active uniform variable in a [Program], ie. a uniform that is used by the shader code and not part of a uniform block.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveUniformInfo {
+    /// The name of the uniform in the shader source without any array brackets.
+    /// This is the name to use in for example [Program::use_uniform] or [Program::use_texture].
+    pub name: String,
+    /// The data type of the uniform, for example [FLOAT_MAT4](crate::context::FLOAT_MAT4) or [SAMPLER_2D](crate::context::SAMPLER_2D).
+    pub data_type: u32,
+    /// The number of elements, which is larger than one if the uniform is an array.
+    pub size: u32,
+}
+
+impl ActiveUniformInfo {
+    ///
+    /// Returns true if this uniform is a texture sampler, ie. it should be assigned using one of the texture methods on [Program], for example [Program::use_texture].
+    ///
+    pub fn is_sampler(&self) -> bool {
+        matches!(
+            self.data_type,
+            crate::context::SAMPLER_2D
+                | crate::context::SAMPLER_3D
+                | crate::context::SAMPLER_CUBE
+                | crate::context::SAMPLER_2D_SHADOW
+                | crate::context::SAMPLER_2D_ARRAY
+                | crate::context::SAMPLER_2D_ARRAY_SHADOW
+                | crate::context::SAMPLER_CUBE_SHADOW
+                | crate::context::SAMPLER_2D_MULTISAMPLE
+                | crate::context::INT_SAMPLER_2D
+                | crate::context::INT_SAMPLER_3D
+                | crate::context::INT_SAMPLER_CUBE
+                | crate::context::INT_SAMPLER_2D_ARRAY
+                | crate::context::UNSIGNED_INT_SAMPLER_2D
+                | crate::context::UNSIGNED_INT_SAMPLER_3D
+                | crate::context::UNSIGNED_INT_SAMPLER_CUBE
+                | crate::context::UNSIGNED_INT_SAMPLER_2D_ARRAY
+        )
+    }
+}
+
+///
+/// Information about an active uniform block in a [Program], ie. a uniform block that is used by the shader code.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveUniformBlockInfo {
+    /// The name of the uniform block in the shader source.
+    /// This is the name to use in [Program::use_uniform_block].
+    pub name: String,
+    /// The index of the uniform block.
+    pub index: u32,
+    /// The minimum size in bytes of the [UniformBuffer] that is bound to the uniform block.
+    pub data_size: u32,
 }
 
 impl Program {
     ///
     /// Creates a new shader program from the given vertex and fragment glsl shader source.
+    /// The source can include named snippets added to the context (see [Context::add_shader_include]) using `#include "name"`.
     ///
     pub fn from_source(
         context: &Context,
         vertex_shader_source: &str,
         fragment_shader_source: &str,
     ) -> Result<Self, CoreError> {
+        Self::from_source_with_defines(context, vertex_shader_source, fragment_shader_source, &[])
+    }
+
+    ///
+    /// Creates a new shader program from the given vertex and fragment glsl shader source like [Program::from_source],
+    /// but also injects a `#define name value` at the top of both shaders for each of the given defines.
+    /// Use an empty value to define a name without a value, for example `("USE_TEXTURE", "")`.
+    ///
+    pub fn from_source_with_defines(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Self, CoreError> {
+        let mut define_source = String::new();
+        for (name, value) in defines {
+            define_source.push_str(&format!("#define {} {}\n", name, value));
+        }
+        let vertex_shader_source = format!(
+            "{}{}",
+            define_source,
+            resolve_includes(context, vertex_shader_source)?
+        );
+        let fragment_shader_source = format!(
+            "{}{}",
+            define_source,
+            resolve_includes(context, fragment_shader_source)?
+        );
         unsafe {
             let vert_shader = context
                 .create_shader(crate::context::VERTEX_SHADER)
@@ -35,8 +137,20 @@ impl Program {
                 .expect("Failed creating fragment shader");
 
             let header: &str = if context.version().is_embedded {
-                "#version 300 es
-                    #ifdef GL_FRAGMENT_PRECISION_HIGH
+                if context
+                    .supported_extensions()
+                    .contains("GL_EXT_clip_cull_distance")
+                {
+                    "#version 300 es
+                    #extension GL_EXT_clip_cull_distance : enable\n"
+                } else {
+                    "#version 300 es\n"
+                }
+            } else {
+                "#version 330 core\n"
+            };
+            let precision: &str = if context.version().is_embedded {
+                "#ifdef GL_FRAGMENT_PRECISION_HIGH
                         precision highp float;
                         precision highp int;
                         precision highp sampler2DArray;
@@ -48,10 +162,11 @@ impl Program {
                         precision mediump sampler3D;
                     #endif\n"
             } else {
-                "#version 330 core\n"
+                ""
             };
-            let vertex_shader_source = format!("{}{}", header, vertex_shader_source);
-            let fragment_shader_source = format!("{}{}", header, fragment_shader_source);
+            let vertex_shader_source = format!("{}{}{}", header, precision, vertex_shader_source);
+            let fragment_shader_source =
+                format!("{}{}{}", header, precision, fragment_shader_source);
 
             context.shader_source(vert_shader, &vertex_shader_source);
             context.shader_source(frag_shader, &fragment_shader_source);
@@ -95,12 +210,19 @@ impl Program {
             // Init vertex attributes
             let num_attribs = context.get_active_attributes(id);
             let mut attributes = HashMap::new();
+            let mut active_attributes = Vec::new();
             for i in 0..num_attribs {
-                if let Some(crate::context::ActiveAttribute { name, .. }) = context
+                if let Some(crate::context::ActiveAttribute { name, size, atype }) = context
                     .get_active_attribute(id, i)
                     .filter(|a| !a.name.starts_with("gl_"))
                 {
                     if let Some(location) = context.get_attrib_location(id, &name) {
+                        active_attributes.push(ActiveAttributeInfo {
+                            name: name.clone(),
+                            location,
+                            data_type: atype,
+                            size: size as u32,
+                        });
                         attributes.insert(name, location);
                     }
                 }
@@ -109,18 +231,51 @@ impl Program {
             // Init uniforms
             let num_uniforms = context.get_active_uniforms(id);
             let mut uniforms = HashMap::new();
+            let mut active_uniforms = Vec::new();
             for i in 0..num_uniforms {
-                if let Some(crate::context::ActiveUniform { name, .. }) = context
+                if let Some(crate::context::ActiveUniform { name, size, utype }) = context
                     .get_active_uniform(id, i)
                     .filter(|a| !a.name.starts_with("gl_"))
                 {
                     if let Some(location) = context.get_uniform_location(id, &name) {
                         let name = name.split('[').next().unwrap().to_string();
+                        active_uniforms.push(ActiveUniformInfo {
+                            name: name.clone(),
+                            data_type: utype,
+                            size: size as u32,
+                        });
                         uniforms.insert(name, location);
                     }
                 }
             }
 
+            // Init uniform blocks
+            // The number of active uniform blocks cannot be queried through the graphics context, instead the
+            // blocks declared in the source are looked up by name, which only succeeds for active blocks.
+            let mut active_uniform_blocks = Vec::new();
+            for name in uniform_block_names(&vertex_shader_source)
+                .chain(uniform_block_names(&fragment_shader_source))
+            {
+                if let Some(index) = context.get_uniform_block_index(id, name) {
+                    if active_uniform_blocks
+                        .iter()
+                        .any(|b: &ActiveUniformBlockInfo| b.index == index)
+                    {
+                        continue;
+                    }
+                    active_uniform_blocks.push(ActiveUniformBlockInfo {
+                        name: name.to_string(),
+                        index,
+                        data_size: context.get_active_uniform_block_parameter_i32(
+                            id,
+                            index,
+                            crate::context::UNIFORM_BLOCK_DATA_SIZE,
+                        ) as u32,
+                    });
+                }
+            }
+            active_uniform_blocks.sort_by_key(|b| b.index);
+
             Ok(Program {
                 context: context.clone(),
                 id,
@@ -128,6 +283,9 @@ impl Program {
                 uniforms,
                 uniform_blocks: RwLock::new(HashMap::new()),
                 textures: RwLock::new(HashMap::new()),
+                active_attributes,
+                active_uniforms,
+                active_uniform_blocks,
             })
         }
     }
@@ -336,7 +494,8 @@ impl Program {
             buffer.bind();
             let loc = self.location(name);
             unsafe {
-                self.context.bind_vertex_array(Some(self.context.vao));
+                self.context
+                    .bind_vertex_array(Some(self.context.vertex_array()));
                 self.context.enable_vertex_attrib_array(loc);
                 if !buffer.normalized()
                     && (buffer.data_type() == crate::context::UNSIGNED_BYTE
@@ -384,7 +543,8 @@ impl Program {
             buffer.bind();
             let loc = self.location(name);
             unsafe {
-                self.context.bind_vertex_array(Some(self.context.vao));
+                self.context
+                    .bind_vertex_array(Some(self.context.vertex_array()));
                 self.context.enable_vertex_attrib_array(loc);
                 if !buffer.normalized()
                     && (buffer.data_type() == crate::context::UNSIGNED_BYTE
@@ -431,6 +591,7 @@ impl Program {
         unsafe {
             self.context
                 .draw_arrays(crate::context::TRIANGLES, 0, count as i32);
+            self.context.record_draw(count as u64 / 3);
             for location in self.attributes.values() {
                 self.context.disable_vertex_attrib_array(*location);
             }
@@ -465,6 +626,8 @@ impl Program {
                 count as i32,
                 instance_count as i32,
             );
+            self.context
+                .record_draw(count as u64 / 3 * instance_count as u64);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
             for location in self.attributes.values() {
@@ -524,6 +687,7 @@ impl Program {
                 element_buffer.data_type(),
                 first as i32,
             );
+            self.context.record_draw(count as u64 / 3);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
 
@@ -586,6 +750,8 @@ impl Program {
                 first as i32,
                 instance_count as i32,
             );
+            self.context
+                .record_draw(count as u64 / 3 * instance_count as u64);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
             for location in self.attributes.values() {
@@ -615,6 +781,45 @@ impl Program {
         self.attributes.contains_key(name)
     }
 
+    ///
+    /// Returns information about the vertex attributes used by this program.
+    /// Attributes that are defined in the shader code, but not used, are removed by the shader compiler and are therefore not part of this list.
+    ///
+    pub fn active_attributes(&self) -> &[ActiveAttributeInfo] {
+        &self.active_attributes
+    }
+
+    ///
+    /// Returns information about the uniforms, including samplers, used by this program.
+    /// Uniforms that are defined in the shader code, but not used, are removed by the shader compiler and are therefore not part of this list.
+    /// Uniforms that are part of a uniform block are not included, see [Program::active_uniform_blocks] instead.
+    ///
+    pub fn active_uniforms(&self) -> &[ActiveUniformInfo] {
+        &self.active_uniforms
+    }
+
+    ///
+    /// Returns information about the texture samplers used by this program, ie. the subset of [Program::active_uniforms] which are samplers.
+    ///
+    pub fn active_samplers(&self) -> impl Iterator<Item = &ActiveUniformInfo> {
+        self.active_uniforms.iter().filter(|u| u.is_sampler())
+    }
+
+    ///
+    /// Returns information about the uniform blocks used by this program.
+    ///
+    pub fn active_uniform_blocks(&self) -> &[ActiveUniformBlockInfo] {
+        &self.active_uniform_blocks
+    }
+
+    ///
+    /// Sets the label of this program, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::PROGRAM, self.id, label);
+    }
+
     fn location(&self, name: &str) -> u32 {
         self.use_program();
         *self.attributes.get(name).unwrap_or_else(|| {
@@ -645,3 +850,64 @@ impl Drop for Program {
         }
     }
 }
+
+///
+/// Returns the names of the uniform blocks declared in the source, ie. each `uniform Name {` declaration.
+///
+fn uniform_block_names(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .match_indices("uniform")
+        .filter_map(move |(start, keyword)| {
+            if source[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                return None;
+            }
+            let rest = &source[start + keyword.len()..];
+            let name_start = rest.find(|c: char| !c.is_whitespace())?;
+            if name_start == 0 {
+                return None;
+            }
+            let rest = &rest[name_start..];
+            let name_end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+            let (name, rest) = rest.split_at(name_end);
+            (!name.is_empty() && rest.trim_start().starts_with('{')).then_some(name)
+        })
+}
+
+///
+/// Replaces each `#include "name"` line in the source with the snippet with that name in [Context::shader_includes].
+/// Each snippet is only included once, so it is fine for snippets to include each other.
+///
+pub(super) fn resolve_includes(context: &Context, source: &str) -> Result<String, CoreError> {
+    if !source.contains("#include") {
+        return Ok(source.to_string());
+    }
+    let includes = context.shader_includes.read().unwrap();
+    let mut included = Vec::new();
+    resolve_includes_recursive(&includes, source, &mut included)
+}
+
+fn resolve_includes_recursive(
+    includes: &HashMap<String, String>,
+    source: &str,
+    included: &mut Vec<String>,
+) -> Result<String, CoreError> {
+    let mut output = String::with_capacity(source.len());
+    for line in source.lines() {
+        if let Some(rest) = line.trim_start().strip_prefix("#include") {
+            let name = rest
+                .trim()
+                .trim_matches(|c| c == '"' || c == '<' || c == '>');
+            if !included.iter().any(|n| n == name) {
+                let snippet = includes
+                    .get(name)
+                    .ok_or_else(|| CoreError::ShaderInclude(name.to_string()))?;
+                included.push(name.to_string());
+                output.push_str(&resolve_includes_recursive(includes, snippet, included)?);
+            }
+        } else {
+            output.push_str(line);
+        }
+        output.push('\n');
+    }
+    Ok(output)
+}
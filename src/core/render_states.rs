@@ -2,6 +2,8 @@
 //! Definitions of the input state needed for any draw call.
 //!
 
+use crate::core::ScissorBox;
+
 ///
 /// A set of render specific states that has to be specified at each render call.
 ///
@@ -31,14 +33,42 @@ pub struct RenderStates {
     /// Defines whether the triangles that are backfacing, frontfacing or both should be skipped in a render call.
     ///
     pub cull: Cull,
+
+    ///
+    /// Defines the part of the render target that a render call is allowed to modify.
+    /// The scissor box is intersected with the scissor box given to the write method of the render target, for example [RenderTarget::write_partially](crate::core::RenderTarget::write_partially),
+    /// so a render call can never modify pixels outside of that.
+    /// If `None`, only the scissor box given to the write method of the render target is used,
+    /// or when rendering outside of a write method, the scissor test set using [Context::set_scissor](crate::core::Context::set_scissor) is kept.
+    ///
+    pub scissor_box: Option<ScissorBox>,
+
+    ///
+    /// Defines the stencil test and how the stencil buffer is updated in a render call.
+    /// The stencil test determines whether or not a fragment from the current render call should be discarded
+    /// when comparing a reference value with the value in the stencil buffer.
+    ///
+    /// **Note:** The stencil test requires a stencil buffer, ie. that the depth target is created with the [f24s8](crate::core::f24s8) data type
+    /// or, when rendering to the screen, that the window is created with a stencil buffer.
+    ///
+    pub stencil: Stencil,
+
+    ///
+    /// Defines whether the alpha value output by the fragment shader determines how many of the samples of each pixel are covered in a render call, also called alpha to coverage.
+    /// This gives smooth edges of surfaces with an alpha cutout, for example foliage, without sorting the objects.
+    ///
+    /// **Note:** Alpha to coverage only has an effect when rendering to a multisample render target, for example a [RenderTargetMultisample](crate::core::RenderTargetMultisample).
+    ///
+    pub alpha_to_coverage: bool,
 }
 
 ///
 /// Defines whether the triangles that are backfacing, frontfacing, both or none should be rendered in a render call.
 ///
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub enum Cull {
     /// Render both front- and backfacing triangles.
+    #[default]
     None,
     /// Render only frontfacing triangles.
     Back,
@@ -48,12 +78,6 @@ pub enum Cull {
     FrontAndBack,
 }
 
-impl Default for Cull {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 ///
 /// Determines whether or not a fragment/pixel from the current render call should be discarded
 /// when comparing its depth with the depth of the current fragment/pixel.
@@ -61,9 +85,10 @@ impl Default for Cull {
 /// **Note:** Depth test is disabled if the render call is not writing to a depth texture.
 ///
 #[allow(missing_docs)]
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub enum DepthTest {
     Never,
+    #[default]
     Less,
     Equal,
     LessOrEqual,
@@ -73,10 +98,118 @@ pub enum DepthTest {
     Always,
 }
 
-impl Default for DepthTest {
-    fn default() -> Self {
-        Self::Less
+///
+/// Defines the stencil test and the operations applied to the stencil buffer in a render call.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Stencil {
+    /// The stencil test is disabled, so fragments are not discarded and the stencil buffer is not modified.
+    #[default]
+    Disabled,
+    /// The stencil test is enabled.
+    Enabled {
+        /// The comparison between the reference value and the value in the stencil buffer.
+        test: StencilTest,
+        /// The reference value used in the stencil test and by [StencilOperation::Replace].
+        reference: u8,
+        /// The mask that is applied to both the reference value and the stencil buffer value before the stencil test.
+        read_mask: u8,
+        /// The mask that defines which bits of the stencil buffer are modified.
+        write_mask: u8,
+        /// The operation to apply to the stencil buffer when the stencil test fails.
+        stencil_fail: StencilOperation,
+        /// The operation to apply to the stencil buffer when the stencil test passes, but the depth test fails.
+        depth_fail: StencilOperation,
+        /// The operation to apply to the stencil buffer when both the stencil and depth test passes.
+        pass: StencilOperation,
+    },
+}
+
+impl Stencil {
+    ///
+    /// All fragments passes the stencil test and the stencil buffer is set to the reference value wherever a fragment is written.
+    /// This is usually used to mark an area of the render target which is then used with [Stencil::equal] or [Stencil::not_equal] in a later render call.
+    ///
+    pub const fn replace(reference: u8) -> Self {
+        Self::Enabled {
+            test: StencilTest::Always,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            stencil_fail: StencilOperation::Keep,
+            depth_fail: StencilOperation::Keep,
+            pass: StencilOperation::Replace,
+        }
     }
+
+    ///
+    /// Only fragments where the stencil buffer is equal to the reference value passes the stencil test. The stencil buffer is not modified.
+    ///
+    pub const fn equal(reference: u8) -> Self {
+        Self::Enabled {
+            test: StencilTest::Equal,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0x00,
+            stencil_fail: StencilOperation::Keep,
+            depth_fail: StencilOperation::Keep,
+            pass: StencilOperation::Keep,
+        }
+    }
+
+    ///
+    /// Only fragments where the stencil buffer is not equal to the reference value passes the stencil test. The stencil buffer is not modified.
+    ///
+    pub const fn not_equal(reference: u8) -> Self {
+        Self::Enabled {
+            test: StencilTest::NotEqual,
+            reference,
+            read_mask: 0xFF,
+            write_mask: 0x00,
+            stencil_fail: StencilOperation::Keep,
+            depth_fail: StencilOperation::Keep,
+            pass: StencilOperation::Keep,
+        }
+    }
+}
+
+///
+/// The comparison between the reference value and the value in the stencil buffer used in the stencil test, see [Stencil].
+///
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StencilTest {
+    Never,
+    Less,
+    Equal,
+    LessOrEqual,
+    Greater,
+    NotEqual,
+    GreaterOrEqual,
+    Always,
+}
+
+///
+/// An operation applied to the value in the stencil buffer, see [Stencil].
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StencilOperation {
+    /// Keeps the current value.
+    Keep,
+    /// Sets the value to zero.
+    Zero,
+    /// Sets the value to the reference value.
+    Replace,
+    /// Increments the value, clamping at the maximum value.
+    Increment,
+    /// Increments the value, wrapping to zero when exceeding the maximum value.
+    IncrementWrap,
+    /// Decrements the value, clamping at zero.
+    Decrement,
+    /// Decrements the value, wrapping to the maximum value when going below zero.
+    DecrementWrap,
+    /// Bitwise inverts the value.
+    Invert,
 }
 
 ///
@@ -151,7 +284,7 @@ impl Default for WriteMask {
 /// This is usually used to simulate transparency.
 ///
 #[allow(missing_docs)]
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub enum Blend {
     Enabled {
         source_rgb_multiplier: BlendMultiplierType,
@@ -161,6 +294,7 @@ pub enum Blend {
         rgb_equation: BlendEquationType,
         alpha_equation: BlendEquationType,
     },
+    #[default]
     Disabled,
 }
 
@@ -203,12 +337,6 @@ impl Blend {
     };
 }
 
-impl Default for Blend {
-    fn default() -> Self {
-        Self::Disabled
-    }
-}
-
 ///
 /// Value multiplied with the source or target color or alpha value in [Blend].
 ///
@@ -0,0 +1,111 @@
+use crate::core::*;
+
+///
+/// Statistics about the rendering on a [Context], for example to display in an overlay when diagnosing performance, see [Context::render_stats].
+///
+/// The memory usage is estimated from the size and format of the buffers and textures created using the [core](crate::core) module,
+/// so the actual memory usage on the GPU is usually higher due to padding, alignment and memory used by the driver.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The number of draw calls in the frame.
+    pub draw_calls: u32,
+    /// The number of triangles drawn in the frame, including all instances.
+    pub triangles: u64,
+    /// The number of buffers created in the frame.
+    pub buffer_allocations: u32,
+    /// The number of textures created in the frame.
+    pub texture_allocations: u32,
+    /// The number of buffers alive at the end of the frame.
+    pub buffers: u32,
+    /// The estimated number of bytes used by the buffers alive at the end of the frame.
+    pub buffer_memory: u64,
+    /// The number of textures alive at the end of the frame.
+    pub textures: u32,
+    /// The estimated number of bytes used by the textures alive at the end of the frame.
+    pub texture_memory: u64,
+}
+
+impl RenderStats {
+    ///
+    /// Returns the estimated number of bytes used by both buffers and textures.
+    ///
+    pub fn memory(&self) -> u64 {
+        self.buffer_memory + self.texture_memory
+    }
+}
+
+#[derive(Default)]
+pub(super) struct RenderStatsState {
+    current: RenderStats,
+    last_frame: RenderStats,
+}
+
+impl Context {
+    ///
+    /// Returns the statistics of the last finished frame, see [Context::end_frame].
+    ///
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats.read().unwrap().last_frame
+    }
+
+    ///
+    /// Returns the statistics of the frame in progress, ie. the draw calls, triangles and allocations since the last call to [Context::end_frame].
+    ///
+    pub fn current_render_stats(&self) -> RenderStats {
+        self.render_stats.read().unwrap().current
+    }
+
+    ///
+    /// Ends the frame in progress, so the statistics of that frame are returned by [Context::render_stats] and the counters of the next frame start from zero.
+    /// This is called automatically each frame when using [Window::render_loop](crate::window::Window::render_loop), otherwise call this after rendering each frame.
+    ///
+    pub fn end_frame(&self) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.last_frame = stats.current;
+        stats.current = RenderStats {
+            buffers: stats.current.buffers,
+            buffer_memory: stats.current.buffer_memory,
+            textures: stats.current.textures,
+            texture_memory: stats.current.texture_memory,
+            ..Default::default()
+        };
+    }
+
+    pub(crate) fn record_draw(&self, triangles: u64) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.current.draw_calls += 1;
+        stats.current.triangles += triangles;
+    }
+
+    pub(crate) fn record_buffer_allocation(&self) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.current.buffer_allocations += 1;
+        stats.current.buffers += 1;
+    }
+
+    pub(crate) fn record_buffer_deallocation(&self, bytes: u64) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.current.buffers -= 1;
+        stats.current.buffer_memory -= bytes;
+    }
+
+    // Called when the data in a buffer is replaced
+    pub(crate) fn record_buffer_resize(&self, old_bytes: u64, new_bytes: u64) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.current.buffer_memory = stats.current.buffer_memory - old_bytes + new_bytes;
+    }
+
+    pub(crate) fn record_texture_allocation(&self, bytes: u64) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.current.texture_allocations += 1;
+        stats.current.textures += 1;
+        stats.current.texture_memory += bytes;
+    }
+
+    pub(crate) fn record_texture_deallocation(&self, bytes: u64) {
+        let mut stats = self.render_stats.write().unwrap();
+        stats.current.textures -= 1;
+        stats.current.texture_memory -= bytes;
+    }
+}
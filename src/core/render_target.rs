@@ -26,6 +26,10 @@ mod depth_target_multisample;
 #[doc(inline)]
 pub use depth_target_multisample::*;
 
+mod pixel_readback;
+#[doc(inline)]
+pub use pixel_readback::*;
+
 use crate::core::*;
 
 use crate::context::Framebuffer;
@@ -97,9 +101,10 @@ impl<'a> RenderTarget<'a> {
     /// Clears the color and depth of the part of this render target that is inside the given scissor box.
     ///
     pub fn clear_partially(&self, scissor_box: ScissorBox, clear_state: ClearState) -> &Self {
-        self.context.set_scissor(scissor_box);
-        self.bind(crate::context::DRAW_FRAMEBUFFER);
-        clear_state.apply(&self.context);
+        self.context.with_target_scissor(scissor_box, || {
+            self.bind(crate::context::DRAW_FRAMEBUFFER);
+            clear_state.apply(&self.context);
+        });
         self
     }
 
@@ -121,9 +126,10 @@ impl<'a> RenderTarget<'a> {
         scissor_box: ScissorBox,
         render: impl FnOnce() -> Result<(), E>,
     ) -> Result<&Self, E> {
-        self.context.set_scissor(scissor_box);
-        self.bind(crate::context::DRAW_FRAMEBUFFER);
-        render()?;
+        self.context.with_target_scissor(scissor_box, || {
+            self.bind(crate::context::DRAW_FRAMEBUFFER);
+            render()
+        })?;
         if let Some(ref color) = self.color {
             color.generate_mip_maps();
         }
@@ -198,6 +204,22 @@ impl<'a> RenderTarget<'a> {
         pixels
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this render target inside the given scissor box without waiting for the GPU to finish rendering, see [PixelReadback].
+    /// The same restrictions on the generic parameter `T` as for [RenderTarget::read_color_partially] apply.
+    ///
+    pub fn read_color_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> PixelReadback<T> {
+        if self.id.is_some() && self.color.is_none() {
+            panic!("Cannot read color from a render target without a color target");
+        }
+        self.bind(crate::context::DRAW_FRAMEBUFFER);
+        self.bind(crate::context::READ_FRAMEBUFFER);
+        PixelReadback::new(&self.context, scissor_box)
+    }
+
     ///
     /// Returns the depth values in this render target.
     ///
@@ -274,25 +296,26 @@ impl<'a> RenderTarget<'a> {
         } else {
             unreachable!()
         };
-        self.context
-            .set_scissor(ScissorBox::new_at_origo(target.width, target.height));
-        unsafe {
-            self.context
-                .bind_framebuffer(crate::context::READ_FRAMEBUFFER, self.id);
-
-            self.context.blit_framebuffer(
-                0,
-                0,
-                self.width as i32,
-                self.height as i32,
-                0,
-                0,
-                target.width as i32,
-                target.height as i32,
-                mask,
-                crate::context::NEAREST,
-            );
-        }
+        self.context.with_target_scissor(
+            ScissorBox::new_at_origo(target.width, target.height),
+            || unsafe {
+                self.context
+                    .bind_framebuffer(crate::context::READ_FRAMEBUFFER, self.id);
+
+                self.context.blit_framebuffer(
+                    0,
+                    0,
+                    self.width as i32,
+                    self.height as i32,
+                    0,
+                    0,
+                    target.width as i32,
+                    target.height as i32,
+                    mask,
+                    crate::context::NEAREST,
+                );
+            },
+        );
     }
 
     fn new_color(color: ColorTarget<'a>) -> Self {
@@ -85,6 +85,7 @@ impl<'a> ColorTarget<'a> {
             scissor_box,
             ClearState {
                 depth: None,
+                stencil: None,
                 ..clear_state
             },
         );
@@ -146,6 +147,18 @@ impl<'a> ColorTarget<'a> {
         self.as_render_target().read_color_partially(scissor_box)
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this color target inside the given scissor box without waiting for the GPU to finish rendering, see [PixelReadback].
+    /// The same restrictions on the generic parameter `T` as for [ColorTarget::read_partially] apply.
+    ///
+    pub fn read_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> PixelReadback<T> {
+        self.as_render_target()
+            .read_color_partially_async(scissor_box)
+    }
+
     ///
     /// Returns the width of the color target in texels.
     /// If using the zero mip level of the underlying texture, then this is simply the width of that texture, otherwise it is the width of the given mip level.
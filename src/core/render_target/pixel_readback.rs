@@ -0,0 +1,113 @@
+use crate::core::*;
+
+///
+/// The pixels read from a render target without waiting for the GPU, see for example [ColorTarget::read_partially_async].
+/// The pixels are copied into a buffer on the GPU when the GPU has finished the rendering commands issued before the read,
+/// so checking the result a frame later avoids that the CPU waits for the GPU.
+///
+pub struct PixelReadback<T: TextureDataType> {
+    context: Context,
+    buffer: crate::context::Buffer,
+    fence: crate::context::Fence,
+    width: u32,
+    height: u32,
+    _data_type: std::marker::PhantomData<T>,
+}
+
+impl<T: TextureDataType> PixelReadback<T> {
+    pub(super) fn new(context: &Context, scissor_box: ScissorBox) -> Self {
+        let size =
+            scissor_box.width as usize * scissor_box.height as usize * std::mem::size_of::<T>();
+        unsafe {
+            let buffer = context.create_buffer().expect("Failed creating buffer");
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(buffer));
+            context.buffer_data_size(
+                crate::context::PIXEL_PACK_BUFFER,
+                size as i32,
+                crate::context::STREAM_READ,
+            );
+            context.read_pixels(
+                scissor_box.x,
+                scissor_box.y,
+                scissor_box.width as i32,
+                scissor_box.height as i32,
+                format_from_data_type::<T>(),
+                T::data_type(),
+                crate::context::PixelPackData::BufferOffset(0),
+            );
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+            let fence = context
+                .fence_sync(crate::context::SYNC_GPU_COMMANDS_COMPLETE, 0)
+                .expect("Failed creating fence");
+            context.flush();
+            Self {
+                context: context.clone(),
+                buffer,
+                fence,
+                width: scissor_box.width,
+                height: scissor_box.height,
+                _data_type: std::marker::PhantomData,
+            }
+        }
+    }
+
+    ///
+    /// Returns whether the pixels are available, ie. whether [PixelReadback::try_read] returns the pixels.
+    /// This does not wait for the GPU, so call it again in a later frame if the pixels are not available yet.
+    ///
+    /// **Web:** The status is only updated between frames, so the pixels are available at the earliest in the frame after the read was started.
+    ///
+    pub fn is_ready(&self) -> bool {
+        unsafe { self.context.get_sync_status(self.fence) == crate::context::SIGNALED }
+    }
+
+    ///
+    /// Returns the pixels, ordered from the top row, if they are available, otherwise `None`.
+    ///
+    pub fn try_read(&self) -> Option<Vec<T>> {
+        self.is_ready().then(|| self.copy())
+    }
+
+    ///
+    /// Returns the pixels, ordered from the top row, and waits for the GPU if they are not available yet.
+    ///
+    /// **Web:** WebGL does not support waiting for the GPU, so instead the browser blocks when copying the pixels if they are not available yet.
+    /// To avoid that, use [PixelReadback::try_read] in each frame until the pixels are returned.
+    ///
+    pub fn read(self) -> Vec<T> {
+        #[cfg(not(target_arch = "wasm32"))]
+        unsafe {
+            self.context.client_wait_sync(
+                self.fence,
+                crate::context::SYNC_FLUSH_COMMANDS_BIT,
+                i32::MAX,
+            );
+        }
+        self.copy()
+    }
+
+    fn copy(&self) -> Vec<T> {
+        let mut bytes =
+            vec![0u8; self.width as usize * self.height as usize * std::mem::size_of::<T>()];
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(self.buffer));
+            self.context
+                .get_buffer_sub_data(crate::context::PIXEL_PACK_BUFFER, 0, &mut bytes);
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+        }
+        let mut pixels = from_byte_slice(&bytes).to_vec();
+        flip_y(&mut pixels, self.width as usize, self.height as usize);
+        pixels
+    }
+}
+
+impl<T: TextureDataType> Drop for PixelReadback<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.delete_sync(self.fence);
+            self.context.delete_buffer(self.buffer);
+        }
+    }
+}
@@ -57,6 +57,28 @@ impl<T: TextureDataType + PrimitiveDataType> TextureDataType for [T; 4] {}
 
 impl TextureDataType for Quat {}
 
+impl TextureDataType for PackedR11G11B10F {}
+impl TextureDataType for PackedRgb9E5 {}
+
+///
+/// Three unsigned floats (red, green and blue) with 11, 11 and 10 bits respectively, packed into 32 bits (the `R11F_G11F_B10F` format).
+/// Use this as [TextureDataType] for HDR render targets which do not need an alpha channel, it uses a quarter of the memory and bandwidth of `[f32; 4]`.
+/// Rendering into this format requires the `EXT_color_buffer_float` extension on web and OpenGL ES, see [Context::supports_format].
+///
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct PackedR11G11B10F(pub u32);
+
+///
+/// Three unsigned floats (red, green and blue) with 9 bit mantissas and a shared 5 bit exponent, packed into 32 bits (the `RGB9_E5` format).
+/// Use this as [TextureDataType] for HDR textures which are only sampled, for example environment maps.
+/// This format cannot be rendered into.
+///
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct PackedRgb9E5(pub u32);
+
+#[allow(clippy::needless_maybe_sized)]
 impl<T: TextureDataType + ?Sized> TextureDataType for &T {}
 
 /// The basic data type used for each pixel in a depth texture.
@@ -67,9 +89,23 @@ pub trait DepthTextureDataType: DepthDataType {}
 #[derive(Clone, Copy, Default, Debug)]
 pub struct f24 {}
 
+/// 24 bit float depth together with an 8 bit stencil value which can be used as [DepthTextureDataType].
+/// Use this type for the depth texture of a render target if the stencil test (see [Stencil]) should be available when writing to the render target.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct f24s8 {}
+
+/// 32 bit float depth together with an 8 bit stencil value which can be used as [DepthTextureDataType].
+/// Use this type instead of [f24s8] if a higher depth precision is needed.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct f32s8 {}
+
 impl DepthTextureDataType for f16 {}
 impl DepthTextureDataType for f24 {}
 impl DepthTextureDataType for f32 {}
+impl DepthTextureDataType for f24s8 {}
+impl DepthTextureDataType for f32s8 {}
 
 ///
 /// A reference to some type of texture containing colors.
@@ -371,6 +407,29 @@ fn calculate_number_of_mip_maps<T: TextureDataType>(
     }
 }
 
+// Estimates the number of bytes used by a texture with the given size, where the layers are not reduced in the mip maps
+fn texture_byte_size(
+    width: u32,
+    height: u32,
+    layers: u32,
+    number_of_mip_maps: u32,
+    texel_byte_size: usize,
+) -> u64 {
+    (0..number_of_mip_maps)
+        .map(|level| (width >> level).max(1) as u64 * (height >> level).max(1) as u64)
+        .sum::<u64>()
+        * layers as u64
+        * texel_byte_size as u64
+}
+
+fn depth_texel_byte_size<T: DepthTextureDataType>() -> usize {
+    match T::internal_format() {
+        crate::context::DEPTH_COMPONENT16 => 2,
+        crate::context::DEPTH32F_STENCIL8 => 8,
+        _ => 4,
+    }
+}
+
 fn wrapping_from(wrapping: Wrapping) -> i32 {
     (match wrapping {
         Wrapping::Repeat => crate::context::REPEAT,
@@ -6,8 +6,10 @@ use crate::core::texture::*;
 pub struct DepthTexture2D {
     context: Context,
     id: crate::context::Texture,
+    attachment: u32,
     width: u32,
     height: u32,
+    byte_size: u64,
 }
 
 impl DepthTexture2D {
@@ -22,11 +24,15 @@ impl DepthTexture2D {
         wrap_t: Wrapping,
     ) -> Self {
         let id = generate(context);
+        let byte_size = texture_byte_size(width, height, 1, 1, depth_texel_byte_size::<T>());
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
+            attachment: T::attachment(),
             width,
             height,
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -69,11 +75,19 @@ impl DepthTexture2D {
         self.height
     }
 
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(in crate::core) fn bind_as_depth_target(&self) {
         unsafe {
             self.context.framebuffer_texture_2d(
                 crate::context::FRAMEBUFFER,
-                crate::context::DEPTH_ATTACHMENT,
+                self.attachment,
                 crate::context::TEXTURE_2D,
                 Some(self.id),
                 0,
@@ -94,5 +108,6 @@ impl Drop for DepthTexture2D {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
@@ -6,9 +6,11 @@ use crate::core::texture::*;
 pub struct DepthTexture2DArray {
     context: Context,
     id: crate::context::Texture,
+    attachment: u32,
     width: u32,
     height: u32,
     depth: u32,
+    byte_size: u64,
 }
 
 impl DepthTexture2DArray {
@@ -24,12 +26,16 @@ impl DepthTexture2DArray {
         wrap_t: Wrapping,
     ) -> Self {
         let id = generate(context);
+        let byte_size = texture_byte_size(width, height, depth, 1, depth_texel_byte_size::<T>());
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
+            attachment: T::attachment(),
             width,
             height,
             depth,
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -78,11 +84,19 @@ impl DepthTexture2DArray {
         self.depth
     }
 
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(in crate::core) fn bind_as_depth_target(&self, layer: u32) {
         unsafe {
             self.context.framebuffer_texture_layer(
                 crate::context::DRAW_FRAMEBUFFER,
-                crate::context::DEPTH_ATTACHMENT,
+                self.attachment,
                 Some(self.id),
                 0,
                 layer as i32,
@@ -103,5 +117,6 @@ impl Drop for DepthTexture2DArray {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
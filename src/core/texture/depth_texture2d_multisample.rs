@@ -3,9 +3,11 @@ use crate::core::texture::*;
 pub struct DepthTexture2DMultisample {
     context: Context,
     id: crate::context::Renderbuffer,
+    attachment: u32,
     width: u32,
     height: u32,
     number_of_samples: u32,
+    byte_size: u64,
 }
 
 impl DepthTexture2DMultisample {
@@ -20,12 +22,22 @@ impl DepthTexture2DMultisample {
                 .create_renderbuffer()
                 .expect("Failed creating render buffer")
         };
+        let byte_size = texture_byte_size(
+            width,
+            height,
+            number_of_samples,
+            1,
+            depth_texel_byte_size::<T>(),
+        );
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
+            attachment: T::attachment(),
             width,
             height,
             number_of_samples,
+            byte_size,
         };
         texture.bind();
         // CHECK: Omitted `set_parameters` since neither filtering, nor mipmap levels, nor clamping makes sense for multisampled textures.
@@ -60,7 +72,7 @@ impl DepthTexture2DMultisample {
         unsafe {
             self.context.framebuffer_renderbuffer(
                 crate::context::FRAMEBUFFER,
-                crate::context::DEPTH_ATTACHMENT,
+                self.attachment,
                 crate::context::RENDERBUFFER,
                 Some(self.id),
             );
@@ -80,5 +92,6 @@ impl Drop for DepthTexture2DMultisample {
         unsafe {
             self.context.delete_renderbuffer(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
@@ -6,8 +6,10 @@ use crate::core::texture::*;
 pub struct DepthTextureCubeMap {
     context: Context,
     id: crate::context::Texture,
+    attachment: u32,
     width: u32,
     height: u32,
+    byte_size: u64,
 }
 
 impl DepthTextureCubeMap {
@@ -23,11 +25,15 @@ impl DepthTextureCubeMap {
         wrap_r: Wrapping,
     ) -> Self {
         let id = generate(context);
+        let byte_size = texture_byte_size(width, height, 6, 1, depth_texel_byte_size::<T>());
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
+            attachment: T::attachment(),
             width,
             height,
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -70,11 +76,19 @@ impl DepthTextureCubeMap {
         self.height
     }
 
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(in crate::core) fn bind_as_depth_target(&self, side: CubeMapSide) {
         unsafe {
             self.context.framebuffer_texture_2d(
                 crate::context::DRAW_FRAMEBUFFER,
-                crate::context::DEPTH_ATTACHMENT,
+                self.attachment,
                 side.to_const(),
                 Some(self.id),
                 0,
@@ -95,5 +109,6 @@ impl Drop for DepthTextureCubeMap {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
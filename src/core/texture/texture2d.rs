@@ -10,6 +10,7 @@ pub struct Texture2D {
     height: u32,
     number_of_mip_maps: u32,
     data_byte_size: usize,
+    byte_size: u64,
 }
 
 impl Texture2D {
@@ -74,6 +75,14 @@ impl Texture2D {
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, None);
+        let byte_size = texture_byte_size(
+            width,
+            height,
+            1,
+            number_of_mip_maps,
+            std::mem::size_of::<T>(),
+        );
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
@@ -81,6 +90,7 @@ impl Texture2D {
             height,
             number_of_mip_maps,
             data_byte_size: std::mem::size_of::<T>(),
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -160,6 +170,14 @@ impl Texture2D {
         self.height
     }
 
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(crate) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.bind();
@@ -193,5 +211,6 @@ impl Drop for Texture2D {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
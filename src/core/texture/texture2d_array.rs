@@ -14,6 +14,7 @@ pub struct Texture2DArray {
     depth: u32,
     number_of_mip_maps: u32,
     data_byte_size: usize,
+    byte_size: u64,
 }
 
 impl Texture2DArray {
@@ -25,7 +26,7 @@ impl Texture2DArray {
     ///
     pub fn new(context: &Context, cpu_textures: &[&CpuTexture]) -> Self {
         let cpu_texture = cpu_textures
-            .get(0)
+            .first()
             .expect("Expect at least one texture in a texture array");
         match &cpu_texture.data {
             TextureData::RU8(_) => Self::new_with_data(
@@ -163,6 +164,14 @@ impl Texture2DArray {
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, None);
+        let byte_size = texture_byte_size(
+            width,
+            height,
+            depth,
+            number_of_mip_maps,
+            std::mem::size_of::<T>(),
+        );
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
@@ -171,6 +180,7 @@ impl Texture2DArray {
             depth,
             number_of_mip_maps,
             data_byte_size: std::mem::size_of::<T>(),
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -281,6 +291,14 @@ impl Texture2DArray {
         self.depth
     }
 
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(in crate::core) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.bind();
@@ -316,5 +334,6 @@ impl Drop for Texture2DArray {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
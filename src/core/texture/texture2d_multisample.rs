@@ -6,6 +6,7 @@ pub struct Texture2DMultisample {
     width: u32,
     height: u32,
     number_of_samples: u32,
+    byte_size: u64,
 }
 
 impl Texture2DMultisample {
@@ -20,12 +21,21 @@ impl Texture2DMultisample {
                 .create_renderbuffer()
                 .expect("Failed creating render buffer")
         };
+        let byte_size = texture_byte_size(
+            width,
+            height,
+            number_of_samples,
+            1,
+            std::mem::size_of::<T>(),
+        );
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
             width,
             height,
             number_of_samples,
+            byte_size,
         };
         texture.bind();
         unsafe {
@@ -78,5 +88,6 @@ impl Drop for Texture2DMultisample {
         unsafe {
             self.context.delete_renderbuffer(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
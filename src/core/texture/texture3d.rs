@@ -10,6 +10,7 @@ pub struct Texture3D {
     depth: u32,
     number_of_mip_maps: u32,
     data_byte_size: usize,
+    byte_size: u64,
 }
 
 impl Texture3D {
@@ -76,6 +77,14 @@ impl Texture3D {
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, Some(depth));
+        let byte_size = texture_byte_size(
+            width,
+            height,
+            depth,
+            number_of_mip_maps,
+            std::mem::size_of::<T>(),
+        );
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
@@ -84,6 +93,7 @@ impl Texture3D {
             depth,
             number_of_mip_maps,
             data_byte_size: std::mem::size_of::<T>(),
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -171,6 +181,14 @@ impl Texture3D {
             }
         }
     }
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(in crate::core) fn bind(&self) {
         unsafe {
             self.context
@@ -184,5 +202,6 @@ impl Drop for Texture3D {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
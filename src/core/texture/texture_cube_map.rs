@@ -111,6 +111,7 @@ pub struct TextureCubeMap {
     height: u32,
     number_of_mip_maps: u32,
     data_byte_size: usize,
+    byte_size: u64,
 }
 
 impl TextureCubeMap {
@@ -317,6 +318,14 @@ impl TextureCubeMap {
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, None);
+        let byte_size = texture_byte_size(
+            width,
+            height,
+            6,
+            number_of_mip_maps,
+            std::mem::size_of::<T>(),
+        );
+        context.record_texture_allocation(byte_size);
         let texture = Self {
             context: context.clone(),
             id,
@@ -324,6 +333,7 @@ impl TextureCubeMap {
             height,
             number_of_mip_maps,
             data_byte_size: std::mem::size_of::<T>(),
+            byte_size,
         };
         texture.bind();
         set_parameters(
@@ -481,7 +491,7 @@ impl TextureCubeMap {
             let program = Program::from_source(
                 context,
                 full_screen_vertex_shader_source(),
-                &fragment_shader_source,
+                fragment_shader_source,
             )
             .expect("Failed compiling shader");
 
@@ -529,6 +539,14 @@ impl TextureCubeMap {
         self.height
     }
 
+    ///
+    /// Sets the label of this texture, which is shown in graphics debuggers like [RenderDoc](https://renderdoc.org/) if supported, see [Context::push_debug_group].
+    ///
+    pub fn set_label(&self, label: &str) {
+        self.context
+            .set_object_label(crate::context::TEXTURE, self.id, label);
+    }
+
     pub(in crate::core) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.bind();
@@ -569,5 +587,6 @@ impl Drop for TextureCubeMap {
         unsafe {
             self.context.delete_texture(self.id);
         }
+        self.context.record_texture_deallocation(self.byte_size);
     }
 }
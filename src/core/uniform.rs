@@ -25,4 +25,5 @@ impl<T: UniformDataType + PrimitiveDataType> UniformDataType for Matrix2<T> {}
 impl<T: UniformDataType + PrimitiveDataType> UniformDataType for Matrix3<T> {}
 impl<T: UniformDataType + PrimitiveDataType> UniformDataType for Matrix4<T> {}
 
+#[allow(clippy::needless_maybe_sized)]
 impl<T: UniformDataType + ?Sized> UniformDataType for &T {}
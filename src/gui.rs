@@ -2,9 +2,47 @@
 //! Graphical User Interface support.
 //!
 
+use crate::control::Event;
+use crate::core::{CoreError, Viewport};
+
 #[cfg(feature = "egui-gui")]
 #[cfg_attr(docsrs, doc(cfg(feature = "egui-gui")))]
 mod egui_gui;
 #[doc(inline)]
 #[cfg(feature = "egui-gui")]
 pub use egui_gui::*;
+
+///
+/// An immediate mode graphical user interface which is constructed and updated with the [events](crate::Event) each frame and then rendered, usually on top of the 3D scene.
+///
+/// The `GUI` (available with the `egui-gui` feature) implements this trait using the version of [egui](https://crates.io/crates/egui) that three-d depends on.
+/// To use another GUI library, for example [imgui](https://crates.io/crates/imgui), or another version of egui, disable the `egui-gui` feature and implement this trait,
+/// which makes it possible to write the rest of the application independent of the GUI library.
+///
+pub trait UserInterface {
+    ///
+    /// The type given to the callback in [UserInterface::update] for constructing the GUI, for example `egui::Context`.
+    ///
+    type Context;
+
+    ///
+    /// Initialises a new frame of the GUI and handles events.
+    /// Construct the GUI (Add panels, widgets etc.) using the context given to the callback function.
+    /// The events used by the GUI should be marked as handled, so they are not also used to for example control the camera.
+    /// Returns whether or not the GUI has changed, ie. if it consumes any events, and therefore needs to be rendered again.
+    ///
+    fn update(
+        &mut self,
+        events: &mut [Event],
+        accumulated_time_in_ms: f64,
+        viewport: Viewport,
+        device_pixel_ratio: f32,
+        callback: impl FnOnce(&Self::Context),
+    ) -> bool;
+
+    ///
+    /// Render the GUI defined in the [update](Self::update) function.
+    /// Must be called in the callback given as input to a [RenderTarget](crate::RenderTarget), [ColorTarget](crate::ColorTarget) or [DepthTarget](crate::DepthTarget) write method.
+    ///
+    fn render(&self) -> Result<(), CoreError>;
+}
@@ -15,6 +15,8 @@ pub struct GUI {
     output: RefCell<Option<egui::FullOutput>>,
     viewport: Viewport,
     modifiers: Modifiers,
+    clipboard: Box<dyn Clipboard>,
+    scene_views: Vec<SceneViewState>,
 }
 
 impl GUI {
@@ -36,9 +38,25 @@ impl GUI {
             output: RefCell::new(None),
             viewport: Viewport::new_at_origo(1, 1),
             modifiers: Modifiers::default(),
+            clipboard: Box::new(MemoryClipboard::default()),
+            scene_views: Vec::new(),
         }
     }
 
+    ///
+    /// Sets the clipboard used for copying and pasting text, see [Clipboard]. The default is a [MemoryClipboard].
+    ///
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard + 'static) {
+        self.clipboard = Box::new(clipboard);
+    }
+
+    ///
+    /// Returns the clipboard used for copying and pasting text, see [GUI::set_clipboard].
+    ///
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        self.clipboard.as_mut()
+    }
+
     ///
     /// Get the egui context.
     ///
@@ -59,20 +77,19 @@ impl GUI {
         device_pixel_ratio: f32,
         callback: impl FnOnce(&egui::Context),
     ) -> bool {
-        self.egui_context
-            .set_pixels_per_point(device_pixel_ratio as f32);
+        self.egui_context.set_pixels_per_point(device_pixel_ratio);
         self.viewport = viewport;
-        let egui_input = egui::RawInput {
+        let mut egui_input = egui::RawInput {
             screen_rect: Some(egui::Rect {
                 min: egui::Pos2 {
-                    x: viewport.x as f32 / device_pixel_ratio as f32,
-                    y: viewport.y as f32 / device_pixel_ratio as f32,
+                    x: viewport.x as f32 / device_pixel_ratio,
+                    y: viewport.y as f32 / device_pixel_ratio,
                 },
                 max: egui::Pos2 {
-                    x: viewport.x as f32 / device_pixel_ratio as f32
-                        + viewport.width as f32 / device_pixel_ratio as f32,
-                    y: viewport.y as f32 / device_pixel_ratio as f32
-                        + viewport.height as f32 / device_pixel_ratio as f32,
+                    x: viewport.x as f32 / device_pixel_ratio
+                        + viewport.width as f32 / device_pixel_ratio,
+                    y: viewport.y as f32 / device_pixel_ratio
+                        + viewport.height as f32 / device_pixel_ratio,
                 },
             }),
             time: Some(accumulated_time_in_ms * 0.001),
@@ -123,9 +140,8 @@ impl GUI {
                         if !handled {
                             Some(egui::Event::PointerButton {
                                 pos: egui::Pos2 {
-                                    x: position.x / device_pixel_ratio as f32,
-                                    y: (viewport.height as f32 - position.y)
-                                        / device_pixel_ratio as f32,
+                                    x: position.x / device_pixel_ratio,
+                                    y: (viewport.height as f32 - position.y) / device_pixel_ratio,
                                 },
                                 button: button.into(),
                                 pressed: true,
@@ -144,9 +160,8 @@ impl GUI {
                         if !handled {
                             Some(egui::Event::PointerButton {
                                 pos: egui::Pos2 {
-                                    x: position.x / device_pixel_ratio as f32,
-                                    y: (viewport.height as f32 - position.y)
-                                        / device_pixel_ratio as f32,
+                                    x: position.x / device_pixel_ratio,
+                                    y: (viewport.height as f32 - position.y) / device_pixel_ratio,
                                 },
                                 button: button.into(),
                                 pressed: false,
@@ -161,15 +176,20 @@ impl GUI {
                     } => {
                         if !handled {
                             Some(egui::Event::PointerMoved(egui::Pos2 {
-                                x: position.x / device_pixel_ratio as f32,
-                                y: (viewport.height as f32 - position.y)
-                                    / device_pixel_ratio as f32,
+                                x: position.x / device_pixel_ratio,
+                                y: (viewport.height as f32 - position.y) / device_pixel_ratio,
                             }))
                         } else {
                             None
                         }
                     }
                     Event::Text(text) => Some(egui::Event::Text(text.clone())),
+                    Event::ImeCompositionStart => Some(egui::Event::CompositionStart),
+                    Event::ImeCompositionUpdate { text, .. } => {
+                        Some(egui::Event::CompositionUpdate(text.clone()))
+                    }
+                    // The written text follows as an Event::Text event
+                    Event::ImeCompositionEnd(_) => Some(egui::Event::CompositionEnd(String::new())),
                     Event::MouseLeave => Some(egui::Event::PointerGone),
                     Event::MouseWheel {
                         delta,
@@ -179,10 +199,10 @@ impl GUI {
                     } => {
                         if !handled {
                             Some(match modifiers.ctrl {
-                                true => egui::Event::Zoom((delta.1 as f32 / 200.0).exp()),
+                                true => egui::Event::Zoom((delta.1 / 200.0).exp()),
                                 false => egui::Event::Scroll(match modifiers.shift {
-                                    true => egui::Vec2::new(delta.1 as f32, delta.0 as f32),
-                                    false => egui::Vec2::new(delta.0 as f32, delta.1 as f32),
+                                    true => egui::Vec2::new(delta.1, delta.0),
+                                    false => egui::Vec2::new(delta.0, delta.1),
                                 }),
                             })
                         } else {
@@ -195,15 +215,50 @@ impl GUI {
             ..Default::default()
         };
 
+        for event in events.iter() {
+            if let Event::KeyPress {
+                kind,
+                modifiers,
+                handled: false,
+            } = event
+            {
+                if modifiers.command {
+                    match kind {
+                        Key::C => egui_input.events.push(egui::Event::Copy),
+                        Key::X => egui_input.events.push(egui::Event::Cut),
+                        Key::V => {
+                            if let Some(text) = self.clipboard.get_text() {
+                                egui_input
+                                    .events
+                                    .push(egui::Event::Paste(text.replace("\r\n", "\n")));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        self.egui_context
+            .data_mut(|data| data.remove::<Vec<SceneViewState>>(egui::Id::new(SCENE_VIEWS_ID)));
         self.egui_context.begin_frame(egui_input);
         callback(&self.egui_context);
-        *self.output.borrow_mut() = Some(self.egui_context.end_frame());
+        let output = self.egui_context.end_frame();
+        self.scene_views = self.egui_context.data(|data| {
+            data.get_temp::<Vec<SceneViewState>>(egui::Id::new(SCENE_VIEWS_ID))
+                .unwrap_or_default()
+        });
+        let scene_view_active = self.scene_views.iter().any(|view| view.active);
+        if !output.platform_output.copied_text.is_empty() {
+            self.clipboard.set_text(&output.platform_output.copied_text);
+        }
+        *self.output.borrow_mut() = Some(output);
 
         for event in events.iter_mut() {
             if let Event::ModifiersChange { modifiers } = event {
                 self.modifiers = *modifiers;
             }
-            if self.egui_context.wants_pointer_input() {
+            if self.egui_context.wants_pointer_input() && !scene_view_active {
                 match event {
                     Event::MousePress {
                         ref mut handled, ..
@@ -245,37 +300,206 @@ impl GUI {
                 }
             }
         }
-        self.egui_context.wants_pointer_input() || self.egui_context.wants_keyboard_input()
+        (self.egui_context.wants_pointer_input() && !scene_view_active)
+            || self.egui_context.wants_keyboard_input()
+    }
+
+    ///
+    /// Returns the viewport of the [SceneView] with the given id source in physical pixels, if it was added to the GUI in the last call to [GUI::update].
+    /// Use this to set the viewport of the camera used for rendering into the scene view, before handling the events with for example an [OrbitControl](crate::OrbitControl).
+    ///
+    pub fn scene_view_viewport(&self, id_source: impl std::hash::Hash) -> Option<Viewport> {
+        let id = egui::Id::new(id_source);
+        self.scene_views
+            .iter()
+            .find(|view| view.id == id)
+            .map(|view| self.scene_view_info(view.rect, view.clip_rect).0)
+    }
+
+    fn scene_view_info(&self, rect: egui::Rect, clip_rect: egui::Rect) -> (Viewport, ScissorBox) {
+        let info = egui::PaintCallbackInfo {
+            viewport: rect,
+            clip_rect,
+            pixels_per_point: self.egui_context.pixels_per_point(),
+            screen_size_px: [self.viewport.width, self.viewport.height],
+        };
+        let viewport = info.viewport_in_pixels();
+        let scissor_box = info.clip_rect_in_pixels();
+        (
+            Viewport {
+                x: viewport.left_px,
+                y: viewport.from_bottom_px,
+                width: viewport.width_px.max(0) as u32,
+                height: viewport.height_px.max(0) as u32,
+            },
+            ScissorBox {
+                x: scissor_box.left_px,
+                y: scissor_box.from_bottom_px,
+                width: scissor_box.width_px.max(0) as u32,
+                height: scissor_box.height_px.max(0) as u32,
+            },
+        )
     }
 
     ///
     /// Render the GUI defined in the [update](Self::update) function.
     /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    /// [SceneView]s in the GUI are left empty, use [GUI::render_with_scene_views] to render a 3D scene into them.
     ///
     pub fn render(&self) -> Result<(), crate::CoreError> {
+        self.render_with_scene_views(|_, _, _| {})
+    }
+
+    ///
+    /// Render the GUI defined in the [update](Self::update) function like [GUI::render], but calls the `render_scene_view` closure for each [SceneView] in the GUI
+    /// at the point where the scene view is painted, so the GUI painted before it is behind the 3D scene and the GUI painted after it, for example a window on top of it, is in front of the 3D scene.
+    ///
+    /// The closure is given the id of the scene view, ie. `egui::Id::new(id_source)`, the viewport of the scene view in physical pixels, which should be used as the viewport of the camera,
+    /// and a scissor box which should be used when clearing and rendering to avoid rendering outside the part of the scene view which is visible in the GUI,
+    /// for example using [RenderTarget::clear_partially] and [RenderTarget::render_partially] on the screen render target.
+    ///
+    pub fn render_with_scene_views(
+        &self,
+        mut render_scene_view: impl FnMut(egui::Id, Viewport, ScissorBox),
+    ) -> Result<(), crate::CoreError> {
         let output = self
             .output
             .borrow_mut()
             .take()
             .expect("need to call GUI::update before GUI::render");
         let scale = self.egui_context.pixels_per_point();
-        let clipped_meshes = self.egui_context.tessellate(output.shapes, scale);
-        self.painter.borrow_mut().paint_and_update_textures(
-            [self.viewport.width, self.viewport.height],
-            scale,
-            &clipped_meshes,
-            &output.textures_delta,
-        );
+        let clipped_primitives = self.egui_context.tessellate(output.shapes, scale);
+        let screen_size = [self.viewport.width, self.viewport.height];
+        let mut painter = self.painter.borrow_mut();
+        for (id, image_delta) in &output.textures_delta.set {
+            painter.set_texture(*id, image_delta);
+        }
+        let mut start = 0;
+        for (index, clipped_primitive) in clipped_primitives.iter().enumerate() {
+            let egui::epaint::Primitive::Callback(callback) = &clipped_primitive.primitive else {
+                continue;
+            };
+            let Some(SceneViewCallback(id)) = callback.callback.downcast_ref() else {
+                continue;
+            };
+            if start < index {
+                painter.paint_primitives(screen_size, scale, &clipped_primitives[start..index]);
+            }
+            start = index + 1;
+            let (viewport, scissor_box) = self.scene_view_info(
+                callback.rect,
+                clipped_primitive.clip_rect.intersect(callback.rect),
+            );
+            if viewport.width > 0
+                && viewport.height > 0
+                && scissor_box.width > 0
+                && scissor_box.height > 0
+            {
+                render_scene_view(*id, viewport, scissor_box);
+            }
+        }
+        if start < clipped_primitives.len() {
+            painter.paint_primitives(screen_size, scale, &clipped_primitives[start..]);
+        }
+        for id in &output.textures_delta.free {
+            painter.free_texture(*id);
+        }
         #[cfg(not(target_arch = "wasm32"))]
         #[allow(unsafe_code)]
         unsafe {
             use glow::HasContext as _;
-            self.painter.borrow().gl().disable(glow::FRAMEBUFFER_SRGB);
+            painter.gl().disable(glow::FRAMEBUFFER_SRGB);
         }
         Ok(())
     }
 }
 
+const SCENE_VIEWS_ID: &str = "three_d_scene_views";
+
+#[derive(Clone, Debug)]
+struct SceneViewState {
+    id: egui::Id,
+    rect: egui::Rect,
+    clip_rect: egui::Rect,
+    active: bool,
+}
+
+struct SceneViewCallback(egui::Id);
+
+///
+/// An [egui] widget which reserves a region of the [GUI] for rendering a 3D scene, for example a dockable 3D view in an editor.
+/// Add it to a [egui::Ui] using `ui.add(SceneView::new("scene", ui.available_size()))`
+/// and render the scene into it using [GUI::render_with_scene_views], which is called with the id `egui::Id::new("scene")`.
+/// The viewport of the scene view is also available after [GUI::update] using [GUI::scene_view_viewport].
+///
+/// While the mouse hovers the scene view, or a drag started in the scene view, the mouse events are not marked as handled by [GUI::update]
+/// so they can be used to control the camera of the 3D scene. Mouse events over the rest of the GUI are still marked as handled.
+///
+pub struct SceneView {
+    id: egui::Id,
+    size: egui::Vec2,
+}
+
+impl SceneView {
+    ///
+    /// Creates a new scene view with the given size in points. The id source must be unique among the scene views in the GUI.
+    ///
+    pub fn new(id_source: impl std::hash::Hash, size: egui::Vec2) -> Self {
+        Self {
+            id: egui::Id::new(id_source),
+            size,
+        }
+    }
+}
+
+impl egui::Widget for SceneView {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let (_, rect) = ui.allocate_space(self.size);
+        let response = ui.interact(rect, self.id, egui::Sense::click_and_drag());
+        let state = SceneViewState {
+            id: self.id,
+            rect,
+            clip_rect: ui.clip_rect().intersect(rect),
+            active: response.hovered() || response.dragged(),
+        };
+        ui.ctx().data_mut(|data| {
+            data.get_temp_mut_or_default::<Vec<SceneViewState>>(egui::Id::new(SCENE_VIEWS_ID))
+                .push(state)
+        });
+        ui.painter().add(egui::PaintCallback {
+            rect,
+            callback: std::sync::Arc::new(SceneViewCallback(self.id)),
+        });
+        response
+    }
+}
+
+impl super::UserInterface for GUI {
+    type Context = egui::Context;
+
+    fn update(
+        &mut self,
+        events: &mut [Event],
+        accumulated_time_in_ms: f64,
+        viewport: Viewport,
+        device_pixel_ratio: f32,
+        callback: impl FnOnce(&egui::Context),
+    ) -> bool {
+        GUI::update(
+            self,
+            events,
+            accumulated_time_in_ms,
+            viewport,
+            device_pixel_ratio,
+            callback,
+        )
+    }
+
+    fn render(&self) -> Result<(), crate::CoreError> {
+        GUI::render(self)
+    }
+}
+
 impl Drop for GUI {
     fn drop(&mut self) {
         self.painter.borrow_mut().destroy();
@@ -29,6 +29,18 @@ pub enum RendererError {
     InvalidBufferLength(String, usize, usize),
     #[error("the material {0} is required by the geometry {1} but could not be found")]
     MissingMaterial(String, String),
+    #[error("invalid color lookup table: {0}")]
+    InvalidColorLookupTable(String),
+    #[error("the font data is not a valid TrueType or OpenType font")]
+    InvalidFont,
+    #[error("invalid glTF file: {0}")]
+    InvalidGltf(String),
+    #[error("the render graph contains a cycle involving the pass {0}")]
+    RenderGraphCycle(String),
+    #[error("failed reading the shader source {0}: {1}")]
+    ShaderSourceRead(String, String),
+    #[error("failed compiling the shader source {0}: {1}")]
+    ShaderCompilation(String, String),
 }
 
 mod camera;
@@ -52,12 +64,37 @@ pub use object::*;
 pub mod control;
 pub use control::*;
 
+mod render_graph;
+pub use render_graph::*;
+
+mod picking;
+pub use picking::*;
+
+mod raycast;
+pub use raycast::*;
+
+mod screenshot;
+pub use screenshot::*;
+
+mod debug_renderer;
+pub use debug_renderer::*;
+
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+mod debug_overlay;
+#[cfg(feature = "text")]
+pub use debug_overlay::*;
+
+mod parallel;
+
 macro_rules! impl_render_target_extensions_body {
     () => {
         ///
         /// Render the objects using the given camera and lights into this render target.
         /// Use an empty array for the `lights` argument, if the objects does not require lights to be rendered.
         /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [cmp_render_order].
+        /// The frustum tests and the sorting run on multiple threads when the `rayon` feature is enabled,
+        /// while the bounding boxes of the objects are computed and the objects are drawn on the calling thread.
         ///
         pub fn render(
             &self,
@@ -80,19 +117,19 @@ macro_rules! impl_render_target_extensions_body {
             objects: impl IntoIterator<Item = impl Object>,
             lights: &[&dyn Light],
         ) -> &Self {
-            let (mut deferred_objects, mut forward_objects): (Vec<_>, Vec<_>) = objects
-                .into_iter()
-                .filter(|o| camera.in_frustum(&o.aabb()))
-                .partition(|o| o.material_type() == MaterialType::Deferred);
+            let (deferred_objects, forward_objects): (Vec<_>, Vec<_>) =
+                cull_and_sort(camera, objects)
+                    .into_iter()
+                    .partition(|o| o.material_type() == MaterialType::Deferred);
 
             // Deferred
             if deferred_objects.len() > 0 {
                 // Geometry pass
+                self.context.push_debug_group("Deferred geometry pass");
                 let mut geometry_pass_camera = camera.clone();
                 let viewport =
                     Viewport::new_at_origo(camera.viewport().width, camera.viewport().height);
                 geometry_pass_camera.set_viewport(viewport);
-                deferred_objects.sort_by(|a, b| cmp_render_order(&geometry_pass_camera, a, b));
                 let mut geometry_pass_texture = Texture2DArray::new_empty::<[u8; 4]>(
                     &self.context,
                     viewport.width,
@@ -111,6 +148,8 @@ macro_rules! impl_render_target_extensions_body {
                     Wrapping::ClampToEdge,
                     Wrapping::ClampToEdge,
                 );
+                geometry_pass_texture.set_label("G-buffer");
+                geometry_pass_depth_texture.set_label("G-buffer depth");
                 let gbuffer_layers = [0, 1, 2];
                 RenderTarget::new(
                     geometry_pass_texture.as_color_target(&gbuffer_layers, None),
@@ -124,31 +163,209 @@ macro_rules! impl_render_target_extensions_body {
                     Ok(())
                 })
                 .unwrap();
+                self.context.pop_debug_group();
 
                 // Lighting pass
-                self.apply_screen_effect_partially(
-                    scissor_box,
-                    &lighting_pass::LightingPassEffect {},
-                    camera,
-                    lights,
-                    Some(ColorTexture::Array {
-                        texture: &geometry_pass_texture,
-                        layers: &gbuffer_layers,
-                    }),
-                    Some(DepthTexture::Single(&geometry_pass_depth_texture)),
-                );
+                self.context.push_debug_group("Deferred lighting pass");
+                let gbuffer = ColorTexture::Array {
+                    texture: &geometry_pass_texture,
+                    layers: &gbuffer_layers,
+                };
+                let gbuffer_depth = DepthTexture::Single(&geometry_pass_depth_texture);
+                if lights.len() <= lighting_pass::MAX_LIGHTS_PER_PASS {
+                    self.apply_screen_effect_partially(
+                        scissor_box,
+                        &lighting_pass::LightingPassEffect::default(),
+                        camera,
+                        lights,
+                        Some(gbuffer),
+                        Some(gbuffer_depth),
+                    );
+                } else {
+                    // Accumulate the lighting from all but the last batch of lights in a HDR texture
+                    // and add it to the lighting from the last batch in the final lighting pass.
+                    let (accumulated_lights, last_lights) =
+                        lights.split_at(lights.len() - lighting_pass::MAX_LIGHTS_PER_PASS);
+                    let mut cached_texture =
+                        self.context.deferred_lighting_texture.write().unwrap();
+                    if !cached_texture.as_ref().is_some_and(|texture| {
+                        texture.width() == viewport.width && texture.height() == viewport.height
+                    }) {
+                        let texture = Texture2D::new_empty::<[f16; 4]>(
+                            &self.context,
+                            viewport.width,
+                            viewport.height,
+                            Interpolation::Nearest,
+                            Interpolation::Nearest,
+                            None,
+                            Wrapping::ClampToEdge,
+                            Wrapping::ClampToEdge,
+                        );
+                        texture.set_label("Deferred lighting accumulation");
+                        *cached_texture = Some(texture);
+                    }
+                    let accumulated_lighting = cached_texture.as_mut().unwrap();
+                    geometry_pass_camera.disable_tone_and_color_mapping();
+                    accumulated_lighting
+                        .as_color_target(None)
+                        .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+                        .write::<RendererError>(|| {
+                            for lights in
+                                accumulated_lights.chunks(lighting_pass::MAX_LIGHTS_PER_PASS)
+                            {
+                                apply_screen_effect(
+                                    &self.context,
+                                    &lighting_pass::LightingPassEffect {
+                                        accumulate: true,
+                                        accumulated_lighting: None,
+                                    },
+                                    &geometry_pass_camera,
+                                    lights,
+                                    Some(gbuffer),
+                                    Some(gbuffer_depth),
+                                );
+                            }
+                            Ok(())
+                        })
+                        .unwrap();
+                    self.apply_screen_effect_partially(
+                        scissor_box,
+                        &lighting_pass::LightingPassEffect {
+                            accumulate: false,
+                            accumulated_lighting: Some(&*accumulated_lighting),
+                        },
+                        camera,
+                        last_lights,
+                        Some(gbuffer),
+                        Some(gbuffer_depth),
+                    );
+                }
+                self.context.pop_debug_group();
             }
 
             // Forward
-            forward_objects.sort_by(|a, b| cmp_render_order(camera, a, b));
-            self.write_partially::<RendererError>(scissor_box, || {
-                for object in forward_objects {
-                    object.render(camera, lights);
+            self.context.debug_group("Forward pass", || {
+                self.write_partially::<RendererError>(scissor_box, || {
+                    for object in forward_objects {
+                        object.render(camera, lights);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            });
+            self
+        }
+
+        ///
+        /// Render the objects like [Self::render] and then the transparent geometries, each with the given material, using weighted blended order independent transparency.
+        /// This means that the transparent surfaces are blended correctly without sorting, even when they overlap or intersect, which is not possible with [Self::render].
+        /// The result is an approximation which is most accurate for surfaces with a low opacity.
+        /// Use an empty array for the `lights` argument, if the objects does not require lights to be rendered.
+        ///
+        /// **Note:** Materials implemented outside this crate must return an id smaller than `0b1u16 << 14` to be used as a transparent material.
+        ///
+        pub fn render_with_order_independent_transparency(
+            &self,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object> + Clone,
+            transparent: impl IntoIterator<Item = (impl Geometry, impl Material)>,
+            lights: &[&dyn Light],
+        ) -> &Self {
+            self.render_partially_with_order_independent_transparency(
+                self.scissor_box(),
+                camera,
+                objects,
+                transparent,
+                lights,
+            )
+        }
+
+        ///
+        /// Render the objects and the transparent geometries like [Self::render_with_order_independent_transparency] into the part of this render target defined by the scissor box.
+        ///
+        pub fn render_partially_with_order_independent_transparency(
+            &self,
+            scissor_box: ScissorBox,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object> + Clone,
+            transparent: impl IntoIterator<Item = (impl Geometry, impl Material)>,
+            lights: &[&dyn Light],
+        ) -> &Self {
+            use order_independent_transparency::*;
+            self.render_partially(scissor_box, camera, objects.clone(), lights);
+
+            let mut accumulation_camera = camera.clone();
+            let viewport =
+                Viewport::new_at_origo(camera.viewport().width, camera.viewport().height);
+            accumulation_camera.set_viewport(viewport);
+
+            // The depth of the opaque objects which hide the transparent surfaces
+            let mut depth_texture = DepthTexture2D::new::<f32>(
+                &self.context,
+                viewport.width,
+                viewport.height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            depth_texture
+                .as_depth_target()
+                .clear(ClearState::depth(1.0))
+                .render_with_material(
+                    &DepthMaterial::new_depth_only(),
+                    &accumulation_camera,
+                    objects
+                        .into_iter()
+                        .filter(|o| o.material_type() != MaterialType::Transparent),
+                    &[],
+                );
+
+            // Accumulation of the transparent surfaces in the first layer and revealage in the second layer
+            let mut accumulation_texture = Texture2DArray::new_empty::<[f16; 4]>(
+                &self.context,
+                viewport.width,
+                viewport.height,
+                2,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            let layers = [0, 1];
+            RenderTarget::new(
+                accumulation_texture.as_color_target(&layers, None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .write::<RendererError>(|| {
+                let frustum = accumulation_camera.frustum();
+                for (geometry, material) in transparent
+                    .into_iter()
+                    .filter(|(g, _)| frustum.intersects_aabb(&g.aabb()))
+                {
+                    render_with_material(
+                        &self.context,
+                        &accumulation_camera,
+                        geometry,
+                        WeightedBlendedMaterial { material },
+                        lights,
+                    );
                 }
                 Ok(())
             })
             .unwrap();
-            self
+
+            self.apply_screen_effect_partially(
+                scissor_box,
+                &WeightedBlendedCompositeEffect {},
+                camera,
+                &[],
+                Some(ColorTexture::Array {
+                    texture: &accumulation_texture,
+                    layers: &layers,
+                }),
+                None,
+            )
         }
 
         ///
@@ -184,9 +401,10 @@ macro_rules! impl_render_target_extensions_body {
             lights: &[&dyn Light],
         ) -> &Self {
             self.write_partially::<RendererError>(scissor_box, || {
+                let frustum = camera.frustum();
                 for geometry in geometries
                     .into_iter()
-                    .filter(|o| camera.in_frustum(&o.aabb()))
+                    .filter(|o| frustum.intersects_aabb(&o.aabb()))
                 {
                     render_with_material(&self.context, camera, geometry, material, lights);
                 }
@@ -235,9 +453,10 @@ macro_rules! impl_render_target_extensions_body {
             depth_texture: Option<DepthTexture>,
         ) -> &Self {
             self.write_partially::<RendererError>(scissor_box, || {
+                let frustum = camera.frustum();
                 for geometry in geometries
                     .into_iter()
-                    .filter(|o| camera.in_frustum(&o.aabb()))
+                    .filter(|o| frustum.intersects_aabb(&o.aabb()))
                 {
                     render_with_effect(
                         &self.context,
@@ -394,12 +613,13 @@ pub fn render_with_material(
 
     let mut programs = context.programs.write().unwrap();
     let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
-            context,
-            &geometry.vertex_shader_source(fragment_attributes),
-            &material.fragment_shader_source(lights),
-        )
-        .expect("Failed compiling shader")
+        context
+            .program(
+                &geometry.vertex_shader_source(fragment_attributes),
+                &material.fragment_shader_source(lights),
+                &[],
+            )
+            .expect("Failed compiling shader")
     });
     material.use_uniforms(program, camera, lights);
     geometry.draw(
@@ -410,6 +630,21 @@ pub fn render_with_material(
     );
 }
 
+///
+/// Removes the programs cached by [render_with_material] for the material with the given id from [Context::programs],
+/// for example when the source of the material changes and the id is no longer used.
+///
+pub(crate) fn remove_material_programs(context: &Context, material_id: u32) {
+    // The keys consist of the geometry id, the material id and the light ids, see render_with_material
+    let material_id = material_id.to_le_bytes();
+    context
+        .programs
+        .write()
+        .unwrap()
+        .retain(|key, _| key.get(2..6) != Some(&material_id[..]));
+    context.remove_unused_programs();
+}
+
 ///
 /// Render the given [Geometry] with the given [Effect].
 /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
@@ -431,12 +666,13 @@ pub fn render_with_effect(
 
     let mut programs = context.programs.write().unwrap();
     let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
-            context,
-            &geometry.vertex_shader_source(fragment_attributes),
-            &effect.fragment_shader_source(lights, color_texture, depth_texture),
-        )
-        .expect("Failed compiling shader")
+        context
+            .program(
+                &geometry.vertex_shader_source(fragment_attributes),
+                &effect.fragment_shader_source(lights, color_texture, depth_texture),
+                &[],
+            )
+            .expect("Failed compiling shader")
     });
     effect.use_uniforms(program, camera, lights, color_texture, depth_texture);
     geometry.draw(camera, program, effect.render_states(), fragment_attributes);
@@ -463,12 +699,13 @@ pub fn apply_screen_material(
 
     let mut programs = context.programs.write().unwrap();
     let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
-            context,
-            full_screen_vertex_shader_source(),
-            &material.fragment_shader_source(lights),
-        )
-        .expect("Failed compiling shader")
+        context
+            .program(
+                full_screen_vertex_shader_source(),
+                &material.fragment_shader_source(lights),
+                &[],
+            )
+            .expect("Failed compiling shader")
     });
     material.use_uniforms(program, camera, lights);
     full_screen_draw(
@@ -502,20 +739,21 @@ pub fn apply_screen_effect(
 
     let mut programs = context.programs.write().unwrap();
     let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
-            context,
-            full_screen_vertex_shader_source(),
-            &effect.fragment_shader_source(lights, color_texture, depth_texture),
-        )
-        .expect("Failed compiling shader")
+        context
+            .program(
+                full_screen_vertex_shader_source(),
+                &effect.fragment_shader_source(lights, color_texture, depth_texture),
+                &[],
+            )
+            .expect("Failed compiling shader")
     });
     effect.use_uniforms(program, camera, lights, color_texture, depth_texture);
     full_screen_draw(context, program, effect.render_states(), camera.viewport());
 }
 
 ///
-/// Compare function for sorting objects based on distance from the camera.
-/// The order is opaque objects from nearest to farthest away from the camera,
+/// Compare function for sorting objects based on distance from the camera and the shader program used to render them.
+/// The order is opaque objects grouped by [Object::program_id] to minimize the number of state changes and from nearest to farthest away from the camera within each group,
 /// then transparent objects from farthest away to closest to the camera.
 ///
 pub fn cmp_render_order(
@@ -523,27 +761,72 @@ pub fn cmp_render_order(
     obj0: impl Object,
     obj1: impl Object,
 ) -> std::cmp::Ordering {
-    if obj0.material_type() == MaterialType::Transparent
-        && obj1.material_type() != MaterialType::Transparent
-    {
-        std::cmp::Ordering::Greater
-    } else if obj0.material_type() != MaterialType::Transparent
-        && obj1.material_type() == MaterialType::Transparent
-    {
-        std::cmp::Ordering::Less
+    cmp_render_keys(
+        *camera.position(),
+        &RenderKey::new(0, &obj0),
+        &RenderKey::new(0, &obj1),
+    )
+}
+
+// The data needed to cull and sort an object, which is gathered on the calling thread since objects are not required to be thread safe
+#[derive(Clone, Copy)]
+struct RenderKey {
+    index: usize,
+    aabb: AxisAlignedBoundingBox,
+    material_type: MaterialType,
+    program_id: Option<u64>,
+}
+
+impl RenderKey {
+    fn new(index: usize, object: &impl Object) -> Self {
+        Self {
+            index,
+            aabb: object.aabb(),
+            material_type: object.material_type(),
+            program_id: object.program_id(),
+        }
+    }
+}
+
+fn cmp_render_keys(position: Vec3, key0: &RenderKey, key1: &RenderKey) -> std::cmp::Ordering {
+    let transparent0 = key0.material_type == MaterialType::Transparent;
+    let transparent1 = key1.material_type == MaterialType::Transparent;
+    if transparent0 != transparent1 {
+        transparent0.cmp(&transparent1)
     } else {
-        let distance_a = camera.position().distance2(obj0.aabb().center());
-        let distance_b = camera.position().distance2(obj1.aabb().center());
-        if distance_a.is_nan() || distance_b.is_nan() {
-            distance_a.is_nan().cmp(&distance_b.is_nan()) // whatever - just save us from panicing on unwrap below
-        } else if obj0.material_type() == MaterialType::Transparent {
-            distance_b.partial_cmp(&distance_a).unwrap()
+        let distance_a = position.distance2(key0.aabb.center());
+        let distance_b = position.distance2(key1.aabb.center());
+        if transparent0 {
+            distance_b.total_cmp(&distance_a)
         } else {
-            distance_a.partial_cmp(&distance_b).unwrap()
+            key0.program_id
+                .cmp(&key1.program_id)
+                .then(distance_a.total_cmp(&distance_b))
         }
     }
 }
 
+///
+/// Returns the objects inside the frustum of the given camera in the order given by [cmp_render_order].
+/// The frustum tests and the sorting run on multiple threads when the `rayon` feature is enabled.
+/// The render keys, including the bounding boxes, are computed on the calling thread since objects are not required to be [Sync].
+///
+fn cull_and_sort<T: Object>(camera: &Camera, objects: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut objects = objects.into_iter().map(Some).collect::<Vec<_>>();
+    let keys = objects
+        .iter()
+        .enumerate()
+        .map(|(i, o)| RenderKey::new(i, o.as_ref().unwrap()))
+        .collect::<Vec<_>>();
+    let frustum = camera.frustum();
+    let position = *camera.position();
+    let mut keys = parallel::filter(&keys, |k| frustum.intersects_aabb(&k.aabb));
+    parallel::sort_by(&mut keys, |a, b| cmp_render_keys(position, a, b));
+    keys.into_iter()
+        .map(|k| objects[k.index].take().unwrap())
+        .collect()
+}
+
 ///
 /// Finds the closest intersection between a ray from the given camera in the given pixel coordinate and the given geometries.
 /// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport
@@ -556,15 +839,9 @@ pub fn pick(
     pixel: impl Into<PhysicalPoint> + Copy,
     geometries: impl IntoIterator<Item = impl Geometry>,
 ) -> Option<Vec3> {
-    let pos = camera.position_at_pixel(pixel);
-    let dir = camera.view_direction_at_pixel(pixel);
-    ray_intersect(
-        context,
-        pos + dir * camera.z_near(),
-        dir,
-        camera.z_far() - camera.z_near(),
-        geometries,
-    )
+    let pos = camera.unproject(pixel, 0.0);
+    let ray = camera.unproject(pixel, 1.0) - pos;
+    ray_intersect(context, pos, ray.normalize(), ray.magnitude(), geometries)
 }
 
 ///
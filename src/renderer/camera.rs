@@ -4,7 +4,11 @@ pub use tone_mapping::*;
 mod color_space;
 pub use color_space::*;
 
+mod fog;
+pub use fog::*;
+
 use crate::core::*;
+use crate::renderer::PhysicalPoint;
 
 ///
 /// Represents a camera used for viewing 2D and 3D objects.
@@ -14,11 +18,32 @@ pub struct Camera {
     camera: three_d_asset::Camera,
     /// This tone mapping is applied to the final color of renders using this camera.
     pub tone_mapping: ToneMapping,
+    /// The exposure adjustment, in stops, applied to the color before the [Camera::tone_mapping], ie. the color is multiplied by `2^exposure`.
+    /// A value of zero leaves the color unchanged, positive values brightens the image and negative values darkens it.
+    /// The exposure is not applied when the tone mapping is [ToneMapping::None], and can be adapted to the brightness of the scene using [AutoExposure](crate::AutoExposure).
+    pub exposure: f32,
+    /// The color value which is mapped to white by the [Camera::tone_mapping]. If not specified, the default white point of the tone mapping curve is used.
+    pub white_point: Option<f32>,
     /// This color mapping is applied to the final color of renders using this camera.
     pub color_mapping: ColorMapping,
+    /// The fog applied by the built-in lit materials and by the [ColorMaterial](crate::ColorMaterial) when rendering with this camera.
+    pub fog: Fog,
+    /// User defined clip planes given as plane equations `(a, b, c, d)` in world space.
+    /// Geometry at a position `p` where `a * p.x + b * p.y + c * p.z + d` is negative is clipped, ie. not rendered, by the built-in vertex shaders.
+    /// At most [Camera::MAX_CLIP_PLANES] planes are used.
+    ///
+    /// **Note:** Clip planes are not supported on web and on OpenGL ES without the `GL_EXT_clip_cull_distance` extension, in which case they are ignored.
+    pub clip_planes: Vec<Vec4>,
+    custom_projection: Option<Mat4>,
+    oblique_near_plane: Option<Vec4>,
 }
 
 impl Camera {
+    ///
+    /// The maximum number of [Camera::clip_planes] that are applied.
+    ///
+    pub const MAX_CLIP_PLANES: usize = 8;
+
     ///
     /// New camera which projects the world with an orthographic projection.
     ///
@@ -36,7 +61,13 @@ impl Camera {
                 viewport, position, target, up, height, z_near, z_far,
             ),
             tone_mapping: ToneMapping::default(),
+            exposure: 0.0,
+            white_point: None,
             color_mapping: ColorMapping::default(),
+            fog: Fog::None,
+            clip_planes: Vec::new(),
+            custom_projection: None,
+            oblique_near_plane: None,
         }
     }
 
@@ -63,10 +94,32 @@ impl Camera {
                 z_far,
             ),
             tone_mapping: ToneMapping::default(),
+            exposure: 0.0,
+            white_point: None,
             color_mapping: ColorMapping::default(),
+            fog: Fog::None,
+            clip_planes: Vec::new(),
+            custom_projection: None,
+            oblique_near_plane: None,
         }
     }
 
+    ///
+    /// New camera which projects the world with the given projection matrix, see [Camera::set_custom_projection].
+    ///
+    pub fn new_custom(
+        viewport: Viewport,
+        position: Vec3,
+        target: Vec3,
+        up: Vec3,
+        projection: Mat4,
+    ) -> Self {
+        let mut camera =
+            Self::new_perspective(viewport, position, target, up, degrees(45.0), 0.1, 1000.0);
+        camera.set_custom_projection(Some(projection));
+        camera
+    }
+
     ///
     /// Returns an orthographic camera for viewing 2D content.
     /// The camera is placed at the center of the given viewport.
@@ -93,6 +146,282 @@ impl Camera {
         )
     }
 
+    ///
+    /// Returns the 2D position at the given pixel in physical pixels relative to the bottom left corner of the viewport,
+    /// for example the position under the mouse cursor when rendering 2D content with an orthographic camera such as [Camera::new_2d].
+    ///
+    pub fn position_2d_at_pixel(&self, pixel: impl Into<PhysicalPoint>) -> Vec2 {
+        self.position_at_pixel(pixel).truncate()
+    }
+
+    ///
+    /// Returns the pixel in physical pixels relative to the bottom left corner of the viewport where the given 2D position is rendered
+    /// when rendering 2D content with an orthographic camera such as [Camera::new_2d].
+    ///
+    pub fn pixel_at_position_2d(&self, position: Vec2) -> PhysicalPoint {
+        self.pixel_at_position(position.extend(0.0))
+    }
+
+    ///
+    /// Returns the projection matrix of this camera, which is the custom projection if one is set using [Camera::set_custom_projection]
+    /// and includes the oblique near plane if one is set using [Camera::set_oblique_near_plane].
+    ///
+    pub fn projection(&self) -> Mat4 {
+        let projection = self.custom_projection.unwrap_or(*self.camera.projection());
+        let Some(plane) = self.oblique_near_plane else {
+            return projection;
+        };
+        // Eric Lengyel, "Oblique View Frustum Depth Projection and Clipping"
+        let plane = self.camera.view().invert().unwrap().transpose() * plane;
+        let corner =
+            projection.invert().unwrap() * vec4(plane.x.signum(), plane.y.signum(), 1.0, 1.0);
+        let clip = plane * (2.0 / plane.dot(corner));
+        let mut projection = projection;
+        projection.x.z = clip.x - projection.x.w;
+        projection.y.z = clip.y - projection.y.w;
+        projection.z.z = clip.z - projection.z.w;
+        projection.w.z = clip.w - projection.w.w;
+        projection
+    }
+
+    ///
+    /// Returns the frustum of this camera, which includes the oblique near plane if one is set using [Camera::set_oblique_near_plane].
+    /// Objects with a bounding box outside the frustum are not rendered, see [Frustum::intersects_aabb].
+    ///
+    pub fn frustum(&self) -> Frustum {
+        Frustum::new(self.projection() * self.camera.view())
+    }
+
+    ///
+    /// Returns the position in world space at the given pixel and the given depth in the range `[0, 1]`, where zero is the near plane and one is the far plane,
+    /// for example a value read from a depth texture rendered with this camera. The pixel must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport.
+    /// This works the same way for perspective and orthographic projections, contrary to [position_at_pixel](three_d_asset::Camera::position_at_pixel),
+    /// which returns the position of the camera for a perspective projection but a position on the near plane for an orthographic projection.
+    ///
+    pub fn unproject(&self, pixel: impl Into<PhysicalPoint>, depth: f32) -> Vec3 {
+        let uv = self.uv_coordinates_at_pixel(pixel.into());
+        let position = (self.projection() * self.camera.view()).invert().unwrap()
+            * vec4(2.0 * uv.u - 1.0, 2.0 * uv.v - 1.0, 2.0 * depth - 1.0, 1.0);
+        position.truncate() / position.w
+    }
+
+    ///
+    /// Returns the position of the viewer to use when calculating the lighting, for example the `camera_position` argument of the `calculate_lighting` shader function returned by [lights_shader_source](crate::lights_shader_source).
+    /// For a perspective projection, this is the position of the camera.
+    /// For an orthographic projection, all view rays are parallel, so this is a position far behind the camera, which makes the view direction the same for all surfaces
+    /// so specular highlights and reflections match the projection.
+    ///
+    pub fn lighting_position(&self) -> Vec3 {
+        match self.projection_type() {
+            three_d_asset::ProjectionType::Perspective { .. } => *self.position(),
+            three_d_asset::ProjectionType::Orthographic { .. } => {
+                self.position()
+                    - self.view_direction() * 1000.0 * (self.z_far() - self.z_near()).max(1.0)
+            }
+        }
+    }
+
+    ///
+    /// Zooms a camera with an orthographic projection by scaling the height of the view by the given factor, limited to the given minimum and maximum height,
+    /// while the given point stays at the same place in the view. A factor below one zooms in and a factor above one zooms out.
+    /// The camera is only moved sideways, so contrary to [zoom_towards](three_d_asset::Camera::zoom_towards), the zoom is not limited by the distance to the point
+    /// and the geometry in front of the camera is not clipped by the near plane when zooming in.
+    /// Nothing happens for a camera with a perspective projection.
+    ///
+    pub fn zoom_orthographic(
+        &mut self,
+        point: &Vec3,
+        factor: f32,
+        min_height: f32,
+        max_height: f32,
+    ) {
+        if let three_d_asset::ProjectionType::Orthographic { height } = *self.projection_type() {
+            let new_height = (height * factor).clamp(min_height, max_height);
+            let forward = self.view_direction();
+            let offset = point - self.position();
+            let sideways = offset - forward * offset.dot(forward);
+            self.translate(&(sideways * (1.0 - new_height / height)));
+            let (z_near, z_far) = (self.z_near(), self.z_far());
+            self.set_orthographic_projection(new_height, z_near, z_far);
+        }
+    }
+
+    ///
+    /// Moves the camera along its view direction and points it at the center of the given bounding box, so the box just fits within the view,
+    /// for example to zoom to a selection. The margin is the fraction of the view to keep free around the box, for example `0.1` for 10%.
+    /// The view direction and the up direction of the camera are unchanged.
+    /// For an orthographic camera, the height of the projection is changed to fit the box, otherwise the field of view is unchanged.
+    /// Nothing happens if the bounding box is empty or infinite.
+    ///
+    /// **Note:** The near and far planes are not changed, so the box might be clipped if it does not fit between them.
+    ///
+    pub fn fit(&mut self, aabb: &AxisAlignedBoundingBox, margin: f32) {
+        if aabb.is_empty() || aabb.is_infinite() {
+            return;
+        }
+        let center = aabb.center();
+        let forward = self.view_direction();
+        let right = self.right_direction().normalize();
+        let up = right.cross(forward);
+        let (min, max) = (aabb.min(), aabb.max());
+        // The corners of the box relative to the center in the coordinate system of the camera
+        let corners = [
+            vec3(min.x, min.y, min.z),
+            vec3(max.x, min.y, min.z),
+            vec3(min.x, max.y, min.z),
+            vec3(max.x, max.y, min.z),
+            vec3(min.x, min.y, max.z),
+            vec3(max.x, min.y, max.z),
+            vec3(min.x, max.y, max.z),
+            vec3(max.x, max.y, max.z),
+        ]
+        .map(|c| {
+            let d = c - center;
+            vec3(d.dot(right), d.dot(up), d.dot(forward))
+        });
+        let aspect = self.viewport().aspect();
+        let scale = 1.0 + margin;
+        let distance = match *self.projection_type() {
+            three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                let tan_y = (0.5 * field_of_view_y.0).tan() / scale;
+                let tan_x = tan_y * aspect;
+                // The distance where each corner is exactly at the edge of the view horizontally and vertically
+                corners
+                    .iter()
+                    .map(|c| (c.x.abs() / tan_x).max(c.y.abs() / tan_y) - c.z)
+                    .fold(0.0, f32::max)
+            }
+            three_d_asset::ProjectionType::Orthographic { .. } => {
+                let height = corners
+                    .iter()
+                    .map(|c| (2.0 * c.y.abs()).max(2.0 * c.x.abs() / aspect))
+                    .fold(0.0, f32::max)
+                    * scale;
+                let (z_near, z_far) = (self.z_near(), self.z_far());
+                self.set_orthographic_projection(height, z_near, z_far);
+                // Place the camera in front of the box, since the distance does not change the size
+                corners.iter().map(|c| -c.z).fold(0.0, f32::max) + z_near.max(0.0)
+            }
+        };
+        let up_direction = *self.up();
+        self.set_view(center - forward * distance, center, up_direction);
+    }
+
+    ///
+    /// Replaces the perspective or orthographic projection with the given projection matrix, or restores the perspective or orthographic projection if `None`.
+    /// This is for example needed for projection mapping and for rendering from a tracked head position onto a display or a wall of a CAVE, see [Camera::set_screen_projection].
+    /// The near and far planes and the field of view or height of the original projection are updated to match the given matrix as closely as possible,
+    /// so a projection with perspective, ie. where the last row of the matrix is `(0, 0, -1, 0)`, results in a perspective projection type and otherwise an orthographic projection type.
+    ///
+    /// **Note:** Only [Camera::projection], [Camera::frustum] and the methods of this camera, for example [Camera::unproject], use the custom projection, so for example
+    /// [position_at_pixel](three_d_asset::Camera::position_at_pixel) and [pixel_at_position](three_d_asset::Camera::pixel_at_position) still use the original projection.
+    /// Setting a new projection using for example [set_perspective_projection](three_d_asset::Camera::set_perspective_projection) does not remove the custom projection.
+    ///
+    pub fn set_custom_projection(&mut self, projection: Option<Mat4>) {
+        self.custom_projection = projection;
+        if let Some(projection) = projection {
+            let (a, b) = (projection.z.z, projection.w.z);
+            if projection.z.w != 0.0 {
+                let (z_near, z_far) = (b / (a - 1.0), b / (a + 1.0));
+                self.camera.set_perspective_projection(
+                    radians(2.0 * (1.0 / projection.y.y).atan()),
+                    z_near,
+                    z_far,
+                );
+            } else {
+                let (z_near, z_far) = ((b + 1.0) / a, (b - 1.0) / a);
+                self.camera
+                    .set_orthographic_projection(2.0 / projection.y.y, z_near, z_far);
+            }
+        }
+    }
+
+    ///
+    /// Returns the custom projection matrix set using [Camera::set_custom_projection], if any.
+    ///
+    pub fn custom_projection(&self) -> Option<Mat4> {
+        self.custom_projection
+    }
+
+    ///
+    /// Sets a perspective projection where the view is not necessarily centered on the view direction, given by the left, right, bottom and top edges
+    /// of the view on the near plane relative to the view direction, like `glFrustum` in OpenGL. See [Camera::set_custom_projection].
+    ///
+    pub fn set_off_axis_projection(
+        &mut self,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        z_near: f32,
+        z_far: f32,
+    ) {
+        self.set_custom_projection(Some(frustum(left, right, bottom, top, z_near, z_far)));
+    }
+
+    ///
+    /// Sets the view and projection so the camera sees through the rectangular screen with the given corners in world space from the given eye position,
+    /// for example a display where the position of the head of the viewer is tracked or a wall of a CAVE.
+    /// The camera looks perpendicular to the screen and uses an off-axis projection, see [Camera::set_off_axis_projection].
+    /// The corners must be given counterclockwise when seen from the eye, so the screen faces the eye.
+    ///
+    pub fn set_screen_projection(
+        &mut self,
+        eye: Vec3,
+        lower_left: Vec3,
+        lower_right: Vec3,
+        upper_left: Vec3,
+        z_near: f32,
+        z_far: f32,
+    ) {
+        // Robert Kooima, "Generalized Perspective Projection"
+        let right = (lower_right - lower_left).normalize();
+        let up = (upper_left - lower_left).normalize();
+        let normal = right.cross(up).normalize();
+        let to_lower_left = lower_left - eye;
+        let distance = -to_lower_left.dot(normal);
+        let scale = z_near / distance;
+        self.set_view(eye, eye - normal, up);
+        self.set_off_axis_projection(
+            right.dot(to_lower_left) * scale,
+            right.dot(lower_right - eye) * scale,
+            up.dot(to_lower_left) * scale,
+            up.dot(upper_left - eye) * scale,
+            z_near,
+            z_far,
+        );
+    }
+
+    ///
+    /// Replaces the near plane of the projection with the given plane `(a, b, c, d)` in world space, or restores the near plane if `None`,
+    /// so everything at a position `p` where `a * p.x + b * p.y + c * p.z + d` is negative is clipped.
+    /// This is for example used to avoid rendering the geometry below a mirror when rendering the reflection, see [PlanarReflection](crate::PlanarReflection).
+    /// Contrary to the [Camera::clip_planes], the clipping works on all platforms, but the far plane is also changed so distant geometry might be clipped.
+    /// The camera must be on the negative side of the plane.
+    ///
+    /// **Note:** Only [Camera::projection] and [Camera::frustum] include the oblique near plane, so for example [Camera::in_frustum](three_d_asset::Camera::in_frustum) still uses the original frustum.
+    ///
+    pub fn set_oblique_near_plane(&mut self, plane: Option<Vec4>) {
+        self.oblique_near_plane = plane;
+    }
+
+    ///
+    /// Sends the uniform data needed to apply the [Camera::tone_mapping] with the [Camera::exposure] and [Camera::white_point] of this camera to the fragment shader.
+    /// The fragment shader must include the source returned by [ToneMapping::fragment_shader_source].
+    ///
+    pub fn use_tone_mapping_uniforms(&self, program: &Program) {
+        self.tone_mapping
+            .use_uniforms_with_exposure(program, self.exposure, self.white_point);
+    }
+
+    ///
+    /// Sends the uniform data needed to apply the [Camera::fog] of this camera to the fragment shader.
+    /// The fragment shader must include the source returned by [Fog::fragment_shader_source].
+    ///
+    pub fn use_fog_uniforms(&self, program: &Program) {
+        self.fog.use_uniforms(program, *self.position());
+    }
+
     ///
     /// Disables the tone and color mapping so as to be ready for rendering into an intermediate render target with this camera.
     ///
@@ -0,0 +1,87 @@
+use crate::core::*;
+
+///
+/// Fog which is applied by the materials when shading each fragment, ie. the color of a surface is blended with the fog color depending on the distance from the camera.
+/// Since the fog is applied to each surface, also transparent surfaces are correctly fogged, unlike when using the [FogEffect](crate::FogEffect) which only uses the depth of the opaque objects.
+/// The fog is set for a scene using [Camera::fog](crate::Camera::fog) and the fog color is tone mapped together with the color of the surface.
+///
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Fog {
+    /// No fog.
+    #[default]
+    None,
+    /// Fog which increases linearly from no fog at the start distance to only fog at the end distance from the camera.
+    Linear {
+        /// The color of the fog.
+        color: Srgba,
+        /// The distance from the camera where the fog starts.
+        start: f32,
+        /// The distance from the camera where the fog completely hides the surfaces.
+        end: f32,
+    },
+    /// Fog where the visibility decreases exponentially with the distance from the camera multiplied by the density, ie. `exp(-density * distance)`.
+    Exponential {
+        /// The color of the fog.
+        color: Srgba,
+        /// The density of the fog.
+        density: f32,
+    },
+    /// Fog where the visibility decreases exponentially with the square of the distance from the camera multiplied by the density, ie. `exp(-(density * distance)^2)`,
+    /// which gives a clearer area close to the camera than [Fog::Exponential].
+    ExponentialSquared {
+        /// The color of the fog.
+        color: Srgba,
+        /// The density of the fog.
+        density: f32,
+    },
+}
+
+impl Fog {
+    ///
+    /// Returns the fragment shader source for applying the fog in a shader, which defines the function `vec3 fog(vec3 color, vec3 position)`
+    /// that blends the color of the surface at the given position in world space with the fog color.
+    /// It should be applied to the final color before the tone mapping.
+    ///
+    pub fn fragment_shader_source() -> &'static str {
+        "
+        uniform uint fogType;
+        uniform vec3 fogColor;
+        uniform vec3 fogParameters;
+        uniform vec3 fogCameraPosition;
+
+        vec3 fog(vec3 color, vec3 position) {
+            if (fogType == 0u) {
+                return color;
+            }
+            float dist = distance(position, fogCameraPosition);
+            float visibility = 1.0;
+            if (fogType == 1u) {
+                visibility = clamp((fogParameters.y - dist) / max(fogParameters.y - fogParameters.x, 0.0001), 0.0, 1.0);
+            } else if (fogType == 2u) {
+                visibility = exp(-fogParameters.z * dist);
+            } else if (fogType == 3u) {
+                float d = fogParameters.z * dist;
+                visibility = exp(-d * d);
+            }
+            return mix(fogColor, color, visibility);
+        }
+        "
+    }
+
+    ///
+    /// Sends the uniform data needed to apply this fog to the fragment shader, where the distance is measured from the given camera position.
+    /// Use [Camera::use_fog_uniforms](crate::Camera::use_fog_uniforms) to apply the fog specified in a camera.
+    ///
+    pub fn use_uniforms(&self, program: &Program, camera_position: Vec3) {
+        let (fog_type, color, parameters) = match *self {
+            Self::None => (0u32, Srgba::BLACK, vec3(0.0, 0.0, 0.0)),
+            Self::Linear { color, start, end } => (1, color, vec3(start, end, 0.0)),
+            Self::Exponential { color, density } => (2, color, vec3(0.0, 0.0, density)),
+            Self::ExponentialSquared { color, density } => (3, color, vec3(0.0, 0.0, density)),
+        };
+        program.use_uniform_if_required("fogType", fog_type);
+        program.use_uniform_if_required("fogColor", color.to_linear_srgb().truncate());
+        program.use_uniform_if_required("fogParameters", parameters);
+        program.use_uniform_if_required("fogCameraPosition", camera_position);
+    }
+}
@@ -3,6 +3,8 @@ use crate::core::*;
 ///
 /// Tone mapping is the process of mapping HDR color values computed with physical based rendering in the range `[0,∞)`
 /// into LDR values that can be displayed on the screen in the range `[0,1]`.
+/// Before the tone mapping curve is applied, the color is scaled by the [Camera::exposure](crate::Camera::exposure),
+/// and the curve is adjusted so that [Camera::white_point](crate::Camera::white_point) is mapped to white.
 ///
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
 pub enum ToneMapping {
@@ -24,13 +26,24 @@ impl ToneMapping {
     pub fn fragment_shader_source() -> &'static str {
         "
         uniform uint toneMappingType;
+        uniform float toneMappingExposure;
+        uniform float toneMappingWhitePoint;
 
         vec3 tone_mapping(vec3 color) {
+            if (toneMappingType != 0u) {
+                color *= exp2(toneMappingExposure);
+            }
             if (toneMappingType == 1u) {
-                color = color / (color + vec3(1.0));
+                if (toneMappingWhitePoint > 0.0) {
+                    color = color * (vec3(1.0) + color / (toneMappingWhitePoint * toneMappingWhitePoint)) / (color + vec3(1.0));
+                } else {
+                    color = color / (color + vec3(1.0));
+                }
                 color = clamp(color, 0.0, 1.0);
             } else if(toneMappingType == 2u) {
-                color = color*(2.51*color + .03) / (color*(2.43*color + .59) + .14);
+                vec4 x = vec4(color, toneMappingWhitePoint);
+                x = x*(2.51*x + .03) / (x*(2.43*x + .59) + .14);
+                color = toneMappingWhitePoint > 0.0 ? x.xyz / x.w : x.xyz;
                 color = clamp(color, 0.0, 1.0);
             } else if(toneMappingType == 3u) {
                 const float A = 0.15;
@@ -39,7 +52,7 @@ impl ToneMapping {
                 const float D = 0.20;
                 const float E = 0.02;
                 const float F = 0.30;
-                const float W = 11.2;
+                float W = toneMappingWhitePoint > 0.0 ? toneMappingWhitePoint : 11.2;
                 
                 vec4 x = vec4(color, W);
                 x = ((x*(A*x+C*B)+D*E)/(x*(A*x+B)+D*F))-E/F;
@@ -52,9 +65,21 @@ impl ToneMapping {
     }
 
     ///
-    /// Sends the uniform data needed to apply this tone mapping to the fragment shader.
+    /// Sends the uniform data needed to apply this tone mapping to the fragment shader, without any exposure adjustment and with the default white point.
+    /// Use [Camera::use_tone_mapping_uniforms](crate::Camera::use_tone_mapping_uniforms) to also apply the exposure and white point specified in a camera.
     ///
     pub fn use_uniforms(&self, program: &Program) {
+        self.use_uniforms_with_exposure(program, 0.0, None);
+    }
+
+    pub(crate) fn use_uniforms_with_exposure(
+        &self,
+        program: &Program,
+        exposure: f32,
+        white_point: Option<f32>,
+    ) {
         program.use_uniform("toneMappingType", *self as u32);
+        program.use_uniform("toneMappingExposure", exposure);
+        program.use_uniform("toneMappingWhitePoint", white_point.unwrap_or(0.0).max(0.0));
     }
 }
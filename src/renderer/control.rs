@@ -6,6 +6,10 @@ mod camera_control;
 #[doc(inline)]
 pub use camera_control::*;
 
+mod camera_controller;
+#[doc(inline)]
+pub use camera_controller::*;
+
 mod orbit_control;
 #[doc(inline)]
 pub use orbit_control::*;
@@ -18,6 +22,25 @@ mod fly_control;
 #[doc(inline)]
 pub use fly_control::*;
 
+mod pan_zoom_control;
+#[doc(inline)]
+pub use pan_zoom_control::*;
+
+mod gamepad_orbit_control;
+#[doc(inline)]
+pub use gamepad_orbit_control::*;
+
+mod gamepad;
+#[doc(inline)]
+pub use gamepad::*;
+
+mod clipboard;
+#[doc(inline)]
+pub use clipboard::*;
+
+mod movement;
+pub use movement::CollisionCallback;
+
 pub use three_d_asset::PixelPoint as PhysicalPoint;
 
 /// Type of mouse button.
@@ -80,6 +103,51 @@ pub enum Event {
         /// Whether or not this event already have been handled.
         handled: bool,
     },
+    /// Fired continuously when two fingers are moved on the screen.
+    /// Moving both fingers in the same direction pans, moving them apart or together pinches and twisting them rotates.
+    /// When only one finger is on the screen, the movement is reported as [Event::MouseMotion] with the left mouse button.
+    TouchGesture {
+        /// The screen position between the two fingers in physical pixels.
+        position: PhysicalPoint,
+        /// The movement of the position between the two fingers since the last [Event::TouchGesture] event in physical pixels, where positive y is up.
+        pan: (f32, f32),
+        /// The change in the distance between the two fingers since the last [Event::TouchGesture] event in physical pixels, which is positive when the fingers move apart.
+        pinch: f32,
+        /// The change in the angle of the line between the two fingers since the last [Event::TouchGesture] event in radians, which is positive when twisting counterclockwise.
+        rotation: f32,
+        /// The state of modifiers.
+        modifiers: Modifiers,
+        /// Whether or not this event already have been handled.
+        handled: bool,
+    },
+    /// Fired when a gamepad is connected. The state of the gamepad is available in `FrameInput::gamepads`.
+    GamepadConnected {
+        /// The id of the gamepad.
+        id: GamepadId,
+    },
+    /// Fired when a gamepad is disconnected.
+    GamepadDisconnected {
+        /// The id of the gamepad.
+        id: GamepadId,
+    },
+    /// Fired when a gamepad button is pressed.
+    GamepadButtonPress {
+        /// The id of the gamepad.
+        id: GamepadId,
+        /// The button.
+        button: GamepadButton,
+        /// Whether or not this event already have been handled.
+        handled: bool,
+    },
+    /// Fired when a gamepad button is released.
+    GamepadButtonRelease {
+        /// The id of the gamepad.
+        id: GamepadId,
+        /// The button.
+        button: GamepadButton,
+        /// Whether or not this event already have been handled.
+        handled: bool,
+    },
     /// Fired when the mouse enters the window.
     MouseEnter,
     /// Fired when the mouse leaves the window.
@@ -108,7 +176,21 @@ pub enum Event {
         modifiers: Modifiers,
     },
     /// Fires when some text has been written.
+    /// This includes the text composed using an input method editor (IME), for example Chinese, Japanese or Korean characters, when the composition is finished.
     Text(String),
+    /// Fired when an input method editor (IME) starts composing text, for example when typing Chinese, Japanese or Korean characters.
+    ImeCompositionStart,
+    /// Fired when the text that is being composed by an input method editor (IME) changes.
+    /// The text is not written yet, but should be shown at the text cursor, usually underlined, replacing the text from the previous [Event::ImeCompositionUpdate] event.
+    ImeCompositionUpdate {
+        /// The text that is being composed.
+        text: String,
+        /// The byte range of the cursor or selection in the text, if the cursor should be visible.
+        cursor: Option<(usize, usize)>,
+    },
+    /// Fired when an input method editor (IME) has finished composing text.
+    /// The composed text should no longer be shown; the written text, if any, is given here and is also reported as an [Event::Text] event right after this event.
+    ImeCompositionEnd(String),
 }
 
 /// Keyboard key input.
@@ -3,9 +3,10 @@ use crate::renderer::*;
 ///
 /// A set of possible actions to apply to a camera when recieving input.
 ///
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub enum CameraAction {
     /// No action.
+    #[default]
     None,
     /// Rotate the camera around the horizontal axis as seen from the camera.
     Pitch {
@@ -52,24 +53,19 @@ pub enum CameraAction {
         speed: f32,
     },
     /// Zooms towards the given target.
+    /// For a camera with an orthographic projection, the height of the view is scaled instead of moving the camera, see [Camera::zoom_orthographic].
     Zoom {
         /// The target of the zoom.
         target: Vec3,
         /// The speed of the zoom.
         speed: f32,
-        /// The minimum distance to the target.
+        /// The minimum distance to the target, or the minimum height of the view for an orthographic projection.
         min: f32,
-        /// The maximum distance to the target.
+        /// The maximum distance to the target, or the maximum height of the view for an orthographic projection.
         max: f32,
     },
 }
 
-impl std::default::Default for CameraAction {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 ///
 /// A customizable controller for the camera.
 /// It is possible to specify a [CameraAction] for each of the input events.
@@ -90,7 +86,7 @@ pub struct CameraControl {
     pub right_drag_vertical: CameraAction,
     /// Specifies what happens when scrolling horizontally.
     pub scroll_horizontal: CameraAction,
-    /// Specifies what happens when scrolling vertically.
+    /// Specifies what happens when scrolling vertically or pinching with two fingers (see [Event::TouchGesture]).
     pub scroll_vertical: CameraAction,
 }
 
@@ -107,35 +103,31 @@ impl CameraControl {
             match event {
                 Event::MouseMotion {
                     delta,
-                    button,
+                    button: Some(b),
                     handled,
                     ..
-                } => {
-                    if !*handled && button.is_some() {
-                        if let Some(b) = button {
-                            let (control_horizontal, control_vertical) = match b {
-                                MouseButton::Left => {
-                                    (self.left_drag_horizontal, self.left_drag_vertical)
-                                }
-                                MouseButton::Middle => {
-                                    (self.middle_drag_horizontal, self.middle_drag_vertical)
-                                }
-                                MouseButton::Right => {
-                                    (self.right_drag_horizontal, self.right_drag_vertical)
-                                }
-                            };
-                            *handled = self.handle_action(camera, control_horizontal, delta.0);
-                            *handled |= self.handle_action(camera, control_vertical, delta.1);
-                            change |= *handled;
+                } if !*handled => {
+                    let (control_horizontal, control_vertical) = match b {
+                        MouseButton::Left => (self.left_drag_horizontal, self.left_drag_vertical),
+                        MouseButton::Middle => {
+                            (self.middle_drag_horizontal, self.middle_drag_vertical)
                         }
-                    }
+                        MouseButton::Right => {
+                            (self.right_drag_horizontal, self.right_drag_vertical)
+                        }
+                    };
+                    *handled = self.handle_action(camera, control_horizontal, delta.0);
+                    *handled |= self.handle_action(camera, control_vertical, delta.1);
+                    change |= *handled;
+                }
+                Event::MouseWheel { delta, handled, .. } if !*handled => {
+                    *handled = self.handle_action(camera, self.scroll_horizontal, delta.0);
+                    *handled |= self.handle_action(camera, self.scroll_vertical, delta.1);
+                    change |= *handled;
                 }
-                Event::MouseWheel { delta, handled, .. } => {
-                    if !*handled {
-                        *handled = self.handle_action(camera, self.scroll_horizontal, delta.0);
-                        *handled |= self.handle_action(camera, self.scroll_vertical, delta.1);
-                        change |= *handled;
-                    }
+                Event::TouchGesture { pinch, handled, .. } if !*handled => {
+                    *handled = self.handle_action(camera, self.scroll_vertical, *pinch);
+                    change |= *handled;
                 }
                 _ => {}
             }
@@ -180,10 +172,23 @@ impl CameraControl {
                 min,
                 max,
             } => {
-                camera.zoom_towards(&target, speed * x, min, max);
+                zoom(camera, &target, speed * x, min, max);
             }
             CameraAction::None => {}
         }
         control_type != CameraAction::None
     }
 }
+
+///
+/// Zooms towards the target by the amount delta like [zoom_towards](three_d_asset::Camera::zoom_towards) for a perspective projection.
+/// For an orthographic projection, the height of the view is scaled by the same relative amount and limited to the minimum and maximum, see [Camera::zoom_orthographic].
+///
+pub(super) fn zoom(camera: &mut Camera, target: &Vec3, delta: f32, min: f32, max: f32) {
+    if let three_d_asset::ProjectionType::Orthographic { .. } = camera.projection_type() {
+        let distance = target.distance(*camera.position()).max(f32::EPSILON);
+        camera.zoom_orthographic(target, (-delta / distance).exp(), min, max);
+    } else {
+        camera.zoom_towards(target, delta, min, max);
+    }
+}
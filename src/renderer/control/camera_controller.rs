@@ -0,0 +1,245 @@
+use crate::renderer::*;
+use std::collections::HashMap;
+
+///
+/// How an animation progresses over time, see [CameraController::fly_to].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slow and ends fast.
+    EaseIn,
+    /// Starts fast and ends slow.
+    EaseOut,
+    /// Starts slow, speeds up and ends slow.
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    ///
+    /// Returns the progress of the animation in the range `[0, 1]` at the given fraction of the duration in the range `[0, 1]`.
+    ///
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - 0.5 * (2.0 - 2.0 * t).powi(3)
+                }
+            }
+        }
+    }
+}
+
+///
+/// A saved view of a camera, ie. the position, target and up direction, see [CameraController::save_bookmark].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraBookmark {
+    /// The position of the camera.
+    pub position: Vec3,
+    /// The target the camera looks at.
+    pub target: Vec3,
+    /// The up direction of the camera.
+    pub up: Vec3,
+}
+
+impl CameraBookmark {
+    ///
+    /// Returns the current view of the given camera.
+    ///
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self {
+            position: *camera.position(),
+            target: *camera.target(),
+            up: *camera.up(),
+        }
+    }
+
+    ///
+    /// Sets the view of the given camera to this view.
+    ///
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.set_view(self.position, self.target, self.up);
+    }
+
+    fn orientation(&self) -> Quat {
+        let forward = (self.target - self.position).normalize();
+        let right = forward.cross(self.up).normalize();
+        let up = right.cross(forward);
+        Quat::from(Mat3::from_cols(right, up, -forward))
+    }
+}
+
+struct CameraTransition {
+    from: CameraBookmark,
+    to: CameraBookmark,
+    duration: f64,
+    time: f64,
+    easing: Easing,
+}
+
+///
+/// Animates a camera smoothly from one view to another instead of snapping to the new view, see [CameraController::fly_to],
+/// and keeps named views of the camera which can be returned to later, see [CameraController::save_bookmark].
+///
+/// The orientation of the camera is interpolated using spherical linear interpolation, so the camera turns the shortest way
+/// without the problems of interpolating angles, while the position and the distance to the target are interpolated linearly.
+///
+#[derive(Default)]
+pub struct CameraController {
+    transition: Option<CameraTransition>,
+    bookmarks: HashMap<String, CameraBookmark>,
+}
+
+impl CameraController {
+    ///
+    /// Creates a new camera controller without any bookmarks.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Starts animating the camera from its current view to the view with the given position and target over the given duration in milliseconds,
+    /// keeping the current up direction of the camera. The animation is applied to the camera in [CameraController::update].
+    /// Any animation in progress is replaced.
+    ///
+    pub fn fly_to(
+        &mut self,
+        camera: &Camera,
+        position: Vec3,
+        target: Vec3,
+        duration: f64,
+        easing: Easing,
+    ) {
+        self.fly_to_view(
+            camera,
+            CameraBookmark {
+                position,
+                target,
+                up: *camera.up(),
+            },
+            duration,
+            easing,
+        );
+    }
+
+    ///
+    /// Starts animating the camera from its current view to the given view over the given duration in milliseconds, see [CameraController::fly_to].
+    ///
+    pub fn fly_to_view(
+        &mut self,
+        camera: &Camera,
+        view: CameraBookmark,
+        duration: f64,
+        easing: Easing,
+    ) {
+        self.transition = Some(CameraTransition {
+            from: CameraBookmark::from_camera(camera),
+            to: view,
+            duration,
+            time: 0.0,
+            easing,
+        });
+    }
+
+    ///
+    /// Starts animating the camera to the bookmark with the given name over the given duration in milliseconds, see [CameraController::fly_to].
+    /// Returns false if there is no bookmark with the given name.
+    ///
+    pub fn fly_to_bookmark(
+        &mut self,
+        camera: &Camera,
+        name: &str,
+        duration: f64,
+        easing: Easing,
+    ) -> bool {
+        if let Some(view) = self.bookmarks.get(name).copied() {
+            self.fly_to_view(camera, view, duration, easing);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Saves the current view of the given camera with the given name, which replaces any bookmark with the same name.
+    ///
+    pub fn save_bookmark(&mut self, name: impl Into<String>, camera: &Camera) {
+        self.bookmarks
+            .insert(name.into(), CameraBookmark::from_camera(camera));
+    }
+
+    ///
+    /// Returns the bookmark with the given name.
+    ///
+    pub fn bookmark(&self, name: &str) -> Option<&CameraBookmark> {
+        self.bookmarks.get(name)
+    }
+
+    ///
+    /// Removes and returns the bookmark with the given name.
+    ///
+    pub fn remove_bookmark(&mut self, name: &str) -> Option<CameraBookmark> {
+        self.bookmarks.remove(name)
+    }
+
+    ///
+    /// Returns the names and views of all bookmarks in arbitrary order.
+    ///
+    pub fn bookmarks(&self) -> impl Iterator<Item = (&str, &CameraBookmark)> {
+        self.bookmarks
+            .iter()
+            .map(|(name, view)| (name.as_str(), view))
+    }
+
+    ///
+    /// Returns whether an animation is in progress.
+    ///
+    pub fn is_animating(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    ///
+    /// Stops the animation in progress, leaving the camera where it is.
+    ///
+    pub fn stop(&mut self) {
+        self.transition = None;
+    }
+
+    ///
+    /// Advances the animation in progress by the given time in milliseconds since the last update, for example `FrameInput::elapsed_time`,
+    /// and updates the view of the camera. Returns whether the camera changed. Must be called each frame.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64) -> bool {
+        let Some(transition) = &mut self.transition else {
+            return false;
+        };
+        transition.time += elapsed_time;
+        if transition.time >= transition.duration {
+            transition.to.apply(camera);
+            self.transition = None;
+            return true;
+        }
+        let (from, to) = (transition.from, transition.to);
+        let t = transition
+            .easing
+            .apply((transition.time / transition.duration) as f32);
+        let orientation = from.orientation().slerp(to.orientation(), t);
+        let position = from.position.lerp(to.position, t);
+        let distance =
+            from.position.distance(from.target) * (1.0 - t) + to.position.distance(to.target) * t;
+        let forward = orientation * vec3(0.0, 0.0, -1.0);
+        let up = orientation * vec3(0.0, 1.0, 0.0);
+        camera.set_view(position, position + forward * distance, up);
+        true
+    }
+}
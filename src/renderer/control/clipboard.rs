@@ -0,0 +1,37 @@
+///
+/// A clipboard which text can be copied to and pasted from, for example used by the `GUI` (available with the `egui-gui` feature) when pressing Ctrl+C, Ctrl+X or Ctrl+V
+/// (Cmd on Mac) while editing text, but it can also be used by the application itself.
+///
+/// three-d does not access the clipboard of the operating system or browser itself, instead the default is a [MemoryClipboard]
+/// which only supports copying and pasting text within the application.
+/// To copy and paste between applications, implement this trait, for example using the [arboard](https://crates.io/crates/arboard) crate, and set it using `GUI::set_clipboard`.
+///
+pub trait Clipboard {
+    ///
+    /// Returns the text in the clipboard, if any.
+    ///
+    fn get_text(&mut self) -> Option<String>;
+
+    ///
+    /// Replaces the content of the clipboard with the given text.
+    ///
+    fn set_text(&mut self, text: &str);
+}
+
+///
+/// A [Clipboard] which stores the text in memory, ie. the text can only be copied and pasted within the application.
+///
+#[derive(Clone, Debug, Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = Some(text.to_string());
+    }
+}
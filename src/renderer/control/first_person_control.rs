@@ -1,10 +1,25 @@
+use super::movement::*;
 use crate::renderer::*;
 
 ///
-/// A control that makes the camera move like it is a person on the ground.
+/// A control that makes the camera move like it is a person on the ground, where the ground is orthogonal to the up direction of the camera.
+///
+/// Look around by dragging with the left mouse button, or by moving the mouse if [FirstPersonControl::mouse_look] is enabled.
+/// Move forward by scrolling or with the keyboard using W/A/S/D or the arrow keys, which moves along the ground regardless of whether the camera looks up or down.
+/// The keyboard movement is applied in [FirstPersonControl::update].
+/// Use [FirstPersonControl::set_collision_callback] to keep the camera at a fixed height above uneven ground or to stop at walls.
 ///
 pub struct FirstPersonControl {
     control: CameraControl,
+    keys: MovementKeys,
+    collision: Option<CollisionCallback>,
+    /// The speed of the movement with the keyboard in units per millisecond.
+    pub speed: f32,
+    /// The rotation in radians per physical pixel the mouse is moved when looking around.
+    pub sensitivity: f32,
+    /// Whether moving the mouse without pressing a button rotates the camera.
+    /// This is meant for when the cursor is hidden and locked to the window.
+    pub mouse_look: bool,
 }
 
 impl FirstPersonControl {
@@ -12,20 +27,86 @@ impl FirstPersonControl {
     pub fn new(speed: f32) -> Self {
         Self {
             control: CameraControl {
-                left_drag_horizontal: CameraAction::Yaw {
-                    speed: std::f32::consts::PI / 1800.0,
-                },
-                left_drag_vertical: CameraAction::Pitch {
-                    speed: std::f32::consts::PI / 1800.0,
-                },
                 scroll_vertical: CameraAction::Forward { speed },
                 ..Default::default()
             },
+            keys: MovementKeys::default(),
+            collision: None,
+            speed,
+            sensitivity: std::f32::consts::PI / 1800.0,
+            mouse_look: false,
         }
     }
 
+    ///
+    /// Sets a callback which is called whenever this control moves the camera, see [CollisionCallback].
+    ///
+    pub fn set_collision_callback(&mut self, callback: impl FnMut(Vec3, Vec3) -> Vec3 + 'static) {
+        self.collision = Some(Box::new(callback));
+    }
+
+    ///
+    /// Removes the callback set using [FirstPersonControl::set_collision_callback].
+    ///
+    pub fn clear_collision_callback(&mut self) {
+        self.collision = None;
+    }
+
     /// Handles the events. Must be called each frame.
     pub fn handle_events(&mut self, camera: &mut Camera, events: &mut [Event]) -> bool {
-        self.control.handle_events(camera, events)
+        let mut change = self.keys.handle_events(events);
+        for event in events.iter_mut() {
+            if let Event::MouseMotion {
+                delta,
+                button,
+                handled,
+                ..
+            } = event
+            {
+                let look = match button {
+                    Some(MouseButton::Left) => true,
+                    None => self.mouse_look,
+                    _ => false,
+                };
+                if look && !*handled {
+                    // Rotate around the up direction so the horizon stays level
+                    let up = camera.up().normalize();
+                    let position = *camera.position();
+                    let distance = camera.target().distance(position);
+                    let direction = Mat3::from_axis_angle(up, radians(self.sensitivity * delta.0))
+                        * camera.view_direction();
+                    camera.set_view(position, position + direction * distance, up);
+                    camera.pitch(radians(self.sensitivity * delta.1));
+                    *handled = true;
+                    change = true;
+                }
+            }
+        }
+        let from = *camera.position();
+        change |= self.control.handle_events(camera, events);
+        let to = *camera.position();
+        if from != to {
+            move_camera(camera, from, to, &mut self.collision);
+        }
+        change
+    }
+
+    ///
+    /// Moves the camera along the ground according to the keys held down, given the time in milliseconds since the last update, for example `FrameInput::elapsed_time`.
+    /// Must be called each frame.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64) -> bool {
+        let direction = self.keys.direction();
+        let up = camera.up().normalize();
+        let right = camera.right_direction();
+        let forward = up.cross(right);
+        let change = right * direction.x + forward * direction.z;
+        if change.magnitude2() < 0.5 {
+            return false;
+        }
+        let change = change.normalize() * self.speed * elapsed_time as f32;
+        let from = *camera.position();
+        move_camera(camera, from, from + change, &mut self.collision);
+        true
     }
 }
@@ -1,33 +1,115 @@
+use super::movement::*;
 use crate::renderer::*;
 
 ///
 /// A control that makes the camera fly through the 3D scene.
 ///
+/// Look around by dragging with the left mouse button, or by moving the mouse if [FlyControl::mouse_look] is enabled.
+/// Move forward by scrolling, sideways and up by dragging with the right mouse button,
+/// or with the keyboard using W/A/S/D or the arrow keys, Space or E to move up and Q to move down.
+/// The keyboard movement is applied in [FlyControl::update].
+///
 pub struct FlyControl {
     control: CameraControl,
+    keys: MovementKeys,
+    collision: Option<CollisionCallback>,
+    /// The speed of the movement with the keyboard in units per millisecond.
+    pub speed: f32,
+    /// The rotation in radians per physical pixel the mouse is moved when looking around.
+    pub sensitivity: f32,
+    /// Whether moving the mouse without pressing a button rotates the camera.
+    /// This is meant for when the cursor is hidden and locked to the window.
+    pub mouse_look: bool,
 }
 
 impl FlyControl {
     /// Creates a new fly control with the given speed of movements.
     pub fn new(speed: f32) -> Self {
+        let sensitivity = std::f32::consts::PI / 1800.0;
         Self {
             control: CameraControl {
-                left_drag_horizontal: CameraAction::Yaw {
-                    speed: std::f32::consts::PI / 1800.0,
-                },
-                left_drag_vertical: CameraAction::Pitch {
-                    speed: std::f32::consts::PI / 1800.0,
-                },
+                left_drag_horizontal: CameraAction::Yaw { speed: sensitivity },
+                left_drag_vertical: CameraAction::Pitch { speed: sensitivity },
                 scroll_vertical: CameraAction::Forward { speed },
                 right_drag_horizontal: CameraAction::Left { speed },
                 right_drag_vertical: CameraAction::Up { speed },
                 ..Default::default()
             },
+            keys: MovementKeys::default(),
+            collision: None,
+            speed,
+            sensitivity,
+            mouse_look: false,
         }
     }
 
+    ///
+    /// Sets a callback which is called whenever this control moves the camera, see [CollisionCallback].
+    ///
+    pub fn set_collision_callback(&mut self, callback: impl FnMut(Vec3, Vec3) -> Vec3 + 'static) {
+        self.collision = Some(Box::new(callback));
+    }
+
+    ///
+    /// Removes the callback set using [FlyControl::set_collision_callback].
+    ///
+    pub fn clear_collision_callback(&mut self) {
+        self.collision = None;
+    }
+
     /// Handles the events. Must be called each frame.
     pub fn handle_events(&mut self, camera: &mut Camera, events: &mut [Event]) -> bool {
-        self.control.handle_events(camera, events)
+        self.control.left_drag_horizontal = CameraAction::Yaw {
+            speed: self.sensitivity,
+        };
+        self.control.left_drag_vertical = CameraAction::Pitch {
+            speed: self.sensitivity,
+        };
+        let mut change = self.keys.handle_events(events);
+        if self.mouse_look {
+            for event in events.iter_mut() {
+                if let Event::MouseMotion {
+                    delta,
+                    button: None,
+                    handled,
+                    ..
+                } = event
+                {
+                    if !*handled {
+                        camera.yaw(radians(self.sensitivity * delta.0));
+                        camera.pitch(radians(self.sensitivity * delta.1));
+                        *handled = true;
+                        change = true;
+                    }
+                }
+            }
+        }
+        let from = *camera.position();
+        change |= self.control.handle_events(camera, events);
+        let to = *camera.position();
+        if from != to {
+            move_camera(camera, from, to, &mut self.collision);
+        }
+        change
+    }
+
+    ///
+    /// Moves the camera according to the keys held down, given the time in milliseconds since the last update, for example `FrameInput::elapsed_time`.
+    /// Must be called each frame.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64) -> bool {
+        let direction = self.keys.direction();
+        if direction == Vec3::zero() {
+            return false;
+        }
+        let right = camera.right_direction().normalize();
+        let forward = camera.view_direction();
+        let up = right.cross(forward);
+        let change = (right * direction.x + up * direction.y + forward * direction.z).normalize()
+            * self.speed
+            * elapsed_time as f32;
+        let from = *camera.position();
+        move_camera(camera, from, from + change, &mut self.collision);
+        true
     }
 }
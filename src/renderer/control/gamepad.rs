@@ -0,0 +1,156 @@
+///
+/// Identifies a connected gamepad.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub usize);
+
+/// Gamepad button input, named after the position on the gamepad.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Hash)]
+pub enum GamepadButton {
+    /// The bottom action button, for example A on an Xbox controller and Cross on a PlayStation controller.
+    South,
+    /// The right action button, for example B on an Xbox controller and Circle on a PlayStation controller.
+    East,
+    /// The top action button, for example Y on an Xbox controller and Triangle on a PlayStation controller.
+    North,
+    /// The left action button, for example X on an Xbox controller and Square on a PlayStation controller.
+    West,
+    /// The upper left shoulder button.
+    LeftBumper,
+    /// The upper right shoulder button.
+    RightBumper,
+    /// The lower left shoulder button, see also [GamepadAxis::LeftTrigger].
+    LeftTrigger,
+    /// The lower right shoulder button, see also [GamepadAxis::RightTrigger].
+    RightTrigger,
+    /// The left center button, for example Back or Share.
+    Select,
+    /// The right center button, for example Start or Options.
+    Start,
+    /// The center button with the logo of the vendor.
+    Mode,
+    /// Pressing the left stick.
+    LeftThumb,
+    /// Pressing the right stick.
+    RightThumb,
+    /// Up on the directional pad.
+    DPadUp,
+    /// Down on the directional pad.
+    DPadDown,
+    /// Left on the directional pad.
+    DPadLeft,
+    /// Right on the directional pad.
+    DPadRight,
+}
+
+impl GamepadButton {
+    const COUNT: usize = 17;
+}
+
+/// Gamepad axis input.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Hash)]
+pub enum GamepadAxis {
+    /// The horizontal position of the left stick in the range `[-1, 1]`, where positive is right.
+    LeftStickX,
+    /// The vertical position of the left stick in the range `[-1, 1]`, where positive is up.
+    LeftStickY,
+    /// The horizontal position of the right stick in the range `[-1, 1]`, where positive is right.
+    RightStickX,
+    /// The vertical position of the right stick in the range `[-1, 1]`, where positive is up.
+    RightStickY,
+    /// How far the lower left shoulder button is pressed in the range `[0, 1]`.
+    LeftTrigger,
+    /// How far the lower right shoulder button is pressed in the range `[0, 1]`.
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    const COUNT: usize = 6;
+}
+
+///
+/// The state of a connected gamepad, see `FrameInput::gamepads`.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gamepad {
+    /// The id of the gamepad, which is the same as long as the gamepad is connected.
+    pub id: GamepadId,
+    /// The name of the gamepad given by the operating system or browser.
+    pub name: String,
+    axes: [f32; GamepadAxis::COUNT],
+    buttons: [bool; GamepadButton::COUNT],
+}
+
+impl Gamepad {
+    ///
+    /// Creates the state of a gamepad with all axes at zero and no buttons pressed.
+    ///
+    pub fn new(id: GamepadId, name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            axes: [0.0; GamepadAxis::COUNT],
+            buttons: [false; GamepadButton::COUNT],
+        }
+    }
+
+    ///
+    /// Returns the value of the given axis.
+    ///
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    ///
+    /// Sets the value of the given axis.
+    ///
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        self.axes[axis as usize] = value;
+    }
+
+    ///
+    /// Returns whether the given button is pressed.
+    ///
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+
+    ///
+    /// Sets whether the given button is pressed.
+    ///
+    pub fn set_pressed(&mut self, button: GamepadButton, pressed: bool) {
+        self.buttons[button as usize] = pressed;
+    }
+
+    ///
+    /// Returns the position of the left stick, where the values within the given dead zone of the center are zero.
+    ///
+    pub fn left_stick(&self, dead_zone: f32) -> (f32, f32) {
+        dead_zoned(
+            self.axis(GamepadAxis::LeftStickX),
+            self.axis(GamepadAxis::LeftStickY),
+            dead_zone,
+        )
+    }
+
+    ///
+    /// Returns the position of the right stick, where the values within the given dead zone of the center are zero.
+    ///
+    pub fn right_stick(&self, dead_zone: f32) -> (f32, f32) {
+        dead_zoned(
+            self.axis(GamepadAxis::RightStickX),
+            self.axis(GamepadAxis::RightStickY),
+            dead_zone,
+        )
+    }
+}
+
+fn dead_zoned(x: f32, y: f32, dead_zone: f32) -> (f32, f32) {
+    let length = x.hypot(y);
+    if length <= dead_zone {
+        return (0.0, 0.0);
+    }
+    // Rescale so the value increases smoothly from zero at the edge of the dead zone
+    let scale = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) / length;
+    (x * scale, y * scale)
+}
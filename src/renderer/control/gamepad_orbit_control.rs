@@ -0,0 +1,75 @@
+use super::camera_control::zoom;
+use crate::renderer::*;
+
+///
+/// A control that makes the camera orbit around a target using a gamepad, see `FrameInput::gamepads`.
+/// The left stick orbits around the target and the right stick or the triggers zoom in and out.
+///
+/// For a camera with an orthographic projection, zooming scales the height of the view instead of moving the camera, see [OrbitControl].
+///
+pub struct GamepadOrbitControl {
+    /// The target the camera orbits around.
+    pub target: Vec3,
+    /// The minimum distance to the target.
+    pub min_distance: f32,
+    /// The maximum distance to the target.
+    pub max_distance: f32,
+    /// The speed of the orbiting in radians per millisecond when a stick is fully pushed.
+    pub speed: f32,
+    /// The speed of the zoom as the fraction of the distance to the target per millisecond when a stick is fully pushed.
+    pub zoom_speed: f32,
+    /// The distance from the center of the sticks where the position of a stick is ignored, which avoids drift from worn sticks.
+    pub dead_zone: f32,
+}
+
+impl GamepadOrbitControl {
+    /// Creates a new gamepad orbit control with the given target and minimum and maximum distance to the target.
+    pub fn new(target: Vec3, min_distance: f32, max_distance: f32) -> Self {
+        Self {
+            target,
+            min_distance,
+            max_distance,
+            speed: 0.002,
+            zoom_speed: 0.001,
+            dead_zone: 0.15,
+        }
+    }
+
+    ///
+    /// Moves the camera according to the state of the given gamepads, given the time in milliseconds since the last update, for example `FrameInput::elapsed_time`.
+    /// Must be called each frame.
+    ///
+    pub fn handle_gamepads(
+        &mut self,
+        camera: &mut Camera,
+        gamepads: &[Gamepad],
+        elapsed_time: f64,
+    ) -> bool {
+        let elapsed_time = elapsed_time as f32;
+        let mut change = false;
+        for gamepad in gamepads {
+            let (x, y) = gamepad.left_stick(self.dead_zone);
+            if x != 0.0 || y != 0.0 {
+                // The rotation is given as a movement in world space at the distance of the target
+                let scale = self.speed * elapsed_time * self.target.distance(*camera.position());
+                camera.rotate_around_with_fixed_up(&self.target, x * scale, -y * scale);
+                change = true;
+            }
+            let zoom_input = gamepad.right_stick(self.dead_zone).1
+                + gamepad.axis(GamepadAxis::RightTrigger)
+                - gamepad.axis(GamepadAxis::LeftTrigger);
+            if zoom_input != 0.0 {
+                let distance = self.target.distance(*camera.position());
+                zoom(
+                    camera,
+                    &self.target,
+                    zoom_input * self.zoom_speed * distance * elapsed_time,
+                    self.min_distance,
+                    self.max_distance,
+                );
+                change = true;
+            }
+        }
+        change
+    }
+}
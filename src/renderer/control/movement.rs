@@ -0,0 +1,90 @@
+use crate::renderer::*;
+
+///
+/// Keeps track of the keys held down for moving with the keyboard,
+/// which is W/arrow up forward, S/arrow down backward, A/arrow left left, D/arrow right right, Space/E up and Q down.
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct MovementKeys {
+    forward: bool,
+    backward: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+}
+
+impl MovementKeys {
+    ///
+    /// Updates the keys held down. Key presses are marked as handled, key releases are always processed so no key is stuck.
+    ///
+    pub fn handle_events(&mut self, events: &mut [Event]) -> bool {
+        let mut change = false;
+        for event in events.iter_mut() {
+            match event {
+                Event::KeyPress { kind, handled, .. } if !*handled => {
+                    if let Some(key) = self.key(*kind) {
+                        *key = true;
+                        *handled = true;
+                        change = true;
+                    }
+                }
+                Event::KeyRelease { kind, .. } => {
+                    if let Some(key) = self.key(*kind) {
+                        change |= *key;
+                        *key = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        change
+    }
+
+    ///
+    /// Returns the direction to move in as seen from the camera, where x is right, y is up and z is forward.
+    ///
+    pub fn direction(&self) -> Vec3 {
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        vec3(
+            axis(self.right, self.left),
+            axis(self.up, self.down),
+            axis(self.forward, self.backward),
+        )
+    }
+
+    fn key(&mut self, key: Key) -> Option<&mut bool> {
+        match key {
+            Key::W | Key::ArrowUp => Some(&mut self.forward),
+            Key::S | Key::ArrowDown => Some(&mut self.backward),
+            Key::A | Key::ArrowLeft => Some(&mut self.left),
+            Key::D | Key::ArrowRight => Some(&mut self.right),
+            Key::Space | Key::E => Some(&mut self.up),
+            Key::Q => Some(&mut self.down),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Moves the camera to the given position, keeping the view direction, after asking the collision callback if one is given.
+///
+pub(super) fn move_camera(
+    camera: &mut Camera,
+    from: Vec3,
+    to: Vec3,
+    collision: &mut Option<CollisionCallback>,
+) {
+    let to = match collision {
+        Some(collision) => collision(from, to),
+        None => to,
+    };
+    let change = to - *camera.position();
+    camera.translate(&change);
+}
+
+///
+/// A callback given the current and the desired position of the camera, which returns the position the camera is moved to instead,
+/// for example the position where the camera hits a wall or the position on the ground below the desired position.
+///
+pub type CollisionCallback = Box<dyn FnMut(Vec3, Vec3) -> Vec3>;
@@ -1,8 +1,15 @@
+use super::camera_control::zoom;
 use crate::renderer::*;
 
 ///
 /// A control that makes the camera orbit around a target.
 ///
+/// Orbit by dragging with the left mouse button or one finger and zoom by scrolling.
+/// On a touch screen, pinching with two fingers zooms, dragging with two fingers pans the camera and the target and twisting two fingers rolls the camera.
+///
+/// For a camera with an orthographic projection, zooming scales the height of the view instead of moving the camera,
+/// and the minimum and maximum distance limit the height of the view instead.
+///
 pub struct OrbitControl {
     control: CameraControl,
 }
@@ -39,6 +46,95 @@ impl OrbitControl {
             let x = target.distance(*camera.position());
             *speed = 0.01 * x + 0.001;
         }
-        self.control.handle_events(camera, events)
+        let mut change = false;
+        for event in events.iter_mut() {
+            if let Event::TouchGesture {
+                position,
+                pan,
+                pinch,
+                rotation,
+                handled,
+                ..
+            } = event
+            {
+                if !*handled {
+                    self.handle_gesture(camera, *position, *pan, *pinch, *rotation);
+                    *handled = true;
+                    change = true;
+                }
+            }
+        }
+        change |= self.control.handle_events(camera, events);
+        change
+    }
+
+    ///
+    /// Returns the target the camera orbits around, which is moved when panning.
+    ///
+    pub fn target(&self) -> Vec3 {
+        match self.control.left_drag_horizontal {
+            CameraAction::OrbitLeft { target, .. } => target,
+            _ => unreachable!(),
+        }
+    }
+
+    ///
+    /// Sets the target the camera orbits around, for example the center of a selected object.
+    /// The camera is not changed, so use for example [Camera::fit] to also point the camera at the new target.
+    ///
+    pub fn set_target(&mut self, new_target: Vec3) {
+        for action in [
+            &mut self.control.left_drag_horizontal,
+            &mut self.control.left_drag_vertical,
+            &mut self.control.scroll_vertical,
+        ] {
+            match action {
+                CameraAction::OrbitLeft { target, .. }
+                | CameraAction::OrbitUp { target, .. }
+                | CameraAction::Zoom { target, .. } => *target = new_target,
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_gesture(
+        &mut self,
+        camera: &mut Camera,
+        position: PhysicalPoint,
+        pan: (f32, f32),
+        pinch: f32,
+        rotation: f32,
+    ) {
+        // Pan so the position under the fingers in the plane through the target follows the fingers
+        let plane = Plane::from_point_and_normal(self.target(), camera.view_direction());
+        let position_in_plane = |pixel: PhysicalPoint| {
+            let origin = camera.position_at_pixel(pixel);
+            let direction = camera.view_direction_at_pixel(pixel);
+            plane
+                .intersect_ray(origin, direction)
+                .map(|t| origin + direction * t)
+        };
+        let previous = PhysicalPoint {
+            x: position.x - pan.0,
+            y: position.y - pan.1,
+        };
+        if let (Some(from), Some(to)) = (position_in_plane(previous), position_in_plane(position)) {
+            let translation = from - to;
+            camera.translate(&translation);
+            self.set_target(self.target() + translation);
+        }
+
+        if let CameraAction::Zoom {
+            target,
+            speed,
+            min,
+            max,
+        } = self.control.scroll_vertical
+        {
+            zoom(camera, &target, speed * pinch, min, max);
+        }
+
+        // Rolling the camera the opposite way makes the scene follow the fingers
+        camera.roll(radians(-rotation));
     }
 }
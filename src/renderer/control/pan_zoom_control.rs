@@ -0,0 +1,86 @@
+use crate::renderer::*;
+
+///
+/// A control for viewing 2D content, for example maps, sketches or plots, using an orthographic camera such as [Camera::new_2d].
+/// Dragging with any mouse button or one finger pans, so the content under the cursor follows the cursor,
+/// and scrolling or pinching with two fingers zooms in and out around the cursor.
+///
+/// Zooming only works for a camera with an orthographic projection.
+///
+pub struct PanZoomControl {
+    last_position: Option<PhysicalPoint>,
+    /// The minimum height of the view in world units, which limits how far it is possible to zoom in.
+    pub min_height: f32,
+    /// The maximum height of the view in world units, which limits how far it is possible to zoom out.
+    pub max_height: f32,
+    /// How fast to zoom, the height of the view is scaled by `exp(-zoom_speed * delta)` for a scroll of `delta`.
+    pub zoom_speed: f32,
+}
+
+impl PanZoomControl {
+    /// Creates a new pan and zoom control with the given minimum and maximum height of the view in world units.
+    pub fn new(min_height: f32, max_height: f32) -> Self {
+        Self {
+            last_position: None,
+            min_height,
+            max_height,
+            zoom_speed: 0.005,
+        }
+    }
+
+    /// Handles the events. Must be called each frame.
+    pub fn handle_events(&mut self, camera: &mut Camera, events: &mut [Event]) -> bool {
+        let mut change = false;
+        for event in events.iter_mut() {
+            match event {
+                Event::MousePress { position, .. } => {
+                    self.last_position = Some(*position);
+                }
+                Event::MouseMotion {
+                    button,
+                    position,
+                    handled,
+                    ..
+                } => {
+                    if let (Some(_), Some(last_position), false) =
+                        (button, self.last_position, *handled)
+                    {
+                        let translation = camera.position_at_pixel(last_position)
+                            - camera.position_at_pixel(*position);
+                        camera.translate(&translation);
+                        *handled = true;
+                        change = true;
+                    }
+                    self.last_position = Some(*position);
+                }
+                Event::MouseWheel {
+                    delta,
+                    position,
+                    handled,
+                    ..
+                } if !*handled => {
+                    if let three_d_asset::ProjectionType::Orthographic { height } =
+                        *camera.projection_type()
+                    {
+                        // Keep the position under the cursor fixed
+                        let point = camera.position_at_pixel(*position);
+                        camera.zoom_orthographic(
+                            &point,
+                            (-self.zoom_speed * delta.1).exp(),
+                            self.min_height,
+                            self.max_height,
+                        );
+                        if let three_d_asset::ProjectionType::Orthographic { height: new_height } =
+                            *camera.projection_type()
+                        {
+                            change |= new_height != height;
+                        }
+                        *handled = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        change
+    }
+}
@@ -0,0 +1,212 @@
+//!
+//! An overlay showing frame times and rendering statistics.
+//!
+
+use crate::renderer::*;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// The number of frames shown in the frame time graph.
+const GRAPH_FRAMES: usize = 120;
+
+///
+/// An overlay in the top left corner of the viewport showing the frames per second, the CPU and GPU time of each frame, a graph of the recent frame times
+/// and the [RenderStats] of the last frame, for example the number of draw calls and the estimated memory usage.
+/// It is rendered using a [Text] and 2D geometries, so it does not require a GUI library, but it requires a [FontAtlas].
+///
+/// Each frame, call [DebugOverlay::handle_events] and [DebugOverlay::update] before rendering the scene and [DebugOverlay::render] as the last thing after rendering the scene.
+/// The CPU time is the time from [DebugOverlay::update] to [DebugOverlay::render].
+/// The GPU time is the time the GPU spends executing the commands issued between two calls to [DebugOverlay::render], see [GpuTimer],
+/// and it is only shown if timer queries are supported.
+///
+pub struct DebugOverlay {
+    context: Context,
+    text: Text,
+    background: Gm<Rectangle, ColorMaterial>,
+    graph: Gm<Polyline, ColorMaterial>,
+    frame_times: VecDeque<f64>,
+    cpu_time: f64,
+    frame_start: Option<Instant>,
+    gpu_timer: Option<GpuTimer>,
+    time_since_text_update: f64,
+    /// Whether the overlay is shown.
+    pub visible: bool,
+    /// The key which shows and hides the overlay when pressed, see [DebugOverlay::handle_events]. The default is `F`.
+    pub toggle_key: Option<Key>,
+}
+
+impl DebugOverlay {
+    ///
+    /// Creates a new visible debug overlay which displays the text using the given font.
+    ///
+    pub fn new(context: &Context, font: Arc<FontAtlas>) -> Self {
+        let mut text = Text::new(
+            context,
+            font,
+            "",
+            14.0,
+            TextPlacement::Screen(PhysicalPoint { x: 0.0, y: 0.0 }),
+        );
+        text.color = Srgba::WHITE;
+        text.set_alignment(HorizontalAlignment::Left, VerticalAlignment::Top);
+        let overlay_states = RenderStates {
+            depth_test: DepthTest::Always,
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        };
+        Self {
+            context: context.clone(),
+            text,
+            background: Gm::new(
+                Rectangle::new(context, vec2(0.0, 0.0), degrees(0.0), 1.0, 1.0),
+                ColorMaterial {
+                    color: Srgba::new(0, 0, 0, 180),
+                    render_states: overlay_states,
+                    is_transparent: true,
+                    ..Default::default()
+                },
+            ),
+            graph: Gm::new(
+                Polyline::new(context, &[], 1.0),
+                ColorMaterial {
+                    color: Srgba::new(100, 220, 100, 255),
+                    render_states: overlay_states,
+                    ..Default::default()
+                },
+            ),
+            frame_times: VecDeque::with_capacity(GRAPH_FRAMES),
+            cpu_time: 0.0,
+            frame_start: None,
+            gpu_timer: GpuTimer::new(context),
+            time_since_text_update: f64::INFINITY,
+            visible: true,
+            toggle_key: Some(Key::F),
+        }
+    }
+
+    ///
+    /// Shows or hides the overlay if the [toggle key](Self::toggle_key) is pressed and the key press is not already handled.
+    /// Returns whether the overlay was shown or hidden.
+    ///
+    pub fn handle_events(&mut self, events: &mut [Event]) -> bool {
+        let mut change = false;
+        for event in events.iter_mut() {
+            if let Event::KeyPress { kind, handled, .. } = event {
+                if !*handled && Some(*kind) == self.toggle_key {
+                    self.visible = !self.visible;
+                    *handled = true;
+                    change = true;
+                }
+            }
+        }
+        change
+    }
+
+    ///
+    /// Updates the frame times with the time since the last frame in milliseconds, usually [FrameInput::elapsed_time](crate::window::FrameInput::elapsed_time).
+    /// Should be called each frame before rendering, also when the overlay is hidden.
+    ///
+    pub fn update(&mut self, elapsed_time_in_ms: f64) {
+        self.frame_start = Some(Instant::now());
+        if self.frame_times.len() == GRAPH_FRAMES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(elapsed_time_in_ms);
+        self.time_since_text_update += elapsed_time_in_ms;
+    }
+
+    ///
+    /// Renders the overlay into the top left corner of the given viewport of the render target, if it is [visible](Self::visible).
+    /// The size of the overlay is scaled by the given device pixel ratio.
+    ///
+    pub fn render(&mut self, target: &RenderTarget, viewport: Viewport, device_pixel_ratio: f32) {
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.end();
+        }
+        if let Some(frame_start) = self.frame_start.take() {
+            self.cpu_time = frame_start.elapsed().as_secs_f64() * 1000.0;
+        }
+        if self.visible {
+            self.render_overlay(target, viewport, device_pixel_ratio);
+        }
+        if let Some(gpu_timer) = &mut self.gpu_timer {
+            gpu_timer.begin();
+        }
+    }
+
+    fn render_overlay(&mut self, target: &RenderTarget, viewport: Viewport, scale: f32) {
+        // Update the text a few times per second so it is readable
+        if self.time_since_text_update > 250.0 {
+            self.time_since_text_update = 0.0;
+            let stats = self.context.render_stats();
+            let frame_time =
+                self.frame_times.iter().sum::<f64>() / self.frame_times.len().max(1) as f64;
+            let gpu_time = match self.gpu_timer.as_mut().and_then(|t| t.time()) {
+                Some(time) => format!("{:.2} ms", time),
+                None => "-".to_string(),
+            };
+            self.text.set_text(&format!(
+                "FPS: {:.0}\nFrame: {:.2} ms\nCPU: {:.2} ms\nGPU: {}\nDraw calls: {}\nTriangles: {}\nBuffers: {} ({:.1} MB)\nTextures: {} ({:.1} MB)",
+                1000.0 / frame_time.max(f64::EPSILON),
+                frame_time,
+                self.cpu_time,
+                gpu_time,
+                stats.draw_calls,
+                stats.triangles,
+                stats.buffers,
+                stats.buffer_memory as f64 / (1024.0 * 1024.0),
+                stats.textures,
+                stats.texture_memory as f64 / (1024.0 * 1024.0),
+            ));
+        }
+
+        let margin = 8.0 * scale;
+        let padding = 6.0 * scale;
+        let graph_width = 2.0 * GRAPH_FRAMES as f32 * scale;
+        let graph_height = 40.0 * scale;
+        self.text.size = 14.0 * scale;
+        let (text_min, text_max) = self.text.bounds();
+        let width = (text_max.x - text_min.x).max(graph_width) + 2.0 * padding;
+        let height = (text_max.y - text_min.y) + graph_height + 3.0 * padding;
+        let left = margin;
+        let top = viewport.height as f32 - margin;
+
+        self.text.placement = TextPlacement::Screen(PhysicalPoint {
+            x: left + padding,
+            y: top - padding,
+        });
+        self.background.set_size(width, height);
+        self.background
+            .set_center(vec2(left + 0.5 * width, top - 0.5 * height));
+
+        // The graph shows frame times up to 33 ms, or the longest frame time if it is longer
+        let max_time = self.frame_times.iter().fold(1000.0 / 30.0, |a, b| b.max(a));
+        let bottom = top - height + padding;
+        let points = self
+            .frame_times
+            .iter()
+            .enumerate()
+            .map(|(i, time)| {
+                vec3(
+                    left + padding + graph_width * i as f32 / GRAPH_FRAMES as f32,
+                    bottom + graph_height * (*time / max_time) as f32,
+                    0.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        self.graph.set_points(&points);
+        self.graph.width = scale;
+
+        let camera = Camera::new_2d(viewport);
+        target
+            .render(&camera, &self.background, &[])
+            .render(&camera, &self.graph, &[])
+            .render(&camera, &self.text, &[]);
+    }
+}
@@ -0,0 +1,219 @@
+//!
+//! Immediate mode drawing of lines for debugging.
+//!
+
+use crate::renderer::*;
+
+/// The number of segments used for each circle of a sphere.
+const CIRCLE_SEGMENTS: usize = 32;
+
+struct DebugBatch {
+    color: Srgba,
+    depth_test: bool,
+    segments: Vec<[Vec3; 2]>,
+}
+
+///
+/// Draws lines, boxes, spheres, axes and frusta for debugging, for example to visualize physics shapes, bounding boxes or the frusta of cameras and lights.
+///
+/// The shapes are drawn in immediate mode, ie. the `draw_` methods can be called anywhere during a frame without creating any geometries.
+/// The shapes are collected and rendered as lines in a few draw calls when [DebugRenderer::flush] is called, which also removes them, so they have to be drawn again each frame.
+///
+pub struct DebugRenderer {
+    context: Context,
+    batches: Vec<DebugBatch>,
+    lines: Vec<Gm<Polyline, ColorMaterial>>,
+    /// The width of the lines in physical pixels.
+    pub line_width: f32,
+    /// Whether the shapes drawn while this is true are hidden behind the scene, otherwise they are drawn on top of the scene. The default is `true`.
+    pub depth_test: bool,
+}
+
+impl DebugRenderer {
+    ///
+    /// Creates a new debug renderer with a line width of one pixel.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            batches: Vec::new(),
+            lines: Vec::new(),
+            line_width: 1.0,
+            depth_test: true,
+        }
+    }
+
+    ///
+    /// Draws a line between the two points.
+    ///
+    pub fn draw_line(&mut self, start: Vec3, end: Vec3, color: Srgba) {
+        self.segments(color).push([start, end]);
+    }
+
+    ///
+    /// Draws the edges of the axis aligned bounding box.
+    ///
+    pub fn draw_aabb(&mut self, aabb: &AxisAlignedBoundingBox, color: Srgba) {
+        if aabb.is_empty() {
+            return;
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        let corners = [
+            vec3(min.x, min.y, min.z),
+            vec3(max.x, min.y, min.z),
+            vec3(max.x, max.y, min.z),
+            vec3(min.x, max.y, min.z),
+            vec3(min.x, min.y, max.z),
+            vec3(max.x, min.y, max.z),
+            vec3(max.x, max.y, max.z),
+            vec3(min.x, max.y, max.z),
+        ];
+        self.draw_box_edges(&corners, color);
+    }
+
+    ///
+    /// Draws a sphere as three circles, one around each axis.
+    ///
+    pub fn draw_sphere(&mut self, center: Vec3, radius: f32, color: Srgba) {
+        for (u, v) in [
+            (Vec3::unit_x(), Vec3::unit_y()),
+            (Vec3::unit_y(), Vec3::unit_z()),
+            (Vec3::unit_z(), Vec3::unit_x()),
+        ] {
+            let point = |i: usize| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            let segments = self.segments(color);
+            for i in 0..CIRCLE_SEGMENTS {
+                segments.push([point(i), point(i + 1)]);
+            }
+        }
+    }
+
+    ///
+    /// Draws the x, y and z axes of the given transformation, ie. the local coordinate system of an object, in red, green and blue respectively.
+    /// The axes are drawn from the origin with the given length, which is scaled by the transformation.
+    ///
+    pub fn draw_axes(&mut self, transformation: &Mat4, length: f32) {
+        let origin = transformation.w.truncate();
+        for (axis, color) in [
+            (transformation.x, Srgba::RED),
+            (transformation.y, Srgba::GREEN),
+            (transformation.z, Srgba::BLUE),
+        ] {
+            self.draw_line(origin, origin + axis.truncate() * length, color);
+        }
+    }
+
+    ///
+    /// Draws the edges of the frustum defined by the given view projection matrix, for example `camera.projection() * camera.view()` for a [Camera].
+    ///
+    pub fn draw_frustum(&mut self, view_projection: &Mat4, color: Srgba) {
+        let Some(inverse) = view_projection.invert() else {
+            return;
+        };
+        let corners = [
+            vec3(-1.0, -1.0, -1.0),
+            vec3(1.0, -1.0, -1.0),
+            vec3(1.0, 1.0, -1.0),
+            vec3(-1.0, 1.0, -1.0),
+            vec3(-1.0, -1.0, 1.0),
+            vec3(1.0, -1.0, 1.0),
+            vec3(1.0, 1.0, 1.0),
+            vec3(-1.0, 1.0, 1.0),
+        ]
+        .map(|corner| {
+            let p = inverse * corner.extend(1.0);
+            p.truncate() / p.w
+        });
+        self.draw_box_edges(&corners, color);
+    }
+
+    ///
+    /// Renders all shapes drawn since the last flush into the render target using the given camera and removes them.
+    /// Should be called once per frame after rendering the scene, so the shapes drawn with [depth test](Self::depth_test) are hidden behind the scene.
+    ///
+    pub fn flush(&mut self, target: &RenderTarget, camera: &Camera) {
+        let batches = std::mem::take(&mut self.batches);
+        // Reuse the lines from the previous frames to avoid allocating new buffers each frame
+        while self.lines.len() < batches.len() {
+            self.lines.push(Gm::new(
+                Polyline::new(&self.context, &[], 1.0),
+                ColorMaterial::default(),
+            ));
+        }
+        for (line, batch) in self.lines.iter_mut().zip(batches.iter()) {
+            line.set_segments(&batch.segments);
+            line.width = self.line_width;
+            line.material.color = batch.color;
+            line.material.is_transparent = batch.color.a < 255;
+            line.material.render_states = RenderStates {
+                depth_test: if batch.depth_test {
+                    DepthTest::Less
+                } else {
+                    DepthTest::Always
+                },
+                write_mask: if batch.depth_test && batch.color.a == 255 {
+                    WriteMask::default()
+                } else {
+                    WriteMask::COLOR
+                },
+                blend: if batch.color.a < 255 {
+                    Blend::TRANSPARENCY
+                } else {
+                    Blend::Disabled
+                },
+                ..Default::default()
+            };
+        }
+        // Lines drawn on top of the scene are rendered last, so they are also on top of the lines with depth test
+        let (on_top, depth_tested): (Vec<_>, Vec<_>) = self
+            .lines
+            .iter()
+            .zip(batches.iter())
+            .partition(|(_, batch)| !batch.depth_test);
+        target
+            .render(camera, depth_tested.into_iter().map(|(line, _)| line), &[])
+            .render(camera, on_top.into_iter().map(|(line, _)| line), &[]);
+    }
+
+    ///
+    /// Removes all shapes drawn since the last flush without rendering them.
+    ///
+    pub fn clear(&mut self) {
+        self.batches.clear();
+    }
+
+    ///
+    /// Draws the 12 edges of a box given by the four corners of the bottom face followed by the four corners of the top face.
+    ///
+    fn draw_box_edges(&mut self, corners: &[Vec3; 8], color: Srgba) {
+        let segments = self.segments(color);
+        for i in 0..4 {
+            segments.push([corners[i], corners[(i + 1) % 4]]);
+            segments.push([corners[i + 4], corners[(i + 1) % 4 + 4]]);
+            segments.push([corners[i], corners[i + 4]]);
+        }
+    }
+
+    fn segments(&mut self, color: Srgba) -> &mut Vec<[Vec3; 2]> {
+        let depth_test = self.depth_test;
+        let index = match self
+            .batches
+            .iter()
+            .position(|batch| batch.color == color && batch.depth_test == depth_test)
+        {
+            Some(index) => index,
+            None => {
+                self.batches.push(DebugBatch {
+                    color,
+                    depth_test,
+                    segments: Vec::new(),
+                });
+                self.batches.len() - 1
+            }
+        };
+        &mut self.batches[index].segments
+    }
+}
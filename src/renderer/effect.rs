@@ -60,12 +60,66 @@ mod fxaa;
 #[doc(inline)]
 pub use fxaa::*;
 
+mod smaa;
+#[doc(inline)]
+pub use smaa::*;
+
 mod water;
 #[doc(inline)]
 pub use water::*;
 
+mod ssao;
+#[doc(inline)]
+pub use ssao::*;
+
+mod ssr;
+#[doc(inline)]
+pub use ssr::*;
+
+mod volumetric_fog;
+#[doc(inline)]
+pub use volumetric_fog::*;
+
+mod bloom;
+#[doc(inline)]
+pub use bloom::*;
+
+mod auto_exposure;
+#[doc(inline)]
+pub use auto_exposure::*;
+
+mod occlusion_culling;
+#[doc(inline)]
+pub use occlusion_culling::*;
+
+mod color_grading;
+#[doc(inline)]
+pub use color_grading::*;
+
+mod depth_of_field;
+#[doc(inline)]
+pub use depth_of_field::*;
+
+mod motion_blur;
+#[doc(inline)]
+pub use motion_blur::*;
+
+mod outline;
+#[doc(inline)]
+pub use outline::*;
+
+mod planar_reflection;
+#[doc(inline)]
+pub use planar_reflection::*;
+
+mod eye_dome_lighting;
+#[doc(inline)]
+pub use eye_dome_lighting::*;
+
 pub(crate) mod lighting_pass;
 
+pub(crate) mod order_independent_transparency;
+
 use crate::renderer::*;
 use std::ops::Deref;
 
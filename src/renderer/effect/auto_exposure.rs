@@ -0,0 +1,176 @@
+use crate::renderer::*;
+
+///
+/// Automatically adapts the [Camera::exposure] to the average brightness of the scene, like the eye or a camera adjusts to dark and bright surroundings.
+///
+/// Call [AutoExposure::update] each frame with the scene rendered into a high dynamic range texture, with the tone and color mapping disabled,
+/// and assign the returned exposure to the camera used when copying the texture to the screen with tone mapping, for example using [ScreenEffect].
+///
+#[derive(Clone, Copy, Debug)]
+pub struct AutoExposure {
+    /// The brightness the average brightness of the scene is mapped to. The default is middle gray (0.18).
+    pub key_value: f32,
+    /// The minimum exposure in stops.
+    pub min_exposure: f32,
+    /// The maximum exposure in stops.
+    pub max_exposure: f32,
+    /// How fast the exposure adapts to a change in brightness, a higher value gives a faster adaptation.
+    /// The exposure is moved roughly 63% towards the target exposure in `1 / adaptation_rate` seconds.
+    pub adaptation_rate: f32,
+    exposure: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            key_value: 0.18,
+            min_exposure: -10.0,
+            max_exposure: 10.0,
+            adaptation_rate: 1.5,
+            exposure: 0.0,
+        }
+    }
+}
+
+impl AutoExposure {
+    const SIZE: u32 = 64;
+
+    ///
+    /// Returns the current exposure.
+    ///
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    ///
+    /// Measures the average brightness of the given high dynamic range texture and moves the exposure towards the exposure that maps the average brightness to the [AutoExposure::key_value].
+    /// The elapsed time is the time in milliseconds since the last update, for example the elapsed time given in the frame input of a window render loop,
+    /// and a negative elapsed time sets the exposure directly without adaptation.
+    /// Returns the new exposure.
+    ///
+    /// **Note:** This reads a small texture back from the GPU each call, which forces the CPU to wait for the GPU to finish rendering.
+    ///
+    pub fn update(
+        &mut self,
+        context: &Context,
+        color_texture: &Texture2D,
+        elapsed_time: f64,
+    ) -> f32 {
+        let target_exposure = (self.key_value / Self::average_luminance(context, color_texture))
+            .log2()
+            .clamp(self.min_exposure, self.max_exposure);
+        if elapsed_time < 0.0 {
+            self.exposure = target_exposure;
+        } else {
+            let t = 1.0 - (-0.001 * elapsed_time as f32 * self.adaptation_rate).exp();
+            self.exposure += (target_exposure - self.exposure) * t;
+        }
+        self.exposure
+    }
+
+    fn average_luminance(context: &Context, color_texture: &Texture2D) -> f32 {
+        let mut texture = Texture2D::new_empty::<[f16; 4]>(
+            context,
+            Self::SIZE,
+            Self::SIZE,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let log_luminances = texture
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .apply_screen_effect(
+                &LogLuminanceEffect {
+                    sample_spacing: 1.0 / (4.0 * Self::SIZE as f32),
+                },
+                &Camera::new_2d(Viewport::new_at_origo(Self::SIZE, Self::SIZE)),
+                &[],
+                Some(ColorTexture::Single(color_texture)),
+                None,
+            )
+            .read::<[f32; 4]>();
+        let sum: f32 = log_luminances.iter().map(|l| l[0]).sum();
+        (sum / log_luminances.len() as f32).exp2()
+    }
+}
+
+///
+/// Computes the average of the logarithm of the luminance of 4x4 samples inside the area of the color texture covered by each pixel.
+///
+struct LogLuminanceEffect {
+    sample_spacing: f32,
+}
+
+impl Effect for LogLuminanceEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            uniform float sampleSpacing;
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                float result = 0.0;
+                for (int x = 0; x < 4; x++) {{
+                    for (int y = 0; y < 4; y++) {{
+                        vec2 uv = uvs + (vec2(float(x), float(y)) - 1.5) * sampleSpacing;
+                        vec3 color = sample_color(uv).rgb;
+                        result += log2(dot(color, vec3(0.2126, 0.7152, 0.0722)) + 0.0001);
+                    }}
+                }}
+                outColor = vec4(result / 16.0, 0.0, 0.0, 1.0);
+            }}
+            ",
+            color_texture
+                .expect("Must supply a color texture to compute the luminance")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1001u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to compute the luminance")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to compute the luminance")
+            .use_uniforms(program);
+        program.use_uniform("sampleSpacing", self.sample_spacing);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
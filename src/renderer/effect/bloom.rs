@@ -0,0 +1,309 @@
+use crate::renderer::*;
+
+///
+/// A bloom effect which makes bright areas, for example emissive materials and strong specular highlights, glow by spreading their light to the surrounding pixels.
+///
+/// The effect needs the scene rendered with high dynamic range, ie. into a floating point texture with the tone and color mapping disabled (see [Camera::disable_tone_and_color_mapping]),
+/// since only colors brighter than the [BloomEffect::threshold] contribute to the bloom.
+/// Use [BloomEffect::apply] to add the bloom to the scene texture and then copy it to the screen using the [ScreenEffect], which applies the tone and color mapping.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct BloomEffect {
+    /// Colors with a brightness above this value contribute to the bloom. The transition is smooth, so colors slightly below the threshold contribute a little.
+    pub threshold: f32,
+    /// The strength of the bloom added to the scene.
+    pub intensity: f32,
+    /// The spread of the bloom in each upsampling step, a value of one gives a smooth bloom while higher values spread the light further at the risk of artifacts.
+    pub radius: f32,
+    /// The number of times the scene is downsampled to half the size. More steps spread the light over a larger part of the screen.
+    pub mip_count: u32,
+}
+
+impl Default for BloomEffect {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            intensity: 0.5,
+            radius: 1.0,
+            mip_count: 5,
+        }
+    }
+}
+
+impl BloomEffect {
+    ///
+    /// Computes the bloom of the given high dynamic range scene texture and adds it to the same texture.
+    /// The scene is first reduced to the bright parts and downsampled to a chain of smaller textures which are then upsampled and combined, before the result is added to the scene.
+    ///
+    pub fn apply(&self, context: &Context, color_texture: &mut Texture2D) {
+        let mut mips: Vec<Texture2D> = Vec::new();
+        let (mut width, mut height) = (color_texture.width(), color_texture.height());
+        for _ in 0..self.mip_count.max(1) {
+            if width < 2 || height < 2 {
+                break;
+            }
+            width /= 2;
+            height /= 2;
+            mips.push(Texture2D::new_empty::<[f16; 4]>(
+                context,
+                width,
+                height,
+                Interpolation::Linear,
+                Interpolation::Linear,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ));
+        }
+        if mips.is_empty() {
+            return;
+        }
+
+        // Downsample
+        for i in 0..mips.len() {
+            let (done, rest) = mips.split_at_mut(i);
+            let target = &mut rest[0];
+            let source = done.last().unwrap_or(&*color_texture);
+            let camera = Camera::new_2d(Viewport::new_at_origo(target.width(), target.height()));
+            target
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+                .apply_screen_effect(
+                    &BloomDownsampleEffect {
+                        threshold: if i == 0 { Some(self.threshold) } else { None },
+                        texel_size: texel_size(source),
+                    },
+                    &camera,
+                    &[],
+                    Some(ColorTexture::Single(source)),
+                    None,
+                );
+        }
+
+        // Upsample and combine
+        for i in (1..mips.len()).rev() {
+            let (targets, sources) = mips.split_at_mut(i);
+            let source = &sources[0];
+            let target = &mut targets[i - 1];
+            let camera = Camera::new_2d(Viewport::new_at_origo(target.width(), target.height()));
+            target.as_color_target(None).apply_screen_effect(
+                &BloomUpsampleEffect {
+                    intensity: 1.0,
+                    radius: self.radius,
+                    texel_size: texel_size(source),
+                },
+                &camera,
+                &[],
+                Some(ColorTexture::Single(source)),
+                None,
+            );
+        }
+        let camera = Camera::new_2d(Viewport::new_at_origo(
+            color_texture.width(),
+            color_texture.height(),
+        ));
+        color_texture.as_color_target(None).apply_screen_effect(
+            &BloomUpsampleEffect {
+                intensity: self.intensity,
+                radius: self.radius,
+                texel_size: texel_size(&mips[0]),
+            },
+            &camera,
+            &[],
+            Some(ColorTexture::Single(&mips[0])),
+            None,
+        );
+    }
+}
+
+fn texel_size(texture: &Texture2D) -> Vec2 {
+    vec2(1.0 / texture.width() as f32, 1.0 / texture.height() as f32)
+}
+
+///
+/// Downsamples to half the size using a 13 tap filter, optionally keeping only the colors above the threshold.
+///
+struct BloomDownsampleEffect {
+    threshold: Option<f32>,
+    texel_size: Vec2,
+}
+
+impl Effect for BloomDownsampleEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}
+            uniform vec2 texelSize;
+            uniform float threshold;
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            vec3 sample_offset(float x, float y)
+            {{
+                return sample_color(uvs + vec2(x, y) * texelSize).rgb;
+            }}
+
+            void main()
+            {{
+                vec3 color = sample_offset(0.0, 0.0) * 0.125
+                    + (sample_offset(-2.0, 2.0) + sample_offset(2.0, 2.0) + sample_offset(-2.0, -2.0) + sample_offset(2.0, -2.0)) * 0.03125
+                    + (sample_offset(0.0, 2.0) + sample_offset(-2.0, 0.0) + sample_offset(2.0, 0.0) + sample_offset(0.0, -2.0)) * 0.0625
+                    + (sample_offset(-1.0, 1.0) + sample_offset(1.0, 1.0) + sample_offset(-1.0, -1.0) + sample_offset(1.0, -1.0)) * 0.125;
+            #ifdef USE_THRESHOLD
+                // Soft threshold with a knee of half the threshold
+                float brightness = max(color.r, max(color.g, color.b));
+                float knee = 0.5 * threshold;
+                float soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+                soft = soft * soft / (4.0 * knee + 0.00001);
+                color *= max(soft, brightness - threshold) / max(brightness, 0.00001);
+                // Avoids fireflies from very bright pixels
+                color = min(color, vec3(1000.0));
+            #endif
+                outColor = vec4(color, 1.0);
+            }}
+            ",
+            if self.threshold.is_some() {
+                "#define USE_THRESHOLD"
+            } else {
+                ""
+            },
+            color_texture
+                .expect("Must supply a color texture to downsample")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | if self.threshold.is_some() {
+                0b111u16 << 7
+            } else {
+                0b110u16 << 7
+            }
+            | color_texture
+                .expect("Must supply a color texture to downsample")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to downsample")
+            .use_uniforms(program);
+        program.use_uniform("texelSize", self.texel_size);
+        if let Some(threshold) = self.threshold {
+            program.use_uniform("threshold", threshold);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Upsamples using a 3x3 tent filter and adds the result to the render target.
+///
+struct BloomUpsampleEffect {
+    intensity: f32,
+    radius: f32,
+    texel_size: Vec2,
+}
+
+impl Effect for BloomUpsampleEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            uniform vec2 texelSize;
+            uniform float radius;
+            uniform float intensity;
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            vec3 sample_offset(float x, float y)
+            {{
+                return sample_color(uvs + vec2(x, y) * radius * texelSize).rgb;
+            }}
+
+            void main()
+            {{
+                vec3 color = sample_offset(0.0, 0.0) * 4.0
+                    + (sample_offset(0.0, 1.0) + sample_offset(-1.0, 0.0) + sample_offset(1.0, 0.0) + sample_offset(0.0, -1.0)) * 2.0
+                    + sample_offset(-1.0, 1.0) + sample_offset(1.0, 1.0) + sample_offset(-1.0, -1.0) + sample_offset(1.0, -1.0);
+                outColor = vec4(intensity * color / 16.0, 0.0);
+            }}
+            ",
+            color_texture
+                .expect("Must supply a color texture to upsample")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1000u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to upsample")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to upsample")
+            .use_uniforms(program);
+        program.use_uniform("texelSize", self.texel_size);
+        program.use_uniform("radius", self.radius);
+        program.use_uniform("intensity", self.intensity);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            blend: Blend::ADD,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A color grading effect which changes the colors of the rendered image using a 3D color lookup table (LUT),
+/// for example to give the image a warmer or colder look or to match the art direction of an application.
+///
+/// The effect should be applied when copying the rendered scene to the screen instead of the [ScreenEffect],
+/// since the lookup table is applied to the sRGB colors after the [Camera::tone_mapping] and before the [Camera::color_mapping].
+/// The lookup table can be created from a [CpuTexture3D] or from the content of a `.cube` file, which is the format exported by most color grading tools.
+///
+#[derive(Clone)]
+pub struct ColorGradingEffect {
+    /// The 3D color lookup table, the red channel is looked up along the width, the green channel along the height and the blue channel along the depth of the texture.
+    pub lut: Arc<Texture3D>,
+    /// The minimum input value of the lookup table, the input is mapped from the range given by this and [ColorGradingEffect::domain_max] to the range `[0..1]` before the lookup.
+    pub domain_min: Vec3,
+    /// The maximum input value of the lookup table.
+    pub domain_max: Vec3,
+    /// How much of the color grading is applied, zero gives the original colors and one gives the colors from the lookup table.
+    pub intensity: f32,
+}
+
+impl ColorGradingEffect {
+    ///
+    /// Creates a color grading effect with the given lookup table.
+    /// The texture must have the same size in all three dimensions and the texel at `(r, g, b)` contains the graded color of the sRGB color `(r, g, b) / (size - 1)`.
+    ///
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new(context: &Context, cpu_texture: &CpuTexture3D) -> Self {
+        Self {
+            lut: Arc::new(Texture3D::new(
+                context,
+                &CpuTexture3D {
+                    min_filter: Interpolation::Linear,
+                    mag_filter: Interpolation::Linear,
+                    mip_map_filter: None,
+                    wrap_s: Wrapping::ClampToEdge,
+                    wrap_t: Wrapping::ClampToEdge,
+                    wrap_r: Wrapping::ClampToEdge,
+                    ..cpu_texture.clone()
+                },
+            )),
+            domain_min: vec3(0.0, 0.0, 0.0),
+            domain_max: vec3(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        }
+    }
+
+    ///
+    /// Creates a color grading effect from the content of a `.cube` file containing a 3D lookup table.
+    /// The file can for example be loaded with `three_d_asset::io::load`, in which case the raw bytes of the loaded file must be converted to a string first.
+    ///
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn from_cube(context: &Context, cube: &str) -> Result<Self, RendererError> {
+        let mut size = None;
+        let mut domain_min = vec3(0.0, 0.0, 0.0);
+        let mut domain_max = vec3(1.0, 1.0, 1.0);
+        let mut data = Vec::new();
+        for line in cube.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+            match keyword {
+                "TITLE" => {}
+                "LUT_1D_SIZE" => {
+                    return Err(RendererError::InvalidColorLookupTable(
+                        "1D lookup tables are not supported".to_owned(),
+                    ))
+                }
+                "LUT_3D_SIZE" => {
+                    size = Some(
+                        words
+                            .next()
+                            .and_then(|s| s.parse::<u32>().ok())
+                            .ok_or_else(|| {
+                                RendererError::InvalidColorLookupTable(format!(
+                                    "invalid size '{}'",
+                                    line
+                                ))
+                            })?,
+                    );
+                }
+                "DOMAIN_MIN" => domain_min = parse_cube_vector(line, keyword, words)?,
+                "DOMAIN_MAX" => domain_max = parse_cube_vector(line, keyword, words)?,
+                _ => {
+                    let v = parse_cube_vector(line, "", line.split_whitespace())?;
+                    data.push([
+                        f16::from_f32(v.x),
+                        f16::from_f32(v.y),
+                        f16::from_f32(v.z),
+                        f16::ONE,
+                    ]);
+                }
+            }
+        }
+        let size = size.ok_or_else(|| {
+            RendererError::InvalidColorLookupTable("missing LUT_3D_SIZE".to_owned())
+        })?;
+        if data.len() != (size * size * size) as usize {
+            return Err(RendererError::InvalidColorLookupTable(format!(
+                "expected {} entries but found {}",
+                size * size * size,
+                data.len()
+            )));
+        }
+        let mut lut = Texture3D::new_empty::<[f16; 4]>(
+            context,
+            size,
+            size,
+            size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        lut.fill(&data);
+        Ok(Self {
+            lut: Arc::new(lut),
+            domain_min,
+            domain_max,
+            intensity: 1.0,
+        })
+    }
+}
+
+fn parse_cube_vector<'a>(
+    line: &str,
+    keyword: &str,
+    words: impl Iterator<Item = &'a str>,
+) -> Result<Vec3, RendererError> {
+    let values = words
+        .map(|w| w.parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|v| v.len() == 3)
+        .ok_or_else(|| {
+            RendererError::InvalidColorLookupTable(if keyword.is_empty() {
+                format!("invalid line '{}'", line)
+            } else {
+                format!("invalid {} '{}'", keyword, line)
+            })
+        })?;
+    Ok(vec3(values[0], values[1], values[2]))
+}
+
+impl Effect for ColorGradingEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}{}
+            uniform highp sampler3D lut;
+            uniform float lutSize;
+            uniform vec3 domainMin;
+            uniform vec3 domainMax;
+            uniform float intensity;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            vec3 linear_to_srgb(vec3 color)
+            {{
+                return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(vec3(0.0031308), color));
+            }}
+
+            vec3 srgb_to_linear(vec3 color)
+            {{
+                return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), step(vec3(0.04045), color));
+            }}
+
+            void main()
+            {{
+                vec4 color = sample_color(uvs);
+                vec3 srgb = linear_to_srgb(clamp(tone_mapping(color.rgb), 0.0, 1.0));
+                vec3 coordinate = clamp((srgb - domainMin) / (domainMax - domainMin), 0.0, 1.0);
+                coordinate = (coordinate * (lutSize - 1.0) + 0.5) / lutSize;
+                vec3 graded = mix(srgb, texture(lut, coordinate).rgb, intensity);
+                outColor = vec4(color_mapping(srgb_to_linear(clamp(graded, 0.0, 1.0))), color.a);
+            }}
+            ",
+            color_texture
+                .expect("Must supply a color texture to apply a color grading effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1010u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a color grading effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        color_texture
+            .expect("Must supply a color texture to apply a color grading effect")
+            .use_uniforms(program);
+        program.use_texture_3d("lut", &self.lut);
+        program.use_uniform("lutSize", self.lut.width() as f32);
+        program.use_uniform("domainMin", self.domain_min);
+        program.use_uniform("domainMax", self.domain_max);
+        program.use_uniform("intensity", self.intensity);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::renderer::*;
+
+///
+/// A depth of field effect which blurs the parts of the scene that are in front of or behind the focus distance, like a real camera lens with a finite aperture.
+/// The blur is calculated per pixel from the depth texture and the bright parts of out of focus areas are spread into round bokeh shapes.
+///
+/// Apply the effect when copying the scene to the screen, with the color and depth texture of the rendered scene, instead of the [ScreenEffect]
+/// since the tone and color mapping of the camera is applied after the blur.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct DepthOfFieldEffect {
+    /// The distance from the camera to the surfaces which are in focus.
+    pub focus_distance: f32,
+    /// The size of the lens aperture, a larger aperture gives a shallower depth of field.
+    /// With an aperture of one, surfaces at twice the focus distance are blurred with half the [DepthOfFieldEffect::max_blur_radius]
+    /// and surfaces infinitely far away are blurred with the maximum blur radius.
+    pub aperture: f32,
+    /// The maximum radius of the blur in pixels. The cost of the effect grows with the square of this radius.
+    pub max_blur_radius: f32,
+}
+
+impl Default for DepthOfFieldEffect {
+    fn default() -> Self {
+        Self {
+            focus_distance: 10.0,
+            aperture: 1.0,
+            max_blur_radius: 10.0,
+        }
+    }
+}
+
+impl Effect for DepthOfFieldEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            color_texture
+                .expect("Must supply a color texture to apply a depth of field effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a depth of field effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/depth_of_field_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1111u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a depth of field effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a depth of field effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a depth of field effect");
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a depth of field effect")
+            .use_uniforms(program);
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("eyePosition", camera.position());
+        program.use_uniform("zFar", camera.z_far());
+        program.use_uniform("focusDistance", self.focus_distance);
+        program.use_uniform("aperture", self.aperture);
+        program.use_uniform("maxBlurRadius", self.max_blur_radius);
+        program.use_uniform(
+            "texelSize",
+            vec2(
+                1.0 / color_texture.width() as f32,
+                1.0 / color_texture.height() as f32,
+            ),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::renderer::*;
+
+///
+/// Eye-dome lighting, a shading technique which improves the perception of depth by darkening the edges where the depth changes,
+/// which is especially useful for point clouds without normals, see [Points].
+/// The shading only depends on a depth texture, so it is applied on top of the rendered scene using [RenderTarget::apply_screen_effect]
+/// with the depth texture of the scene, which must not be attached to the render target.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct EyeDomeLightingEffect {
+    /// How much the edges are darkened.
+    pub strength: f32,
+    /// The distance in pixels to the neighbouring depth values which are compared with the depth of each pixel.
+    pub radius: f32,
+}
+
+impl Default for EyeDomeLightingEffect {
+    fn default() -> Self {
+        Self {
+            strength: 10.0,
+            radius: 1.5,
+        }
+    }
+}
+
+impl Effect for EyeDomeLightingEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            depth_texture
+                .expect("Must supply a depth texture to apply eye-dome lighting")
+                .fragment_shader_source(),
+            include_str!("shaders/eye_dome_lighting_effect.frag")
+        )
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b100010u16 << 7
+            | depth_texture
+                .expect("Must supply a depth texture to apply eye-dome lighting")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let depth_texture =
+            depth_texture.expect("Must supply a depth texture to apply eye-dome lighting");
+        depth_texture.use_uniforms(program);
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform(
+            "texelSize",
+            vec2(
+                1.0 / depth_texture.width() as f32,
+                1.0 / depth_texture.height() as f32,
+            ),
+        );
+        program.use_uniform("strength", self.strength);
+        program.use_uniform("radius", self.radius);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -2,6 +2,7 @@ use crate::renderer::*;
 
 ///
 /// An effect that simulates fog, ie. the area where it is applied gets hazy when objects are far away.
+/// The effect only uses the depth of the opaque objects, so use [Camera::fog] to also apply fog to transparent objects.
 ///
 #[derive(Clone, Debug)]
 pub struct FogEffect {
@@ -75,7 +76,7 @@ impl Effect for FogEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
+        camera.use_tone_mapping_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         color_texture
             .expect("Must supply a color texture to apply a fog effect")
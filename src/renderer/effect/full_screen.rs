@@ -77,7 +77,7 @@ impl Effect for ScreenEffect {
         depth_texture: Option<DepthTexture>,
     ) {
         if let Some(color_texture) = color_texture {
-            camera.tone_mapping.use_uniforms(program);
+            camera.use_tone_mapping_uniforms(program);
             camera.color_mapping.use_uniforms(program);
             color_texture.use_uniforms(program);
         }
@@ -92,6 +92,7 @@ impl Effect for ScreenEffect {
             cull: Cull::Back,
             write_mask: self.write_mask,
             blend: self.blend,
+            ..Default::default()
         }
     }
 }
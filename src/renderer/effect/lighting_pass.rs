@@ -1,31 +1,60 @@
 use crate::renderer::*;
 
-pub struct LightingPassEffect {}
+///
+/// The maximum number of lights evaluated in one lighting pass.
+/// If more lights are given, the lighting is accumulated over several passes.
+///
+pub const MAX_LIGHTS_PER_PASS: usize = 16;
 
-impl Effect for LightingPassEffect {
+#[derive(Default)]
+pub struct LightingPassEffect<'a> {
+    /// If true, only the lighting from the lights is written to the render target (no emission, tone mapping or color mapping), to be accumulated with additive blending.
+    pub accumulate: bool,
+    /// The lighting accumulated in previous passes which is added to the lighting in this pass.
+    pub accumulated_lighting: Option<&'a Texture2D>,
+}
+
+impl Effect for LightingPassEffect<'_> {
     fn fragment_shader_source(
         &self,
         lights: &[&dyn Light],
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) -> String {
-        let mut fragment_shader = lights_shader_source(
+        let mut fragment_shader = String::new();
+        if self.accumulate {
+            fragment_shader.push_str("#define ACCUMULATE\n");
+        }
+        if self.accumulated_lighting.is_some() {
+            fragment_shader.push_str("#define USE_ACCUMULATED_LIGHTING\n");
+        }
+        fragment_shader.push_str(&lights_shader_source(
             lights,
             LightingModel::Cook(
                 NormalDistributionFunction::TrowbridgeReitzGGX,
                 GeometryFunction::SmithSchlickGGX,
             ),
-        );
+        ));
         fragment_shader.push_str(&color_texture.unwrap().fragment_shader_source());
         fragment_shader.push_str(&depth_texture.unwrap().fragment_shader_source());
         fragment_shader.push_str(ToneMapping::fragment_shader_source());
+        fragment_shader.push_str(Fog::fragment_shader_source());
         fragment_shader.push_str(ColorMapping::fragment_shader_source());
         fragment_shader.push_str(include_str!("shaders/deferred_lighting.frag"));
         fragment_shader
     }
 
     fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
-        0b1u16 << 14 | 0b1u16 << 12 | color_texture.unwrap().id() | depth_texture.unwrap().id()
+        0b1u16 << 14
+            | 0b1u16 << 12
+            | if self.accumulate { 0b1u16 << 7 } else { 0 }
+            | if self.accumulated_lighting.is_some() {
+                0b1u16 << 8
+            } else {
+                0
+            }
+            | color_texture.unwrap().id()
+            | depth_texture.unwrap().id()
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
@@ -43,11 +72,15 @@ impl Effect for LightingPassEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
+        if !self.accumulate {
+            // The accumulated lighting is tone mapped in the final lighting pass
+            camera.use_tone_mapping_uniforms(program);
+        }
+        camera.use_fog_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         color_texture.unwrap().use_uniforms(program);
         depth_texture.unwrap().use_uniforms(program);
-        program.use_uniform_if_required("cameraPosition", camera.position());
+        program.use_uniform_if_required("cameraPosition", camera.lighting_position());
         for (i, light) in lights.iter().enumerate() {
             light.use_uniforms(program, i as u32);
         }
@@ -56,13 +89,26 @@ impl Effect for LightingPassEffect {
             (camera.projection() * camera.view()).invert().unwrap(),
         );
         program.use_uniform("debug_type", DebugType::None as i32);
+        if let Some(accumulated_lighting) = self.accumulated_lighting {
+            program.use_texture("accumulatedLighting", accumulated_lighting);
+        }
     }
 
     fn render_states(&self) -> RenderStates {
-        RenderStates {
-            depth_test: DepthTest::Always,
-            cull: Cull::Back,
-            ..Default::default()
+        if self.accumulate {
+            RenderStates {
+                depth_test: DepthTest::Always,
+                write_mask: WriteMask::COLOR,
+                blend: Blend::ADD,
+                cull: Cull::Back,
+                ..Default::default()
+            }
+        } else {
+            RenderStates {
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            }
         }
     }
 }
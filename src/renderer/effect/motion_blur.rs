@@ -0,0 +1,256 @@
+use crate::renderer::*;
+
+///
+/// A motion blur effect which blurs the scene in the direction things move on the screen between two frames,
+/// which makes fast movement look smooth instead of strobe-like, especially at low frame rates.
+///
+/// The blur from the movement of the camera is calculated from the depth texture and the camera in the previous frame.
+/// To also blur objects that move, render their velocity with [MotionBlurEffect::velocity_texture] and call [Mesh::update_previous_transformation]
+/// (or [Model::update_previous_transformations]) on the moving objects after each frame.
+///
+/// Use [MotionBlurEffect::apply] when copying the scene to the screen instead of the [ScreenEffect], since the tone and color mapping of the camera is applied after the blur.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct MotionBlurEffect {
+    /// The fraction of the time between two frames where the shutter of the camera is open, which scales the length of the blur.
+    /// The default is a half, which corresponds to the common 180 degree shutter of film cameras.
+    pub shutter: f32,
+    /// The maximum length of the blur in pixels.
+    pub max_blur_radius: f32,
+    /// The number of samples along the direction of the movement.
+    pub sample_count: u32,
+    previous_view_projection: Option<Mat4>,
+}
+
+impl Default for MotionBlurEffect {
+    fn default() -> Self {
+        Self {
+            shutter: 0.5,
+            max_blur_radius: 32.0,
+            sample_count: 12,
+            previous_view_projection: None,
+        }
+    }
+}
+
+impl MotionBlurEffect {
+    ///
+    /// Renders the movement on the screen of the given geometries since the previous frame into a texture with the same size as the viewport of the camera.
+    /// The movement of each [Mesh] is found from its transformation in the previous frame, see [Mesh::update_previous_transformation],
+    /// and other geometries are treated as not moving except for the movement of the camera.
+    /// Must be called before [MotionBlurEffect::apply] in each frame.
+    ///
+    pub fn velocity_texture(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+    ) -> Texture2D {
+        let viewport = camera.viewport();
+        let mut velocity_texture = Texture2D::new_empty::<[f16; 4]>(
+            context,
+            viewport.width,
+            viewport.height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            context,
+            viewport.width,
+            viewport.height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut camera = camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        RenderTarget::new(
+            velocity_texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .render_with_material(
+            &VelocityMaterial {
+                previous_view_projection: self.previous_view_projection(&camera),
+            },
+            &camera,
+            geometries,
+            &[],
+        );
+        velocity_texture
+    }
+
+    ///
+    /// Applies the motion blur to the given color texture and writes the result to the given render target.
+    /// The depth texture is used to find the movement from the camera, and the optional velocity texture from [MotionBlurEffect::velocity_texture] gives the movement of objects.
+    /// The camera is stored as the camera in the previous frame for the next time the effect is applied, so this should be called exactly once each frame.
+    ///
+    pub fn apply(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        depth_texture: DepthTexture,
+        velocity_texture: Option<&Texture2D>,
+    ) {
+        target.apply_screen_effect(
+            &MotionBlurPass {
+                effect: self,
+                previous_view_projection: self.previous_view_projection(camera),
+                velocity_texture,
+            },
+            camera,
+            &[],
+            Some(color_texture),
+            Some(depth_texture),
+        );
+        self.previous_view_projection = Some(camera.projection() * camera.view());
+    }
+
+    fn previous_view_projection(&self, camera: &Camera) -> Mat4 {
+        self.previous_view_projection
+            .unwrap_or_else(|| camera.projection() * camera.view())
+    }
+}
+
+struct MotionBlurPass<'a> {
+    effect: &'a MotionBlurEffect,
+    previous_view_projection: Mat4,
+    velocity_texture: Option<&'a Texture2D>,
+}
+
+impl Effect for MotionBlurPass<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}\n{}\n{}\n{}\n{}\n{}",
+            if self.velocity_texture.is_some() {
+                "#define USE_VELOCITY_TEXTURE\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            color_texture
+                .expect("Must supply a color texture to apply a motion blur effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a motion blur effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/motion_blur_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | if self.velocity_texture.is_some() {
+                0b10001u16 << 7
+            } else {
+                0b10000u16 << 7
+            }
+            | color_texture
+                .expect("Must supply a color texture to apply a motion blur effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a motion blur effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a motion blur effect");
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a motion blur effect")
+            .use_uniforms(program);
+        if let Some(velocity_texture) = self.velocity_texture {
+            program.use_texture("velocityMap", velocity_texture);
+        }
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("previousViewProjection", self.previous_view_projection);
+        program.use_uniform("shutter", self.effect.shutter);
+        program.use_uniform("maxBlurRadius", self.effect.max_blur_radius);
+        program.use_uniform("sampleCount", self.effect.sample_count.max(1) as i32);
+        program.use_uniform(
+            "texelSize",
+            vec2(
+                1.0 / color_texture.width() as f32,
+                1.0 / color_texture.height() as f32,
+            ),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Renders the movement on the screen since the previous frame in the red and green channel and one in the alpha channel.
+///
+struct VelocityMaterial {
+    previous_view_projection: Mat4,
+}
+
+impl Material for VelocityMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        include_str!("shaders/velocity_material.frag").to_owned()
+    }
+
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1010u32
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("currentViewProjection", camera.projection() * camera.view());
+        program.use_uniform("previousViewProjection", self.previous_view_projection);
+        // Geometries which know their previous transformation overwrite this
+        program.use_uniform("previousTransformation", Mat4::identity());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,280 @@
+use crate::renderer::*;
+
+///
+/// Skips rendering objects which are hidden behind other objects, called occluders, for example the buildings in a city.
+///
+/// Call [OcclusionCulling::update] each frame with the camera and the occluders, which renders the depth of the occluders into a small texture on the GPU
+/// and reads back a hierarchical depth buffer (depth pyramid), where each texel contains the largest depth of the texels it covers in the level below.
+/// Then use [OcclusionCulling::visible] to remove the objects hidden behind the occluders before rendering them, for example using [RenderTarget::render].
+/// An object is hidden if its bounding box is farther away than the occluders in all the pixels covered by the bounding box.
+///
+/// Only large objects should be used as occluders, since rendering the occluders is an extra cost.
+/// The culling is conservative, except that thin occluders can hide objects seen through gaps smaller than a texel of the depth buffer.
+///
+/// **Note:** This reads a small texture back from the GPU each update, which forces the CPU to wait for the GPU to finish rendering.
+///
+pub struct OcclusionCulling {
+    context: Context,
+    /// The width in texels of the depth buffer the occluders are rendered into. The default is 512.
+    pub resolution: u32,
+    /// The width in texels of the most detailed level of the depth pyramid, which is read back to the CPU. The default is 128.
+    pub pyramid_resolution: u32,
+    view_projection: Mat4,
+    levels: Vec<(u32, u32, Vec<f32>)>,
+}
+
+impl OcclusionCulling {
+    ///
+    /// Creates a new occlusion culling, where no objects are hidden until [OcclusionCulling::update] is called.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            resolution: 512,
+            pyramid_resolution: 128,
+            view_projection: Mat4::identity(),
+            levels: Vec::new(),
+        }
+    }
+
+    ///
+    /// Renders the depth of the given occluders seen from the given camera and builds the depth pyramid used to test whether objects are hidden behind the occluders.
+    ///
+    pub fn update(&mut self, camera: &Camera, occluders: impl IntoIterator<Item = impl Geometry>) {
+        let viewport = camera.viewport();
+        let aspect = viewport.height as f32 / viewport.width.max(1) as f32;
+        let size = |width: u32| (width.max(1), ((width as f32 * aspect).ceil() as u32).max(1));
+        let (width, height) = size(self.resolution);
+        let (pyramid_width, pyramid_height) = size(self.pyramid_resolution.min(self.resolution));
+
+        let mut depth_camera = camera.clone();
+        depth_camera.set_viewport(Viewport::new_at_origo(width, height));
+        let frustum = depth_camera.frustum();
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &self.context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let depth_material = DepthMaterial::new_depth_only();
+        depth_texture
+            .as_depth_target()
+            .clear(ClearState::depth(1.0))
+            .write::<RendererError>(|| {
+                for geometry in occluders
+                    .into_iter()
+                    .filter(|g| frustum.intersects_aabb(&g.aabb()))
+                {
+                    render_with_material(
+                        &self.context,
+                        &depth_camera,
+                        geometry,
+                        &depth_material,
+                        &[],
+                    );
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let mut texture = Texture2D::new_empty::<[f32; 4]>(
+            &self.context,
+            pyramid_width,
+            pyramid_height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let depths = texture
+            .as_color_target(None)
+            .clear(ClearState::color(1.0, 1.0, 1.0, 1.0))
+            .apply_screen_effect(
+                &MaxDepthEffect,
+                &Camera::new_2d(Viewport::new_at_origo(pyramid_width, pyramid_height)),
+                &[],
+                None,
+                Some(DepthTexture::Single(&depth_texture)),
+            )
+            .read::<[f32; 4]>();
+
+        // The rows are read from the top, so they are reversed to match the window coordinates
+        let mut level = depths
+            .chunks(pyramid_width as usize)
+            .rev()
+            .flat_map(|row| row.iter().map(|d| d[0]))
+            .collect::<Vec<_>>();
+        let (mut w, mut h) = (pyramid_width, pyramid_height);
+        self.levels.clear();
+        loop {
+            let (next_w, next_h) = (w.div_ceil(2), h.div_ceil(2));
+            let mut next = vec![0.0f32; (next_w * next_h) as usize];
+            for y in 0..h {
+                for x in 0..w {
+                    let i = ((y / 2) * next_w + x / 2) as usize;
+                    next[i] = next[i].max(level[(y * w + x) as usize]);
+                }
+            }
+            self.levels.push((w, h, level));
+            if w == 1 && h == 1 {
+                break;
+            }
+            (w, h, level) = (next_w, next_h, next);
+        }
+        self.view_projection = camera.projection() * camera.view();
+    }
+
+    ///
+    /// Returns whether the given bounding box is visible from the camera given in the last [OcclusionCulling::update], ie. whether it is inside the camera frustum and not hidden behind the occluders.
+    ///
+    pub fn is_visible(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        if self.levels.is_empty() || aabb.is_infinite() {
+            return true;
+        }
+        if aabb.is_empty() {
+            return false;
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        let mut ndc_min = vec3(f32::MAX, f32::MAX, f32::MAX);
+        let mut ndc_max = vec3(f32::MIN, f32::MIN, f32::MIN);
+        for i in 0..8 {
+            let corner = vec3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+            let p = self.view_projection * corner.extend(1.0);
+            if p.w <= 0.0 {
+                // The box intersects the plane of the camera
+                return true;
+            }
+            let p = p.truncate() / p.w;
+            ndc_min = vec3(ndc_min.x.min(p.x), ndc_min.y.min(p.y), ndc_min.z.min(p.z));
+            ndc_max = vec3(ndc_max.x.max(p.x), ndc_max.y.max(p.y), ndc_max.z.max(p.z));
+        }
+        if ndc_max.x < -1.0
+            || ndc_max.y < -1.0
+            || ndc_min.x > 1.0
+            || ndc_min.y > 1.0
+            || ndc_max.z < -1.0
+            || ndc_min.z > 1.0
+        {
+            return false;
+        }
+        if ndc_min.z <= -1.0 {
+            return true;
+        }
+        let depth = ndc_min.z * 0.5 + 0.5;
+
+        let (width, height, _) = &self.levels[0];
+        let texel = |ndc: f32, size: u32| {
+            (((ndc * 0.5 + 0.5) * size as f32).floor() as i64).clamp(0, size as i64 - 1) as u32
+        };
+        let (mut x0, mut x1) = (texel(ndc_min.x, *width), texel(ndc_max.x, *width));
+        let (mut y0, mut y1) = (texel(ndc_min.y, *height), texel(ndc_max.y, *height));
+        // Use the level where the box covers at most 2x2 texels
+        let mut level = 0;
+        while x1 - x0 > 1 || y1 - y0 > 1 {
+            (x0, x1, y0, y1) = (x0 / 2, x1 / 2, y0 / 2, y1 / 2);
+            level += 1;
+        }
+        let (w, _, depths) = &self.levels[level];
+        let mut occluder_depth = 0.0f32;
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                occluder_depth = occluder_depth.max(depths[(y * w + x) as usize]);
+            }
+        }
+        depth <= occluder_depth
+    }
+
+    ///
+    /// Returns the given objects except those which are not visible, see [OcclusionCulling::is_visible].
+    ///
+    pub fn visible<T: Geometry>(&self, objects: impl IntoIterator<Item = T>) -> Vec<T> {
+        objects
+            .into_iter()
+            .filter(|o| self.is_visible(&o.aabb()))
+            .collect()
+    }
+}
+
+///
+/// Computes the largest depth of the texels in the depth texture covered by each pixel.
+///
+struct MaxDepthEffect;
+
+impl Effect for MaxDepthEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            uniform ivec2 outputSize;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                ivec2 inputSize = textureSize(depthMap, 0);
+                ivec2 pixel = ivec2(gl_FragCoord.xy);
+                ivec2 start = (pixel * inputSize) / outputSize;
+                ivec2 end = min(((pixel + 1) * inputSize + outputSize - 1) / outputSize, inputSize);
+                float result = 0.0;
+                for (int y = start.y; y < end.y; y++) {{
+                    for (int x = start.x; x < end.x; x++) {{
+                        result = max(result, texelFetch(depthMap, ivec2(x, y), 0).x);
+                    }}
+                }}
+                outColor = vec4(result, 0.0, 0.0, 1.0);
+            }}
+            ",
+            depth_texture
+                .expect("Must supply a depth texture to compute the depth pyramid")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b100011u16 << 7
+            | depth_texture
+                .expect("Must supply a depth texture to compute the depth pyramid")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        depth_texture
+            .expect("Must supply a depth texture to compute the depth pyramid")
+            .use_uniforms(program);
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "outputSize",
+            Vector2::new(viewport.width as i32, viewport.height as i32),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::renderer::*;
+
+///
+/// Renders a transparent material into the accumulation and revealage layers used for weighted blended order independent transparency.
+/// The fragment shader of the material is used as it is and the output is then weighted by the depth.
+///
+pub struct WeightedBlendedMaterial<M: Material> {
+    pub material: M,
+}
+
+impl<M: Material> Material for WeightedBlendedMaterial<M> {
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        format!(
+            "#define main material_main\n{}\n#undef main\n{}",
+            self.material.fragment_shader_source(lights),
+            include_str!("shaders/weighted_blended_material.frag")
+        )
+    }
+
+    fn id(&self) -> u32 {
+        let id = self.material.id();
+        assert!(
+            id & (0b1u32 << 30) == 0,
+            "The id of a material rendered with order independent transparency must be smaller than 0b1u32 << 30"
+        );
+        id | 0b1u32 << 30
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.material.fragment_attributes()
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights)
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::ADD,
+            ..self.material.render_states()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+///
+/// Composites the accumulated transparent surfaces on top of the render target.
+///
+pub struct WeightedBlendedCompositeEffect {}
+
+impl Effect for WeightedBlendedCompositeEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}",
+            color_texture
+                .expect("Must supply the accumulated transparency")
+                .fragment_shader_source(),
+            include_str!("shaders/weighted_blended_composite.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b10010u16 << 7
+            | color_texture
+                .expect("Must supply the accumulated transparency")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply the accumulated transparency")
+            .use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
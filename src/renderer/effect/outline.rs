@@ -0,0 +1,361 @@
+use crate::renderer::*;
+
+///
+/// An effect which draws a colored outline around a set of geometries, for example to highlight the selected objects in an editor.
+/// The outline is drawn on top of the rest of the scene and is therefore also visible where the geometries are hidden behind other objects.
+///
+/// The geometries are rendered into a mask and the distance to the mask is found using the jump flooding algorithm,
+/// so the cost of the effect only grows with the logarithm of the width of the outline.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct OutlineEffect {
+    /// The color of the outline.
+    pub color: Srgba,
+    /// The width of the outline in pixels.
+    pub width: f32,
+}
+
+impl Default for OutlineEffect {
+    fn default() -> Self {
+        Self {
+            color: Srgba::new(255, 165, 0, 255),
+            width: 3.0,
+        }
+    }
+}
+
+impl OutlineEffect {
+    ///
+    /// Draws the outline of the given geometries as seen from the given camera on top of the content of the render target.
+    ///
+    pub fn apply(
+        &self,
+        target: &RenderTarget,
+        camera: &Camera,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+    ) {
+        let context = &target.context;
+        let viewport = Viewport::new_at_origo(camera.viewport().width, camera.viewport().height);
+        let mut mask_camera = camera.clone();
+        mask_camera.set_viewport(viewport);
+        mask_camera.disable_tone_and_color_mapping();
+        let pass_camera = Camera::new_2d(viewport);
+        let texel_size = vec2(1.0 / viewport.width as f32, 1.0 / viewport.height as f32);
+
+        let mut mask = Texture2D::new_empty::<[u8; 4]>(
+            context,
+            viewport.width,
+            viewport.height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            context,
+            viewport.width,
+            viewport.height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        RenderTarget::new(mask.as_color_target(None), depth_texture.as_depth_target())
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+            .render_with_material(
+                &ColorMaterial {
+                    color: Srgba::WHITE,
+                    ..Default::default()
+                },
+                &mask_camera,
+                geometries,
+                &[],
+            );
+
+        let new_seed_texture = || {
+            Texture2D::new_empty::<[f32; 4]>(
+                context,
+                viewport.width,
+                viewport.height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        let mut seeds = new_seed_texture();
+        seeds
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .apply_screen_effect(
+                &OutlineSeedEffect {},
+                &pass_camera,
+                &[],
+                Some(ColorTexture::Single(&mask)),
+                None,
+            );
+
+        let width = self.width.max(0.0);
+        let mut step = (width.ceil() as u32).max(1).next_power_of_two();
+        let mut next_seeds = new_seed_texture();
+        while step >= 1 {
+            next_seeds
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+                .apply_screen_effect(
+                    &JumpFloodEffect {
+                        offset: texel_size * step as f32,
+                    },
+                    &pass_camera,
+                    &[],
+                    Some(ColorTexture::Single(&seeds)),
+                    None,
+                );
+            std::mem::swap(&mut seeds, &mut next_seeds);
+            step /= 2;
+        }
+
+        target.apply_screen_effect(
+            &OutlineCompositeEffect {
+                color: self.color,
+                width,
+                mask: &mask,
+                resolution: vec2(viewport.width as f32, viewport.height as f32),
+            },
+            camera,
+            &[],
+            Some(ColorTexture::Single(&seeds)),
+            None,
+        );
+    }
+}
+
+fn screen_render_states() -> RenderStates {
+    RenderStates {
+        write_mask: WriteMask::COLOR,
+        depth_test: DepthTest::Always,
+        cull: Cull::Back,
+        ..Default::default()
+    }
+}
+
+///
+/// Writes the uv coordinates of each pixel inside the mask, which are the seeds of the jump flooding.
+///
+struct OutlineSeedEffect {}
+
+impl Effect for OutlineSeedEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                outColor = sample_color(uvs).a > 0.5 ? vec4(uvs, 0.0, 1.0) : vec4(0.0);
+            }}
+            ",
+            color_texture
+                .expect("Must supply a mask texture")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14 | 0b10011u16 << 7 | color_texture.expect("Must supply a mask texture").id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a mask texture")
+            .use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        screen_render_states()
+    }
+}
+
+///
+/// One step of the jump flooding algorithm, which keeps the closest of the seeds found at the given offset in each direction.
+///
+struct JumpFloodEffect {
+    offset: Vec2,
+}
+
+impl Effect for JumpFloodEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            uniform vec2 offset;
+            uniform vec2 resolution;
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                vec4 closest = vec4(0.0);
+                float closest_distance = 1.0e20;
+                for (int x = -1; x <= 1; x++) {{
+                    for (int y = -1; y <= 1; y++) {{
+                        vec4 seed = sample_color(uvs + vec2(float(x), float(y)) * offset);
+                        if (seed.a > 0.5) {{
+                            vec2 d = (seed.xy - uvs) * resolution;
+                            float distance_squared = dot(d, d);
+                            if (distance_squared < closest_distance) {{
+                                closest_distance = distance_squared;
+                                closest = seed;
+                            }}
+                        }}
+                    }}
+                }}
+                outColor = closest;
+            }}
+            ",
+            color_texture
+                .expect("Must supply a seed texture")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14 | 0b10100u16 << 7 | color_texture.expect("Must supply a seed texture").id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture = color_texture.expect("Must supply a seed texture");
+        color_texture.use_uniforms(program);
+        program.use_uniform("offset", self.offset);
+        program.use_uniform(
+            "resolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        screen_render_states()
+    }
+}
+
+///
+/// Draws the outline where the distance to the closest seed is less than the width and outside the mask.
+///
+struct OutlineCompositeEffect<'a> {
+    color: Srgba,
+    width: f32,
+    mask: &'a Texture2D,
+    resolution: Vec2,
+}
+
+impl Effect for OutlineCompositeEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}
+            uniform sampler2D maskMap;
+            uniform vec4 outlineColor;
+            uniform float outlineWidth;
+            uniform vec2 resolution;
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                vec4 seed = sample_color(uvs);
+                if (seed.a < 0.5 || texture(maskMap, uvs).a > 0.5) {{
+                    discard;
+                }}
+                float d = length((seed.xy - uvs) * resolution);
+                float alpha = clamp(outlineWidth - d + 0.5, 0.0, 1.0) * outlineColor.a;
+                if (alpha <= 0.0) {{
+                    discard;
+                }}
+                outColor = vec4(color_mapping(outlineColor.rgb), alpha);
+            }}
+            ",
+            color_texture
+                .expect("Must supply a seed texture")
+                .fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14 | 0b10101u16 << 7 | color_texture.expect("Must supply a seed texture").id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        camera.color_mapping.use_uniforms(program);
+        color_texture
+            .expect("Must supply a seed texture")
+            .use_uniforms(program);
+        program.use_texture("maskMap", self.mask);
+        program.use_uniform("outlineColor", self.color.to_linear_srgb());
+        program.use_uniform("outlineWidth", self.width);
+        program.use_uniform("resolution", self.resolution);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            blend: Blend::TRANSPARENCY,
+            ..screen_render_states()
+        }
+    }
+}
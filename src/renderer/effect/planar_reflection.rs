@@ -0,0 +1,161 @@
+use crate::renderer::*;
+
+///
+/// Renders the reflection of the scene in a plane, for example a mirror, a polished floor or a calm water surface, into a texture which can be used by a material.
+///
+/// The camera is mirrored about the plane and the scene is rendered with an oblique near plane (see [Camera::set_oblique_near_plane]),
+/// so the geometry on the back side of the plane is not visible in the reflection.
+/// A material rendering the reflecting surface must include [PlanarReflection::fragment_shader_source] in its fragment shader, call [PlanarReflection::use_uniforms]
+/// and then look up the reflection with the `sample_reflection` shader function.
+///
+pub struct PlanarReflection {
+    context: Context,
+    color_texture: Texture2D,
+    depth_texture: DepthTexture2D,
+    viewport: Viewport,
+    /// A point on the reflecting plane.
+    pub point: Vec3,
+    /// The normal of the reflecting plane which points to the side of the plane that is reflected.
+    pub normal: Vec3,
+    /// The distance along the normal from the plane to where the geometry is clipped in the reflection.
+    /// A small positive value avoids seams where geometry intersects the plane.
+    pub clip_offset: f32,
+}
+
+impl PlanarReflection {
+    ///
+    /// Constructs a new planar reflection in the plane through the given point with the given normal.
+    /// The reflection is empty until rendered with [PlanarReflection::render].
+    ///
+    pub fn new(context: &Context, point: Vec3, normal: Vec3) -> Self {
+        Self {
+            context: context.clone(),
+            color_texture: new_color_texture(context, 1, 1),
+            depth_texture: new_depth_texture(context, 1, 1),
+            viewport: Viewport::new_at_origo(1, 1),
+            point,
+            normal,
+            clip_offset: 0.0,
+        }
+    }
+
+    ///
+    /// Returns the camera mirrored about the plane which is used to render the reflection as seen from the given camera.
+    ///
+    pub fn reflection_camera(&self, camera: &Camera) -> Camera {
+        let normal = self.normal.normalize();
+        let reflect_direction = |direction: Vec3| direction - 2.0 * normal * direction.dot(normal);
+        let reflect_position =
+            |position: Vec3| position - 2.0 * normal * (position - self.point).dot(normal);
+        let mut reflection_camera = camera.clone();
+        reflection_camera.set_view(
+            reflect_position(*camera.position()),
+            reflect_position(*camera.target()),
+            reflect_direction(*camera.up()),
+        );
+        reflection_camera.set_viewport(Viewport::new_at_origo(
+            camera.viewport().width,
+            camera.viewport().height,
+        ));
+        reflection_camera.disable_tone_and_color_mapping();
+        reflection_camera.set_oblique_near_plane(Some(
+            normal.extend(-normal.dot(self.point) - self.clip_offset),
+        ));
+        reflection_camera
+    }
+
+    ///
+    /// Renders the reflection of the given objects lit by the given lights as seen from the given camera.
+    /// Must be called each frame before rendering the reflecting surface, and the reflecting surface should not be one of the objects.
+    /// The camera must be on the side of the plane that the normal points to.
+    ///
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = impl Object>,
+        lights: &[&dyn Light],
+    ) {
+        let viewport = camera.viewport();
+        if self.color_texture.width() != viewport.width
+            || self.color_texture.height() != viewport.height
+        {
+            self.color_texture = new_color_texture(&self.context, viewport.width, viewport.height);
+            self.depth_texture = new_depth_texture(&self.context, viewport.width, viewport.height);
+        }
+        self.viewport = viewport;
+        let reflection_camera = self.reflection_camera(camera);
+        RenderTarget::new(
+            self.color_texture.as_color_target(None),
+            self.depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .render(&reflection_camera, objects, lights);
+    }
+
+    ///
+    /// Returns the texture containing the rendered reflection in linear color space.
+    /// The texture is mirrored horizontally compared to the view of the camera given to [PlanarReflection::render].
+    ///
+    pub fn color_texture(&self) -> &Texture2D {
+        &self.color_texture
+    }
+
+    ///
+    /// Returns the fragment shader source for looking up the reflection, which defines the function
+    /// ```no_rust
+    /// vec4 sample_reflection(vec2 offset)
+    /// ```
+    /// that returns the reflection at the current fragment, where the offset in uv coordinates can be used to distort the reflection, for example by waves.
+    ///
+    pub fn fragment_shader_source() -> &'static str {
+        "
+        uniform sampler2D reflectionMap;
+        uniform vec4 reflectionViewport;
+
+        vec4 sample_reflection(vec2 offset)
+        {
+            vec2 uv = (gl_FragCoord.xy - reflectionViewport.xy) / reflectionViewport.zw;
+            return texture(reflectionMap, clamp(vec2(1.0 - uv.x, uv.y) + offset, vec2(0.0), vec2(1.0)));
+        }
+        "
+    }
+
+    ///
+    /// Sends the uniform data needed for looking up the reflection to the fragment shader, see [PlanarReflection::fragment_shader_source].
+    ///
+    pub fn use_uniforms(&self, program: &Program) {
+        program.use_texture("reflectionMap", &self.color_texture);
+        program.use_uniform(
+            "reflectionViewport",
+            vec4(
+                self.viewport.x as f32,
+                self.viewport.y as f32,
+                self.viewport.width as f32,
+                self.viewport.height as f32,
+            ),
+        );
+    }
+}
+
+fn new_color_texture(context: &Context, width: u32, height: u32) -> Texture2D {
+    Texture2D::new_empty::<[f16; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+fn new_depth_texture(context: &Context, width: u32, height: u32) -> DepthTexture2D {
+    DepthTexture2D::new::<f32>(
+        context,
+        width,
+        height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
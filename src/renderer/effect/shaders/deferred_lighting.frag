@@ -4,6 +4,9 @@ uniform float zNear;
 uniform float zFar;
 uniform vec3 cameraPosition;
 uniform int debug_type;
+#ifdef USE_ACCUMULATED_LIGHTING
+uniform sampler2D accumulatedLighting;
+#endif
 
 in vec2 uvs;
 
@@ -61,7 +64,16 @@ void main()
         outColor = vec4(uvs, 0., 1.);
     }
     else { // None
-        outColor.rgb = total_emissive + calculate_lighting(cameraPosition, surface_color.rgb, position, normal, metallic_factor, roughness_factor, occlusion);
+        outColor.rgb = calculate_lighting(cameraPosition, surface_color.rgb, position, normal, metallic_factor, roughness_factor, occlusion);
+#ifdef ACCUMULATE
+        outColor.a = 1.0;
+        return;
+#endif
+#ifdef USE_ACCUMULATED_LIGHTING
+        outColor.rgb += texture(accumulatedLighting, uvs).rgb;
+#endif
+        outColor.rgb += total_emissive;
+        outColor.rgb = fog(outColor.rgb, position);
         outColor.rgb = tone_mapping(outColor.rgb);
         outColor.rgb = color_mapping(outColor.rgb);
         outColor.a = surface_color.a;
@@ -0,0 +1,59 @@
+
+uniform mat4 viewProjectionInverse;
+uniform vec3 eyePosition;
+uniform float zFar;
+uniform float focusDistance;
+uniform float aperture;
+uniform float maxBlurRadius;
+uniform vec2 texelSize;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+const float GOLDEN_ANGLE = 2.39996323;
+const float RADIUS_SCALE = 0.5;
+
+float view_distance(vec2 uv)
+{
+    float depth = sample_depth(uv);
+    if (depth > 0.99999) {
+        return zFar;
+    }
+    return distance(world_pos_from_depth(viewProjectionInverse, depth, uv), eyePosition);
+}
+
+float blur_radius(float d)
+{
+    return maxBlurRadius * min(1.0, aperture * abs(d - focusDistance) / max(d, 0.00001));
+}
+
+// Scatter-as-gather bokeh along a golden angle spiral, based on "Bokeh depth of field in a single pass" by Dennis Gustafsson
+void main()
+{
+    vec4 center = sample_color(uvs);
+    float center_distance = view_distance(uvs);
+    float center_radius = blur_radius(center_distance);
+
+    vec3 color = center.rgb;
+    float total = 1.0;
+    float radius = RADIUS_SCALE;
+    for (float angle = 0.0; radius < maxBlurRadius; angle += GOLDEN_ANGLE)
+    {
+        vec2 uv = uvs + vec2(cos(angle), sin(angle)) * texelSize * radius;
+        vec3 sample_rgb = sample_color(uv).rgb;
+        float sample_distance = view_distance(uv);
+        float sample_radius = blur_radius(sample_distance);
+        // Surfaces behind the center pixel cannot blur onto it more than the center pixel is blurred itself
+        if (sample_distance > center_distance) {
+            sample_radius = clamp(sample_radius, 0.0, center_radius * 2.0);
+        }
+        float m = smoothstep(radius - 0.5, radius + 0.5, sample_radius);
+        color += mix(color / total, sample_rgb, m);
+        total += 1.0;
+        radius += RADIUS_SCALE / radius;
+    }
+    color /= total;
+
+    outColor = vec4(color_mapping(tone_mapping(color)), center.a);
+}
@@ -0,0 +1,38 @@
+uniform mat4 viewProjectionInverse;
+uniform vec3 cameraPosition;
+uniform vec2 texelSize;
+uniform float strength;
+uniform float radius;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+// The logarithm of the distance from the camera, or a large negative value if nothing is rendered at the given uv coordinates
+float log_distance(vec2 uv)
+{
+    float depth = sample_depth(uv);
+    if (depth > 0.99999) {
+        return -1e6;
+    }
+    return log2(max(distance(cameraPosition, world_pos_from_depth(viewProjectionInverse, depth, uv)), 1e-6));
+}
+
+void main()
+{
+    float center = log_distance(uvs);
+    if (center < -1e5) {
+        discard;
+    }
+
+    float response = 0.0;
+    for (int i = 0; i < 8; i++) {
+        float angle = 0.7853982 * float(i);
+        float neighbour = log_distance(uvs + radius * texelSize * vec2(cos(angle), sin(angle)));
+        if (neighbour > -1e5) {
+            response += max(0.0, center - neighbour);
+        }
+    }
+    float shade = exp(-strength * response / 8.0);
+    outColor = vec4(0.0, 0.0, 0.0, 1.0 - shade);
+}
@@ -0,0 +1,47 @@
+
+uniform mat4 viewProjectionInverse;
+uniform mat4 previousViewProjection;
+uniform float shutter;
+uniform float maxBlurRadius;
+uniform int sampleCount;
+uniform vec2 texelSize;
+
+#ifdef USE_VELOCITY_TEXTURE
+uniform sampler2D velocityMap;
+#endif
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // Camera motion: reproject the position into the previous frame
+    vec3 position = world_pos_from_depth(viewProjectionInverse, sample_depth(uvs), uvs);
+    vec4 previous = previousViewProjection * vec4(position, 1.0);
+    vec2 velocity = uvs - (0.5 * previous.xy / max(previous.w, 0.00001) + 0.5);
+
+#ifdef USE_VELOCITY_TEXTURE
+    // Object motion: use the rendered velocity where an object was rendered
+    vec4 object_velocity = texture(velocityMap, uvs);
+    if (object_velocity.a > 0.5) {
+        velocity = object_velocity.xy;
+    }
+#endif
+
+    velocity *= shutter;
+    float blur_length = length(velocity / texelSize);
+    if (blur_length > maxBlurRadius) {
+        velocity *= maxBlurRadius / blur_length;
+    }
+
+    vec4 center = sample_color(uvs);
+    vec3 color = vec3(0.0);
+    for (int i = 0; i < sampleCount; i++) {
+        float t = float(i) / float(max(sampleCount - 1, 1)) - 0.5;
+        color += sample_color(uvs + velocity * t).rgb;
+    }
+    color /= float(sampleCount);
+
+    outColor = vec4(color_mapping(tone_mapping(color)), center.a);
+}
@@ -0,0 +1,29 @@
+
+uniform sampler2D weightsMap;
+uniform vec2 texelSize;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 weights = texture(weightsMap, uvs);
+    float bottom = weights.r;
+    float top = texture(weightsMap, uvs + vec2(0.0, texelSize.y)).g;
+    float left = weights.b;
+    float right = texture(weightsMap, uvs + vec2(texelSize.x, 0.0)).a;
+
+    vec4 color = sample_color(uvs);
+    if (bottom + top + left + right < 0.00001) {
+        outColor = color;
+    } else if (bottom + top >= left + right) {
+        outColor = color * (1.0 - bottom - top)
+            + sample_color(uvs - vec2(0.0, texelSize.y)) * bottom
+            + sample_color(uvs + vec2(0.0, texelSize.y)) * top;
+    } else {
+        outColor = color * (1.0 - left - right)
+            + sample_color(uvs - vec2(texelSize.x, 0.0)) * left
+            + sample_color(uvs + vec2(texelSize.x, 0.0)) * right;
+    }
+}
@@ -0,0 +1,31 @@
+
+uniform vec2 texelSize;
+uniform float threshold;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+float luma(float x, float y)
+{
+    return dot(sample_color(uvs + vec2(x, y) * texelSize).rgb, vec3(0.2126, 0.7152, 0.0722));
+}
+
+void main()
+{
+    float L = luma(0.0, 0.0);
+    vec2 delta = abs(L - vec2(luma(-1.0, 0.0), luma(0.0, -1.0)));
+    vec2 edges = step(threshold, delta);
+    if (edges.x + edges.y == 0.0) {
+        outColor = vec4(0.0);
+        return;
+    }
+
+    // Local contrast adaptation: remove edges which are much weaker than a neighbouring edge
+    vec2 delta_next = abs(L - vec2(luma(1.0, 0.0), luma(0.0, 1.0)));
+    vec2 delta_far = abs(vec2(luma(-1.0, 0.0) - luma(-2.0, 0.0), luma(0.0, -1.0) - luma(0.0, -2.0)));
+    float max_delta = max(max(max(delta.x, delta.y), max(delta_next.x, delta_next.y)), max(delta_far.x, delta_far.y));
+    edges *= step(max_delta, 2.0 * delta);
+
+    outColor = vec4(edges, 0.0, 0.0);
+}
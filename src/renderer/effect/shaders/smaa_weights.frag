@@ -0,0 +1,84 @@
+
+uniform vec2 texelSize;
+uniform int maxSearchSteps;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+// Returns the left edge in the red channel and the bottom edge in the green channel of the given pixel
+vec2 edge(vec2 pixel)
+{
+    if (any(lessThan(pixel, vec2(0.0))) || any(greaterThanEqual(pixel * texelSize, vec2(1.0)))) {
+        return vec2(0.0);
+    }
+    return sample_color((pixel + 0.5) * texelSize).rg;
+}
+
+float edge_along(vec2 pixel, bool vertical)
+{
+    vec2 e = edge(pixel);
+    return vertical ? e.r : e.g;
+}
+
+// Returns the number of pixels the edge line continues from the given pixel in the given direction
+float search(vec2 pixel, vec2 direction, bool vertical)
+{
+    float steps = 0.0;
+    for (int i = 1; i <= maxSearchSteps; i++) {
+        if (edge_along(pixel + float(i) * direction, vertical) < 0.5) {
+            break;
+        }
+        steps += 1.0;
+    }
+    return steps;
+}
+
+// Returns 1 if the crossing edge at the end of the line turns towards the side of the current pixel,
+// -1 if it turns towards the neighbour pixel and 0 if there is no crossing edge or if it crosses both sides
+float crossing(vec2 pixel, vec2 neighbour_offset, bool vertical)
+{
+    return edge_along(pixel, !vertical) - edge_along(pixel + neighbour_offset, !vertical);
+}
+
+// Computes the area covered by the other side for the pixel at distance d1 from the start of a line of d1 + d2 + 1 pixels.
+// The silhouette is revectorized as a line from the half pixel offset at each end with a crossing edge to the middle of the line.
+// Returns the area on the side of the pixel in x and the area on the side of the neighbour in y.
+vec2 area(float d1, float d2, float s1, float s2)
+{
+    float half_length = 0.5 * (d1 + d2 + 1.0);
+    vec2 result = vec2(0.0);
+    float end = min(d1 + 1.0, half_length);
+    if (end > d1) {
+        float h = s1 * 0.5 * (1.0 - 0.5 * (d1 + end) / half_length);
+        result += (end - d1) * vec2(max(h, 0.0), max(-h, 0.0));
+    }
+    float start = max(d1, half_length);
+    if (d1 + 1.0 > start) {
+        float h = s2 * 0.5 * (0.5 * (start + d1 + 1.0) / half_length - 1.0);
+        result += (d1 + 1.0 - start) * vec2(max(h, 0.0), max(-h, 0.0));
+    }
+    return result;
+}
+
+vec2 blending_weights(vec2 pixel, vec2 direction, vec2 neighbour_offset, bool vertical)
+{
+    float d1 = search(pixel, -direction, vertical);
+    float d2 = search(pixel, direction, vertical);
+    float s1 = crossing(pixel - d1 * direction, neighbour_offset, vertical);
+    float s2 = crossing(pixel + (d2 + 1.0) * direction, neighbour_offset, vertical);
+    return area(d1, d2, s1, s2);
+}
+
+void main()
+{
+    vec2 pixel = floor(uvs / texelSize);
+    vec2 e = edge(pixel);
+    outColor = vec4(0.0);
+    if (e.g > 0.5) {
+        outColor.rg = blending_weights(pixel, vec2(1.0, 0.0), vec2(0.0, -1.0), false);
+    }
+    if (e.r > 0.5) {
+        outColor.ba = blending_weights(pixel, vec2(0.0, 1.0), vec2(-1.0, 0.0), true);
+    }
+}
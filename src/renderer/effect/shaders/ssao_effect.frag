@@ -0,0 +1,77 @@
+
+uniform mat4 projection;
+uniform mat4 projectionInverse;
+uniform float radius;
+uniform float intensity;
+uniform float bias;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+vec3 view_position(vec2 uv)
+{
+    return world_pos_from_depth(projectionInverse, sample_depth(uv), uv);
+}
+
+vec3 view_normal(vec3 position, vec2 uv)
+{
+    // Reconstruct the normal from the neighbouring depths, choosing the smallest difference to avoid artifacts at edges
+    vec2 texel_size = 1.0 / vec2(textureSize(depthMap, 0).xy);
+    vec3 right = view_position(uv + vec2(texel_size.x, 0.0)) - position;
+    vec3 left = position - view_position(uv - vec2(texel_size.x, 0.0));
+    vec3 up = view_position(uv + vec2(0.0, texel_size.y)) - position;
+    vec3 down = position - view_position(uv - vec2(0.0, texel_size.y));
+    vec3 dx = abs(right.z) < abs(left.z) ? right : left;
+    vec3 dy = abs(up.z) < abs(down.z) ? up : down;
+    return normalize(cross(dx, dy));
+}
+
+// Interleaved gradient noise, see "Next Generation Post Processing in Call of Duty: Advanced Warfare" by Jorge Jimenez
+float interleaved_gradient_noise(vec2 pixel)
+{
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+const int SAMPLE_COUNT = 16;
+
+void main()
+{
+    float depth = sample_depth(uvs);
+    if (depth > 0.999) {
+        outColor = vec4(1.0);
+        return;
+    }
+    vec3 position = view_position(uvs);
+    vec3 normal = view_normal(position, uvs);
+
+    float angle = 2.0 * PI * interleaved_gradient_noise(gl_FragCoord.xy);
+    vec3 random_vector = vec3(cos(angle), sin(angle), 0.0);
+    vec3 tangent = normalize(random_vector - normal * dot(random_vector, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 TBN = mat3(tangent, bitangent, normal);
+
+    float occlusion = 0.0;
+    for (int i = 0; i < SAMPLE_COUNT; i++)
+    {
+        // Hemisphere sample with more samples close to the center
+        vec2 Xi = Hammersley(uint(i), uint(SAMPLE_COUNT));
+        float phi = 2.0 * PI * Xi.x;
+        float cos_theta = sqrt(1.0 - Xi.y);
+        float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+        float scale = mix(0.1, 1.0, pow(float(i + 1) / float(SAMPLE_COUNT), 2.0));
+        vec3 sample_direction = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta) * scale;
+        vec3 sample_position = position + TBN * sample_direction * radius;
+
+        vec4 offset = projection * vec4(sample_position, 1.0);
+        vec2 sample_uv = offset.xy / offset.w * 0.5 + 0.5;
+        if (sample_uv.x < 0.0 || sample_uv.x > 1.0 || sample_uv.y < 0.0 || sample_uv.y > 1.0) {
+            continue;
+        }
+        float scene_depth = view_position(sample_uv).z;
+        float range_check = smoothstep(0.0, 1.0, radius / abs(position.z - scene_depth));
+        occlusion += (scene_depth >= sample_position.z + bias ? 1.0 : 0.0) * range_check;
+    }
+    float ambient_occlusion = pow(clamp(1.0 - occlusion / float(SAMPLE_COUNT), 0.0, 1.0), intensity);
+    outColor = vec4(ambient_occlusion, ambient_occlusion, ambient_occlusion, 1.0);
+}
@@ -0,0 +1,124 @@
+uniform mat4 viewProjection;
+uniform mat4 viewProjectionInverse;
+uniform vec3 cameraPosition;
+
+#ifdef USE_BACKGROUND_TEXTURE
+uniform samplerCube environmentMap;
+#else
+uniform vec4 environmentColor;
+#endif
+
+uniform vec4 albedo;
+uniform float metallic;
+uniform float roughness;
+uniform float maxDistance;
+uniform float thickness;
+uniform int stepCount;
+uniform int rayCount;
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 outColor;
+
+float interleaved_gradient_noise(vec2 pixel)
+{
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+vec2 screen_uv(vec3 world_pos, out bool valid)
+{
+    vec4 clip = viewProjection * vec4(world_pos, 1.0);
+    vec2 uv = 0.5 + 0.5 * clip.xy / clip.w;
+    valid = clip.w > 0.0 && uv.x >= 0.0 && uv.x <= 1.0 && uv.y >= 0.0 && uv.y <= 1.0;
+    return uv;
+}
+
+// Returns the distance to the scene surface minus the distance to the given point as seen from the camera.
+float depth_difference(vec3 world_pos, vec2 uv)
+{
+    vec3 scene_pos = world_pos_from_depth(viewProjectionInverse, sample_depth(uv), uv);
+    return distance(cameraPosition, scene_pos) - distance(cameraPosition, world_pos);
+}
+
+// Marches along the ray in world space and returns the color of the scene where it hits and the confidence of the hit in the alpha channel.
+vec4 trace(vec3 origin, vec3 direction, float jitter)
+{
+    vec3 step_vector = direction * maxDistance / float(stepCount);
+    vec3 previous = origin;
+    vec3 current = origin + step_vector * jitter;
+    for (int i = 0; i < stepCount; i++)
+    {
+        current += step_vector;
+        bool valid;
+        vec2 uv = screen_uv(current, valid);
+        if (!valid) {
+            break;
+        }
+        float difference = depth_difference(current, uv);
+        if (difference < 0.0 && difference > -thickness) {
+            // Refine the hit with a binary search
+            vec3 a = previous;
+            vec3 b = current;
+            for (int j = 0; j < 5; j++)
+            {
+                vec3 middle = 0.5 * (a + b);
+                if (depth_difference(middle, screen_uv(middle, valid)) < 0.0) {
+                    b = middle;
+                } else {
+                    a = middle;
+                }
+            }
+            uv = screen_uv(b, valid);
+            vec2 edge = smoothstep(0.0, 0.1, uv) * (1.0 - smoothstep(0.9, 1.0, uv));
+            float fade = edge.x * edge.y * (1.0 - float(i) / float(stepCount));
+            return vec4(sample_color(uv).rgb, fade);
+        }
+        previous = current;
+    }
+    return vec4(0.0);
+}
+
+vec3 environment_color(vec3 direction)
+{
+#ifdef USE_BACKGROUND_TEXTURE
+    return texture(environmentMap, direction).rgb;
+#else
+    return environmentColor.rgb;
+#endif
+}
+
+void main()
+{
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec3 view_direction = normalize(cameraPosition - pos);
+    vec3 surface_color = albedo.rgb;
+
+    vec3 T = abs(normal.y) < 0.99 ? normalize(cross(normal, vec3(0.0, 1.0, 0.0))) : normalize(cross(normal, vec3(1.0, 0.0, 0.0)));
+    vec3 B = cross(normal, T);
+    float noise = interleaved_gradient_noise(gl_FragCoord.xy);
+
+    // Glossy reflections by tracing rays along GGX distributed half vectors
+    vec3 reflection = vec3(0.0);
+    for (int i = 0; i < rayCount; i++)
+    {
+        vec2 Xi = fract(Hammersley(uint(i), uint(rayCount)) + noise);
+        vec3 H = ImportanceSampleGGX(Xi, normal, max(roughness, 0.01));
+        vec3 direction = reflect(-view_direction, H);
+        if (dot(direction, normal) <= 0.0) {
+            direction = reflect(-view_direction, normal);
+        }
+        vec4 hit = trace(pos, direction, noise);
+        reflection += mix(environment_color(direction), hit.rgb, hit.a);
+    }
+    reflection /= float(rayCount);
+
+    vec3 F0 = mix(vec3(0.04), surface_color, metallic);
+    vec3 specular_fresnel = fresnel_schlick_roughness(F0, max(dot(normal, view_direction), 0.0), roughness);
+
+    outColor.rgb = calculate_lighting(cameraPosition, surface_color, pos, normal, metallic, roughness, 1.0);
+    outColor.rgb += reflection * specular_fresnel;
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = albedo.a;
+}
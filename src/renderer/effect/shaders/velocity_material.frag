@@ -0,0 +1,16 @@
+
+uniform mat4 currentViewProjection;
+uniform mat4 previousViewProjection;
+uniform mat4 previousTransformation;
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 current = currentViewProjection * vec4(pos, 1.0);
+    vec4 previous = previousViewProjection * previousTransformation * vec4(pos, 1.0);
+    vec2 velocity = 0.5 * (current.xy / current.w - previous.xy / max(previous.w, 0.00001));
+    outColor = vec4(velocity, 0.0, 1.0);
+}
@@ -0,0 +1,70 @@
+uniform mat4 viewProjectionInverse;
+uniform vec3 cameraPosition;
+uniform vec3 fogColor;
+uniform float fogDensity;
+uniform float heightFalloff;
+uniform float baseHeight;
+uniform float maxDistance;
+uniform float scattering;
+uniform int stepCount;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+float fog_density(vec3 position)
+{
+    return fogDensity * exp(-heightFalloff * (position.y - baseHeight));
+}
+
+// The integral of the fog density along the ray from the camera to the given position.
+float optical_depth(vec3 position)
+{
+    vec3 ray = position - cameraPosition;
+    float dist = length(ray);
+    float height_change = heightFalloff * ray.y;
+    float factor = abs(height_change) > 0.0001 ? (1.0 - exp(-height_change)) / height_change : 1.0;
+    return fog_density(cameraPosition) * factor * dist;
+}
+
+float interleaved_gradient_noise(vec2 pixel)
+{
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main()
+{
+    vec4 color = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    vec3 position = world_pos_from_depth(viewProjectionInverse, depth, uvs);
+    vec3 view_ray = position - cameraPosition;
+    float dist = min(length(view_ray), maxDistance);
+    vec3 view_direction = normalize(view_ray);
+    position = cameraPosition + view_direction * dist;
+
+    float transmittance = exp(-optical_depth(position));
+    outColor.rgb = color.rgb * transmittance + fogColor * (1.0 - transmittance);
+
+#ifdef USE_SCATTERING
+    // Ray march the light scattered towards the camera, the lights are evaluated with their shadows which gives light shafts
+    float step_length = dist / float(stepCount);
+    float accumulated_transmittance = 1.0;
+    vec3 scattered = vec3(0.0);
+    vec3 sample_position = cameraPosition + view_direction * step_length * interleaved_gradient_noise(gl_FragCoord.xy);
+    for (int i = 0; i < stepCount; i++)
+    {
+        float density = fog_density(sample_position);
+        vec3 light = 0.5 * (calculate_lighting(cameraPosition, vec3(1.0), sample_position, -view_direction, 0.0, 1.0, 1.0)
+            + calculate_lighting(cameraPosition, vec3(1.0), sample_position, view_direction, 0.0, 1.0, 1.0));
+        scattered += accumulated_transmittance * density * step_length * light;
+        accumulated_transmittance *= exp(-density * step_length);
+        sample_position += view_direction * step_length;
+    }
+    outColor.rgb += scattering * scattered;
+#endif
+
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = color.a;
+    gl_FragDepth = depth;
+}
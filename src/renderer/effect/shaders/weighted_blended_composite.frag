@@ -0,0 +1,14 @@
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float revealage = exp2(sample_layer(uvs, 1).r);
+    if (revealage > 0.9999) {
+        discard;
+    }
+    vec4 accumulation = sample_layer(uvs, 0);
+    outColor = vec4(accumulation.rgb / max(accumulation.a, 0.00001), 1.0 - revealage);
+}
@@ -0,0 +1,17 @@
+
+layout (location = 1) out vec4 outRevealage;
+
+void main()
+{
+    material_main();
+
+    // Weight function from "Weighted Blended Order-Independent Transparency" by McGuire and Bavoil,
+    // which gives surfaces closer to the camera a higher weight
+    float alpha = clamp(outColor.a, 0.0, 0.999);
+    float z = gl_FragCoord.z;
+    float weight = clamp(alpha * max(0.01, 3000.0 * pow(1.0 - z, 3.0)), 0.01, 3000.0);
+    outColor = vec4(outColor.rgb * alpha * weight, alpha * weight);
+
+    // The product of (1 - alpha) is accumulated as a sum of logarithms, so additive blending can be used for both outputs
+    outRevealage = vec4(log2(1.0 - alpha), 0.0, 0.0, 0.0);
+}
@@ -0,0 +1,278 @@
+use crate::renderer::*;
+
+///
+/// A morphological anti-aliasing approach based on SMAA (Subpixel Morphological Anti-Aliasing) which finds the jagged edges in the image
+/// and blends the pixels along the edges according to the shape of the edge.
+/// Compared to the [FxaaEffect], this keeps the rest of the image sharp, but it needs three passes and intermediate textures.
+/// Like the [FxaaEffect], it is useful when multisampling is too expensive or not available, for example when rendering into a texture on some platforms.
+///
+/// This is a simplified version of SMAA 1x without diagonal and corner detection, where the blending weights are calculated in the shader instead of looked up in precomputed area textures.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct SmaaEffect {
+    /// The minimum difference in brightness between two neighbouring pixels for an edge to be detected. A lower value finds more edges, but also smooths details.
+    pub threshold: f32,
+    /// The maximum number of pixels searched in each direction to find the ends of an edge. Longer edges are treated as straight lines.
+    pub max_search_steps: u32,
+}
+
+impl Default for SmaaEffect {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_search_steps: 16,
+        }
+    }
+}
+
+impl SmaaEffect {
+    ///
+    /// Applies the anti-aliasing to the given color texture and writes the result to the given render target using the viewport of the camera.
+    /// The anti-aliasing works best on colors which are tone and color mapped, ie. on the final image.
+    ///
+    pub fn apply(&self, target: &RenderTarget, camera: &Camera, color_texture: &Texture2D) {
+        let context = &target.context;
+        let (width, height) = (color_texture.width(), color_texture.height());
+        let texel_size = vec2(1.0 / width as f32, 1.0 / height as f32);
+        let pass_camera = Camera::new_2d(Viewport::new_at_origo(width, height));
+        let new_texture = || {
+            Texture2D::new_empty::<[u8; 4]>(
+                context,
+                width,
+                height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+
+        let mut edges = new_texture();
+        edges
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .apply_screen_effect(
+                &SmaaEdgeDetectionEffect {
+                    threshold: self.threshold,
+                    texel_size,
+                },
+                &pass_camera,
+                &[],
+                Some(ColorTexture::Single(color_texture)),
+                None,
+            );
+
+        let mut weights = new_texture();
+        weights
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .apply_screen_effect(
+                &SmaaBlendingWeightsEffect {
+                    max_search_steps: self.max_search_steps,
+                    texel_size,
+                },
+                &pass_camera,
+                &[],
+                Some(ColorTexture::Single(&edges)),
+                None,
+            );
+
+        target.apply_screen_effect(
+            &SmaaBlendingEffect {
+                weights: &weights,
+                texel_size,
+            },
+            camera,
+            &[],
+            Some(ColorTexture::Single(color_texture)),
+            None,
+        );
+    }
+}
+
+fn screen_render_states() -> RenderStates {
+    RenderStates {
+        write_mask: WriteMask::COLOR,
+        depth_test: DepthTest::Always,
+        cull: Cull::Back,
+        ..Default::default()
+    }
+}
+
+///
+/// Finds the edges between each pixel and the neighbouring pixel to the left (red channel) and below (green channel).
+///
+struct SmaaEdgeDetectionEffect {
+    threshold: f32,
+    texel_size: Vec2,
+}
+
+impl Effect for SmaaEdgeDetectionEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}",
+            color_texture
+                .expect("Must supply a color texture to detect edges")
+                .fragment_shader_source(),
+            include_str!("shaders/smaa_edges.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1100u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to detect edges")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to detect edges")
+            .use_uniforms(program);
+        program.use_uniform("texelSize", self.texel_size);
+        program.use_uniform("threshold", self.threshold);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        screen_render_states()
+    }
+}
+
+///
+/// Calculates how much each pixel should be blended with the neighbouring pixels across the edges found in the edge texture.
+///
+struct SmaaBlendingWeightsEffect {
+    max_search_steps: u32,
+    texel_size: Vec2,
+}
+
+impl Effect for SmaaBlendingWeightsEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}",
+            color_texture
+                .expect("Must supply an edge texture to calculate blending weights")
+                .fragment_shader_source(),
+            include_str!("shaders/smaa_weights.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1101u16 << 7
+            | color_texture
+                .expect("Must supply an edge texture to calculate blending weights")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply an edge texture to calculate blending weights")
+            .use_uniforms(program);
+        program.use_uniform("texelSize", self.texel_size);
+        program.use_uniform("maxSearchSteps", self.max_search_steps as i32);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        screen_render_states()
+    }
+}
+
+///
+/// Blends each pixel with its neighbours using the blending weights.
+///
+struct SmaaBlendingEffect<'a> {
+    weights: &'a Texture2D,
+    texel_size: Vec2,
+}
+
+impl Effect for SmaaBlendingEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}",
+            color_texture
+                .expect("Must supply a color texture to apply a smaa effect")
+                .fragment_shader_source(),
+            include_str!("shaders/smaa_blending.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1110u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a smaa effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to apply a smaa effect")
+            .use_uniforms(program);
+        program.use_texture("weightsMap", self.weights);
+        program.use_uniform("texelSize", self.texel_size);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        screen_render_states()
+    }
+}
@@ -0,0 +1,244 @@
+use crate::renderer::*;
+
+///
+/// Screen space ambient occlusion which darkens creases, holes and surfaces close to each other,
+/// ie. the areas where ambient light is blocked by nearby geometry.
+/// The occlusion is estimated from the depth texture of the scene, the normals are reconstructed from the depths.
+///
+/// Use [SsaoEffect::occlusion_texture] to compute a blurred ambient occlusion texture from a depth texture
+/// and assign it to [AmbientLight::occlusion_map] to modulate the ambient light when rendering the scene.
+/// The effect can also be applied directly using [apply_screen_effect] with a depth texture which writes the unblurred occlusion to the red, green and blue channels.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct SsaoEffect {
+    /// The radius of the hemisphere around each surface point which is tested for occluders, in world space units.
+    pub radius: f32,
+    /// The strength of the occlusion, a value of one gives the estimated occlusion while higher values darken the occluded areas more.
+    pub intensity: f32,
+    /// A small depth offset used to avoid self occlusion on flat surfaces.
+    pub bias: f32,
+    /// The radius in pixels of the blur applied in [SsaoEffect::occlusion_texture] to remove the noise from the sampling pattern. Zero disables the blur.
+    pub blur_radius: u32,
+}
+
+impl Default for SsaoEffect {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            intensity: 1.0,
+            bias: 0.025,
+            blur_radius: 2,
+        }
+    }
+}
+
+impl SsaoEffect {
+    ///
+    /// Computes the ambient occlusion of the scene with the given depth texture, rendered with the given camera,
+    /// and returns a texture with the same size as the depth texture containing the blurred occlusion in the red channel.
+    ///
+    pub fn occlusion_texture(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        depth_texture: DepthTexture,
+    ) -> Texture2D {
+        let new_texture = || {
+            Texture2D::new_empty::<u8>(
+                context,
+                depth_texture.width(),
+                depth_texture.height(),
+                Interpolation::Linear,
+                Interpolation::Linear,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        let mut camera = camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(
+            depth_texture.width(),
+            depth_texture.height(),
+        ));
+        let mut occlusion = new_texture();
+        occlusion
+            .as_color_target(None)
+            .clear(ClearState::color(1.0, 1.0, 1.0, 1.0))
+            .apply_screen_effect(self, &camera, &[], None, Some(depth_texture));
+        if self.blur_radius == 0 {
+            return occlusion;
+        }
+        let mut blurred = new_texture();
+        blurred
+            .as_color_target(None)
+            .clear(ClearState::color(1.0, 1.0, 1.0, 1.0))
+            .apply_screen_effect(
+                &SsaoBlurEffect {
+                    radius: self.blur_radius,
+                },
+                &camera,
+                &[],
+                Some(ColorTexture::Single(&occlusion)),
+                Some(depth_texture),
+            );
+        blurred
+    }
+}
+
+impl Effect for SsaoEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            depth_texture
+                .expect(
+                    "Must supply a depth texture to apply a screen space ambient occlusion effect"
+                )
+                .fragment_shader_source(),
+            include_str!("shaders/ssao_effect.frag")
+        )
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 7
+            | depth_texture
+                .expect(
+                    "Must supply a depth texture to apply a screen space ambient occlusion effect",
+                )
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        depth_texture
+            .expect("Must supply a depth texture to apply a screen space ambient occlusion effect")
+            .use_uniforms(program);
+        program.use_uniform("projection", camera.projection());
+        program.use_uniform("projectionInverse", camera.projection().invert().unwrap());
+        program.use_uniform("radius", self.radius);
+        program.use_uniform("intensity", self.intensity);
+        program.use_uniform("bias", self.bias);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// Blurs the ambient occlusion while preserving edges by ignoring samples with a very different depth.
+///
+struct SsaoBlurEffect {
+    radius: u32,
+}
+
+impl Effect for SsaoBlurEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}
+            uniform int blurRadius;
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                vec2 texel_size = 1.0 / vec2(textureSize(depthMap, 0).xy);
+                float depth = sample_depth(uvs);
+                float result = 0.0;
+                float weight = 0.0;
+                for (int x = -blurRadius; x <= blurRadius; x++)
+                {{
+                    for (int y = -blurRadius; y <= blurRadius; y++)
+                    {{
+                        vec2 uv = uvs + vec2(float(x), float(y)) * texel_size;
+                        float w = abs(sample_depth(uv) - depth) < 0.01 * (1.0 - depth) + 0.0001 ? 1.0 : 0.0;
+                        result += w * sample_color(uv).r;
+                        weight += w;
+                    }}
+                }}
+                float ambient_occlusion = weight > 0.0 ? result / weight : sample_color(uvs).r;
+                outColor = vec4(ambient_occlusion, ambient_occlusion, ambient_occlusion, 1.0);
+            }}
+            ",
+            color_texture
+                .expect("Must supply a color texture to blur the ambient occlusion")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to blur the ambient occlusion")
+                .fragment_shader_source(),
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b10u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to blur the ambient occlusion")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to blur the ambient occlusion")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to blur the ambient occlusion")
+            .use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to blur the ambient occlusion")
+            .use_uniforms(program);
+        program.use_uniform("blurRadius", self.radius as i32);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// An effect that renders a reflective surface, for example a polished floor or a metal plate, with screen space reflections
+/// and should therefore only be applied to the geometry of the reflective surface using [render_with_effect].
+/// This effect needs the rendered scene (without the reflective surface) in a color and depth texture.
+/// The reflections are found by marching rays through the depth texture, and where a ray leaves the screen or doesn't hit anything,
+/// the [Background] is used instead.
+///
+#[derive(Clone)]
+pub struct SsrEffect {
+    /// The background of the scene which is used for reflections that are not found on the screen.
+    pub background: Background,
+    /// The base color of the surface.
+    pub albedo: Srgba,
+    /// A value in the range `[0..1]` specifying how metallic the surface is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the surface is. Rough surfaces give blurry reflections.
+    pub roughness: f32,
+    /// The lighting model used when rendering this effect.
+    pub lighting_model: LightingModel,
+    /// The maximum distance, in world space units, a reflection ray travels.
+    pub max_distance: f32,
+    /// The thickness, in world space units, assumed for the surfaces in the depth texture when testing for a hit.
+    pub thickness: f32,
+    /// The number of steps along each reflection ray.
+    pub step_count: u32,
+    /// The number of reflection rays for each pixel. More rays reduces the noise on rough surfaces.
+    pub ray_count: u32,
+}
+
+impl Default for SsrEffect {
+    fn default() -> Self {
+        Self {
+            background: Background::default(),
+            albedo: Srgba::WHITE,
+            metallic: 1.0,
+            roughness: 0.1,
+            lighting_model: LightingModel::Cook(
+                NormalDistributionFunction::TrowbridgeReitzGGX,
+                GeometryFunction::SmithSchlickGGX,
+            ),
+            max_distance: 10.0,
+            thickness: 0.5,
+            step_count: 32,
+            ray_count: 4,
+        }
+    }
+}
+
+impl Effect for SsrEffect {
+    fn fragment_shader_source(
+        &self,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            match &self.background {
+                Background::Color(_) => "",
+                Background::Texture(_) => "#define USE_BACKGROUND_TEXTURE",
+            },
+            color_texture
+                .expect("Must supply a color texture to apply a screen space reflection effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a screen space reflection effect")
+                .fragment_shader_source(),
+            lights_shader_source(lights, self.lighting_model),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/ssr_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b11u16 << 7
+            | match &self.background {
+                Background::Color(_) => 0,
+                Background::Texture(_) => 0b1u16 << 10,
+            }
+            | color_texture
+                .expect("Must supply a color texture to apply a screen space reflection effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a screen space reflection effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        color_texture
+            .expect("Must supply a color texture to apply a screen space reflection effect")
+            .use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a screen space reflection effect")
+            .use_uniforms(program);
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("cameraPosition", camera.lighting_position());
+        program.use_uniform("albedo", self.albedo.to_linear_srgb());
+        program.use_uniform("metallic", self.metallic);
+        program.use_uniform("roughness", self.roughness);
+        program.use_uniform("maxDistance", self.max_distance);
+        program.use_uniform("thickness", self.thickness);
+        program.use_uniform("stepCount", self.step_count.max(1) as i32);
+        program.use_uniform("rayCount", self.ray_count.max(1) as i32);
+        match &self.background {
+            Background::Color(color) => {
+                program.use_uniform("environmentColor", color.to_linear_srgb())
+            }
+            Background::Texture(tex) => program.use_texture_cube("environmentMap", tex),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::renderer::*;
+
+///
+/// An effect that simulates an atmosphere with exponential height fog, ie. fog that gets thicker with distance and thinner with height,
+/// and optionally light scattered in the fog which gives light shafts (god rays) from the lights, especially from directional and spot lights with shadow maps.
+/// The effect is applied to the rendered scene using the color and depth texture and the camera used to render the scene, so each camera can have different fog.
+///
+#[derive(Clone, Debug)]
+pub struct VolumetricFogEffect {
+    /// The color of the fog.
+    pub color: Srgba,
+    /// The density of the fog at the base height.
+    pub density: f32,
+    /// How fast the density decreases with height above the base height. Zero gives uniform distance fog.
+    pub height_falloff: f32,
+    /// The height where the fog has the given density.
+    pub base_height: f32,
+    /// The maximum distance from the camera where fog is applied, this is also the distance used for the background.
+    pub max_distance: f32,
+    /// The amount of light from the given lights which is scattered towards the camera. Zero disables the scattering.
+    pub scattering: f32,
+    /// The number of steps along each view ray when computing the scattered light.
+    pub step_count: u32,
+}
+
+impl Default for VolumetricFogEffect {
+    fn default() -> Self {
+        Self {
+            color: Srgba::new_opaque(200, 210, 225),
+            density: 0.02,
+            height_falloff: 0.1,
+            base_height: 0.0,
+            max_distance: 500.0,
+            scattering: 0.0,
+            step_count: 32,
+        }
+    }
+}
+
+impl VolumetricFogEffect {
+    fn use_scattering(&self, lights: &[&dyn Light]) -> bool {
+        self.scattering > 0.0 && !lights.is_empty()
+    }
+}
+
+impl Effect for VolumetricFogEffect {
+    fn fragment_shader_source(
+        &self,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            if self.use_scattering(lights) {
+                format!(
+                    "#define USE_SCATTERING\n{}",
+                    lights_shader_source(lights, LightingModel::Blinn)
+                )
+            } else {
+                include_str!("../../core/shared.frag").to_string()
+            },
+            color_texture
+                .expect("Must supply a color texture to apply a volumetric fog effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a volumetric fog effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/volumetric_fog_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | if self.scattering > 0.0 {
+                0b101u16 << 7
+            } else {
+                0b100u16 << 7
+            }
+            | color_texture
+                .expect("Must supply a color texture to apply a volumetric fog effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a volumetric fog effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        color_texture
+            .expect("Must supply a color texture to apply a volumetric fog effect")
+            .use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a volumetric fog effect")
+            .use_uniforms(program);
+        if self.use_scattering(lights) {
+            for (i, light) in lights.iter().enumerate() {
+                light.use_uniforms(program, i as u32);
+            }
+            program.use_uniform("scattering", self.scattering);
+            program.use_uniform("stepCount", self.step_count.max(1) as i32);
+        }
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("fogColor", self.color.to_linear_srgb().truncate());
+        program.use_uniform("fogDensity", self.density);
+        program.use_uniform("heightFalloff", self.height_falloff);
+        program.use_uniform("baseHeight", self.base_height);
+        program.use_uniform("maxDistance", self.max_distance);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -95,7 +95,7 @@ impl Effect for WaterEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
+        camera.use_tone_mapping_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         color_texture
             .expect("Must supply a color texture to apply a water effect")
@@ -111,7 +111,7 @@ impl Effect for WaterEffect {
             "viewProjectionInverse",
             (camera.projection() * camera.view()).invert().unwrap(),
         );
-        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("cameraPosition", camera.lighting_position());
         program.use_uniform(
             "screenSize",
             vec2(
@@ -61,6 +61,18 @@ mod instanced_mesh;
 #[doc(inline)]
 pub use instanced_mesh::*;
 
+mod skinned_mesh;
+#[doc(inline)]
+pub use skinned_mesh::*;
+
+mod instanced_skinned_mesh;
+#[doc(inline)]
+pub use instanced_skinned_mesh::*;
+
+mod morph_target;
+#[doc(inline)]
+pub use morph_target::*;
+
 mod sprites;
 #[doc(inline)]
 pub use sprites::*;
@@ -77,6 +89,10 @@ mod line;
 #[doc(inline)]
 pub use line::*;
 
+mod polyline;
+#[doc(inline)]
+pub use polyline::*;
+
 mod rectangle;
 #[doc(inline)]
 pub use rectangle::*;
@@ -85,6 +101,14 @@ mod circle;
 #[doc(inline)]
 pub use circle::*;
 
+mod shape2d;
+#[doc(inline)]
+pub use shape2d::*;
+
+mod sprite_batch;
+#[doc(inline)]
+pub use sprite_batch::*;
+
 use crate::core::*;
 use crate::renderer::*;
 
@@ -257,13 +281,16 @@ impl<T: Geometry> Geometry for std::sync::RwLock<T> {
     }
 }
 
-struct BaseMesh {
+pub(in crate::renderer) struct BaseMesh {
+    context: Context,
     indices: Option<ElementBuffer>,
     positions: VertexBuffer,
     normals: Option<VertexBuffer>,
     tangents: Option<VertexBuffer>,
     uvs: Option<VertexBuffer>,
+    uvs2: Option<VertexBuffer>,
     colors: Option<VertexBuffer>,
+    morph_targets: Option<MorphTargets>,
 }
 
 impl BaseMesh {
@@ -272,6 +299,7 @@ impl BaseMesh {
         cpu_mesh.validate().expect("invalid cpu mesh");
 
         Self {
+            context: context.clone(),
             indices: match &cpu_mesh.indices {
                 Indices::U8(ind) => Some(ElementBuffer::new_with_data(context, ind)),
                 Indices::U16(ind) => Some(ElementBuffer::new_with_data(context, ind)),
@@ -296,15 +324,50 @@ impl BaseMesh {
                         .collect::<Vec<_>>(),
                 )
             }),
+            uvs2: None,
             colors: cpu_mesh.colors.as_ref().map(|data| {
                 VertexBuffer::new_with_data(
                     context,
                     &data.iter().map(|c| c.to_linear_srgb()).collect::<Vec<_>>(),
                 )
             }),
+            morph_targets: None,
+        }
+    }
+
+    pub fn set_morph_targets(&mut self, morph_targets: &[MorphTarget]) {
+        self.morph_targets = if morph_targets.is_empty() {
+            None
+        } else {
+            Some(MorphTargets::new(
+                &self.context,
+                morph_targets,
+                self.positions.vertex_count(),
+            ))
+        };
+    }
+
+    pub fn morph_weights(&self) -> &[f32] {
+        self.morph_targets.as_ref().map_or(&[], |m| m.weights())
+    }
+
+    pub fn set_morph_weights(&mut self, weights: &[f32]) {
+        if let Some(morph_targets) = &mut self.morph_targets {
+            morph_targets.set_weights(weights);
+        } else {
+            assert!(
+                weights.is_empty(),
+                "There must be a weight for each morph target"
+            );
         }
     }
 
+    pub fn morph_aabb(&self, aabb: AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
+        self.morph_targets
+            .as_ref()
+            .map_or(aabb, |m| m.expand_aabb(aabb))
+    }
+
     pub fn draw(
         &self,
         program: &Program,
@@ -313,6 +376,7 @@ impl BaseMesh {
         attributes: FragmentAttributes,
     ) {
         self.use_attributes(program, attributes);
+        let clip_plane_count = self.use_clip_planes(program, camera);
         if let Some(index_buffer) = &self.indices {
             program.draw_elements(render_states, camera.viewport(), index_buffer)
         } else {
@@ -322,6 +386,9 @@ impl BaseMesh {
                 self.positions.vertex_count(),
             )
         }
+        if clip_plane_count > 0 {
+            self.context.set_clip_distances(0);
+        }
     }
 
     pub fn draw_instanced(
@@ -333,7 +400,7 @@ impl BaseMesh {
         instance_count: u32,
     ) {
         self.use_attributes(program, attributes);
-
+        let clip_plane_count = self.use_clip_planes(program, camera);
         if let Some(index_buffer) = &self.indices {
             program.draw_elements_instanced(
                 render_states,
@@ -349,6 +416,23 @@ impl BaseMesh {
                 instance_count,
             )
         }
+        if clip_plane_count > 0 {
+            self.context.set_clip_distances(0);
+        }
+    }
+
+    fn use_clip_planes(&self, program: &Program, camera: &Camera) -> u32 {
+        if !program.requires_uniform("clipPlanes") {
+            return 0;
+        }
+        let mut clip_planes = [vec4(0.0, 0.0, 0.0, 0.0); Camera::MAX_CLIP_PLANES];
+        let clip_plane_count = camera.clip_planes.len().min(Camera::MAX_CLIP_PLANES);
+        clip_planes[..clip_plane_count].copy_from_slice(&camera.clip_planes[..clip_plane_count]);
+        program.use_uniform_array("clipPlanes", &clip_planes);
+        if clip_plane_count > 0 {
+            self.context.set_clip_distances(clip_plane_count as u32);
+        }
+        clip_plane_count as u32
     }
 
     fn use_attributes(&self, program: &Program, attributes: FragmentAttributes) {
@@ -387,10 +471,27 @@ impl BaseMesh {
             );
         }
 
+        if attributes.uv2 {
+            program.use_vertex_attribute(
+                "uv2_coordinates",
+                self.uvs2.as_ref().unwrap_or_else(|| {
+                    panic!(
+                        "the material requires a second set of uv coordinates but the geometry did not provide it"
+                    )
+                }),
+            );
+        }
+
         if attributes.color {
             if let Some(colors) = &self.colors {
                 program.use_vertex_attribute("color", colors);
             }
         }
+
+        if let Some(morph_targets) = &self.morph_targets {
+            if program.requires_uniform("morphTargets") {
+                morph_targets.use_uniforms(program);
+            }
+        }
     }
 }
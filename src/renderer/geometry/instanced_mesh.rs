@@ -86,11 +86,15 @@ impl InstancedMesh {
     }
 
     fn update_aabb(&mut self) {
+        let (aabb_local, mesh_transformation) = (self.aabb_local, self.transformation);
+        let aabbs = parallel::map(&self.instances.transformations, |transformation| {
+            let mut aabb = aabb_local;
+            aabb.transform(&(transformation * mesh_transformation));
+            aabb
+        });
         let mut aabb = AxisAlignedBoundingBox::EMPTY;
-        for transformation in self.instances.transformations.iter() {
-            let mut aabb2 = self.aabb_local;
-            aabb2.transform(&(transformation * self.transformation));
-            aabb.expand_with_aabb(&aabb2);
+        for aabb2 in aabbs.iter() {
+            aabb.expand_with_aabb(aabb2);
         }
         self.aabb = aabb;
     }
@@ -103,14 +107,12 @@ impl InstancedMesh {
         let indices = if let Some(position) = camera.map(|c| *c.position()) {
             s.1 = position;
             // Need to order by using the position.
-            let distances = self
-                .instances
-                .transformations
-                .iter()
-                .map(|m| (self.transformation * m).w.truncate().distance2(position))
-                .collect::<Vec<_>>();
+            let transformation = self.transformation;
+            let distances = parallel::map(&self.instances.transformations, |m| {
+                (transformation * m).w.truncate().distance2(position)
+            });
             let mut indices = (0..self.instance_count() as usize).collect::<Vec<usize>>();
-            indices.sort_by(|a, b| {
+            parallel::sort_by(&mut indices, |a, b| {
                 distances[*b]
                     .partial_cmp(&distances[*a])
                     .unwrap_or(std::cmp::Ordering::Equal)
@@ -142,14 +144,18 @@ impl InstancedMesh {
                 ),
             );
         } else {
-            let mut row1 = Vec::new();
-            let mut row2 = Vec::new();
-            let mut row3 = Vec::new();
-            for transformation in indices.iter().map(|i| self.instances.transformations[*i]) {
-                row1.push(transformation.row(0));
-                row2.push(transformation.row(1));
-                row3.push(transformation.row(2));
-            }
+            let transformations = &self.instances.transformations;
+            let rows = parallel::map(&indices, |i| {
+                let transformation = transformations[*i];
+                (
+                    transformation.row(0),
+                    transformation.row(1),
+                    transformation.row(2),
+                )
+            });
+            let row1 = rows.iter().map(|r| r.0).collect::<Vec<_>>();
+            let row2 = rows.iter().map(|r| r.1).collect::<Vec<_>>();
+            let row3 = rows.iter().map(|r| r.2).collect::<Vec<_>>();
 
             instance_buffers.insert(
                 "row1".to_string(),
@@ -0,0 +1,457 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+use super::BaseMesh;
+
+#[derive(Clone, Copy, Debug)]
+struct BakedClip {
+    first_frame: u32,
+    frame_count: u32,
+    duration: f32,
+}
+
+///
+/// The [AnimationClip]s of a [Skeleton] sampled at a fixed number of frames per second and stored in a texture,
+/// so each instance of an [InstancedSkinnedMesh] can play an animation without computing the pose of the skeleton on the CPU.
+/// The animations are baked as looping animations. Cloning is cheap, since the data is shared between the clones.
+///
+#[derive(Clone)]
+pub struct BakedAnimations {
+    texture: Arc<Texture2D>,
+    matrices: Arc<Vec<Mat4>>,
+    clips: Vec<BakedClip>,
+    joint_count: u32,
+}
+
+impl BakedAnimations {
+    ///
+    /// Samples the given animations of the given skeleton with the given number of frames per second.
+    /// The vertices are interpolated between the frames, so a low number of frames per second is usually sufficient.
+    ///
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new(
+        context: &Context,
+        skeleton: &Skeleton,
+        clips: &[AnimationClip],
+        frames_per_second: f32,
+    ) -> Self {
+        let joint_count = skeleton.joints().len() as u32;
+        let mut matrices = Vec::new();
+        let mut baked_clips = Vec::new();
+        for clip in clips {
+            let frame_count = ((clip.duration * frames_per_second).ceil() as u32).max(1);
+            baked_clips.push(BakedClip {
+                first_frame: (matrices.len() as u32) / joint_count.max(1),
+                frame_count,
+                duration: clip.duration,
+            });
+            for frame in 0..frame_count {
+                let mut pose = skeleton.rest_pose();
+                clip.apply(frame as f32 * clip.duration / frame_count as f32, &mut pose);
+                matrices.extend(skeleton.skinning_matrices(&pose));
+            }
+        }
+        let frame_count = baked_clips
+            .iter()
+            .map(|c| c.frame_count)
+            .sum::<u32>()
+            .max(1);
+        let mut texture = Texture2D::new_empty::<[f32; 4]>(
+            context,
+            4 * joint_count.max(1),
+            frame_count,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        if joint_count > 0 && !matrices.is_empty() {
+            texture.fill(
+                &matrices
+                    .iter()
+                    .flat_map(|m| [m.x, m.y, m.z, m.w].map(|c| [c.x, c.y, c.z, c.w]))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Self {
+            texture: Arc::new(texture),
+            matrices: Arc::new(matrices),
+            clips: baked_clips,
+            joint_count,
+        }
+    }
+
+    ///
+    /// Returns the number of animations.
+    ///
+    pub fn clip_count(&self) -> u32 {
+        self.clips.len() as u32
+    }
+
+    ///
+    /// Returns the number of joints in the skeleton.
+    ///
+    pub fn joint_count(&self) -> u32 {
+        self.joint_count
+    }
+
+    fn frame(&self, frame: u32) -> &[Mat4] {
+        let start = (frame * self.joint_count) as usize;
+        &self.matrices[start..start + self.joint_count as usize]
+    }
+}
+
+///
+/// Defines the instances of an [InstancedSkinnedMesh].
+///
+#[derive(Clone, Debug, Default)]
+pub struct SkinnedInstances {
+    /// The transformations applied to each instance.
+    pub transformations: Vec<Mat4>,
+    /// The index of the animation in the [BakedAnimations] played by each instance.
+    pub animations: Vec<usize>,
+    /// The time in seconds at which the animation of each instance starts, which can be used to avoid that all instances move in sync.
+    pub time_offsets: Option<Vec<f32>>,
+    /// The playback speed of the animation of each instance, where one is normal speed.
+    pub speeds: Option<Vec<f32>>,
+}
+
+impl SkinnedInstances {
+    ///
+    /// Returns an error if the instances is not valid.
+    ///
+    pub fn validate(&self) -> Result<(), RendererError> {
+        let instance_count = self.count() as usize;
+        for (length, name) in [
+            (Some(self.animations.len()), "animations"),
+            (self.time_offsets.as_ref().map(|b| b.len()), "time offsets"),
+            (self.speeds.as_ref().map(|b| b.len()), "speeds"),
+        ] {
+            if let Some(length) = length {
+                if length < instance_count {
+                    Err(RendererError::InvalidBufferLength(
+                        name.to_string(),
+                        instance_count,
+                        length,
+                    ))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of instances.
+    pub fn count(&self) -> u32 {
+        self.transformations.len() as u32
+    }
+}
+
+///
+/// Similar to [SkinnedMesh], except it is possible to render many instances of the same mesh efficiently, for example a crowd of characters.
+/// Each instance plays one of the [BakedAnimations] with its own time offset and speed, which is evaluated entirely on the GPU.
+///
+pub struct InstancedSkinnedMesh {
+    context: Context,
+    base_mesh: BaseMesh,
+    joint_indices: VertexBuffer,
+    joint_weights: VertexBuffer,
+    animations: BakedAnimations,
+    clip_aabbs: Vec<AxisAlignedBoundingBox>,
+    instances: SkinnedInstances,
+    instance_buffers: Vec<(&'static str, InstanceBuffer)>,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    time: f32,
+}
+
+impl InstancedSkinnedMesh {
+    ///
+    /// Creates a new instanced skinned mesh from the given [CpuMesh] and the indices and weights of the (up to) four joints affecting each vertex, see [SkinnedMesh::new].
+    /// The joint indices refer to the joints of the skeleton used to bake the animations.
+    ///
+    pub fn new(
+        context: &Context,
+        instances: &SkinnedInstances,
+        cpu_mesh: &CpuMesh,
+        joint_indices: &[[u16; 4]],
+        joint_weights: &[Vec4],
+        animations: &BakedAnimations,
+    ) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        assert_eq!(
+            joint_indices.len(),
+            positions.len(),
+            "There must be joint indices for each vertex"
+        );
+        assert_eq!(
+            joint_weights.len(),
+            positions.len(),
+            "There must be joint weights for each vertex"
+        );
+        // The bounding box of the vertices affected by each joint in the bind pose
+        let mut joint_aabbs = vec![AxisAlignedBoundingBox::EMPTY; animations.joint_count as usize];
+        for ((position, indices), weights) in positions
+            .iter()
+            .zip(joint_indices.iter())
+            .zip(joint_weights.iter())
+        {
+            for (index, weight) in indices
+                .iter()
+                .zip([weights.x, weights.y, weights.z, weights.w])
+            {
+                if weight > 0.0 {
+                    assert!(
+                        (*index as u32) < animations.joint_count,
+                        "The joint indices must be smaller than the joint count"
+                    );
+                    joint_aabbs[*index as usize].expand(&[*position]);
+                }
+            }
+        }
+        // The bounding box of the mesh in all frames of each animation
+        let clip_aabbs = animations
+            .clips
+            .iter()
+            .map(|clip| {
+                let mut aabb = AxisAlignedBoundingBox::EMPTY;
+                for frame in clip.first_frame..clip.first_frame + clip.frame_count {
+                    for (joint_aabb, matrix) in joint_aabbs.iter().zip(animations.frame(frame)) {
+                        if !joint_aabb.is_empty() {
+                            let mut joint_aabb = *joint_aabb;
+                            joint_aabb.transform(matrix);
+                            aabb.expand_with_aabb(&joint_aabb);
+                        }
+                    }
+                }
+                aabb
+            })
+            .collect();
+        let mut mesh = Self {
+            context: context.clone(),
+            base_mesh: BaseMesh::new(context, cpu_mesh),
+            joint_indices: VertexBuffer::new_with_data(
+                context,
+                &joint_indices
+                    .iter()
+                    .map(|i| vec4(i[0] as f32, i[1] as f32, i[2] as f32, i[3] as f32))
+                    .collect::<Vec<_>>(),
+            ),
+            joint_weights: VertexBuffer::new_with_data(context, joint_weights),
+            animations: animations.clone(),
+            clip_aabbs,
+            instances: SkinnedInstances::default(),
+            instance_buffers: Vec::new(),
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            transformation: Mat4::identity(),
+            time: 0.0,
+        };
+        mesh.set_instances(instances);
+        mesh
+    }
+
+    ///
+    /// Returns the local to world transformation applied to all instances.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to all instances.
+    /// This is applied after the transformation of each instance.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+        self.update_aabb();
+    }
+
+    /// Returns the number of instances that is rendered.
+    pub fn instance_count(&self) -> u32 {
+        self.instances.count()
+    }
+
+    ///
+    /// Update the instances.
+    ///
+    pub fn set_instances(&mut self, instances: &SkinnedInstances) {
+        #[cfg(debug_assertions)]
+        instances.validate().expect("invalid instances");
+        for animation in instances.animations.iter() {
+            assert!(
+                *animation < self.clip_aabbs.len(),
+                "The animation {} does not exist",
+                animation
+            );
+        }
+        self.instances = instances.clone();
+        self.update_aabb();
+
+        let mut row1 = Vec::new();
+        let mut row2 = Vec::new();
+        let mut row3 = Vec::new();
+        let mut animation = Vec::new();
+        for i in 0..instances.count() as usize {
+            let transformation = instances.transformations[i];
+            row1.push(transformation.row(0));
+            row2.push(transformation.row(1));
+            row3.push(transformation.row(2));
+            let clip = self.animations.clips[instances.animations[i]];
+            let speed = instances.speeds.as_ref().map_or(1.0, |s| s[i]);
+            animation.push(vec4(
+                clip.first_frame as f32,
+                clip.frame_count as f32,
+                if clip.duration > 0.0 {
+                    speed * clip.frame_count as f32 / clip.duration
+                } else {
+                    0.0
+                },
+                instances.time_offsets.as_ref().map_or(0.0, |t| t[i]),
+            ));
+        }
+        self.instance_buffers = vec![
+            ("row1", InstanceBuffer::new_with_data(&self.context, &row1)),
+            ("row2", InstanceBuffer::new_with_data(&self.context, &row2)),
+            ("row3", InstanceBuffer::new_with_data(&self.context, &row3)),
+            (
+                "instance_animation",
+                InstanceBuffer::new_with_data(&self.context, &animation),
+            ),
+        ];
+    }
+
+    fn update_aabb(&mut self) {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for (transformation, animation) in self
+            .instances
+            .transformations
+            .iter()
+            .zip(self.instances.animations.iter())
+        {
+            let mut instance_aabb = self.clip_aabbs[*animation];
+            if !instance_aabb.is_empty() {
+                instance_aabb.transform(&(self.transformation * transformation));
+                aabb.expand_with_aabb(&instance_aabb);
+            }
+        }
+        self.aabb = aabb;
+    }
+}
+
+impl<'a> IntoIterator for &'a InstancedSkinnedMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for InstancedSkinnedMesh {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    fn animate(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_uniform("animationTime", self.time);
+        program.use_texture("jointMatrices", &self.animations.texture);
+        program.use_vertex_attribute("joint_indices", &self.joint_indices);
+        program.use_vertex_attribute("joint_weights", &self.joint_weights);
+        for (name, buffer) in self.instance_buffers.iter() {
+            program.use_instance_attribute(name, buffer);
+        }
+        self.base_mesh.draw_instanced(
+            program,
+            render_states,
+            camera,
+            attributes,
+            self.instance_count(),
+        );
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define USE_SKINNING\n#define USE_BAKED_ANIMATION\n#define USE_INSTANCE_TRANSFORMS\n{}{}{}{}{}{}",
+            if required_attributes.normal {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            if required_attributes.color && self.base_mesh.colors.is_some() {
+                "#define USE_VERTEX_COLORS\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/mesh.vert"),
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 14;
+        if required_attributes.normal {
+            id |= 0b1u16;
+        }
+        if required_attributes.tangents {
+            id |= 0b1u16 << 1;
+        }
+        if required_attributes.uv {
+            id |= 0b1u16 << 2;
+        }
+        if required_attributes.color && self.base_mesh.colors.is_some() {
+            id |= 0b1u16 << 3;
+        }
+        id
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
@@ -12,6 +12,7 @@ pub struct Mesh {
     aabb: AxisAlignedBoundingBox,
     transformation: Mat4,
     current_transformation: Mat4,
+    previous_transformation: Option<Mat4>,
     animation: Option<Box<dyn Fn(f32) -> Mat4 + Send + Sync>>,
 }
 
@@ -28,6 +29,7 @@ impl Mesh {
             aabb,
             transformation: Mat4::identity(),
             current_transformation: Mat4::identity(),
+            previous_transformation: None,
             animation: None,
         }
     }
@@ -78,6 +80,15 @@ impl Mesh {
         self.animation = Some(Box::new(animation));
     }
 
+    ///
+    /// Stores the current transformation, including the transformation from the animation, as the transformation in the previous frame.
+    /// The movement since the previous frame is used for per object motion blur, see [MotionBlurEffect::velocity_texture], so call this method after rendering each frame.
+    /// Until this method is called, the mesh is treated as not moving.
+    ///
+    pub fn update_previous_transformation(&mut self) {
+        self.previous_transformation = Some(self.current_transformation);
+    }
+
     ///
     /// Returns the number of vertices in this mesh.
     ///
@@ -114,6 +125,96 @@ impl Mesh {
             self.base_mesh.normals = Some(VertexBuffer::new_with_data(&self.context, normals));
         }
     }
+
+    ///
+    /// Updates the second set of uv coordinates of the mesh, which is for example used to look up a [PhysicalMaterial::lightmap_texture] or a texture with a [Texture2DRef::uv_channel] of 1.
+    /// Unlike the first set of uv coordinates, which is given in the [CpuMesh], the second set is only specified using this method.
+    /// For lightmaps, the uv coordinates should not overlap and there should be some space between the uv islands to avoid light bleeding between them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of uv coordinates does not match the number of vertices in the mesh.
+    pub fn update_uvs2(&mut self, uvs: &[Vec2]) {
+        if uvs.len() as u32 != self.vertex_count() {
+            panic!("Failed updating uv coordinates: The number of uv coordinates {} does not match the number of vertices {} in the mesh.", uvs.len(), self.vertex_count())
+        }
+        let uvs = uvs
+            .iter()
+            .map(|uv| vec2(uv.x, 1.0 - uv.y))
+            .collect::<Vec<_>>();
+        if let Some(uv_buffer) = &mut self.base_mesh.uvs2 {
+            uv_buffer.fill(&uvs);
+        } else {
+            self.base_mesh.uvs2 = Some(VertexBuffer::new_with_data(&self.context, &uvs));
+        }
+    }
+
+    ///
+    /// Sets the morph targets, also called blend shapes, of the mesh, which displace the vertices according to the weights given by [Self::set_morph_weights].
+    /// Initially, all the weights are zero, ie. the vertices are not displaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of offsets in a morph target does not match the number of vertices in the mesh.
+    pub fn set_morph_targets(&mut self, morph_targets: &[MorphTarget]) {
+        self.base_mesh.set_morph_targets(morph_targets);
+    }
+
+    ///
+    /// Returns the weight of each of the morph targets, see [Self::set_morph_targets].
+    ///
+    pub fn morph_weights(&self) -> &[f32] {
+        self.base_mesh.morph_weights()
+    }
+
+    ///
+    /// Sets the weight of each of the morph targets, see [Self::set_morph_targets]. Call this each frame to animate the morph targets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of weights does not match the number of morph targets.
+    pub fn set_morph_weights(&mut self, weights: &[f32]) {
+        self.base_mesh.set_morph_weights(weights);
+    }
+
+    ///
+    /// Bakes the light from the given lights into a lightmap texture with the given size, which can be assigned to [PhysicalMaterial::lightmap_texture].
+    /// The light is calculated for each texel of the lightmap at the corresponding position on the surface of this mesh given by the second set of uv coordinates (see [Mesh::update_uvs2]),
+    /// and contains the light reflected from a rough white surface, including the shadows from lights which have a shadow map.
+    /// Since the lighting is baked into the texture, the lights do not need to be evaluated for each pixel when rendering a static scene.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the second set of uv coordinates has not been specified or if the mesh has no normals.
+    ///
+    pub fn bake_lightmap(&self, texture_size: u32, lights: &[&dyn Light]) -> Texture2D {
+        let mut lightmap = Texture2D::new_empty::<[f16; 4]>(
+            &self.context,
+            texture_size,
+            texture_size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let camera = Camera::new_2d(Viewport::new_at_origo(texture_size, texture_size));
+        lightmap
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .write::<RendererError>(|| {
+                render_with_material(
+                    &self.context,
+                    &camera,
+                    LightmapGeometry { mesh: self },
+                    &LightmapMaterial,
+                    lights,
+                );
+                Ok(())
+            })
+            .unwrap();
+        lightmap
+    }
 }
 
 impl<'a> IntoIterator for &'a Mesh {
@@ -127,7 +228,7 @@ impl<'a> IntoIterator for &'a Mesh {
 
 impl Geometry for Mesh {
     fn aabb(&self) -> AxisAlignedBoundingBox {
-        let mut aabb = self.aabb;
+        let mut aabb = self.base_mesh.morph_aabb(self.aabb);
         aabb.transform(&self.current_transformation);
         aabb
     }
@@ -156,6 +257,15 @@ impl Geometry for Mesh {
 
         program.use_uniform("viewProjection", camera.projection() * camera.view());
         program.use_uniform("modelMatrix", self.current_transformation);
+        if program.requires_uniform("previousTransformation") {
+            // Transforms the current world position to the world position in the previous frame
+            program.use_uniform(
+                "previousTransformation",
+                self.previous_transformation
+                    .and_then(|previous| Some(previous * self.current_transformation.invert()?))
+                    .unwrap_or(Mat4::identity()),
+            );
+        }
 
         self.base_mesh
             .draw(program, render_states, camera, attributes);
@@ -163,7 +273,12 @@ impl Geometry for Mesh {
 
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         format!(
-            "{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}",
+            if self.base_mesh.morph_targets.is_some() {
+                "#define USE_MORPH_TARGETS\n"
+            } else {
+                ""
+            },
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -179,6 +294,11 @@ impl Geometry for Mesh {
             } else {
                 ""
             },
+            if required_attributes.uv2 {
+                "#define USE_UVS2\n"
+            } else {
+                ""
+            },
             if required_attributes.color && self.base_mesh.colors.is_some() {
                 "#define USE_VERTEX_COLORS\n"
             } else {
@@ -203,6 +323,12 @@ impl Geometry for Mesh {
         if required_attributes.color && self.base_mesh.colors.is_some() {
             id |= 0b1u16 << 3;
         }
+        if required_attributes.uv2 {
+            id |= 0b1u16 << 8;
+        }
+        if self.base_mesh.morph_targets.is_some() {
+            id |= 0b1u16 << 6;
+        }
         id
     }
 
@@ -234,3 +360,136 @@ impl Geometry for Mesh {
         )
     }
 }
+
+///
+/// Renders a mesh with the second set of uv coordinates as the position in the render target, so each fragment corresponds to a texel in the lightmap.
+///
+struct LightmapGeometry<'a> {
+    mesh: &'a Mesh,
+}
+
+impl Geometry for LightmapGeometry<'_> {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if let Some(inverse) = self.mesh.current_transformation.invert() {
+            program.use_uniform_if_required("normalMatrix", inverse.transpose());
+        } else {
+            return;
+        }
+        program.use_uniform("modelMatrix", self.mesh.current_transformation);
+        self.mesh.base_mesh.draw(
+            program,
+            render_states,
+            camera,
+            FragmentAttributes {
+                uv2: true,
+                ..attributes
+            },
+        );
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define USE_NORMALS\n#define USE_UVS2\n#define LIGHTMAP_BAKING\n{}{}",
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/mesh.vert"),
+        )
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b110u16
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.mesh.aabb()
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.mesh.context, camera, self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.mesh.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+///
+/// Calculates the light reflected from a rough white surface.
+///
+struct LightmapMaterial;
+
+impl Material for LightmapMaterial {
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        format!(
+            "{}
+            in vec3 pos;
+            in vec3 nor;
+
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                vec3 normal = normalize(nor);
+                outColor = vec4(calculate_lighting(pos + normal, vec3(1.0), pos, normal, 0.0, 1.0, 1.0), 1.0);
+            }}
+            ",
+            lights_shader_source(lights, LightingModel::Blinn)
+        )
+    }
+
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1001u32
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, lights: &[&dyn Light]) {
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,180 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A morph target, also called a blend shape, which displaces the vertices of a mesh, for example to animate facial expressions.
+/// Each vertex is displaced by the offsets of all the morph targets multiplied by the weight of each morph target, see [Mesh::set_morph_weights].
+///
+#[derive(Clone, Debug, Default)]
+pub struct MorphTarget {
+    /// The name of the morph target.
+    pub name: String,
+    /// The offset added to the position of each vertex.
+    pub positions: Vec<Vec3>,
+    /// The optional offset added to the normal of each vertex.
+    pub normals: Option<Vec<Vec3>>,
+    /// The optional offset added to the tangent of each vertex.
+    pub tangents: Option<Vec<Vec3>>,
+}
+
+///
+/// The morph targets of a mesh together with the initial weight of each morph target, see [Mesh::set_morph_targets] and [Mesh::set_morph_weights].
+///
+#[derive(Clone, Debug, Default)]
+pub struct CpuMorphTargets {
+    /// The morph targets.
+    pub targets: Vec<MorphTarget>,
+    /// The weight of each morph target.
+    pub weights: Vec<f32>,
+}
+
+///
+/// The morph targets of a mesh on the GPU.
+/// The offsets are stored in a texture array with a layer for each attribute of each morph target, which is looked up using the vertex id in the vertex shader.
+///
+pub(in crate::renderer) struct MorphTargets {
+    offsets: Texture2DArray,
+    weights_texture: Texture2D,
+    weights: Vec<f32>,
+    layers_per_target: u32,
+    bounds: Vec<(Vec3, Vec3)>,
+}
+
+impl MorphTargets {
+    pub fn new(context: &Context, morph_targets: &[MorphTarget], vertex_count: u32) -> Self {
+        for morph_target in morph_targets {
+            assert_eq!(
+                morph_target.positions.len() as u32,
+                vertex_count,
+                "There must be a position offset for each vertex in the morph target {}",
+                morph_target.name
+            );
+            for offsets in [&morph_target.normals, &morph_target.tangents]
+                .into_iter()
+                .flatten()
+            {
+                assert_eq!(
+                    offsets.len() as u32,
+                    vertex_count,
+                    "There must be an offset for each vertex in the morph target {}",
+                    morph_target.name
+                );
+            }
+        }
+        let layers_per_target = if morph_targets
+            .iter()
+            .any(|t| t.normals.is_some() || t.tangents.is_some())
+        {
+            3
+        } else {
+            1
+        };
+        let width = vertex_count.clamp(1, 4096);
+        let height = vertex_count.div_ceil(width).max(1);
+        let mut offsets = Texture2DArray::new_empty::<[f32; 4]>(
+            context,
+            width,
+            height,
+            (morph_targets.len() as u32 * layers_per_target).max(1),
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let layer_data = |values: Option<&Vec<Vec3>>| {
+            let mut data = vec![[0.0; 4]; (width * height) as usize];
+            if let Some(values) = values {
+                for (texel, value) in data.iter_mut().zip(values.iter()) {
+                    *texel = [value.x, value.y, value.z, 0.0];
+                }
+            }
+            data
+        };
+        let mut layer = 0;
+        for morph_target in morph_targets {
+            offsets.fill_layer(layer, &layer_data(Some(&morph_target.positions)));
+            if layers_per_target > 1 {
+                offsets.fill_layer(layer + 1, &layer_data(morph_target.normals.as_ref()));
+                offsets.fill_layer(layer + 2, &layer_data(morph_target.tangents.as_ref()));
+            }
+            layer += layers_per_target;
+        }
+        let bounds = morph_targets
+            .iter()
+            .map(|t| {
+                t.positions.iter().fold(
+                    (vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0)),
+                    |(min, max): (Vec3, Vec3), p| {
+                        (
+                            vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                            vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                        )
+                    },
+                )
+            })
+            .collect();
+        let mut morph_targets = Self {
+            offsets,
+            weights_texture: Texture2D::new_empty::<f32>(
+                context,
+                (morph_targets.len() as u32).max(1),
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            weights: Vec::new(),
+            layers_per_target,
+            bounds,
+        };
+        morph_targets.set_weights(&vec![0.0; morph_targets.bounds.len()]);
+        morph_targets
+    }
+
+    pub fn count(&self) -> u32 {
+        self.bounds.len() as u32
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn set_weights(&mut self, weights: &[f32]) {
+        assert_eq!(
+            weights.len(),
+            self.bounds.len(),
+            "There must be a weight for each morph target"
+        );
+        self.weights = weights.to_vec();
+        if !weights.is_empty() {
+            self.weights_texture.fill(weights);
+        }
+    }
+
+    ///
+    /// Expands the given bounding box of the vertices so it contains the vertices displaced by the morph targets with the current weights.
+    ///
+    pub fn expand_aabb(&self, aabb: AxisAlignedBoundingBox) -> AxisAlignedBoundingBox {
+        if aabb.is_empty() {
+            return aabb;
+        }
+        let mut min = aabb.min();
+        let mut max = aabb.max();
+        for ((lower, upper), weight) in self.bounds.iter().zip(self.weights.iter()) {
+            let (a, b) = (*lower * *weight, *upper * *weight);
+            min += vec3(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+            max += vec3(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+        }
+        AxisAlignedBoundingBox::new_with_positions(&[min, max])
+    }
+
+    pub fn use_uniforms(&self, program: &Program) {
+        program.use_texture_array("morphTargets", &self.offsets);
+        program.use_texture("morphWeights", &self.weights_texture);
+        program.use_uniform("morphTargetCount", self.count() as i32);
+        program.use_uniform("morphLayersPerTarget", self.layers_per_target as i32);
+    }
+}
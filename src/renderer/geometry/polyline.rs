@@ -0,0 +1,431 @@
+use crate::core::*;
+use crate::renderer::*;
+
+/// The number of triangles used for each round join or cap.
+const ROUND_TRIANGLES: usize = 8;
+
+///
+/// Defines how two connected segments of a [Polyline] are joined.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// The outer edges of the segments are extended until they meet, limited by [Polyline::miter_limit].
+    #[default]
+    Miter,
+    /// The segments are joined by a circle with a diameter equal to the width of the line.
+    Round,
+}
+
+///
+/// Defines how the ends of a [Polyline] are drawn.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// The line ends exactly at the end points.
+    #[default]
+    Butt,
+    /// The line is extended by half the width beyond the end points.
+    Square,
+    /// The line ends with a half circle centered at the end points.
+    Round,
+}
+
+///
+/// A line through a list of points in 3D with a width given in pixels, for example for wireframe overlays, graph edges or paths.
+///
+/// The line is expanded into quads in screen space in the vertex shader, so it has the same width no matter how far it is from the camera
+/// and does not depend on the line width support of the graphics driver, which is limited to one pixel almost everywhere.
+/// The line can consist of multiple unconnected strips, see [Polyline::new_with_segments].
+///
+/// The uv coordinates of the line, if required by the material, are the distance along the line in world space and a value from zero to one across the line.
+///
+pub struct Polyline {
+    context: Context,
+    vertex_buffer: VertexBuffer,
+    previous: InstanceBuffer,
+    starts: InstanceBuffer,
+    ends: InstanceBuffer,
+    next: InstanceBuffer,
+    distances: InstanceBuffer,
+    instance_count: u32,
+    strips: Vec<Vec<Vec3>>,
+    closed: bool,
+    dash: Option<(f32, f32)>,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    /// The width of the line in physical pixels.
+    pub width: f32,
+    /// How two connected segments of the line are joined.
+    pub join: LineJoin,
+    /// How the ends of the line are drawn.
+    pub cap: LineCap,
+    /// The maximum length of a miter join relative to half the width of the line, which avoids long spikes where the line turns sharply.
+    pub miter_limit: f32,
+}
+
+impl Polyline {
+    ///
+    /// Constructs a new line through the given points with the given width in physical pixels.
+    ///
+    pub fn new(context: &Context, points: &[Vec3], width: f32) -> Self {
+        Self::new_with_strips(context, vec![points.to_vec()], width)
+    }
+
+    ///
+    /// Constructs a new line consisting of the given unconnected segments with the given width in physical pixels, for example the edges of a graph or a wireframe.
+    ///
+    pub fn new_with_segments(context: &Context, segments: &[[Vec3; 2]], width: f32) -> Self {
+        Self::new_with_strips(
+            context,
+            segments.iter().map(|segment| segment.to_vec()).collect(),
+            width,
+        )
+    }
+
+    fn new_with_strips(context: &Context, strips: Vec<Vec<Vec3>>, width: f32) -> Self {
+        let mut template = vec![
+            vec3(0.0, -1.0, 0.0),
+            vec3(1.0, -1.0, 0.0),
+            vec3(1.0, 1.0, 0.0),
+            vec3(1.0, 1.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(0.0, -1.0, 0.0),
+        ];
+        // Half circles at both ends which are only used for round joins and caps, otherwise they are collapsed in the vertex shader
+        for end in [0.0, 1.0] {
+            for i in 0..ROUND_TRIANGLES {
+                let angle0 = std::f32::consts::PI * i as f32 / ROUND_TRIANGLES as f32;
+                let angle1 = std::f32::consts::PI * (i + 1) as f32 / ROUND_TRIANGLES as f32;
+                template.push(vec3(end, 0.0, 0.0));
+                template.push(vec3(end, angle0.cos(), angle0.sin()));
+                template.push(vec3(end, angle1.cos(), angle1.sin()));
+            }
+        }
+        let mut polyline = Self {
+            context: context.clone(),
+            vertex_buffer: VertexBuffer::new_with_data(context, &template),
+            previous: InstanceBuffer::new(context),
+            starts: InstanceBuffer::new(context),
+            ends: InstanceBuffer::new(context),
+            next: InstanceBuffer::new(context),
+            distances: InstanceBuffer::new(context),
+            instance_count: 0,
+            strips,
+            closed: false,
+            dash: None,
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            transformation: Mat4::identity(),
+            width,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+        };
+        polyline.update();
+        polyline
+    }
+
+    ///
+    /// Set the points of the line. This replaces all strips of the line with a single strip through the given points.
+    ///
+    pub fn set_points(&mut self, points: &[Vec3]) {
+        self.strips = vec![points.to_vec()];
+        self.update();
+    }
+
+    ///
+    /// Set the segments of the line. This replaces all strips of the line with the given unconnected segments.
+    ///
+    pub fn set_segments(&mut self, segments: &[[Vec3; 2]]) {
+        self.strips = segments.iter().map(|segment| segment.to_vec()).collect();
+        self.update();
+    }
+
+    ///
+    /// Returns whether the last point of each strip of the line is connected to the first point.
+    ///
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    ///
+    /// Set whether the last point of each strip of the line is connected to the first point.
+    ///
+    pub fn set_closed(&mut self, closed: bool) {
+        self.closed = closed;
+        self.update();
+    }
+
+    ///
+    /// Returns the dash pattern of the line, see [Polyline::set_dash].
+    ///
+    pub fn dash(&self) -> Option<(f32, f32)> {
+        self.dash
+    }
+
+    ///
+    /// Set the dash pattern of the line given as the length of each dash and the length of each gap in world space, or `None` for a solid line.
+    /// The pattern starts over at the beginning of each strip of the line.
+    ///
+    pub fn set_dash(&mut self, dash: Option<(f32, f32)>) {
+        self.dash = dash;
+        self.update();
+    }
+
+    ///
+    /// Returns the local to world transformation applied to the line.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to the line.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    fn update(&mut self) {
+        let mut previous = Vec::new();
+        let mut starts = Vec::new();
+        let mut ends = Vec::new();
+        let mut next = Vec::new();
+        let mut distances = Vec::new();
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for strip in self.strips.iter() {
+            aabb.expand(strip);
+            let closed = self.closed && strip.len() > 2;
+            let runs = match self.dash {
+                Some((dash, gap)) if dash > 0.0 && gap > 0.0 => {
+                    dash_strip(strip, closed, dash, gap)
+                }
+                _ => {
+                    let mut points = strip.clone();
+                    let mut distances = vec![0.0];
+                    for i in 1..points.len() {
+                        distances.push(distances[i - 1] + (points[i] - points[i - 1]).magnitude());
+                    }
+                    if closed {
+                        points.push(points[0]);
+                        distances.push(
+                            distances.last().unwrap()
+                                + (points[0] - strip.last().unwrap()).magnitude(),
+                        );
+                    }
+                    vec![(points, distances, closed)]
+                }
+            };
+            for (points, run_distances, closed) in runs {
+                // The points of a closed run ends with the first point, so the neighbours wrap around past it
+                let segment_count = points.len().saturating_sub(1);
+                for i in 0..segment_count {
+                    let previous_point = if i > 0 {
+                        Some(points[i - 1])
+                    } else if closed {
+                        Some(points[segment_count - 1])
+                    } else {
+                        None
+                    };
+                    let next_point = if i + 2 < points.len() {
+                        Some(points[i + 2])
+                    } else if closed {
+                        Some(points[1])
+                    } else {
+                        None
+                    };
+                    previous
+                        .push(previous_point.map_or(vec4(0.0, 0.0, 0.0, 0.0), |p| p.extend(1.0)));
+                    starts.push(points[i]);
+                    ends.push(points[i + 1]);
+                    next.push(next_point.map_or(vec4(0.0, 0.0, 0.0, 0.0), |p| p.extend(1.0)));
+                    distances.push(vec2(run_distances[i], run_distances[i + 1]));
+                }
+            }
+        }
+        self.instance_count = starts.len() as u32;
+        self.previous.fill(&previous);
+        self.starts.fill(&starts);
+        self.ends.fill(&ends);
+        self.next.fill(&next);
+        self.distances.fill(&distances);
+        self.aabb = aabb;
+    }
+}
+
+///
+/// Splits the strip into runs of points which are visible according to the dash pattern,
+/// each with the distance along the strip at each point and whether the run is closed.
+///
+fn dash_strip(
+    strip: &[Vec3],
+    closed: bool,
+    dash: f32,
+    gap: f32,
+) -> Vec<(Vec<Vec3>, Vec<f32>, bool)> {
+    let mut points = strip.to_vec();
+    if closed {
+        points.push(strip[0]);
+    }
+    let period = dash + gap;
+    let mut runs = Vec::new();
+    let mut run: Option<(Vec<Vec3>, Vec<f32>)> = None;
+    let mut distance = 0.0;
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let length = (b - a).magnitude();
+        let mut t = 0.0;
+        while t < length {
+            let position_in_period = (distance + t).rem_euclid(period);
+            let in_dash = position_in_period < dash;
+            let remaining = if in_dash {
+                dash - position_in_period
+            } else {
+                period - position_in_period
+            };
+            let step = remaining.min(length - t).max(length * 1e-5);
+            if in_dash {
+                let (run_points, run_distances) = run
+                    .get_or_insert_with(|| (vec![a + (b - a) * (t / length)], vec![distance + t]));
+                run_points.push(a + (b - a) * ((t + step) / length).min(1.0));
+                run_distances.push(distance + t + step);
+            } else if let Some((run_points, run_distances)) = run.take() {
+                runs.push((run_points, run_distances, false));
+            }
+            t += step;
+        }
+        distance += length;
+    }
+    if let Some((run_points, run_distances)) = run {
+        runs.push((run_points, run_distances, false));
+    }
+    runs
+}
+
+impl<'a> IntoIterator for &'a Polyline {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Polyline {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if attributes.tangents {
+            panic!("the material requires tangent attributes but a polyline does not provide it")
+        }
+        if self.instance_count == 0 {
+            return;
+        }
+        let viewport = camera.viewport();
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_uniform(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_uniform("halfWidth", 0.5 * self.width);
+        program.use_uniform(
+            "join",
+            match self.join {
+                LineJoin::Miter => 0,
+                LineJoin::Round => 1,
+            },
+        );
+        program.use_uniform(
+            "cap",
+            match self.cap {
+                LineCap::Butt => 0,
+                LineCap::Square => 1,
+                LineCap::Round => 2,
+            },
+        );
+        program.use_uniform("miterLimit", self.miter_limit.max(1.0));
+        program.use_uniform_if_required("cameraPosition", camera.lighting_position());
+        program.use_vertex_attribute("position", &self.vertex_buffer);
+        program.use_instance_attribute("instance_previous", &self.previous);
+        program.use_instance_attribute("instance_start", &self.starts);
+        program.use_instance_attribute("instance_end", &self.ends);
+        program.use_instance_attribute("instance_next", &self.next);
+        if program.requires_attribute("instance_distance") {
+            program.use_instance_attribute("instance_distance", &self.distances);
+        }
+        program.draw_arrays_instanced(
+            render_states,
+            viewport,
+            self.vertex_buffer.vertex_count(),
+            self.instance_count,
+        );
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "{}{}{}",
+            if required_attributes.normal {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            include_str!("shaders/polyline.vert"),
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 10;
+        if required_attributes.normal {
+            id |= 0b1u16;
+        }
+        if required_attributes.uv {
+            id |= 0b1u16 << 1;
+        }
+        id
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        if !aabb.is_empty() {
+            aabb.transform(&self.transformation);
+        }
+        aabb
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, &self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
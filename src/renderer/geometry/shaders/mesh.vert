@@ -1,4 +1,10 @@
 
+#if !defined(GL_ES) || defined(GL_EXT_clip_cull_distance)
+#define USE_CLIP_PLANES
+uniform vec4 clipPlanes[8];
+out float gl_ClipDistance[8];
+#endif
+
 uniform mat4 viewProjection;
 uniform mat4 modelMatrix;
 in vec3 position;
@@ -20,6 +26,52 @@ in vec4 row2;
 in vec4 row3;
 #endif
 
+#ifdef USE_SKINNING
+uniform sampler2D jointMatrices;
+in vec4 joint_indices;
+in vec4 joint_weights;
+
+#ifdef USE_BAKED_ANIMATION
+uniform float animationTime;
+// The first frame, the number of frames, the frames per second and the time offset of the animation of each instance
+in vec4 instance_animation;
+#endif
+
+mat4 joint_matrix(int frame, float index)
+{
+    int j = 4 * int(index + 0.5);
+    // The rows are flipped when filling the texture
+    int row = textureSize(jointMatrices, 0).y - 1 - frame;
+    return mat4(texelFetch(jointMatrices, ivec2(j, row), 0),
+                texelFetch(jointMatrices, ivec2(j + 1, row), 0),
+                texelFetch(jointMatrices, ivec2(j + 2, row), 0),
+                texelFetch(jointMatrices, ivec2(j + 3, row), 0));
+}
+
+mat4 skinning_matrix(int frame)
+{
+    return joint_weights.x * joint_matrix(frame, joint_indices.x)
+        + joint_weights.y * joint_matrix(frame, joint_indices.y)
+        + joint_weights.z * joint_matrix(frame, joint_indices.z)
+        + joint_weights.w * joint_matrix(frame, joint_indices.w);
+}
+#endif
+
+#ifdef USE_MORPH_TARGETS
+uniform sampler2DArray morphTargets;
+uniform sampler2D morphWeights;
+uniform int morphTargetCount;
+uniform int morphLayersPerTarget;
+
+vec3 morph_offset(int target, int attribute)
+{
+    ivec3 size = textureSize(morphTargets, 0);
+    // The rows are flipped when filling the texture
+    ivec2 texel = ivec2(gl_VertexID % size.x, size.y - 1 - gl_VertexID / size.x);
+    return texelFetch(morphTargets, ivec3(texel, target * morphLayersPerTarget + attribute), 0).xyz;
+}
+#endif
+
 out vec3 pos;
 
 #ifdef USE_NORMALS 
@@ -45,6 +97,11 @@ in vec2 uv_coordinates;
 out vec2 uvs;
 #endif
 
+#ifdef USE_UVS2
+in vec2 uv2_coordinates;
+out vec2 uvs2;
+#endif
+
 #ifdef USE_VERTEX_COLORS 
 in vec4 color;
 #endif
@@ -56,6 +113,31 @@ out vec4 col;
 
 void main()
 {
+    // *** MORPH TARGETS ***
+    vec3 localPosition = position;
+#ifdef USE_NORMALS
+    vec3 localNormal = normal;
+#ifdef USE_TANGENTS
+    vec3 localTangent = tangent.xyz;
+#endif
+#endif
+#ifdef USE_MORPH_TARGETS
+    for (int i = 0; i < morphTargetCount; i++) {
+        float weight = texelFetch(morphWeights, ivec2(i, 0), 0).r;
+        if (weight != 0.0) {
+            localPosition += weight * morph_offset(i, 0);
+            if (morphLayersPerTarget > 1) {
+#ifdef USE_NORMALS
+                localNormal += weight * morph_offset(i, 1);
+#ifdef USE_TANGENTS
+                localTangent += weight * morph_offset(i, 2);
+#endif
+#endif
+            }
+        }
+    }
+#endif
+
     // *** POSITION ***
     mat4 local2World = modelMatrix;
     
@@ -68,7 +150,21 @@ void main()
     local2World *= transform;
 #endif
 
-    vec4 worldPosition = local2World * vec4(position, 1.);
+#ifdef USE_SKINNING
+#ifdef USE_BAKED_ANIMATION
+    float frame = mod((animationTime - instance_animation.w) * instance_animation.z, instance_animation.y);
+    int frame_count = int(instance_animation.y + 0.5);
+    int frame0 = min(int(frame), frame_count - 1);
+    int frame1 = (frame0 + 1) % frame_count;
+    int first_frame = int(instance_animation.x + 0.5);
+    float t = frame - float(frame0);
+    local2World *= (1.0 - t) * skinning_matrix(first_frame + frame0) + t * skinning_matrix(first_frame + frame1);
+#else
+    local2World *= skinning_matrix(0);
+#endif
+#endif
+
+    vec4 worldPosition = local2World * vec4(localPosition, 1.);
     worldPosition /= worldPosition.w;
 #ifdef PARTICLES
     worldPosition.xyz += start_position + start_velocity * time + 0.5 * acceleration * time * time;
@@ -76,21 +172,31 @@ void main()
 #ifdef USE_INSTANCE_TRANSLATIONS 
     worldPosition.xyz += instance_translation;
 #endif
+#ifdef LIGHTMAP_BAKING
+    gl_Position = vec4(uv2_coordinates * 2.0 - 1.0, 0.0, 1.0);
+#else
     gl_Position = viewProjection * worldPosition;
+#endif
+
+#ifdef USE_CLIP_PLANES
+    for (int i = 0; i < 8; i++) {
+        gl_ClipDistance[i] = dot(clipPlanes[i], vec4(worldPosition.xyz, 1.0));
+    }
+#endif
 
     pos = worldPosition.xyz;
 
     // *** NORMAL ***
 #ifdef USE_NORMALS 
-#ifdef USE_INSTANCE_TRANSFORMS
+#if defined(USE_INSTANCE_TRANSFORMS) || defined(USE_SKINNING)
     mat3 normalMat = mat3(transpose(inverse(local2World)));
 #else
     mat3 normalMat = mat3(normalMatrix);
 #endif
-    nor = normalize(normalMat * normal);
+    nor = normalize(normalMat * localNormal);
 
 #ifdef USE_TANGENTS 
-    tang = normalize(normalMat * tangent.xyz);
+    tang = normalize(normalMat * localTangent);
     bitang = normalize(cross(nor, tang) * tangent.w);
 #endif
 
@@ -109,6 +215,10 @@ void main()
 #endif
 #endif
 
+#ifdef USE_UVS2
+    uvs2 = uv2_coordinates;
+#endif
+
     // *** COLOR ***
     col = vec4(1.0);
 #ifdef USE_VERTEX_COLORS 
@@ -0,0 +1,121 @@
+uniform mat4 viewProjection;
+uniform mat4 modelMatrix;
+uniform vec2 viewportSize;
+uniform float halfWidth;
+uniform int join;
+uniform int cap;
+uniform float miterLimit;
+
+in vec3 position;
+in vec4 instance_previous;
+in vec3 instance_start;
+in vec3 instance_end;
+in vec4 instance_next;
+
+out vec3 pos;
+out vec4 col;
+
+#ifdef USE_NORMALS
+uniform vec3 cameraPosition;
+out vec3 nor;
+#endif
+
+#ifdef USE_UVS
+in vec2 instance_distance;
+out vec2 uvs;
+#endif
+
+vec4 to_clip_space(vec3 p)
+{
+    return viewProjection * modelMatrix * vec4(p, 1.0);
+}
+
+// The distance to the near plane in clip space, which is negative behind the near plane
+float near_distance(vec4 p)
+{
+    return p.z + p.w;
+}
+
+vec2 to_screen_space(vec4 p)
+{
+    return 0.5 * viewportSize * p.xy / p.w;
+}
+
+void main()
+{
+    bool at_end = position.x > 0.5;
+    vec3 world_start = (modelMatrix * vec4(instance_start, 1.0)).xyz;
+    vec3 world_end = (modelMatrix * vec4(instance_end, 1.0)).xyz;
+    pos = at_end ? world_end : world_start;
+    col = vec4(1.0);
+#ifdef USE_NORMALS
+    nor = normalize(cameraPosition - pos);
+#endif
+#ifdef USE_UVS
+    uvs = vec2(at_end ? instance_distance.y : instance_distance.x, 0.5 + 0.5 * position.y);
+#endif
+
+    vec4 clip_start = to_clip_space(instance_start);
+    vec4 clip_end = to_clip_space(instance_end);
+    float d_start = near_distance(clip_start);
+    float d_end = near_distance(clip_end);
+    if (d_start < 0.0 && d_end < 0.0) {
+        // The segment is behind the camera, so it is placed outside the clip volume
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+    // Clips the segment against the near plane, so the end points behind the camera do not flip the direction of the segment
+    if (d_start < 0.0) {
+        clip_start = mix(clip_start, clip_end, d_start / (d_start - d_end));
+    } else if (d_end < 0.0) {
+        clip_end = mix(clip_start, clip_end, d_start / (d_start - d_end));
+    }
+
+    vec2 screen_start = to_screen_space(clip_start);
+    vec2 screen_end = to_screen_space(clip_end);
+    vec2 direction = screen_end - screen_start;
+    direction = dot(direction, direction) > 1e-10 ? normalize(direction) : vec2(1.0, 0.0);
+    vec2 normal = vec2(-direction.y, direction.x);
+
+    vec4 clip_position = at_end ? clip_end : clip_start;
+    vec2 screen_position = at_end ? screen_end : screen_start;
+    vec2 outward = at_end ? direction : -direction;
+    vec4 neighbour = at_end ? instance_next : instance_previous;
+    bool has_neighbour = neighbour.w > 0.5 && (at_end ? d_end >= 0.0 : d_start >= 0.0);
+    vec2 neighbour_normal = normal;
+    if (has_neighbour) {
+        vec4 clip_neighbour = to_clip_space(neighbour.xyz);
+        if (near_distance(clip_neighbour) > 0.0) {
+            vec2 neighbour_direction = at_end ? to_screen_space(clip_neighbour) - screen_position : screen_position - to_screen_space(clip_neighbour);
+            if (dot(neighbour_direction, neighbour_direction) > 1e-10) {
+                neighbour_direction = normalize(neighbour_direction);
+                neighbour_normal = vec2(-neighbour_direction.y, neighbour_direction.x);
+            }
+        }
+    }
+
+    // The template vertex is given as an offset across the line and an offset outwards from the end point, relative to half the width
+    float across = position.y;
+    float along = position.z;
+    bool is_round_vertex = along > 0.0;
+    vec2 offset = normal * across;
+    if (has_neighbour) {
+        if (join == 1) {
+            offset += outward * along;
+        } else if (!is_round_vertex) {
+            // Both segments at the join move their corners to the same miter point, so there is no gap between them
+            vec2 miter = normal + neighbour_normal;
+            if (dot(miter, miter) > 1e-6) {
+                miter = normalize(miter);
+                offset = miter * across / max(dot(miter, normal), 1.0 / miterLimit);
+            }
+        }
+    } else if (cap == 1) {
+        offset += outward;
+    } else if (cap == 2) {
+        offset += outward * along;
+    }
+
+    screen_position += offset * halfWidth;
+    gl_Position = vec4(screen_position / (0.5 * viewportSize) * clip_position.w, clip_position.zw);
+}
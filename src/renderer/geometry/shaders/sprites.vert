@@ -12,9 +12,41 @@ out vec2 uvs;
 out vec4 col;
 out vec3 pos;
 
+#ifdef USE_SIZES
+in vec2 size;
+#endif
+
+#ifdef USE_SPRITE_SHEET
+uniform vec2 sheetSize;
+uniform float frameCount;
+uniform float frame;
+uniform int looping;
+#ifdef USE_FRAME_OFFSETS
+in float frame_offset;
+#endif
+#endif
+
+#ifdef USE_NORMALS
+out vec3 nor;
+#endif
+
+#ifdef USE_TANGENTS
+out vec3 tang;
+out vec3 bitang;
+#endif
+
 void main()
 {
     uvs = uv_coordinate;
+#ifdef USE_SPRITE_SHEET
+    float f = frame;
+#ifdef USE_FRAME_OFFSETS
+    f += frame_offset;
+#endif
+    f = looping == 1 ? mod(floor(f), frameCount) : clamp(floor(f), 0.0, frameCount - 1.0);
+    vec2 cell = vec2(mod(f, sheetSize.x), floor(f / sheetSize.x));
+    uvs = (cell + uv_coordinate) / sheetSize;
+#endif
     col = vec4(1.0);
 
     vec3 z = normalize(eye - center);
@@ -35,7 +67,22 @@ void main()
                 y, 0.0,
                 z, 0.0,
                 center.x, center.y, center.z, 1.0);
-    vec4 world_pos = instanced_transform * transformation * vec4(position, 1.);
+    vec3 p = position;
+#ifdef USE_SIZES
+    p.xy *= 0.5 * size;
+#endif
+    mat4 local_to_world = instanced_transform * transformation;
+    vec4 world_pos = local_to_world * vec4(p, 1.);
     pos = world_pos.xyz / world_pos.w;
     gl_Position = viewProjection * world_pos;
+
+#ifdef USE_NORMALS
+    vec3 tangent = normalize((local_to_world * vec4(1.0, 0.0, 0.0, 0.0)).xyz);
+    vec3 bitangent = normalize((local_to_world * vec4(0.0, 1.0, 0.0, 0.0)).xyz);
+    nor = normalize(cross(tangent, bitangent));
+#ifdef USE_TANGENTS
+    tang = tangent;
+    bitang = cross(nor, tang);
+#endif
+#endif
 }
@@ -0,0 +1,422 @@
+use crate::renderer::*;
+
+///
+/// A 2D path consisting of one or more sub paths, each a sequence of connected straight lines and curves.
+/// The path can be turned into a filled or stroked [Shape2D] which can be rendered using a camera created by [Camera::new_2d].
+/// The curves are approximated by straight lines when added to the path.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Path2D {
+    sub_paths: Vec<(Vec<Vec2>, bool)>,
+}
+
+impl Path2D {
+    ///
+    /// Creates a new empty path.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Creates a new path containing a closed rectangle with the given center, rotation and size.
+    ///
+    pub fn rectangle(
+        center: impl Into<PhysicalPoint>,
+        rotation: impl Into<Radians>,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        let center: Vec2 = center.into().into();
+        let rotation = Mat2::from_angle(rotation.into());
+        let corners = [
+            vec2(-0.5 * width, -0.5 * height),
+            vec2(0.5 * width, -0.5 * height),
+            vec2(0.5 * width, 0.5 * height),
+            vec2(-0.5 * width, 0.5 * height),
+        ];
+        Self::polygon(corners.map(|c| center + rotation * c))
+    }
+
+    ///
+    /// Creates a new path containing a closed circle with the given center and radius.
+    ///
+    pub fn circle(center: impl Into<PhysicalPoint>, radius: f32) -> Self {
+        let center: Vec2 = center.into().into();
+        let segment_count = curve_segment_count(std::f32::consts::TAU * radius).max(16);
+        Self::polygon((0..segment_count).map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / segment_count as f32;
+            center + radius * vec2(angle.cos(), angle.sin())
+        }))
+    }
+
+    ///
+    /// Creates a new path containing a closed polygon with the given corners.
+    ///
+    pub fn polygon(corners: impl IntoIterator<Item = impl Into<PhysicalPoint>>) -> Self {
+        let mut path = Self::new();
+        for (i, corner) in corners.into_iter().enumerate() {
+            if i == 0 {
+                path.move_to(corner);
+            } else {
+                path.line_to(corner);
+            }
+        }
+        path.close();
+        path
+    }
+
+    ///
+    /// Starts a new sub path at the given point.
+    ///
+    pub fn move_to(&mut self, point: impl Into<PhysicalPoint>) -> &mut Self {
+        self.sub_paths.push((vec![point.into().into()], false));
+        self
+    }
+
+    ///
+    /// Adds a straight line from the current point to the given point.
+    ///
+    pub fn line_to(&mut self, point: impl Into<PhysicalPoint>) -> &mut Self {
+        let point = point.into().into();
+        self.current_sub_path().push(point);
+        self
+    }
+
+    ///
+    /// Adds a quadratic Bézier curve from the current point to the given point.
+    ///
+    pub fn quadratic_to(
+        &mut self,
+        control: impl Into<PhysicalPoint>,
+        point: impl Into<PhysicalPoint>,
+    ) -> &mut Self {
+        let p0 = self.current_point();
+        let p1: Vec2 = control.into().into();
+        let p2: Vec2 = point.into().into();
+        let segment_count = curve_segment_count((p1 - p0).magnitude() + (p2 - p1).magnitude());
+        let sub_path = self.current_sub_path();
+        for i in 1..=segment_count {
+            let t = i as f32 / segment_count as f32;
+            let s = 1.0 - t;
+            sub_path.push(s * s * p0 + 2.0 * s * t * p1 + t * t * p2);
+        }
+        self
+    }
+
+    ///
+    /// Adds a cubic Bézier curve from the current point to the given point.
+    ///
+    pub fn cubic_to(
+        &mut self,
+        control0: impl Into<PhysicalPoint>,
+        control1: impl Into<PhysicalPoint>,
+        point: impl Into<PhysicalPoint>,
+    ) -> &mut Self {
+        let p0 = self.current_point();
+        let p1: Vec2 = control0.into().into();
+        let p2: Vec2 = control1.into().into();
+        let p3: Vec2 = point.into().into();
+        let segment_count = curve_segment_count(
+            (p1 - p0).magnitude() + (p2 - p1).magnitude() + (p3 - p2).magnitude(),
+        );
+        let sub_path = self.current_sub_path();
+        for i in 1..=segment_count {
+            let t = i as f32 / segment_count as f32;
+            let s = 1.0 - t;
+            sub_path.push(
+                s * s * s * p0 + 3.0 * s * s * t * p1 + 3.0 * s * t * t * p2 + t * t * t * p3,
+            );
+        }
+        self
+    }
+
+    ///
+    /// Closes the current sub path by connecting the current point with the first point of the sub path.
+    ///
+    pub fn close(&mut self) -> &mut Self {
+        if let Some(sub_path) = self.sub_paths.last_mut() {
+            sub_path.1 = true;
+        }
+        self
+    }
+
+    ///
+    /// Returns a triangle mesh covering the inside of each sub path, where each sub path is filled as if it was closed.
+    /// The sub paths are filled independently, so a sub path inside another does not create a hole.
+    ///
+    pub fn fill(&self) -> CpuMesh {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        for (points, _) in self.sub_paths.iter() {
+            let points = remove_duplicates(points, true);
+            let offset = positions.len() as u32;
+            indices.extend(triangulate(&points).into_iter().map(|i| offset + i));
+            positions.extend(points);
+        }
+        mesh_2d(positions, indices)
+    }
+
+    ///
+    /// Returns a triangle mesh covering a line with the given width along each sub path.
+    /// The lines are joined with a miter, or a bevel if the angle between two lines is sharp, and the ends of open sub paths are cut off at the end points.
+    ///
+    pub fn stroke(&self, width: f32) -> CpuMesh {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        for (points, closed) in self.sub_paths.iter() {
+            stroke(
+                &remove_duplicates(points, *closed),
+                *closed,
+                0.5 * width,
+                &mut positions,
+                &mut indices,
+            );
+        }
+        mesh_2d(positions, indices)
+    }
+
+    fn current_point(&self) -> Vec2 {
+        self.sub_paths
+            .last()
+            .and_then(|(points, _)| points.last().copied())
+            .unwrap_or(vec2(0.0, 0.0))
+    }
+
+    fn current_sub_path(&mut self) -> &mut Vec<Vec2> {
+        if self.sub_paths.last().is_none_or(|(_, closed)| *closed) {
+            let start = self.current_point();
+            self.sub_paths.push((vec![start], false));
+        }
+        &mut self.sub_paths.last_mut().unwrap().0
+    }
+}
+
+/// Returns the number of straight lines used to approximate a curve with the given approximate length.
+fn curve_segment_count(length: f32) -> u32 {
+    ((length / 4.0).ceil() as u32).clamp(1, 128)
+}
+
+fn remove_duplicates(points: &[Vec2], closed: bool) -> Vec<Vec2> {
+    let mut result: Vec<Vec2> = Vec::with_capacity(points.len());
+    for p in points {
+        if result
+            .last()
+            .is_none_or(|last| (last - p).magnitude2() > 1e-12)
+        {
+            result.push(*p);
+        }
+    }
+    if closed && result.len() > 1 && (result[0] - result[result.len() - 1]).magnitude2() <= 1e-12 {
+        result.pop();
+    }
+    result
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+///
+/// Triangulates a simple polygon using ear clipping.
+///
+fn triangulate(points: &[Vec2]) -> Vec<u32> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+    let area: f32 = (0..points.len())
+        .map(|i| cross(points[i], points[(i + 1) % points.len()]))
+        .sum();
+    // The remaining corners in counter clockwise order
+    let mut remaining = (0..points.len() as u32).collect::<Vec<_>>();
+    if area < 0.0 {
+        remaining.reverse();
+    }
+    let mut indices = Vec::with_capacity(3 * (points.len() - 2));
+    let mut i = 0;
+    let mut attempts = 0;
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let (i0, i1, i2) = (
+            remaining[(i + n - 1) % n],
+            remaining[i % n],
+            remaining[(i + 1) % n],
+        );
+        let (a, b, c) = (
+            points[i0 as usize],
+            points[i1 as usize],
+            points[i2 as usize],
+        );
+        let is_convex = cross(b - a, c - b) > 0.0;
+        let is_ear = is_convex
+            && remaining.iter().all(|&j| {
+                let p = points[j as usize];
+                j == i0
+                    || j == i1
+                    || j == i2
+                    || cross(b - a, p - a) < 0.0
+                    || cross(c - b, p - b) < 0.0
+                    || cross(a - c, p - c) < 0.0
+            });
+        // If no ear is found, the polygon is self-intersecting, so the corner is clipped anyway
+        if is_ear || attempts > n {
+            indices.extend([i0, i1, i2]);
+            remaining.remove(i % n);
+            attempts = 0;
+        } else {
+            i += 1;
+            attempts += 1;
+        }
+        i %= remaining.len();
+    }
+    indices.extend(remaining);
+    indices
+}
+
+fn stroke(
+    points: &[Vec2],
+    closed: bool,
+    half_width: f32,
+    positions: &mut Vec<Vec2>,
+    indices: &mut Vec<u32>,
+) {
+    const MITER_LIMIT: f32 = 4.0;
+    let n = points.len();
+    if n < 2 {
+        return;
+    }
+    let segment_count = if closed { n } else { n - 1 };
+    let normal = |i: usize| {
+        let d = (points[(i + 1) % n] - points[i]).normalize();
+        vec2(-d.y, d.x)
+    };
+    // The left and right corners of the end of the incoming segment and the start of the outgoing segment at each point
+    let mut ends = vec![(vec2(0.0, 0.0), vec2(0.0, 0.0)); n];
+    let mut starts = vec![(vec2(0.0, 0.0), vec2(0.0, 0.0)); n];
+    for i in 0..n {
+        let p = points[i];
+        let incoming = (closed || i > 0).then(|| normal((i + n - 1) % n));
+        let outgoing = (closed || i < n - 1).then(|| normal(i));
+        match (incoming, outgoing) {
+            (Some(n0), Some(n1)) => {
+                let miter = (n0 + n1).normalize();
+                let length = half_width / miter.dot(n1);
+                if (n0 + n1).magnitude2() > 1e-6 && length < MITER_LIMIT * half_width {
+                    ends[i] = (p + miter * length, p - miter * length);
+                    starts[i] = ends[i];
+                } else {
+                    ends[i] = (p + n0 * half_width, p - n0 * half_width);
+                    starts[i] = (p + n1 * half_width, p - n1 * half_width);
+                    // Fills the gap on the outer side of the turn with a bevel
+                    let offset = positions.len() as u32;
+                    if cross(n0, n1) > 0.0 {
+                        positions.extend([p, ends[i].1, starts[i].1]);
+                    } else {
+                        positions.extend([p, starts[i].0, ends[i].0]);
+                    }
+                    indices.extend([offset, offset + 1, offset + 2]);
+                }
+            }
+            (Some(n0), None) => ends[i] = (p + n0 * half_width, p - n0 * half_width),
+            (None, Some(n1)) => starts[i] = (p + n1 * half_width, p - n1 * half_width),
+            (None, None) => unreachable!(),
+        }
+    }
+    for i in 0..segment_count {
+        let offset = positions.len() as u32;
+        let (start, end) = (starts[i], ends[(i + 1) % n]);
+        positions.extend([start.1, end.1, end.0, start.0]);
+        indices.extend([
+            offset,
+            offset + 1,
+            offset + 2,
+            offset,
+            offset + 2,
+            offset + 3,
+        ]);
+    }
+}
+
+fn mesh_2d(positions: Vec<Vec2>, indices: Vec<u32>) -> CpuMesh {
+    // The uv coordinates map the bounding rectangle of the shape to the entire texture
+    let (min, max) = positions.iter().fold(
+        (vec2(f32::MAX, f32::MAX), vec2(f32::MIN, f32::MIN)),
+        |(min, max), p| {
+            (
+                vec2(min.x.min(p.x), min.y.min(p.y)),
+                vec2(max.x.max(p.x), max.y.max(p.y)),
+            )
+        },
+    );
+    let size = vec2((max.x - min.x).max(1e-6), (max.y - min.y).max(1e-6));
+    CpuMesh {
+        uvs: Some(
+            positions
+                .iter()
+                .map(|p| vec2((p.x - min.x) / size.x, (max.y - p.y) / size.y))
+                .collect(),
+        ),
+        normals: Some(vec![vec3(0.0, 0.0, 1.0); positions.len()]),
+        positions: Positions::F32(positions.iter().map(|p| p.extend(0.0)).collect()),
+        indices: Indices::U32(indices),
+        ..Default::default()
+    }
+}
+
+///
+/// A filled or stroked 2D shape created from a [Path2D] which can be rendered using a camera created by [Camera::new_2d].
+///
+pub struct Shape2D {
+    mesh: Mesh,
+}
+
+impl Shape2D {
+    ///
+    /// Constructs a new shape covering the inside of the given path, see [Path2D::fill].
+    ///
+    pub fn new_filled(context: &Context, path: &Path2D) -> Self {
+        Self {
+            mesh: Mesh::new(context, &path.fill()),
+        }
+    }
+
+    ///
+    /// Constructs a new shape covering a line with the given width along the given path, see [Path2D::stroke].
+    ///
+    pub fn new_stroked(context: &Context, path: &Path2D, width: f32) -> Self {
+        Self {
+            mesh: Mesh::new(context, &path.stroke(width)),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Shape2D {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+use std::ops::Deref;
+impl Deref for Shape2D {
+    type Target = Mesh;
+    fn deref(&self) -> &Self::Target {
+        &self.mesh
+    }
+}
+
+impl std::ops::DerefMut for Shape2D {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.mesh
+    }
+}
+
+impl Geometry for Shape2D {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.mesh.animate(time)
+    }
+}
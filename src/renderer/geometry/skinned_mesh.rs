@@ -0,0 +1,307 @@
+use crate::core::*;
+use crate::renderer::*;
+
+use super::BaseMesh;
+
+///
+/// A triangle mesh [Geometry] which is deformed by a skeleton, ie. each vertex is transformed by a weighted sum of up to four joint matrices on the GPU.
+/// The joint matrices are usually computed from a [Skeleton] and a [Pose], see [Skeleton::skinning_matrices], or handled automatically by a [SkinnedModel].
+///
+pub struct SkinnedMesh {
+    base_mesh: BaseMesh,
+    context: Context,
+    joint_indices: VertexBuffer,
+    joint_weights: VertexBuffer,
+    joint_matrices: Texture2D,
+    joint_count: u32,
+    joint_aabbs: Vec<AxisAlignedBoundingBox>,
+    joint_matrices_cpu: Vec<Mat4>,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+}
+
+impl SkinnedMesh {
+    ///
+    /// Creates a new skinned mesh from the given [CpuMesh] and the indices and weights of the (up to) four joints affecting each vertex.
+    /// The joint count is the number of joint matrices, which must be larger than all the joint indices.
+    /// Initially, all joint matrices are the identity, ie. the mesh is in its bind pose.
+    ///
+    pub fn new(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        joint_indices: &[[u16; 4]],
+        joint_weights: &[Vec4],
+        joint_count: u32,
+    ) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        assert_eq!(
+            joint_indices.len(),
+            positions.len(),
+            "There must be joint indices for each vertex"
+        );
+        assert_eq!(
+            joint_weights.len(),
+            positions.len(),
+            "There must be joint weights for each vertex"
+        );
+        // The bounding box of the vertices affected by each joint in the bind pose
+        let mut joint_aabbs = vec![AxisAlignedBoundingBox::EMPTY; joint_count as usize];
+        for ((position, indices), weights) in positions
+            .iter()
+            .zip(joint_indices.iter())
+            .zip(joint_weights.iter())
+        {
+            for (index, weight) in indices
+                .iter()
+                .zip([weights.x, weights.y, weights.z, weights.w])
+            {
+                if weight > 0.0 {
+                    assert!(
+                        (*index as u32) < joint_count,
+                        "The joint indices must be smaller than the joint count"
+                    );
+                    joint_aabbs[*index as usize].expand(&[*position]);
+                }
+            }
+        }
+        let mut mesh = Self {
+            base_mesh: BaseMesh::new(context, cpu_mesh),
+            context: context.clone(),
+            joint_indices: VertexBuffer::new_with_data(
+                context,
+                &joint_indices
+                    .iter()
+                    .map(|i| vec4(i[0] as f32, i[1] as f32, i[2] as f32, i[3] as f32))
+                    .collect::<Vec<_>>(),
+            ),
+            joint_weights: VertexBuffer::new_with_data(context, joint_weights),
+            joint_matrices: Texture2D::new_empty::<[f32; 4]>(
+                context,
+                4 * joint_count.max(1),
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            joint_count,
+            joint_aabbs,
+            joint_matrices_cpu: Vec::new(),
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            transformation: Mat4::identity(),
+        };
+        mesh.set_joint_matrices(&vec![Mat4::identity(); joint_count as usize]);
+        mesh
+    }
+
+    ///
+    /// Returns the local to world transformation applied to this mesh after the skinning.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to this mesh after the skinning.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    ///
+    /// Returns the number of joint matrices.
+    ///
+    pub fn joint_count(&self) -> u32 {
+        self.joint_count
+    }
+
+    ///
+    /// Set the matrices which transform each vertex from the bind pose to the current pose for each joint,
+    /// ie. the transformation of the joint multiplied by the inverse bind matrix of the joint.
+    /// There must be a matrix for each joint.
+    ///
+    pub fn set_joint_matrices(&mut self, joint_matrices: &[Mat4]) {
+        assert_eq!(
+            joint_matrices.len() as u32,
+            self.joint_count,
+            "There must be a matrix for each joint"
+        );
+        self.joint_matrices_cpu = joint_matrices.to_vec();
+        self.update_aabb();
+        if self.joint_count > 0 {
+            self.joint_matrices.fill(
+                &joint_matrices
+                    .iter()
+                    .flat_map(|m| [m.x, m.y, m.z, m.w].map(|c| [c.x, c.y, c.z, c.w]))
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    ///
+    /// Sets the morph targets, also called blend shapes, of the mesh, which displace the vertices before the skinning according to the weights given by [Self::set_morph_weights].
+    /// Initially, all the weights are zero, ie. the vertices are not displaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of offsets in a morph target does not match the number of vertices in the mesh.
+    pub fn set_morph_targets(&mut self, morph_targets: &[MorphTarget]) {
+        self.base_mesh.set_morph_targets(morph_targets);
+        self.update_aabb();
+    }
+
+    ///
+    /// Returns the weight of each of the morph targets, see [Self::set_morph_targets].
+    ///
+    pub fn morph_weights(&self) -> &[f32] {
+        self.base_mesh.morph_weights()
+    }
+
+    ///
+    /// Sets the weight of each of the morph targets, see [Self::set_morph_targets].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of weights does not match the number of morph targets.
+    pub fn set_morph_weights(&mut self, weights: &[f32]) {
+        self.base_mesh.set_morph_weights(weights);
+        self.update_aabb();
+    }
+
+    fn update_aabb(&mut self) {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for (joint_aabb, matrix) in self.joint_aabbs.iter().zip(self.joint_matrices_cpu.iter()) {
+            if !joint_aabb.is_empty() {
+                let mut joint_aabb = self.base_mesh.morph_aabb(*joint_aabb);
+                joint_aabb.transform(matrix);
+                aabb.expand_with_aabb(&joint_aabb);
+            }
+        }
+        self.aabb = aabb;
+    }
+}
+
+impl<'a> IntoIterator for &'a SkinnedMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for SkinnedMesh {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        if !aabb.is_empty() {
+            aabb.transform(&self.transformation);
+        }
+        aabb
+    }
+
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_texture("jointMatrices", &self.joint_matrices);
+        program.use_vertex_attribute("joint_indices", &self.joint_indices);
+        program.use_vertex_attribute("joint_weights", &self.joint_weights);
+        self.base_mesh
+            .draw(program, render_states, camera, attributes);
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define USE_SKINNING\n{}{}{}{}{}{}{}{}",
+            if self.base_mesh.morph_targets.is_some() {
+                "#define USE_MORPH_TARGETS\n"
+            } else {
+                ""
+            },
+            if required_attributes.normal {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv2 {
+                "#define USE_UVS2\n"
+            } else {
+                ""
+            },
+            if required_attributes.color && self.base_mesh.colors.is_some() {
+                "#define USE_VERTEX_COLORS\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/mesh.vert"),
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 13;
+        if required_attributes.normal {
+            id |= 0b1u16;
+        }
+        if required_attributes.tangents {
+            id |= 0b1u16 << 1;
+        }
+        if required_attributes.uv {
+            id |= 0b1u16 << 2;
+        }
+        if required_attributes.color && self.base_mesh.colors.is_some() {
+            id |= 0b1u16 << 3;
+        }
+        if required_attributes.uv2 {
+            id |= 0b1u16 << 8;
+        }
+        if self.base_mesh.morph_targets.is_some() {
+            id |= 0b1u16 << 6;
+        }
+        id
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
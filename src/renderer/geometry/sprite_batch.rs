@@ -0,0 +1,126 @@
+use crate::renderer::*;
+
+///
+/// A single 2D sprite in a [SpriteBatch].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite2D {
+    /// The center of the sprite.
+    pub center: Vec2,
+    /// The width and height of the sprite.
+    pub size: Vec2,
+    /// The rotation of the sprite around its center.
+    pub rotation: Radians,
+    /// The minimum uv coordinates of the region of the texture shown by the sprite, for example a single image in a texture atlas.
+    pub uv_min: Vec2,
+    /// The maximum uv coordinates of the region of the texture shown by the sprite, see [Self::uv_min].
+    pub uv_max: Vec2,
+    /// The color multiplied onto the color of the material.
+    pub color: Srgba,
+}
+
+impl Default for Sprite2D {
+    fn default() -> Self {
+        Self {
+            center: vec2(0.0, 0.0),
+            size: vec2(1.0, 1.0),
+            rotation: radians(0.0),
+            uv_min: vec2(0.0, 0.0),
+            uv_max: vec2(1.0, 1.0),
+            color: Srgba::WHITE,
+        }
+    }
+}
+
+///
+/// A batch of 2D sprites, ie. textured rectangles, which can be rendered using a camera created by [Camera::new_2d].
+/// All sprites in the batch are rendered in a single draw call with the same material, so use a texture atlas and [Sprite2D::uv_min] and [Sprite2D::uv_max]
+/// to show different images, which is much faster than rendering each sprite individually.
+///
+pub struct SpriteBatch {
+    mesh: InstancedMesh,
+}
+
+impl SpriteBatch {
+    ///
+    /// Constructs a new batch containing the given sprites.
+    ///
+    pub fn new(context: &Context, sprites: &[Sprite2D]) -> Self {
+        let mut mesh = CpuMesh::square();
+        mesh.transform(&Mat4::from_scale(0.5)).unwrap();
+        Self {
+            mesh: InstancedMesh::new(context, &Self::instances(sprites), &mesh),
+        }
+    }
+
+    ///
+    /// Replaces the sprites in the batch.
+    ///
+    pub fn set_sprites(&mut self, sprites: &[Sprite2D]) {
+        self.mesh.set_instances(&Self::instances(sprites));
+    }
+
+    ///
+    /// Returns the number of sprites in the batch.
+    ///
+    pub fn sprite_count(&self) -> u32 {
+        self.mesh.instance_count()
+    }
+
+    fn instances(sprites: &[Sprite2D]) -> Instances {
+        Instances {
+            transformations: sprites
+                .iter()
+                .map(|s| {
+                    Mat4::from_translation(s.center.extend(0.0))
+                        * Mat4::from_angle_z(s.rotation)
+                        * Mat4::from_nonuniform_scale(s.size.x, s.size.y, 1.0)
+                })
+                .collect(),
+            texture_transformations: Some(
+                sprites
+                    .iter()
+                    .map(|s| {
+                        Mat3::from_translation(s.uv_min)
+                            * Mat3::from_nonuniform_scale(
+                                s.uv_max.x - s.uv_min.x,
+                                s.uv_max.y - s.uv_min.y,
+                            )
+                    })
+                    .collect(),
+            ),
+            colors: Some(sprites.iter().map(|s| s.color).collect()),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a SpriteBatch {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+use std::ops::Deref;
+impl Deref for SpriteBatch {
+    type Target = InstancedMesh;
+    fn deref(&self) -> &Self::Target {
+        &self.mesh
+    }
+}
+
+impl std::ops::DerefMut for SpriteBatch {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.mesh
+    }
+}
+
+impl Geometry for SpriteBatch {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.mesh.animate(time)
+    }
+}
@@ -1,6 +1,39 @@
 use crate::core::*;
 use crate::renderer::*;
 
+///
+/// Defines how the texture applied to [Sprites] is divided into a grid of equally sized frames and how the frames are animated.
+/// The frames are numbered row by row, starting at the first row of the texture, ie. the top left corner of an image loaded from a file.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteSheet {
+    /// The number of columns in the grid of frames.
+    pub columns: u32,
+    /// The number of rows in the grid of frames.
+    pub rows: u32,
+    /// The number of frames in the animation, which can be less than the number of cells in the grid if the last row is not full.
+    pub frame_count: u32,
+    /// The number of frames shown per second. If zero, each sprite shows the frame given by its frame offset, see [Sprites::set_frame_offsets].
+    pub frames_per_second: f32,
+    /// Whether the animation starts over after the last frame or stays at the last frame.
+    pub looping: bool,
+}
+
+impl SpriteSheet {
+    ///
+    /// Creates a new looping sprite sheet where all cells in the grid with the given number of columns and rows are frames.
+    ///
+    pub fn new(columns: u32, rows: u32, frames_per_second: f32) -> Self {
+        Self {
+            columns,
+            rows,
+            frame_count: columns * rows,
+            frames_per_second,
+            looping: true,
+        }
+    }
+}
+
 ///
 /// A set of sprites, ie. a set of quads that orients itself towards the camera.
 ///
@@ -8,13 +41,20 @@ use crate::renderer::*;
 /// For example, if the up direction is specified, the sprites will rotate around the up direction trying to face the camera.
 /// Sprites are also known as billboards in the case where no direction is specified.
 ///
+/// If a [SpriteSheet] is specified, each sprite shows one frame of the texture applied by the material at a time,
+/// which is animated when calling [Geometry::animate], for example to show explosions, markers or animated characters.
+///
 pub struct Sprites {
     context: Context,
     position_buffer: VertexBuffer,
     uv_buffer: VertexBuffer,
     center_buffer: InstanceBuffer,
+    size_buffer: Option<InstanceBuffer>,
+    frame_offset_buffer: Option<InstanceBuffer>,
     transformation: Mat4,
     direction: Option<Vec3>,
+    sprite_sheet: Option<SpriteSheet>,
+    time: f32,
 }
 
 impl Sprites {
@@ -50,8 +90,12 @@ impl Sprites {
             position_buffer,
             uv_buffer,
             center_buffer: InstanceBuffer::new_with_data(context, centers),
+            size_buffer: None,
+            frame_offset_buffer: None,
             transformation: Mat4::identity(),
             direction,
+            sprite_sheet: None,
+            time: 0.0,
         }
     }
 
@@ -78,11 +122,54 @@ impl Sprites {
 
     ///
     /// Set the centers of the sprites. The centers also determines the number of sprites.
+    /// This removes the sizes and frame offsets if the number of sprites changes.
     ///
     pub fn set_centers(&mut self, centers: &[Vec3]) {
+        if centers.len() as u32 != self.center_buffer.instance_count() {
+            self.size_buffer = None;
+            self.frame_offset_buffer = None;
+        }
         self.center_buffer.fill(centers);
     }
 
+    ///
+    /// Set the width and height of each sprite before the transformation is applied, or remove the sizes so all sprites have a width and height of 2.
+    /// There must be a size for each sprite.
+    ///
+    pub fn set_sizes(&mut self, sizes: Option<&[Vec2]>) {
+        self.size_buffer = sizes.map(|sizes| {
+            assert_eq!(
+                sizes.len() as u32,
+                self.center_buffer.instance_count(),
+                "There must be a size for each sprite"
+            );
+            InstanceBuffer::new_with_data(&self.context, sizes)
+        });
+    }
+
+    ///
+    /// Set the [SpriteSheet] which defines the frames of the texture and how they are animated, or remove it so each sprite shows the entire texture.
+    ///
+    pub fn set_sprite_sheet(&mut self, sprite_sheet: Option<SpriteSheet>) {
+        self.sprite_sheet = sprite_sheet;
+    }
+
+    ///
+    /// Set the number of frames each sprite is ahead in the animation defined by the [SpriteSheet], so the sprites are not animated in sync,
+    /// or remove the offsets so all sprites show the same frame. If [SpriteSheet::frames_per_second] is zero, this is the frame shown by each sprite.
+    /// There must be a frame offset for each sprite.
+    ///
+    pub fn set_frame_offsets(&mut self, frame_offsets: Option<&[f32]>) {
+        self.frame_offset_buffer = frame_offsets.map(|frame_offsets| {
+            assert_eq!(
+                frame_offsets.len() as u32,
+                self.center_buffer.instance_count(),
+                "There must be a frame offset for each sprite"
+            );
+            InstanceBuffer::new_with_data(&self.context, frame_offsets)
+        });
+    }
+
     fn draw(&self, program: &Program, render_states: RenderStates, camera: &Camera) {
         program.use_uniform("eye", camera.position());
         program.use_uniform("viewProjection", camera.projection() * camera.view());
@@ -91,6 +178,24 @@ impl Sprites {
         program.use_vertex_attribute("uv_coordinate", &self.uv_buffer);
         program.use_instance_attribute("center", &self.center_buffer);
         program.use_uniform("direction", self.direction.unwrap_or(vec3(0.0, 0.0, 0.0)));
+        if let Some(size_buffer) = &self.size_buffer {
+            program.use_instance_attribute("size", size_buffer);
+        }
+        if let Some(sprite_sheet) = &self.sprite_sheet {
+            program.use_uniform(
+                "sheetSize",
+                vec2(
+                    sprite_sheet.columns.max(1) as f32,
+                    sprite_sheet.rows.max(1) as f32,
+                ),
+            );
+            program.use_uniform("frameCount", sprite_sheet.frame_count.max(1) as f32);
+            program.use_uniform("frame", self.time * sprite_sheet.frames_per_second);
+            program.use_uniform("looping", if sprite_sheet.looping { 1 } else { 0 });
+            if let Some(frame_offset_buffer) = &self.frame_offset_buffer {
+                program.use_instance_attribute("frame_offset", frame_offset_buffer);
+            }
+        }
         program.draw_arrays_instanced(
             render_states,
             camera.viewport(),
@@ -117,21 +222,51 @@ impl Geometry for Sprites {
         render_states: RenderStates,
         attributes: FragmentAttributes,
     ) {
-        if !attributes.uv {
-            todo!()
-        }
-        if attributes.normal || attributes.tangents {
-            todo!()
+        if attributes.uv2 {
+            panic!("the material requires a second set of uv coordinates but sprites do not provide it")
         }
         self.draw(program, render_states, camera);
     }
 
-    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
-        include_str!("shaders/sprites.vert").to_owned()
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        let mut output = String::new();
+        if required_attributes.normal || required_attributes.tangents {
+            output.push_str("#define USE_NORMALS\n");
+        }
+        if required_attributes.tangents {
+            output.push_str("#define USE_TANGENTS\n");
+        }
+        if self.size_buffer.is_some() {
+            output.push_str("#define USE_SIZES\n");
+        }
+        if self.sprite_sheet.is_some() {
+            output.push_str("#define USE_SPRITE_SHEET\n");
+            if self.frame_offset_buffer.is_some() {
+                output.push_str("#define USE_FRAME_OFFSETS\n");
+            }
+        }
+        output.push_str(include_str!("shaders/sprites.vert"));
+        output
     }
 
-    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
-        0b1u16 << 15 | 0b100u16
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 12;
+        if required_attributes.normal || required_attributes.tangents {
+            id |= 0b1u16;
+        }
+        if required_attributes.tangents {
+            id |= 0b1u16 << 1;
+        }
+        if self.size_buffer.is_some() {
+            id |= 0b1u16 << 2;
+        }
+        if self.sprite_sheet.is_some() {
+            id |= 0b1u16 << 3;
+            if self.frame_offset_buffer.is_some() {
+                id |= 0b1u16 << 4;
+            }
+        }
+        id
     }
 
     fn render_with_material(
@@ -165,4 +300,8 @@ impl Geometry for Sprites {
     fn aabb(&self) -> AxisAlignedBoundingBox {
         AxisAlignedBoundingBox::INFINITE
     }
+
+    fn animate(&mut self, time: f32) {
+        self.time = time;
+    }
 }
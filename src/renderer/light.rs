@@ -32,6 +32,22 @@ mod point_light;
 #[doc(inline)]
 pub use point_light::*;
 
+mod rect_area_light;
+#[doc(inline)]
+pub use rect_area_light::*;
+
+mod disk_light;
+#[doc(inline)]
+pub use disk_light::*;
+
+mod light_probe_grid;
+#[doc(inline)]
+pub use light_probe_grid::*;
+
+mod reflection_probe;
+#[doc(inline)]
+pub use reflection_probe::*;
+
 mod ambient_light;
 #[doc(inline)]
 pub use ambient_light::*;
@@ -40,6 +56,14 @@ mod environment;
 #[doc(inline)]
 pub use environment::*;
 
+mod clustered_lights;
+#[doc(inline)]
+pub use clustered_lights::*;
+
+mod shadow_filter;
+#[doc(inline)]
+pub use shadow_filter::*;
+
 use crate::core::*;
 use crate::renderer::camera::*;
 
@@ -124,6 +148,17 @@ impl<T: Light> Light for std::sync::Arc<std::sync::RwLock<T>> {
     }
 }
 
+///
+/// Adds the `light_shared.frag` shader snippet, which contains the lighting helper functions used by the built-in materials, to the given context
+/// so it can be included in the shader source given to [Program::from_source] using `#include "light_shared.frag"`, see [Context::add_shader_include].
+///
+pub fn add_light_shader_include(context: &Context) {
+    context.add_shader_include(
+        "light_shared.frag",
+        include_str!("light/shaders/light_shared.frag"),
+    );
+}
+
 ///
 /// Returns shader source code with the function `calculate_lighting` which calculate the lighting contribution for the given lights and the given [LightingModel].
 /// Use this if you want to implement a custom [Material](crate::renderer::Material) but use the default lighting calculations.
@@ -164,6 +199,25 @@ fn shadow_matrix(camera: &Camera) -> Mat4 {
     bias_matrix * camera.projection() * camera.view()
 }
 
+fn new_data_texture<T: TextureDataType>(context: &Context, width: u32, height: u32) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+fn area_light_axes(direction: Vec3, up: Vec3) -> (Vec3, Vec3) {
+    let direction = direction.normalize();
+    let right = up.cross(direction).normalize();
+    (right, direction.cross(right))
+}
+
 fn compute_up_direction(direction: Vec3) -> Vec3 {
     if vec3(1.0, 0.0, 0.0).dot(direction).abs() > 0.9 {
         (vec3(0.0, 1.0, 0.0).cross(direction)).normalize()
@@ -12,6 +12,9 @@ pub struct AmbientLight {
     pub color: Srgba,
     /// The light shining from the environment. This is calculated based on an environment map.
     pub environment: Option<Environment>,
+    /// A screen space ambient occlusion texture, for example computed using [SsaoEffect::occlusion_texture], which modulates the ambient light.
+    /// The texture is looked up using the fragment coordinates, so it must have the same size as the render target the scene is rendered into.
+    pub occlusion_map: Option<Texture2D>,
 }
 
 impl AmbientLight {
@@ -21,6 +24,7 @@ impl AmbientLight {
             intensity,
             color,
             environment: None,
+            occlusion_map: None,
         }
     }
 
@@ -35,15 +39,25 @@ impl AmbientLight {
             intensity,
             color,
             environment: Some(Environment::new(context, environment_map)),
+            occlusion_map: None,
         }
     }
 }
 
 impl Light for AmbientLight {
     fn shader_source(&self, i: u32) -> String {
+        let (occlusion_uniform, occlusion_lookup) = if self.occlusion_map.is_some() {
+            (
+                "uniform sampler2D ambientOcclusionMap;",
+                "occlusion *= texture(ambientOcclusionMap, gl_FragCoord.xy / vec2(textureSize(ambientOcclusionMap, 0))).r;",
+            )
+        } else {
+            ("", "")
+        };
         if self.environment.is_some() {
             format!(
             "
+                {}
                 uniform samplerCube irradianceMap;
                 uniform samplerCube prefilterMap;
                 uniform sampler2D brdfLUT;
@@ -51,14 +65,19 @@ impl Light for AmbientLight {
     
                 vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                 {{
+                    {}
                     vec3 N = normal;
                     vec3 V = view_direction;
-                    vec3 R = reflect(-V, N); 
+                    #ifdef USE_ANISOTROPY
+                    vec3 R = reflect(-V, anisotropic_reflection_normal(N, V, roughness));
+                    #else
+                    vec3 R = reflect(-V, N);
+                    #endif
                     float NdV = max(0.001, dot(N, V));
                     
                     // calculate reflectance at normal incidence; if dia-electric (like plastic) use F0 
-                    // of 0.04 and if it's a metal, use the albedo color as F0 (metallic workflow)    
-                    vec3 F0 = mix(vec3(0.04), surface_color, metallic);
+                    // DIELECTRIC_F0 (0.04 by default) and if it's a metal, use the albedo color as F0 (metallic workflow)    
+                    vec3 F0 = mix(DIELECTRIC_F0, surface_color, metallic);
                     vec3 specular_fresnel = fresnel_schlick_roughness(F0, NdV, roughness);
                     vec3 diffuse_fresnel = 1.0 - specular_fresnel;
 
@@ -75,17 +94,19 @@ impl Light for AmbientLight {
                     return (diffuse + specular) * occlusion * ambientColor;
                 }}
             
-            ", i)
+            ", occlusion_uniform, i, occlusion_lookup)
         } else {
             format!(
                 "
+                    {}
                     uniform vec3 ambientColor;
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
+                        {}
                         return occlusion * ambientColor * mix(surface_color, vec3(0.0), metallic);
                     }}
                 
-                ", i)
+                ", occlusion_uniform, i, occlusion_lookup)
         }
     }
     fn use_uniforms(&self, program: &Program, _i: u32) {
@@ -94,6 +115,9 @@ impl Light for AmbientLight {
             program.use_texture_cube("prefilterMap", &environment.prefilter_map);
             program.use_texture("brdfLUT", &environment.brdf_map);
         }
+        if let Some(ref occlusion_map) = self.occlusion_map {
+            program.use_texture("ambientOcclusionMap", occlusion_map);
+        }
         program.use_uniform(
             "ambientColor",
             self.color.to_linear_srgb().truncate() * self.intensity,
@@ -101,10 +125,15 @@ impl Light for AmbientLight {
     }
 
     fn id(&self) -> u8 {
+        let occlusion = if self.occlusion_map.is_some() {
+            0b1u8 << 6
+        } else {
+            0u8
+        };
         if self.environment.is_some() {
-            0b1u8 << 7
+            0b1u8 << 7 | occlusion
         } else {
-            0b1u8 << 7 | 0b1u8
+            0b1u8 << 7 | occlusion | 0b1u8
         }
     }
 }
@@ -115,6 +144,7 @@ impl Default for AmbientLight {
             color: Srgba::WHITE,
             intensity: 1.0,
             environment: None,
+            occlusion_map: None,
         }
     }
 }
@@ -0,0 +1,309 @@
+use crate::core::*;
+use crate::renderer::light::*;
+use crate::renderer::*;
+
+const CLUSTERS_X: usize = 16;
+const CLUSTERS_Y: usize = 9;
+const CLUSTERS_Z: usize = 24;
+const MAX_INDEX_TEXTURE_WIDTH: usize = 4096;
+
+type ClusterIndex = (usize, usize, usize);
+
+///
+/// A collection of [PointLight]s and [SpotLight]s which is evaluated using clustered lighting.
+/// The view frustum of the camera is divided into clusters (screen space tiles which are further divided in depth)
+/// and for each cluster, only the lights that can reach that cluster are evaluated.
+/// This makes it possible to light a scene with hundreds of lights, since each fragment is only lit by the nearby lights,
+/// instead of passing every light to every draw call.
+///
+/// The lights are assigned to clusters in [ClusteredLights::update], which must be called whenever the camera or the lights change, usually every frame.
+/// Then use this as any other [Light] when rendering.
+///
+/// **Note:** Shadows are not supported for the lights in a [ClusteredLights].
+/// Also, a light only affects the clusters within the distance where its attenuated intensity is above a small threshold,
+/// so lights with an [Attenuation] which is constant, ie. with zero linear and quadratic attenuation, affects all clusters.
+///
+pub struct ClusteredLights {
+    context: Context,
+    light_texture: Texture2D,
+    cluster_texture: Texture2D,
+    index_texture: Texture2D,
+    view_projection: Mat4,
+    camera_position: Vec3,
+    view_direction: Vec3,
+    depth_range: Vec2,
+}
+
+impl ClusteredLights {
+    ///
+    /// Constructs a new empty collection of clustered lights. Use [ClusteredLights::update] to assign lights.
+    ///
+    pub fn new(context: &Context) -> Self {
+        let mut lights = Self {
+            context: context.clone(),
+            light_texture: new_data_texture::<[f32; 4]>(context, 1, 1),
+            cluster_texture: new_data_texture::<[f32; 2]>(context, 1, 1),
+            index_texture: new_data_texture::<f32>(context, 1, 1),
+            view_projection: Mat4::identity(),
+            camera_position: vec3(0.0, 0.0, 0.0),
+            view_direction: vec3(0.0, 0.0, -1.0),
+            depth_range: vec2(0.01, 1.0),
+        };
+        lights.light_texture.fill(&[[0.0f32; 4]]);
+        lights.cluster_texture.fill(&[[0.0f32; 2]]);
+        lights.index_texture.fill(&[0.0f32]);
+        lights
+    }
+
+    ///
+    /// Assigns the given point and spot lights to the clusters of the given camera.
+    /// Must be called whenever the camera or the lights change.
+    ///
+    pub fn update(
+        &mut self,
+        camera: &Camera,
+        point_lights: &[&PointLight],
+        spot_lights: &[&SpotLight],
+    ) {
+        // Each light is stored as four texels: position and type, color and cutoff, attenuation and direction.
+        let mut light_data = Vec::with_capacity(4 * (point_lights.len() + spot_lights.len()));
+        let mut bounds = Vec::new();
+        for light in point_lights.iter() {
+            let color = light.color.to_linear_srgb().truncate() * light.intensity;
+            light_data.push([light.position.x, light.position.y, light.position.z, 0.0]);
+            light_data.push([color.x, color.y, color.z, 0.0]);
+            light_data.push(attenuation_data(&light.attenuation));
+            light_data.push([0.0; 4]);
+            bounds.push((light.position, range(light.intensity, &light.attenuation)));
+        }
+        for light in spot_lights.iter() {
+            let color = light.color.to_linear_srgb().truncate() * light.intensity;
+            let direction = light.direction.normalize();
+            light_data.push([light.position.x, light.position.y, light.position.z, 1.0]);
+            light_data.push([color.x, color.y, color.z, light.cutoff.0]);
+            light_data.push(attenuation_data(&light.attenuation));
+            light_data.push([direction.x, direction.y, direction.z, 0.0]);
+            bounds.push((light.position, range(light.intensity, &light.attenuation)));
+        }
+
+        self.view_projection = camera.projection() * camera.view();
+        self.camera_position = *camera.position();
+        self.view_direction = camera.view_direction().normalize();
+        let z_far = camera.z_far();
+        let z_near = camera.z_near().max(z_far * 0.0001);
+        self.depth_range = vec2(z_near, z_far);
+
+        let mut clusters = vec![Vec::new(); CLUSTERS_X * CLUSTERS_Y * CLUSTERS_Z];
+        for (light_index, (position, range)) in bounds.into_iter().enumerate() {
+            if let Some((min, max)) = self.cluster_bounds(position, range) {
+                for z in min.2..=max.2 {
+                    for y in min.1..=max.1 {
+                        for x in min.0..=max.0 {
+                            clusters[x + y * CLUSTERS_X + z * CLUSTERS_X * CLUSTERS_Y]
+                                .push(light_index as f32);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut cluster_data = Vec::with_capacity(clusters.len());
+        let mut index_data = Vec::new();
+        for cluster in clusters {
+            cluster_data.push([index_data.len() as f32, cluster.len() as f32]);
+            index_data.extend(cluster);
+        }
+
+        let light_count = light_data.len() / 4;
+        if light_count > 0 {
+            self.light_texture = new_data_texture::<[f32; 4]>(&self.context, 4, light_count as u32);
+            self.light_texture.fill(&light_data);
+        }
+        self.cluster_texture = new_data_texture::<[f32; 2]>(
+            &self.context,
+            (CLUSTERS_X * CLUSTERS_Y) as u32,
+            CLUSTERS_Z as u32,
+        );
+        self.cluster_texture.fill(&cluster_data);
+        if !index_data.is_empty() {
+            let width = index_data.len().min(MAX_INDEX_TEXTURE_WIDTH);
+            let height = index_data.len().div_ceil(width);
+            index_data.resize(width * height, 0.0);
+            self.index_texture =
+                new_data_texture::<f32>(&self.context, width as u32, height as u32);
+            self.index_texture.fill(&index_data);
+        }
+    }
+
+    ///
+    /// Returns the minimum and maximum cluster that a light with the given position and range can reach
+    /// or `None` if the light is not visible from the camera.
+    ///
+    fn cluster_bounds(
+        &self,
+        position: Vec3,
+        range: Option<f32>,
+    ) -> Option<(ClusterIndex, ClusterIndex)> {
+        let full = ((0, 0, 0), (CLUSTERS_X - 1, CLUSTERS_Y - 1, CLUSTERS_Z - 1));
+        let Some(range) = range else {
+            return Some(full);
+        };
+        let depth = (position - self.camera_position).dot(self.view_direction);
+        if depth + range < self.depth_range.x || depth - range > self.depth_range.y {
+            return None;
+        }
+        let min_slice = self.slice(depth - range);
+        let max_slice = self.slice(depth + range);
+
+        if depth - range <= self.depth_range.x {
+            // The light sphere intersects the near plane, so the projection of the bounding box is not reliable
+            return Some((
+                (0, 0, min_slice),
+                (CLUSTERS_X - 1, CLUSTERS_Y - 1, max_slice),
+            ));
+        }
+        let mut min = vec2(f32::MAX, f32::MAX);
+        let mut max = vec2(f32::MIN, f32::MIN);
+        for i in 0..8 {
+            let corner = position
+                + vec3(
+                    if i & 1 == 0 { -range } else { range },
+                    if i & 2 == 0 { -range } else { range },
+                    if i & 4 == 0 { -range } else { range },
+                );
+            let clip = self.view_projection * corner.extend(1.0);
+            if clip.w <= 0.0 {
+                return Some((
+                    (0, 0, min_slice),
+                    (CLUSTERS_X - 1, CLUSTERS_Y - 1, max_slice),
+                ));
+            }
+            let ndc = vec2(clip.x / clip.w, clip.y / clip.w);
+            min = vec2(min.x.min(ndc.x), min.y.min(ndc.y));
+            max = vec2(max.x.max(ndc.x), max.y.max(ndc.y));
+        }
+        if max.x < -1.0 || max.y < -1.0 || min.x > 1.0 || min.y > 1.0 {
+            return None;
+        }
+        let tile = |ndc: f32, count: usize| {
+            ((ndc * 0.5 + 0.5) * count as f32)
+                .floor()
+                .clamp(0.0, (count - 1) as f32) as usize
+        };
+        Some((
+            (tile(min.x, CLUSTERS_X), tile(min.y, CLUSTERS_Y), min_slice),
+            (tile(max.x, CLUSTERS_X), tile(max.y, CLUSTERS_Y), max_slice),
+        ))
+    }
+
+    fn slice(&self, depth: f32) -> usize {
+        let (z_near, z_far) = (self.depth_range.x, self.depth_range.y);
+        ((depth.max(z_near) / z_near).ln() / (z_far / z_near).ln() * CLUSTERS_Z as f32)
+            .floor()
+            .clamp(0.0, (CLUSTERS_Z - 1) as f32) as usize
+    }
+}
+
+impl Light for ClusteredLights {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+            "
+            uniform highp sampler2D clusterLights{i};
+            uniform highp sampler2D clusterGrid{i};
+            uniform highp sampler2D clusterIndices{i};
+            uniform mat4 clusterViewProjection{i};
+            uniform vec3 clusterCameraPosition{i};
+            uniform vec3 clusterViewDirection{i};
+            uniform vec2 clusterDepthRange{i};
+
+            vec4 cluster_fetch{i}(highp sampler2D data, int x, int y)
+            {{
+                return texelFetch(data, ivec2(x, textureSize(data, 0).y - 1 - y), 0);
+            }}
+
+            vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                ivec3 cluster_count = ivec3({CLUSTERS_X}, {CLUSTERS_Y}, {CLUSTERS_Z});
+                vec4 clip = clusterViewProjection{i} * vec4(position, 1.0);
+                ivec2 tile = clamp(ivec2(floor((clip.xy / clip.w * 0.5 + 0.5) * vec2(cluster_count.xy))), ivec2(0), cluster_count.xy - 1);
+                float depth = max(dot(position - clusterCameraPosition{i}, clusterViewDirection{i}), clusterDepthRange{i}.x);
+                int slice = clamp(int(floor(log(depth / clusterDepthRange{i}.x) / log(clusterDepthRange{i}.y / clusterDepthRange{i}.x) * float(cluster_count.z))), 0, cluster_count.z - 1);
+                vec2 cluster = cluster_fetch{i}(clusterGrid{i}, tile.x + tile.y * cluster_count.x, slice).xy;
+                int offset = int(cluster.x);
+                int count = int(cluster.y);
+                int index_width = textureSize(clusterIndices{i}, 0).x;
+
+                vec3 result = vec3(0.0);
+                for (int j = 0; j < count; j++) {{
+                    int index = offset + j;
+                    int light_index = int(cluster_fetch{i}(clusterIndices{i}, index % index_width, index / index_width).x);
+                    vec4 position_and_type = cluster_fetch{i}(clusterLights{i}, 0, light_index);
+                    vec4 color_and_cutoff = cluster_fetch{i}(clusterLights{i}, 1, light_index);
+                    vec3 attenuation = cluster_fetch{i}(clusterLights{i}, 2, light_index).xyz;
+
+                    vec3 light_direction = position_and_type.xyz - position;
+                    float distance = length(light_direction);
+                    light_direction = light_direction / distance;
+                    vec3 light_color = attenuate(color_and_cutoff.rgb, attenuation, distance);
+                    if (position_and_type.w > 0.5) {{
+                        vec3 direction = cluster_fetch{i}(clusterLights{i}, 3, light_index).xyz;
+                        float angle = acos(dot(-light_direction, direction));
+                        float cutoff = color_and_cutoff.w;
+                        if (angle >= cutoff) {{
+                            continue;
+                        }}
+                        light_color *= 1.0 - smoothstep(0.75 * cutoff, cutoff, angle);
+                    }}
+                    result += calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness);
+                }}
+                return result;
+            }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_texture(&format!("clusterLights{}", i), &self.light_texture);
+        program.use_texture(&format!("clusterGrid{}", i), &self.cluster_texture);
+        program.use_texture(&format!("clusterIndices{}", i), &self.index_texture);
+        program.use_uniform(&format!("clusterViewProjection{}", i), self.view_projection);
+        program.use_uniform(&format!("clusterCameraPosition{}", i), self.camera_position);
+        program.use_uniform(&format!("clusterViewDirection{}", i), self.view_direction);
+        program.use_uniform(&format!("clusterDepthRange{}", i), self.depth_range);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b111u8
+    }
+}
+
+fn attenuation_data(attenuation: &Attenuation) -> [f32; 4] {
+    [
+        attenuation.constant,
+        attenuation.linear,
+        attenuation.quadratic,
+        0.0,
+    ]
+}
+
+///
+/// Returns the distance at which the attenuated intensity of a light falls below 1/256 of a unit intensity
+/// or `None` if the intensity never falls below that.
+///
+fn range(intensity: f32, attenuation: &Attenuation) -> Option<f32> {
+    let target = 256.0 * intensity.max(0.0);
+    let (a, b, c) = (
+        attenuation.quadratic,
+        attenuation.linear,
+        attenuation.constant - target,
+    );
+    if c >= 0.0 {
+        Some(0.0)
+    } else if a > 0.0 {
+        Some((-b + (b * b - 4.0 * a * c).sqrt()) / (2.0 * a))
+    } else if b > 0.0 {
+        Some(-c / b)
+    } else {
+        None
+    }
+}
@@ -10,12 +10,15 @@ pub struct DirectionalLight {
     context: Context,
     shadow_texture: Option<DepthTexture2D>,
     shadow_matrix: Mat4,
+    shadow_moments: Option<Texture2D>,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
     pub color: Srgba,
     /// The direction the light shines.
     pub direction: Vec3,
+    /// The [ShadowFilter] applied when looking up the shadow map.
+    pub shadow_filter: ShadowFilter,
 }
 
 impl DirectionalLight {
@@ -30,6 +33,8 @@ impl DirectionalLight {
             context: context.clone(),
             shadow_matrix: Mat4::identity(),
             shadow_texture: None,
+            shadow_moments: None,
+            shadow_filter: ShadowFilter::default(),
             intensity,
             color,
             direction: *direction,
@@ -42,6 +47,7 @@ impl DirectionalLight {
     ///
     pub fn clear_shadow_map(&mut self) {
         self.shadow_texture = None;
+        self.shadow_moments = None;
         self.shadow_matrix = Mat4::identity();
     }
 
@@ -80,6 +86,8 @@ impl DirectionalLight {
             z_near,
             z_far,
         );
+        self.context
+            .push_debug_group("Directional light shadow map");
         let mut shadow_texture = DepthTexture2D::new::<f32>(
             &self.context,
             texture_size,
@@ -87,20 +95,16 @@ impl DirectionalLight {
             Wrapping::ClampToEdge,
             Wrapping::ClampToEdge,
         );
-        let depth_material = DepthMaterial {
-            render_states: RenderStates {
-                write_mask: WriteMask::DEPTH,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        shadow_texture.set_label("Directional light shadow map");
+        let depth_material = DepthMaterial::new_depth_only();
         shadow_texture
             .as_depth_target()
             .clear(ClearState::default())
             .write::<RendererError>(|| {
+                let frustum = shadow_camera.frustum();
                 for geometry in geometries
                     .into_iter()
-                    .filter(|g| shadow_camera.in_frustum(&g.aabb()))
+                    .filter(|g| frustum.intersects_aabb(&g.aabb()))
                 {
                     render_with_material(
                         &self.context,
@@ -113,7 +117,10 @@ impl DirectionalLight {
                 Ok(())
             })
             .unwrap();
+        self.shadow_moments = (self.shadow_filter == ShadowFilter::Vsm)
+            .then(|| generate_shadow_moments(&self.context, &shadow_camera, &shadow_texture));
         self.shadow_texture = Some(shadow_texture);
+        self.context.pop_debug_group();
         self.shadow_matrix = shadow_matrix(&shadow_camera);
     }
 
@@ -123,6 +130,10 @@ impl DirectionalLight {
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
     }
+
+    fn resolved_shadow_filter(&self) -> ShadowFilter {
+        self.shadow_filter.resolve(self.shadow_moments.as_ref())
+    }
 }
 
 impl Light for DirectionalLight {
@@ -132,6 +143,7 @@ impl Light for DirectionalLight {
                 "
                     uniform sampler2D shadowMap{};
                     uniform mat4 shadowMVP{};
+                    {}
 
                     uniform vec3 color{};
                     uniform vec3 direction{};
@@ -139,10 +151,10 @@ impl Light for DirectionalLight {
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
                         return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness)
-                            * calculate_shadow(-direction{}, normal, shadowMap{}, shadowMVP{}, position);
+                            * calculate_shadow{}(-direction{}, normal, position);
                     }}
 
-                ", i, i, i, i, i, i, i, i, i, i)
+                ", i, i, self.resolved_shadow_filter().shader_source(i), i, i, i, i, i, i, i)
         } else {
             format!(
                 "
@@ -159,8 +171,13 @@ impl Light for DirectionalLight {
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
         if let Some(ref tex) = self.shadow_texture {
-            program.use_depth_texture(&format!("shadowMap{}", i), tex);
             program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+            self.resolved_shadow_filter().use_uniforms(
+                program,
+                i,
+                tex,
+                self.shadow_moments.as_ref(),
+            );
         }
         program.use_uniform(
             &format!("color{}", i),
@@ -171,7 +188,7 @@ impl Light for DirectionalLight {
 
     fn id(&self) -> u8 {
         if self.shadow_texture.is_some() {
-            0b1u8 << 7 | 0b10u8
+            0b1u8 << 7 | self.resolved_shadow_filter().id() | 0b10u8
         } else {
             0b1u8 << 7 | 0b11u8
         }
@@ -0,0 +1,74 @@
+use crate::core::*;
+use crate::renderer::light::*;
+use crate::renderer::*;
+
+///
+/// A disk shaped light which emits light uniformly from its surface in the given direction, for example a round ceiling lamp.
+/// The disk is approximated by a regular octagon with the same area. The diffuse lighting is integrated exactly over the part of the octagon above the surface,
+/// while the specular highlight is an approximation, see the `calculate_area_light` shader function.
+///
+pub struct DiskLight {
+    /// The intensity of the light, ie. the emitted radiance. This allows for higher intensity than 1 which can be used to simulate high intensity light sources.
+    pub intensity: f32,
+    /// The base color of the light.
+    pub color: Srgba,
+    /// The position of the center of the disk.
+    pub position: Vec3,
+    /// The direction the light shines, ie. the normal of the disk.
+    pub direction: Vec3,
+    /// The radius of the disk.
+    pub radius: f32,
+}
+
+impl DiskLight {
+    /// Constructs a new disk light.
+    pub fn new(
+        _context: &Context,
+        intensity: f32,
+        color: Srgba,
+        position: &Vec3,
+        direction: &Vec3,
+        radius: f32,
+    ) -> DiskLight {
+        DiskLight {
+            intensity,
+            color,
+            position: *position,
+            direction: *direction,
+            radius,
+        }
+    }
+}
+
+impl Light for DiskLight {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+        "
+            uniform vec3 color{};
+            uniform vec3 position{};
+            uniform vec3 radiusX{};
+            uniform vec3 radiusY{};
+
+            vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                return calculate_area_light(color{}, position{}, radiusX{}, radiusY{}, 8, surface_color, position, normal, view_direction, metallic, roughness);
+            }}
+
+        ", i, i, i, i, i, i, i, i, i)
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        let (right, up) = area_light_axes(self.direction, compute_up_direction(self.direction));
+        program.use_uniform(
+            &format!("color{}", i),
+            self.color.to_linear_srgb().truncate() * self.intensity,
+        );
+        program.use_uniform(&format!("position{}", i), self.position);
+        program.use_uniform(&format!("radiusX{}", i), right * self.radius);
+        program.use_uniform(&format!("radiusY{}", i), up * self.radius);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1u8 << 5 | 0b001u8
+    }
+}
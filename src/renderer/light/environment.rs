@@ -155,8 +155,8 @@ impl Material for PrefilterMaterial<'_> {
         )
     }
 
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b1u16 << 7
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1u32 << 7
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
@@ -201,8 +201,8 @@ impl Material for BrdfMaterial {
         )
     }
 
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b1110u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1110u32
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
@@ -237,8 +237,8 @@ impl Material for IrradianceMaterial<'_> {
         )
     }
 
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b1111u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1111u32
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
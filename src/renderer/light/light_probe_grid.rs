@@ -0,0 +1,308 @@
+use crate::core::*;
+use crate::renderer::light::*;
+use crate::renderer::*;
+
+///
+/// A regular grid of light probes inside an axis aligned box which each stores the irradiance at the position of the probe
+/// as second order spherical harmonics. When used as a [Light], the irradiance is trilinearly interpolated between the eight closest probes for each pixel,
+/// which gives plausible indirect light that varies through the scene, for example dark corners indoors and bright areas next to windows,
+/// where a single [AmbientLight] would light everything equally.
+///
+/// The probes can either be captured at runtime from the scene using [LightProbeGrid::capture]
+/// or baked offline and set using [LightProbeGrid::set_coefficients].
+///
+/// **Note:** Only diffuse indirect light is provided by the probes, combine with an [AmbientLight] with an environment for reflections.
+///
+pub struct LightProbeGrid {
+    context: Context,
+    aabb: AxisAlignedBoundingBox,
+    resolution: (u32, u32, u32),
+    coefficients: Vec<[Vec3; 9]>,
+    texture: Texture2D,
+    /// The intensity of the light from the probes.
+    pub intensity: f32,
+}
+
+impl LightProbeGrid {
+    ///
+    /// Constructs a new grid with the given number of probes in each direction evenly distributed inside the given box.
+    /// All probes are initially black.
+    ///
+    pub fn new(
+        context: &Context,
+        aabb: AxisAlignedBoundingBox,
+        resolution: (u32, u32, u32),
+    ) -> Self {
+        let resolution = (
+            resolution.0.max(1),
+            resolution.1.max(1),
+            resolution.2.max(1),
+        );
+        let count = (resolution.0 * resolution.1 * resolution.2) as usize;
+        let mut grid = Self {
+            context: context.clone(),
+            aabb,
+            resolution,
+            coefficients: vec![[vec3(0.0, 0.0, 0.0); 9]; count],
+            texture: new_data_texture::<[f32; 4]>(context, 9, count as u32),
+            intensity: 1.0,
+        };
+        grid.update_texture();
+        grid
+    }
+
+    ///
+    /// The number of probes in each direction.
+    ///
+    pub fn resolution(&self) -> (u32, u32, u32) {
+        self.resolution
+    }
+
+    ///
+    /// The box containing the probes.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    ///
+    /// Returns the positions of all probes, the probe with grid index `(x, y, z)` is at index `x + y * resolution.0 + z * resolution.0 * resolution.1`.
+    ///
+    pub fn probe_positions(&self) -> Vec<Vec3> {
+        let (rx, ry, rz) = self.resolution;
+        let mut positions = Vec::with_capacity(self.coefficients.len());
+        for z in 0..rz {
+            for y in 0..ry {
+                for x in 0..rx {
+                    let t = vec3(
+                        grid_parameter(x, rx),
+                        grid_parameter(y, ry),
+                        grid_parameter(z, rz),
+                    );
+                    let size = self.aabb.size();
+                    positions
+                        .push(self.aabb.min() + vec3(t.x * size.x, t.y * size.y, t.z * size.z));
+                }
+            }
+        }
+        positions
+    }
+
+    ///
+    /// Returns the spherical harmonics coefficients for all probes in the same order as [LightProbeGrid::probe_positions].
+    ///
+    pub fn coefficients(&self) -> &[[Vec3; 9]] {
+        &self.coefficients
+    }
+
+    ///
+    /// Sets the spherical harmonics coefficients for all probes in the same order as [LightProbeGrid::probe_positions].
+    /// The coefficients are second order spherical harmonics of the incoming radiance convolved with the cosine lobe and divided by pi,
+    /// ie. evaluating them in the direction of the surface normal gives the color of a white diffuse surface.
+    ///
+    pub fn set_coefficients(&mut self, coefficients: &[[Vec3; 9]]) {
+        if coefficients.len() != self.coefficients.len() {
+            panic!(
+                "Expected coefficients for {} probes, but got {}",
+                self.coefficients.len(),
+                coefficients.len()
+            );
+        }
+        self.coefficients = coefficients.to_vec();
+        self.update_texture();
+    }
+
+    ///
+    /// Captures the light at each probe position by rendering the given objects lit by the given lights into a cube map with the given size
+    /// and projecting the result onto spherical harmonics.
+    /// This renders the scene six times per probe, so it is usually only done when loading a scene or when the static part of the scene changes.
+    ///
+    pub fn capture(
+        &mut self,
+        texture_size: u32,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) {
+        let mut scene_aabb = AxisAlignedBoundingBox::EMPTY;
+        for object in objects.clone() {
+            scene_aabb.expand_with_aabb(&object.aabb());
+        }
+        if scene_aabb.is_empty() {
+            return;
+        }
+
+        let mut color_texture = Texture2D::new_empty::<[f16; 4]>(
+            &self.context,
+            texture_size,
+            texture_size,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &self.context,
+            texture_size,
+            texture_size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let viewport = Viewport::new_at_origo(texture_size, texture_size);
+        let weights = face_weights(texture_size);
+
+        for (index, position) in self.probe_positions().into_iter().enumerate() {
+            let z_far = scene_aabb.distance_max(&position).max(0.1);
+            let mut coefficients = [vec3(0.0, 0.0, 0.0); 9];
+            let mut total_weight = 0.0;
+            for side in CubeMapSide::iter() {
+                let mut camera = Camera::new_perspective(
+                    viewport,
+                    position,
+                    position + side.direction(),
+                    side.up(),
+                    degrees(90.0),
+                    0.001 * z_far,
+                    z_far,
+                );
+                camera.disable_tone_and_color_mapping();
+                let pixels = RenderTarget::new(
+                    color_texture.as_color_target(None),
+                    depth_texture.as_depth_target(),
+                )
+                .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+                .render(&camera, objects.clone(), lights)
+                .read_color::<[f32; 4]>();
+
+                let right = side.direction().cross(side.up());
+                for (pixel, (uv, weight)) in pixels.iter().zip(weights.iter()) {
+                    let direction =
+                        (side.direction() + uv.x * right + uv.y * side.up()).normalize();
+                    let radiance = vec3(pixel[0], pixel[1], pixel[2]);
+                    for (c, y) in coefficients.iter_mut().zip(sh_basis(direction)) {
+                        *c += radiance * y * *weight;
+                    }
+                    total_weight += weight;
+                }
+            }
+
+            // Normalize to the area of the unit sphere and convolve with the cosine lobe
+            let normalization = 4.0 * std::f32::consts::PI / total_weight;
+            for (i, c) in coefficients.iter_mut().enumerate() {
+                let band = match i {
+                    0 => 1.0,
+                    1..=3 => 2.0 / 3.0,
+                    _ => 0.25,
+                };
+                *c *= normalization * band;
+            }
+            self.coefficients[index] = coefficients;
+        }
+        self.update_texture();
+    }
+
+    fn update_texture(&mut self) {
+        let data = self
+            .coefficients
+            .iter()
+            .flat_map(|c| c.iter().map(|v| [v.x, v.y, v.z, 0.0]))
+            .collect::<Vec<_>>();
+        self.texture.fill(&data);
+    }
+}
+
+impl Light for LightProbeGrid {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+            "
+            uniform highp sampler2D probeCoefficients{i};
+            uniform vec3 probeGridMin{i};
+            uniform vec3 probeGridMax{i};
+            uniform ivec3 probeGridResolution{i};
+            uniform float probeIntensity{i};
+
+            vec3 probe_irradiance{i}(ivec3 probe, vec3 normal)
+            {{
+                int index = probe.x + probe.y * probeGridResolution{i}.x + probe.z * probeGridResolution{i}.x * probeGridResolution{i}.y;
+                int row = textureSize(probeCoefficients{i}, 0).y - 1 - index;
+                vec3 c[9];
+                for (int j = 0; j < 9; j++) {{
+                    c[j] = texelFetch(probeCoefficients{i}, ivec2(j, row), 0).rgb;
+                }}
+                return evaluate_irradiance_sh(c, normal);
+            }}
+
+            vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                ivec3 resolution = probeGridResolution{i};
+                vec3 cell = clamp((position - probeGridMin{i}) / max(probeGridMax{i} - probeGridMin{i}, vec3(0.00001)), 0.0, 1.0) * vec3(resolution - 1);
+                ivec3 base = clamp(ivec3(floor(cell)), ivec3(0), max(resolution - 2, ivec3(0)));
+                vec3 t = clamp(cell - vec3(base), 0.0, 1.0);
+
+                vec3 irradiance = vec3(0.0);
+                for (int j = 0; j < 8; j++) {{
+                    ivec3 offset = ivec3(j & 1, (j >> 1) & 1, (j >> 2) & 1);
+                    vec3 w = mix(vec3(1.0) - t, t, vec3(offset));
+                    irradiance += w.x * w.y * w.z * probe_irradiance{i}(min(base + offset, resolution - 1), normal);
+                }}
+                return occlusion * probeIntensity{i} * max(irradiance, vec3(0.0)) * mix(surface_color, vec3(0.0), metallic);
+            }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_texture(&format!("probeCoefficients{}", i), &self.texture);
+        program.use_uniform(&format!("probeGridMin{}", i), self.aabb.min());
+        program.use_uniform(&format!("probeGridMax{}", i), self.aabb.max());
+        program.use_uniform(
+            &format!("probeGridResolution{}", i),
+            Vector3::new(
+                self.resolution.0 as i32,
+                self.resolution.1 as i32,
+                self.resolution.2 as i32,
+            ),
+        );
+        program.use_uniform(&format!("probeIntensity{}", i), self.intensity);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1u8 << 5 | 0b010u8
+    }
+}
+
+fn grid_parameter(i: u32, resolution: u32) -> f32 {
+    if resolution > 1 {
+        i as f32 / (resolution - 1) as f32
+    } else {
+        0.5
+    }
+}
+
+// Returns the position on the cube side in the range [-1, 1] and the solid angle of each pixel, starting from the top left pixel.
+fn face_weights(size: u32) -> Vec<(Vec2, f32)> {
+    let mut weights = Vec::with_capacity((size * size) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let v = 1.0 - (y as f32 + 0.5) / size as f32 * 2.0;
+            let weight = 1.0 / (1.0 + u * u + v * v).powf(1.5);
+            weights.push((vec2(u, v), weight));
+        }
+    }
+    weights
+}
+
+fn sh_basis(d: Vec3) -> [f32; 9] {
+    [
+        0.282095,
+        0.488603 * d.y,
+        0.488603 * d.z,
+        0.488603 * d.x,
+        1.092548 * d.x * d.y,
+        1.092548 * d.y * d.z,
+        0.315392 * (3.0 * d.z * d.z - 1.0),
+        1.092548 * d.x * d.z,
+        0.546274 * (d.x * d.x - d.y * d.y),
+    ]
+}
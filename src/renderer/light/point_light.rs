@@ -3,8 +3,15 @@ use crate::renderer::*;
 
 ///
 /// A light which shines from the given position in all directions.
+/// The light will cast shadows if you [generate a shadow map](PointLight::generate_shadow_map).
+///
+/// Like [SpotLight] and [DirectionalLight], the light holds its shadow map in private fields,
+/// so it must be constructed using [PointLight::new] instead of a struct literal.
 ///
 pub struct PointLight {
+    context: Context,
+    shadow_texture: Option<DepthTextureCubeMap>,
+    shadow_depth_range: Vec2,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
@@ -13,47 +20,185 @@ pub struct PointLight {
     pub position: Vec3,
     /// The [Attenuation] of the light.
     pub attenuation: Attenuation,
+    /// The [ShadowFilter] applied when looking up the shadow map.
+    /// Only [ShadowFilter::Hard] and [ShadowFilter::Pcf] are supported for point lights, the other filters fall back to the default percentage-closer filtering.
+    pub shadow_filter: ShadowFilter,
 }
 
 impl PointLight {
     /// Constructs a new point light.
     pub fn new(
-        _context: &Context,
+        context: &Context,
         intensity: f32,
         color: Srgba,
         position: &Vec3,
         attenuation: Attenuation,
     ) -> PointLight {
         PointLight {
+            context: context.clone(),
+            shadow_texture: None,
+            shadow_depth_range: vec2(0.0, 1.0),
             intensity,
             color,
             position: *position,
             attenuation,
+            shadow_filter: ShadowFilter::default(),
+        }
+    }
+
+    ///
+    /// Clear the shadow map, effectively disable the shadow.
+    /// Only necessary if you want to disable the shadow, if you want to update the shadow, just use [PointLight::generate_shadow_map].
+    ///
+    pub fn clear_shadow_map(&mut self) {
+        self.shadow_texture = None;
+        self.shadow_depth_range = vec2(0.0, 1.0);
+    }
+
+    ///
+    /// Generate a shadow map which is used to simulate shadows from the point light onto the geometries given as input.
+    /// The shadow map is a depth cube map, so the scene is rendered six times, once for each side of the cube.
+    /// It is recomended that the texture size is power of 2.
+    /// If the shadows are too low resolution (the edges between shadow and non-shadow are pixelated) try to increase the texture size.
+    ///
+    pub fn generate_shadow_map(
+        &mut self,
+        texture_size: u32,
+        geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+    ) {
+        let mut z_far = 0.0f32;
+        let mut z_near = f32::MAX;
+        for geometry in geometries.clone() {
+            let aabb = geometry.aabb();
+            if !aabb.is_empty() {
+                z_far = z_far.max(aabb.distance_max(&self.position));
+                z_near = z_near.min(aabb.distance(&self.position));
+            }
+        }
+        if z_far <= 0.0 {
+            return;
+        }
+        let z_near = z_near.max(0.01).min(0.5 * z_far);
+
+        self.context.push_debug_group("Point light shadow map");
+        let mut shadow_texture = DepthTextureCubeMap::new::<f32>(
+            &self.context,
+            texture_size,
+            texture_size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        shadow_texture.set_label("Point light shadow map");
+        let depth_material = DepthMaterial::new_depth_only();
+        let viewport = Viewport::new_at_origo(texture_size, texture_size);
+        for side in CubeMapSide::iter() {
+            let shadow_camera = Camera::new_perspective(
+                viewport,
+                self.position,
+                self.position + side.direction(),
+                side.up(),
+                degrees(90.0),
+                z_near,
+                z_far,
+            );
+            shadow_texture
+                .as_depth_target(side)
+                .clear(ClearState::default())
+                .write::<RendererError>(|| {
+                    let frustum = shadow_camera.frustum();
+                    for geometry in geometries
+                        .clone()
+                        .into_iter()
+                        .filter(|g| frustum.intersects_aabb(&g.aabb()))
+                    {
+                        render_with_material(
+                            &self.context,
+                            &shadow_camera,
+                            &geometry,
+                            &depth_material,
+                            &[],
+                        );
+                    }
+                    Ok(())
+                })
+                .unwrap();
+        }
+        self.shadow_texture = Some(shadow_texture);
+        self.context.pop_debug_group();
+        self.shadow_depth_range = vec2(z_near, z_far);
+    }
+
+    ///
+    /// Returns a reference to the shadow map if it has been generated.
+    ///
+    pub fn shadow_map(&self) -> Option<&DepthTextureCubeMap> {
+        self.shadow_texture.as_ref()
+    }
+
+    fn shadow_kernel_radius(&self) -> u32 {
+        match self.shadow_filter {
+            ShadowFilter::Hard => 0,
+            ShadowFilter::Pcf { kernel_radius } => kernel_radius,
+            _ => 1,
         }
     }
 }
 
 impl Light for PointLight {
     fn shader_source(&self, i: u32) -> String {
-        format!(
-        "
-            uniform vec3 color{};
-            uniform vec3 attenuation{};
-            uniform vec3 position{};
-
-            vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-            {{
-                vec3 light_direction = position{} - position;
-                float distance = length(light_direction);
-                light_direction = light_direction / distance;
-
-                vec3 light_color = attenuate(color{}, attenuation{}, distance);
-                return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness);
-            }}
-        
-        ", i, i, i, i, i, i, i)
+        if self.shadow_texture.is_some() {
+            format!(
+            "
+                uniform samplerCube shadowMap{};
+                uniform vec2 shadowDepthRange{};
+                uniform int shadowKernelRadius{};
+
+                uniform vec3 color{};
+                uniform vec3 attenuation{};
+                uniform vec3 position{};
+
+                vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    vec3 light_direction = position{} - position;
+                    float distance = length(light_direction);
+                    light_direction = light_direction / distance;
+
+                    vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                    return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness)
+                        * calculate_point_shadow(normal, shadowMap{}, position{}, position, shadowDepthRange{}, shadowKernelRadius{});
+                }}
+
+            ", i, i, i, i, i, i, i, i, i, i, i, i, i, i)
+        } else {
+            format!(
+            "
+                uniform vec3 color{};
+                uniform vec3 attenuation{};
+                uniform vec3 position{};
+
+                vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    vec3 light_direction = position{} - position;
+                    float distance = length(light_direction);
+                    light_direction = light_direction / distance;
+
+                    vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                    return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness);
+                }}
+
+            ", i, i, i, i, i, i, i)
+        }
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
+        if let Some(ref tex) = self.shadow_texture {
+            program.use_depth_texture_cube(&format!("shadowMap{}", i), tex);
+            program.use_uniform(&format!("shadowDepthRange{}", i), self.shadow_depth_range);
+            program.use_uniform(
+                &format!("shadowKernelRadius{}", i),
+                self.shadow_kernel_radius() as i32,
+            );
+        }
         program.use_uniform(
             &format!("color{}", i),
             self.color.to_linear_srgb().truncate() * self.intensity,
@@ -70,6 +215,10 @@ impl Light for PointLight {
     }
 
     fn id(&self) -> u8 {
-        0b1u8 << 7 | 0b100u8
+        if self.shadow_texture.is_some() {
+            0b1u8 << 7 | 0b1u8 << 5 | 0b100u8
+        } else {
+            0b1u8 << 7 | 0b100u8
+        }
     }
 }
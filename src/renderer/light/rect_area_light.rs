@@ -0,0 +1,82 @@
+use crate::core::*;
+use crate::renderer::light::*;
+use crate::renderer::*;
+
+///
+/// A rectangular light which emits light uniformly from its surface in the given direction, for example a window or a ceiling panel.
+/// The diffuse lighting is integrated exactly over the part of the rectangle above the surface, while the specular highlight is an approximation
+/// which gives soft highlights, see the `calculate_area_light` shader function.
+///
+pub struct RectAreaLight {
+    /// The intensity of the light, ie. the emitted radiance. This allows for higher intensity than 1 which can be used to simulate high intensity light sources.
+    pub intensity: f32,
+    /// The base color of the light.
+    pub color: Srgba,
+    /// The position of the center of the rectangle.
+    pub position: Vec3,
+    /// The direction the light shines, ie. the normal of the rectangle.
+    pub direction: Vec3,
+    /// The up direction of the rectangle, the height of the rectangle is measured along this direction.
+    pub up: Vec3,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle.
+    pub height: f32,
+}
+
+impl RectAreaLight {
+    /// Constructs a new rectangular area light.
+    pub fn new(
+        _context: &Context,
+        intensity: f32,
+        color: Srgba,
+        position: &Vec3,
+        direction: &Vec3,
+        up: &Vec3,
+        width: f32,
+        height: f32,
+    ) -> RectAreaLight {
+        RectAreaLight {
+            intensity,
+            color,
+            position: *position,
+            direction: *direction,
+            up: *up,
+            width,
+            height,
+        }
+    }
+}
+
+impl Light for RectAreaLight {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+        "
+            uniform vec3 color{};
+            uniform vec3 position{};
+            uniform vec3 halfWidth{};
+            uniform vec3 halfHeight{};
+
+            vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                return calculate_area_light(color{}, position{}, halfWidth{}, halfHeight{}, 4, surface_color, position, normal, view_direction, metallic, roughness);
+            }}
+
+        ", i, i, i, i, i, i, i, i, i)
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        let (right, up) = area_light_axes(self.direction, self.up);
+        program.use_uniform(
+            &format!("color{}", i),
+            self.color.to_linear_srgb().truncate() * self.intensity,
+        );
+        program.use_uniform(&format!("position{}", i), self.position);
+        program.use_uniform(&format!("halfWidth{}", i), right * 0.5 * self.width);
+        program.use_uniform(&format!("halfHeight{}", i), up * 0.5 * self.height);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1u8 << 5
+    }
+}
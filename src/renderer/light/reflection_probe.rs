@@ -0,0 +1,242 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A light which shines on all surfaces inside an axis aligned box with the light captured from the scene at the position of the probe.
+/// This works like an [AmbientLight] with an [Environment], but where the environment map is rendered from the scene instead of loaded from an HDR image,
+/// which gives correct reflections and indirect light indoors where the light from an outdoor environment map is wrong.
+/// Surfaces outside the box are not lit by the probe, so use the probe instead of an ambient light with an environment for the objects inside the box.
+///
+/// The reflections are parallax corrected by assuming that the captured surroundings are placed on the sides of the box,
+/// so the box should fit the walls of the room for the best result.
+///
+pub struct ReflectionProbe {
+    context: Context,
+    position: Vec3,
+    aabb: AxisAlignedBoundingBox,
+    texture_size: u32,
+    environment: Environment,
+    frame: u32,
+    /// The intensity of the light.
+    pub intensity: f32,
+    /// The distance from the sides of the box where the light from the probe fades out, to avoid a sharp transition between objects inside and outside the box.
+    pub blend_distance: f32,
+    /// The number of calls to [ReflectionProbe::update] between each capture, for example a value of 1 captures the scene every frame if update is called every frame.
+    /// A value of zero disables the automatic capture, in which case only [ReflectionProbe::capture] captures the scene.
+    pub update_interval: u32,
+}
+
+impl ReflectionProbe {
+    ///
+    /// Constructs a new reflection probe at the given position which lights the surfaces inside the given box.
+    /// The scene is captured into a cube map with the given size, see [ReflectionProbe::capture].
+    /// The probe is black until the scene is captured.
+    ///
+    pub fn new(
+        context: &Context,
+        position: Vec3,
+        aabb: AxisAlignedBoundingBox,
+        texture_size: u32,
+    ) -> Self {
+        let mut environment_map = new_environment_map(context, texture_size);
+        for side in CubeMapSide::iter() {
+            environment_map
+                .as_color_target(&[side], None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 1.0));
+        }
+        Self {
+            context: context.clone(),
+            position,
+            aabb,
+            texture_size,
+            environment: Environment::new(context, &environment_map),
+            frame: 0,
+            intensity: 1.0,
+            blend_distance: 0.0,
+            update_interval: 0,
+        }
+    }
+
+    ///
+    /// The position where the scene is captured.
+    ///
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    ///
+    /// The box where the probe shines.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    ///
+    /// Captures the scene by rendering the given objects lit by the given lights into a cube map at the position of the probe
+    /// and prefilters the cube map into an [Environment].
+    /// This renders the scene six times and is therefore expensive, so it is usually only done when the scene changes.
+    ///
+    pub fn capture(
+        &mut self,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) {
+        let mut scene_aabb = self.aabb;
+        for object in objects.clone() {
+            let aabb = object.aabb();
+            if aabb.is_infinite() {
+                continue;
+            }
+            scene_aabb.expand_with_aabb(&aabb);
+        }
+        let z_far = scene_aabb.distance_max(&self.position).max(0.1);
+
+        let mut environment_map = new_environment_map(&self.context, self.texture_size);
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &self.context,
+            self.texture_size,
+            self.texture_size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let viewport = Viewport::new_at_origo(self.texture_size, self.texture_size);
+        for side in CubeMapSide::iter() {
+            let mut camera = Camera::new_perspective(
+                viewport,
+                self.position,
+                self.position + side.direction(),
+                side.up(),
+                degrees(90.0),
+                0.001 * z_far,
+                z_far,
+            );
+            camera.disable_tone_and_color_mapping();
+            RenderTarget::new(
+                environment_map.as_color_target(&[side], None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render(&camera, objects.clone(), lights);
+        }
+        self.environment = Environment::new(&self.context, &environment_map);
+    }
+
+    ///
+    /// Captures the scene, see [ReflectionProbe::capture], if [ReflectionProbe::update_interval] calls have passed since the last capture.
+    /// Call this every frame to keep the probe updated when the scene is animated. Returns whether or not the scene was captured.
+    ///
+    pub fn update(
+        &mut self,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) -> bool {
+        if self.update_interval == 0 {
+            return false;
+        }
+        self.frame += 1;
+        if self.frame < self.update_interval {
+            return false;
+        }
+        self.frame = 0;
+        self.capture(objects, lights);
+        true
+    }
+
+    ///
+    /// Returns the prefiltered light captured from the scene.
+    ///
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+}
+
+impl Light for ReflectionProbe {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+            "
+            uniform samplerCube irradianceMap{i};
+            uniform samplerCube prefilterMap{i};
+            uniform sampler2D brdfLUT{i};
+            uniform vec3 probePosition{i};
+            uniform vec3 probeMin{i};
+            uniform vec3 probeMax{i};
+            uniform float probeBlendDistance{i};
+            uniform float probeIntensity{i};
+
+            vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                vec3 distances = min(position - probeMin{i}, probeMax{i} - position);
+                float distance = min(distances.x, min(distances.y, distances.z));
+                if (distance < 0.0) {{
+                    return vec3(0.0);
+                }}
+                float weight = probeBlendDistance{i} > 0.0 ? clamp(distance / probeBlendDistance{i}, 0.0, 1.0) : 1.0;
+
+                vec3 N = normal;
+                vec3 V = view_direction;
+                #ifdef USE_ANISOTROPY
+                vec3 R = reflect(-V, anisotropic_reflection_normal(N, V, roughness));
+                #else
+                vec3 R = reflect(-V, N);
+                #endif
+                float NdV = max(0.001, dot(N, V));
+
+                // Parallax correction: find where the reflection ray leaves the box and look up the direction from the probe to that point
+                vec3 safe_R = mix(R, vec3(0.00001), lessThan(abs(R), vec3(0.00001)));
+                vec3 far_planes = max((probeMax{i} - position) / safe_R, (probeMin{i} - position) / safe_R);
+                float hit_distance = min(far_planes.x, min(far_planes.y, far_planes.z));
+                vec3 corrected_R = position + R * hit_distance - probePosition{i};
+
+                vec3 F0 = mix(DIELECTRIC_F0, surface_color, metallic);
+                vec3 specular_fresnel = fresnel_schlick_roughness(F0, NdV, roughness);
+                vec3 diffuse_fresnel = 1.0 - specular_fresnel;
+
+                vec3 irradiance = texture(irradianceMap{i}, N).rgb;
+                vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) * irradiance;
+
+                const float MAX_REFLECTION_LOD = 4.0;
+                vec3 prefiltered_color = textureLod(prefilterMap{i}, corrected_R, roughness * MAX_REFLECTION_LOD).rgb;
+                vec2 brdf = texture(brdfLUT{i}, vec2(NdV, roughness)).rg;
+                vec3 specular = prefiltered_color * (specular_fresnel * brdf.x + brdf.y);
+
+                return weight * probeIntensity{i} * occlusion * (diffuse + specular);
+            }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_texture_cube(
+            &format!("irradianceMap{}", i),
+            &self.environment.irradiance_map,
+        );
+        program.use_texture_cube(
+            &format!("prefilterMap{}", i),
+            &self.environment.prefilter_map,
+        );
+        program.use_texture(&format!("brdfLUT{}", i), &self.environment.brdf_map);
+        program.use_uniform(&format!("probePosition{}", i), self.position);
+        program.use_uniform(&format!("probeMin{}", i), self.aabb.min());
+        program.use_uniform(&format!("probeMax{}", i), self.aabb.max());
+        program.use_uniform(&format!("probeBlendDistance{}", i), self.blend_distance);
+        program.use_uniform(&format!("probeIntensity{}", i), self.intensity);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1u8 << 5 | 0b011u8
+    }
+}
+
+fn new_environment_map(context: &Context, texture_size: u32) -> TextureCubeMap {
+    TextureCubeMap::new_empty::<[f16; 4]>(
+        context,
+        texture_size,
+        texture_size,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        Some(Interpolation::Linear),
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
@@ -4,6 +4,11 @@ struct BaseLight
     float intensity;
 };
 
+// the reflectance at normal incidence of non-metals, which materials can override by defining DIELECTRIC_F0 before the light functions
+#ifndef DIELECTRIC_F0
+#define DIELECTRIC_F0 vec3(0.04)
+#endif
+
 // compute fresnel specular factor
 // cosTheta could be NdV or VdH depending on used technique
 vec3 fresnel_schlick(vec3 F0, float cosTheta)
@@ -61,6 +66,52 @@ float calculate_D(float roughness, float NdH) {
     return D;
 }
 
+#ifdef USE_ANISOTROPY
+// The direction in which the roughness is increased and the strength of the anisotropy, which are set by the material before calculating the lighting
+vec3 anisotropy_direction = vec3(1.0, 0.0, 0.0);
+float anisotropy_strength = 0.0;
+
+// GGX distribution where the roughness along the anisotropy direction is increased with the anisotropy strength
+float D_GGX_anisotropic(float roughness, vec3 N, vec3 H)
+{
+    vec3 T = anisotropy_direction;
+    vec3 B = cross(N, T);
+    float alpha_b = max(roughness * roughness, 0.001);
+    float alpha_t = mix(alpha_b, 1.0, anisotropy_strength * anisotropy_strength);
+    float alpha2 = alpha_t * alpha_b;
+    vec3 v = vec3(alpha_b * dot(T, H), alpha_t * dot(B, H), alpha2 * dot(N, H));
+    float w2 = alpha2 / dot(v, v);
+    return alpha2 * w2 * w2 / PI;
+}
+
+// The normal used for looking up reflections, which is bent to stretch the reflections perpendicular to the anisotropy direction
+vec3 anisotropic_reflection_normal(vec3 N, vec3 V, float roughness)
+{
+    vec3 B = cross(N, anisotropy_direction);
+    vec3 anisotropic_normal = cross(cross(B, V), B);
+    float bend = 1.0 - anisotropy_strength * (1.0 - roughness);
+    bend *= bend;
+    return normalize(mix(anisotropic_normal, N, bend * bend));
+}
+#endif
+
+#ifdef USE_SUBSURFACE
+// The color of the light scattered below the surface and the thickness of the surface in the range [0..1], which are set by the material before calculating the lighting
+vec3 subsurface_color = vec3(0.0);
+float subsurface_thickness = 1.0;
+
+// Approximates the light scattered below the surface using wrap lighting, which lets the diffuse light reach past the point where the surface turns away from the light,
+// and adds the light from behind the surface which is transmitted through the thin parts of the surface
+vec3 subsurface_scattering(vec3 L, vec3 V, vec3 N)
+{
+    float NdL = dot(N, L);
+    float wrap = 0.5;
+    float wrapped = saturate((NdL + wrap) / (1.0 + wrap)) - saturate(NdL);
+    float transmitted = pow(saturate(dot(V, -normalize(L + 0.3 * N))), 4.0) * (1.0 - subsurface_thickness);
+    return subsurface_color * (wrapped + transmitted) / PI;
+}
+#endif
+
 // Smith's Schlick-GGX geometry function
 float G_schlick(in float roughness, in float NdV, in float NdL)
 {
@@ -105,9 +156,15 @@ vec3 calculate_light(vec3 light_color, vec3 L, vec3 surface_color, vec3 V, vec3
     float NdV = max(0.001, dot(N, V));
 
     // mix between metal and non-metal material, for non-metal
-    // constant base specular factor of 0.04 grey is used
-    vec3 F0 = mix(vec3(0.04), surface_color, metallic);
+    // the dielectric base specular factor, by default 0.04 grey, is used
+    vec3 F0 = mix(DIELECTRIC_F0, surface_color, metallic);
 
+#ifdef USE_ANISOTROPY
+    // specular reflectance with COOK-TORRANCE using an anisotropic GGX distribution, independent of the lighting model
+    vec3 H = normalize(L + V);
+    vec3 specular_fresnel = fresnel_schlick_roughness(F0, max(0.001, dot(H, V)), roughness);
+    vec3 specular = specular_fresnel * G_schlick(roughness, NdV, NdL) * D_GGX_anisotropic(roughness, N, H) / (4.0 * NdV * NdL);
+#else
 #ifdef PHONG
     // specular reflectance with PHONG
     vec3 specular_fresnel = fresnel_schlick_roughness(F0, NdV, roughness);
@@ -127,6 +184,7 @@ vec3 calculate_light(vec3 light_color, vec3 L, vec3 surface_color, vec3 V, vec3
 #ifdef COOK
     // specular reflectance with COOK-TORRANCE
     vec3 specular = cooktorrance_specular(NdL, NdV, NdH, specular_fresnel, roughness);
+#endif
 #endif
 
     // diffuse is common for any model
@@ -134,7 +192,11 @@ vec3 calculate_light(vec3 light_color, vec3 L, vec3 surface_color, vec3 V, vec3
     vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) / PI;
 
     // final result
-    return (diffuse + specular) * light_color * NdL;
+    vec3 result = (diffuse + specular) * light_color * NdL;
+#ifdef USE_SUBSURFACE
+    result += subsurface_scattering(L, V, N) * mix(surface_color, vec3(0.0), metallic) * light_color;
+#endif
+    return result;
 }
 
 vec3 attenuate(vec3 light_color, vec3 attenuation, float distance)
@@ -146,6 +208,11 @@ vec3 attenuate(vec3 light_color, vec3 attenuation, float distance)
     return light_color / max(1.0, att);
 }
 
+float shadow_bias(vec3 lightDirection, vec3 normal)
+{
+    return max(0.05 * (1.0 - dot(normal, lightDirection)), 0.005);
+}
+
 float is_visible(vec3 lightDirection, vec3 normal, sampler2D shadowMap, vec4 shadow_coord, vec2 offset)
 {
     vec2 uv = (shadow_coord.xy + offset)/shadow_coord.w;
@@ -157,26 +224,250 @@ float is_visible(vec3 lightDirection, vec3 normal, sampler2D shadowMap, vec4 sha
         return 1.0;
     }
     // Adjust shadow bias based on surface normal and light direction
-    float bias = max(0.05 * (1.0 - dot(normal, lightDirection)), 0.005);
-    float true_distance = (shadow_coord.z - bias)/shadow_coord.w;
+    float true_distance = (shadow_coord.z - shadow_bias(lightDirection, normal))/shadow_coord.w;
     return shadow_cast_distance > true_distance ? 1.0 : 0.0;
 }
 
-float calculate_shadow(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec3 position)
+float calculate_shadow_hard(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec3 position)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    return is_visible(lightDirection, normal, shadowMap, shadow_coord, vec2(0.0));
+}
+
+float calculate_shadow_pcf(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec3 position, int radius)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 texel_size = shadow_coord.w / vec2(textureSize(shadowMap, 0));
+    float visibility = 0.0;
+    for (int x = -radius; x <= radius; x++)
+    {
+        for (int y = -radius; y <= radius; y++)
+        {
+            visibility += is_visible(lightDirection, normal, shadowMap, shadow_coord, vec2(float(x), float(y)) * texel_size);
+        }
+    }
+    float kernel_size = float(2 * radius + 1);
+    return visibility / (kernel_size * kernel_size);
+}
+
+const vec2 shadowPoissonDisk[16] = vec2[](
+    vec2(-0.94201624, -0.39906216),
+    vec2(0.94558609, -0.76890725),
+    vec2(-0.094184101, -0.92938870),
+    vec2(0.34495938, 0.29387760),
+    vec2(-0.91588581, 0.45771432),
+    vec2(-0.81544232, -0.87912464),
+    vec2(-0.38277543, 0.27676845),
+    vec2(0.97484398, 0.75648379),
+    vec2(0.44323325, -0.97511554),
+    vec2(0.53742981, -0.47373420),
+    vec2(-0.26496911, -0.41893023),
+    vec2(0.79197514, 0.19090188),
+    vec2(-0.24188840, 0.99706507),
+    vec2(-0.81409955, 0.91437590),
+    vec2(0.19984126, 0.78641367),
+    vec2(0.14383161, -0.14100790)
+);
+
+float calculate_shadow_pcss(vec3 lightDirection, vec3 normal, sampler2D shadowMap, mat4 shadowMVP, vec3 position, float lightSize)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 uv = shadow_coord.xy / shadow_coord.w;
+    float receiver_depth = (shadow_coord.z - shadow_bias(lightDirection, normal)) / shadow_coord.w;
+
+    // Blocker search
+    float blocker_depth = 0.0;
+    float blocker_count = 0.0;
+    for (int i = 0; i < 16; i++)
+    {
+        float depth = texture(shadowMap, uv + shadowPoissonDisk[i] * lightSize).x;
+        if (depth < receiver_depth) {
+            blocker_depth += depth;
+            blocker_count += 1.0;
+        }
+    }
+    if (blocker_count < 0.5) {
+        return 1.0;
+    }
+    blocker_depth /= blocker_count;
+
+    // The depth of a perspective shadow map (spot light) is not linear, so the distances along the light direction are used instead
+    bool perspective = shadowMVP[0][3] != 0.0 || shadowMVP[1][3] != 0.0 || shadowMVP[2][3] != 0.0;
+    float blocker_distance = perspective ? 1.0 / (inverse(shadowMVP) * vec4(uv, blocker_depth, 1.0)).w : blocker_depth;
+    float receiver_distance = perspective ? shadow_coord.w : receiver_depth;
+
+    // Filter with a kernel size given by the estimated penumbra width
+    float penumbra = max((receiver_distance - blocker_distance) * lightSize / max(blocker_distance, 0.0001), 1.0 / float(textureSize(shadowMap, 0).x));
+    float visibility = 0.0;
+    for (int i = 0; i < 16; i++)
+    {
+        visibility += is_visible(lightDirection, normal, shadowMap, shadow_coord, shadowPoissonDisk[i] * penumbra * shadow_coord.w);
+    }
+    return visibility / 16.0;
+}
+
+float calculate_shadow_vsm(sampler2D shadowMoments, mat4 shadowMVP, vec3 position)
 {
     vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 uv = shadow_coord.xy / shadow_coord.w;
+    if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return 1.0;
+    }
+    vec2 moments = texture(shadowMoments, uv).xy;
+    float depth = shadow_coord.z / shadow_coord.w;
+    if (depth <= moments.x) {
+        return 1.0;
+    }
+    // Chebyshev's upper bound, with light bleeding reduction
+    float variance = max(moments.y - moments.x * moments.x, 0.00002);
+    float d = depth - moments.x;
+    float p_max = variance / (variance + d * d);
+    return clamp((p_max - 0.2) / 0.8, 0.0, 1.0);
+}
+
+float point_shadow_depth(vec3 light_to_position, vec2 depth_range)
+{
+    float z = max(max(abs(light_to_position.x), abs(light_to_position.y)), abs(light_to_position.z));
+    float z_near = depth_range.x;
+    float z_far = depth_range.y;
+    float ndc_depth = (z_far + z_near) / (z_far - z_near) - 2.0 * z_far * z_near / ((z_far - z_near) * z);
+    return 0.5 * ndc_depth + 0.5;
+}
+
+float calculate_point_shadow(vec3 normal, samplerCube shadowMap, vec3 light_position, vec3 position, vec2 depth_range, int radius)
+{
+    vec3 light_to_position = position - light_position;
+    float z = max(max(abs(light_to_position.x), abs(light_to_position.y)), abs(light_to_position.z));
+    float texel_size = 2.0 * z / float(textureSize(shadowMap, 0).x);
+
+    // Offset along the normal to avoid shadow acne
+    light_to_position += normal * texel_size * 1.5;
+    float true_distance = point_shadow_depth(light_to_position, depth_range);
+
+    vec3 tangent = normalize(cross(light_to_position, abs(light_to_position.y) < 0.99 * length(light_to_position) ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0)));
+    vec3 bitangent = normalize(cross(light_to_position, tangent));
     float visibility = 0.0;
-    vec2 poissonDisk[4] = vec2[](
-                                 vec2( -0.94201624, -0.39906216 ),
-                                 vec2( 0.94558609, -0.76890725 ),
-                                 vec2( -0.094184101, -0.92938870 ),
-                                 vec2( 0.34495938, 0.29387760 )
-                                 );
-    for (int i=0;i<4;i++)
+    for (int x = -radius; x <= radius; x++)
+    {
+        for (int y = -radius; y <= radius; y++)
+        {
+            vec3 direction = light_to_position + (tangent * float(x) + bitangent * float(y)) * texel_size;
+            float shadow_cast_distance = texture(shadowMap, direction).x;
+            visibility += shadow_cast_distance > 0.999 || shadow_cast_distance > true_distance - 0.00005 ? 1.0 : 0.0;
+        }
+    }
+    float kernel_size = float(2 * radius + 1);
+    return visibility / (kernel_size * kernel_size);
+}
+
+// Integral of the cosine over the spherical edge between v1 and v2, see "Real-Time Polygonal-Light Shading with Linearly Transformed Cosines" by Heitz et al.
+vec3 integrate_cosine_edge(vec3 v1, vec3 v2)
+{
+    float x = dot(v1, v2);
+    float y = abs(x);
+    float a = 0.8543985 + (0.4965155 + 0.0145206 * y) * y;
+    float b = 3.4175940 + (4.1616724 + y) * y;
+    float v = a / b;
+    float theta_sintheta = (x > 0.0) ? v : 0.5 * inversesqrt(max(1.0 - x * x, 1e-7)) - v;
+    return cross(v1, v2) * theta_sintheta;
+}
+
+// The maximum number of vertices of an area light polygon after it is clipped by two planes.
+const int AREA_LIGHT_MAX_VERTICES = 10;
+
+// Clips the polygon with the given vertices relative to the shaded position to the half space in front of the plane through the shaded position with the given normal.
+int clip_polygon(inout vec3 vertices[AREA_LIGHT_MAX_VERTICES], int vertex_count, vec3 plane_normal)
+{
+    vec3 clipped[AREA_LIGHT_MAX_VERTICES];
+    int clipped_count = 0;
+    for (int i = 0; i < vertex_count; i++)
     {
-        visibility += is_visible(lightDirection, normal, shadowMap, shadow_coord, poissonDisk[i] * 0.001f);
+        vec3 current = vertices[i];
+        vec3 next = vertices[(i + 1) % vertex_count];
+        float current_distance = dot(plane_normal, current);
+        float next_distance = dot(plane_normal, next);
+        if (current_distance >= 0.0 && clipped_count < AREA_LIGHT_MAX_VERTICES) {
+            clipped[clipped_count] = current;
+            clipped_count++;
+        }
+        if ((current_distance >= 0.0) != (next_distance >= 0.0) && clipped_count < AREA_LIGHT_MAX_VERTICES) {
+            clipped[clipped_count] = mix(current, next, current_distance / (current_distance - next_distance));
+            clipped_count++;
+        }
     }
-    return visibility * 0.25;
+    vertices = clipped;
+    return clipped_count;
+}
+
+// Integrates the clamped cosine around the z axis over the polygon with the given vertices relative to the shaded position, after transforming the vertices by M.
+// The polygon must already be clipped to the half space in front of the plane which is mapped to z = 0 by M.
+float integrate_cosine_polygon(vec3 vertices[AREA_LIGHT_MAX_VERTICES], int vertex_count, mat3 M)
+{
+    if (vertex_count < 3) {
+        return 0.0;
+    }
+    vec3 sum = vec3(0.0);
+    for (int i = 0; i < vertex_count; i++)
+    {
+        sum += integrate_cosine_edge(normalize(M * vertices[i]), normalize(M * vertices[(i + 1) % vertex_count]));
+    }
+    return abs(sum.z) / (2.0 * PI);
+}
+
+// Computes an approximation of the light reflected from an area light facing the direction cross(ex, ey) with the given radiance.
+// The area light is a polygon with the given number of vertices (at most 8) placed on the ellipse spanned by ex and ey around the center.
+// The polygon is clipped to the hemisphere above the surface, after which the diffuse part is integrated exactly.
+// The specular part is not the GGX lobe but a cosine lobe around the reflection direction narrowed by the roughness,
+// which gives plausible soft highlights without the lookup tables needed for linearly transformed cosines.
+vec3 calculate_area_light(vec3 light_color, vec3 center, vec3 ex, vec3 ey, int vertex_count, vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness)
+{
+    if (dot(position - center, cross(ex, ey)) <= 0.0) {
+        return vec3(0.0);
+    }
+    vec3 N = normal;
+    vec3 V = view_direction;
+
+    vec3 vertices[AREA_LIGHT_MAX_VERTICES];
+    float angle_step = 2.0 * PI / float(vertex_count);
+    float angle_offset = vertex_count == 4 ? 0.25 * PI : 0.0;
+    float scale = vertex_count == 4 ? sqrt(2.0) : sqrt(PI / (0.5 * float(vertex_count) * sin(angle_step)));
+    for (int i = 0; i < vertex_count; i++)
+    {
+        float angle = angle_offset + float(i) * angle_step;
+        vertices[i] = center + scale * (cos(angle) * ex + sin(angle) * ey) - position;
+    }
+    int count = clip_polygon(vertices, vertex_count, N);
+
+    vec3 T1 = abs(N.y) < 0.99 ? normalize(cross(N, vec3(0.0, 1.0, 0.0))) : normalize(cross(N, vec3(1.0, 0.0, 0.0)));
+    vec3 T2 = cross(N, T1);
+    float diffuse = integrate_cosine_polygon(vertices, count, transpose(mat3(T1, T2, N)));
+
+    vec3 R = reflect(-V, N);
+    count = clip_polygon(vertices, count, R);
+    vec3 R1 = abs(R.y) < 0.99 ? normalize(cross(R, vec3(0.0, 1.0, 0.0))) : normalize(cross(R, vec3(1.0, 0.0, 0.0)));
+    vec3 R2 = cross(R, R1);
+    float alpha = max(roughness * roughness, 0.01);
+    mat3 lobe_scale = mat3(1.0 / alpha, 0.0, 0.0, 0.0, 1.0 / alpha, 0.0, 0.0, 0.0, 1.0);
+    float specular = integrate_cosine_polygon(vertices, count, lobe_scale * transpose(mat3(R1, R2, R)));
+
+    vec3 F0 = mix(DIELECTRIC_F0, surface_color, metallic);
+    vec3 specular_fresnel = fresnel_schlick_roughness(F0, max(dot(N, V), 0.0), roughness);
+    vec3 diffuse_color = (1.0 - metallic) * surface_color * (vec3(1.0) - specular_fresnel);
+    return light_color * (diffuse_color * diffuse + specular_fresnel * specular);
+}
+
+// Evaluates irradiance (divided by PI) from second order spherical harmonics coefficients which are already convolved with the cosine lobe.
+vec3 evaluate_irradiance_sh(vec3 c[9], vec3 n)
+{
+    return c[0] * 0.282095
+        + c[1] * 0.488603 * n.y
+        + c[2] * 0.488603 * n.z
+        + c[3] * 0.488603 * n.x
+        + c[4] * 1.092548 * n.x * n.y
+        + c[5] * 1.092548 * n.y * n.z
+        + c[6] * 0.315392 * (3.0 * n.z * n.z - 1.0)
+        + c[7] * 1.092548 * n.x * n.z
+        + c[8] * 0.546274 * (n.x * n.x - n.y * n.y);
 }
 
 vec3 ImportanceSampleGGX(vec2 Xi, vec3 N, float roughness)
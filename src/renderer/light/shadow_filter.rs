@@ -0,0 +1,249 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Specifies how the shadow map of a [DirectionalLight] or [SpotLight] is filtered when looked up in the shader.
+/// Filtering softens the edges between shadow and non-shadow and hides the aliasing caused by the limited resolution of the shadow map.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShadowFilter {
+    /// A single lookup in the shadow map which results in hard, aliased edges.
+    Hard,
+    /// Percentage-closer filtering which averages the shadow test over a square kernel of `2 * kernel_radius + 1` texels in each direction.
+    Pcf {
+        /// The radius of the filter kernel in texels.
+        kernel_radius: u32,
+    },
+    /// Percentage-closer soft shadows which estimates the distance to the occluder and widens the filter kernel accordingly,
+    /// resulting in shadows that are hard close to the occluder and soft further away.
+    Pcss {
+        /// The size of the light source relative to the size of the shadow map, for example `0.01`.
+        light_size: f32,
+    },
+    /// Variance shadow maps which stores the depth and the squared depth in a blurred texture and uses Chebyshev's inequality
+    /// to estimate the visibility. Gives soft shadows at the cost of an additional texture and some light bleeding.
+    /// The moments texture is created in `generate_shadow_map`, so the shadow map needs to be generated after choosing this filter.
+    Vsm,
+}
+
+impl Default for ShadowFilter {
+    fn default() -> Self {
+        Self::Pcf { kernel_radius: 1 }
+    }
+}
+
+impl ShadowFilter {
+    ///
+    /// Returns the shader source defining `float calculate_shadow{i}(vec3 light_direction, vec3 normal, vec3 position)`
+    /// which assumes that `shadowMap{i}` and `shadowMVP{i}` are declared.
+    ///
+    pub(crate) fn shader_source(&self, i: u32) -> String {
+        let (uniforms, call) = match self {
+            Self::Hard => (
+                String::new(),
+                format!("calculate_shadow_hard(light_direction, normal, shadowMap{i}, shadowMVP{i}, position)"),
+            ),
+            Self::Pcf { .. } => (
+                format!("uniform int shadowKernelRadius{i};"),
+                format!("calculate_shadow_pcf(light_direction, normal, shadowMap{i}, shadowMVP{i}, position, shadowKernelRadius{i})"),
+            ),
+            Self::Pcss { .. } => (
+                format!("uniform float shadowLightSize{i};"),
+                format!("calculate_shadow_pcss(light_direction, normal, shadowMap{i}, shadowMVP{i}, position, shadowLightSize{i})"),
+            ),
+            Self::Vsm => (
+                format!("uniform sampler2D shadowMoments{i};"),
+                format!("calculate_shadow_vsm(shadowMoments{i}, shadowMVP{i}, position)"),
+            ),
+        };
+        format!(
+            "
+                {uniforms}
+                float calculate_shadow{i}(vec3 light_direction, vec3 normal, vec3 position)
+                {{
+                    return {call};
+                }}
+            "
+        )
+    }
+
+    ///
+    /// Sends the shadow map, or the moments texture for [ShadowFilter::Vsm] which does not use the shadow map, and the filter parameters to the program.
+    ///
+    pub(crate) fn use_uniforms(
+        &self,
+        program: &Program,
+        i: u32,
+        shadow_map: &DepthTexture2D,
+        moments: Option<&Texture2D>,
+    ) {
+        if *self != Self::Vsm {
+            program.use_depth_texture(&format!("shadowMap{}", i), shadow_map);
+        }
+        match self {
+            Self::Hard => {}
+            Self::Pcf { kernel_radius } => {
+                program.use_uniform(&format!("shadowKernelRadius{}", i), *kernel_radius as i32)
+            }
+            Self::Pcss { light_size } => {
+                program.use_uniform(&format!("shadowLightSize{}", i), *light_size)
+            }
+            Self::Vsm => program.use_texture(
+                &format!("shadowMoments{}", i),
+                moments.expect("variance shadow maps require a moments texture"),
+            ),
+        }
+    }
+
+    ///
+    /// Returns the bits to combine with the id of the light, they are placed above the bits used to distinguish the light types.
+    ///
+    pub(crate) fn id(&self) -> u8 {
+        match self {
+            Self::Hard => 0b00u8 << 3,
+            Self::Pcf { .. } => 0b01u8 << 3,
+            Self::Pcss { .. } => 0b10u8 << 3,
+            Self::Vsm => 0b11u8 << 3,
+        }
+    }
+
+    ///
+    /// Returns the filter to use given the moments texture that was generated with the shadow map.
+    /// Falls back to the default filter if variance shadow maps are chosen but the moments are not available.
+    ///
+    pub(crate) fn resolve(&self, moments: Option<&Texture2D>) -> Self {
+        match self {
+            Self::Vsm if moments.is_none() => Self::default(),
+            _ => *self,
+        }
+    }
+}
+
+///
+/// Computes the blurred first and second moments of the depth in the given shadow map which is needed for [ShadowFilter::Vsm].
+///
+pub(crate) fn generate_shadow_moments(
+    context: &Context,
+    camera: &Camera,
+    shadow_texture: &DepthTexture2D,
+) -> Texture2D {
+    let (width, height) = (shadow_texture.width(), shadow_texture.height());
+    let mut moments = if supports_float_linear_filtering(context) {
+        new_moments_texture::<[f32; 2]>(context, width, height)
+    } else {
+        new_moments_texture::<[f16; 2]>(context, width, height)
+    };
+    moments
+        .as_color_target(None)
+        .clear(ClearState::color(1.0, 1.0, 0.0, 0.0))
+        .write::<RendererError>(|| {
+            apply_screen_effect(
+                context,
+                ShadowMomentsEffect,
+                camera,
+                &[],
+                None,
+                Some(DepthTexture::Single(shadow_texture)),
+            );
+            Ok(())
+        })
+        .unwrap();
+    moments
+}
+
+///
+/// Returns whether 32 bit float textures can be sampled with linear interpolation, which on OpenGL ES and WebGL requires the `OES_texture_float_linear` extension.
+/// Otherwise the moments are stored as 16 bit floats which can always be linearly interpolated.
+///
+fn supports_float_linear_filtering(context: &Context) -> bool {
+    let extensions = context.supported_extensions();
+    !context.version().is_embedded
+        || extensions.contains("GL_OES_texture_float_linear")
+        || extensions.contains("OES_texture_float_linear")
+}
+
+fn new_moments_texture<T: TextureDataType>(
+    context: &Context,
+    width: u32,
+    height: u32,
+) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        width,
+        height,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+struct ShadowMomentsEffect;
+
+impl Effect for ShadowMomentsEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                vec2 texel_size = 1.0 / vec2(textureSize(depthMap, 0));
+                vec2 moments = vec2(0.0);
+                for (int x = -2; x <= 2; x++)
+                {{
+                    for (int y = -2; y <= 2; y++)
+                    {{
+                        float depth = sample_depth(uvs + vec2(float(x), float(y)) * texel_size);
+                        moments += vec2(depth, depth * depth);
+                    }}
+                }}
+                outColor = vec4(moments / 25.0, 0.0, 1.0);
+            }}
+            ",
+            depth_texture
+                .expect("Must supply a depth texture to compute shadow moments")
+                .fragment_shader_source()
+        )
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14 | 0b1u16 << 11 | depth_texture.map(|t| t.id()).unwrap_or(0u16)
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if let Some(depth_texture) = depth_texture {
+            depth_texture.use_uniforms(program);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -10,6 +10,7 @@ pub struct SpotLight {
     context: Context,
     shadow_texture: Option<DepthTexture2D>,
     shadow_matrix: Mat4,
+    shadow_moments: Option<Texture2D>,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
@@ -22,6 +23,8 @@ pub struct SpotLight {
     pub cutoff: Radians,
     /// The [Attenuation] of the light.
     pub attenuation: Attenuation,
+    /// The [ShadowFilter] applied when looking up the shadow map.
+    pub shadow_filter: ShadowFilter,
 }
 
 impl SpotLight {
@@ -38,6 +41,8 @@ impl SpotLight {
         SpotLight {
             context: context.clone(),
             shadow_texture: None,
+            shadow_moments: None,
+            shadow_filter: ShadowFilter::default(),
             intensity,
             color,
             position: *position,
@@ -54,6 +59,7 @@ impl SpotLight {
     ///
     pub fn clear_shadow_map(&mut self) {
         self.shadow_texture = None;
+        self.shadow_moments = None;
         self.shadow_matrix = Mat4::identity();
     }
 
@@ -94,6 +100,7 @@ impl SpotLight {
         );
         self.shadow_matrix = shadow_matrix(&shadow_camera);
 
+        self.context.push_debug_group("Spot light shadow map");
         let mut shadow_texture = DepthTexture2D::new::<f32>(
             &self.context,
             texture_size,
@@ -101,20 +108,16 @@ impl SpotLight {
             Wrapping::ClampToEdge,
             Wrapping::ClampToEdge,
         );
-        let depth_material = DepthMaterial {
-            render_states: RenderStates {
-                write_mask: WriteMask::DEPTH,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        shadow_texture.set_label("Spot light shadow map");
+        let depth_material = DepthMaterial::new_depth_only();
         shadow_texture
             .as_depth_target()
             .clear(ClearState::default())
             .write::<RendererError>(|| {
+                let frustum = shadow_camera.frustum();
                 for geometry in geometries
                     .into_iter()
-                    .filter(|g| shadow_camera.in_frustum(&g.aabb()))
+                    .filter(|g| frustum.intersects_aabb(&g.aabb()))
                 {
                     render_with_material(
                         &self.context,
@@ -127,7 +130,10 @@ impl SpotLight {
                 Ok(())
             })
             .unwrap();
+        self.shadow_moments = (self.shadow_filter == ShadowFilter::Vsm)
+            .then(|| generate_shadow_moments(&self.context, &shadow_camera, &shadow_texture));
         self.shadow_texture = Some(shadow_texture);
+        self.context.pop_debug_group();
     }
 
     ///
@@ -136,6 +142,10 @@ impl SpotLight {
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
     }
+
+    fn resolved_shadow_filter(&self) -> ShadowFilter {
+        self.shadow_filter.resolve(self.shadow_moments.as_ref())
+    }
 }
 
 impl Light for SpotLight {
@@ -145,6 +155,7 @@ impl Light for SpotLight {
                 "
                     uniform sampler2D shadowMap{};
                     uniform mat4 shadowMVP{};
+                    {}
 
                     uniform vec3 color{};
                     uniform vec3 attenuation{};
@@ -165,12 +176,12 @@ impl Light for SpotLight {
                             vec3 light_color = attenuate(color{}, attenuation{}, distance);
                             result = calculate_light(light_color, light_direction, surface_color, view_direction, normal,
                                 metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
-                            result *= calculate_shadow(light_direction, normal, shadowMap{}, shadowMVP{}, position);
+                            result *= calculate_shadow{}(light_direction, normal, position);
                         }}
                         return result;
                     }}
 
-                ", i, i, i, i, i, i, i, i, i, i, i, i, i, i, i)
+                ", i, i, self.resolved_shadow_filter().shader_source(i), i, i, i, i, i, i, i, i, i, i, i, i)
         } else {
             format!(
                 "
@@ -202,8 +213,13 @@ impl Light for SpotLight {
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
         if let Some(ref tex) = self.shadow_texture {
-            program.use_depth_texture(&format!("shadowMap{}", i), tex);
             program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+            self.resolved_shadow_filter().use_uniforms(
+                program,
+                i,
+                tex,
+                self.shadow_moments.as_ref(),
+            );
         }
         program.use_uniform(
             &format!("color{}", i),
@@ -224,7 +240,7 @@ impl Light for SpotLight {
 
     fn id(&self) -> u8 {
         if self.shadow_texture.is_some() {
-            0b1u8 << 7 | 0b101u8
+            0b1u8 << 7 | self.resolved_shadow_filter().id() | 0b101u8
         } else {
             0b1u8 << 7 | 0b110u8
         }
@@ -22,7 +22,7 @@ macro_rules! impl_material_body {
         fn material_type(&self) -> MaterialType {
             self.$inner().material_type()
         }
-        fn id(&self) -> u16 {
+        fn id(&self) -> u32 {
             self.$inner().id()
         }
     };
@@ -74,6 +74,22 @@ mod isosurface_material;
 #[doc(inline)]
 pub use isosurface_material::*;
 
+mod toon_material;
+#[doc(inline)]
+pub use toon_material::*;
+
+mod water_material;
+#[doc(inline)]
+pub use water_material::*;
+
+mod splat_material;
+#[doc(inline)]
+pub use splat_material::*;
+
+mod custom_material;
+#[doc(inline)]
+pub use custom_material::*;
+
 use std::{ops::Deref, sync::Arc};
 
 ///
@@ -84,24 +100,48 @@ pub struct Texture2DRef {
     /// A reference to the texture.
     pub texture: Arc<Texture2D>,
     /// A transformation applied to the uv coordinates before reading a texel value at those uv coordinates.
-    /// This is primarily used in relation to texture atlasing.
+    /// This is primarily used in relation to texture atlasing and tiling, see [Texture2DRef::uv_transformation],
+    /// and can be changed at runtime without recompiling the shader, for example to scroll a texture across a surface.
     pub transformation: Mat3,
+    /// The set of uv coordinates used to look up the texture, where 0 is the uv coordinates of the [CpuMesh] and 1 is the second set of uv coordinates, see [Mesh::update_uvs2].
+    /// This corresponds to the `texCoord` index in glTF. Only the first two sets are supported, so any other value also uses the second set.
+    /// The channel is only used by the albedo, metallic roughness, occlusion, normal and emissive textures of a [PhysicalMaterial], other textures always use the first set.
+    pub uv_channel: u32,
 }
 
 impl Texture2DRef {
-    /// Creates a new [Texture2DRef] with an identity transformation from a [CpuTexture].
+    ///
+    /// Returns a transformation of the uv coordinates, which can be used as the [Texture2DRef::transformation], that first scales the uv coordinates,
+    /// then rotates them by the rotation in radians around the origin and finally adds the offset.
+    /// This is the same transformation as the `KHR_texture_transform` glTF extension, ie. it is given in the uv coordinates of a [CpuMesh] where the origin is at the top left corner of the texture,
+    /// so the rotation is counterclockwise. The returned transformation is converted to the uv coordinates used in the shaders, which are flipped in v.
+    ///
+    pub fn uv_transformation(offset: Vec2, rotation: f32, scale: Vec2) -> Mat3 {
+        let (sin, cos) = rotation.sin_cos();
+        let flip = Mat3::new(1.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 1.0, 1.0);
+        flip * Mat3::from_translation(offset)
+            * Mat3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+            * Mat3::from_nonuniform_scale(scale.x, scale.y)
+            * flip
+    }
+
+    /// Creates a new [Texture2DRef] with an identity transformation and the first set of uv coordinates from a [CpuTexture].
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn from_cpu_texture(context: &Context, cpu_texture: &CpuTexture) -> Self {
         Self {
             texture: Arc::new(Texture2D::new(context, cpu_texture)),
             transformation: Mat3::identity(),
+            uv_channel: 0,
         }
     }
 
-    /// Creates a new [Texture2DRef] with an identity transformation from a [Texture2D].
+    /// Creates a new [Texture2DRef] with an identity transformation and the first set of uv coordinates from a [Texture2D].
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn from_texture(texture: Texture2D) -> Self {
         Self {
             texture: Arc::new(texture),
             transformation: Mat3::identity(),
+            uv_channel: 0,
         }
     }
 }
@@ -124,6 +164,7 @@ impl std::convert::From<Arc<Texture2D>> for Texture2DRef {
         Self {
             texture,
             transformation: Mat3::identity(),
+            uv_channel: 0,
         }
     }
 }
@@ -156,6 +197,8 @@ pub struct FragmentAttributes {
     pub tangents: bool,
     /// UV coordinates: `in vec2 uvs;`
     pub uv: bool,
+    /// A second set of UV coordinates, for example used for lightmaps: `in vec2 uvs2;`
+    pub uv2: bool,
     /// Color: `in vec4 col;`
     pub color: bool,
 }
@@ -167,6 +210,7 @@ impl FragmentAttributes {
         normal: true,
         tangents: true,
         uv: true,
+        uv2: true,
         color: true,
     };
     /// No attributes
@@ -175,6 +219,7 @@ impl FragmentAttributes {
         normal: false,
         tangents: false,
         uv: false,
+        uv2: false,
         color: false,
     };
 }
@@ -183,6 +228,8 @@ impl FragmentAttributes {
 /// Represents a material that, together with a [geometry], can be rendered using [Geometry::render_with_material].
 /// Alternatively, a geometry and a material can be combined in a [Gm],
 /// thereby creating an [Object] which can be used in a render call, for example [RenderTarget::render].
+/// To write a custom material which is lit in the same way as the built-in materials, either implement this trait using [lights_shader_source]
+/// or use a [CustomMaterial], which only requires the body of the fragment shader.
 ///
 pub trait Material {
     ///
@@ -193,10 +240,10 @@ pub trait Material {
     ///
     /// Returns a unique ID for each variation of the shader source returned from [Material::fragment_shader_source].
     ///
-    /// **Note:** The last bit is reserved to internally implemented materials, so if implementing the [Material] trait
-    /// outside of this crate, always return an id that is smaller than `0b1u16 << 15`.
+    /// **Note:** The bits from the 16th bit and up are reserved to internally implemented materials, so if implementing the [Material] trait
+    /// outside of this crate, always return an id that is smaller than `0b1u32 << 15`.
     ///
-    fn id(&self) -> u16;
+    fn id(&self) -> u32;
 
     ///
     /// Returns a [FragmentAttributes] struct that describes which fragment attributes,
@@ -280,7 +327,7 @@ impl<T: Material> Material for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
-    fn id(&self) -> u16 {
+    fn id(&self) -> u32 {
         self.read().unwrap().id()
     }
 }
@@ -16,6 +16,8 @@ pub struct ColorMaterial {
     pub render_states: RenderStates,
     /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
     pub is_transparent: bool,
+    /// Whether to ignore the per vertex colors and per instance colors of the [Geometry], which are otherwise multiplied with the [Self::color].
+    pub ignore_vertex_colors: bool,
 }
 
 impl ColorMaterial {
@@ -51,6 +53,7 @@ impl ColorMaterial {
             texture,
             is_transparent: false,
             render_states: RenderStates::default(),
+            ignore_vertex_colors: false,
         }
     }
 
@@ -77,6 +80,7 @@ impl ColorMaterial {
                 blend: Blend::TRANSPARENCY,
                 ..Default::default()
             },
+            ignore_vertex_colors: false,
         }
     }
 
@@ -87,6 +91,7 @@ impl ColorMaterial {
             texture: physical_material.albedo_texture.clone(),
             render_states: physical_material.render_states,
             is_transparent: physical_material.is_transparent,
+            ignore_vertex_colors: physical_material.ignore_vertex_colors,
         }
     }
 }
@@ -98,11 +103,11 @@ impl FromCpuMaterial for ColorMaterial {
 }
 
 impl Material for ColorMaterial {
-    fn id(&self) -> u16 {
+    fn id(&self) -> u32 {
         if self.texture.is_some() {
-            0b1u16 << 15
+            0b1u32 << 15
         } else {
-            0b1u16 << 15 | 0b1u16
+            0b1u32 << 15 | 0b1u32
         }
     }
 
@@ -112,6 +117,7 @@ impl Material for ColorMaterial {
             shader.push_str("#define USE_TEXTURE\nin vec2 uvs;\n");
         }
         shader.push_str(include_str!("../../core/shared.frag"));
+        shader.push_str(Fog::fragment_shader_source());
         shader.push_str(ColorMapping::fragment_shader_source());
         shader.push_str(include_str!("shaders/color_material.frag"));
         shader
@@ -119,13 +125,15 @@ impl Material for ColorMaterial {
 
     fn fragment_attributes(&self) -> FragmentAttributes {
         FragmentAttributes {
-            color: true,
+            position: true,
+            color: !self.ignore_vertex_colors,
             uv: self.texture.is_some(),
             ..FragmentAttributes::NONE
         }
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.use_fog_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         program.use_uniform("surfaceColor", self.color.to_linear_srgb());
         if let Some(ref tex) = self.texture {
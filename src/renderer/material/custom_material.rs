@@ -0,0 +1,298 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+///
+/// A material defined by a fragment shader source given by the user, which makes it possible to write a custom material that is lit by the lights
+/// in the same way as the built-in materials without implementing the [Material] trait.
+///
+/// The given fragment shader source is added after the following declarations:
+/// - The inputs from the vertex shader given by the [FragmentAttributes], for example `in vec3 pos;` and `in vec3 nor;`.
+/// - The output `layout (location = 0) out vec4 outColor;`.
+/// - The uniform `uniform vec3 cameraPosition;` which contains the position of the viewer to use when calculating the lighting, see [Camera::lighting_position].
+/// - The function `vec3 calculate_lighting(vec3 camera_position, vec3 surface_color, vec3 position, vec3 normal, float metallic, float roughness, float occlusion)`
+///   which calculates the light reflected from the surface for the lights given when rendering, see [lights_shader_source].
+///   The shader must use this function if the material is rendered with any lights.
+/// - The functions `vec3 fog(vec3 color, vec3 position)`, `vec3 tone_mapping(vec3 color)` and `vec3 color_mapping(vec3 color)`, which should be applied to the final color in that order,
+///   using the [Camera::fog], [Camera::tone_mapping] and [Camera::color_mapping] of the camera.
+/// - The helper functions used by the built-in materials, for example `fresnel_schlick`, which are found in the `shared.frag` and `light_shared.frag` shader snippets.
+///
+/// The source must define the `main` function and any additional uniforms, which are set by the closure given to [CustomMaterial::new]. For example:
+/// ```no_rust
+/// uniform vec4 surfaceColor;
+///
+/// void main()
+/// {
+///     vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+///     vec3 light = calculate_lighting(cameraPosition, surfaceColor.rgb, pos, normal, 0.0, 0.5, 1.0);
+///     outColor = vec4(color_mapping(tone_mapping(fog(light, pos))), surfaceColor.a);
+/// }
+/// ```
+///
+/// During development, the source can instead be loaded from a file using [CustomMaterial::from_file] and reloaded
+/// whenever the file changes using [CustomMaterial::reload_if_changed].
+///
+#[derive(Clone)]
+pub struct CustomMaterial {
+    source: String,
+    id: u32,
+    fragment_attributes: FragmentAttributes,
+    lighting_model: LightingModel,
+    uniforms: Arc<dyn Fn(&Program) + Send + Sync>,
+    file: Option<ShaderFile>,
+    /// Render states.
+    pub render_states: RenderStates,
+    /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
+    pub is_transparent: bool,
+}
+
+impl CustomMaterial {
+    ///
+    /// Constructs a new custom material from the given fragment shader source, see [CustomMaterial] for the declarations available to the source.
+    /// The fragment attributes specify which inputs from the vertex shader are declared, the lighting model is used by the `calculate_lighting` function
+    /// and the `uniforms` closure is called before each draw call to set the uniforms declared in the source, for example using [Program::use_uniform].
+    ///
+    pub fn new(
+        source: impl Into<String>,
+        fragment_attributes: FragmentAttributes,
+        lighting_model: LightingModel,
+        uniforms: impl Fn(&Program) + Send + Sync + 'static,
+    ) -> Self {
+        let source = source.into();
+        Self {
+            id: unique_id(),
+            source,
+            fragment_attributes,
+            lighting_model,
+            uniforms: Arc::new(uniforms),
+            file: None,
+            render_states: RenderStates::default(),
+            is_transparent: false,
+        }
+    }
+
+    ///
+    /// Constructs a new custom material from the fragment shader source in the file at the given path, see [CustomMaterial::new].
+    /// The source is compiled before returning, so an error is returned if the file cannot be read or the source fails to compile.
+    ///
+    /// Call [CustomMaterial::reload_if_changed], for example each frame, to reload the source when the file is modified.
+    /// The file is not watched, instead its modification time is checked each time [CustomMaterial::reload_if_changed] is called.
+    /// This is meant for development, shaders should be embedded in the binary when shipping an application.
+    ///
+    pub fn from_file(
+        context: &Context,
+        path: impl AsRef<Path>,
+        fragment_attributes: FragmentAttributes,
+        lighting_model: LightingModel,
+        uniforms: impl Fn(&Program) + Send + Sync + 'static,
+    ) -> Result<Self, RendererError> {
+        let mut file = ShaderFile {
+            context: context.clone(),
+            path: path.as_ref().to_path_buf(),
+            modified: None,
+        };
+        let source = file.read()?;
+        let mut material = Self::new(source, fragment_attributes, lighting_model, uniforms);
+        material.validate(&file)?;
+        material.file = Some(file);
+        Ok(material)
+    }
+
+    ///
+    /// Reloads the fragment shader source if this material was constructed using [CustomMaterial::from_file] and the file has been modified since it was last read.
+    /// Returns whether or not the source was replaced.
+    /// This polls the modification time of the file, which is a cheap file system query, so it is fine to call this every frame.
+    /// When the source is replaced, the programs compiled from the previous source are removed from [Context::programs].
+    ///
+    /// The new source is compiled before it replaces the current source. If the file cannot be read or the new source fails to compile,
+    /// the error is returned and the material keeps rendering with the current source, so the render loop can continue while the shader is being fixed.
+    ///
+    pub fn reload_if_changed(&mut self) -> Result<bool, RendererError> {
+        let Some(mut file) = self.file.take() else {
+            return Ok(false);
+        };
+        let result = self.reload(&mut file);
+        self.file = Some(file);
+        result
+    }
+
+    fn reload(&mut self, file: &mut ShaderFile) -> Result<bool, RendererError> {
+        if !file.is_modified() {
+            return Ok(false);
+        }
+        let source = file.read()?;
+        if source == self.source {
+            return Ok(false);
+        }
+        let previous_source = std::mem::replace(&mut self.source, source);
+        if let Err(e) = self.validate(file) {
+            self.source = previous_source;
+            return Err(e);
+        }
+        // Clones of this material keep the previous source, so they keep the previous id,
+        // but the programs are removed from the cache and compiled again if the clones are rendered.
+        let previous_id = std::mem::replace(&mut self.id, unique_id());
+        remove_material_programs(&file.context, previous_id);
+        Ok(true)
+    }
+
+    ///
+    /// Compiles the source without any lights together with a minimal vertex shader that outputs the fragment attributes,
+    /// to catch errors in the source before it is used for rendering.
+    ///
+    fn validate(&self, file: &ShaderFile) -> Result<(), RendererError> {
+        let attributes = self.fragment_attributes;
+        let mut vertex_shader_source = String::from("in vec3 position;\n");
+        let mut assignments = String::new();
+        for (enabled, declaration, assignment) in [
+            (attributes.position, "out vec3 pos;", "pos = position;"),
+            (attributes.normal, "out vec3 nor;", "nor = position;"),
+            (
+                attributes.tangents,
+                "out vec3 tang;\nout vec3 bitang;",
+                "tang = position;\nbitang = position;",
+            ),
+            (attributes.uv, "out vec2 uvs;", "uvs = position.xy;"),
+            (attributes.uv2, "out vec2 uvs2;", "uvs2 = position.xy;"),
+            (
+                attributes.color,
+                "out vec4 col;",
+                "col = vec4(position, 1.0);",
+            ),
+        ] {
+            if enabled {
+                vertex_shader_source.push_str(declaration);
+                vertex_shader_source.push('\n');
+                assignments.push_str(assignment);
+                assignments.push('\n');
+            }
+        }
+        vertex_shader_source.push_str(&format!(
+            "void main()\n{{\n{}gl_Position = vec4(position, 1.0);\n}}\n",
+            assignments
+        ));
+        Program::from_source(
+            &file.context,
+            &vertex_shader_source,
+            &self.fragment_shader_source(&[]),
+        )
+        .map(|_| ())
+        .map_err(|e| {
+            RendererError::ShaderCompilation(file.path.display().to_string(), e.to_string())
+        })
+    }
+}
+
+///
+/// Returns a new id that is unique for each constructed or reloaded [CustomMaterial].
+/// Materials with identical sources still share the same compiled program, since [Context::program] caches programs by their source.
+///
+fn unique_id() -> u32 {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    // The bits of the count are placed in the bits not used by the internal markers of materials
+    0b1u32 << 15 | 0b1u32 << 28 | (count & 0x7fff) | ((count << 1) & 0x0fff_0000)
+}
+
+///
+/// The file a [CustomMaterial] source is loaded from together with the modification time when it was last read.
+///
+#[derive(Clone)]
+struct ShaderFile {
+    context: Context,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ShaderFile {
+    fn modification_time(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    fn is_modified(&self) -> bool {
+        match (self.modification_time(), self.modified) {
+            (Some(modified), Some(last_modified)) => modified != last_modified,
+            // Without modification times, the source is compared after reading the file
+            _ => true,
+        }
+    }
+
+    fn read(&mut self) -> Result<String, RendererError> {
+        self.modified = self.modification_time();
+        std::fs::read_to_string(&self.path).map_err(|e| {
+            RendererError::ShaderSourceRead(self.path.display().to_string(), e.to_string())
+        })
+    }
+}
+
+impl Material for CustomMaterial {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let attributes = self.fragment_attributes;
+        let mut output = lights_shader_source(lights, self.lighting_model);
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(Fog::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str("uniform vec3 cameraPosition;\n");
+        if attributes.position {
+            output.push_str("in vec3 pos;\n");
+        }
+        if attributes.normal {
+            output.push_str("in vec3 nor;\n");
+        }
+        if attributes.tangents {
+            output.push_str("in vec3 tang;\nin vec3 bitang;\n");
+        }
+        if attributes.uv {
+            output.push_str("in vec2 uvs;\n");
+        }
+        if attributes.uv2 {
+            output.push_str("in vec2 uvs2;\n");
+        }
+        if attributes.color {
+            output.push_str("in vec4 col;\n");
+        }
+        output.push_str("layout (location = 0) out vec4 outColor;\n");
+        output.push_str(&self.source);
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.fragment_attributes
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        if program.requires_uniform("toneMappingType") {
+            camera.use_tone_mapping_uniforms(program);
+        }
+        camera.use_fog_uniforms(program);
+        if program.requires_uniform("ColorMappingType") {
+            camera.color_mapping.use_uniforms(program);
+        }
+        program.use_uniform_if_required("cameraPosition", camera.lighting_position());
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        (self.uniforms)(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        if self.is_transparent {
+            MaterialType::Transparent
+        } else {
+            MaterialType::Opaque
+        }
+    }
+}
@@ -11,6 +11,11 @@ use crate::renderer::*;
 /// Instead render the object into a [RenderTarget] consisting of a [Texture2DArray] with three RGBA u8 layers as color target and a [DepthTexture2D] as depth target.
 /// Then call the [DeferredPhysicalMaterial::lighting_pass] method with these textures to render to the screen.
 ///
+/// Deferred rendering is opt-in per object by using this material, objects with other materials are rendered forward as usual afterwards.
+/// The lighting pass applies up to 16 lights per pass, so with more lights,
+/// the lighting is accumulated over several passes in a HDR texture, which is kept on the [Context] and reused between frames with the same viewport size.
+/// The layout of the geometry information is fixed, custom layouts are not supported.
+///
 #[derive(Clone)]
 pub struct DeferredPhysicalMaterial {
     /// Name.
@@ -47,6 +52,8 @@ pub struct DeferredPhysicalMaterial {
     /// If the alpha value of a pixel touched by an object with this material is less than the threshold, then that object is not contributing to the color of that pixel.
     /// On the other hand, if the alpha value is more than the threshold, then it is contributing fully to that pixel and thereby blocks out everything behind.
     pub alpha_cutout: Option<f32>,
+    /// Whether both sides of the surface are rendered, see [PhysicalMaterial::double_sided].
+    pub double_sided: bool,
 }
 
 impl DeferredPhysicalMaterial {
@@ -114,6 +121,7 @@ impl DeferredPhysicalMaterial {
             occlusion_strength: cpu_material.occlusion_strength,
             render_states: RenderStates::default(),
             alpha_cutout: cpu_material.alpha_cutout,
+            double_sided: true,
             emissive: cpu_material.emissive,
             emissive_texture,
         }
@@ -142,10 +150,11 @@ impl DeferredPhysicalMaterial {
             emissive: physical_material.emissive,
             emissive_texture: physical_material.emissive_texture.clone(),
             alpha_cutout: if physical_material.is_transparent {
-                Some(0.5)
+                Some(physical_material.alpha_cutout.unwrap_or(0.5))
             } else {
-                None
+                physical_material.alpha_cutout
             },
+            double_sided: physical_material.double_sided,
         }
     }
     ///
@@ -162,7 +171,7 @@ impl DeferredPhysicalMaterial {
     ) {
         apply_screen_effect(
             context,
-            lighting_pass::LightingPassEffect {},
+            lighting_pass::LightingPassEffect::default(),
             camera,
             lights,
             Some(geometry_pass_color_texture),
@@ -178,25 +187,25 @@ impl FromCpuMaterial for DeferredPhysicalMaterial {
 }
 
 impl Material for DeferredPhysicalMaterial {
-    fn id(&self) -> u16 {
-        let mut id = 0b1u16 << 15 | 0b1u16 << 6;
+    fn id(&self) -> u32 {
+        let mut id = 0b1u32 << 15 | 0b1u32 << 6;
         if self.albedo_texture.is_some() {
-            id |= 0b1u16;
+            id |= 0b1u32;
         }
         if self.metallic_roughness_texture.is_some() {
-            id |= 0b1u16 << 1;
+            id |= 0b1u32 << 1;
         }
         if self.occlusion_texture.is_some() {
-            id |= 0b1u16 << 2;
+            id |= 0b1u32 << 2;
         }
         if self.normal_texture.is_some() {
-            id |= 0b1u16 << 3;
+            id |= 0b1u32 << 3;
         }
         if self.emissive_texture.is_some() {
-            id |= 0b1u16 << 4;
+            id |= 0b1u32 << 4;
         }
         if self.alpha_cutout.is_some() {
-            id |= 0b1u16 << 5;
+            id |= 0b1u32 << 5;
         }
         id
     }
@@ -226,13 +235,9 @@ impl Material for DeferredPhysicalMaterial {
             if self.emissive_texture.is_some() {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
             }
-            if self.alpha_cutout.is_some() {
+            if let Some(alpha_cutout) = self.alpha_cutout {
                 output.push_str(
-                    format!(
-                        "#define ALPHACUT;\nfloat acut = {};",
-                        self.alpha_cutout.unwrap()
-                    )
-                    .as_str(),
+                    format!("#define ALPHACUT;\nfloat acut = {};", alpha_cutout).as_str(),
                 );
             }
         }
@@ -251,6 +256,7 @@ impl Material for DeferredPhysicalMaterial {
                 || self.occlusion_texture.is_some()
                 || self.emissive_texture.is_some()
                 || self.alpha_cutout.is_some(),
+            uv2: false,
             tangents: self.normal_texture.is_some(),
         }
     }
@@ -287,7 +293,14 @@ impl Material for DeferredPhysicalMaterial {
     }
 
     fn render_states(&self) -> RenderStates {
-        self.render_states
+        if self.double_sided {
+            self.render_states
+        } else {
+            RenderStates {
+                cull: Cull::Back,
+                ..self.render_states
+            }
+        }
     }
 
     fn material_type(&self) -> MaterialType {
@@ -310,6 +323,7 @@ impl Default for DeferredPhysicalMaterial {
             occlusion_strength: 1.0,
             render_states: RenderStates::default(),
             alpha_cutout: None,
+            double_sided: true,
             emissive: Srgba::BLACK,
             emissive_texture: None,
         }
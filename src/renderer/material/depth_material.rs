@@ -4,6 +4,7 @@ use crate::renderer::*;
 ///
 /// Used for rendering the distance from the camera to the object with this material in each pixel.
 /// Can be used for debug purposes but is also used to create shadow maps from light sources.
+/// Use [DepthMaterial::new_depth_only] to only write to the depth buffer, for example in a depth prepass.
 ///
 #[derive(Default, Clone)]
 pub struct DepthMaterial {
@@ -15,6 +16,22 @@ pub struct DepthMaterial {
     pub render_states: RenderStates,
 }
 
+impl DepthMaterial {
+    ///
+    /// Constructs a new depth material which only writes to the depth buffer, for example to render a depth prepass
+    /// before rendering the opaque objects with a depth test that discards the hidden fragments before shading them.
+    ///
+    pub fn new_depth_only() -> Self {
+        Self {
+            render_states: RenderStates {
+                write_mask: WriteMask::DEPTH,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
 impl FromCpuMaterial for DepthMaterial {
     fn from_cpu_material(_context: &Context, _cpu_material: &CpuMaterial) -> Self {
         Self::default()
@@ -22,8 +39,8 @@ impl FromCpuMaterial for DepthMaterial {
 }
 
 impl Material for DepthMaterial {
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b10u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b10u32
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
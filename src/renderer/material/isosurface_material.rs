@@ -25,8 +25,8 @@ pub struct IsosurfaceMaterial {
 }
 
 impl Material for IsosurfaceMaterial {
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b1100u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1100u32
     }
 
     fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
@@ -45,7 +45,7 @@ impl Material for IsosurfaceMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
+        camera.use_tone_mapping_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         for (i, light) in lights.iter().enumerate() {
             light.use_uniforms(program, i as u32);
@@ -78,6 +78,7 @@ impl Material for IsosurfaceMaterial {
 }
 
 impl FromCpuVoxelGrid for IsosurfaceMaterial {
+    #[allow(clippy::arc_with_non_send_sync)]
     fn from_cpu_voxel_grid(context: &Context, cpu_voxel_grid: &CpuVoxelGrid) -> Self {
         Self {
             voxels: std::sync::Arc::new(Texture3D::new(context, &cpu_voxel_grid.voxels)),
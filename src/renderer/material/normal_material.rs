@@ -6,12 +6,21 @@ use crate::renderer::*;
 /// A normal with an x value of -1 yields 0.0 in the red channel and an x value of 1 yields 1.0 in the red channel.
 /// The same mapping is applied from y value to green channel and z value to blue channel.
 ///
+/// It can also be used to render the input for effects that need the normals and depth of the scene, for example ambient occlusion,
+/// by rendering the normals in view space, see [Self::view_space], and the depth in the alpha channel, see [Self::output_depth],
+/// preferably to a floating point color texture. The [NormalMaterial::new_normal_depth] constructor enables both.
+///
 #[derive(Clone)]
 pub struct NormalMaterial {
     /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
     pub normal_scale: f32,
     /// A tangent space normal map, also known as bump map.
     pub normal_texture: Option<Texture2DRef>,
+    /// Whether the normals are given in view space instead of world space, ie. relative to the camera where the z axis points towards the viewer.
+    pub view_space: bool,
+    /// Whether the distance from the camera is written to the alpha channel, where a value of 0.0 is at the near plane and 1.0 at the far plane of the camera,
+    /// which is the same as the output of a default [DepthMaterial]. Otherwise, the alpha value is 1.0.
+    pub output_depth: bool,
     /// Render states.
     pub render_states: RenderStates,
 }
@@ -26,7 +35,19 @@ impl NormalMaterial {
         Self {
             normal_scale: cpu_material.normal_scale,
             normal_texture,
-            render_states: RenderStates::default(),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Constructs a new normal material which renders the normals in view space and the distance from the camera in the alpha channel,
+    /// for example to use as the input to ambient occlusion or other effects which need the normals and depth of the scene.
+    ///
+    pub fn new_normal_depth() -> Self {
+        Self {
+            view_space: true,
+            output_depth: true,
+            ..Default::default()
         }
     }
 
@@ -35,6 +56,8 @@ impl NormalMaterial {
         Self {
             normal_scale: physical_material.normal_scale,
             normal_texture: physical_material.normal_texture.clone(),
+            view_space: false,
+            output_depth: false,
             render_states: RenderStates {
                 write_mask: WriteMask::default(),
                 blend: Blend::Disabled,
@@ -51,31 +74,39 @@ impl FromCpuMaterial for NormalMaterial {
 }
 
 impl Material for NormalMaterial {
-    fn id(&self) -> u16 {
-        if self.normal_texture.is_some() {
-            0b1u16 << 15 | 0b110u16
+    fn id(&self) -> u32 {
+        let mut id = if self.normal_texture.is_some() {
+            0b1u32 << 15 | 0b110u32
         } else {
-            0b1u16 << 15 | 0b111u16
+            0b1u32 << 15 | 0b111u32
+        };
+        if self.view_space {
+            id |= 0b1u32 << 16;
         }
+        if self.output_depth {
+            id |= 0b1u32 << 17;
+        }
+        id
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
-        let mut attributes = FragmentAttributes {
-            normal: true,
-            ..FragmentAttributes::NONE
-        };
         let mut source = String::new();
         if self.normal_texture.is_some() {
-            attributes.uv = true;
-            attributes.tangents = true;
             source.push_str("#define USE_TEXTURE\nin vec2 uvs;\nin vec3 tang;\nin vec3 bitang;\n");
         }
+        if self.view_space {
+            source.push_str("#define USE_VIEW_SPACE\n");
+        }
+        if self.output_depth {
+            source.push_str("#define USE_DEPTH\n");
+        }
         source.push_str(include_str!("shaders/normal_material.frag"));
         source
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
         FragmentAttributes {
+            position: self.output_depth,
             normal: true,
             uv: self.normal_texture.is_some(),
             tangents: self.normal_texture.is_some(),
@@ -83,12 +114,20 @@ impl Material for NormalMaterial {
         }
     }
 
-    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
         if let Some(ref tex) = self.normal_texture {
             program.use_uniform("normalScale", self.normal_scale);
             program.use_uniform("textureTransformation", tex.transformation);
             program.use_texture("normalTexture", tex);
         }
+        if self.view_space {
+            program.use_uniform("viewMatrix", camera.view());
+        }
+        if self.output_depth {
+            program.use_uniform("minDistance", camera.z_near());
+            program.use_uniform("maxDistance", camera.z_far());
+            program.use_uniform("eye", camera.position());
+        }
     }
     fn render_states(&self) -> RenderStates {
         self.render_states
@@ -103,6 +142,8 @@ impl Default for NormalMaterial {
         Self {
             normal_texture: None,
             normal_scale: 1.0,
+            view_space: false,
+            output_depth: false,
             render_states: RenderStates::default(),
         }
     }
@@ -39,8 +39,73 @@ pub struct PhysicalMaterial {
     /// Texture with color of light shining from an object.
     /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
     pub emissive_texture: Option<Texture2DRef>,
+    /// Texture with light baked into it, for example using [Mesh::bake_lightmap], which is multiplied with the albedo color and added to the light from the lights given when rendering.
+    /// The texture is looked up using the second set of uv coordinates, see [Mesh::update_uvs2].
+    pub lightmap_texture: Option<Texture2DRef>,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
+    /// A value in the range `[0..1]` specifying the strength of a clear coat, ie. a thin transparent layer on top of the surface like the lacquer on car paint.
+    /// The clear coat reflects light using the normal of the geometry, so it is not affected by the [Self::normal_texture].
+    pub clearcoat: f32,
+    /// A value in the range `[0..1]` specifying how rough the clear coat is.
+    pub clearcoat_roughness: f32,
+    /// The color of the sheen, ie. the light scattered by the fibers of cloth like velvet, which is mostly seen at grazing angles. No sheen is rendered if the color is black.
+    pub sheen_color: Srgba,
+    /// A value in the range `[0..1]` specifying how rough the sheen is, where a rough sheen is seen at a wider range of angles.
+    pub sheen_roughness: f32,
+    /// The color of the light scattered below the surface, which approximates subsurface scattering in materials like skin, wax and leaves. No subsurface scattering is rendered if the color is black.
+    /// The scattered light is multiplied with the albedo color and lights the surface past the point where it turns away from the light, and thin parts of the surface, see [Self::subsurface_thickness], are lit from behind.
+    /// The light from behind the surface is blocked by any shadows the object casts on itself.
+    pub subsurface_color: Srgba,
+    /// A value in the range `[0..1]` specifying how thick the surface is, where a thin surface lets the light from behind the surface through. The default is 1.0.
+    pub subsurface_thickness: f32,
+    /// Texture containing the thickness of the surface which is multiplied with the [Self::subsurface_thickness]. The thickness values are sampled from the red channel.
+    pub subsurface_thickness_texture: Option<Texture2DRef>,
+    /// A value in the range `[0..1]` specifying how much of the light that is not reflected is transmitted through the surface, for example for glass and thin plastic.
+    /// A transmissive material is rendered after the opaque objects, ie. it has the [MaterialType::Transparent] material type.
+    /// Without a [Self::refraction_texture], the transmitted light is blended with the scene behind the surface using premultiplied alpha blending, so it is not refracted or tinted by the albedo color.
+    pub transmission: f32,
+    /// A texture with the colors of the scene behind a transmissive surface, usually the opaque objects rendered with the same camera into a texture the size of the viewport.
+    /// The texture is looked up where the refracted view ray exits the volume, see [Self::thickness], and is blurred using the mip maps of the texture for rough surfaces.
+    pub refraction_texture: Option<Texture2DRef>,
+    /// The thickness of the volume below a transmissive surface in world space, which determines how much the scene behind the surface is displaced by refraction.
+    /// A thickness of zero means that the surface is thin walled, ie. the view ray is not refracted.
+    pub thickness: f32,
+    /// The index of refraction of non-metals, which determines how much light is reflected and how the transmitted light is refracted. The default is 1.5.
+    pub index_of_refraction: f32,
+    /// A value in the range `[0..1]` which is multiplied with the reflectance of non-metals given by the [Self::index_of_refraction].
+    pub specular: f32,
+    /// Color which is multiplied with the reflectance of non-metals given by the [Self::index_of_refraction].
+    pub specular_color: Srgba,
+    /// A value in the range `[0..1]` specifying how much the specular reflections are stretched, for example for brushed metal.
+    /// The roughness is increased along the anisotropy direction, which is the tangent of the geometry rotated by [Self::anisotropy_rotation], so the geometry must have tangents if the strength is larger than zero.
+    pub anisotropy_strength: f32,
+    /// The rotation of the anisotropy direction in radians, counterclockwise from the tangent of the geometry.
+    pub anisotropy_rotation: f32,
+    /// Texture containing the anisotropy direction in tangent space in the red and green channels, which is rotated by [Self::anisotropy_rotation],
+    /// and a strength in the blue channel which is multiplied with the [Self::anisotropy_strength].
+    pub anisotropy_texture: Option<Texture2DRef>,
+    /// A height map used for parallax occlusion mapping, which displaces the texture coordinates of all textures to give the illusion of surface relief, for example on bricks and rocks.
+    /// The height values are sampled from the red channel, where white is the top of the surface and black is [Self::height_scale] below it. The geometry must have tangents when using a height map.
+    pub height_texture: Option<Texture2DRef>,
+    /// The depth of the surface relief given by the [Self::height_texture] relative to the size of the texture.
+    pub height_scale: f32,
+    /// The number of steps used when searching for the intersection between the view ray and the surface given by the [Self::height_texture].
+    /// More steps gives a more accurate result at a higher cost.
+    pub parallax_step_count: u32,
+    /// Projects the textures onto the surface along the world axes instead of using the uv coordinates of the geometry, see [TriplanarMapping].
+    /// This is useful for geometry without uv coordinates, for example terrain or the output of marching cubes. The [Self::height_texture] is not used when projecting the textures.
+    pub triplanar_mapping: Option<TriplanarMapping>,
+    /// A threshold on the alpha value of the color, which corresponds to the `MASK` alpha mode in glTF, for example for foliage.
+    /// The surface is not rendered where the alpha value is less than the threshold and is rendered fully opaque elsewhere, so a material with an alpha cutout should not be transparent.
+    /// If [RenderStates::alpha_to_coverage] is enabled in the [Self::render_states], the edges of the cutout are smoothed when rendering to a multisample render target.
+    pub alpha_cutout: Option<f32>,
+    /// Whether both sides of the surface are rendered, which corresponds to the `doubleSided` property in glTF, for example for thin geometry like leaves and cloth.
+    /// The normal is flipped on the back side, so both sides are lit as if they were facing the viewer.
+    /// If false, the back faces are culled regardless of the cull mode in the [Self::render_states]. The default is true.
+    pub double_sided: bool,
+    /// Whether to ignore the per vertex colors and per instance colors of the [Geometry], for example the `COLOR_0` attribute in glTF, which are otherwise multiplied with the [Self::albedo].
+    pub ignore_vertex_colors: bool,
 }
 
 impl PhysicalMaterial {
@@ -48,11 +113,16 @@ impl PhysicalMaterial {
     /// Constructs a new physical material from a [CpuMaterial].
     /// If the input contains an [CpuMaterial::occlusion_metallic_roughness_texture], this texture is used for both
     /// [PhysicalMaterial::metallic_roughness_texture] and [PhysicalMaterial::occlusion_texture] while any [CpuMaterial::metallic_roughness_texture] or [CpuMaterial::occlusion_texture] are ignored.
-    /// Tries to infer whether this material is transparent or opaque from the alpha value of the albedo color and the alpha values in the albedo texture.
+    /// Tries to infer whether this material is transparent or opaque from the alpha value of the albedo color and the alpha values in the albedo texture,
+    /// unless the material has an [CpuMaterial::alpha_cutout] in which case it is opaque.
     /// Since this is not always correct, it is preferred to use [PhysicalMaterial::new_opaque] or [PhysicalMaterial::new_transparent].
     ///
     pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        Self::new_internal(context, cpu_material, super::is_transparent(cpu_material))
+        Self::new_internal(
+            context,
+            cpu_material,
+            cpu_material.alpha_cutout.is_none() && super::is_transparent(cpu_material),
+        )
     }
 
     /// Constructs a new opaque physical material from a [CpuMaterial].
@@ -138,7 +208,229 @@ impl PhysicalMaterial {
             is_transparent,
             emissive: cpu_material.emissive,
             emissive_texture,
+            lightmap_texture: None,
             lighting_model: cpu_material.lighting_model,
+            transmission: cpu_material.transmission,
+            index_of_refraction: cpu_material.index_of_refraction,
+            alpha_cutout: cpu_material.alpha_cutout,
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Sets the material properties which are not part of a [CpuMaterial], ie. the clear coat, sheen, specular, thickness and anisotropy properties.
+    /// If the extensions contain an alpha cutout, the alpha cutout is also set and the material is made opaque.
+    /// The [Self::double_sided] property and the uv transformations and uv channels of the textures are also set from the extensions.
+    ///
+    pub fn set_extensions(&mut self, extensions: &CpuMaterialExtensions) {
+        self.clearcoat = extensions.clearcoat;
+        self.clearcoat_roughness = extensions.clearcoat_roughness;
+        self.sheen_color = extensions.sheen_color;
+        self.sheen_roughness = extensions.sheen_roughness;
+        self.specular = extensions.specular;
+        self.specular_color = extensions.specular_color;
+        self.thickness = extensions.thickness;
+        self.anisotropy_strength = extensions.anisotropy_strength;
+        self.anisotropy_rotation = extensions.anisotropy_rotation;
+        self.double_sided = extensions.double_sided;
+        for (texture, transformation, uv_channel) in [
+            (
+                &mut self.albedo_texture,
+                extensions.albedo_texture_transformation,
+                extensions.albedo_texture_uv_channel,
+            ),
+            (
+                &mut self.metallic_roughness_texture,
+                extensions.metallic_roughness_texture_transformation,
+                extensions.metallic_roughness_texture_uv_channel,
+            ),
+            (
+                &mut self.occlusion_texture,
+                extensions.occlusion_texture_transformation,
+                extensions.occlusion_texture_uv_channel,
+            ),
+            (
+                &mut self.normal_texture,
+                extensions.normal_texture_transformation,
+                extensions.normal_texture_uv_channel,
+            ),
+            (
+                &mut self.emissive_texture,
+                extensions.emissive_texture_transformation,
+                extensions.emissive_texture_uv_channel,
+            ),
+        ] {
+            if let Some(texture) = texture {
+                texture.transformation = transformation;
+                texture.uv_channel = uv_channel;
+            }
+        }
+        if extensions.alpha_cutout.is_some() {
+            self.alpha_cutout = extensions.alpha_cutout;
+            self.is_transparent = false;
+            self.render_states.write_mask = WriteMask::default();
+            self.render_states.blend = Blend::Disabled;
+        }
+    }
+
+    fn has_sheen(&self) -> bool {
+        self.sheen_color.r > 0 || self.sheen_color.g > 0 || self.sheen_color.b > 0
+    }
+
+    fn has_subsurface(&self) -> bool {
+        self.subsurface_color.r > 0 || self.subsurface_color.g > 0 || self.subsurface_color.b > 0
+    }
+
+    fn has_subsurface_thickness_texture(&self) -> bool {
+        self.has_subsurface() && self.subsurface_thickness_texture.is_some()
+    }
+
+    fn has_anisotropy_texture(&self) -> bool {
+        self.anisotropy_strength > 0.0 && self.anisotropy_texture.is_some()
+    }
+
+    fn has_height_texture(&self) -> bool {
+        self.height_texture.is_some() && self.triplanar_mapping.is_none()
+    }
+
+    fn requires_uvs(&self) -> bool {
+        self.triplanar_mapping.is_none()
+            && (self.albedo_texture.is_some()
+                || self.metallic_roughness_texture.is_some()
+                || self.normal_texture.is_some()
+                || self.occlusion_texture.is_some()
+                || self.emissive_texture.is_some()
+                || self.has_anisotropy_texture()
+                || self.has_subsurface_thickness_texture()
+                || self.height_texture.is_some())
+    }
+
+    // The textures which can be looked up using the second set of uv coordinates together with the name used for the uv channel in the shader
+    fn uv_channel_textures(&self) -> [(&str, &Option<Texture2DRef>); 5] {
+        [
+            ("ALBEDO", &self.albedo_texture),
+            ("METALLIC_ROUGHNESS", &self.metallic_roughness_texture),
+            ("OCCLUSION", &self.occlusion_texture),
+            ("NORMAL", &self.normal_texture),
+            ("EMISSIVE", &self.emissive_texture),
+        ]
+    }
+
+    fn requires_uvs2(&self) -> bool {
+        self.triplanar_mapping.is_none()
+            && self.uv_channel_textures().iter().any(|(_, texture)| {
+                texture
+                    .as_ref()
+                    .is_some_and(|texture| texture.uv_channel > 0)
+            })
+    }
+
+    fn requires_tangents(&self) -> bool {
+        (self.normal_texture.is_some() && self.triplanar_mapping.is_none())
+            || self.anisotropy_strength > 0.0
+            || self.has_height_texture()
+    }
+}
+
+///
+/// Options for projecting the textures of a [PhysicalMaterial] onto the surface along each of the world axes, also called triplanar mapping.
+/// The textures are tiled across the planes perpendicular to the x, y and z axis and the three projections are blended using the normal of the surface.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriplanarMapping {
+    /// The size of one tile of the textures in world space.
+    pub tile_size: f32,
+    /// How sharp the transition between the projections is, where a higher value gives narrower seams between the projections.
+    pub blend_sharpness: f32,
+}
+
+impl Default for TriplanarMapping {
+    fn default() -> Self {
+        Self {
+            tile_size: 1.0,
+            blend_sharpness: 4.0,
+        }
+    }
+}
+
+///
+/// The properties of a [PhysicalMaterial] given by the glTF material extensions which are not part of a [CpuMaterial],
+/// ie. the clear coat (`KHR_materials_clearcoat`), sheen (`KHR_materials_sheen`), specular (`KHR_materials_specular`), thickness (`KHR_materials_volume`) and anisotropy (`KHR_materials_anisotropy`) properties.
+/// The transmission and index of refraction are part of the [CpuMaterial].
+/// It also contains the alpha cutout of materials with the `MASK` alpha mode, which defaults to 0.5 when the glTF file does not specify it, whether the material is double sided
+/// and the uv transformations of the textures given by the `KHR_texture_transform` extension together with the set of uv coordinates used by each texture.
+/// Only the factors are supported, the textures of the extensions are ignored.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CpuMaterialExtensions {
+    /// See [PhysicalMaterial::clearcoat].
+    pub clearcoat: f32,
+    /// See [PhysicalMaterial::clearcoat_roughness].
+    pub clearcoat_roughness: f32,
+    /// See [PhysicalMaterial::sheen_color].
+    pub sheen_color: Srgba,
+    /// See [PhysicalMaterial::sheen_roughness].
+    pub sheen_roughness: f32,
+    /// See [PhysicalMaterial::specular].
+    pub specular: f32,
+    /// See [PhysicalMaterial::specular_color].
+    pub specular_color: Srgba,
+    /// See [PhysicalMaterial::thickness].
+    pub thickness: f32,
+    /// See [PhysicalMaterial::anisotropy_strength].
+    pub anisotropy_strength: f32,
+    /// See [PhysicalMaterial::anisotropy_rotation].
+    pub anisotropy_rotation: f32,
+    /// See [PhysicalMaterial::alpha_cutout].
+    pub alpha_cutout: Option<f32>,
+    /// See [PhysicalMaterial::double_sided]. The default is false, as in glTF.
+    pub double_sided: bool,
+    /// The transformation of the uv coordinates of the [PhysicalMaterial::albedo_texture], see [Texture2DRef::uv_transformation].
+    pub albedo_texture_transformation: Mat3,
+    /// The transformation of the uv coordinates of the [PhysicalMaterial::metallic_roughness_texture], see [Texture2DRef::uv_transformation].
+    pub metallic_roughness_texture_transformation: Mat3,
+    /// The transformation of the uv coordinates of the [PhysicalMaterial::occlusion_texture], see [Texture2DRef::uv_transformation].
+    pub occlusion_texture_transformation: Mat3,
+    /// The transformation of the uv coordinates of the [PhysicalMaterial::normal_texture], see [Texture2DRef::uv_transformation].
+    pub normal_texture_transformation: Mat3,
+    /// The transformation of the uv coordinates of the [PhysicalMaterial::emissive_texture], see [Texture2DRef::uv_transformation].
+    pub emissive_texture_transformation: Mat3,
+    /// The set of uv coordinates used by the [PhysicalMaterial::albedo_texture], see [Texture2DRef::uv_channel].
+    pub albedo_texture_uv_channel: u32,
+    /// The set of uv coordinates used by the [PhysicalMaterial::metallic_roughness_texture], see [Texture2DRef::uv_channel].
+    pub metallic_roughness_texture_uv_channel: u32,
+    /// The set of uv coordinates used by the [PhysicalMaterial::occlusion_texture], see [Texture2DRef::uv_channel].
+    pub occlusion_texture_uv_channel: u32,
+    /// The set of uv coordinates used by the [PhysicalMaterial::normal_texture], see [Texture2DRef::uv_channel].
+    pub normal_texture_uv_channel: u32,
+    /// The set of uv coordinates used by the [PhysicalMaterial::emissive_texture], see [Texture2DRef::uv_channel].
+    pub emissive_texture_uv_channel: u32,
+}
+
+impl Default for CpuMaterialExtensions {
+    fn default() -> Self {
+        Self {
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.0,
+            sheen_color: Srgba::BLACK,
+            sheen_roughness: 0.0,
+            specular: 1.0,
+            specular_color: Srgba::WHITE,
+            thickness: 0.0,
+            anisotropy_strength: 0.0,
+            anisotropy_rotation: 0.0,
+            alpha_cutout: None,
+            double_sided: false,
+            albedo_texture_transformation: Mat3::identity(),
+            metallic_roughness_texture_transformation: Mat3::identity(),
+            occlusion_texture_transformation: Mat3::identity(),
+            normal_texture_transformation: Mat3::identity(),
+            emissive_texture_transformation: Mat3::identity(),
+            albedo_texture_uv_channel: 0,
+            metallic_roughness_texture_uv_channel: 0,
+            occlusion_texture_uv_channel: 0,
+            normal_texture_uv_channel: 0,
+            emissive_texture_uv_channel: 0,
         }
     }
 }
@@ -150,52 +442,155 @@ impl FromCpuMaterial for PhysicalMaterial {
 }
 
 impl Material for PhysicalMaterial {
-    fn id(&self) -> u16 {
-        let mut id = 0b1u16 << 15 | 0b1u16 << 5;
+    fn id(&self) -> u32 {
+        let mut id = 0b1u32 << 15 | 0b1u32 << 5;
         if self.albedo_texture.is_some() {
-            id |= 0b1u16;
+            id |= 0b1u32;
         }
         if self.metallic_roughness_texture.is_some() {
-            id |= 0b1u16 << 1;
+            id |= 0b1u32 << 1;
         }
         if self.occlusion_texture.is_some() {
-            id |= 0b1u16 << 2;
+            id |= 0b1u32 << 2;
         }
         if self.normal_texture.is_some() {
-            id |= 0b1u16 << 3;
+            id |= 0b1u32 << 3;
         }
         if self.emissive_texture.is_some() {
-            id |= 0b1u16 << 4;
+            id |= 0b1u32 << 4;
+        }
+        if self.lightmap_texture.is_some() {
+            id |= 0b1u32 << 8;
+        }
+        if self.clearcoat > 0.0 {
+            id |= 0b1u32 << 9;
+        }
+        if self.has_sheen() {
+            id |= 0b1u32 << 10;
+        }
+        if self.transmission > 0.0 {
+            id |= 0b1u32 << 11;
+            if self.refraction_texture.is_some() {
+                id |= 0b1u32 << 12;
+            }
+        }
+        if self.anisotropy_strength > 0.0 {
+            id |= 0b1u32 << 13;
+            if self.anisotropy_texture.is_some() {
+                id |= 0b1u32 << 14;
+            }
+        }
+        if self.has_height_texture() {
+            id |= 0b1u32 << 7;
+        }
+        if self.triplanar_mapping.is_some() {
+            id |= 0b1u32 << 16;
+        }
+        if self.alpha_cutout.is_some() {
+            id |= 0b1u32 << 17;
+            if self.render_states.alpha_to_coverage {
+                id |= 0b1u32 << 18;
+            }
+        }
+        if self.has_subsurface() {
+            id |= 0b1u32 << 19;
+            if self.subsurface_thickness_texture.is_some() {
+                id |= 0b1u32 << 20;
+            }
+        }
+        for (i, (_, texture)) in self.uv_channel_textures().iter().enumerate() {
+            if texture
+                .as_ref()
+                .is_some_and(|texture| texture.uv_channel > 0)
+            {
+                id |= 0b1u32 << (21 + i);
+            }
         }
         id
     }
 
     fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
-        let mut output = lights_shader_source(lights, self.lighting_model);
-        if self.albedo_texture.is_some()
-            || self.metallic_roughness_texture.is_some()
-            || self.normal_texture.is_some()
-            || self.occlusion_texture.is_some()
-            || self.emissive_texture.is_some()
-        {
-            output.push_str("in vec2 uvs;\n");
-            if self.albedo_texture.is_some() {
-                output.push_str("#define USE_ALBEDO_TEXTURE;\n");
-            }
-            if self.metallic_roughness_texture.is_some() {
-                output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
+        let mut output =
+            String::from("uniform vec3 dielectricF0;\n#define DIELECTRIC_F0 dielectricF0\n");
+        if self.anisotropy_strength > 0.0 {
+            // Must be defined before the light functions, which use the anisotropy direction and strength set by the material
+            output.push_str("#define USE_ANISOTROPY;\n");
+        }
+        if self.has_subsurface() {
+            // Must be defined before the light functions, which use the subsurface color and thickness set by the material
+            output.push_str("#define USE_SUBSURFACE;\n");
+        }
+        output.push_str(&lights_shader_source(lights, self.lighting_model));
+        if self.requires_tangents() {
+            output.push_str("in vec3 tang;\nin vec3 bitang;\n");
+        }
+        if self.triplanar_mapping.is_some() {
+            output.push_str("#define USE_TRIPLANAR;\n");
+        } else if self.requires_uvs() {
+            output.push_str("#define USE_UVS;\nin vec2 uvs;\n");
+            if self.requires_uvs2() {
+                output.push_str("#define USE_UVS2;\n");
             }
-            if self.occlusion_texture.is_some() {
-                output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
+        }
+        for (name, texture) in self.uv_channel_textures() {
+            if let Some(texture) = texture {
+                output.push_str(&format!(
+                    "#define {}_UV_CHANNEL {}\n",
+                    name,
+                    texture.uv_channel.min(1)
+                ));
             }
-            if self.normal_texture.is_some() {
-                output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+        }
+        if self.lightmap_texture.is_some() || self.requires_uvs2() {
+            output.push_str("in vec2 uvs2;\n");
+        }
+        if self.albedo_texture.is_some() {
+            output.push_str("#define USE_ALBEDO_TEXTURE;\n");
+        }
+        if self.metallic_roughness_texture.is_some() {
+            output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
+        }
+        if self.occlusion_texture.is_some() {
+            output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
+        }
+        if self.normal_texture.is_some() {
+            output.push_str("#define USE_NORMAL_TEXTURE;\n");
+        }
+        if self.emissive_texture.is_some() {
+            output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
+        }
+        if self.has_anisotropy_texture() {
+            output.push_str("#define USE_ANISOTROPY_TEXTURE;\n");
+        }
+        if self.has_subsurface_thickness_texture() {
+            output.push_str("#define USE_SUBSURFACE_THICKNESS_TEXTURE;\n");
+        }
+        if self.has_height_texture() {
+            output.push_str("#define USE_HEIGHT_TEXTURE;\n");
+        }
+        if self.lightmap_texture.is_some() {
+            output.push_str("#define USE_LIGHTMAP_TEXTURE;\n");
+        }
+        if self.alpha_cutout.is_some() {
+            output.push_str("#define USE_ALPHA_CUTOUT;\n");
+            if self.render_states.alpha_to_coverage {
+                output.push_str("#define USE_ALPHA_TO_COVERAGE;\n");
             }
-            if self.emissive_texture.is_some() {
-                output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
+        }
+        if self.clearcoat > 0.0 {
+            output.push_str("#define USE_CLEARCOAT;\n");
+        }
+        if self.has_sheen() {
+            output.push_str("#define USE_SHEEN;\n");
+        }
+        if self.transmission > 0.0 {
+            output.push_str("#define USE_TRANSMISSION;\n");
+            if self.refraction_texture.is_some() {
+                output.push_str("#define USE_REFRACTION_TEXTURE;\n");
             }
         }
         output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(Fog::fragment_shader_source());
         output.push_str(ColorMapping::fragment_shader_source());
         output.push_str(include_str!("shaders/physical_material.frag"));
         output
@@ -205,51 +600,49 @@ impl Material for PhysicalMaterial {
         FragmentAttributes {
             position: true,
             normal: true,
-            color: true,
-            uv: self.albedo_texture.is_some()
-                || self.metallic_roughness_texture.is_some()
-                || self.normal_texture.is_some()
-                || self.occlusion_texture.is_some()
-                || self.emissive_texture.is_some(),
-            tangents: self.normal_texture.is_some(),
+            color: !self.ignore_vertex_colors,
+            uv: self.requires_uvs(),
+            uv2: self.lightmap_texture.is_some() || self.requires_uvs2(),
+            tangents: self.requires_tangents(),
         }
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
+        camera.use_tone_mapping_uniforms(program);
+        camera.use_fog_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         if !lights.is_empty() {
-            program.use_uniform_if_required("cameraPosition", camera.position());
+            program.use_uniform_if_required("cameraPosition", camera.lighting_position());
             for (i, light) in lights.iter().enumerate() {
                 light.use_uniforms(program, i as u32);
             }
-            program.use_uniform("metallic", self.metallic);
-            program.use_uniform_if_required("roughness", self.roughness);
-            if program.requires_uniform("albedoTexture") {
-                if let Some(ref texture) = self.albedo_texture {
-                    program.use_uniform("albedoTexTransform", texture.transformation);
-                    program.use_texture("albedoTexture", texture);
-                }
+        }
+        program.use_uniform_if_required("metallic", self.metallic);
+        program.use_uniform_if_required("roughness", self.roughness);
+        if program.requires_uniform("metallicRoughnessTexture") {
+            if let Some(ref texture) = self.metallic_roughness_texture {
+                program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
+                program.use_texture("metallicRoughnessTexture", texture);
             }
-            if program.requires_uniform("metallicRoughnessTexture") {
-                if let Some(ref texture) = self.metallic_roughness_texture {
-                    program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
-                    program.use_texture("metallicRoughnessTexture", texture);
-                }
+        }
+        if program.requires_uniform("normalTexture") {
+            if let Some(ref texture) = self.normal_texture {
+                program.use_uniform("normalTexTransform", texture.transformation);
+                program.use_uniform("normalScale", self.normal_scale);
+                program.use_texture("normalTexture", texture);
             }
-            if program.requires_uniform("occlusionTexture") {
-                if let Some(ref texture) = self.occlusion_texture {
-                    program.use_uniform("occlusionTexTransform", texture.transformation);
-                    program.use_uniform("occlusionStrength", self.occlusion_strength);
-                    program.use_texture("occlusionTexture", texture);
-                }
+        }
+        if program.requires_uniform("albedoTexture") {
+            if let Some(ref texture) = self.albedo_texture {
+                program.use_uniform("albedoTexTransform", texture.transformation);
+                program.use_texture("albedoTexture", texture);
             }
-            if program.requires_uniform("normalTexture") {
-                if let Some(ref texture) = self.normal_texture {
-                    program.use_uniform("normalTexTransform", texture.transformation);
-                    program.use_uniform("normalScale", self.normal_scale);
-                    program.use_texture("normalTexture", texture);
-                }
+        }
+        if program.requires_uniform("occlusionTexture") {
+            if let Some(ref texture) = self.occlusion_texture {
+                program.use_uniform("occlusionTexTransform", texture.transformation);
+                program.use_uniform("occlusionStrength", self.occlusion_strength);
+                program.use_texture("occlusionTexture", texture);
             }
         }
         program.use_uniform("albedo", self.albedo.to_linear_srgb());
@@ -260,13 +653,105 @@ impl Material for PhysicalMaterial {
                 program.use_texture("emissiveTexture", texture);
             }
         }
+        if program.requires_uniform("lightmapTexture") {
+            if let Some(ref texture) = self.lightmap_texture {
+                program.use_uniform("lightmapTexTransform", texture.transformation);
+                program.use_texture("lightmapTexture", texture);
+            }
+        }
+        // The reflectance at normal incidence of non-metals given by the index of refraction and the specular properties
+        let reflectance =
+            ((self.index_of_refraction - 1.0) / (self.index_of_refraction + 1.0)).powi(2);
+        let specular_color = self.specular_color.to_linear_srgb().truncate();
+        program.use_uniform_if_required(
+            "dielectricF0",
+            (specular_color * reflectance).map(|c| c.min(1.0)) * self.specular,
+        );
+        program.use_uniform_if_required("clearcoat", self.clearcoat);
+        program.use_uniform_if_required("clearcoatRoughness", self.clearcoat_roughness);
+        program.use_uniform_if_required("sheenColor", self.sheen_color.to_linear_srgb().truncate());
+        program.use_uniform_if_required("sheenRoughness", self.sheen_roughness);
+        program.use_uniform_if_required(
+            "subsurfaceColor",
+            self.subsurface_color.to_linear_srgb().truncate(),
+        );
+        program.use_uniform_if_required("subsurfaceThickness", self.subsurface_thickness);
+        if program.requires_uniform("subsurfaceThicknessTexture") {
+            if let Some(ref texture) = self.subsurface_thickness_texture {
+                program.use_uniform("subsurfaceThicknessTexTransform", texture.transformation);
+                program.use_texture("subsurfaceThicknessTexture", texture);
+            }
+        }
+        program.use_uniform_if_required("transmission", self.transmission);
+        if program.requires_uniform("refractionTexture") {
+            if let Some(ref texture) = self.refraction_texture {
+                program.use_uniform("viewProjection", camera.projection() * camera.view());
+                program.use_uniform("indexOfRefraction", self.index_of_refraction);
+                program.use_uniform("thickness", self.thickness);
+                program.use_texture("refractionTexture", texture);
+            }
+        }
+        program.use_uniform_if_required("anisotropyStrength", self.anisotropy_strength);
+        program.use_uniform_if_required(
+            "anisotropyRotation",
+            vec2(
+                self.anisotropy_rotation.cos(),
+                self.anisotropy_rotation.sin(),
+            ),
+        );
+        if program.requires_uniform("anisotropyTexture") {
+            if let Some(ref texture) = self.anisotropy_texture {
+                program.use_uniform("anisotropyTexTransform", texture.transformation);
+                program.use_texture("anisotropyTexture", texture);
+            }
+        }
+        if let Some(alpha_cutout) = self.alpha_cutout {
+            program.use_uniform_if_required("alphaCutout", alpha_cutout);
+        }
+        if let Some(triplanar_mapping) = self.triplanar_mapping {
+            program.use_uniform_if_required("triplanarTileSize", triplanar_mapping.tile_size);
+            program
+                .use_uniform_if_required("triplanarSharpness", triplanar_mapping.blend_sharpness);
+        }
+        if program.requires_uniform("heightTexture") {
+            if let Some(ref texture) = self.height_texture {
+                program.use_uniform_if_required("cameraPosition", camera.lighting_position());
+                program.use_uniform("heightTexTransform", texture.transformation);
+                program.use_uniform("heightScale", self.height_scale);
+                program.use_uniform("parallaxStepCount", self.parallax_step_count.max(1) as i32);
+                program.use_texture("heightTexture", texture);
+            }
+        }
     }
 
     fn render_states(&self) -> RenderStates {
-        self.render_states
+        let render_states = if self.double_sided {
+            self.render_states
+        } else {
+            RenderStates {
+                cull: Cull::Back,
+                ..self.render_states
+            }
+        };
+        if self.transmission > 0.0 && self.refraction_texture.is_none() {
+            // The shader outputs the color premultiplied with the alpha value when blending the transmitted light
+            RenderStates {
+                blend: Blend::Enabled {
+                    source_rgb_multiplier: BlendMultiplierType::One,
+                    source_alpha_multiplier: BlendMultiplierType::Zero,
+                    destination_rgb_multiplier: BlendMultiplierType::OneMinusSrcAlpha,
+                    destination_alpha_multiplier: BlendMultiplierType::One,
+                    rgb_equation: BlendEquationType::Add,
+                    alpha_equation: BlendEquationType::Add,
+                },
+                ..render_states
+            }
+        } else {
+            render_states
+        }
     }
     fn material_type(&self) -> MaterialType {
-        if self.is_transparent {
+        if self.is_transparent || self.transmission > 0.0 {
             MaterialType::Transparent
         } else {
             MaterialType::Opaque
@@ -291,7 +776,31 @@ impl Default for PhysicalMaterial {
             is_transparent: false,
             emissive: Srgba::BLACK,
             emissive_texture: None,
+            lightmap_texture: None,
             lighting_model: LightingModel::Blinn,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.0,
+            sheen_color: Srgba::BLACK,
+            sheen_roughness: 0.0,
+            subsurface_color: Srgba::BLACK,
+            subsurface_thickness: 1.0,
+            subsurface_thickness_texture: None,
+            transmission: 0.0,
+            refraction_texture: None,
+            thickness: 0.0,
+            index_of_refraction: 1.5,
+            specular: 1.0,
+            specular_color: Srgba::WHITE,
+            anisotropy_strength: 0.0,
+            anisotropy_rotation: 0.0,
+            anisotropy_texture: None,
+            height_texture: None,
+            height_scale: 0.05,
+            parallax_step_count: 32,
+            triplanar_mapping: None,
+            alpha_cutout: None,
+            double_sided: true,
+            ignore_vertex_colors: false,
         }
     }
 }
@@ -5,6 +5,7 @@ uniform sampler2D tex;
 uniform mat3 textureTransformation;
 #endif
 
+in vec3 pos;
 in vec4 col;
 
 layout (location = 0) out vec4 outColor;
@@ -17,5 +18,6 @@ void main()
     outColor *= texture(tex, (textureTransformation * vec3(uvs, 1.0)).xy);
     #endif
 
+    outColor.rgb = fog(outColor.rgb, pos);
     outColor.rgb = color_mapping(outColor.rgb);
 }
\ No newline at end of file
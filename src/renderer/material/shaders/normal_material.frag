@@ -7,6 +7,18 @@ uniform mat3 textureTransformation;
 uniform float normalScale;
 #endif
 
+#ifdef USE_VIEW_SPACE
+uniform mat4 viewMatrix;
+#endif
+
+#ifdef USE_DEPTH
+uniform vec3 eye;
+uniform float minDistance;
+uniform float maxDistance;
+
+in vec3 pos;
+#endif
+
 layout (location = 0) out vec4 outColor;
 
 void main()
@@ -19,5 +31,12 @@ void main()
     mat3 tbn = mat3(tangent, bitangent, normal);
     normal = tbn * ((2.0 * texture(normalTexture, (textureTransformation * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
 #endif
-    outColor = vec4(0.5 + 0.5 * normal, 1.0);
+#ifdef USE_VIEW_SPACE
+    normal = normalize(mat3(viewMatrix) * normal);
+#endif
+    float depth = 1.0;
+#ifdef USE_DEPTH
+    depth = (distance(pos, eye) - minDistance) / (maxDistance - minDistance);
+#endif
+    outColor = vec4(0.5 + 0.5 * normal, depth);
 }
\ No newline at end of file
@@ -15,6 +15,11 @@ uniform sampler2D emissiveTexture;
 uniform mat3 emissiveTexTransform;
 #endif
 
+#ifdef USE_LIGHTMAP_TEXTURE
+uniform sampler2D lightmapTexture;
+uniform mat3 lightmapTexTransform;
+#endif
+
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
 uniform sampler2D metallicRoughnessTexture;
 uniform mat3 metallicRoughnessTexTransform;
@@ -32,51 +37,286 @@ uniform mat3 normalTexTransform;
 uniform float normalScale;
 #endif
 
+#ifdef USE_CLEARCOAT
+uniform float clearcoat;
+uniform float clearcoatRoughness;
+#endif
+
+#ifdef USE_SHEEN
+uniform vec3 sheenColor;
+uniform float sheenRoughness;
+#endif
+
+#ifdef USE_SUBSURFACE
+uniform vec3 subsurfaceColor;
+uniform float subsurfaceThickness;
+#ifdef USE_SUBSURFACE_THICKNESS_TEXTURE
+uniform sampler2D subsurfaceThicknessTexture;
+uniform mat3 subsurfaceThicknessTexTransform;
+#endif
+#endif
+
+#ifdef USE_TRANSMISSION
+uniform float transmission;
+#ifdef USE_REFRACTION_TEXTURE
+uniform sampler2D refractionTexture;
+uniform mat4 viewProjection;
+uniform float indexOfRefraction;
+uniform float thickness;
+#endif
+#endif
+
+#ifdef USE_ANISOTROPY
+uniform float anisotropyStrength;
+uniform vec2 anisotropyRotation;
+#ifdef USE_ANISOTROPY_TEXTURE
+uniform sampler2D anisotropyTexture;
+uniform mat3 anisotropyTexTransform;
+#endif
+#endif
+
+#ifdef USE_ALPHA_CUTOUT
+uniform float alphaCutout;
+#endif
+
+#ifdef USE_HEIGHT_TEXTURE
+uniform sampler2D heightTexture;
+uniform mat3 heightTexTransform;
+uniform float heightScale;
+uniform int parallaxStepCount;
+#endif
+
 in vec3 pos;
 in vec3 nor;
 in vec4 col;
 
 layout (location = 0) out vec4 outColor;
 
+#ifdef USE_TRIPLANAR
+uniform float triplanarTileSize;
+uniform float triplanarSharpness;
+
+// The weights of the projections along the x, y and z axis, which are set before sampling any textures
+vec3 triplanar_weights;
+
+// Samples the texture projected onto the surface along each of the world axes and blends the results using the weights of each projection, the uv channel is not used
+vec4 sample_texture(sampler2D tex, mat3 transform, int channel)
+{
+    vec3 p = pos / triplanarTileSize;
+    return triplanar_weights.x * texture(tex, (transform * vec3(p.zy, 1.0)).xy)
+        + triplanar_weights.y * texture(tex, (transform * vec3(p.xz, 1.0)).xy)
+        + triplanar_weights.z * texture(tex, (transform * vec3(p.xy, 1.0)).xy);
+}
+
+// Samples the tangent space normal texture projected along each of the world axes, where the tangent space of each projection is aligned with the world axes,
+// and returns the blended world space offset to the normal of the surface
+vec3 sample_normal_offset(sampler2D tex, mat3 transform, float scale)
+{
+    vec3 p = pos / triplanarTileSize;
+    vec2 x = scale * (2.0 * texture(tex, (transform * vec3(p.zy, 1.0)).xy).xy - 1.0);
+    vec2 y = scale * (2.0 * texture(tex, (transform * vec3(p.xz, 1.0)).xy).xy - 1.0);
+    vec2 z = scale * (2.0 * texture(tex, (transform * vec3(p.xy, 1.0)).xy).xy - 1.0);
+    return triplanar_weights.x * vec3(0.0, x.y, x.x) + triplanar_weights.y * vec3(y.x, 0.0, y.y) + triplanar_weights.z * vec3(z.xy, 0.0);
+}
+#else
+#ifdef USE_UVS
+// The texture coordinates, which are set before sampling any textures
+vec2 uv;
+#ifdef USE_UVS2
+vec2 uv2;
+#endif
+
+// Samples the texture using the first set of texture coordinates or, if the channel is 1, the second set
+vec4 sample_texture(sampler2D tex, mat3 transform, int channel)
+{
+#ifdef USE_UVS2
+    if (channel > 0) {
+        return texture(tex, (transform * vec3(uv2, 1.0)).xy);
+    }
+#endif
+    return texture(tex, (transform * vec3(uv, 1.0)).xy);
+}
+#endif
+#endif
+
+#ifdef USE_HEIGHT_TEXTURE
+// Finds the texture coordinates where the view ray, given in tangent space, first hits the surface given by the height texture.
+// The ray is marched in steps from the top of the surface, after which the intersection is interpolated between the last two steps.
+vec2 parallax_occlusion_mapping(vec2 uv, vec3 view_direction_tangent_space)
+{
+    // The derivatives are computed up front, since they are undefined inside the loop
+    vec2 dx = dFdx(uv);
+    vec2 dy = dFdy(uv);
+    float step_depth = 1.0 / float(parallaxStepCount);
+    vec2 step_uv = view_direction_tangent_space.xy / max(view_direction_tangent_space.z, 0.05) * heightScale * step_depth;
+
+    float depth = 0.0;
+    float surface_depth = 1.0 - textureGrad(heightTexture, (heightTexTransform * vec3(uv, 1.0)).xy, dx, dy).r;
+    for (int i = 0; i < parallaxStepCount && depth < surface_depth; i++) {
+        uv -= step_uv;
+        depth += step_depth;
+        surface_depth = 1.0 - textureGrad(heightTexture, (heightTexTransform * vec3(uv, 1.0)).xy, dx, dy).r;
+    }
+
+    vec2 previous_uv = uv + step_uv;
+    float depth_after = surface_depth - depth;
+    float depth_before = 1.0 - textureGrad(heightTexture, (heightTexTransform * vec3(previous_uv, 1.0)).xy, dx, dy).r - depth + step_depth;
+    float weight = depth_after / min(depth_after - depth_before, -0.0001);
+    return mix(uv, previous_uv, weight);
+}
+#endif
+
 void main()
 {
+#ifdef USE_TRIPLANAR
+    // The projections are weighted by the normal of the geometry, where a higher sharpness gives narrower seams between the projections
+    vec3 triplanar_normal = normalize(gl_FrontFacing ? nor : -nor);
+    triplanar_weights = pow(abs(triplanar_normal), vec3(triplanarSharpness));
+    triplanar_weights /= triplanar_weights.x + triplanar_weights.y + triplanar_weights.z;
+#endif
+#ifdef USE_UVS
+    uv = uvs;
+#ifdef USE_UVS2
+    uv2 = uvs2;
+#endif
+#ifdef USE_HEIGHT_TEXTURE
+    vec3 height_tangent = normalize(gl_FrontFacing ? tang : -tang);
+    vec3 height_bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
+    vec3 height_normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec3 height_view_direction = transpose(mat3(height_tangent, height_bitangent, height_normal)) * normalize(cameraPosition - pos);
+    uv = parallax_occlusion_mapping(uv, height_view_direction);
+#endif
+#endif
+
     vec4 surface_color = albedo * col;
 #ifdef USE_ALBEDO_TEXTURE
-    vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(uvs, 1.0)).xy);
-    #ifdef ALPHACUT
-        if (c.a < acut) discard;
-    #endif
-    surface_color *= c;
+    surface_color *= sample_texture(albedoTexture, albedoTexTransform, ALBEDO_UV_CHANNEL);
+#endif
+
+#ifdef USE_ALPHA_CUTOUT
+#ifdef USE_ALPHA_TO_COVERAGE
+    // The alpha value is sharpened to go from zero to one across one pixel at the cutout, so the coverage of the samples gives a smooth edge
+    surface_color.a = (surface_color.a - alphaCutout) / max(fwidth(surface_color.a), 0.0001) + 0.5;
+    if (surface_color.a <= 0.0) discard;
+    surface_color.a = min(surface_color.a, 1.0);
+#else
+    if (surface_color.a < alphaCutout) discard;
+    surface_color.a = 1.0;
+#endif
 #endif
 
     float metallic_factor = metallic;
     float roughness_factor = roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
-    vec2 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(uvs, 1.0)).xy).gb;
+    vec2 t = sample_texture(metallicRoughnessTexture, metallicRoughnessTexTransform, METALLIC_ROUGHNESS_UV_CHANNEL).gb;
     roughness_factor *= t.x;
     metallic_factor *= t.y;
 #endif
 
     float occlusion = 1.0;
 #ifdef USE_OCCLUSION_TEXTURE
-    occlusion = mix(1.0, texture(occlusionTexture, (occlusionTexTransform * vec3(uvs, 1.0)).xy).r, occlusionStrength);
+    occlusion = mix(1.0, sample_texture(occlusionTexture, occlusionTexTransform, OCCLUSION_UV_CHANNEL).r, occlusionStrength);
 #endif
 
     vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
 #ifdef USE_NORMAL_TEXTURE
+#ifdef USE_TRIPLANAR
+    normal = normalize(normal + sample_normal_offset(normalTexture, normalTexTransform, normalScale));
+#else
     vec3 tangent = normalize(gl_FrontFacing ? tang : -tang);
     vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
     mat3 tbn = mat3(tangent, bitangent, normal);
-    normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+    normal = tbn * ((2.0 * sample_texture(normalTexture, normalTexTransform, NORMAL_UV_CHANNEL).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+#endif
+#endif
+
+#ifdef USE_ANISOTROPY
+    // The anisotropy direction is given in tangent space, rotated counterclockwise from the tangent, and is made perpendicular to the normal
+    vec2 anisotropy_tangent_direction = anisotropyRotation;
+    anisotropy_strength = anisotropyStrength;
+#ifdef USE_ANISOTROPY_TEXTURE
+    vec3 a = sample_texture(anisotropyTexture, anisotropyTexTransform, 0).rgb;
+    anisotropy_tangent_direction = mat2(anisotropyRotation.x, anisotropyRotation.y, -anisotropyRotation.y, anisotropyRotation.x) * (2.0 * a.rg - 1.0);
+    anisotropy_strength *= a.b;
+#endif
+    vec3 anisotropy_tangent = normalize(gl_FrontFacing ? tang : -tang);
+    vec3 anisotropy_bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
+    vec3 direction = mat3(anisotropy_tangent, anisotropy_bitangent, normal) * vec3(anisotropy_tangent_direction, 0.0);
+    anisotropy_direction = normalize(direction - normal * dot(normal, direction));
 #endif
 
     vec3 total_emissive = emissive.rgb;
 #ifdef USE_EMISSIVE_TEXTURE
-    total_emissive *= texture(emissiveTexture, (emissiveTexTransform * vec3(uvs, 1.0)).xy).rgb;
+    total_emissive *= sample_texture(emissiveTexture, emissiveTexTransform, EMISSIVE_UV_CHANNEL).rgb;
 #endif
 
-    outColor.rgb = total_emissive + calculate_lighting(cameraPosition, surface_color.rgb, pos, normal, metallic_factor, roughness_factor, occlusion);
+#ifdef USE_SUBSURFACE
+    subsurface_color = subsurfaceColor;
+    subsurface_thickness = subsurfaceThickness;
+#ifdef USE_SUBSURFACE_THICKNESS_TEXTURE
+    subsurface_thickness *= sample_texture(subsurfaceThicknessTexture, subsurfaceThicknessTexTransform, 0).r;
+#endif
+#endif
+
+    vec3 light = calculate_lighting(cameraPosition, surface_color.rgb, pos, normal, metallic_factor, roughness_factor, occlusion);
+#ifdef USE_LIGHTMAP_TEXTURE
+    light += texture(lightmapTexture, (lightmapTexTransform * vec3(uvs2, 1.0)).xy).rgb * mix(surface_color.rgb, vec3(0.0), metallic_factor) * occlusion;
+#endif
+
+#ifdef USE_SUBSURFACE
+    // Only the light reaching the base layer is scattered below the surface
+    subsurface_color = vec3(0.0);
+#endif
+    vec3 view_direction = normalize(cameraPosition - pos);
+    float NdV = max(0.001, dot(normal, view_direction));
+
+#ifdef USE_TRANSMISSION
+    // The transmissive part of the surface only reflects light specularly, the rest of the light passes through the surface
+    float transmission_factor = transmission * (1.0 - metallic_factor);
+    vec3 specular_light = calculate_lighting(cameraPosition, DIELECTRIC_F0, pos, normal, 1.0, roughness_factor, occlusion);
+    light = mix(light, specular_light, transmission_factor);
+    vec3 transmitted = transmission_factor * (1.0 - fresnel_schlick_roughness(DIELECTRIC_F0, NdV, roughness_factor));
+#endif
+
+#ifdef USE_ANISOTROPY
+    // The sheen and clear coat layers are not anisotropic
+    anisotropy_strength = 0.0;
+#endif
+
+#ifdef USE_SHEEN
+    // Approximates the sheen of cloth by a rim of sheen color lit by the diffuse light, which becomes wider with the sheen roughness
+    vec3 diffuse_light = calculate_lighting(cameraPosition, vec3(1.0), pos, normal, 0.0, 1.0, occlusion);
+    float sheen_rim = pow(1.0 - NdV, mix(5.0, 1.0, sheenRoughness));
+    float sheen_albedo = max(max(sheenColor.r, sheenColor.g), sheenColor.b) * sheen_rim;
+    light = light * (1.0 - sheen_albedo) + sheenColor * sheen_rim * diffuse_light;
+#endif
+
+#ifdef USE_CLEARCOAT
+    // The clear coat is a thin dielectric layer on top of the surface using the geometric normal, which reflects light specularly and reduces the light from the surface below
+    vec3 clearcoat_normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec3 clearcoat_light = calculate_lighting(cameraPosition, vec3(0.04), pos, clearcoat_normal, 1.0, clearcoatRoughness, occlusion);
+    float clearcoat_fresnel = fresnel_schlick(vec3(0.04), max(0.001, dot(clearcoat_normal, view_direction))).x;
+    light = light * (1.0 - clearcoat * clearcoat_fresnel) + clearcoat * clearcoat_light;
+#endif
+
+    outColor.rgb = total_emissive + light;
+    outColor.rgb = fog(outColor.rgb, pos);
     outColor.rgb = tone_mapping(outColor.rgb);
     outColor.rgb = color_mapping(outColor.rgb);
     outColor.a = surface_color.a;
+
+#ifdef USE_TRANSMISSION
+#ifdef USE_REFRACTION_TEXTURE
+    // The transmitted light is looked up in the scene behind the surface where the view ray exits the surface after being refracted
+    vec3 refracted = refract(-view_direction, normal, 1.0 / indexOfRefraction);
+    vec4 exit_position = viewProjection * vec4(pos + refracted * thickness, 1.0);
+    vec2 screen_uv = clamp(0.5 * exit_position.xy / exit_position.w + 0.5, 0.0, 1.0);
+    float lod = roughness_factor * log2(float(textureSize(refractionTexture, 0).x));
+    outColor.rgb += transmitted * surface_color.rgb * textureLod(refractionTexture, screen_uv, lod).rgb;
+#else
+    // The transmitted light is blended with the scene behind the surface using premultiplied alpha, so the reflected light is not reduced
+    outColor.rgb *= surface_color.a;
+    outColor.a *= 1.0 - max(max(transmitted.r, transmitted.g), transmitted.b);
+#endif
+#endif
 }
\ No newline at end of file
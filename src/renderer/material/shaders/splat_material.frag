@@ -0,0 +1,54 @@
+uniform float metallic;
+uniform float roughness;
+uniform vec3 cameraPosition;
+
+uniform sampler2DArray splatMaps;
+uniform vec2 splatMapOrigin;
+uniform vec2 splatMapSize;
+uniform sampler2DArray albedoTextures;
+uniform int layerCount;
+uniform float tileSizes[MAX_SPLAT_LAYERS];
+
+#ifdef USE_NORMAL_TEXTURES
+uniform sampler2DArray normalTextures;
+uniform float normalScale;
+#endif
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec2 splat_uv = (pos.xz - splatMapOrigin) / splatMapSize;
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+
+    vec3 surface_color = vec3(0.0);
+    vec3 tangent_normal = vec3(0.0);
+    float total_weight = 0.0;
+    for (int i = 0; i < layerCount; i++) {
+        float weight = texture(splatMaps, vec3(splat_uv, float(i / 4)))[i % 4];
+        vec3 uv = vec3(pos.xz / tileSizes[i], float(i));
+        surface_color += weight * texture(albedoTextures, uv).rgb;
+#ifdef USE_NORMAL_TEXTURES
+        tangent_normal += weight * (2.0 * texture(normalTextures, uv).xyz - 1.0);
+#endif
+        total_weight += weight;
+    }
+    if (total_weight > 0.0) {
+        surface_color /= total_weight;
+#ifdef USE_NORMAL_TEXTURES
+        // The tiled textures are mapped onto the xz plane, so the tangent follows the x axis and the bitangent follows the z axis
+        vec3 tangent = normalize(vec3(1.0, 0.0, 0.0) - normal * normal.x);
+        vec3 bitangent = cross(tangent, normal);
+        tangent_normal /= total_weight;
+        normal = normalize(mat3(tangent, bitangent, normal) * (tangent_normal * vec3(normalScale, normalScale, 1.0)));
+#endif
+    }
+
+    outColor.rgb = calculate_lighting(cameraPosition, surface_color, pos, normal, metallic, roughness, 1.0);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = 1.0;
+}
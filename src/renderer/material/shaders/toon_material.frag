@@ -0,0 +1,44 @@
+
+uniform vec3 cameraPosition;
+uniform vec4 albedo;
+#ifdef USE_ALBEDO_TEXTURE
+uniform sampler2D albedoTexture;
+uniform mat3 albedoTexTransform;
+#endif
+uniform vec4 rimColor;
+uniform float rimWidth;
+uniform vec4 outlineColor;
+uniform float outlineWidth;
+
+in vec3 pos;
+in vec3 nor;
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 surface_color = albedo * col;
+#ifdef USE_ALBEDO_TEXTURE
+    surface_color *= texture(albedoTexture, (albedoTexTransform * vec3(uvs, 1.0)).xy);
+#endif
+
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec3 view_direction = normalize(cameraPosition - pos);
+    float n_dot_v = max(dot(normal, view_direction), 0.0);
+
+    vec3 light = calculate_toon_lighting(pos, normal, view_direction);
+    outColor.rgb = surface_color.rgb * light;
+
+    // Rim light on the lit parts of the surface which face away from the camera
+    float rim = step(1.0 - rimWidth, 1.0 - n_dot_v) * step(0.00001, max(light.r, max(light.g, light.b)));
+    outColor.rgb = mix(outColor.rgb, outColor.rgb + rimColor.rgb * light, rim * rimColor.a);
+
+    // Ink outline along the silhouette
+    outColor.rgb = mix(outColor.rgb, outlineColor.rgb, float(n_dot_v < outlineWidth) * outlineColor.a);
+
+    outColor.rgb = fog(outColor.rgb, pos);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = surface_color.a;
+}
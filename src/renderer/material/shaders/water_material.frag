@@ -0,0 +1,91 @@
+uniform vec3 cameraPosition;
+uniform mat4 viewProjectionInverse;
+uniform vec4 viewport;
+
+uniform vec4 shallowColor;
+uniform vec4 deepColor;
+uniform vec4 reflectionColor;
+uniform float absorption;
+uniform float refractionStrength;
+uniform float metallic;
+uniform float roughness;
+
+#ifdef USE_NORMAL_TEXTURE
+uniform sampler2D normalTexture;
+uniform float normalScale;
+uniform float normalTiling;
+uniform vec2 normalOffset0;
+uniform vec2 normalOffset1;
+#endif
+
+#ifdef USE_SCENE_COLOR
+uniform sampler2D sceneColorMap;
+#endif
+
+#ifdef USE_SCENE_DEPTH
+uniform sampler2D sceneDepthMap;
+#endif
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 outColor;
+
+const float FresnelF0 = 0.02;
+
+void main()
+{
+    vec3 surface_normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec3 normal = surface_normal;
+#ifdef USE_NORMAL_TEXTURE
+    // Blends two copies of the normal texture which scroll in different directions across the surface
+    vec2 uv = pos.xz / normalTiling;
+    vec3 n0 = 2.0 * texture(normalTexture, uv + normalOffset0).xyz - 1.0;
+    vec3 n1 = 2.0 * texture(normalTexture, 0.7 * uv + normalOffset1).xyz - 1.0;
+    vec3 n = vec3((n0.xy + n1.xy) * normalScale, n0.z * n1.z);
+    vec3 tangent = normalize(vec3(1.0, 0.0, 0.0) - surface_normal * surface_normal.x);
+    vec3 bitangent = cross(tangent, surface_normal);
+    normal = normalize(mat3(tangent, bitangent, surface_normal) * n);
+#endif
+
+    vec3 view_direction = normalize(cameraPosition - pos);
+    vec2 screen_uv = (gl_FragCoord.xy - viewport.xy) / viewport.zw;
+    vec2 distortion = refractionStrength * (normal - surface_normal).xz;
+
+    // The distance the light travels through the water before reaching the surface
+    float water_depth = 1.0e10;
+    vec2 refracted_uv = screen_uv + distortion;
+#ifdef USE_SCENE_DEPTH
+    vec3 bottom = world_pos_from_depth(viewProjectionInverse, texture(sceneDepthMap, refracted_uv).x, refracted_uv);
+    if (dot(bottom - pos, view_direction) > 0.0) {
+        // Avoid refracting geometry which is in front of the water surface
+        refracted_uv = screen_uv;
+        bottom = world_pos_from_depth(viewProjectionInverse, texture(sceneDepthMap, refracted_uv).x, refracted_uv);
+    }
+    water_depth = distance(bottom, pos);
+#endif
+
+#ifdef USE_SCENE_COLOR
+    vec3 bottom_color = texture(sceneColorMap, refracted_uv).rgb * shallowColor.rgb;
+#else
+    vec3 bottom_color = shallowColor.rgb;
+#endif
+    vec3 refracted = mix(deepColor.rgb, bottom_color, exp(-absorption * water_depth));
+
+#ifdef USE_PLANAR_REFLECTION
+    vec3 reflected = sample_reflection(distortion).rgb;
+#else
+    vec3 reflected = reflectionColor.rgb;
+#endif
+
+    float fresnel = FresnelF0 + (1.0 - FresnelF0) * pow(1.0 - max(dot(normal, view_direction), 0.0), 5.0);
+    outColor.rgb = mix(refracted, reflected, fresnel);
+
+    // Only the specular highlights of the lights since the color below the surface is already lit
+    outColor.rgb += calculate_lighting(cameraPosition, vec3(0.0), pos, normal, metallic, roughness, 1.0);
+
+    outColor.rgb = fog(outColor.rgb, pos);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = 1.0;
+}
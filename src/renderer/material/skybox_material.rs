@@ -7,8 +7,8 @@ pub struct SkyboxMaterial {
 }
 
 impl Material for SkyboxMaterial {
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b100u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b100u32
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
@@ -26,7 +26,7 @@ impl Material for SkyboxMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
+        camera.use_tone_mapping_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         program.use_texture_cube("texture0", &self.texture);
     }
@@ -0,0 +1,170 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+/// The maximum number of layers in a [SplatMaterial].
+pub const MAX_SPLAT_LAYERS: usize = 16;
+
+///
+/// A material which blends a set of tiled textures, for example grass, rock, sand and snow, across a large surface like a [Terrain]
+/// according to the weights in a set of splat maps.
+///
+/// Each channel of the splat maps contains the weight of one layer, so the red, green, blue and alpha channel of the first splat map
+/// are the weights of the first four layers, the channels of the second splat map are the weights of the next four layers and so on.
+/// The splat maps are stretched across an area of the xz plane, while the textures of each layer are tiled across the xz plane.
+///
+#[derive(Clone)]
+pub struct SplatMaterial {
+    /// The splat maps containing the weight of each layer.
+    pub splat_maps: Arc<Texture2DArray>,
+    /// The corner of the area in the xz plane which the splat maps are stretched across.
+    pub splat_map_origin: Vec2,
+    /// The size of the area in the xz plane which the splat maps are stretched across.
+    pub splat_map_size: Vec2,
+    /// The color texture of each layer.
+    pub albedo_textures: Arc<Texture2DArray>,
+    /// The normal texture of each layer in tangent space.
+    pub normal_textures: Option<Arc<Texture2DArray>>,
+    /// A scalar multiplier applied to each normal vector of the [Self::normal_textures].
+    pub normal_scale: f32,
+    /// The size of one tile of the textures of each layer in world space.
+    /// Layers without a tile size use the tile size of the last layer.
+    pub tile_sizes: Vec<f32>,
+    /// A value in the range `[0..1]` specifying how metallic the surface is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the surface is.
+    pub roughness: f32,
+    /// The lighting model used when rendering this material
+    pub lighting_model: LightingModel,
+}
+
+impl SplatMaterial {
+    ///
+    /// Constructs a new splat material with a layer for each of the given albedo textures, which must all have the same size.
+    /// The splat maps must also have the same size and there must be at least one splat map for every four layers.
+    /// If normal textures are given, there must be one for each layer.
+    /// The splat maps are stretched across the area in the xz plane from `splat_map_origin` to `splat_map_origin + splat_map_size`.
+    ///
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new(
+        context: &Context,
+        splat_maps: &[&CpuTexture],
+        splat_map_origin: Vec2,
+        splat_map_size: Vec2,
+        albedo_textures: &[&CpuTexture],
+        normal_textures: Option<&[&CpuTexture]>,
+    ) -> Self {
+        assert!(
+            albedo_textures.len() <= MAX_SPLAT_LAYERS,
+            "A splat material can have at most {} layers",
+            MAX_SPLAT_LAYERS
+        );
+        assert!(
+            4 * splat_maps.len() >= albedo_textures.len(),
+            "A splat material needs a splat map for every four layers"
+        );
+        let albedo_textures = albedo_textures
+            .iter()
+            .map(|cpu_texture| match &cpu_texture.data {
+                TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
+                    let mut cpu_texture = (*cpu_texture).clone();
+                    cpu_texture.data.to_linear_srgb();
+                    cpu_texture
+                }
+                _ => (*cpu_texture).clone(),
+            })
+            .collect::<Vec<_>>();
+        Self {
+            splat_maps: Arc::new(Texture2DArray::new(context, splat_maps)),
+            splat_map_origin,
+            splat_map_size,
+            albedo_textures: Arc::new(Texture2DArray::new(
+                context,
+                &albedo_textures.iter().collect::<Vec<_>>(),
+            )),
+            normal_textures: normal_textures.map(|normal_textures| {
+                assert_eq!(
+                    normal_textures.len(),
+                    albedo_textures.len(),
+                    "A splat material needs a normal texture for each layer"
+                );
+                Arc::new(Texture2DArray::new(context, normal_textures))
+            }),
+            normal_scale: 1.0,
+            tile_sizes: vec![10.0],
+            metallic: 0.0,
+            roughness: 1.0,
+            lighting_model: LightingModel::Blinn,
+        }
+    }
+
+    fn layer_count(&self) -> u32 {
+        self.albedo_textures
+            .depth()
+            .min(4 * self.splat_maps.depth())
+            .min(MAX_SPLAT_LAYERS as u32)
+    }
+}
+
+impl Material for SplatMaterial {
+    fn id(&self) -> u32 {
+        if self.normal_textures.is_some() {
+            0b1u32 << 15 | 0b1u32 << 11 | 0b1u32
+        } else {
+            0b1u32 << 15 | 0b1u32 << 11
+        }
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut output = format!("#define MAX_SPLAT_LAYERS {}\n", MAX_SPLAT_LAYERS);
+        if self.normal_textures.is_some() {
+            output.push_str("#define USE_NORMAL_TEXTURES\n");
+        }
+        output.push_str(&lights_shader_source(lights, self.lighting_model));
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/splat_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform_if_required("cameraPosition", camera.lighting_position());
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        program.use_uniform_if_required("metallic", self.metallic);
+        program.use_uniform_if_required("roughness", self.roughness);
+        program.use_texture_array("splatMaps", &self.splat_maps);
+        program.use_uniform("splatMapOrigin", self.splat_map_origin);
+        program.use_uniform("splatMapSize", self.splat_map_size);
+        program.use_texture_array("albedoTextures", &self.albedo_textures);
+        program.use_uniform("layerCount", self.layer_count() as i32);
+        let last_tile_size = self.tile_sizes.last().copied().unwrap_or(1.0);
+        let tile_sizes = (0..MAX_SPLAT_LAYERS)
+            .map(|i| self.tile_sizes.get(i).copied().unwrap_or(last_tile_size))
+            .collect::<Vec<_>>();
+        program.use_uniform_array("tileSizes", &tile_sizes);
+        if let Some(ref normal_textures) = self.normal_textures {
+            program.use_texture_array("normalTextures", normal_textures);
+            program.use_uniform("normalScale", self.normal_scale);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
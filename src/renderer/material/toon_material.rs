@@ -0,0 +1,210 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A non-photorealistic material which renders a [Geometry] with a cartoon look (also called cel shading),
+/// where the light is quantized into a few bands of constant brightness, the edges facing away from the camera get a rim light
+/// and the silhouette is drawn with an ink outline.
+/// This material is affected by the same lights as the [PhysicalMaterial].
+///
+#[derive(Clone)]
+pub struct ToonMaterial {
+    /// Base surface color.
+    pub albedo: Srgba,
+    /// Texture with base surface colors.
+    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    pub albedo_texture: Option<Texture2DRef>,
+    /// The number of bands of constant brightness that the light from each light is quantized into.
+    pub band_count: u32,
+    /// The color of the rim light, where the alpha value is the strength of the rim light.
+    pub rim_color: Srgba,
+    /// A value in the range `[0..1]` specifying the width of the rim light, where zero is no rim light.
+    pub rim_width: f32,
+    /// The color of the ink outline, where the alpha value is the strength of the outline.
+    pub outline_color: Srgba,
+    /// A value in the range `[0..1]` specifying the width of the ink outline along the silhouette, where zero is no outline.
+    /// The outline is found from the angle between the surface and the view direction,
+    /// so the width in pixels depends on the curvature of the surface and flat surfaces seen from the side are completely filled.
+    pub outline_width: f32,
+    /// Render states.
+    pub render_states: RenderStates,
+    /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
+    pub is_transparent: bool,
+}
+
+impl ToonMaterial {
+    ///
+    /// Constructs a new toon material from a [CpuMaterial], where only the albedo color and texture are used.
+    /// Tries to infer whether this material is transparent or opaque from the alpha value of the albedo color and the alpha values in the albedo texture.
+    /// Since this is not always correct, it is preferred to use [ToonMaterial::new_opaque] or [ToonMaterial::new_transparent].
+    ///
+    pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new_internal(context, cpu_material, super::is_transparent(cpu_material))
+    }
+
+    /// Constructs a new opaque toon material from a [CpuMaterial], where only the albedo color and texture are used.
+    pub fn new_opaque(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new_internal(context, cpu_material, false)
+    }
+
+    /// Constructs a new transparent toon material from a [CpuMaterial], where only the albedo color and texture are used.
+    pub fn new_transparent(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new_internal(context, cpu_material, true)
+    }
+
+    fn new_internal(context: &Context, cpu_material: &CpuMaterial, is_transparent: bool) -> Self {
+        let albedo_texture =
+            cpu_material
+                .albedo_texture
+                .as_ref()
+                .map(|cpu_texture| match &cpu_texture.data {
+                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
+                        let mut cpu_texture = cpu_texture.clone();
+                        cpu_texture.data.to_linear_srgb();
+                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
+                    }
+                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
+                });
+        Self {
+            albedo: cpu_material.albedo,
+            albedo_texture,
+            render_states: if is_transparent {
+                RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                }
+            } else {
+                RenderStates::default()
+            },
+            is_transparent,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a toon material with the albedo color and texture of a [PhysicalMaterial].
+    pub fn from_physical_material(physical_material: &PhysicalMaterial) -> Self {
+        Self {
+            albedo: physical_material.albedo,
+            albedo_texture: physical_material.albedo_texture.clone(),
+            render_states: physical_material.render_states,
+            is_transparent: physical_material.is_transparent,
+            ..Default::default()
+        }
+    }
+}
+
+impl FromCpuMaterial for ToonMaterial {
+    fn from_cpu_material(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new(context, cpu_material)
+    }
+}
+
+impl Material for ToonMaterial {
+    fn id(&self) -> u32 {
+        if self.albedo_texture.is_some() {
+            0b1u32 << 15 | 0b1u32 << 9 | 0b1u32
+        } else {
+            0b1u32 << 15 | 0b1u32 << 9
+        }
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut output = lights_shader_source(lights, LightingModel::Blinn);
+        let mut lighting = String::new();
+        for i in 0..lights.len() {
+            lighting.push_str(&format!(
+                "light += band(calculate_lighting{}(vec3(1.0), position, normal, view_direction, 0.0, 1.0, 1.0));\n",
+                i
+            ));
+        }
+        output.push_str(&format!(
+            "
+            uniform float bandCount;
+
+            vec3 band(vec3 light)
+            {{
+                float brightness = max(light.r, max(light.g, light.b));
+                if (brightness <= 0.0) {{
+                    return vec3(0.0);
+                }}
+                return light * floor(brightness * bandCount + 0.5) / (bandCount * brightness);
+            }}
+
+            vec3 calculate_toon_lighting(vec3 position, vec3 normal, vec3 view_direction)
+            {{
+                vec3 light = vec3(0.0);
+                {}
+                return light;
+            }}
+            ",
+            lighting
+        ));
+        if self.albedo_texture.is_some() {
+            output.push_str("in vec2 uvs;\n#define USE_ALBEDO_TEXTURE;\n");
+        }
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(Fog::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/toon_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            color: true,
+            uv: self.albedo_texture.is_some(),
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.use_fog_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform_if_required("cameraPosition", camera.lighting_position());
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        program.use_uniform_if_required("bandCount", self.band_count.max(1) as f32);
+        program.use_uniform("albedo", self.albedo.to_linear_srgb());
+        if let Some(ref texture) = self.albedo_texture {
+            program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_texture("albedoTexture", texture);
+        }
+        program.use_uniform_if_required("rimColor", self.rim_color.to_linear_srgb());
+        program.use_uniform_if_required("rimWidth", self.rim_width);
+        program.use_uniform_if_required("outlineColor", self.outline_color.to_linear_srgb());
+        program.use_uniform_if_required("outlineWidth", self.outline_width);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        if self.is_transparent {
+            MaterialType::Transparent
+        } else {
+            MaterialType::Opaque
+        }
+    }
+}
+
+impl Default for ToonMaterial {
+    fn default() -> Self {
+        Self {
+            albedo: Srgba::WHITE,
+            albedo_texture: None,
+            band_count: 3,
+            rim_color: Srgba::WHITE,
+            rim_width: 0.2,
+            outline_color: Srgba::BLACK,
+            outline_width: 0.2,
+            render_states: RenderStates::default(),
+            is_transparent: false,
+        }
+    }
+}
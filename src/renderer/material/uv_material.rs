@@ -18,8 +18,8 @@ impl FromCpuMaterial for UVMaterial {
 }
 
 impl Material for UVMaterial {
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b101u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b101u32
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
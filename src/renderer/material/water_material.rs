@@ -0,0 +1,181 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material for a water surface, for example the [Water] object or a flat [Mesh], with animated waves, refraction of the scene below the surface,
+/// color absorption depending on the depth of the water and reflections.
+///
+/// The waves are made by blending two copies of the [WaterMaterial::normal_texture] which scroll in different directions as the [WaterMaterial::time] increases.
+/// The refraction and absorption need the scene without the water surface rendered into a color and depth texture from the same camera,
+/// see [WaterMaterial::scene_color_texture] and [WaterMaterial::scene_depth_texture].
+/// The reflections are found in a [PlanarReflection] if one is given and otherwise the [WaterMaterial::reflection_color] is reflected.
+///
+#[derive(Clone)]
+pub struct WaterMaterial<'a> {
+    /// Tiling texture containing the normals of small waves in tangent space.
+    pub normal_texture: Option<Texture2DRef>,
+    /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
+    pub normal_scale: f32,
+    /// The size of one tile of the [Self::normal_texture] in world space, which is mapped onto the xz plane.
+    pub normal_tiling: f32,
+    /// The velocities in tiles per second which the two copies of the [Self::normal_texture] are scrolled with.
+    pub scroll_velocities: [Vec2; 2],
+    /// The time in seconds which animates the waves.
+    pub time: f32,
+    /// The color that the scene below the surface is tinted with.
+    pub shallow_color: Srgba,
+    /// The color of the water where it is so deep that the scene below the surface is not visible.
+    pub deep_color: Srgba,
+    /// The color which is reflected when no [Self::planar_reflection] is given.
+    pub reflection_color: Srgba,
+    /// How fast the light is absorbed by the water, ie. how fast the color changes from the [Self::shallow_color] to the [Self::deep_color] with the distance through the water.
+    pub absorption: f32,
+    /// How much the waves distort the refraction and reflection in uv coordinates.
+    pub refraction_strength: f32,
+    /// A value in the range `[0..1]` specifying how metallic the surface is, which affects the highlights from the lights.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the surface is, which affects the highlights from the lights.
+    pub roughness: f32,
+    /// The lighting model used when rendering the highlights from the lights.
+    pub lighting_model: LightingModel,
+    /// The scene without the water surface rendered from the same camera, which is refracted.
+    pub scene_color_texture: Option<&'a Texture2D>,
+    /// The depth of the scene without the water surface rendered from the same camera, which is used to find the depth of the water.
+    pub scene_depth_texture: Option<&'a DepthTexture2D>,
+    /// The reflection in the plane of the water surface.
+    pub planar_reflection: Option<&'a PlanarReflection>,
+}
+
+impl WaterMaterial<'_> {
+    fn normal_offset(&self, i: usize) -> Vec2 {
+        let offset = self.scroll_velocities[i] * self.time;
+        vec2(offset.x.fract(), offset.y.fract())
+    }
+}
+
+impl Material for WaterMaterial<'_> {
+    fn id(&self) -> u32 {
+        let mut id = 0b1u32 << 15 | 0b1u32 << 10;
+        if self.normal_texture.is_some() {
+            id |= 0b1u32;
+        }
+        if self.scene_color_texture.is_some() {
+            id |= 0b1u32 << 1;
+        }
+        if self.scene_depth_texture.is_some() {
+            id |= 0b1u32 << 2;
+        }
+        if self.planar_reflection.is_some() {
+            id |= 0b1u32 << 3;
+        }
+        id
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut output = lights_shader_source(lights, self.lighting_model);
+        if self.normal_texture.is_some() {
+            output.push_str("#define USE_NORMAL_TEXTURE\n");
+        }
+        if self.scene_color_texture.is_some() {
+            output.push_str("#define USE_SCENE_COLOR\n");
+        }
+        if self.scene_depth_texture.is_some() {
+            output.push_str("#define USE_SCENE_DEPTH\n");
+        }
+        if self.planar_reflection.is_some() {
+            output.push_str("#define USE_PLANAR_REFLECTION\n");
+            output.push_str(PlanarReflection::fragment_shader_source());
+        }
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(Fog::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/water_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.use_fog_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        program.use_uniform("cameraPosition", camera.lighting_position());
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "viewport",
+            vec4(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ),
+        );
+        program.use_uniform_if_required(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("shallowColor", self.shallow_color.to_linear_srgb());
+        program.use_uniform("deepColor", self.deep_color.to_linear_srgb());
+        program.use_uniform_if_required("reflectionColor", self.reflection_color.to_linear_srgb());
+        program.use_uniform_if_required("absorption", self.absorption);
+        program.use_uniform_if_required("refractionStrength", self.refraction_strength);
+        program.use_uniform_if_required("metallic", self.metallic);
+        program.use_uniform_if_required("roughness", self.roughness);
+        if let Some(ref texture) = self.normal_texture {
+            program.use_texture("normalTexture", texture);
+            program.use_uniform("normalScale", self.normal_scale);
+            program.use_uniform("normalTiling", self.normal_tiling);
+            program.use_uniform("normalOffset0", self.normal_offset(0));
+            program.use_uniform("normalOffset1", self.normal_offset(1));
+        }
+        if let Some(texture) = self.scene_color_texture {
+            program.use_texture("sceneColorMap", texture);
+        }
+        if let Some(texture) = self.scene_depth_texture {
+            program.use_depth_texture("sceneDepthMap", texture);
+        }
+        if let Some(reflection) = self.planar_reflection {
+            reflection.use_uniforms(program);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+impl Default for WaterMaterial<'_> {
+    fn default() -> Self {
+        Self {
+            normal_texture: None,
+            normal_scale: 1.0,
+            normal_tiling: 10.0,
+            scroll_velocities: [vec2(0.03, 0.01), vec2(-0.02, 0.025)],
+            time: 0.0,
+            shallow_color: Srgba::new(200, 235, 230, 255),
+            deep_color: Srgba::new(5, 40, 60, 255),
+            reflection_color: Srgba::new(150, 190, 230, 255),
+            absorption: 0.3,
+            refraction_strength: 0.05,
+            metallic: 0.0,
+            roughness: 0.1,
+            lighting_model: LightingModel::Blinn,
+            scene_color_texture: None,
+            scene_depth_texture: None,
+            planar_reflection: None,
+        }
+    }
+}
@@ -15,6 +15,10 @@ macro_rules! impl_object_body {
         fn material_type(&self) -> MaterialType {
             self.$inner().material_type()
         }
+
+        fn program_id(&self) -> Option<u64> {
+            self.$inner().program_id()
+        }
     };
 }
 
@@ -26,10 +30,29 @@ mod model;
 #[doc(inline)]
 pub use model::*;
 
+#[cfg(feature = "gltf")]
+mod gltf_io;
+
+mod skeleton;
+#[doc(inline)]
+pub use skeleton::*;
+
+mod animation_mixer;
+#[doc(inline)]
+pub use animation_mixer::*;
+
+mod skinned_model;
+#[doc(inline)]
+pub use skinned_model::*;
+
 mod instanced_model;
 #[doc(inline)]
 pub use instanced_model::*;
 
+mod instanced_skinned_model;
+#[doc(inline)]
+pub use instanced_skinned_model::*;
+
 mod voxel_grid;
 #[doc(inline)]
 pub use voxel_grid::*;
@@ -38,6 +61,10 @@ mod skybox;
 #[doc(inline)]
 pub use skybox::*;
 
+mod sky;
+#[doc(inline)]
+pub use sky::*;
+
 mod imposters;
 #[doc(inline)]
 pub use imposters::*;
@@ -46,14 +73,65 @@ mod terrain;
 #[doc(inline)]
 pub use terrain::*;
 
+mod foliage;
+#[doc(inline)]
+pub use foliage::*;
+
 mod water;
 #[doc(inline)]
 pub use water::*;
 
+mod decal;
+#[doc(inline)]
+pub use decal::*;
+
+mod particle_emitter;
+#[doc(inline)]
+pub use particle_emitter::*;
+
+mod points;
+#[doc(inline)]
+pub use points::*;
+
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+mod text;
+#[doc(inline)]
+#[cfg(feature = "text")]
+pub use text::*;
+
+mod static_batch;
+#[doc(inline)]
+pub use static_batch::*;
+
+mod lod_group;
+#[doc(inline)]
+pub use lod_group::*;
+
+mod scene;
+#[doc(inline)]
+pub use scene::*;
+
 mod axes;
 #[doc(inline)]
 pub use axes::*;
 
+mod gizmo;
+#[doc(inline)]
+pub use gizmo::*;
+
+mod wireframe;
+#[doc(inline)]
+pub use wireframe::*;
+
+mod normal_glyphs;
+#[doc(inline)]
+pub use normal_glyphs::*;
+
+mod grid;
+#[doc(inline)]
+pub use grid::*;
+
 use crate::core::*;
 use crate::renderer::*;
 
@@ -72,6 +150,14 @@ pub trait Object: Geometry {
     /// Returns the type of material applied to this object.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns an id of the shader program used to render this object, if it is known, which is used to render objects using the same program after each other.
+    /// Objects with the same id must use the same vertex and fragment shader.
+    ///
+    fn program_id(&self) -> Option<u64> {
+        None
+    }
 }
 
 use std::ops::Deref;
@@ -107,4 +193,8 @@ impl<T: Object> Object for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
+
+    fn program_id(&self) -> Option<u64> {
+        self.read().unwrap().program_id()
+    }
 }
@@ -0,0 +1,329 @@
+use crate::renderer::*;
+
+///
+/// The weight of each joint in a [Skeleton], which restricts an [AnimationLayer] to some of the joints, for example the upper body.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationMask {
+    /// The weight of each joint between zero and one, in the same order as the joints in the skeleton.
+    pub weights: Vec<f32>,
+}
+
+impl AnimationMask {
+    ///
+    /// Creates a mask containing the given joint and all of its descendants, for example the spine to mask the upper body.
+    ///
+    pub fn from_joint(skeleton: &Skeleton, joint: usize) -> Self {
+        let joints = skeleton.joints();
+        Self {
+            weights: (0..joints.len())
+                .map(|mut i| loop {
+                    if i == joint {
+                        break 1.0;
+                    }
+                    match joints[i].parent {
+                        Some(parent) => i = parent,
+                        None => break 0.0,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    ///
+    /// Returns the inverse of this mask, for example the lower body if this mask is the upper body.
+    ///
+    pub fn inverse(&self) -> Self {
+        Self {
+            weights: self.weights.iter().map(|w| 1.0 - w).collect(),
+        }
+    }
+
+    ///
+    /// Returns the weight of the joint with the given index.
+    ///
+    pub fn weight(&self, joint: usize) -> f32 {
+        self.weights.get(joint).copied().unwrap_or(0.0)
+    }
+}
+
+///
+/// A state in an [AnimationStateMachine], for example idle, walking or jumping.
+///
+#[derive(Clone, Debug)]
+pub struct AnimationState {
+    /// The name of the state.
+    pub name: String,
+    /// The index of the animation played in this state.
+    pub clip: usize,
+    /// Whether the animation starts over when it ends.
+    pub looping: bool,
+}
+
+///
+/// A transition between two states in an [AnimationStateMachine].
+///
+#[derive(Clone, Debug)]
+pub struct AnimationTransition {
+    /// The name of the state the transition starts from or `None` if the transition can start from any state.
+    pub from: Option<String>,
+    /// The name of the state the transition ends in.
+    pub to: String,
+    /// The name of the trigger starting the transition, see [AnimationStateMachine::trigger],
+    /// or `None` if the transition starts when the animation of the state it starts from has finished.
+    pub trigger: Option<String>,
+    /// The number of seconds to blend from the animation of the state the transition starts from to the animation of the new state.
+    pub crossfade_duration: f32,
+}
+
+///
+/// Controls which animation an [AnimationPlayer] plays using a set of states and the transitions between them.
+/// The state changes when a transition is triggered by the application, for example when the user presses the jump button,
+/// or when the animation of a state has finished, for example to return to idle after landing.
+///
+#[derive(Clone, Debug)]
+pub struct AnimationStateMachine {
+    states: Vec<AnimationState>,
+    transitions: Vec<AnimationTransition>,
+    current: usize,
+    started: bool,
+}
+
+impl AnimationStateMachine {
+    ///
+    /// Creates a new state machine with the given states and transitions, which starts in the state with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initial state or a state used by a transition does not exist.
+    pub fn new(
+        states: Vec<AnimationState>,
+        transitions: Vec<AnimationTransition>,
+        initial_state: &str,
+    ) -> Self {
+        let state_index = |name: &str| {
+            states
+                .iter()
+                .position(|s| s.name == name)
+                .unwrap_or_else(|| panic!("The animation state {} does not exist", name))
+        };
+        for transition in transitions.iter() {
+            if let Some(from) = &transition.from {
+                state_index(from);
+            }
+            state_index(&transition.to);
+        }
+        Self {
+            current: state_index(initial_state),
+            states,
+            transitions,
+            started: false,
+        }
+    }
+
+    ///
+    /// Returns the current state.
+    ///
+    pub fn state(&self) -> &AnimationState {
+        &self.states[self.current]
+    }
+
+    ///
+    /// Starts the transitions from the current state with the given trigger and returns whether the state changed.
+    ///
+    pub fn trigger(&mut self, player: &mut AnimationPlayer, trigger: &str) -> bool {
+        self.transition(player, Some(trigger))
+    }
+
+    ///
+    /// Starts the animation of the initial state the first time it is called and afterwards starts the transitions from the current state when its animation has finished.
+    /// Call this every frame before updating the player, which is done automatically by [AnimationMixer::update].
+    ///
+    pub fn update(&mut self, player: &mut AnimationPlayer) {
+        if !self.started {
+            self.started = true;
+            let state = &self.states[self.current];
+            player.play(state.clip, state.looping, 0.0);
+        } else if player.is_finished() {
+            self.transition(player, None);
+        }
+    }
+
+    fn transition(&mut self, player: &mut AnimationPlayer, trigger: Option<&str>) -> bool {
+        let current = &self.states[self.current].name;
+        let Some(transition) = self.transitions.iter().find(|t| {
+            t.trigger.as_deref() == trigger
+                && t.from.as_ref().is_none_or(|from| from == current)
+                && &t.to != current
+        }) else {
+            return false;
+        };
+        self.current = self
+            .states
+            .iter()
+            .position(|s| s.name == transition.to)
+            .unwrap();
+        self.started = true;
+        let state = &self.states[self.current];
+        player.play(state.clip, state.looping, transition.crossfade_duration);
+        true
+    }
+}
+
+///
+/// A layer in an [AnimationMixer], which plays animations on top of the layers below.
+///
+#[derive(Clone, Debug)]
+pub struct AnimationLayer {
+    /// The player of the animations in this layer.
+    pub player: AnimationPlayer,
+    /// How much the pose of this layer replaces the pose of the layers below, between zero and one.
+    pub weight: f32,
+    /// The joints affected by this layer or `None` if all joints are affected.
+    pub mask: Option<AnimationMask>,
+    /// An optional state machine controlling which animation is played in this layer.
+    pub state_machine: Option<AnimationStateMachine>,
+}
+
+///
+/// Blends the animations of a [Skeleton] played in a number of layers, for example running on the base layer and waving on a layer masked to the upper body.
+/// Each layer has an [AnimationPlayer], which can be controlled directly or using an [AnimationStateMachine].
+///
+#[derive(Clone, Debug)]
+pub struct AnimationMixer {
+    layers: Vec<AnimationLayer>,
+    last_time: Option<f32>,
+    time: f32,
+    /// The playback speed of all layers, where one is normal speed.
+    pub speed: f32,
+}
+
+impl AnimationMixer {
+    ///
+    /// Creates a new mixer for the given animations with a single base layer affecting all joints. No animation is playing initially.
+    ///
+    pub fn new(clips: Vec<AnimationClip>) -> Self {
+        Self {
+            layers: vec![AnimationLayer {
+                player: AnimationPlayer::new(clips),
+                weight: 1.0,
+                mask: None,
+                state_machine: None,
+            }],
+            last_time: None,
+            time: 0.0,
+            speed: 1.0,
+        }
+    }
+
+    ///
+    /// Adds a layer on top of the existing layers with the given mask and weight and returns the index of the layer.
+    /// The layer can play the same animations as the base layer.
+    ///
+    pub fn add_layer(&mut self, mask: Option<AnimationMask>, weight: f32) -> usize {
+        self.layers.push(AnimationLayer {
+            player: AnimationPlayer::new(self.layers[0].player.clips().to_vec()),
+            weight,
+            mask,
+            state_machine: None,
+        });
+        self.layers.len() - 1
+    }
+
+    ///
+    /// Returns the layers, where the first layer is the base layer.
+    ///
+    pub fn layers(&self) -> &[AnimationLayer] {
+        &self.layers
+    }
+
+    ///
+    /// Returns the layers, where the first layer is the base layer.
+    ///
+    pub fn layers_mut(&mut self) -> &mut [AnimationLayer] {
+        &mut self.layers
+    }
+
+    ///
+    /// Starts the transitions with the given trigger in the state machines of all layers, see [AnimationStateMachine::trigger], and returns whether any state changed.
+    ///
+    pub fn trigger(&mut self, trigger: &str) -> bool {
+        let mut changed = false;
+        for layer in self.layers.iter_mut() {
+            if let Some(state_machine) = &mut layer.state_machine {
+                changed |= state_machine.trigger(&mut layer.player, trigger);
+            }
+        }
+        changed
+    }
+
+    ///
+    /// Advances the animations in all layers. Call this every frame with some continuous time in seconds, for example the time since start.
+    /// Returns the events which have been passed since the last update, see [AnimationClip::events].
+    ///
+    pub fn update(&mut self, time: f32) -> Vec<AnimationEvent> {
+        self.time += self
+            .last_time
+            .map_or(0.0, |last_time| (time - last_time) * self.speed);
+        self.last_time = Some(time);
+        let mut events = Vec::new();
+        for layer in self.layers.iter_mut() {
+            if let Some(state_machine) = &mut layer.state_machine {
+                state_machine.update(&mut layer.player);
+            }
+            events.extend(layer.player.update(self.time));
+        }
+        events
+    }
+
+    ///
+    /// Returns the current pose of the given skeleton, where the pose of each layer is blended on top of the layers below according to the weight and mask of the layer.
+    ///
+    pub fn pose(&self, skeleton: &Skeleton) -> Pose {
+        let mut pose = skeleton.rest_pose();
+        for layer in self.layers.iter() {
+            if layer.player.playing().is_none() {
+                continue;
+            }
+            let layer_pose = layer.player.pose(skeleton);
+            for (joint, (joint_pose, layer_joint_pose)) in pose
+                .joints
+                .iter_mut()
+                .zip(layer_pose.joints.iter())
+                .enumerate()
+            {
+                let weight = layer.weight * layer.mask.as_ref().map_or(1.0, |m| m.weight(joint));
+                if weight > 0.0 {
+                    *joint_pose = joint_pose.interpolate(layer_joint_pose, weight);
+                }
+            }
+        }
+        pose
+    }
+
+    ///
+    /// Returns the current morph target weights of the mesh with the given index, where the given weights are used if the mesh is not animated, see [AnimationPlayer::morph_weights].
+    /// The masks of the layers do not apply to the morph target weights.
+    ///
+    pub fn morph_weights(&self, mesh: usize, weights: &[f32]) -> Vec<f32> {
+        let mut result = weights.to_vec();
+        for layer in self.layers.iter() {
+            let animated = layer.player.playing().is_some_and(|clip| {
+                layer.player.clips()[clip]
+                    .morph_channels
+                    .iter()
+                    .any(|(i, _)| *i == mesh)
+            });
+            if !animated {
+                continue;
+            }
+            for (w, layer_w) in result
+                .iter_mut()
+                .zip(layer.player.morph_weights(mesh, weights))
+            {
+                *w += (layer_w - *w) * layer.weight;
+            }
+        }
+        result
+    }
+}
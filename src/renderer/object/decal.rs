@@ -0,0 +1,271 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Defines how the color of a [Decal] is combined with the color of the surface it is projected onto.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DecalBlend {
+    /// The lit color of the decal is blended on top of the surface using the alpha value of the decal, for example for stickers and road markings.
+    #[default]
+    Alpha,
+    /// The color of the surface is multiplied by the color of the decal, for example for dirt, stains and bullet holes.
+    /// If the decal has a normal texture, the shading of the surface is also changed according to the normals of the decal.
+    Multiply,
+}
+
+///
+/// A decal which projects a texture onto the geometries already rendered inside a box, for example bullet holes, stickers or road markings,
+/// without changing the meshes of the geometries.
+///
+/// The box is a cube from -1 to 1 in each dimension which is placed in the scene by [Decal::set_transformation].
+/// The textures are mapped onto the xy plane of the box and projected along the negative z axis, so only surfaces facing the positive z axis receive the decal.
+/// The position and normal of the surfaces are reconstructed from a depth texture containing the rendered scene, see [Decal::render].
+///
+pub struct Decal {
+    mesh: Mesh,
+    /// Base color of the decal.
+    pub albedo: Srgba,
+    /// Texture with the colors of the decal, where the alpha value specifies where the decal is visible.
+    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    pub albedo_texture: Option<Texture2DRef>,
+    /// Texture containing the normals of the decal in tangent space.
+    pub normal_texture: Option<Texture2DRef>,
+    /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
+    pub normal_scale: f32,
+    /// A value in the range `[0..1]` specifying how much the normals of the [Self::normal_texture] replace the normals of the surface, where zero keeps the normals of the surface.
+    pub normal_blend: f32,
+    /// A value in the range `[0..1]` specifying how metallic the decal is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the decal is.
+    pub roughness: f32,
+    /// How the color of the decal is combined with the color of the surface.
+    pub blend: DecalBlend,
+    /// The lighting model used when rendering this decal.
+    pub lighting_model: LightingModel,
+}
+
+impl Decal {
+    ///
+    /// Creates a new decal from a [CpuMaterial], where the albedo, normal, metallic and roughness properties are used.
+    /// The decal is placed in the unit cube until a transformation is set with [Decal::set_transformation].
+    ///
+    pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        let albedo_texture =
+            cpu_material
+                .albedo_texture
+                .as_ref()
+                .map(|cpu_texture| match &cpu_texture.data {
+                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
+                        let mut cpu_texture = cpu_texture.clone();
+                        cpu_texture.data.to_linear_srgb();
+                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
+                    }
+                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
+                });
+        Self {
+            mesh: Mesh::new(context, &CpuMesh::cube()),
+            albedo: cpu_material.albedo,
+            albedo_texture,
+            normal_texture: cpu_material
+                .normal_texture
+                .as_ref()
+                .map(|cpu_texture| Texture2DRef::from_cpu_texture(context, cpu_texture)),
+            normal_scale: cpu_material.normal_scale,
+            normal_blend: 1.0,
+            metallic: cpu_material.metallic,
+            roughness: cpu_material.roughness,
+            blend: DecalBlend::default(),
+            lighting_model: cpu_material.lighting_model,
+        }
+    }
+
+    ///
+    /// Returns the local to world transformation of the box which the decal is projected within.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.mesh.transformation()
+    }
+
+    ///
+    /// Set the local to world transformation of the box which the decal is projected within.
+    /// The box is a cube from -1 to 1 in each dimension before the transformation is applied.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.mesh.set_transformation(transformation);
+    }
+
+    ///
+    /// Returns the axis aligned bounding box of the box which the decal is projected within.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.mesh.aabb()
+    }
+
+    ///
+    /// Renders the decal on top of the content of the render target.
+    /// The depth texture must contain the depth of the scene rendered with the same camera and must not be attached to the render target.
+    ///
+    pub fn render(
+        &self,
+        target: &RenderTarget,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        depth_texture: DepthTexture,
+    ) {
+        target.render_with_effect(
+            &DecalPass { decal: self },
+            camera,
+            &self.mesh,
+            lights,
+            None,
+            Some(depth_texture),
+        );
+    }
+}
+
+struct DecalPass<'a> {
+    decal: &'a Decal,
+}
+
+impl Effect for DecalPass<'_> {
+    fn fragment_shader_source(
+        &self,
+        lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let mut output = String::new();
+        if self.decal.albedo_texture.is_some() {
+            output.push_str("#define USE_ALBEDO_TEXTURE\n");
+        }
+        if self.decal.normal_texture.is_some() {
+            output.push_str("#define USE_NORMAL_TEXTURE\n");
+        }
+        if self.decal.blend == DecalBlend::Multiply {
+            output.push_str("#define USE_MULTIPLY_BLEND\n");
+        }
+        output.push_str(&lights_shader_source(lights, self.decal.lighting_model));
+        output.push_str(
+            &depth_texture
+                .expect("Must supply a depth texture to render a decal")
+                .fragment_shader_source(),
+        );
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/decal.frag"));
+        output
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b11000u16 << 7
+            | if self.decal.albedo_texture.is_some() {
+                0b1u16 << 7
+            } else {
+                0
+            }
+            | if self.decal.normal_texture.is_some() {
+                0b1u16 << 8
+            } else {
+                0
+            }
+            | if self.decal.blend == DecalBlend::Multiply {
+                0b1u16 << 9
+            } else {
+                0
+            }
+            | depth_texture
+                .expect("Must supply a depth texture to render a decal")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let decal = self.decal;
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to render a decal")
+            .use_uniforms(program);
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        let transformation = decal.transformation();
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform(
+            "decalTransformationInverse",
+            transformation.invert().unwrap(),
+        );
+        program.use_uniform_if_required(
+            "decalTangent",
+            transformation
+                .transform_vector(vec3(1.0, 0.0, 0.0))
+                .normalize(),
+        );
+        program.use_uniform(
+            "decalDirection",
+            transformation
+                .transform_vector(vec3(0.0, 0.0, 1.0))
+                .normalize(),
+        );
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "viewport",
+            vec4(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ),
+        );
+        program.use_uniform("cameraPosition", camera.lighting_position());
+        program.use_uniform("albedo", decal.albedo.to_linear_srgb());
+        if let Some(ref texture) = decal.albedo_texture {
+            program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_texture("albedoTexture", texture);
+        }
+        if let Some(ref texture) = decal.normal_texture {
+            program.use_uniform("normalTexTransform", texture.transformation);
+            program.use_uniform("normalScale", decal.normal_scale);
+            program.use_uniform("normalBlend", decal.normal_blend);
+            program.use_texture("normalTexture", texture);
+        }
+        program.use_uniform_if_required("metallic", decal.metallic);
+        program.use_uniform_if_required("roughness", decal.roughness);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            // The back faces of the box are rendered so the decal is also visible when the camera is inside the box
+            cull: Cull::Front,
+            blend: match self.decal.blend {
+                DecalBlend::Alpha => Blend::TRANSPARENCY,
+                DecalBlend::Multiply => Blend::Enabled {
+                    source_rgb_multiplier: BlendMultiplierType::DstColor,
+                    source_alpha_multiplier: BlendMultiplierType::Zero,
+                    destination_rgb_multiplier: BlendMultiplierType::Zero,
+                    destination_alpha_multiplier: BlendMultiplierType::One,
+                    rgb_equation: BlendEquationType::Add,
+                    alpha_equation: BlendEquationType::Add,
+                },
+            },
+            ..Default::default()
+        }
+    }
+}
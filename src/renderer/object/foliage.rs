@@ -0,0 +1,372 @@
+use crate::core::*;
+use crate::renderer::geometry::BaseMesh;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// Defines where the instances of a [Foliage] are scattered.
+///
+#[derive(Clone)]
+pub struct FoliageScatter {
+    /// The corner of the area in the xz plane where the instances are scattered.
+    pub origin: Vec2,
+    /// The size of the area in the xz plane where the instances are scattered.
+    pub size: Vec2,
+    /// The average number of instances per square unit where the [Self::density_map] is one.
+    pub density: f32,
+    /// A function of the (x, z) coordinates which returns a value in the range `[0..1]` that the [Self::density] is multiplied with,
+    /// for example created from an image using [height_map_from_texture]. If not specified, the density is the same everywhere.
+    pub density_map: Option<Arc<dyn Fn(f32, f32) -> f32 + Send + Sync>>,
+    /// A function of the (x, z) coordinates which returns the height y where the instances are placed, for example the height map of a [Terrain].
+    /// If not specified, the instances are placed at zero height.
+    pub height_map: Option<Arc<dyn Fn(f32, f32) -> f32 + Send + Sync>>,
+    /// The minimum and maximum uniform scale applied to each instance, where the scale of each instance is chosen randomly in between.
+    pub scale_range: (f32, f32),
+    /// The side length of the square patches which the instances are divided into.
+    /// Each patch is culled individually when outside the view of the camera or beyond the fade distance, see [Foliage::set_fade_distance].
+    pub patch_size: f32,
+    /// The seed of the random placement, so the same seed gives the same placement.
+    pub seed: u64,
+}
+
+impl Default for FoliageScatter {
+    fn default() -> Self {
+        Self {
+            origin: vec2(-5.0, -5.0),
+            size: vec2(10.0, 10.0),
+            density: 10.0,
+            density_map: None,
+            height_map: None,
+            scale_range: (0.8, 1.2),
+            patch_size: 8.0,
+            seed: 0,
+        }
+    }
+}
+
+///
+/// A large number of instances of a mesh, for example grass blades, flowers or small rocks, scattered across an area with an applied material.
+/// The instances are randomly rotated around the y axis and, if the mesh is modelled with the y axis as up, bent by the wind in the vertex shader
+/// so that the part of the mesh above zero height sways more the higher it is.
+/// The instances are shrunk and then removed between two distances from the camera, which hides them in the distance without popping.
+///
+pub struct Foliage<M: Material> {
+    patches: Vec<Gm<FoliagePatch, M>>,
+}
+
+impl<M: Material + Clone> Foliage<M> {
+    ///
+    /// Scatters instances of the given mesh with the given material according to the given [FoliageScatter].
+    ///
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        material: M,
+        scatter: &FoliageScatter,
+    ) -> Self {
+        let base_mesh = Arc::new(BaseMesh::new(context, cpu_mesh));
+        let mesh_aabb = cpu_mesh.compute_aabb();
+        let mut random = Random::new(scatter.seed);
+        let patch_size = scatter.patch_size.max(0.001);
+        let patch_count_x = (scatter.size.x / patch_size).ceil().max(1.0) as u32;
+        let patch_count_z = (scatter.size.y / patch_size).ceil().max(1.0) as u32;
+        let mut patches = Vec::new();
+        for i in 0..patch_count_x {
+            for j in 0..patch_count_z {
+                let min = scatter.origin + vec2(i as f32, j as f32) * patch_size;
+                let max = vec2(
+                    (min.x + patch_size).min(scatter.origin.x + scatter.size.x),
+                    (min.y + patch_size).min(scatter.origin.y + scatter.size.y),
+                );
+                let expected_count = scatter.density * (max.x - min.x) * (max.y - min.y);
+                let mut candidate_count = expected_count.floor() as u32;
+                if random.next_f32() < expected_count.fract() {
+                    candidate_count += 1;
+                }
+                let mut positions = Vec::new();
+                let mut rotations = Vec::new();
+                for _ in 0..candidate_count {
+                    let x = min.x + random.next_f32() * (max.x - min.x);
+                    let z = min.y + random.next_f32() * (max.y - min.y);
+                    let rotation = random.next_f32() * std::f32::consts::TAU;
+                    let scale = scatter.scale_range.0
+                        + random.next_f32() * (scatter.scale_range.1 - scatter.scale_range.0);
+                    let probability = random.next_f32();
+                    if let Some(density_map) = &scatter.density_map {
+                        if probability >= density_map(x, z) {
+                            continue;
+                        }
+                    }
+                    let y = scatter.height_map.as_ref().map_or(0.0, |h| h(x, z));
+                    positions.push(vec4(x, y, z, scale));
+                    rotations.push(rotation);
+                }
+                if !positions.is_empty() {
+                    patches.push(Gm::new(
+                        FoliagePatch::new(
+                            context,
+                            base_mesh.clone(),
+                            mesh_aabb,
+                            positions,
+                            rotations,
+                        ),
+                        material.clone(),
+                    ));
+                }
+            }
+        }
+        Self { patches }
+    }
+
+    ///
+    /// Returns the total number of instances.
+    ///
+    pub fn instance_count(&self) -> u32 {
+        self.patches.iter().map(|p| p.instance_count).sum()
+    }
+
+    ///
+    /// Set the wind which bends the instances, given as the direction in the xz plane and the strength, where zero strength turns off the wind.
+    /// The waves of wind move across the area with the given speed in units per second and with the given distance between each wave.
+    ///
+    pub fn set_wind(&mut self, direction: Vec2, strength: f32, speed: f32, wavelength: f32) {
+        let direction = if direction.magnitude2() > 0.0 {
+            direction.normalize()
+        } else {
+            vec2(1.0, 0.0)
+        };
+        for patch in self.patches.iter_mut() {
+            patch.wind = Wind {
+                direction,
+                strength,
+                speed,
+                wavelength: wavelength.max(0.001),
+            };
+        }
+    }
+
+    ///
+    /// Set the distances from the camera where the instances start to shrink and where they are gone.
+    /// The default is to start at a distance of 40 and end at a distance of 50.
+    ///
+    pub fn set_fade_distance(&mut self, start: f32, end: f32) {
+        for patch in self.patches.iter_mut() {
+            patch.fade_distance = (start, end.max(start + 0.001));
+        }
+    }
+
+    ///
+    /// Animates the wind. Call this every frame with the elapsed time in seconds.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        for patch in self.patches.iter_mut() {
+            patch.animate(time);
+        }
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a Foliage<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.patches
+            .iter()
+            .map(|m| m as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Wind {
+    direction: Vec2,
+    strength: f32,
+    speed: f32,
+    wavelength: f32,
+}
+
+struct FoliagePatch {
+    context: Context,
+    base_mesh: Arc<BaseMesh>,
+    positions: InstanceBuffer,
+    rotations: InstanceBuffer,
+    instance_count: u32,
+    aabb: AxisAlignedBoundingBox,
+    wind: Wind,
+    fade_distance: (f32, f32),
+    time: f32,
+}
+
+impl FoliagePatch {
+    fn new(
+        context: &Context,
+        base_mesh: Arc<BaseMesh>,
+        mesh_aabb: AxisAlignedBoundingBox,
+        positions: Vec<Vec4>,
+        rotations: Vec<f32>,
+    ) -> Self {
+        // The instances are rotated around the y axis, bent by the wind and scaled, so the bounding box is expanded to contain any rotation and some bending
+        let (min, max) = (mesh_aabb.min(), mesh_aabb.max());
+        let radius = vec2(min.x.abs().max(max.x.abs()), min.z.abs().max(max.z.abs())).magnitude()
+            + max.y.max(0.0);
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for p in positions.iter() {
+            let scale = p.w;
+            aabb.expand(&[
+                vec3(
+                    p.x - radius * scale,
+                    p.y + min.y * scale,
+                    p.z - radius * scale,
+                ),
+                vec3(
+                    p.x + radius * scale,
+                    p.y + max.y * scale,
+                    p.z + radius * scale,
+                ),
+            ]);
+        }
+        Self {
+            context: context.clone(),
+            base_mesh,
+            instance_count: positions.len() as u32,
+            positions: InstanceBuffer::new_with_data(context, &positions),
+            rotations: InstanceBuffer::new_with_data(context, &rotations),
+            aabb,
+            wind: Wind {
+                direction: vec2(1.0, 0.0),
+                strength: 0.1,
+                speed: 2.0,
+                wavelength: 10.0,
+            },
+            fade_distance: (40.0, 50.0),
+            time: 0.0,
+        }
+    }
+}
+
+impl Geometry for FoliagePatch {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if self.aabb.distance(camera.position()) > self.fade_distance.1 {
+            return;
+        }
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("time", self.time);
+        program.use_uniform("windDirection", self.wind.direction);
+        program.use_uniform("windStrength", self.wind.strength);
+        program.use_uniform("windSpeed", self.wind.speed);
+        program.use_uniform("windWavelength", self.wind.wavelength);
+        program.use_uniform("fadeStart", self.fade_distance.0);
+        program.use_uniform("fadeEnd", self.fade_distance.1);
+        program.use_instance_attribute("instance_position", &self.positions);
+        program.use_instance_attribute("instance_rotation", &self.rotations);
+        self.base_mesh.draw_instanced(
+            program,
+            render_states,
+            camera,
+            attributes,
+            self.instance_count,
+        );
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "{}{}{}{}",
+            if required_attributes.normal || required_attributes.tangents {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            include_str!("shaders/foliage.vert"),
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 9;
+        if required_attributes.normal || required_attributes.tangents {
+            id |= 0b1u16;
+        }
+        if required_attributes.tangents {
+            id |= 0b1u16 << 1;
+        }
+        if required_attributes.uv {
+            id |= 0b1u16 << 2;
+        }
+        id
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    fn animate(&mut self, time: f32) {
+        self.time = time;
+    }
+}
+
+///
+/// A small deterministic random number generator (SplitMix64), so the placement only depends on the seed.
+///
+pub(super) struct Random(u64);
+
+impl Random {
+    pub(super) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns a random number in the range `[0..1)`.
+    pub(super) fn next_f32(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -0,0 +1,455 @@
+use crate::renderer::*;
+
+///
+/// The kind of transformation a [Gizmo] applies.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GizmoMode {
+    /// Moves the target along one of the axes using arrow handles.
+    #[default]
+    Translate,
+    /// Rotates the target around one of the axes using ring handles.
+    Rotate,
+    /// Scales the target along one of its local axes using handles with a cube at the end.
+    Scale,
+}
+
+///
+/// The coordinate system the axes of a [Gizmo] are aligned with.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GizmoSpace {
+    /// The axes are the world x, y and z axes.
+    #[default]
+    World,
+    /// The axes are the x, y and z axes of the target, ie. they are rotated with the target.
+    Local,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct GizmoDrag {
+    axis: usize,
+    start_transformation: Mat4,
+    start_value: f32,
+}
+
+///
+/// An interactive 3D manipulator for translating, rotating or scaling a target transformation, for example the transformation of the selected object in an editor, see [GizmoMode].
+///
+/// The gizmo is placed at the translation of the target transformation and consists of a handle for each axis, which is highlighted when the mouse hovers it.
+/// Dragging a handle with the left mouse button changes the target transformation, see [Gizmo::handle_events].
+/// The handles have the same size on the screen no matter how far the gizmo is from the camera, given by [Gizmo::size],
+/// and they are rendered on top of the scene, so render the gizmo after the scene.
+///
+/// Scaling is always along the local axes of the target, so in [GizmoMode::Scale] the handles are aligned with the target independent of the [GizmoSpace].
+///
+pub struct Gizmo {
+    models: [Gm<InstancedMesh, ColorMaterial>; 3],
+    hovered: Option<usize>,
+    drag: Option<GizmoDrag>,
+    /// The kind of transformation applied when dragging a handle.
+    pub mode: GizmoMode,
+    /// The coordinate system the handles are aligned with when translating or rotating.
+    pub space: GizmoSpace,
+    /// The length of the handles in physical pixels.
+    pub size: f32,
+    /// If specified, translations are rounded to a multiple of this distance.
+    pub translation_snap: Option<f32>,
+    /// If specified, rotations are rounded to a multiple of this angle.
+    pub rotation_snap: Option<Radians>,
+    /// If specified, scale factors are rounded to a multiple of this value.
+    pub scale_snap: Option<f32>,
+}
+
+impl Gizmo {
+    ///
+    /// Creates a new gizmo in [GizmoMode::Translate] and [GizmoSpace::World] mode.
+    ///
+    pub fn new(context: &Context) -> Self {
+        let material = ColorMaterial {
+            render_states: RenderStates {
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let model = |cpu_mesh: CpuMesh| {
+            Gm::new(
+                InstancedMesh::new(context, &Self::instances(Mat4::identity(), None), &cpu_mesh),
+                material.clone(),
+            )
+        };
+
+        let arrow = CpuMesh::arrow(0.8, 0.25, 16)
+            .transformed(&Mat4::from_nonuniform_scale(1.0, 0.08, 0.08));
+        let ring = torus(1.0, 0.02, 64, 8);
+        let handle = combine(&[
+            CpuMesh::cylinder(16).transformed(&Mat4::from_nonuniform_scale(0.9, 0.02, 0.02)),
+            CpuMesh::cube().transformed(
+                &(Mat4::from_translation(vec3(0.93, 0.0, 0.0)) * Mat4::from_scale(0.07)),
+            ),
+        ]);
+        Self {
+            models: [model(arrow), model(ring), model(handle)],
+            hovered: None,
+            drag: None,
+            mode: GizmoMode::default(),
+            space: GizmoSpace::default(),
+            size: 100.0,
+            translation_snap: None,
+            rotation_snap: None,
+            scale_snap: None,
+        }
+    }
+
+    ///
+    /// Returns whether a handle is being dragged.
+    ///
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    ///
+    /// Handles the events, ie. highlights the handle below the mouse and changes the target transformation when dragging a handle.
+    /// Should be called each frame before the events are handled by for example a camera control, since the mouse events used by the gizmo are marked as handled.
+    /// The gizmo is also updated for rendering, see [Gizmo::update].
+    /// Returns whether the target transformation was changed.
+    ///
+    pub fn handle_events(
+        &mut self,
+        camera: &Camera,
+        transformation: &mut Mat4,
+        events: &mut [Event],
+    ) -> bool {
+        let mut change = false;
+        for event in events.iter_mut() {
+            match event {
+                Event::MousePress {
+                    button: MouseButton::Left,
+                    position,
+                    handled,
+                    ..
+                } if !*handled => {
+                    self.hovered = self.pick(camera, transformation, *position);
+                    if let Some(axis) = self.hovered {
+                        let start_value = self
+                            .value(camera, transformation, axis, *position)
+                            .unwrap_or(0.0);
+                        self.drag = Some(GizmoDrag {
+                            axis,
+                            start_transformation: *transformation,
+                            start_value,
+                        });
+                        *handled = true;
+                    }
+                }
+                Event::MouseMotion {
+                    position, handled, ..
+                } if !*handled => {
+                    if let Some(drag) = self.drag {
+                        if let Some(new_transformation) = self.drag(camera, &drag, *position) {
+                            *transformation = new_transformation;
+                            change = true;
+                        }
+                        *handled = true;
+                    } else {
+                        self.hovered = self.pick(camera, transformation, *position);
+                    }
+                }
+                Event::MouseRelease {
+                    button: MouseButton::Left,
+                    handled,
+                    ..
+                } if !*handled && self.drag.is_some() => {
+                    self.drag = None;
+                    *handled = true;
+                }
+                Event::MouseLeave if self.drag.is_none() => {
+                    self.hovered = None;
+                }
+                _ => {}
+            }
+        }
+        self.update(camera, transformation);
+        change
+    }
+
+    ///
+    /// Places the gizmo at the given target transformation and scales it so the handles have the length [Gizmo::size] in pixels seen from the given camera.
+    /// This is called by [Gizmo::handle_events], so it is only necessary to call this if the target or the camera has changed after the events were handled.
+    ///
+    pub fn update(&mut self, camera: &Camera, transformation: &Mat4) {
+        let axes = self.axes(transformation);
+        let origin = transformation.w.truncate();
+        let frame = Mat4::from_translation(origin)
+            * Mat4::from(axes)
+            * Mat4::from_scale(self.world_size(camera, origin));
+        let highlighted = self.drag.map(|drag| drag.axis).or(self.hovered);
+        let index = self.mode as usize;
+        self.models[index].set_instances(&Self::instances(frame, highlighted));
+    }
+
+    fn instances(frame: Mat4, highlighted: Option<usize>) -> Instances {
+        let colors = [Srgba::RED, Srgba::GREEN, Srgba::BLUE];
+        Instances {
+            transformations: vec![
+                frame,
+                frame * Mat4::from_angle_z(degrees(90.0)),
+                frame * Mat4::from_angle_y(degrees(-90.0)),
+            ],
+            texture_transformations: None,
+            colors: Some(
+                (0..3)
+                    .map(|axis| {
+                        if Some(axis) == highlighted {
+                            Srgba::new(255, 220, 0, 255)
+                        } else {
+                            colors[axis]
+                        }
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn model(&self) -> &Gm<InstancedMesh, ColorMaterial> {
+        &self.models[self.mode as usize]
+    }
+
+    ///
+    /// Returns the directions of the handles as the columns of a rotation matrix.
+    ///
+    fn axes(&self, transformation: &Mat4) -> Mat3 {
+        if self.space == GizmoSpace::World && self.mode != GizmoMode::Scale {
+            return Mat3::identity();
+        }
+        // Removes the scale and shear, so the axes are orthonormal
+        let x = transformation.x.truncate().normalize();
+        let y = (transformation.y.truncate() - x * x.dot(transformation.y.truncate())).normalize();
+        let z = x.cross(y);
+        let z = if z.dot(transformation.z.truncate()) < 0.0 {
+            -z
+        } else {
+            z
+        };
+        if x.x.is_finite() && y.x.is_finite() {
+            Mat3::from_cols(x, y, z)
+        } else {
+            Mat3::identity()
+        }
+    }
+
+    ///
+    /// Returns the size in world space at the given position which has the length [Gizmo::size] in pixels.
+    ///
+    fn world_size(&self, camera: &Camera, position: Vec3) -> f32 {
+        let w = (camera.projection() * camera.view() * position.extend(1.0)).w;
+        2.0 * w.abs() * self.size / (camera.projection().y.y * camera.viewport().height as f32)
+    }
+
+    ///
+    /// Returns the axis of the handle closest to the given pixel, if any handle is close enough.
+    ///
+    fn pick(&self, camera: &Camera, transformation: &Mat4, pixel: PhysicalPoint) -> Option<usize> {
+        let origin = transformation.w.truncate();
+        let axes = self.axes(transformation);
+        let size = self.world_size(camera, origin);
+        let pixel = vec2(pixel.x, pixel.y);
+        let to_pixel = |position: Vec3| {
+            let p = camera.pixel_at_position(position);
+            vec2(p.x, p.y)
+        };
+        let max_distance = (0.1 * self.size).max(4.0);
+        (0..3)
+            .filter_map(|axis| {
+                let direction = axes[axis];
+                let points = match self.mode {
+                    GizmoMode::Translate | GizmoMode::Scale => {
+                        vec![to_pixel(origin), to_pixel(origin + direction * size)]
+                    }
+                    GizmoMode::Rotate => {
+                        let u = axes[(axis + 1) % 3];
+                        let v = axes[(axis + 2) % 3];
+                        (0..=64)
+                            .map(|i| {
+                                let angle = i as f32 / 64.0 * std::f32::consts::TAU;
+                                to_pixel(origin + (u * angle.cos() + v * angle.sin()) * size)
+                            })
+                            .collect()
+                    }
+                };
+                let distance = points
+                    .windows(2)
+                    .map(|segment| distance_to_segment(pixel, segment[0], segment[1]))
+                    .fold(f32::INFINITY, f32::min);
+                (distance < max_distance).then_some((axis, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(axis, _)| axis)
+    }
+
+    ///
+    /// Returns the distance along the axis for translation and scaling and the angle on the screen for rotation at the given pixel.
+    ///
+    fn value(
+        &self,
+        camera: &Camera,
+        transformation: &Mat4,
+        axis: usize,
+        pixel: PhysicalPoint,
+    ) -> Option<f32> {
+        let origin = transformation.w.truncate();
+        let direction = self.axes(transformation)[axis];
+        match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                // The closest point on the axis to the ray through the pixel
+                let ray_origin = camera.position_at_pixel(pixel);
+                let ray_direction = camera.view_direction_at_pixel(pixel);
+                let w = origin - ray_origin;
+                let b = direction.dot(ray_direction);
+                let denominator = 1.0 - b * b;
+                (denominator > 1.0e-4)
+                    .then(|| (b * ray_direction.dot(w) - direction.dot(w)) / denominator)
+            }
+            GizmoMode::Rotate => {
+                let center = camera.pixel_at_position(origin);
+                Some((pixel.y - center.y).atan2(pixel.x - center.x))
+            }
+        }
+    }
+
+    fn drag(&self, camera: &Camera, drag: &GizmoDrag, pixel: PhysicalPoint) -> Option<Mat4> {
+        let start = drag.start_transformation;
+        let origin = start.w.truncate();
+        let direction = self.axes(&start)[drag.axis];
+        let value = self.value(camera, &start, drag.axis, pixel)?;
+        let snap = |value: f32, step: Option<f32>| match step {
+            Some(step) if step > 0.0 => (value / step).round() * step,
+            _ => value,
+        };
+        Some(match self.mode {
+            GizmoMode::Translate => {
+                let distance = snap(value - drag.start_value, self.translation_snap);
+                Mat4::from_translation(direction * distance) * start
+            }
+            GizmoMode::Rotate => {
+                let mut angle = value - drag.start_value;
+                // The screen angle increases counterclockwise, which is a positive rotation if the axis points towards the camera
+                if direction.dot(camera.view_direction()) > 0.0 {
+                    angle = -angle;
+                }
+                let angle = snap(angle, self.rotation_snap.map(|a| a.0));
+                Mat4::from_translation(origin)
+                    * Mat4::from_axis_angle(direction, radians(angle))
+                    * Mat4::from_translation(-origin)
+                    * start
+            }
+            GizmoMode::Scale => {
+                if drag.start_value.abs() < 1.0e-6 {
+                    return None;
+                }
+                let factor = snap(value / drag.start_value, self.scale_snap);
+                if factor.abs() < 1.0e-4 {
+                    return None;
+                }
+                let mut scale = vec3(1.0, 1.0, 1.0);
+                scale[drag.axis] = factor;
+                start * Mat4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+            }
+        })
+    }
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab.magnitude2() > 0.0 {
+        ((p - a).dot(ab) / ab.magnitude2()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (a + ab * t - p).magnitude()
+}
+
+///
+/// Returns a torus around the x-axis with the given radius of the ring and radius of the tube.
+///
+fn torus(radius: f32, tube_radius: f32, ring_subdivisions: u32, tube_subdivisions: u32) -> CpuMesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for i in 0..ring_subdivisions {
+        let ring_angle = i as f32 / ring_subdivisions as f32 * std::f32::consts::TAU;
+        let center = vec3(0.0, ring_angle.cos(), ring_angle.sin()) * radius;
+        let outwards = center.normalize();
+        for j in 0..tube_subdivisions {
+            let tube_angle = j as f32 / tube_subdivisions as f32 * std::f32::consts::TAU;
+            positions.push(
+                center
+                    + (outwards * tube_angle.cos() + vec3(tube_angle.sin(), 0.0, 0.0))
+                        * tube_radius,
+            );
+            let next_i = (i + 1) % ring_subdivisions;
+            let next_j = (j + 1) % tube_subdivisions;
+            let index = |i: u32, j: u32| i * tube_subdivisions + j;
+            indices.extend([index(i, j), index(next_i, j), index(next_i, next_j)]);
+            indices.extend([index(i, j), index(next_i, next_j), index(i, next_j)]);
+        }
+    }
+    let mut mesh = CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        ..Default::default()
+    };
+    mesh.compute_normals();
+    mesh
+}
+
+fn combine(meshes: &[CpuMesh]) -> CpuMesh {
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for mesh in meshes {
+        let offset = positions.len() as u32;
+        let mesh_positions = mesh.positions.to_f32();
+        let mesh_indices = mesh
+            .indices
+            .to_u32()
+            .unwrap_or_else(|| (0..mesh_positions.len() as u32).collect());
+        indices.extend(mesh_indices.into_iter().map(|i| i + offset));
+        positions.extend(mesh_positions);
+    }
+    let mut mesh = CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        ..Default::default()
+    };
+    mesh.compute_normals();
+    mesh
+}
+
+trait Transformed {
+    fn transformed(self, transformation: &Mat4) -> Self;
+}
+
+impl Transformed for CpuMesh {
+    fn transformed(mut self, transformation: &Mat4) -> Self {
+        self.transform(transformation).unwrap();
+        self
+    }
+}
+
+impl<'a> IntoIterator for &'a Gizmo {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Gizmo {
+    impl_geometry_body!(model);
+}
+
+impl Object for Gizmo {
+    impl_object_body!(model);
+}
@@ -0,0 +1,308 @@
+// Parsing of the parts of glTF files which are not supported by three-d-asset, ie. skins, morph targets and material extensions
+use crate::renderer::*;
+use std::path::Path;
+
+pub(super) fn error(e: impl std::fmt::Display) -> RendererError {
+    RendererError::InvalidGltf(e.to_string())
+}
+
+///
+/// Parses the glTF file at the given path and returns the document together with the data of each buffer.
+///
+pub(super) fn parse(
+    raw_assets: &three_d_asset::io::RawAssets,
+    path: &Path,
+) -> Result<(gltf::Document, Vec<Vec<u8>>), RendererError> {
+    let gltf::Gltf { document, mut blob } =
+        gltf::Gltf::from_slice(raw_assets.get(path).map_err(error)?).map_err(error)?;
+    let base_path = path.parent().unwrap_or(Path::new(""));
+    let mut buffers = Vec::new();
+    for buffer in document.buffers() {
+        buffers.push(match buffer.source() {
+            gltf::buffer::Source::Uri(uri) if uri.starts_with("data:") => {
+                raw_assets.get(uri).map_err(error)?.to_vec()
+            }
+            gltf::buffer::Source::Uri(uri) => {
+                raw_assets.get(base_path.join(uri)).map_err(error)?.to_vec()
+            }
+            gltf::buffer::Source::Bin => blob
+                .take()
+                .ok_or_else(|| error("the binary buffer is missing"))?,
+        });
+    }
+    Ok((document, buffers))
+}
+
+///
+/// Reads the morph targets of the given primitive and the initial weights of the morph targets, which are specified by the node or otherwise the mesh.
+///
+pub(super) fn morph_targets(
+    node: &gltf::Node,
+    primitive: &gltf::Primitive,
+    buffers: &[Vec<u8>],
+) -> CpuMorphTargets {
+    let reader = primitive.reader(|buffer| Some(buffers[buffer.index()].as_slice()));
+    let vertex_count = reader.read_positions().map_or(0, |p| p.len());
+    let targets = reader
+        .read_morph_targets()
+        .enumerate()
+        .map(|(i, (positions, normals, tangents))| MorphTarget {
+            name: format!("index {}", i),
+            positions: positions
+                .map(|values| values.map(|p| p.into()).collect())
+                .unwrap_or(vec![vec3(0.0, 0.0, 0.0); vertex_count]),
+            normals: normals.map(|values| values.map(|n| n.into()).collect()),
+            tangents: tangents.map(|values| values.map(|t| t.into()).collect()),
+        })
+        .collect::<Vec<_>>();
+    let weights = node
+        .weights()
+        .or(node.mesh().and_then(|mesh| mesh.weights()))
+        .map(|weights| weights.to_vec())
+        .unwrap_or_default();
+    CpuMorphTargets {
+        weights: (0..targets.len())
+            .map(|i| weights.get(i).copied().unwrap_or(0.0))
+            .collect(),
+        targets,
+    }
+}
+
+impl CpuMorphTargets {
+    ///
+    /// Deserializes the morph targets in the glTF file at the given path, which must be loaded together with its dependencies into the raw assets, for example using `three_d_asset::io::load`.
+    /// Returns the morph targets of each geometry in the same order as the geometries of a [CpuModel] deserialized from the same file, see [Model::set_morph_targets].
+    /// Call this before deserializing the [CpuModel], since that removes the glTF file from the raw assets.
+    ///
+    pub fn deserialize_gltf(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<Self>, RendererError> {
+        let (document, buffers) = parse(raw_assets, path.as_ref())?;
+        let mut morph_targets = Vec::new();
+        if let Some(scene) = document.scenes().next() {
+            for node in scene.nodes() {
+                visit(&node, &mut |node, primitive| {
+                    morph_targets.push(self::morph_targets(node, primitive, &buffers))
+                });
+            }
+        }
+        Ok(morph_targets)
+    }
+}
+
+impl Mesh {
+    ///
+    /// Deserializes the second set of uv coordinates (`TEXCOORD_1`) of each geometry in the glTF file at the given path, which must be loaded together with its dependencies into the raw assets,
+    /// for example using `three_d_asset::io::load`. The second set is not part of a [CpuMesh], but is for example used by the occlusion texture or a lightmap, see [Texture2DRef::uv_channel].
+    /// Returns the uv coordinates in the same order as the geometries of a [CpuModel] deserialized from the same file, where a geometry without a second set has none, see [Model::set_uvs2].
+    /// Call this before deserializing the [CpuModel], since that removes the glTF file from the raw assets.
+    ///
+    pub fn deserialize_gltf_uvs2(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<Option<Vec<Vec2>>>, RendererError> {
+        let (document, buffers) = parse(raw_assets, path.as_ref())?;
+        let mut uvs = Vec::new();
+        if let Some(scene) = document.scenes().next() {
+            for node in scene.nodes() {
+                visit(&node, &mut |_, primitive| {
+                    let reader =
+                        primitive.reader(|buffer| Some(buffers[buffer.index()].as_slice()));
+                    uvs.push(
+                        reader
+                            .read_tex_coords(1)
+                            .map(|values| values.into_f32().map(|uv| uv.into()).collect()),
+                    )
+                });
+            }
+        }
+        Ok(uvs)
+    }
+}
+
+impl CpuMaterialExtensions {
+    ///
+    /// Deserializes the material extensions of each geometry in the glTF file at the given path, which must be loaded together with its dependencies into the raw assets, for example using `three_d_asset::io::load`.
+    /// Returns the material extensions in the same order as the geometries of a [CpuModel] deserialized from the same file, see [Model::set_material_extensions].
+    /// Call this before deserializing the [CpuModel], since that removes the glTF file from the raw assets.
+    ///
+    pub fn deserialize_gltf(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<Self>, RendererError> {
+        let (document, _) = parse(raw_assets, path.as_ref())?;
+        let mut extensions = Vec::new();
+        if let Some(scene) = document.scenes().next() {
+            for node in scene.nodes() {
+                visit(&node, &mut |_, primitive| {
+                    extensions.push(material_extensions(&primitive.material()))
+                });
+            }
+        }
+        Ok(extensions)
+    }
+}
+
+fn material_extensions(material: &gltf::Material) -> CpuMaterialExtensions {
+    let factor = |extension: &str, name: &str| {
+        material
+            .extension_value(extension)
+            .and_then(|value| value.get(name))
+    };
+    let float = |extension: &str, name: &str, default: f32| {
+        factor(extension, name)
+            .and_then(|value| value.as_f64())
+            .map_or(default, |value| value as f32)
+    };
+    let color = |extension: &str, name: &str, default: Srgba| {
+        factor(extension, name)
+            .and_then(|value| value.as_array())
+            .and_then(|values| {
+                values
+                    .iter()
+                    .map(|value| value.as_f64().map(|value| value as f32))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .filter(|values| values.len() == 3)
+            .map_or(default, |values| [values[0], values[1], values[2]].into())
+    };
+    let pbr = material.pbr_metallic_roughness();
+    let no_texture = (Mat3::identity(), 0);
+    let (albedo_texture_transformation, albedo_texture_uv_channel) =
+        pbr.base_color_texture().map_or(no_texture, |info| {
+            texture_transformation(
+                info.tex_coord(),
+                info.extension_value("KHR_texture_transform"),
+            )
+        });
+    let (metallic_roughness_texture_transformation, metallic_roughness_texture_uv_channel) =
+        pbr.metallic_roughness_texture().map_or(no_texture, |info| {
+            texture_transformation(
+                info.tex_coord(),
+                info.extension_value("KHR_texture_transform"),
+            )
+        });
+    let (occlusion_texture_transformation, occlusion_texture_uv_channel) =
+        material.occlusion_texture().map_or(no_texture, |info| {
+            texture_transformation(
+                info.tex_coord(),
+                info.extension_value("KHR_texture_transform"),
+            )
+        });
+    let (normal_texture_transformation, normal_texture_uv_channel) =
+        material.normal_texture().map_or(no_texture, |info| {
+            texture_transformation(
+                info.tex_coord(),
+                info.extension_value("KHR_texture_transform"),
+            )
+        });
+    let (emissive_texture_transformation, emissive_texture_uv_channel) =
+        material.emissive_texture().map_or(no_texture, |info| {
+            texture_transformation(
+                info.tex_coord(),
+                info.extension_value("KHR_texture_transform"),
+            )
+        });
+    let default = CpuMaterialExtensions::default();
+    CpuMaterialExtensions {
+        clearcoat: float(
+            "KHR_materials_clearcoat",
+            "clearcoatFactor",
+            default.clearcoat,
+        ),
+        clearcoat_roughness: float(
+            "KHR_materials_clearcoat",
+            "clearcoatRoughnessFactor",
+            default.clearcoat_roughness,
+        ),
+        sheen_color: color(
+            "KHR_materials_sheen",
+            "sheenColorFactor",
+            default.sheen_color,
+        ),
+        sheen_roughness: float(
+            "KHR_materials_sheen",
+            "sheenRoughnessFactor",
+            default.sheen_roughness,
+        ),
+        specular: float("KHR_materials_specular", "specularFactor", default.specular),
+        specular_color: color(
+            "KHR_materials_specular",
+            "specularColorFactor",
+            default.specular_color,
+        ),
+        thickness: float("KHR_materials_volume", "thicknessFactor", default.thickness),
+        anisotropy_strength: float(
+            "KHR_materials_anisotropy",
+            "anisotropyStrength",
+            default.anisotropy_strength,
+        ),
+        anisotropy_rotation: float(
+            "KHR_materials_anisotropy",
+            "anisotropyRotation",
+            default.anisotropy_rotation,
+        ),
+        alpha_cutout: match material.alpha_mode() {
+            gltf::material::AlphaMode::Mask => Some(material.alpha_cutoff().unwrap_or(0.5)),
+            _ => None,
+        },
+        double_sided: material.double_sided(),
+        albedo_texture_transformation,
+        metallic_roughness_texture_transformation,
+        occlusion_texture_transformation,
+        normal_texture_transformation,
+        emissive_texture_transformation,
+        albedo_texture_uv_channel,
+        metallic_roughness_texture_uv_channel,
+        occlusion_texture_uv_channel,
+        normal_texture_uv_channel,
+        emissive_texture_uv_channel,
+    }
+}
+
+// The uv transformation given by the offset, rotation and scale of the KHR_texture_transform extension of a texture
+// together with the uv channel of the texture, which can be overridden by the extension
+fn texture_transformation(tex_coord: u32, extension: Option<&gltf::json::Value>) -> (Mat3, u32) {
+    let pair = |name: &str, default: Vec2| {
+        extension
+            .and_then(|value| value.get(name))
+            .and_then(|value| value.as_array())
+            .filter(|values| values.len() == 2)
+            .and_then(|values| Some(vec2(values[0].as_f64()?, values[1].as_f64()?)))
+            .map_or(default, |values| values.cast().unwrap())
+    };
+    let transformation = Texture2DRef::uv_transformation(
+        pair("offset", vec2(0.0, 0.0)),
+        extension
+            .and_then(|value| value.get("rotation"))
+            .and_then(|value| value.as_f64())
+            .map_or(0.0, |value| value as f32),
+        pair("scale", vec2(1.0, 1.0)),
+    );
+    let uv_channel = extension
+        .and_then(|value| value.get("texCoord"))
+        .and_then(|value| value.as_u64())
+        .map_or(tex_coord, |value| value as u32);
+    (transformation, uv_channel)
+}
+
+// Visits the primitives with positions in the same order as when converting a glTF file to a CpuModel
+fn visit<'a>(
+    node: &gltf::Node<'a>,
+    callback: &mut impl FnMut(&gltf::Node<'a>, &gltf::Primitive<'a>),
+) {
+    if Mat4::from(node.transform().matrix()).determinant() == 0.0 {
+        return;
+    }
+    if let Some(mesh) = node.mesh() {
+        for primitive in mesh.primitives() {
+            if primitive.get(&gltf::Semantic::Positions).is_some() {
+                callback(node, &primitive);
+            }
+        }
+    }
+    for child in node.children() {
+        visit(&child, callback);
+    }
+}
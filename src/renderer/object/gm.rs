@@ -69,4 +69,9 @@ impl<G: Geometry, M: Material> Object for Gm<G, M> {
     fn material_type(&self) -> MaterialType {
         self.material.material_type()
     }
+
+    fn program_id(&self) -> Option<u64> {
+        let geometry_id = self.geometry.id(self.material.fragment_attributes());
+        Some((geometry_id as u64) << 32 | self.material.id() as u64)
+    }
 }
@@ -0,0 +1,198 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// An infinite reference grid in the xz-plane, ie. the ground plane, with minor and major lines, colored x and z axes and lines fading out with the distance to the camera.
+///
+/// The lines are computed in the fragment shader from the position on the plane, so they are antialiased and have the same width in pixels no matter how far they are from the camera,
+/// and lines closer than a few pixels are faded out to avoid moiré patterns.
+/// The grid is rendered after the opaque objects and does not write depth, so it does not hide transparent objects below the grid.
+///
+pub struct Grid {
+    context: Context,
+    vertex_buffer: VertexBuffer,
+    /// The height of the grid, ie. the y-coordinate of the plane.
+    pub height: f32,
+    /// The distance between the minor lines in world space.
+    pub cell_size: f32,
+    /// The number of cells between the major lines.
+    pub major_line_every: u32,
+    /// The width of the lines in physical pixels.
+    pub line_width: f32,
+    /// The color of the minor lines.
+    pub minor_color: Srgba,
+    /// The color of the major lines.
+    pub major_color: Srgba,
+    /// The color of the line along the x-axis, ie. where z is zero.
+    pub x_axis_color: Srgba,
+    /// The color of the line along the z-axis, ie. where x is zero.
+    pub z_axis_color: Srgba,
+    /// The distance from the camera where the grid has faded out completely.
+    /// The grid starts to fade out at half this distance.
+    pub fade_distance: f32,
+}
+
+impl Grid {
+    ///
+    /// Creates a new grid with one unit between the minor lines and major lines every ten units.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            vertex_buffer: VertexBuffer::new_with_data(
+                context,
+                &[
+                    vec2(-1.0, -1.0),
+                    vec2(1.0, -1.0),
+                    vec2(1.0, 1.0),
+                    vec2(1.0, 1.0),
+                    vec2(-1.0, 1.0),
+                    vec2(-1.0, -1.0),
+                ],
+            ),
+            height: 0.0,
+            cell_size: 1.0,
+            major_line_every: 10,
+            line_width: 1.0,
+            minor_color: Srgba::new(128, 128, 128, 100),
+            major_color: Srgba::new(160, 160, 160, 200),
+            x_axis_color: Srgba::new(220, 60, 60, 255),
+            z_axis_color: Srgba::new(60, 90, 220, 255),
+            fade_distance: 100.0,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Grid {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Grid {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        _attributes: FragmentAttributes,
+    ) {
+        // The grid is a square below the camera which is large enough to contain the visible part of the grid
+        let position = camera.position();
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("center", vec3(position.x, self.height, position.z));
+        program.use_uniform("extent", self.fade_distance.max(0.0));
+        program.use_vertex_attribute("position", &self.vertex_buffer);
+        program.draw_arrays(render_states, camera.viewport(), 6);
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/grid.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b1110u16
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::INFINITE
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl Object for Grid {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            GridMaterial { grid: self },
+            lights,
+        )
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct GridMaterial<'a> {
+    grid: &'a Grid,
+}
+
+impl Material for GridMaterial<'_> {
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1u32 << 13 | 0b11u32
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/grid.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("cellSize", self.grid.cell_size.max(f32::EPSILON));
+        program.use_uniform("majorLineEvery", self.grid.major_line_every.max(1) as f32);
+        program.use_uniform("halfWidth", 0.5 * self.grid.line_width);
+        program.use_uniform("fadeDistance", self.grid.fade_distance.max(f32::EPSILON));
+        program.use_uniform("minorColor", self.grid.minor_color.to_linear_srgb());
+        program.use_uniform("majorColor", self.grid.major_color.to_linear_srgb());
+        program.use_uniform("xAxisColor", self.grid.x_axis_color.to_linear_srgb());
+        program.use_uniform("zAxisColor", self.grid.z_axis_color.to_linear_srgb());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
@@ -212,8 +212,8 @@ impl ImpostersMaterial {
 }
 
 impl Material for ImpostersMaterial {
-    fn id(&self) -> u16 {
-        0b1u16 << 15 | 0b1101u16
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1101u32
     }
 
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
@@ -234,7 +234,7 @@ impl Material for ImpostersMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
+        camera.use_tone_mapping_uniforms(program);
         camera.color_mapping.use_uniforms(program);
         program.use_uniform("no_views", NO_VIEW_ANGLES as i32);
         program.use_uniform("view", camera.view());
@@ -0,0 +1,131 @@
+use crate::renderer::*;
+
+///
+/// Similar to [SkinnedModel], except it is possible to render many instances of the same model efficiently, for example a crowd of characters.
+/// The animations are baked into a texture, see [BakedAnimations], and each instance plays one of the animations with its own time offset and speed, see [SkinnedInstances].
+/// Therefore, the animations cannot be blended and the morph targets of the model are ignored.
+///
+pub struct InstancedSkinnedModel<M: Material> {
+    parts: Vec<Gm<InstancedSkinnedMesh, M>>,
+    animation_names: Vec<Option<String>>,
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default> InstancedSkinnedModel<M> {
+    ///
+    /// Constructs an [InstancedSkinnedModel] from a [CpuSkinnedModel] where the animations of the model are sampled with the given number of frames per second.
+    /// The model is rendered in as many instances as there are transformations in the given [SkinnedInstances] and the animations of the instances refer to the animations of the model.
+    ///
+    pub fn new(
+        context: &Context,
+        instances: &SkinnedInstances,
+        cpu_model: &CpuSkinnedModel,
+        frames_per_second: f32,
+    ) -> Result<Self, RendererError> {
+        let materials = cpu_model
+            .materials
+            .iter()
+            .map(|m| M::from_cpu_material(context, m))
+            .collect::<Vec<_>>();
+        let animations = BakedAnimations::new(
+            context,
+            &cpu_model.skeleton,
+            &cpu_model.animations,
+            frames_per_second,
+        );
+        let mut parts = Vec::new();
+        for geometry in cpu_model.geometries.iter() {
+            let material = if let Some(material_index) = geometry.material_index {
+                materials
+                    .get(material_index)
+                    .ok_or_else(|| {
+                        RendererError::MissingMaterial(
+                            material_index.to_string(),
+                            geometry.name.clone(),
+                        )
+                    })?
+                    .clone()
+            } else {
+                M::default()
+            };
+            parts.push(Gm::new(
+                InstancedSkinnedMesh::new(
+                    context,
+                    instances,
+                    &geometry.mesh,
+                    &geometry.joint_indices,
+                    &geometry.joint_weights,
+                    &animations,
+                ),
+                material,
+            ));
+        }
+        Ok(Self {
+            parts,
+            animation_names: cpu_model
+                .animations
+                .iter()
+                .map(|a| a.name.clone())
+                .collect(),
+        })
+    }
+}
+
+impl<M: Material> InstancedSkinnedModel<M> {
+    ///
+    /// Returns the names of the animations, where the index of an animation is used in [SkinnedInstances::animations].
+    ///
+    pub fn animations(&self) -> &[Option<String>] {
+        &self.animation_names
+    }
+
+    ///
+    /// Returns the index of the animation with the given name.
+    ///
+    pub fn animation_index(&self, name: &str) -> Option<usize> {
+        self.animation_names
+            .iter()
+            .position(|n| n.as_deref() == Some(name))
+    }
+
+    ///
+    /// Update the instances.
+    ///
+    pub fn set_instances(&mut self, instances: &SkinnedInstances) {
+        for part in self.parts.iter_mut() {
+            part.geometry.set_instances(instances);
+        }
+    }
+
+    ///
+    /// For updating the animation. The time parameter should be some continious time, for example the time since start.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        self.parts.iter_mut().for_each(|m| m.animate(time));
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a InstancedSkinnedModel<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts
+            .iter()
+            .map(|m| m as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<M: Material> std::ops::Deref for InstancedSkinnedModel<M> {
+    type Target = Vec<Gm<InstancedSkinnedMesh, M>>;
+    fn deref(&self) -> &Self::Target {
+        &self.parts
+    }
+}
+
+impl<M: Material> std::ops::DerefMut for InstancedSkinnedModel<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.parts
+    }
+}
@@ -0,0 +1,283 @@
+use crate::renderer::*;
+
+///
+/// Defines how the level of detail is selected in a [LodGroup].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LodSelection {
+    /// The level is selected by the distance from the camera to the center of the bounding box of the group,
+    /// where the threshold of a level is the largest distance at which the level is used.
+    Distance,
+    /// The level is selected by the height of the bounding sphere of the group on the screen relative to the height of the viewport,
+    /// where the threshold of a level is the smallest relative height at which the level is used.
+    ScreenSize,
+}
+
+///
+/// A level of detail in a [LodGroup].
+///
+pub struct LodLevel<G: Geometry, M: Material> {
+    /// The object rendered at this level of detail.
+    pub object: Gm<G, M>,
+    /// The distance or relative screen size, depending on the [LodSelection], at which the next level is used instead of this level.
+    /// For the last level, this defines when the group is no longer rendered, for example use [f32::INFINITY] or zero to always render the last level.
+    pub threshold: f32,
+}
+
+///
+/// A group of objects representing the same object at different levels of detail, for example a detailed tree mesh used close to the camera and a simplified mesh used far away.
+/// The level is selected each time the group is rendered, so each camera, including the cameras used for rendering shadows, selects the level suitable for that camera.
+///
+/// Two levels are cross-faded near the threshold between them using complementary dither patterns, which avoids that the switch between the levels pops.
+///
+pub struct LodGroup<G: Geometry, M: Material> {
+    levels: Vec<LodLevel<G, M>>,
+    /// How the level of detail is selected.
+    pub selection: LodSelection,
+    /// The width of the transition between two levels relative to the threshold between the levels, for example 0.1 means that the levels are cross-faded over 10% of the threshold.
+    /// Zero disables the cross-fading.
+    pub fade_width: f32,
+}
+
+impl<G: Geometry, M: Material> LodGroup<G, M> {
+    ///
+    /// Creates a new group with the given levels ordered from the most detailed to the least detailed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no levels.
+    ///
+    pub fn new(levels: Vec<LodLevel<G, M>>, selection: LodSelection) -> Self {
+        assert!(
+            !levels.is_empty(),
+            "A LodGroup must have at least one level"
+        );
+        Self {
+            levels,
+            selection,
+            fade_width: 0.1,
+        }
+    }
+
+    ///
+    /// Returns the levels ordered from the most detailed to the least detailed.
+    ///
+    pub fn levels(&self) -> &[LodLevel<G, M>] {
+        &self.levels
+    }
+
+    ///
+    /// Returns the levels ordered from the most detailed to the least detailed.
+    ///
+    pub fn levels_mut(&mut self) -> &mut [LodLevel<G, M>] {
+        &mut self.levels
+    }
+
+    ///
+    /// Returns the index of the level used when rendering with the given camera, ignoring the cross-fading, or `None` if the group is not rendered.
+    ///
+    pub fn level(&self, camera: &Camera) -> Option<usize> {
+        self.select(camera)
+            .into_iter()
+            .max_by(|a, b| (a.2 - a.1).total_cmp(&(b.2 - b.1)))
+            .map(|(level, _, _)| level)
+    }
+
+    // Returns the levels to render together with the range of dither thresholds where each level is rendered
+    fn select(&self, camera: &Camera) -> Vec<(usize, f32, f32)> {
+        let aabb = self.aabb();
+        if aabb.is_empty() {
+            return Vec::new();
+        }
+        let distance = camera.position().distance(aabb.center());
+        // The levels are selected by comparing a value, which increases as less detail is needed, to the thresholds
+        let (value, sign) = match self.selection {
+            LodSelection::Distance => (distance, 1.0),
+            LodSelection::ScreenSize => {
+                let radius = 0.5 * aabb.size().magnitude();
+                let screen_size = match camera.projection_type() {
+                    three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                        if distance <= radius {
+                            f32::INFINITY
+                        } else {
+                            radius / (distance * (*field_of_view_y * 0.5).tan())
+                        }
+                    }
+                    three_d_asset::ProjectionType::Orthographic { height } => 2.0 * radius / height,
+                };
+                (-screen_size, -1.0)
+            }
+        };
+        for (i, level) in self.levels.iter().enumerate() {
+            let threshold = sign * level.threshold;
+            if value <= threshold {
+                return vec![(i, 0.0, 1.0)];
+            }
+            let fade = self.fade_width * level.threshold.abs();
+            if value < threshold + fade {
+                let t = 1.0 - (value - threshold) / fade;
+                let mut levels = vec![(i, 0.0, t)];
+                if i + 1 < self.levels.len() {
+                    levels.push((i + 1, t, 1.0));
+                }
+                return levels;
+            }
+        }
+        Vec::new()
+    }
+
+    fn render_levels(
+        &self,
+        material: Option<&dyn Material>,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        for (i, start, end) in self.select(camera) {
+            let object = &self.levels[i].object;
+            let material = material.unwrap_or(&object.material);
+            if start <= 0.0 && end >= 1.0 {
+                object.render_with_material(material, camera, lights);
+            } else {
+                object.render_with_material(
+                    &LodDitherMaterial {
+                        material,
+                        range: vec2(start, end),
+                    },
+                    camera,
+                    lights,
+                );
+            }
+        }
+    }
+}
+
+impl<'a, G: Geometry, M: Material> IntoIterator for &'a LodGroup<G, M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl<G: Geometry, M: Material> Geometry for LodGroup<G, M> {
+    // The most detailed level is drawn when the group is used directly in a render function
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        self.levels[0]
+            .object
+            .draw(camera, program, render_states, attributes)
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        self.levels[0]
+            .object
+            .vertex_shader_source(required_attributes)
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        self.levels[0].object.id(required_attributes)
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        self.render_levels(Some(material), camera, lights);
+    }
+
+    fn render_with_effect(
+        &self,
+        effect: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        // Effects are not cross-faded, instead the level covering most of the pixels is used
+        if let Some(level) = self.level(camera) {
+            self.levels[level].object.render_with_effect(
+                effect,
+                camera,
+                lights,
+                color_texture,
+                depth_texture,
+            );
+        }
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for level in self.levels.iter() {
+            aabb.expand_with_aabb(&level.object.aabb());
+        }
+        aabb
+    }
+
+    fn animate(&mut self, time: f32) {
+        for level in self.levels.iter_mut() {
+            level.object.animate(time);
+        }
+    }
+}
+
+impl<G: Geometry, M: Material> Object for LodGroup<G, M> {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        self.render_levels(None, camera, lights);
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.levels[0].object.material.material_type()
+    }
+}
+
+///
+/// Only renders the pixels where the threshold of the dither pattern is inside the given range, so two levels rendered with complementary ranges fill each pixel exactly once.
+///
+struct LodDitherMaterial<'a> {
+    material: &'a dyn Material,
+    range: Vec2,
+}
+
+impl Material for LodDitherMaterial<'_> {
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        format!(
+            "#define main material_main\n{}\n#undef main\n{}",
+            self.material.fragment_shader_source(lights),
+            include_str!("shaders/lod_dither.frag")
+        )
+    }
+
+    fn id(&self) -> u32 {
+        let id = self.material.id();
+        assert!(
+            id & (0b11u32 << 29) == 0,
+            "The id of a material cross-faded in a LodGroup must not use the bits 0b11u32 << 29"
+        );
+        id | 0b11u32 << 29
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.material.fragment_attributes()
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights);
+        program.use_uniform("lodDitherRange", self.range);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.material.render_states()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.material.material_type()
+    }
+}
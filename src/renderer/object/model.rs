@@ -7,6 +7,7 @@ pub use three_d_asset::Model as CpuModel;
 pub struct ModelPart<M: Material> {
     gm: Gm<Mesh, M>,
     animations: Vec<KeyFrameAnimation>,
+    morph_key_frames: Option<std::sync::Arc<KeyFrames>>,
 }
 
 impl<M: Material> ModelPart<M> {
@@ -30,6 +31,11 @@ impl<M: Material> ModelPart<M> {
             .find(|a| animation_name == a.name.as_deref())
             .cloned()
         {
+            self.morph_key_frames = animation
+                .key_frames
+                .iter()
+                .find(|(_, key_frames)| key_frames.weights.is_some())
+                .map(|(_, key_frames)| key_frames.clone());
             self.set_animation(move |time| animation.transformation(time));
         }
     }
@@ -53,7 +59,16 @@ impl<M: Material> Geometry for ModelPart<M> {
     impl_geometry_body!(deref);
 
     fn animate(&mut self, time: f32) {
-        self.gm.animate(time)
+        self.gm.animate(time);
+        if let Some(weights) = self
+            .morph_key_frames
+            .as_ref()
+            .and_then(|key_frames| key_frames.weights(time))
+        {
+            if weights.len() == self.gm.geometry.morph_weights().len() {
+                self.gm.geometry.set_morph_weights(&weights);
+            }
+        }
     }
 }
 
@@ -122,6 +137,7 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
                 gms.push(ModelPart {
                     gm,
                     animations: primitive.animations.clone(),
+                    morph_key_frames: None,
                 });
             }
         }
@@ -152,12 +168,82 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
         }
     }
 
+    ///
+    /// Sets the morph targets and the initial morph target weights of each part of the model, given in the same order as the parts, see [Mesh::set_morph_targets].
+    /// The morph targets of a glTF file can be loaded using `CpuMorphTargets::deserialize_gltf` when the `gltf` feature is enabled.
+    /// If the chosen animation contains morph target weights, the weights are animated when calling [Self::animate].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the morph targets are not given for each part or if they do not match the vertices of the part.
+    pub fn set_morph_targets(&mut self, morph_targets: &[CpuMorphTargets]) {
+        assert_eq!(
+            morph_targets.len(),
+            self.0.len(),
+            "There must be morph targets for each part of the model"
+        );
+        for (part, morph_targets) in self.0.iter_mut().zip(morph_targets) {
+            part.geometry.set_morph_targets(&morph_targets.targets);
+            part.geometry.set_morph_weights(&morph_targets.weights);
+        }
+    }
+
+    ///
+    /// Sets the second set of uv coordinates of each part of the model, given in the same order as the parts, see [Mesh::update_uvs2].
+    /// The second set of uv coordinates of a glTF file can be loaded using `Mesh::deserialize_gltf_uvs2` when the `gltf` feature is enabled.
+    /// The parts without a second set of uv coordinates are left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the uv coordinates are not given for each part or if they do not match the vertices of the part.
+    pub fn set_uvs2(&mut self, uvs2: &[Option<Vec<Vec2>>]) {
+        assert_eq!(
+            uvs2.len(),
+            self.0.len(),
+            "There must be uv coordinates for each part of the model"
+        );
+        for (part, uvs) in self.0.iter_mut().zip(uvs2) {
+            if let Some(uvs) = uvs {
+                part.geometry.update_uvs2(uvs);
+            }
+        }
+    }
+
     ///
     /// For updating the animation. The time parameter should be some continious time, for example the time since start.
     ///
     pub fn animate(&mut self, time: f32) {
         self.iter_mut().for_each(|m| m.animate(time));
     }
+
+    ///
+    /// Stores the current transformation of each part of the model as the transformation in the previous frame, see [Mesh::update_previous_transformation].
+    /// Call this after rendering each frame to use per object motion blur.
+    ///
+    pub fn update_previous_transformations(&mut self) {
+        self.iter_mut()
+            .for_each(|m| m.geometry.update_previous_transformation());
+    }
+}
+
+impl Model<PhysicalMaterial> {
+    ///
+    /// Sets the material properties which are not part of a [CpuMaterial] of each part of the model, given in the same order as the parts, see [PhysicalMaterial::set_extensions].
+    /// The material extensions of a glTF file can be loaded using `CpuMaterialExtensions::deserialize_gltf` when the `gltf` feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the material extensions are not given for each part of the model.
+    pub fn set_material_extensions(&mut self, extensions: &[CpuMaterialExtensions]) {
+        assert_eq!(
+            extensions.len(),
+            self.0.len(),
+            "There must be material extensions for each part of the model"
+        );
+        for (part, extensions) in self.0.iter_mut().zip(extensions) {
+            part.material.set_extensions(extensions);
+        }
+    }
 }
 
 impl<M: Material> std::ops::Deref for Model<M> {
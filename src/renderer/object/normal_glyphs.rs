@@ -0,0 +1,141 @@
+use crate::renderer::*;
+
+///
+/// Which normals are shown by [NormalGlyphs].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NormalSource {
+    /// The normal of each vertex, starting at the vertex. The normals are computed if the mesh does not have any.
+    #[default]
+    Vertex,
+    /// The normal of each triangle given by the winding order of the vertices, starting at the center of the triangle.
+    Face,
+}
+
+///
+/// Lines showing the direction of the normals of a mesh, for example to find triangles with flipped normals in imported geometry.
+/// Face normals pointing into the mesh indicate triangles with the wrong winding order, while vertex normals pointing in another direction than the face normals indicate wrong normals.
+///
+/// The lines are a [Polyline] rendered with a [ColorMaterial], which can be changed using [Deref](std::ops::Deref).
+/// See also [Wireframe].
+///
+pub struct NormalGlyphs {
+    lines: Gm<Polyline, ColorMaterial>,
+}
+
+impl NormalGlyphs {
+    ///
+    /// Creates lines of the given length in world space showing the normals of the given mesh.
+    /// Vertex normals are blue and face normals are magenta.
+    /// Use the same transformation as the mesh, see [Polyline::set_transformation], to place the lines on the mesh.
+    ///
+    pub fn new(context: &Context, cpu_mesh: &CpuMesh, source: NormalSource, length: f32) -> Self {
+        Self::new_with_meshes(context, [cpu_mesh.clone()], source, length)
+    }
+
+    ///
+    /// Creates lines of the given length in world space showing the normals of all the meshes of the given model, where the transformation of each part of the model is applied.
+    ///
+    pub fn new_from_model(
+        context: &Context,
+        cpu_model: &CpuModel,
+        source: NormalSource,
+        length: f32,
+    ) -> Self {
+        Self::new_with_meshes(
+            context,
+            cpu_model
+                .geometries
+                .iter()
+                .filter_map(|primitive| match &primitive.geometry {
+                    CpuGeometry::Triangles(mesh) => {
+                        let mut mesh = mesh.clone();
+                        mesh.transform(&primitive.transformation).ok()?;
+                        Some(mesh)
+                    }
+                    _ => None,
+                }),
+            source,
+            length,
+        )
+    }
+
+    fn new_with_meshes(
+        context: &Context,
+        meshes: impl IntoIterator<Item = CpuMesh>,
+        source: NormalSource,
+        length: f32,
+    ) -> Self {
+        let mut segments = Vec::new();
+        for mut mesh in meshes {
+            let positions = mesh.positions.to_f32();
+            match source {
+                NormalSource::Vertex => {
+                    if mesh.normals.is_none() {
+                        mesh.compute_normals();
+                    }
+                    for (position, normal) in positions.iter().zip(mesh.normals.as_ref().unwrap()) {
+                        segments.push([*position, position + normal.normalize() * length]);
+                    }
+                }
+                NormalSource::Face => {
+                    mesh.for_each_triangle(|i0, i1, i2| {
+                        let (p0, p1, p2) = (positions[i0], positions[i1], positions[i2]);
+                        let normal = (p1 - p0).cross(p2 - p0);
+                        if normal.magnitude2() > 0.0 {
+                            let center = (p0 + p1 + p2) / 3.0;
+                            segments.push([center, center + normal.normalize() * length]);
+                        }
+                    });
+                }
+            }
+        }
+        Self {
+            lines: Gm::new(
+                Polyline::new_with_segments(context, &segments, 1.0),
+                ColorMaterial {
+                    color: match source {
+                        NormalSource::Vertex => Srgba::new(0, 128, 255, 255),
+                        NormalSource::Face => Srgba::new(255, 0, 255, 255),
+                    },
+                    ..Default::default()
+                },
+            ),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a NormalGlyphs {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+use std::ops::Deref;
+impl Deref for NormalGlyphs {
+    type Target = Gm<Polyline, ColorMaterial>;
+    fn deref(&self) -> &Self::Target {
+        &self.lines
+    }
+}
+
+impl std::ops::DerefMut for NormalGlyphs {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.lines
+    }
+}
+
+impl Geometry for NormalGlyphs {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.lines.animate(time)
+    }
+}
+
+impl Object for NormalGlyphs {
+    impl_object_body!(deref);
+}
@@ -0,0 +1,674 @@
+use super::foliage::Random;
+use crate::core::*;
+use crate::renderer::*;
+
+/// The number of samples of each curve of a [ParticleEmitter] which are sent to the shader.
+const CURVE_SAMPLES: usize = 16;
+
+///
+/// The shape of the volume or area of a [ParticleEmitter] where the particles are spawned, defined in the local space of the emitter.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleEmitterShape {
+    /// All particles are spawned at the origin.
+    Point,
+    /// The particles are spawned at random positions inside a sphere with the given radius centered at the origin.
+    Sphere(f32),
+    /// The particles are spawned at random positions inside a box centered at the origin with the given half size in each dimension.
+    Box(Vec3),
+    /// The particles are spawned at random positions on a disk with the given radius centered at the origin and orthogonal to [ParticleEmission::direction].
+    Disk(f32),
+}
+
+///
+/// Defines how the color of the particles of a [ParticleEmitter] is combined with the color already in the render target.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ParticleBlend {
+    /// The particles are blended on top of the render target using their alpha value, for example for smoke and dust.
+    /// The particles are not sorted, so overlapping particles might be blended in the wrong order.
+    #[default]
+    Alpha,
+    /// The color of the particles, multiplied by their alpha value, is added to the render target, for example for fire, sparks and magic effects.
+    /// The result does not depend on the order of the particles.
+    Additive,
+}
+
+///
+/// Defines how the particles of a [ParticleEmitter] are spawned.
+/// Each value given as a range is chosen randomly in between the minimum and maximum for each particle.
+///
+#[derive(Clone, Debug)]
+pub struct ParticleEmission {
+    /// The number of particles spawned per second.
+    pub rate: f32,
+    /// The minimum and maximum number of seconds each particle is alive.
+    pub lifetime: (f32, f32),
+    /// Where the particles are spawned.
+    pub shape: ParticleEmitterShape,
+    /// The main direction of the initial velocity of the particles in the local space of the emitter.
+    pub direction: Vec3,
+    /// The angle between the [Self::direction] and the initial velocity of the particles is in the range from zero to this angle.
+    pub spread: Radians,
+    /// The minimum and maximum initial speed of the particles in units per second.
+    pub speed: (f32, f32),
+    /// The minimum and maximum size of the particles, which is multiplied by [ParticleEmitter::size_over_life].
+    pub size: (f32, f32),
+    /// The minimum and maximum speed in radians per second with which the particles rotate around the view direction.
+    pub rotation_speed: (f32, f32),
+    /// The seed of the random values, so the same seed gives the same particles.
+    pub seed: u64,
+}
+
+impl Default for ParticleEmission {
+    fn default() -> Self {
+        Self {
+            rate: 50.0,
+            lifetime: (1.0, 2.0),
+            shape: ParticleEmitterShape::Point,
+            direction: vec3(0.0, 1.0, 0.0),
+            spread: degrees(15.0).into(),
+            speed: (1.0, 2.0),
+            size: (0.1, 0.2),
+            rotation_speed: (0.0, 0.0),
+            seed: 0,
+        }
+    }
+}
+
+///
+/// A particle emitter which continuously spawns camera facing particles, for example for smoke, fire and sparks.
+///
+/// The particles are simulated entirely on the GPU in the vertex shader: Each particle is given a random spawn position, velocity, size and rotation
+/// when the emitter is created, and its position at a given time is then evaluated in closed form from its age and the curves of the emitter.
+/// This works the same way on native and web and requires no data to be transferred to the GPU each frame, only a call to [ParticleEmitter::animate].
+/// A particle is respawned with a new random direction around [ParticleEmission::direction] each time it dies.
+///
+/// The particles are simulated in the local space of the emitter, so they follow the emitter when the transformation changes,
+/// while the [ParticleEmitter::acceleration] is applied in world space.
+///
+/// The emitter can be rendered as any other [Object], in which case the particles are depth tested against the content of the render target.
+/// Alternatively, [ParticleEmitter::render_soft] fades out the particles where they intersect the geometries in a depth texture, which hides the hard edges of the particles.
+///
+pub struct ParticleEmitter {
+    context: Context,
+    vertex_buffer: VertexBuffer,
+    timings: InstanceBuffer,
+    start_positions: InstanceBuffer,
+    velocities: InstanceBuffer,
+    sizes: InstanceBuffer,
+    particle_count: u32,
+    emission: ParticleEmission,
+    transformation: Mat4,
+    time: f32,
+    /// The acceleration applied to all particles in the world coordinate system, for example gravity or wind.
+    pub acceleration: Vec3,
+    /// The color of the particles as a function of their age, given as a list of keys where the first value is the age relative to the lifetime in the range `[0..1]`.
+    /// The color is linearly interpolated between the keys and the alpha value specifies the opacity of the particles.
+    pub color_over_life: Vec<(f32, Srgba)>,
+    /// The size of the particles, relative to their initial size, as a function of their age, given as a list of keys where the first value is the age relative to the lifetime in the range `[0..1]`.
+    pub size_over_life: Vec<(f32, f32)>,
+    /// The speed of the particles, relative to their initial speed, as a function of their age, given as a list of keys where the first value is the age relative to the lifetime in the range `[0..1]`.
+    pub speed_over_life: Vec<(f32, f32)>,
+    /// A texture which is multiplied with the color of the particles.
+    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    /// If no texture is specified, the particles are round with soft edges.
+    pub texture: Option<Texture2DRef>,
+    /// How the color of the particles is combined with the color already in the render target.
+    pub blend: ParticleBlend,
+    /// The distance over which the particles fade out in front of the geometries in the depth texture given to [ParticleEmitter::render_soft].
+    pub softness: f32,
+}
+
+impl ParticleEmitter {
+    ///
+    /// Creates a new particle emitter which spawns particles as specified by the given [ParticleEmission].
+    /// The emitter starts without any particles at time zero, see [ParticleEmitter::animate].
+    ///
+    pub fn new(context: &Context, emission: &ParticleEmission) -> Self {
+        let mut particle_emitter = Self {
+            context: context.clone(),
+            vertex_buffer: VertexBuffer::new_with_data(
+                context,
+                &[
+                    vec2(-0.5, -0.5),
+                    vec2(0.5, -0.5),
+                    vec2(0.5, 0.5),
+                    vec2(0.5, 0.5),
+                    vec2(-0.5, 0.5),
+                    vec2(-0.5, -0.5),
+                ],
+            ),
+            timings: InstanceBuffer::new(context),
+            start_positions: InstanceBuffer::new(context),
+            velocities: InstanceBuffer::new(context),
+            sizes: InstanceBuffer::new(context),
+            particle_count: 0,
+            emission: emission.clone(),
+            transformation: Mat4::identity(),
+            time: 0.0,
+            acceleration: vec3(0.0, 0.0, 0.0),
+            color_over_life: vec![
+                (0.0, Srgba::new(255, 255, 255, 0)),
+                (0.1, Srgba::WHITE),
+                (1.0, Srgba::new(255, 255, 255, 0)),
+            ],
+            size_over_life: vec![(0.0, 1.0)],
+            speed_over_life: vec![(0.0, 1.0)],
+            texture: None,
+            blend: ParticleBlend::default(),
+            softness: 0.5,
+        };
+        particle_emitter.set_emission(emission);
+        particle_emitter
+    }
+
+    ///
+    /// Set how the particles are spawned.
+    /// This respawns all particles, so it should not be called every frame.
+    ///
+    pub fn set_emission(&mut self, emission: &ParticleEmission) {
+        self.emission = emission.clone();
+        let rate = emission.rate.max(0.001);
+        let max_lifetime = emission.lifetime.0.max(emission.lifetime.1).max(0.001);
+        self.particle_count = (rate * max_lifetime).ceil().max(1.0) as u32;
+
+        let direction = if emission.direction.magnitude2() > 0.0 {
+            emission.direction.normalize()
+        } else {
+            vec3(0.0, 1.0, 0.0)
+        };
+        let tangent = if direction.x.abs() < 0.9 {
+            direction.cross(vec3(1.0, 0.0, 0.0)).normalize()
+        } else {
+            direction.cross(vec3(0.0, 1.0, 0.0)).normalize()
+        };
+        let bitangent = direction.cross(tangent);
+        let cos_spread = emission.spread.0.min(std::f32::consts::PI).cos();
+        let range = |random: &mut Random, range: (f32, f32)| {
+            range.0 + random.next_f32() * (range.1 - range.0)
+        };
+
+        let mut random = Random::new(emission.seed);
+        let mut timings = Vec::new();
+        let mut start_positions = Vec::new();
+        let mut velocities = Vec::new();
+        let mut sizes = Vec::new();
+        for i in 0..self.particle_count {
+            timings.push(vec4(
+                i as f32 / rate,
+                range(&mut random, emission.lifetime).max(0.001),
+                random.next_f32(),
+                0.0,
+            ));
+            start_positions.push(match emission.shape {
+                ParticleEmitterShape::Point => vec3(0.0, 0.0, 0.0),
+                ParticleEmitterShape::Sphere(radius) => loop {
+                    let p = vec3(
+                        2.0 * random.next_f32() - 1.0,
+                        2.0 * random.next_f32() - 1.0,
+                        2.0 * random.next_f32() - 1.0,
+                    );
+                    if p.magnitude2() <= 1.0 {
+                        break p * radius;
+                    }
+                },
+                ParticleEmitterShape::Box(half_size) => vec3(
+                    (2.0 * random.next_f32() - 1.0) * half_size.x,
+                    (2.0 * random.next_f32() - 1.0) * half_size.y,
+                    (2.0 * random.next_f32() - 1.0) * half_size.z,
+                ),
+                ParticleEmitterShape::Disk(radius) => {
+                    let r = radius * random.next_f32().sqrt();
+                    let angle = random.next_f32() * std::f32::consts::TAU;
+                    tangent * r * angle.cos() + bitangent * r * angle.sin()
+                }
+            });
+            // Uniformly distributed directions inside the cone around the main direction
+            let cos_theta = 1.0 - random.next_f32() * (1.0 - cos_spread);
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = random.next_f32() * std::f32::consts::TAU;
+            velocities.push(
+                (direction * cos_theta
+                    + tangent * sin_theta * phi.cos()
+                    + bitangent * sin_theta * phi.sin())
+                    * range(&mut random, emission.speed),
+            );
+            sizes.push(vec3(
+                range(&mut random, emission.size),
+                random.next_f32() * std::f32::consts::TAU,
+                range(&mut random, emission.rotation_speed),
+            ));
+        }
+        self.timings.fill(&timings);
+        self.start_positions.fill(&start_positions);
+        self.velocities.fill(&velocities);
+        self.sizes.fill(&sizes);
+    }
+
+    ///
+    /// Returns the [ParticleEmission] which specifies how the particles are spawned.
+    ///
+    pub fn emission(&self) -> &ParticleEmission {
+        &self.emission
+    }
+
+    ///
+    /// Returns the maximum number of particles which are alive at the same time.
+    ///
+    pub fn particle_count(&self) -> u32 {
+        self.particle_count
+    }
+
+    ///
+    /// Returns the local to world transformation of the emitter.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation of the emitter.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    ///
+    /// Renders the particles on top of the content of the render target and fades them out where they are closer than [ParticleEmitter::softness]
+    /// to the geometries in the depth texture. The particles are also hidden behind the geometries in the depth texture.
+    /// The depth texture must contain the depth of the scene rendered with the same camera and must not be attached to the render target.
+    ///
+    pub fn render_soft(&self, target: &RenderTarget, camera: &Camera, depth_texture: DepthTexture) {
+        target.render_with_effect(
+            &SoftParticlePass { emitter: self },
+            camera,
+            self,
+            &[],
+            None,
+            Some(depth_texture),
+        );
+    }
+
+    fn period(&self) -> f32 {
+        self.particle_count as f32 / self.emission.rate.max(0.001)
+    }
+
+    ///
+    /// Returns the distance travelled relative to the initial speed and the lifetime, which is the integral of the speed over life curve.
+    ///
+    fn distance_over_life(&self) -> Vec<f32> {
+        const STEPS_PER_SAMPLE: usize = 8;
+        let speed =
+            |t: f32| sample_curve_at(&self.speed_over_life, t, 1.0, |a, b, t| a + (b - a) * t);
+        let step = 1.0 / ((CURVE_SAMPLES - 1) * STEPS_PER_SAMPLE) as f32;
+        let mut distance = 0.0;
+        let mut samples = vec![0.0];
+        for i in 1..CURVE_SAMPLES {
+            for j in 0..STEPS_PER_SAMPLE {
+                let t = ((i - 1) * STEPS_PER_SAMPLE + j) as f32 * step;
+                distance += 0.5 * (speed(t) + speed(t + step)) * step;
+            }
+            samples.push(distance);
+        }
+        samples
+    }
+
+    fn fragment_shader_source(&self, depth_texture: Option<DepthTexture>) -> String {
+        let mut output = String::new();
+        if self.texture.is_some() {
+            output.push_str("#define USE_TEXTURE\n");
+        }
+        if let Some(depth_texture) = depth_texture {
+            output.push_str("#define USE_SOFT_PARTICLES\n");
+            output.push_str(include_str!("../../core/shared.frag"));
+            output.push_str(&depth_texture.fragment_shader_source());
+        }
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/particle_emitter.frag"));
+        output
+    }
+
+    fn use_fragment_uniforms(&self, program: &Program, camera: &Camera) {
+        camera.use_tone_mapping_uniforms(program);
+        camera.color_mapping.use_uniforms(program);
+        if let Some(ref texture) = self.texture {
+            program.use_uniform("textureTransformation", texture.transformation);
+            program.use_texture("particleTexture", texture);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: match self.blend {
+                ParticleBlend::Alpha => Blend::TRANSPARENCY,
+                ParticleBlend::Additive => Blend::Enabled {
+                    source_rgb_multiplier: BlendMultiplierType::SrcAlpha,
+                    source_alpha_multiplier: BlendMultiplierType::Zero,
+                    destination_rgb_multiplier: BlendMultiplierType::One,
+                    destination_alpha_multiplier: BlendMultiplierType::One,
+                    rgb_equation: BlendEquationType::Add,
+                    alpha_equation: BlendEquationType::Add,
+                },
+            },
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a ParticleEmitter {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for ParticleEmitter {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        _attributes: FragmentAttributes,
+    ) {
+        if self.particle_count == 0 {
+            return;
+        }
+        let view = camera.view();
+        program.use_uniform("viewProjection", camera.projection() * view);
+        program.use_uniform("cameraRight", vec3(view.x.x, view.y.x, view.z.x));
+        program.use_uniform("cameraUp", vec3(view.x.y, view.y.y, view.z.y));
+        program.use_uniform("transformation", self.transformation);
+        program.use_uniform(
+            "emissionDirection",
+            if self.emission.direction.magnitude2() > 0.0 {
+                self.emission.direction.normalize()
+            } else {
+                vec3(0.0, 1.0, 0.0)
+            },
+        );
+        program.use_uniform("acceleration", self.acceleration);
+        program.use_uniform("time", self.time);
+        program.use_uniform("period", self.period());
+        if program.requires_uniform("colorOverLife") {
+            let keys = self
+                .color_over_life
+                .iter()
+                .map(|(t, c)| (*t, c.to_linear_srgb()))
+                .collect::<Vec<_>>();
+            program.use_uniform_array(
+                "colorOverLife",
+                &sample_curve(&keys, vec4(1.0, 1.0, 1.0, 1.0), |a, b, t| a + (b - a) * t),
+            );
+        }
+        program.use_uniform_array(
+            "sizeOverLife",
+            &sample_curve(&self.size_over_life, 1.0, |a, b, t| a + (b - a) * t),
+        );
+        program.use_uniform_array("distanceOverLife", &self.distance_over_life());
+        program.use_vertex_attribute("position", &self.vertex_buffer);
+        program.use_instance_attribute("instance_timing", &self.timings);
+        program.use_instance_attribute("instance_position", &self.start_positions);
+        program.use_instance_attribute("instance_velocity", &self.velocities);
+        program.use_instance_attribute("instance_size", &self.sizes);
+        program.draw_arrays_instanced(render_states, camera.viewport(), 6, self.particle_count);
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define CURVE_SAMPLES {}\n{}",
+            CURVE_SAMPLES,
+            include_str!("shaders/particle_emitter.vert")
+        )
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b111u16
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let emission = &self.emission;
+        let max_lifetime = emission.lifetime.0.max(emission.lifetime.1);
+        let max_speed = emission.speed.0.abs().max(emission.speed.1.abs());
+        let max_distance = self
+            .distance_over_life()
+            .iter()
+            .fold(0.0f32, |a, d| a.max(d.abs()));
+        let max_size = emission.size.0.abs().max(emission.size.1.abs())
+            * self
+                .size_over_life
+                .iter()
+                .fold(1.0f32, |a, (_, s)| a.max(s.abs()));
+        let shape_radius = match emission.shape {
+            ParticleEmitterShape::Point => 0.0,
+            ParticleEmitterShape::Sphere(radius) | ParticleEmitterShape::Disk(radius) => {
+                radius.abs()
+            }
+            ParticleEmitterShape::Box(half_size) => half_size.magnitude(),
+        };
+        let radius = shape_radius + max_speed * max_lifetime * max_distance;
+        let mut aabb = AxisAlignedBoundingBox::new_with_transformed_positions(
+            &[
+                vec3(-radius, -radius, -radius),
+                vec3(radius, radius, radius),
+            ],
+            &self.transformation,
+        );
+        let offset = 0.5 * self.acceleration * max_lifetime * max_lifetime;
+        let margin = vec3(max_size, max_size, max_size);
+        let (min, max) = (aabb.min(), aabb.max());
+        aabb.expand(&[
+            min - margin,
+            max + margin,
+            min + offset - margin,
+            max + offset + margin,
+        ]);
+        aabb
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn animate(&mut self, time: f32) {
+        self.time = time;
+    }
+}
+
+impl Object for ParticleEmitter {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            ParticleMaterial { emitter: self },
+            lights,
+        )
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct ParticleMaterial<'a> {
+    emitter: &'a ParticleEmitter,
+}
+
+impl Material for ParticleMaterial<'_> {
+    fn id(&self) -> u32 {
+        if self.emitter.texture.is_some() {
+            0b1u32 << 15 | 0b1u32 << 12 | 0b1u32
+        } else {
+            0b1u32 << 15 | 0b1u32 << 12
+        }
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        self.emitter.fragment_shader_source(None)
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            color: true,
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        self.emitter.use_fragment_uniforms(program, camera);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.emitter.render_states()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct SoftParticlePass<'a> {
+    emitter: &'a ParticleEmitter,
+}
+
+impl Effect for SoftParticlePass<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        self.emitter.fragment_shader_source(Some(
+            depth_texture.expect("Must supply a depth texture to render soft particles"),
+        ))
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | if self.emitter.texture.is_some() {
+                0b10111u16 << 7
+            } else {
+                0b10110u16 << 7
+            }
+            | depth_texture
+                .expect("Must supply a depth texture to render soft particles")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            color: true,
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        self.emitter.use_fragment_uniforms(program, camera);
+        depth_texture
+            .expect("Must supply a depth texture to render soft particles")
+            .use_uniforms(program);
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "viewport",
+            vec4(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ),
+        );
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("softness", self.emitter.softness.max(0.0001));
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            // The particles are hidden behind the geometries in the depth texture by the fragment shader
+            depth_test: DepthTest::Always,
+            ..self.emitter.render_states()
+        }
+    }
+}
+
+///
+/// Samples the curve given by the keys at evenly spaced positions in the range `[0..1]`.
+///
+fn sample_curve<T: Copy>(keys: &[(f32, T)], default: T, lerp: impl Fn(T, T, f32) -> T) -> Vec<T> {
+    (0..CURVE_SAMPLES)
+        .map(|i| sample_curve_at(keys, i as f32 / (CURVE_SAMPLES - 1) as f32, default, &lerp))
+        .collect()
+}
+
+///
+/// Returns the value of the curve given by the keys at the given position, linearly interpolated between the keys.
+///
+fn sample_curve_at<T: Copy>(
+    keys: &[(f32, T)],
+    t: f32,
+    default: T,
+    lerp: impl Fn(T, T, f32) -> T,
+) -> T {
+    let Some(first) = keys.first() else {
+        return default;
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    for window in keys.windows(2) {
+        let (a, b) = (window[0], window[1]);
+        if t <= b.0 {
+            let length = b.0 - a.0;
+            return if length > 0.0 {
+                lerp(a.1, b.1, (t - a.0) / length)
+            } else {
+                b.1
+            };
+        }
+    }
+    keys.last().unwrap().1
+}
@@ -0,0 +1,369 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The shape of each point of [Points].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PointShape {
+    /// A square facing the camera.
+    Square,
+    /// A disk facing the camera.
+    #[default]
+    Round,
+    /// A disk orthogonal to the normal of each point, also called a surfel, which gives a closed surface when the points are dense enough.
+    /// The size of the points is always in world space and the points are rendered as [PointShape::Round] if no normals are specified, see [Points::set_normals].
+    Surfel,
+}
+
+///
+/// The size of each point of [Points], which is multiplied by the size of each point if specified, see [Points::set_sizes].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PointSize {
+    /// The diameter of the points in physical pixels, so the points have the same size on the screen no matter how far they are from the camera.
+    Pixels(f32),
+    /// The diameter of the points in world space.
+    World(f32),
+}
+
+///
+/// A point cloud, ie. a large number of points in 3D each with an optional color, size and normal.
+///
+/// Each point is rendered as a small camera facing square or disk, or as a disk orthogonal to its normal, see [PointShape],
+/// which is much faster than rendering a mesh for each point and can handle millions of points.
+/// The points can be rendered as any other [Object] using their colors multiplied by [Points::color],
+/// or used as a [Geometry] together with a material, in which case the points are always square.
+/// Use the [EyeDomeLightingEffect] to improve the perception of depth when rendering point clouds without normals.
+///
+pub struct Points {
+    context: Context,
+    vertex_buffer: VertexBuffer,
+    positions: InstanceBuffer,
+    colors: Option<InstanceBuffer>,
+    sizes: Option<InstanceBuffer>,
+    normals: Option<InstanceBuffer>,
+    point_count: u32,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    /// The color of the points which is multiplied by the color of each point.
+    pub color: Srgba,
+    /// The size of the points.
+    pub size: PointSize,
+    /// The shape of the points.
+    pub shape: PointShape,
+}
+
+impl Points {
+    ///
+    /// Creates new points from the positions and colors of the given [PointCloud].
+    ///
+    pub fn new(context: &Context, point_cloud: &PointCloud) -> Self {
+        let mut points = Self {
+            context: context.clone(),
+            vertex_buffer: VertexBuffer::new_with_data(
+                context,
+                &[
+                    vec2(-0.5, -0.5),
+                    vec2(0.5, -0.5),
+                    vec2(0.5, 0.5),
+                    vec2(0.5, 0.5),
+                    vec2(-0.5, 0.5),
+                    vec2(-0.5, -0.5),
+                ],
+            ),
+            positions: InstanceBuffer::new(context),
+            colors: None,
+            sizes: None,
+            normals: None,
+            point_count: 0,
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            transformation: Mat4::identity(),
+            color: Srgba::WHITE,
+            size: PointSize::Pixels(4.0),
+            shape: PointShape::default(),
+        };
+        points.set_point_cloud(point_cloud);
+        points
+    }
+
+    ///
+    /// Set the positions and colors of the points from the given [PointCloud].
+    /// This removes the sizes and normals of the points if the number of points changes.
+    ///
+    pub fn set_point_cloud(&mut self, point_cloud: &PointCloud) {
+        let positions = point_cloud.positions.to_f32();
+        if positions.len() as u32 != self.point_count {
+            self.sizes = None;
+            self.normals = None;
+        }
+        self.point_count = positions.len() as u32;
+        self.aabb = AxisAlignedBoundingBox::new_with_positions(&positions);
+        self.positions.fill(&positions);
+        self.colors = point_cloud.colors.as_ref().map(|colors| {
+            InstanceBuffer::new_with_data(
+                &self.context,
+                &colors
+                    .iter()
+                    .map(|c| c.to_linear_srgb())
+                    .collect::<Vec<_>>(),
+            )
+        });
+    }
+
+    ///
+    /// Set the size of each point, which is multiplied by [Points::size], or remove the sizes so all points have the same size.
+    /// There must be a size for each point.
+    ///
+    pub fn set_sizes(&mut self, sizes: Option<&[f32]>) {
+        self.sizes = sizes.map(|sizes| {
+            assert_eq!(
+                sizes.len() as u32,
+                self.point_count,
+                "There must be a size for each point"
+            );
+            InstanceBuffer::new_with_data(&self.context, sizes)
+        });
+    }
+
+    ///
+    /// Set the normal of each point, which is used when the shape is [PointShape::Surfel] and when the material requires normals, or remove the normals.
+    /// There must be a normal for each point.
+    ///
+    pub fn set_normals(&mut self, normals: Option<&[Vec3]>) {
+        self.normals = normals.map(|normals| {
+            assert_eq!(
+                normals.len() as u32,
+                self.point_count,
+                "There must be a normal for each point"
+            );
+            InstanceBuffer::new_with_data(&self.context, normals)
+        });
+    }
+
+    ///
+    /// Returns the number of points.
+    ///
+    pub fn point_count(&self) -> u32 {
+        self.point_count
+    }
+
+    ///
+    /// Returns the local to world transformation applied to the points.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to the points.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    fn is_surfel(&self) -> bool {
+        self.shape == PointShape::Surfel && self.normals.is_some()
+    }
+}
+
+impl<'a> IntoIterator for &'a Points {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Points {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if attributes.tangents {
+            panic!("the material requires tangent attributes but points do not provide it")
+        }
+        if self.point_count == 0 {
+            return;
+        }
+        let view = camera.view();
+        let viewport = camera.viewport();
+        program.use_uniform("viewProjection", camera.projection() * view);
+        program.use_uniform("modelMatrix", self.transformation);
+        if attributes.normal || self.is_surfel() {
+            if let Some(inverse) = self.transformation.invert() {
+                program.use_uniform_if_required("normalMatrix", inverse.transpose());
+            } else {
+                // determinant is float zero
+                return;
+            }
+        }
+        program.use_uniform_if_required("cameraPosition", camera.lighting_position());
+        program.use_uniform_if_required("cameraRight", vec3(view.x.x, view.y.x, view.z.x));
+        program.use_uniform_if_required("cameraUp", vec3(view.x.y, view.y.y, view.z.y));
+        program.use_uniform_if_required(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        let (size, size_in_pixels) = match self.size {
+            PointSize::Pixels(size) => (size, 1),
+            PointSize::World(size) => (size, 0),
+        };
+        program.use_uniform("pointSize", size);
+        program.use_uniform_if_required("sizeInPixels", size_in_pixels);
+        program.use_vertex_attribute("position", &self.vertex_buffer);
+        program.use_instance_attribute("instance_position", &self.positions);
+        if program.requires_attribute("instance_color") {
+            program.use_instance_attribute("instance_color", self.colors.as_ref().unwrap());
+        }
+        if program.requires_attribute("instance_size") {
+            program.use_instance_attribute("instance_size", self.sizes.as_ref().unwrap());
+        }
+        if program.requires_attribute("instance_normal") {
+            program.use_instance_attribute("instance_normal", self.normals.as_ref().unwrap());
+        }
+        program.draw_arrays_instanced(render_states, viewport, 6, self.point_count);
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        let mut output = String::new();
+        if required_attributes.normal {
+            output.push_str("#define USE_NORMALS\n");
+        }
+        if required_attributes.color && self.colors.is_some() {
+            output.push_str("#define USE_INSTANCE_COLORS\n");
+        }
+        if self.sizes.is_some() {
+            output.push_str("#define USE_INSTANCE_SIZES\n");
+        }
+        if self.normals.is_some() && (required_attributes.normal || self.is_surfel()) {
+            output.push_str("#define USE_INSTANCE_NORMALS\n");
+        }
+        if self.is_surfel() {
+            output.push_str("#define USE_SURFELS\n");
+        }
+        output.push_str(include_str!("shaders/points.vert"));
+        output
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 11;
+        if required_attributes.normal {
+            id |= 0b1u16;
+        }
+        if required_attributes.color && self.colors.is_some() {
+            id |= 0b1u16 << 1;
+        }
+        if self.sizes.is_some() {
+            id |= 0b1u16 << 2;
+        }
+        if self.normals.is_some() && (required_attributes.normal || self.is_surfel()) {
+            id |= 0b1u16 << 3;
+        }
+        if self.is_surfel() {
+            id |= 0b1u16 << 4;
+        }
+        id
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        if !aabb.is_empty() {
+            aabb.transform(&self.transformation);
+        }
+        aabb
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl Object for Points {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            PointsMaterial { points: self },
+            lights,
+        )
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+struct PointsMaterial<'a> {
+    points: &'a Points,
+}
+
+impl Material for PointsMaterial<'_> {
+    fn id(&self) -> u32 {
+        if self.points.shape == PointShape::Square {
+            0b1u32 << 15 | 0b1u32 << 13
+        } else {
+            0b1u32 << 15 | 0b1u32 << 13 | 0b1u32
+        }
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut output = String::new();
+        if self.points.shape != PointShape::Square {
+            output.push_str("#define USE_ROUND_POINTS\n");
+        }
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/points.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("surfaceColor", self.points.color.to_linear_srgb());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
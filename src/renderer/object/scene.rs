@@ -0,0 +1,348 @@
+use crate::renderer::*;
+
+///
+/// Implemented by objects and geometries with a local to world transformation, which is needed to place them in a [Scene].
+///
+pub trait Transformable {
+    ///
+    /// Returns the local to world transformation.
+    ///
+    fn transformation(&self) -> Mat4;
+
+    ///
+    /// Set the local to world transformation.
+    ///
+    fn set_transformation(&mut self, transformation: Mat4);
+}
+
+macro_rules! impl_transformable {
+    ($($t:ty),*) => {
+        $(
+            impl Transformable for $t {
+                fn transformation(&self) -> Mat4 {
+                    <$t>::transformation(self)
+                }
+
+                fn set_transformation(&mut self, transformation: Mat4) {
+                    <$t>::set_transformation(self, transformation)
+                }
+            }
+        )*
+    };
+}
+
+impl_transformable!(
+    Mesh,
+    InstancedMesh,
+    SkinnedMesh,
+    InstancedSkinnedMesh,
+    ParticleSystem,
+    Polyline,
+    Sprites,
+    Points,
+    ParticleEmitter
+);
+
+impl<G: Geometry + Transformable, M: Material> Transformable for Gm<G, M> {
+    fn transformation(&self) -> Mat4 {
+        self.geometry.transformation()
+    }
+
+    fn set_transformation(&mut self, transformation: Mat4) {
+        self.geometry.set_transformation(transformation)
+    }
+}
+
+///
+/// An [Object] which can be added to a [Scene], that is, any object which is also [Transformable].
+///
+pub trait SceneObject: Object + Transformable {}
+
+impl<T: Object + Transformable> SceneObject for T {}
+
+///
+/// Refers to a node in a [Scene].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+struct Node {
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    transformation: Mat4,
+    world_transformation: Mat4,
+    dirty: bool,
+    object: Option<Box<dyn SceneObject>>,
+}
+
+///
+/// A scene graph, which is a tree of nodes where each node has a transformation relative to its parent and optionally an object.
+/// The world transformation of an object is the transformation of its node multiplied by the transformations of all the ancestors of the node,
+/// so for example moving a tank also moves the turret on the tank.
+///
+/// The world transformations are only recomputed for the nodes which have changed and only when calling [Scene::update],
+/// which must be done before rendering the scene, for example once per frame.
+/// The objects in the scene can then be rendered in one call, for example using [RenderTarget::render].
+///
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    roots: Vec<NodeId>,
+}
+
+impl Scene {
+    ///
+    /// Creates a new empty scene.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds a node without an object with the given transformation relative to the given parent or to the world if the parent is `None`.
+    /// This is useful for grouping objects.
+    ///
+    pub fn add_node(&mut self, parent: Option<NodeId>, transformation: Mat4) -> NodeId {
+        self.insert(parent, transformation, None)
+    }
+
+    ///
+    /// Adds a node with the given object and transformation relative to the given parent or to the world if the parent is `None`.
+    /// The transformation of the object is replaced by the world transformation of the node when calling [Scene::update].
+    ///
+    pub fn add(
+        &mut self,
+        parent: Option<NodeId>,
+        transformation: Mat4,
+        object: impl SceneObject + 'static,
+    ) -> NodeId {
+        self.insert(parent, transformation, Some(Box::new(object)))
+    }
+
+    fn insert(
+        &mut self,
+        parent: Option<NodeId>,
+        transformation: Mat4,
+        object: Option<Box<dyn SceneObject>>,
+    ) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Some(Node {
+            parent,
+            children: Vec::new(),
+            transformation,
+            world_transformation: Mat4::identity(),
+            dirty: true,
+            object,
+        }));
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+        id
+    }
+
+    ///
+    /// Removes the given node together with all of its descendants.
+    ///
+    pub fn remove(&mut self, node: NodeId) {
+        self.detach(node);
+        let mut stack = vec![node];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes[id.0].take() {
+                stack.extend(node.children);
+            }
+        }
+    }
+
+    ///
+    /// Returns whether the given node is in this scene, ie. it has not been removed.
+    ///
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.nodes.get(node.0).is_some_and(|n| n.is_some())
+    }
+
+    ///
+    /// Returns the nodes without a parent.
+    ///
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    ///
+    /// Returns the parent of the given node or `None` if the node is a root.
+    ///
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.node(node).parent
+    }
+
+    ///
+    /// Returns the children of the given node.
+    ///
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        &self.node(node).children
+    }
+
+    ///
+    /// Moves the given node, together with its descendants, to the given parent or to the root of the scene if the parent is `None`.
+    /// The transformation of the node is kept, so it becomes relative to the new parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new parent is the node itself or one of its descendants.
+    ///
+    pub fn set_parent(&mut self, node: NodeId, parent: Option<NodeId>) {
+        let mut ancestor = parent;
+        while let Some(id) = ancestor {
+            assert_ne!(id, node, "A node cannot be a descendant of itself");
+            ancestor = self.node(id).parent;
+        }
+        self.detach(node);
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(node),
+            None => self.roots.push(node),
+        }
+        let n = self.node_mut(node);
+        n.parent = parent;
+        n.dirty = true;
+    }
+
+    fn detach(&mut self, node: NodeId) {
+        let siblings = match self.node(node).parent {
+            Some(parent) => &mut self.node_mut(parent).children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|id| *id != node);
+    }
+
+    ///
+    /// Returns the transformation of the given node relative to its parent.
+    ///
+    pub fn transformation(&self, node: NodeId) -> Mat4 {
+        self.node(node).transformation
+    }
+
+    ///
+    /// Set the transformation of the given node relative to its parent.
+    ///
+    pub fn set_transformation(&mut self, node: NodeId, transformation: Mat4) {
+        let n = self.node_mut(node);
+        n.transformation = transformation;
+        n.dirty = true;
+    }
+
+    ///
+    /// Returns the local to world transformation of the given node, which is also up to date if [Scene::update] has not been called since the scene changed.
+    ///
+    pub fn world_transformation(&self, node: NodeId) -> Mat4 {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            let n = self.node(id);
+            if n.dirty {
+                let parent_transformation = n
+                    .parent
+                    .map_or(Mat4::identity(), |p| self.world_transformation(p));
+                return parent_transformation * self.path_transformation(node, id);
+            }
+            current = n.parent;
+        }
+        self.node(node).world_transformation
+    }
+
+    // The transformation from the given node up to and including the given ancestor
+    fn path_transformation(&self, node: NodeId, ancestor: NodeId) -> Mat4 {
+        let n = self.node(node);
+        if node == ancestor {
+            n.transformation
+        } else {
+            self.path_transformation(n.parent.unwrap(), ancestor) * n.transformation
+        }
+    }
+
+    ///
+    /// Returns the object of the given node.
+    ///
+    pub fn object(&self, node: NodeId) -> Option<&dyn SceneObject> {
+        self.node(node).object.as_deref()
+    }
+
+    ///
+    /// Returns the object of the given node.
+    /// Note that the transformation of the object is replaced by the world transformation of the node the next time the node changes, instead use [Scene::set_transformation].
+    ///
+    pub fn object_mut(&mut self, node: NodeId) -> Option<&mut dyn SceneObject> {
+        match &mut self.node_mut(node).object {
+            Some(object) => Some(object.as_mut()),
+            None => None,
+        }
+    }
+
+    ///
+    /// Recomputes the world transformations of the nodes which have changed, and of their descendants, and applies them to the objects.
+    ///
+    pub fn update(&mut self) {
+        let mut stack = self
+            .roots
+            .iter()
+            .map(|id| (*id, Mat4::identity(), false))
+            .collect::<Vec<_>>();
+        while let Some((id, parent_transformation, parent_changed)) = stack.pop() {
+            let node = self.nodes[id.0].as_mut().unwrap();
+            let changed = parent_changed || node.dirty;
+            if changed {
+                node.world_transformation = parent_transformation * node.transformation;
+                node.dirty = false;
+                if let Some(object) = &mut node.object {
+                    object.set_transformation(node.world_transformation);
+                }
+            }
+            let world_transformation = node.world_transformation;
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|child| (*child, world_transformation, changed)),
+            );
+        }
+    }
+
+    ///
+    /// Updates the animation of all objects in the scene, see [Geometry::animate].
+    ///
+    pub fn animate(&mut self, time: f32) {
+        for object in self.objects_mut() {
+            object.animate(time);
+        }
+    }
+
+    fn objects_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn SceneObject>> {
+        self.nodes
+            .iter_mut()
+            .flatten()
+            .filter_map(|n| n.object.as_mut())
+    }
+
+    fn node(&self, node: NodeId) -> &Node {
+        self.nodes[node.0]
+            .as_ref()
+            .expect("The node has been removed from the scene")
+    }
+
+    fn node_mut(&mut self, node: NodeId) -> &mut Node {
+        self.nodes[node.0]
+            .as_mut()
+            .expect("The node has been removed from the scene")
+    }
+}
+
+impl<'a> IntoIterator for &'a Scene {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes
+            .iter()
+            .flatten()
+            .filter_map(|n| n.object.as_deref().map(|o| o as &dyn Object))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
@@ -0,0 +1,74 @@
+uniform mat4 viewProjectionInverse;
+uniform mat4 decalTransformationInverse;
+uniform vec3 decalTangent;
+uniform vec3 decalDirection;
+uniform vec4 viewport;
+uniform vec3 cameraPosition;
+
+uniform vec4 albedo;
+#ifdef USE_ALBEDO_TEXTURE
+uniform sampler2D albedoTexture;
+uniform mat3 albedoTexTransform;
+#endif
+
+#ifdef USE_NORMAL_TEXTURE
+uniform sampler2D normalTexture;
+uniform mat3 normalTexTransform;
+uniform float normalScale;
+uniform float normalBlend;
+#endif
+
+uniform float metallic;
+uniform float roughness;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec2 screen_uv = (gl_FragCoord.xy - viewport.xy) / viewport.zw;
+    float depth = sample_depth(screen_uv);
+    vec3 position = world_pos_from_depth(viewProjectionInverse, depth, screen_uv);
+
+    // The derivatives are calculated before any fragment is discarded
+    vec3 surface_normal = normalize(cross(dFdx(position), dFdy(position)));
+    if (dot(surface_normal, cameraPosition - position) < 0.0) {
+        surface_normal = -surface_normal;
+    }
+
+    vec3 local_position = (decalTransformationInverse * vec4(position, 1.0)).xyz;
+    float facing = dot(surface_normal, decalDirection);
+    if (depth > 0.99999 || any(greaterThan(abs(local_position), vec3(1.0))) || facing <= 0.0) {
+        discard;
+    }
+    vec2 uv = 0.5 + 0.5 * local_position.xy;
+
+    vec4 surface_color = albedo;
+#ifdef USE_ALBEDO_TEXTURE
+    surface_color *= texture(albedoTexture, (albedoTexTransform * vec3(uv, 1.0)).xy);
+#endif
+    // Fade out on surfaces which are almost parallel to the projection direction to avoid stretching
+    float alpha = surface_color.a * smoothstep(0.0, 0.25, facing);
+
+    vec3 normal = surface_normal;
+#ifdef USE_NORMAL_TEXTURE
+    vec3 tangent = normalize(decalTangent - surface_normal * dot(surface_normal, decalTangent));
+    vec3 bitangent = cross(surface_normal, tangent);
+    mat3 tbn = mat3(tangent, bitangent, surface_normal);
+    vec3 decal_normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uv, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+    normal = normalize(mix(surface_normal, normalize(decal_normal), normalBlend));
+#endif
+
+#ifdef USE_MULTIPLY_BLEND
+    vec3 factor = surface_color.rgb;
+#ifdef USE_NORMAL_TEXTURE
+    // Changes the shading of the surface below by the ratio between the lighting with and without the decal normal
+    vec3 decal_lighting = calculate_lighting(cameraPosition, vec3(1.0), position, normal, metallic, roughness, 1.0);
+    vec3 surface_lighting = calculate_lighting(cameraPosition, vec3(1.0), position, surface_normal, metallic, roughness, 1.0);
+    factor *= clamp(decal_lighting / max(surface_lighting, vec3(0.0001)), vec3(0.0), vec3(4.0));
+#endif
+    outColor = vec4(mix(vec3(1.0), color_mapping(factor), alpha), 1.0);
+#else
+    vec3 color = calculate_lighting(cameraPosition, surface_color.rgb, position, normal, metallic, roughness, 1.0);
+    outColor = vec4(color_mapping(tone_mapping(color)), alpha);
+#endif
+}
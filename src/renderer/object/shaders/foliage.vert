@@ -0,0 +1,73 @@
+uniform mat4 viewProjection;
+uniform vec3 cameraPosition;
+uniform float time;
+uniform vec2 windDirection;
+uniform float windStrength;
+uniform float windSpeed;
+uniform float windWavelength;
+uniform float fadeStart;
+uniform float fadeEnd;
+
+in vec3 position;
+in vec4 instance_position;
+in float instance_rotation;
+
+out vec3 pos;
+out vec4 col;
+
+#ifdef USE_NORMALS
+in vec3 normal;
+out vec3 nor;
+
+#ifdef USE_TANGENTS
+in vec4 tangent;
+out vec3 tang;
+out vec3 bitang;
+#endif
+#endif
+
+#ifdef USE_UVS
+in vec2 uv_coordinates;
+out vec2 uvs;
+#endif
+
+vec3 rotate_y(vec3 v, float c, float s)
+{
+    return vec3(c * v.x + s * v.z, v.y, -s * v.x + c * v.z);
+}
+
+void main()
+{
+    float c = cos(instance_rotation);
+    float s = sin(instance_rotation);
+    float scale = instance_position.w;
+
+    // Shrinks the instances to nothing between the fade distances
+    float fade = 1.0 - smoothstep(fadeStart, fadeEnd, distance(cameraPosition, instance_position.xyz));
+
+    vec3 local_position = rotate_y(position, c, s) * scale * fade;
+
+    // Bends the instances in the wind direction, more the higher above the ground, with waves moving across the field
+    float phase = 6.2831853 * (dot(instance_position.xz, windDirection) / windWavelength - windSpeed * time / windWavelength);
+    float sway = windStrength * (0.6 + 0.4 * sin(phase) + 0.1 * sin(2.7 * phase + instance_rotation));
+    float height = max(position.y, 0.0);
+    local_position.xz += windDirection * sway * height * height * scale * fade;
+
+    vec3 world_position = instance_position.xyz + local_position;
+    pos = world_position;
+    gl_Position = viewProjection * vec4(world_position, 1.0);
+
+#ifdef USE_NORMALS
+    nor = normalize(rotate_y(normal, c, s));
+#ifdef USE_TANGENTS
+    tang = normalize(rotate_y(tangent.xyz, c, s));
+    bitang = normalize(cross(nor, tang) * tangent.w);
+#endif
+#endif
+
+#ifdef USE_UVS
+    uvs = uv_coordinates;
+#endif
+
+    col = vec4(1.0);
+}
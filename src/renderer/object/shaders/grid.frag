@@ -0,0 +1,46 @@
+uniform vec3 cameraPosition;
+uniform float cellSize;
+uniform float majorLineEvery;
+uniform float halfWidth;
+uniform float fadeDistance;
+uniform vec4 minorColor;
+uniform vec4 majorColor;
+uniform vec4 xAxisColor;
+uniform vec4 zAxisColor;
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+// Returns the coverage of the closest line of the grid with lines at the integer coordinates
+float grid_lines(vec2 coordinates)
+{
+    vec2 derivative = max(fwidth(coordinates), vec2(0.00001));
+    // The distance in pixels to the closest line in each direction
+    vec2 distance = abs(fract(coordinates - 0.5) - 0.5) / derivative;
+    float coverage = 1.0 - smoothstep(halfWidth - 0.5, halfWidth + 0.5, min(distance.x, distance.y));
+    // Fade out the lines when they are closer than a few pixels to avoid moiré patterns
+    return coverage * (1.0 - smoothstep(0.2, 0.5, max(derivative.x, derivative.y)));
+}
+
+// Returns the coverage of the line where the coordinate is zero
+float axis_line(float coordinate)
+{
+    float distance = abs(coordinate) / max(fwidth(coordinate), 0.00001);
+    return 1.0 - smoothstep(halfWidth - 0.5, halfWidth + 0.5, distance);
+}
+
+void main()
+{
+    vec2 coordinates = pos.xz / cellSize;
+    vec4 color = vec4(minorColor.rgb, minorColor.a * grid_lines(coordinates));
+    color = mix(color, majorColor, grid_lines(coordinates / majorLineEvery));
+    color = mix(color, xAxisColor, axis_line(pos.z));
+    color = mix(color, zAxisColor, axis_line(pos.x));
+    color.a *= 1.0 - smoothstep(0.5 * fadeDistance, fadeDistance, distance(cameraPosition, pos));
+    if (color.a <= 0.001) {
+        discard;
+    }
+    outColor = color;
+    outColor.rgb = color_mapping(outColor.rgb);
+}
@@ -0,0 +1,15 @@
+uniform mat4 viewProjection;
+uniform vec3 center;
+uniform float extent;
+
+in vec2 position;
+
+out vec3 pos;
+out vec4 col;
+
+void main()
+{
+    pos = center + vec3(position.x, 0.0, position.y) * extent;
+    col = vec4(1.0);
+    gl_Position = viewProjection * vec4(pos, 1.0);
+}
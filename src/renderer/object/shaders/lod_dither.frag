@@ -0,0 +1,20 @@
+
+uniform vec2 lodDitherRange;
+
+// 4x4 Bayer matrix giving each pixel in a 4x4 block a different threshold
+const float ditherThresholds[16] = float[16](
+    0.0, 8.0, 2.0, 10.0,
+    12.0, 4.0, 14.0, 6.0,
+    3.0, 11.0, 1.0, 9.0,
+    15.0, 7.0, 13.0, 5.0
+);
+
+void main()
+{
+    ivec2 pixel = ivec2(gl_FragCoord.xy) % 4;
+    float threshold = (ditherThresholds[pixel.y * 4 + pixel.x] + 0.5) / 16.0;
+    if (threshold < lodDitherRange.x || threshold >= lodDitherRange.y) {
+        discard;
+    }
+    material_main();
+}
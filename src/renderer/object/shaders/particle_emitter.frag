@@ -0,0 +1,43 @@
+#ifdef USE_TEXTURE
+uniform sampler2D particleTexture;
+uniform mat3 textureTransformation;
+#endif
+
+#ifdef USE_SOFT_PARTICLES
+uniform mat4 viewProjectionInverse;
+uniform vec4 viewport;
+uniform vec3 cameraPosition;
+uniform float softness;
+#endif
+
+in vec3 pos;
+in vec2 uvs;
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 color = col;
+#ifdef USE_TEXTURE
+    color *= texture(particleTexture, (textureTransformation * vec3(uvs, 1.0)).xy);
+#else
+    // Round particles with soft edges
+    color.a *= 1.0 - smoothstep(0.2, 0.5, length(uvs - 0.5));
+#endif
+
+#ifdef USE_SOFT_PARTICLES
+    // Fades out the particle where it is close to or behind the geometry in the depth texture
+    vec2 screen_uv = (gl_FragCoord.xy - viewport.xy) / viewport.zw;
+    vec3 scene_position = world_pos_from_depth(viewProjectionInverse, sample_depth(screen_uv), screen_uv);
+    color.a *= clamp((distance(cameraPosition, scene_position) - distance(cameraPosition, pos)) / softness, 0.0, 1.0);
+#endif
+
+    if (color.a < 0.001) {
+        discard;
+    }
+
+    outColor.rgb = tone_mapping(color.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = color.a;
+}
@@ -0,0 +1,79 @@
+uniform mat4 viewProjection;
+uniform vec3 cameraRight;
+uniform vec3 cameraUp;
+uniform mat4 transformation;
+uniform vec3 emissionDirection;
+uniform vec3 acceleration;
+uniform float time;
+uniform float period;
+uniform vec4 colorOverLife[CURVE_SAMPLES];
+uniform float sizeOverLife[CURVE_SAMPLES];
+uniform float distanceOverLife[CURVE_SAMPLES];
+
+in vec2 position;
+in vec4 instance_timing;
+in vec3 instance_position;
+in vec3 instance_velocity;
+in vec3 instance_size;
+
+out vec3 pos;
+out vec2 uvs;
+out vec4 col;
+
+float sample_curve(float curve[CURVE_SAMPLES], float x)
+{
+    float s = clamp(x, 0.0, 1.0) * float(CURVE_SAMPLES - 1);
+    int i = min(int(s), CURVE_SAMPLES - 2);
+    return mix(curve[i], curve[i + 1], s - float(i));
+}
+
+vec4 sample_color_curve(vec4 curve[CURVE_SAMPLES], float x)
+{
+    float s = clamp(x, 0.0, 1.0) * float(CURVE_SAMPLES - 1);
+    int i = min(int(s), CURVE_SAMPLES - 2);
+    return mix(curve[i], curve[i + 1], s - float(i));
+}
+
+// Rotates the vector around the unit length axis by the given angle (Rodrigues' rotation formula)
+vec3 rotate(vec3 v, vec3 axis, float angle)
+{
+    float c = cos(angle);
+    float s = sin(angle);
+    return v * c + cross(axis, v) * s + axis * dot(axis, v) * (1.0 - c);
+}
+
+void main()
+{
+    pos = vec3(0.0);
+    uvs = vec2(position.x + 0.5, 0.5 - position.y);
+    col = vec4(0.0);
+
+    float spawn_time = instance_timing.x;
+    float lifetime = instance_timing.y;
+    float t = time - spawn_time;
+    // Each particle is respawned every period, which is at least as long as the longest lifetime
+    float cycle = floor(t / period);
+    float age = t - cycle * period;
+    if (t < 0.0 || age > lifetime) {
+        // The particle is not alive, so it is placed outside the clip volume
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+    float x = age / lifetime;
+
+    // A new random rotation of the velocity around the emission direction each time the particle is respawned
+    float angle = 6.2831853 * fract(sin(cycle * 12.9898 + instance_timing.z * 78.233) * 43758.5453);
+    vec3 velocity = rotate(instance_velocity, emissionDirection, angle);
+    vec3 local_position = instance_position + velocity * lifetime * sample_curve(distanceOverLife, x);
+    vec3 center = (transformation * vec4(local_position, 1.0)).xyz + 0.5 * acceleration * age * age;
+
+    float size = instance_size.x * sample_curve(sizeOverLife, x);
+    float rotation = instance_size.y + instance_size.z * age;
+    float c = cos(rotation);
+    float s = sin(rotation);
+    vec2 corner = vec2(c * position.x - s * position.y, s * position.x + c * position.y) * size;
+
+    pos = center + cameraRight * corner.x + cameraUp * corner.y;
+    col = sample_color_curve(colorOverLife, x);
+    gl_Position = viewProjection * vec4(pos, 1.0);
+}
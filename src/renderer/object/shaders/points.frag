@@ -0,0 +1,18 @@
+uniform vec4 surfaceColor;
+
+in vec2 uvs;
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+#ifdef USE_ROUND_POINTS
+    vec2 offset = 2.0 * uvs - 1.0;
+    if (dot(offset, offset) > 1.0) {
+        discard;
+    }
+#endif
+    outColor = surfaceColor * col;
+    outColor.rgb = color_mapping(outColor.rgb);
+}
@@ -0,0 +1,76 @@
+uniform mat4 viewProjection;
+uniform mat4 modelMatrix;
+uniform vec3 cameraRight;
+uniform vec3 cameraUp;
+uniform vec2 viewportSize;
+uniform float pointSize;
+uniform int sizeInPixels;
+
+in vec2 position;
+in vec3 instance_position;
+
+out vec3 pos;
+out vec4 col;
+out vec2 uvs;
+
+#ifdef USE_INSTANCE_COLORS
+in vec4 instance_color;
+#endif
+
+#ifdef USE_INSTANCE_SIZES
+in float instance_size;
+#endif
+
+#ifdef USE_INSTANCE_NORMALS
+uniform mat4 normalMatrix;
+in vec3 instance_normal;
+#endif
+
+#ifdef USE_NORMALS
+uniform vec3 cameraPosition;
+out vec3 nor;
+#endif
+
+void main()
+{
+    vec4 world_position = modelMatrix * vec4(instance_position, 1.0);
+    float size = pointSize;
+#ifdef USE_INSTANCE_SIZES
+    size *= instance_size;
+#endif
+#ifdef USE_INSTANCE_NORMALS
+    vec3 normal = normalize(mat3(normalMatrix) * instance_normal);
+#endif
+
+#ifdef USE_SURFELS
+    // A disk orthogonal to the normal of the point
+    vec3 tangent = normalize(cross(normal, abs(normal.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0)));
+    vec3 bitangent = cross(normal, tangent);
+    world_position.xyz += (tangent * position.x + bitangent * position.y) * size;
+    gl_Position = viewProjection * world_position;
+#else
+    if (sizeInPixels == 1) {
+        gl_Position = viewProjection * world_position;
+        gl_Position.xy += 2.0 * position * size / viewportSize * gl_Position.w;
+    } else {
+        world_position.xyz += (cameraRight * position.x + cameraUp * position.y) * size;
+        gl_Position = viewProjection * world_position;
+    }
+#endif
+
+    pos = world_position.xyz;
+    uvs = position + 0.5;
+#ifdef USE_INSTANCE_COLORS
+    col = instance_color;
+#else
+    col = vec4(1.0);
+#endif
+
+#ifdef USE_NORMALS
+#ifdef USE_INSTANCE_NORMALS
+    nor = normal;
+#else
+    nor = normalize(cameraPosition - pos);
+#endif
+#endif
+}
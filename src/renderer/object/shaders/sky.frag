@@ -0,0 +1,81 @@
+uniform vec3 direction;
+uniform vec3 up;
+uniform vec3 sunDirection;
+uniform float turbidity;
+uniform float intensity;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+// The Perez distribution function for the sky luminance and chromaticity, see "A Practical Analytic Model for Daylight" by Preetham et al.
+float perez(float cos_theta, float gamma, float cos_gamma, float A, float B, float C, float D, float E)
+{
+    return (1.0 + A * exp(B / max(cos_theta, 0.01))) * (1.0 + C * exp(D * gamma) + E * cos_gamma * cos_gamma);
+}
+
+vec3 sky_xyY(vec3 view_direction, vec3 sun_direction, float T)
+{
+    float cos_theta = max(view_direction.y, 0.0);
+    float cos_gamma = clamp(dot(view_direction, sun_direction), -1.0, 1.0);
+    float gamma = acos(cos_gamma);
+    float theta_s = acos(clamp(sun_direction.y, 0.0, 1.0));
+    float cos_theta_s = cos(theta_s);
+
+    // Zenith values
+    float chi = (4.0 / 9.0 - T / 120.0) * (PI - 2.0 * theta_s);
+    float Yz = (4.0453 * T - 4.9710) * tan(chi) - 0.2155 * T + 2.4192;
+    vec3 theta_s_powers = vec3(theta_s * theta_s * theta_s, theta_s * theta_s, theta_s);
+    float xz = T * T * dot(vec3(0.00166, -0.00375, 0.00209), theta_s_powers)
+        + T * (dot(vec3(-0.02903, 0.06377, -0.03202), theta_s_powers) + 0.00394)
+        + dot(vec3(0.11693, -0.21196, 0.06052), theta_s_powers) + 0.25886;
+    float yz = T * T * dot(vec3(0.00275, -0.00610, 0.00317), theta_s_powers)
+        + T * (dot(vec3(-0.04214, 0.08970, -0.04153), theta_s_powers) + 0.00516)
+        + dot(vec3(0.15346, -0.26756, 0.06670), theta_s_powers) + 0.26688;
+
+    // Distribution coefficients
+    vec3 A = vec3(0.1787 * T - 1.4630, -0.0193 * T - 0.2592, -0.0167 * T - 0.2608);
+    vec3 B = vec3(-0.3554 * T + 0.4275, -0.0665 * T + 0.0008, -0.0950 * T + 0.0092);
+    vec3 C = vec3(-0.0227 * T + 5.3251, -0.0004 * T + 0.2125, -0.0079 * T + 0.2102);
+    vec3 D = vec3(0.1206 * T - 2.5771, -0.0641 * T - 0.8989, -0.0441 * T - 1.6537);
+    vec3 E = vec3(-0.0670 * T + 0.3703, -0.0033 * T + 0.0452, -0.0109 * T + 0.0529);
+
+    vec3 result;
+    result.z = Yz * perez(cos_theta, gamma, cos_gamma, A.x, B.x, C.x, D.x, E.x) / perez(1.0, theta_s, cos_theta_s, A.x, B.x, C.x, D.x, E.x);
+    result.x = xz * perez(cos_theta, gamma, cos_gamma, A.y, B.y, C.y, D.y, E.y) / perez(1.0, theta_s, cos_theta_s, A.y, B.y, C.y, D.y, E.y);
+    result.y = yz * perez(cos_theta, gamma, cos_gamma, A.z, B.z, C.z, D.z, E.z) / perez(1.0, theta_s, cos_theta_s, A.z, B.z, C.z, D.z, E.z);
+    return result;
+}
+
+vec3 xyY_to_linear_srgb(vec3 xyY)
+{
+    float Y = xyY.z;
+    float X = xyY.x / xyY.y * Y;
+    float Z = (1.0 - xyY.x - xyY.y) / xyY.y * Y;
+    return max(vec3(
+        3.2406 * X - 1.5372 * Y - 0.4986 * Z,
+        -0.9689 * X + 1.8758 * Y + 0.0415 * Z,
+        0.0557 * X - 0.2040 * Y + 1.0570 * Z
+    ), vec3(0.0));
+}
+
+void main()
+{
+    vec3 right = cross(direction, up);
+    vec3 view_direction = normalize(up * (uvs.y - 0.5) * 2.0 + right * (uvs.x - 0.5) * 2.0 + direction);
+    vec3 sun_direction = normalize(sunDirection);
+
+    // Dim the sky when the sun sets
+    float sun_height = smoothstep(-0.1, 0.1, sun_direction.y);
+    vec3 sky = sun_height * intensity * xyY_to_linear_srgb(sky_xyY(vec3(view_direction.x, max(view_direction.y, 0.0), view_direction.z), sun_direction, turbidity));
+
+    // Sun disk, colored by the sky close to the sun so it turns red when the sun sets
+    float sun_disk = smoothstep(0.9998, 0.99995, dot(view_direction, sun_direction));
+    vec3 sun_color = sun_height * intensity * xyY_to_linear_srgb(sky_xyY(normalize(vec3(sun_direction.x, max(sun_direction.y, 0.001), sun_direction.z)), sun_direction, turbidity));
+    sky += sun_disk * 50.0 * sun_color;
+
+    // Ground
+    vec3 horizon = sun_height * intensity * xyY_to_linear_srgb(sky_xyY(normalize(vec3(view_direction.x, 0.0, view_direction.z) + vec3(0.0, 0.0001, 0.0)), sun_direction, turbidity));
+    vec3 ground = 0.3 * horizon;
+    outColor = vec4(mix(ground, sky, smoothstep(-0.02, 0.0, view_direction.y)), 1.0);
+}
@@ -0,0 +1,18 @@
+uniform vec4 textColor;
+uniform sampler2D fontAtlas;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float distance = texture(fontAtlas, uvs).r;
+    float width = max(fwidth(distance), 0.0001);
+    float alpha = smoothstep(0.5 - width, 0.5 + width, distance);
+    if (alpha <= 0.0) {
+        discard;
+    }
+    outColor = vec4(textColor.rgb, textColor.a * alpha);
+    outColor.rgb = color_mapping(outColor.rgb);
+}
@@ -0,0 +1,35 @@
+uniform vec2 viewportSize;
+uniform float size;
+uniform int placement;
+uniform vec3 anchor;
+uniform mat4 viewProjection;
+uniform mat4 transformation;
+
+in vec2 position;
+in vec4 glyph_position;
+in vec4 glyph_uv;
+
+out vec3 pos;
+out vec2 uvs;
+
+void main()
+{
+    vec2 offset = size * mix(glyph_position.xy, glyph_position.zw, position);
+    uvs = mix(glyph_uv.xw, glyph_uv.zy, position);
+    if (placement == 0) {
+        // Screen space in physical pixels
+        vec2 p = anchor.xy + offset;
+        pos = vec3(p, 0.0);
+        gl_Position = vec4(2.0 * p / viewportSize - 1.0, 0.0, 1.0);
+    } else if (placement == 1) {
+        // Billboard with the offset in physical pixels
+        pos = anchor;
+        gl_Position = viewProjection * vec4(anchor, 1.0);
+        gl_Position.xy += 2.0 * offset / viewportSize * gl_Position.w;
+    } else {
+        // The xy plane in world space
+        vec4 world_position = transformation * vec4(offset, 0.0, 1.0);
+        pos = world_position.xyz / world_position.w;
+        gl_Position = viewProjection * world_position;
+    }
+}
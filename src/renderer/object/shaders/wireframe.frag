@@ -0,0 +1,19 @@
+uniform vec4 surfaceColor;
+uniform float halfWidth;
+
+in vec3 bary;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The distance in pixels to the closest edge of the triangle
+    vec3 distances = bary / max(fwidth(bary), vec3(0.00001));
+    float distance = min(min(distances.x, distances.y), distances.z);
+    float alpha = 1.0 - smoothstep(halfWidth - 0.5, halfWidth + 0.5, distance);
+    if (alpha <= 0.0) {
+        discard;
+    }
+    outColor = vec4(surfaceColor.rgb, surfaceColor.a * alpha);
+    outColor.rgb = color_mapping(outColor.rgb);
+}
@@ -0,0 +1,20 @@
+uniform mat4 viewProjection;
+uniform mat4 modelMatrix;
+
+in vec3 position;
+in vec3 barycentric;
+
+out vec3 pos;
+out vec4 col;
+out vec3 bary;
+
+void main()
+{
+    vec4 world_position = modelMatrix * vec4(position, 1.0);
+    pos = world_position.xyz;
+    col = vec4(1.0);
+    bary = barycentric;
+    gl_Position = viewProjection * world_position;
+    // Moves the lines slightly towards the camera, so they are not hidden by the surface they are drawn on top of
+    gl_Position.z -= 0.0001 * gl_Position.w;
+}
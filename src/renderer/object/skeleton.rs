@@ -0,0 +1,461 @@
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// The translation, rotation and scale of a joint relative to its parent joint.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointPose {
+    /// The translation relative to the parent joint.
+    pub translation: Vec3,
+    /// The rotation relative to the parent joint.
+    pub rotation: Quat,
+    /// The scale relative to the parent joint.
+    pub scale: Vec3,
+}
+
+impl Default for JointPose {
+    fn default() -> Self {
+        Self {
+            translation: vec3(0.0, 0.0, 0.0),
+            rotation: Quat::one(),
+            scale: vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl JointPose {
+    ///
+    /// Returns the transformation relative to the parent joint, ie. the scale followed by the rotation and then the translation.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * Mat4::from(self.rotation)
+            * Mat4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+
+    ///
+    /// Returns the pose in between this pose and the other pose, where a factor of zero returns this pose and a factor of one returns the other pose.
+    ///
+    pub fn interpolate(&self, other: &Self, factor: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(other.translation, factor),
+            rotation: self.rotation.slerp(other.rotation, factor),
+            scale: self.scale.lerp(other.scale, factor),
+        }
+    }
+}
+
+///
+/// A joint, also called a bone, in a [Skeleton].
+///
+#[derive(Clone, Debug)]
+pub struct Joint {
+    /// The name of the joint.
+    pub name: String,
+    /// The index of the parent joint in the skeleton or `None` if this is a root joint.
+    pub parent: Option<usize>,
+    /// The pose of the joint when it is not animated.
+    pub rest_pose: JointPose,
+    /// The inverse of the transformation of the joint when the mesh was bound to the skeleton,
+    /// which transforms the vertices of the mesh into the space of the joint.
+    pub inverse_bind_matrix: Mat4,
+}
+
+///
+/// A hierarchy of joints which deforms one or more [SkinnedMesh]es.
+/// The transformation of each joint is computed from a [Pose], which is usually sampled from [AnimationClip]s by an [AnimationPlayer].
+///
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    order: Vec<usize>,
+    /// The transformation applied to the root joints.
+    pub transformation: Mat4,
+}
+
+impl Skeleton {
+    ///
+    /// Creates a new skeleton with the given joints. The parent of each joint must be another joint in the list and the hierarchy must not contain cycles.
+    ///
+    pub fn new(joints: Vec<Joint>) -> Self {
+        // Orders the joints so each parent is visited before its children
+        let mut order = Vec::with_capacity(joints.len());
+        let mut visited = vec![false; joints.len()];
+        while order.len() < joints.len() {
+            let count = order.len();
+            for (i, joint) in joints.iter().enumerate() {
+                if !visited[i] && joint.parent.is_none_or(|p| visited[p]) {
+                    visited[i] = true;
+                    order.push(i);
+                }
+            }
+            assert!(
+                order.len() > count,
+                "The joint hierarchy must not contain cycles"
+            );
+        }
+        Self {
+            joints,
+            order,
+            transformation: Mat4::identity(),
+        }
+    }
+
+    ///
+    /// Returns the joints.
+    ///
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    ///
+    /// Returns the index of the joint with the given name.
+    ///
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|j| j.name == name)
+    }
+
+    ///
+    /// Returns the pose where all joints are in their rest pose.
+    ///
+    pub fn rest_pose(&self) -> Pose {
+        Pose {
+            joints: self.joints.iter().map(|j| j.rest_pose).collect(),
+        }
+    }
+
+    ///
+    /// Returns the transformation of each joint relative to the model in the given pose, for example to attach an object to a hand.
+    ///
+    pub fn joint_transformations(&self, pose: &Pose) -> Vec<Mat4> {
+        let mut transformations = vec![Mat4::identity(); self.joints.len()];
+        for &i in self.order.iter() {
+            let local = pose
+                .joints
+                .get(i)
+                .unwrap_or(&self.joints[i].rest_pose)
+                .transformation();
+            transformations[i] = match self.joints[i].parent {
+                Some(parent) => transformations[parent] * local,
+                None => self.transformation * local,
+            };
+        }
+        transformations
+    }
+
+    ///
+    /// Returns the matrices which transform the vertices of a [SkinnedMesh] from the bind pose to the given pose for each joint, see [SkinnedMesh::set_joint_matrices].
+    ///
+    pub fn skinning_matrices(&self, pose: &Pose) -> Vec<Mat4> {
+        self.joint_transformations(pose)
+            .into_iter()
+            .zip(self.joints.iter())
+            .map(|(transformation, joint)| transformation * joint.inverse_bind_matrix)
+            .collect()
+    }
+    ///
+    /// Returns the skinning matrices for each of the given poses, see [Skeleton::skinning_matrices], for example for many characters sharing the same skeleton.
+    /// The matrices are computed on multiple threads when the `rayon` feature is enabled.
+    ///
+    pub fn skinning_matrices_of_poses(&self, poses: &[Pose]) -> Vec<Vec<Mat4>> {
+        parallel::map(poses, |pose| self.skinning_matrices(pose))
+    }
+}
+
+///
+/// The pose of each joint in a [Skeleton].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pose {
+    /// The pose of each joint in the same order as the joints in the skeleton.
+    pub joints: Vec<JointPose>,
+}
+
+impl Pose {
+    ///
+    /// Returns the pose in between this pose and the other pose, where a factor of zero returns this pose and a factor of one returns the other pose.
+    /// Both poses must belong to the same skeleton.
+    ///
+    pub fn interpolate(&self, other: &Self, factor: f32) -> Self {
+        Self {
+            joints: self
+                .joints
+                .iter()
+                .zip(other.joints.iter())
+                .map(|(a, b)| a.interpolate(b, factor))
+                .collect(),
+        }
+    }
+}
+
+///
+/// An event at a specific time in an [AnimationClip], for example when a foot touches the ground, which is returned by [AnimationPlayer::update] when the animation passes the time of the event.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationEvent {
+    /// The name of the event.
+    pub name: String,
+    /// The time of the event in seconds from the start of the animation.
+    pub time: f32,
+}
+
+///
+/// An animation of the joints of a [Skeleton], for example walking or jumping.
+///
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    /// The name of the animation.
+    pub name: Option<String>,
+    /// The duration of the animation in seconds.
+    pub duration: f32,
+    /// The key frames of the animated joints together with the index of the joint they animate.
+    /// The translation, rotation and scale of the joints which are not animated are not changed by the animation.
+    pub channels: Vec<(usize, Arc<KeyFrames>)>,
+    /// The key frames of the morph target weights together with the index of the mesh they animate, see [CpuSkinnedModel::geometries] and [SkinnedMesh::set_morph_weights].
+    pub morph_channels: Vec<(usize, Arc<KeyFrames>)>,
+    /// The events of the animation, for example footsteps.
+    pub events: Vec<AnimationEvent>,
+}
+
+impl AnimationClip {
+    ///
+    /// Applies the animation at the given time in seconds to the given pose.
+    ///
+    pub fn apply(&self, time: f32, pose: &mut Pose) {
+        for (joint, key_frames) in self.channels.iter() {
+            if let Some(joint_pose) = pose.joints.get_mut(*joint) {
+                if let Some(translation) = key_frames.translation(time) {
+                    joint_pose.translation = translation;
+                }
+                if let Some(rotation) = key_frames.rotation(time) {
+                    joint_pose.rotation = rotation;
+                }
+                if let Some(scale) = key_frames.scale(time) {
+                    joint_pose.scale = scale;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Returns the morph target weights of the mesh with the given index at the given time in seconds or `None` if the animation does not animate the morph targets of the mesh.
+    ///
+    pub fn morph_weights(&self, time: f32, mesh: usize) -> Option<Vec<f32>> {
+        self.morph_channels
+            .iter()
+            .find(|(i, _)| *i == mesh)
+            .and_then(|(_, key_frames)| key_frames.weights(time))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PlayingClip {
+    index: usize,
+    time: f32,
+    looping: bool,
+}
+
+///
+/// Plays [AnimationClip]s and computes the resulting [Pose] of a [Skeleton].
+/// When switching to another animation, the two animations can be blended over a period of time to avoid a sudden jump in the pose.
+///
+#[derive(Clone, Debug)]
+pub struct AnimationPlayer {
+    clips: Vec<AnimationClip>,
+    current: Option<PlayingClip>,
+    previous: Option<PlayingClip>,
+    crossfade: (f32, f32),
+    last_time: Option<f32>,
+    /// The playback speed, where one is normal speed and a negative speed plays the animations backwards.
+    pub speed: f32,
+}
+
+impl AnimationPlayer {
+    ///
+    /// Creates a new player for the given animations. No animation is playing initially, see [Self::play].
+    ///
+    pub fn new(clips: Vec<AnimationClip>) -> Self {
+        Self {
+            clips,
+            current: None,
+            previous: None,
+            crossfade: (0.0, 0.0),
+            last_time: None,
+            speed: 1.0,
+        }
+    }
+
+    ///
+    /// Returns the animations.
+    ///
+    pub fn clips(&self) -> &[AnimationClip] {
+        &self.clips
+    }
+
+    ///
+    /// Returns the index of the animation with the given name.
+    ///
+    pub fn clip_index(&self, name: &str) -> Option<usize> {
+        self.clips
+            .iter()
+            .position(|c| c.name.as_deref() == Some(name))
+    }
+
+    ///
+    /// Returns the index of the animation that is currently playing.
+    ///
+    pub fn playing(&self) -> Option<usize> {
+        self.current.map(|c| c.index)
+    }
+
+    ///
+    /// Starts playing the animation with the given index from the beginning. If looping, the animation starts over when it ends, otherwise it stays at the last frame.
+    /// If the crossfade duration is larger than zero, the pose is blended from the animation that was playing to the new animation over the given number of seconds.
+    ///
+    pub fn play(&mut self, index: usize, looping: bool, crossfade_duration: f32) {
+        assert!(index < self.clips.len(), "The animation does not exist");
+        self.previous = if crossfade_duration > 0.0 {
+            self.current
+        } else {
+            None
+        };
+        self.crossfade = (0.0, crossfade_duration);
+        self.current = Some(PlayingClip {
+            index,
+            time: 0.0,
+            looping,
+        });
+    }
+
+    ///
+    /// Returns whether the animation that is currently playing has ended, which is never the case for a looping animation.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.current.is_some_and(|clip| {
+            let duration = self.clips[clip.index].duration;
+            !clip.looping
+                && if self.speed < 0.0 {
+                    clip.time <= 0.0
+                } else {
+                    clip.time >= duration
+                }
+        })
+    }
+
+    ///
+    /// Stops the animations, so the skeleton returns to its rest pose.
+    ///
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.previous = None;
+    }
+
+    ///
+    /// Advances the animations. Call this every frame with some continuous time in seconds, for example the time since start.
+    /// Returns the events of the playing animation which have been passed since the last update, see [AnimationClip::events].
+    ///
+    pub fn update(&mut self, time: f32) -> Vec<AnimationEvent> {
+        let elapsed = self.last_time.map_or(0.0, |last_time| time - last_time);
+        self.last_time = Some(time);
+        let mut events = Vec::new();
+        if let Some(clip) = &self.current {
+            let animation = &self.clips[clip.index];
+            let duration = animation.duration;
+            let (mut t0, mut t1) = (clip.time, clip.time + elapsed * self.speed);
+            if !clip.looping {
+                t0 = t0.clamp(0.0, duration);
+                t1 = t1.clamp(0.0, duration);
+            }
+            if duration > 0.0 && t0 != t1 {
+                for event in animation.events.iter() {
+                    let e = event.time;
+                    let passed = if clip.looping {
+                        // The event is repeated every time the animation loops
+                        if t1 > t0 {
+                            ((t1 - e) / duration).floor() > ((t0 - e) / duration).floor()
+                        } else {
+                            ((t0 - e) / duration).ceil() > ((t1 - e) / duration).ceil()
+                        }
+                    } else if t1 > t0 {
+                        t0 < e && e <= t1
+                    } else {
+                        t1 <= e && e < t0
+                    };
+                    if passed {
+                        events.push(event.clone());
+                    }
+                }
+            }
+        }
+        for clip in self.current.iter_mut().chain(self.previous.iter_mut()) {
+            clip.time += elapsed * self.speed;
+        }
+        if self.previous.is_some() {
+            self.crossfade.0 += elapsed;
+            if self.crossfade.0 >= self.crossfade.1 {
+                self.previous = None;
+            }
+        }
+        events
+    }
+
+    ///
+    /// Returns the current pose of the given skeleton.
+    ///
+    pub fn pose(&self, skeleton: &Skeleton) -> Pose {
+        let sample = |clip: &PlayingClip| {
+            let mut pose = skeleton.rest_pose();
+            self.clips[clip.index].apply(self.clip_time(clip), &mut pose);
+            pose
+        };
+        let Some(current) = &self.current else {
+            return skeleton.rest_pose();
+        };
+        let pose = sample(current);
+        match &self.previous {
+            Some(previous) => {
+                sample(previous).interpolate(&pose, self.crossfade.0 / self.crossfade.1)
+            }
+            None => pose,
+        }
+    }
+
+    ///
+    /// Returns the current morph target weights of the mesh with the given index, where the given weights are used if the mesh is not animated.
+    ///
+    pub fn morph_weights(&self, mesh: usize, weights: &[f32]) -> Vec<f32> {
+        let sample = |clip: &PlayingClip| {
+            self.clips[clip.index]
+                .morph_weights(self.clip_time(clip), mesh)
+                .filter(|w| w.len() == weights.len())
+                .unwrap_or(weights.to_vec())
+        };
+        let Some(current) = &self.current else {
+            return weights.to_vec();
+        };
+        let current = sample(current);
+        match &self.previous {
+            Some(previous) => {
+                let factor = self.crossfade.0 / self.crossfade.1;
+                sample(previous)
+                    .iter()
+                    .zip(current.iter())
+                    .map(|(a, b)| a + (b - a) * factor)
+                    .collect()
+            }
+            None => current,
+        }
+    }
+
+    fn clip_time(&self, clip: &PlayingClip) -> f32 {
+        let animation = &self.clips[clip.index];
+        if animation.duration <= 0.0 {
+            0.0
+        } else if clip.looping {
+            clip.time.rem_euclid(animation.duration)
+        } else {
+            clip.time.clamp(0.0, animation.duration)
+        }
+    }
+}
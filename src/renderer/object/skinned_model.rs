@@ -0,0 +1,486 @@
+use crate::renderer::*;
+
+///
+/// A triangle mesh on the CPU together with the indices and weights of the joints affecting each vertex, see [SkinnedMesh].
+///
+#[derive(Clone, Debug)]
+pub struct CpuSkinnedMesh {
+    /// The name of the mesh.
+    pub name: String,
+    /// The triangle mesh in its bind pose.
+    pub mesh: CpuMesh,
+    /// The indices of the (up to) four joints affecting each vertex.
+    pub joint_indices: Vec<[u16; 4]>,
+    /// The weights of the (up to) four joints affecting each vertex, which should sum to one.
+    pub joint_weights: Vec<Vec4>,
+    /// The morph targets of the mesh, which are applied before the skinning, see [SkinnedMesh::set_morph_targets].
+    pub morph_targets: CpuMorphTargets,
+    /// Optional index into [CpuSkinnedModel::materials], indicating which material should be applied to the mesh.
+    pub material_index: Option<usize>,
+}
+
+///
+/// A model on the CPU consisting of meshes deformed by a single [Skeleton], the materials of the meshes and the animations of the skeleton.
+/// Use it to construct a [SkinnedModel].
+///
+#[derive(Clone, Debug)]
+pub struct CpuSkinnedModel {
+    /// The name of the model.
+    pub name: String,
+    /// The meshes.
+    pub geometries: Vec<CpuSkinnedMesh>,
+    /// The materials referenced by [CpuSkinnedMesh::material_index].
+    pub materials: Vec<CpuMaterial>,
+    /// The skeleton deforming the meshes.
+    pub skeleton: Skeleton,
+    /// The animations of the skeleton.
+    pub animations: Vec<AnimationClip>,
+}
+
+#[cfg(feature = "gltf")]
+impl CpuSkinnedModel {
+    ///
+    /// Deserializes the glTF file at the given path, which must be loaded together with its dependencies into the raw assets, for example using `three_d_asset::io::load`.
+    /// The meshes, morph targets, joints and animations of the first skin in the file are used, while everything else in the file is ignored.
+    /// The glTF file and its dependencies are removed from the raw assets.
+    ///
+    pub fn deserialize_gltf(
+        raw_assets: &mut three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+        let mut model = gltf_skin::parse(raw_assets, path)?;
+        let cpu_model: CpuModel = raw_assets
+            .deserialize(path)
+            .map_err(super::gltf_io::error)?;
+        model.materials = cpu_model.materials;
+        Ok(model)
+    }
+}
+
+#[cfg(feature = "gltf")]
+mod gltf_skin {
+    use super::super::gltf_io::{self, error};
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    pub fn parse(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: &Path,
+    ) -> Result<CpuSkinnedModel, RendererError> {
+        let (document, buffers) = gltf_io::parse(raw_assets, path)?;
+        let buffer_data = |buffer: gltf::Buffer| Some(buffers[buffer.index()].as_slice());
+
+        let skin = document
+            .skins()
+            .next()
+            .ok_or_else(|| error("the model does not contain a skin"))?;
+
+        // The skeleton
+        let mut parents = vec![None; document.nodes().count()];
+        for node in document.nodes() {
+            for child in node.children() {
+                parents[child.index()] = Some(node.index());
+            }
+        }
+        let nodes = document.nodes().collect::<Vec<_>>();
+        let global_transformation = |mut index: Option<usize>| {
+            let mut transformation = Mat4::identity();
+            while let Some(i) = index {
+                transformation = Mat4::from(nodes[i].transform().matrix()) * transformation;
+                index = parents[i];
+            }
+            transformation
+        };
+        let joint_indices = skin
+            .joints()
+            .enumerate()
+            .map(|(i, node)| (node.index(), i))
+            .collect::<HashMap<_, _>>();
+        let inverse_bind_matrices = skin
+            .reader(buffer_data)
+            .read_inverse_bind_matrices()
+            .map(|matrices| matrices.map(Mat4::from).collect::<Vec<_>>());
+        let mut root_transformation = None;
+        let mut joints = Vec::new();
+        for (i, node) in skin.joints().enumerate() {
+            // The parent is the closest ancestor which is also a joint
+            let mut parent = parents[node.index()];
+            while let Some(p) = parent {
+                if joint_indices.contains_key(&p) {
+                    break;
+                }
+                parent = parents[p];
+            }
+            let parent = parent.map(|p| joint_indices[&p]);
+            if parent.is_none() && root_transformation.is_none() {
+                root_transformation = Some(global_transformation(parents[node.index()]));
+            }
+            let (translation, rotation, scale) = node.transform().decomposed();
+            joints.push(Joint {
+                name: node
+                    .name()
+                    .map(|s| s.to_string())
+                    .unwrap_or(format!("index {}", node.index())),
+                parent,
+                rest_pose: JointPose {
+                    translation: translation.into(),
+                    rotation: Quat::new(rotation[3], rotation[0], rotation[1], rotation[2]),
+                    scale: scale.into(),
+                },
+                inverse_bind_matrix: inverse_bind_matrices
+                    .as_ref()
+                    .and_then(|m| m.get(i).copied())
+                    .unwrap_or(Mat4::identity()),
+            });
+        }
+        let mut skeleton = Skeleton::new(joints);
+        skeleton.transformation = root_transformation.unwrap_or(Mat4::identity());
+
+        // The meshes deformed by the skin
+        let mut geometries = Vec::new();
+        let mut geometry_nodes = Vec::new();
+        for node in document.nodes() {
+            let (Some(mesh), Some(node_skin)) = (node.mesh(), node.skin()) else {
+                continue;
+            };
+            if node_skin.index() != skin.index() {
+                continue;
+            }
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    continue;
+                }
+                let reader = primitive.reader(buffer_data);
+                let (Some(positions), Some(joint_indices), Some(joint_weights)) = (
+                    reader.read_positions(),
+                    reader.read_joints(0),
+                    reader.read_weights(0),
+                ) else {
+                    continue;
+                };
+                geometries.push(CpuSkinnedMesh {
+                    name: mesh
+                        .name()
+                        .map(|s| s.to_string())
+                        .unwrap_or(format!("index {}", mesh.index())),
+                    mesh: CpuMesh {
+                        positions: Positions::F32(positions.map(|p| p.into()).collect()),
+                        normals: reader
+                            .read_normals()
+                            .map(|values| values.map(|n| n.into()).collect()),
+                        tangents: reader
+                            .read_tangents()
+                            .map(|values| values.map(|t| t.into()).collect()),
+                        uvs: reader
+                            .read_tex_coords(0)
+                            .map(|values| values.into_f32().map(|uv| uv.into()).collect()),
+                        colors: reader.read_colors(0).map(|values| {
+                            values
+                                .into_rgba_u8()
+                                .map(|c| Srgba::new(c[0], c[1], c[2], c[3]))
+                                .collect()
+                        }),
+                        indices: reader
+                            .read_indices()
+                            .map(|values| match values {
+                                gltf::mesh::util::ReadIndices::U8(iter) => {
+                                    Indices::U8(iter.collect())
+                                }
+                                gltf::mesh::util::ReadIndices::U16(iter) => {
+                                    Indices::U16(iter.collect())
+                                }
+                                gltf::mesh::util::ReadIndices::U32(iter) => {
+                                    Indices::U32(iter.collect())
+                                }
+                            })
+                            .unwrap_or(Indices::None),
+                    },
+                    joint_indices: joint_indices.into_u16().collect(),
+                    joint_weights: joint_weights.into_f32().map(|w| w.into()).collect(),
+                    morph_targets: gltf_io::morph_targets(&node, &primitive, &buffers),
+                    material_index: primitive.material().index(),
+                });
+                geometry_nodes.push(node.index());
+            }
+        }
+
+        // The animations of the joints
+        let mut animations = Vec::new();
+        for animation in document.animations() {
+            let mut channels = Vec::new();
+            let mut morph_channels = Vec::new();
+            let mut duration = 0.0f32;
+            for channel in animation.channels() {
+                let node = channel.target().node().index();
+                let joint = joint_indices.get(&node).copied();
+                if joint.is_none() && !geometry_nodes.contains(&node) {
+                    continue;
+                }
+                let reader = channel.reader(buffer_data);
+                let (Some(times), Some(outputs)) = (reader.read_inputs(), reader.read_outputs())
+                else {
+                    continue;
+                };
+                let times = times.collect::<Vec<_>>();
+                if times.is_empty() {
+                    continue;
+                }
+                duration = duration.max(*times.last().unwrap());
+                // Cubic spline key frames contain an in-tangent, a value and an out-tangent, of which only the value is used
+                let (interpolation, values) = match channel.sampler().interpolation() {
+                    gltf::animation::Interpolation::Step => (Interpolation::Nearest, (1, 0)),
+                    gltf::animation::Interpolation::Linear => (Interpolation::Linear, (1, 0)),
+                    gltf::animation::Interpolation::CubicSpline => (Interpolation::Linear, (3, 1)),
+                };
+                let mut key_frames = KeyFrames {
+                    times,
+                    interpolation,
+                    ..Default::default()
+                };
+                match outputs {
+                    gltf::animation::util::ReadOutputs::MorphTargetWeights(weights) => {
+                        let weights = weights.into_f32().collect::<Vec<_>>();
+                        let count = weights.len() / (key_frames.times.len() * values.0);
+                        if count == 0 {
+                            continue;
+                        }
+                        key_frames.weights = Some(
+                            weights
+                                .chunks(count)
+                                .skip(values.1)
+                                .step_by(values.0)
+                                .map(|w| w.to_vec())
+                                .collect(),
+                        );
+                        let key_frames = Arc::new(key_frames);
+                        for (geometry, _) in geometry_nodes
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, n)| **n == node)
+                        {
+                            morph_channels.push((geometry, key_frames.clone()));
+                        }
+                        continue;
+                    }
+                    gltf::animation::util::ReadOutputs::Translations(translations) => {
+                        key_frames.translations = Some(
+                            translations
+                                .skip(values.1)
+                                .step_by(values.0)
+                                .map(|t| t.into())
+                                .collect(),
+                        );
+                    }
+                    gltf::animation::util::ReadOutputs::Rotations(rotations) => {
+                        key_frames.rotations = Some(
+                            rotations
+                                .into_f32()
+                                .skip(values.1)
+                                .step_by(values.0)
+                                .map(|r| Quat::new(r[3], r[0], r[1], r[2]))
+                                .collect(),
+                        );
+                    }
+                    gltf::animation::util::ReadOutputs::Scales(scales) => {
+                        key_frames.scales = Some(
+                            scales
+                                .skip(values.1)
+                                .step_by(values.0)
+                                .map(|s| s.into())
+                                .collect(),
+                        );
+                    }
+                }
+                if let Some(joint) = joint {
+                    channels.push((joint, Arc::new(key_frames)));
+                }
+            }
+            if !channels.is_empty() || !morph_channels.is_empty() {
+                animations.push(AnimationClip {
+                    name: animation.name().map(|s| s.to_string()),
+                    duration,
+                    channels,
+                    morph_channels,
+                    events: Vec::new(),
+                });
+            }
+        }
+
+        Ok(CpuSkinnedModel {
+            name: path.to_string_lossy().to_string(),
+            geometries,
+            materials: Vec::new(),
+            skeleton,
+            animations,
+        })
+    }
+}
+
+///
+/// A 3D model consisting of a set of [Gm]s with [SkinnedMesh]es as the geometries, which are deformed by a [Skeleton] animated by an [AnimationMixer],
+/// and a [material] type specified by the generic parameter.
+///
+pub struct SkinnedModel<M: Material> {
+    parts: Vec<Gm<SkinnedMesh, M>>,
+    skeleton: Skeleton,
+    mixer: AnimationMixer,
+    morph_weights: Vec<Vec<f32>>,
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default> SkinnedModel<M> {
+    ///
+    /// Constructs a [SkinnedModel] from a [CpuSkinnedModel]. The first animation, if any, is played in a loop.
+    ///
+    pub fn new(context: &Context, cpu_model: &CpuSkinnedModel) -> Result<Self, RendererError> {
+        let materials = cpu_model
+            .materials
+            .iter()
+            .map(|m| M::from_cpu_material(context, m))
+            .collect::<Vec<_>>();
+        let joint_count = cpu_model.skeleton.joints().len() as u32;
+        let mut parts = Vec::new();
+        for geometry in cpu_model.geometries.iter() {
+            let material = if let Some(material_index) = geometry.material_index {
+                materials
+                    .get(material_index)
+                    .ok_or_else(|| {
+                        RendererError::MissingMaterial(
+                            material_index.to_string(),
+                            geometry.name.clone(),
+                        )
+                    })?
+                    .clone()
+            } else {
+                M::default()
+            };
+            let mut mesh = SkinnedMesh::new(
+                context,
+                &geometry.mesh,
+                &geometry.joint_indices,
+                &geometry.joint_weights,
+                joint_count,
+            );
+            mesh.set_morph_targets(&geometry.morph_targets.targets);
+            parts.push(Gm::new(mesh, material));
+        }
+        let mut mixer = AnimationMixer::new(cpu_model.animations.clone());
+        if !cpu_model.animations.is_empty() {
+            mixer.layers_mut()[0].player.play(0, true, 0.0);
+        }
+        let mut model = Self {
+            parts,
+            skeleton: cpu_model.skeleton.clone(),
+            mixer,
+            morph_weights: cpu_model
+                .geometries
+                .iter()
+                .map(|g| g.morph_targets.weights.clone())
+                .collect(),
+        };
+        model.set_pose(&model.mixer.pose(&model.skeleton));
+        model.update_morph_weights();
+        Ok(model)
+    }
+}
+
+impl<M: Material> SkinnedModel<M> {
+    ///
+    /// Returns the skeleton deforming the meshes.
+    ///
+    pub fn skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    ///
+    /// Returns the player of the base layer of the animation mixer, which can be used to start, stop and blend animations.
+    ///
+    pub fn player(&self) -> &AnimationPlayer {
+        &self.mixer.layers()[0].player
+    }
+
+    ///
+    /// Returns the player of the base layer of the animation mixer, which can be used to start, stop and blend animations.
+    ///
+    pub fn player_mut(&mut self) -> &mut AnimationPlayer {
+        &mut self.mixer.layers_mut()[0].player
+    }
+
+    ///
+    /// Returns the mixer of the animations of the skeleton, which can be used to play animations in layers, for example on the upper body only.
+    ///
+    pub fn mixer(&self) -> &AnimationMixer {
+        &self.mixer
+    }
+
+    ///
+    /// Returns the mixer of the animations of the skeleton, which can be used to play animations in layers, for example on the upper body only.
+    ///
+    pub fn mixer_mut(&mut self) -> &mut AnimationMixer {
+        &mut self.mixer
+    }
+
+    ///
+    /// For updating the animation. The time parameter should be some continious time, for example the time since start.
+    /// Returns the animation events which have been passed since the last update, for example footsteps.
+    ///
+    pub fn animate(&mut self, time: f32) -> Vec<AnimationEvent> {
+        let events = self.mixer.update(time);
+        let pose = self.mixer.pose(&self.skeleton);
+        self.set_pose(&pose);
+        self.update_morph_weights();
+        events
+    }
+
+    fn update_morph_weights(&mut self) {
+        for (i, (part, weights)) in self
+            .parts
+            .iter_mut()
+            .zip(self.morph_weights.iter())
+            .enumerate()
+        {
+            if !weights.is_empty() {
+                part.geometry
+                    .set_morph_weights(&self.mixer.morph_weights(i, weights));
+            }
+        }
+    }
+
+    ///
+    /// Deforms the meshes according to the given pose of the skeleton, which is overwritten by the animation on the next call to [Self::animate].
+    ///
+    pub fn set_pose(&mut self, pose: &Pose) {
+        let joint_matrices = self.skeleton.skinning_matrices(pose);
+        for part in self.parts.iter_mut() {
+            part.geometry.set_joint_matrices(&joint_matrices);
+        }
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a SkinnedModel<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts
+            .iter()
+            .map(|m| m as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<M: Material> std::ops::Deref for SkinnedModel<M> {
+    type Target = Vec<Gm<SkinnedMesh, M>>;
+    fn deref(&self) -> &Self::Target {
+        &self.parts
+    }
+}
+
+impl<M: Material> std::ops::DerefMut for SkinnedModel<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.parts
+    }
+}
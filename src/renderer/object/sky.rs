@@ -0,0 +1,196 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A procedural sky which is calculated from the direction of the sun and the turbidity of the atmosphere
+/// using the analytic daylight model by Preetham et al.
+/// This gives a plausible outdoor sky for any time of day without loading an HDR image,
+/// and the sky can be animated by calling [Sky::set_sun] every time the sun moves.
+///
+/// The sky is rendered into a cube map, see [Sky::texture], which can also be used to light the scene,
+/// for example with [AmbientLight::new_with_environment].
+///
+pub struct Sky {
+    context: Context,
+    skybox: Skybox,
+    texture_size: u32,
+    sun_direction: Vec3,
+    turbidity: f32,
+    intensity: f32,
+}
+
+impl Sky {
+    ///
+    /// Creates a new sky with the sun in the given direction (pointing from the scene towards the sun, with positive y being up)
+    /// and the given turbidity which is a measure of the haze in the atmosphere.
+    /// A turbidity of 2 gives a very clear sky while 10 gives a hazy sky, the model is valid for values in the range `[2..10]`.
+    ///
+    pub fn new(context: &Context, sun_direction: Vec3, turbidity: f32) -> Self {
+        Self::new_with_texture_size(context, 256, sun_direction, turbidity, 0.05)
+    }
+
+    ///
+    /// Creates a new sky like [Sky::new] but where the size of each side of the cube map texture and the intensity of the sky are specified.
+    /// The intensity scales the luminance of the sky model, which is in kilocandela per square meter, to the range used when rendering.
+    ///
+    pub fn new_with_texture_size(
+        context: &Context,
+        texture_size: u32,
+        sun_direction: Vec3,
+        turbidity: f32,
+        intensity: f32,
+    ) -> Self {
+        let texture = Self::generate(context, texture_size, sun_direction, turbidity, intensity);
+        Self {
+            context: context.clone(),
+            skybox: Skybox::new_with_texture(context, texture),
+            texture_size,
+            sun_direction,
+            turbidity,
+            intensity,
+        }
+    }
+
+    ///
+    /// Updates the direction of the sun and the turbidity and regenerates the sky texture.
+    /// Note that an environment computed from the previous [Sky::texture], for example in an [AmbientLight], is not updated.
+    ///
+    pub fn set_sun(&mut self, sun_direction: Vec3, turbidity: f32) {
+        self.sun_direction = sun_direction;
+        self.turbidity = turbidity;
+        let texture = Self::generate(
+            &self.context,
+            self.texture_size,
+            sun_direction,
+            turbidity,
+            self.intensity,
+        );
+        self.skybox = Skybox::new_with_texture(&self.context, texture);
+    }
+
+    ///
+    /// Returns the direction towards the sun.
+    /// The negated direction can be used as the direction of a [DirectionalLight] representing the sun.
+    ///
+    pub fn sun_direction(&self) -> Vec3 {
+        self.sun_direction
+    }
+
+    ///
+    /// Returns the turbidity of the atmosphere.
+    ///
+    pub fn turbidity(&self) -> f32 {
+        self.turbidity
+    }
+
+    ///
+    /// Returns a reference to the cube map texture containing the sky.
+    ///
+    pub fn texture(&self) -> &Arc<TextureCubeMap> {
+        self.skybox.texture()
+    }
+
+    fn skybox(&self) -> &Skybox {
+        &self.skybox
+    }
+
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn generate(
+        context: &Context,
+        texture_size: u32,
+        sun_direction: Vec3,
+        turbidity: f32,
+        intensity: f32,
+    ) -> Arc<TextureCubeMap> {
+        let mut texture = TextureCubeMap::new_empty::<[f16; 4]>(
+            context,
+            texture_size,
+            texture_size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let viewport = Viewport::new_at_origo(texture_size, texture_size);
+        for side in CubeMapSide::iter() {
+            texture
+                .as_color_target(&[side], None)
+                .clear(ClearState::default())
+                .apply_screen_material(
+                    &SkyMaterial {
+                        side,
+                        sun_direction: sun_direction.normalize(),
+                        turbidity: turbidity.clamp(2.0, 10.0),
+                        intensity,
+                    },
+                    &Camera::new_2d(viewport),
+                    &[],
+                );
+        }
+        Arc::new(texture)
+    }
+}
+
+impl<'a> IntoIterator for &'a Sky {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Sky {
+    impl_geometry_body!(skybox);
+}
+
+impl Object for Sky {
+    impl_object_body!(skybox);
+}
+
+struct SkyMaterial {
+    side: CubeMapSide,
+    sun_direction: Vec3,
+    turbidity: f32,
+    intensity: f32,
+}
+
+impl Material for SkyMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/sky.frag")
+        )
+    }
+
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1000u32
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("direction", self.side.direction());
+        program.use_uniform("up", self.side.up());
+        program.use_uniform("sunDirection", self.sun_direction);
+        program.use_uniform("turbidity", self.turbidity);
+        program.use_uniform("intensity", self.intensity);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
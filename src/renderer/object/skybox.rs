@@ -16,6 +16,7 @@ impl Skybox {
     /// Creates a new skybox with the given [CpuTexture]s placed at the indicated sides of the skybox.
     /// All of the cpu textures must contain data with the same [TextureDataType].
     ///
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new(
         context: &Context,
         right: &CpuTexture,
@@ -50,6 +51,7 @@ impl Skybox {
     ///
     /// Creates a new skybox with a cube texture generated from the equirectangular texture given as input.
     ///
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new_from_equirectangular(context: &Context, cpu_texture: &CpuTexture) -> Self {
         let texture = match cpu_texture.data {
             TextureData::RgbaU8(_) | TextureData::RgbU8(_) => {
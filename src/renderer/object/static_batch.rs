@@ -0,0 +1,192 @@
+use crate::renderer::*;
+use three_d_asset::Primitive;
+
+///
+/// Merges the geometry of many static models and meshes, for example the pieces of a modular building kit, into one mesh for each material.
+/// The geometry is transformed into world space when merged, so the resulting [CpuModel] can be rendered with one draw call per material using [Model::new].
+///
+/// Materials with the same name are considered the same material, in which case the first added material with that name is used.
+/// Animations are ignored and point clouds are skipped, since the merged geometry is static.
+///
+#[derive(Clone, Debug, Default)]
+pub struct StaticBatch {
+    materials: Vec<CpuMaterial>,
+    meshes: Vec<Vec<CpuMesh>>,
+    default_meshes: Vec<CpuMesh>,
+}
+
+impl StaticBatch {
+    ///
+    /// Creates a new empty batch.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds all the triangle geometries of the given model with the given transformation applied on top of the transformation of each geometry.
+    ///
+    pub fn add_model(
+        &mut self,
+        cpu_model: &CpuModel,
+        transformation: Mat4,
+    ) -> Result<(), RendererError> {
+        for primitive in cpu_model.geometries.iter() {
+            if let three_d_asset::Geometry::Triangles(mesh) = &primitive.geometry {
+                let material = primitive
+                    .material_index
+                    .map(|i| {
+                        cpu_model.materials.get(i).ok_or_else(|| {
+                            RendererError::MissingMaterial(i.to_string(), primitive.name.clone())
+                        })
+                    })
+                    .transpose()?;
+                self.add_mesh(mesh, transformation * primitive.transformation, material);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Adds the given mesh with the given transformation and material.
+    /// Meshes without a material are merged into one mesh, which is rendered with the default material.
+    ///
+    pub fn add_mesh(
+        &mut self,
+        cpu_mesh: &CpuMesh,
+        transformation: Mat4,
+        material: Option<&CpuMaterial>,
+    ) {
+        let mesh = transformed(cpu_mesh, transformation);
+        match material {
+            Some(material) => {
+                let index = self
+                    .materials
+                    .iter()
+                    .position(|m| m.name == material.name)
+                    .unwrap_or_else(|| {
+                        self.materials.push(material.clone());
+                        self.meshes.push(Vec::new());
+                        self.materials.len() - 1
+                    });
+                self.meshes[index].push(mesh);
+            }
+            None => self.default_meshes.push(mesh),
+        }
+    }
+
+    ///
+    /// Returns the merged model with one geometry for each material.
+    ///
+    pub fn build(&self) -> CpuModel {
+        let mut geometries = Vec::new();
+        let primitive = |name: String, meshes: &[CpuMesh], material_index| Primitive {
+            name,
+            transformation: Mat4::identity(),
+            animations: Vec::new(),
+            geometry: three_d_asset::Geometry::Triangles(merge_meshes(meshes)),
+            material_index,
+        };
+        for (index, (material, meshes)) in self.materials.iter().zip(self.meshes.iter()).enumerate()
+        {
+            geometries.push(primitive(material.name.clone(), meshes, Some(index)));
+        }
+        if !self.default_meshes.is_empty() {
+            geometries.push(primitive("default".to_owned(), &self.default_meshes, None));
+        }
+        CpuModel {
+            name: "static batch".to_owned(),
+            geometries,
+            materials: self.materials.clone(),
+        }
+    }
+}
+
+///
+/// Merges the given meshes, which must already be transformed into the same coordinate system, into one mesh with 32 bit indices.
+/// The normals, tangents and uv coordinates are only kept if all the meshes have them, while vertices without colors are given a white color if some meshes have colors.
+///
+pub fn merge_meshes(meshes: &[CpuMesh]) -> CpuMesh {
+    let all = |f: fn(&CpuMesh) -> bool| !meshes.is_empty() && meshes.iter().all(f);
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    let mut normals = all(|m| m.normals.is_some()).then(Vec::new);
+    let mut tangents = all(|m| m.tangents.is_some()).then(Vec::new);
+    let mut uvs = all(|m| m.uvs.is_some()).then(Vec::new);
+    let mut colors = meshes.iter().any(|m| m.colors.is_some()).then(Vec::new);
+    for mesh in meshes {
+        let offset = positions.len() as u32;
+        let vertex_count = mesh.vertex_count();
+        positions.extend(mesh.positions.to_f32());
+        match mesh.indices.to_u32() {
+            Some(mesh_indices) => indices.extend(mesh_indices.into_iter().map(|i| i + offset)),
+            None => indices.extend(offset..offset + vertex_count as u32),
+        }
+        if let Some(normals) = &mut normals {
+            normals.extend(mesh.normals.as_ref().unwrap());
+        }
+        if let Some(tangents) = &mut tangents {
+            tangents.extend(mesh.tangents.as_ref().unwrap());
+        }
+        if let Some(uvs) = &mut uvs {
+            uvs.extend(mesh.uvs.as_ref().unwrap());
+        }
+        if let Some(colors) = &mut colors {
+            match &mesh.colors {
+                Some(mesh_colors) => colors.extend(mesh_colors),
+                None => colors.extend(std::iter::repeat_n(Srgba::WHITE, vertex_count)),
+            }
+        }
+    }
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals,
+        tangents,
+        uvs,
+        colors,
+    }
+}
+
+fn transformed(cpu_mesh: &CpuMesh, transformation: Mat4) -> CpuMesh {
+    let linear = Mat3::from_cols(
+        transformation.x.truncate(),
+        transformation.y.truncate(),
+        transformation.z.truncate(),
+    );
+    let normal_transformation = linear.invert().unwrap_or(Mat3::identity()).transpose();
+    let mut mesh = cpu_mesh.clone();
+    mesh.positions = Positions::F32(
+        cpu_mesh
+            .positions
+            .to_f32()
+            .into_iter()
+            .map(|p| (transformation * p.extend(1.0)).truncate())
+            .collect(),
+    );
+    if let Some(normals) = &mut mesh.normals {
+        for n in normals.iter_mut() {
+            *n = (normal_transformation * *n).normalize();
+        }
+    }
+    // A mirroring transformation flips the winding order of the triangles and the handedness of the tangent frames
+    let mirrored = linear.determinant() < 0.0;
+    if let Some(tangents) = &mut mesh.tangents {
+        // Tangents lie in the surface, so they are transformed like positions
+        for t in tangents.iter_mut() {
+            let w = if mirrored { -t.w } else { t.w };
+            *t = (linear * t.truncate()).normalize().extend(w);
+        }
+    }
+    if mirrored {
+        let mut indices = mesh
+            .indices
+            .to_u32()
+            .unwrap_or_else(|| (0..mesh.vertex_count() as u32).collect());
+        for triangle in indices.chunks_mut(3) {
+            triangle.swap(1, 2);
+        }
+        mesh.indices = Indices::U32(indices);
+    }
+    mesh
+}
@@ -17,6 +17,11 @@ const VERTICES_PER_SIDE: usize = 33;
 ///
 /// A terrain geometry based on a height map and with an applied material.
 ///
+/// The terrain is divided into square patches which are culled individually when outside the view of the camera
+/// and rendered with a level of detail depending on the distance to the center of the terrain, see [Terrain::set_lod] and [Terrain::set_center].
+/// Each patch has a skirt hanging down from its sides, which hides the cracks between patches with different levels of detail.
+/// Use a [SplatMaterial] to blend a set of tiled textures across the terrain and [height_map_from_texture] to create the height map from an image.
+///
 pub struct Terrain<M: Material> {
     context: Context,
     center: (i32, i32),
@@ -183,6 +188,7 @@ impl<M: Material + Clone> Terrain<M> {
         })
     }
 
+    #[allow(clippy::arc_with_non_send_sync)]
     fn indices(context: &Context, resolution: u32) -> Arc<ElementBuffer> {
         let mut indices: Vec<u32> = Vec::new();
         let stride = VERTICES_PER_SIDE as u32;
@@ -197,6 +203,17 @@ impl<M: Material + Clone> Terrain<M> {
                 indices.push(r * resolution + resolution + (c * resolution + resolution) * stride);
             }
         }
+        for edge in 0..4 {
+            for k in (0..stride - 1).step_by(resolution as usize) {
+                let a = edge_vertex_id(edge, k as usize) as u32;
+                let b = edge_vertex_id(edge, (k + resolution) as usize) as u32;
+                let a_skirt = skirt_vertex_id(edge, k as usize) as u32;
+                let b_skirt = skirt_vertex_id(edge, (k + resolution) as usize) as u32;
+                // Both sides of the skirt are added since it is visible from both sides depending on the side of the patch
+                indices.extend([a, b, a_skirt, a_skirt, b, b_skirt]);
+                indices.extend([a, a_skirt, b, a_skirt, b_skirt, b]);
+            }
+        }
         Arc::new(ElementBuffer::new_with_data(context, &indices))
     }
 }
@@ -214,6 +231,47 @@ impl<'a, M: Material> IntoIterator for &'a Terrain<M> {
     }
 }
 
+///
+/// Creates a height map function which can be used to construct a [Terrain] from the first channel of the given texture.
+/// The texture covers the area in the xz plane from `origin` to `origin + size`, where the x axis of the texture follows the x axis
+/// and the y axis of the texture follows the z axis, and is bilinearly interpolated and clamped to the edges outside the area.
+/// The values are scaled by `height_scale`, where the unsigned byte formats are first normalized to the range `[0..1]`.
+///
+pub fn height_map_from_texture(
+    texture: &CpuTexture,
+    origin: Vec2,
+    size: Vec2,
+    height_scale: f32,
+) -> Arc<dyn Fn(f32, f32) -> f32 + Send + Sync> {
+    let heights: Vec<f32> = match &texture.data {
+        TextureData::RU8(data) => data.iter().map(|v| *v as f32 / 255.0).collect(),
+        TextureData::RgU8(data) => data.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RgbU8(data) => data.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RgbaU8(data) => data.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RF16(data) => data.iter().map(|v| v.to_f32()).collect(),
+        TextureData::RgF16(data) => data.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RgbF16(data) => data.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RgbaF16(data) => data.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RF32(data) => data.clone(),
+        TextureData::RgF32(data) => data.iter().map(|v| v[0]).collect(),
+        TextureData::RgbF32(data) => data.iter().map(|v| v[0]).collect(),
+        TextureData::RgbaF32(data) => data.iter().map(|v| v[0]).collect(),
+    };
+    let width = texture.width as usize;
+    let height = texture.height as usize;
+    Arc::new(move |x, z| {
+        let u = ((x - origin.x) / size.x).clamp(0.0, 1.0) * (width - 1) as f32;
+        let v = ((z - origin.y) / size.y).clamp(0.0, 1.0) * (height - 1) as f32;
+        let (i, j) = (u.floor() as usize, v.floor() as usize);
+        let (i1, j1) = ((i + 1).min(width - 1), (j + 1).min(height - 1));
+        let (fu, fv) = (u.fract(), v.fract());
+        let h = |i: usize, j: usize| heights[j * width + i];
+        let h0 = h(i, j) * (1.0 - fu) + h(i1, j) * fu;
+        let h1 = h(i, j1) * (1.0 - fu) + h(i1, j1) * fu;
+        height_scale * (h0 * (1.0 - fv) + h1 * fv)
+    })
+}
+
 fn patch_size(vertex_distance: f32) -> f32 {
     vertex_distance * (VERTICES_PER_SIDE - 1) as f32
 }
@@ -224,6 +282,25 @@ fn half_patches_per_side(vertex_distance: f32, side_length: f32) -> i32 {
     (patches_per_side as i32 - 1) / 2
 }
 
+///
+/// Returns the grid index of the `k`th vertex along the given edge of a patch.
+///
+fn edge_vertex_id(edge: usize, k: usize) -> usize {
+    match edge {
+        0 => k,
+        1 => (VERTICES_PER_SIDE - 1) * VERTICES_PER_SIDE + k,
+        2 => k * VERTICES_PER_SIDE,
+        _ => k * VERTICES_PER_SIDE + VERTICES_PER_SIDE - 1,
+    }
+}
+
+///
+/// Returns the index of the skirt vertex below the `k`th vertex along the given edge of a patch.
+///
+fn skirt_vertex_id(edge: usize, k: usize) -> usize {
+    VERTICES_PER_SIDE * VERTICES_PER_SIDE + edge * VERTICES_PER_SIDE + k
+}
+
 fn pos2patch(vertex_distance: f32, position: Vec2) -> (i32, i32) {
     let patch_size = vertex_distance * (VERTICES_PER_SIDE - 1) as f32;
     (
@@ -291,6 +368,13 @@ impl TerrainPatch {
                 data[vertex_id] = vec3(x, height_map(x, z), z);
             }
         }
+        // The skirts reach as far down as the largest height difference between two levels of detail is expected to be
+        let skirt_depth = 16.0 * vertex_distance;
+        for edge in 0..4 {
+            for k in 0..VERTICES_PER_SIDE {
+                data.push(data[edge_vertex_id(edge, k)] - vec3(0.0, skirt_depth, 0.0));
+            }
+        }
         data
     }
 
@@ -332,6 +416,11 @@ impl TerrainPatch {
                 data[vertex_id] = vec3(-dx, 2.0 * h, -dz).normalize();
             }
         }
+        for edge in 0..4 {
+            for k in 0..VERTICES_PER_SIDE {
+                data.push(data[edge_vertex_id(edge, k)]);
+            }
+        }
         data
     }
 }
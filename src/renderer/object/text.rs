@@ -0,0 +1,650 @@
+use crate::core::*;
+use crate::renderer::*;
+use ab_glyph::{Font, FontVec, GlyphId, ScaleFont};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The width of the atlas texture in texels.
+const ATLAS_WIDTH: usize = 1024;
+
+///
+/// A font loaded from a TrueType or OpenType font file together with a texture atlas containing a signed distance field of each of its glyphs,
+/// which allows the [Text] rendered with the font to stay sharp at any size.
+///
+pub struct FontAtlas {
+    font: FontVec,
+    texture: Texture2D,
+    glyphs: HashMap<char, GlyphInfo>,
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+}
+
+#[derive(Clone, Copy)]
+struct GlyphInfo {
+    id: GlyphId,
+    advance: f32,
+    /// The left, bottom, right and top edges of the glyph relative to the pen position, or `None` if the glyph is empty.
+    bounds: Option<Vec4>,
+    /// The left, top, right and bottom edges of the glyph in the atlas texture in uv coordinates.
+    uv_bounds: Vec4,
+}
+
+impl FontAtlas {
+    ///
+    /// Loads the font from the bytes of a TrueType or OpenType font file and creates an atlas with the printable ASCII and Latin-1 characters.
+    ///
+    pub fn new(context: &Context, font_data: &[u8]) -> Result<Self, RendererError> {
+        Self::new_with_characters(
+            context,
+            font_data,
+            (' '..='~').chain('\u{a1}'..='\u{ff}'),
+            48.0,
+        )
+    }
+
+    ///
+    /// Loads the font from the bytes of a TrueType or OpenType font file and creates an atlas with the given characters.
+    /// The glyph size is the height in texels of each glyph in the atlas; a larger size gives sharper corners at the cost of a larger texture.
+    ///
+    pub fn new_with_characters(
+        context: &Context,
+        font_data: &[u8],
+        characters: impl IntoIterator<Item = char>,
+        glyph_size: f32,
+    ) -> Result<Self, RendererError> {
+        let font =
+            FontVec::try_from_vec(font_data.to_vec()).map_err(|_| RendererError::InvalidFont)?;
+        let scaled_font = font.as_scaled(glyph_size);
+        // The distance in texels from the edge of a glyph where the signed distance field is clamped
+        let spread = (glyph_size / 8.0).ceil().max(2.0) as usize;
+
+        let mut glyphs = HashMap::new();
+        let mut sdfs = Vec::new();
+        for character in characters {
+            if glyphs.contains_key(&character) {
+                continue;
+            }
+            let id = font.glyph_id(character);
+            if id.0 == 0 && character != ' ' {
+                continue;
+            }
+            let mut info = GlyphInfo {
+                id,
+                advance: scaled_font.h_advance(id) / glyph_size,
+                bounds: None,
+                uv_bounds: vec4(0.0, 0.0, 0.0, 0.0),
+            };
+            if let Some(outlined) = font
+                .outline_glyph(id.with_scale_and_position(glyph_size, ab_glyph::point(0.0, 0.0)))
+            {
+                let px_bounds = outlined.px_bounds();
+                let width = px_bounds.width() as usize + 2 * spread;
+                let height = px_bounds.height() as usize + 2 * spread;
+                let mut coverage = vec![0.0; width * height];
+                outlined.draw(|x, y, c| {
+                    coverage[(y as usize + spread) * width + x as usize + spread] = c;
+                });
+                // The pixel bounds are given with the y axis pointing down
+                info.bounds = Some(
+                    vec4(
+                        px_bounds.min.x - spread as f32,
+                        -px_bounds.max.y - spread as f32,
+                        px_bounds.max.x + spread as f32,
+                        -px_bounds.min.y + spread as f32,
+                    ) / glyph_size,
+                );
+                sdfs.push((
+                    character,
+                    width,
+                    height,
+                    signed_distance_field(&coverage, width, height, spread as f32),
+                ));
+            }
+            glyphs.insert(character, info);
+        }
+
+        // Places the glyphs in rows from the top of the atlas
+        let atlas_width = sdfs
+            .iter()
+            .map(|(_, width, _, _)| *width + 1)
+            .max()
+            .unwrap_or(1)
+            .next_power_of_two()
+            .max(ATLAS_WIDTH);
+        let mut positions = Vec::new();
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for (_, width, height, _) in sdfs.iter() {
+            if x + width > atlas_width {
+                x = 0;
+                y += row_height + 1;
+                row_height = 0;
+            }
+            positions.push((x, y));
+            x += width + 1;
+            row_height = row_height.max(*height);
+        }
+        let atlas_height = (y + row_height).max(1).next_power_of_two();
+        let mut data = vec![0u8; atlas_width * atlas_height];
+        for ((character, width, height, sdf), (x, y)) in sdfs.iter().zip(positions) {
+            for row in 0..*height {
+                data[(y + row) * atlas_width + x..(y + row) * atlas_width + x + width]
+                    .copy_from_slice(&sdf[row * width..(row + 1) * width]);
+            }
+            // The rows are flipped when uploaded to the texture, so the top of the atlas is at v = 1
+            glyphs.get_mut(character).unwrap().uv_bounds = vec4(
+                x as f32 / atlas_width as f32,
+                1.0 - y as f32 / atlas_height as f32,
+                (x + width) as f32 / atlas_width as f32,
+                1.0 - (y + height) as f32 / atlas_height as f32,
+            );
+        }
+        let texture = Texture2D::new(
+            context,
+            &CpuTexture {
+                name: "font atlas".to_string(),
+                data: TextureData::RU8(data),
+                width: atlas_width as u32,
+                height: atlas_height as u32,
+                mip_map_filter: None,
+                wrap_s: Wrapping::ClampToEdge,
+                wrap_t: Wrapping::ClampToEdge,
+                ..Default::default()
+            },
+        );
+
+        // The metrics are relative to the height of the font, ie. the distance from the descent to the ascent
+        let height = scaled_font.height();
+        Ok(Self {
+            ascent: scaled_font.ascent() / height,
+            descent: scaled_font.descent() / height,
+            line_gap: scaled_font.line_gap() / height,
+            font,
+            texture,
+            glyphs,
+        })
+    }
+
+    ///
+    /// Returns the texture containing the signed distance field of each glyph, where the edge of the glyphs is at the value 0.5.
+    ///
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    fn glyph(&self, character: char) -> Option<&GlyphInfo> {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&'\u{fffd}'))
+            .or_else(|| self.glyphs.get(&'?'))
+    }
+
+    fn kerning(&self, first: GlyphId, second: GlyphId) -> f32 {
+        self.font.kern_unscaled(first, second) / self.font.height_unscaled()
+    }
+}
+
+///
+/// Computes a signed distance field from the coverage of each pixel, where the edge is at the value 128 and the values are clamped at the given distance in pixels from the edge.
+///
+fn signed_distance_field(coverage: &[f32], width: usize, height: usize, spread: f32) -> Vec<u8> {
+    let distance_to = |inside: bool| {
+        let mut grid = coverage
+            .iter()
+            .map(|c| if (*c >= 0.5) == inside { 0.0 } else { 1e20 })
+            .collect::<Vec<_>>();
+        squared_distance_transform(&mut grid, width, height);
+        grid
+    };
+    let to_inside = distance_to(true);
+    let to_outside = distance_to(false);
+    to_inside
+        .iter()
+        .zip(to_outside.iter())
+        .map(|(to_inside, to_outside)| {
+            // The distance is measured between pixel centers, so the edge is half a pixel from the nearest pixel on the other side
+            let distance = if *to_inside > 0.0 {
+                to_inside.sqrt() - 0.5
+            } else {
+                0.5 - to_outside.sqrt()
+            };
+            ((0.5 - 0.5 * distance / spread).clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect()
+}
+
+///
+/// Computes the squared euclidean distance to the nearest zero in the grid in place, using the algorithm by Felzenszwalb and Huttenlocher.
+///
+fn squared_distance_transform(grid: &mut [f32], width: usize, height: usize) {
+    let n = width.max(height);
+    let mut f = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    let mut v = vec![0usize; n];
+    let mut z = vec![0.0; n + 1];
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+        distance_transform_1d(&f[..height], &mut d, &mut v, &mut z);
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+    for y in 0..height {
+        f[..width].copy_from_slice(&grid[y * width..(y + 1) * width]);
+        distance_transform_1d(&f[..width], &mut d, &mut v, &mut z);
+        grid[y * width..(y + 1) * width].copy_from_slice(&d[..width]);
+    }
+}
+
+fn distance_transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let intersection = |q: usize, p: usize| {
+        ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2 * q - 2 * p) as f32
+    };
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = f32::NEG_INFINITY;
+    z[1] = f32::INFINITY;
+    for q in 1..f.len() {
+        let mut s = intersection(q, v[k]);
+        while s <= z[k] {
+            k -= 1;
+            s = intersection(q, v[k]);
+        }
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = f32::INFINITY;
+    }
+    k = 0;
+    for (q, d) in d.iter_mut().enumerate().take(f.len()) {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let offset = q as f32 - v[k] as f32;
+        *d = offset * offset + f[v[k]];
+    }
+}
+
+///
+/// The horizontal alignment of each line of a [Text] relative to its position.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum HorizontalAlignment {
+    /// The lines start at the position.
+    #[default]
+    Left,
+    /// The lines are centered at the position.
+    Center,
+    /// The lines end at the position.
+    Right,
+}
+
+///
+/// The vertical alignment of a [Text] relative to its position.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum VerticalAlignment {
+    /// The top of the first line is at the position.
+    Top,
+    /// The text is centered vertically at the position.
+    Middle,
+    /// The baseline of the first line is at the position.
+    #[default]
+    Baseline,
+    /// The bottom of the last line is at the position.
+    Bottom,
+}
+
+///
+/// Where a [Text] is placed and how it is oriented.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextPlacement {
+    /// The text is placed on top of everything else at the given position in physical pixels relative to the bottom left corner of the viewport.
+    /// The size of the text is in physical pixels.
+    Screen(PhysicalPoint),
+    /// The text is placed at the given position in world space, facing the camera and hidden behind the geometries in front of it, for example for labels and annotations.
+    /// The size of the text is in physical pixels, so it has the same size no matter how far it is from the camera.
+    Billboard(Vec3),
+    /// The text is placed in the xy plane transformed by the given local to world transformation, hidden behind the geometries in front of it.
+    /// The size of the text is in world space units.
+    World(Mat4),
+}
+
+///
+/// A text rendered with a [FontAtlas], either in screen space or in the 3D scene, see [TextPlacement].
+/// Use `\n` to start a new line.
+///
+pub struct Text {
+    context: Context,
+    font: Arc<FontAtlas>,
+    vertex_buffer: VertexBuffer,
+    glyph_positions: InstanceBuffer,
+    glyph_uvs: InstanceBuffer,
+    glyph_count: u32,
+    text: String,
+    horizontal_alignment: HorizontalAlignment,
+    vertical_alignment: VerticalAlignment,
+    bounds: (Vec2, Vec2),
+    /// The color of the text.
+    pub color: Srgba,
+    /// The height of the font, ie. the distance from the lowest to the highest point of the glyphs, in the units given by [Self::placement].
+    pub size: f32,
+    /// Where the text is placed.
+    pub placement: TextPlacement,
+}
+
+impl Text {
+    ///
+    /// Creates a new text with the given font.
+    ///
+    pub fn new(
+        context: &Context,
+        font: Arc<FontAtlas>,
+        text: &str,
+        size: f32,
+        placement: TextPlacement,
+    ) -> Self {
+        let mut t = Self {
+            context: context.clone(),
+            font,
+            vertex_buffer: VertexBuffer::new_with_data(
+                context,
+                &[
+                    vec2(0.0, 0.0),
+                    vec2(1.0, 0.0),
+                    vec2(1.0, 1.0),
+                    vec2(1.0, 1.0),
+                    vec2(0.0, 1.0),
+                    vec2(0.0, 0.0),
+                ],
+            ),
+            glyph_positions: InstanceBuffer::new(context),
+            glyph_uvs: InstanceBuffer::new(context),
+            glyph_count: 0,
+            text: text.to_string(),
+            horizontal_alignment: HorizontalAlignment::default(),
+            vertical_alignment: VerticalAlignment::default(),
+            bounds: (vec2(0.0, 0.0), vec2(0.0, 0.0)),
+            color: Srgba::BLACK,
+            size,
+            placement,
+        };
+        t.update();
+        t
+    }
+
+    ///
+    /// Returns the text.
+    ///
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    ///
+    /// Set the text.
+    ///
+    pub fn set_text(&mut self, text: &str) {
+        if self.text != text {
+            self.text = text.to_string();
+            self.update();
+        }
+    }
+
+    ///
+    /// Returns the font.
+    ///
+    pub fn font(&self) -> &Arc<FontAtlas> {
+        &self.font
+    }
+
+    ///
+    /// Set the font.
+    ///
+    pub fn set_font(&mut self, font: Arc<FontAtlas>) {
+        self.font = font;
+        self.update();
+    }
+
+    ///
+    /// Set the horizontal and vertical alignment of the text relative to its position.
+    ///
+    pub fn set_alignment(&mut self, horizontal: HorizontalAlignment, vertical: VerticalAlignment) {
+        self.horizontal_alignment = horizontal;
+        self.vertical_alignment = vertical;
+        self.update();
+    }
+
+    ///
+    /// Returns the minimum and maximum corner of the rectangle containing the text relative to its position, in the units given by [Self::placement].
+    ///
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        (self.bounds.0 * self.size, self.bounds.1 * self.size)
+    }
+
+    fn update(&mut self) {
+        let font = &self.font;
+        let line_height = font.ascent - font.descent + font.line_gap;
+        let lines = self.text.split('\n').collect::<Vec<_>>();
+        let top = font.ascent;
+        let bottom = font.descent - (lines.len() - 1) as f32 * line_height;
+        let offset_y = match self.vertical_alignment {
+            VerticalAlignment::Top => -top,
+            VerticalAlignment::Middle => -0.5 * (top + bottom),
+            VerticalAlignment::Baseline => 0.0,
+            VerticalAlignment::Bottom => -bottom,
+        };
+
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        let mut min_x = f32::MAX;
+        let mut max_x = f32::MIN;
+        for (i, line) in lines.iter().enumerate() {
+            let mut glyphs = Vec::new();
+            let mut x = 0.0;
+            let mut previous: Option<GlyphId> = None;
+            for character in line.chars() {
+                if let Some(glyph) = font.glyph(character) {
+                    if let Some(previous) = previous {
+                        x += font.kerning(previous, glyph.id);
+                    }
+                    glyphs.push((x, *glyph));
+                    x += glyph.advance;
+                    previous = Some(glyph.id);
+                }
+            }
+            let offset_x = match self.horizontal_alignment {
+                HorizontalAlignment::Left => 0.0,
+                HorizontalAlignment::Center => -0.5 * x,
+                HorizontalAlignment::Right => -x,
+            };
+            min_x = min_x.min(offset_x);
+            max_x = max_x.max(offset_x + x);
+            let baseline = offset_y - i as f32 * line_height;
+            for (x, glyph) in glyphs {
+                if let Some(bounds) = glyph.bounds {
+                    let origin = vec4(offset_x + x, baseline, offset_x + x, baseline);
+                    positions.push(origin + bounds);
+                    uvs.push(glyph.uv_bounds);
+                }
+            }
+        }
+        self.bounds = (
+            vec2(min_x.min(0.0), offset_y + bottom),
+            vec2(max_x.max(0.0), offset_y + top),
+        );
+        self.glyph_count = positions.len() as u32;
+        self.glyph_positions.fill(&positions);
+        self.glyph_uvs.fill(&uvs);
+    }
+}
+
+impl<'a> IntoIterator for &'a Text {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Text {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if attributes.normal || attributes.tangents {
+            panic!(
+                "the material requires normal or tangent attributes but a text does not provide it"
+            )
+        }
+        if self.glyph_count == 0 {
+            return;
+        }
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_uniform("size", self.size);
+        match self.placement {
+            TextPlacement::Screen(position) => {
+                program.use_uniform("placement", 0);
+                program.use_uniform("anchor", vec3(position.x, position.y, 0.0));
+            }
+            TextPlacement::Billboard(position) => {
+                program.use_uniform("placement", 1);
+                program.use_uniform("anchor", position);
+                program.use_uniform("viewProjection", camera.projection() * camera.view());
+            }
+            TextPlacement::World(transformation) => {
+                program.use_uniform("placement", 2);
+                program.use_uniform("transformation", transformation);
+                program.use_uniform("viewProjection", camera.projection() * camera.view());
+            }
+        }
+        program.use_vertex_attribute("position", &self.vertex_buffer);
+        program.use_instance_attribute("glyph_position", &self.glyph_positions);
+        if attributes.uv {
+            program.use_instance_attribute("glyph_uv", &self.glyph_uvs);
+        }
+        program.draw_arrays_instanced(render_states, viewport, 6, self.glyph_count);
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/text.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b1010u16
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        match self.placement {
+            TextPlacement::World(transformation) => {
+                let (min, max) = self.bounds();
+                AxisAlignedBoundingBox::new_with_transformed_positions(
+                    &[min.extend(0.0), max.extend(0.0)],
+                    &transformation,
+                )
+            }
+            _ => AxisAlignedBoundingBox::INFINITE,
+        }
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl Object for Text {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            TextMaterial { text: self },
+            lights,
+        )
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct TextMaterial<'a> {
+    text: &'a Text,
+}
+
+impl Material for TextMaterial<'_> {
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1011u32
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/text.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("textColor", self.text.color.to_linear_srgb());
+        program.use_texture("fontAtlas", &self.text.font.texture);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            depth_test: match self.text.placement {
+                TextPlacement::Screen(_) => DepthTest::Always,
+                _ => DepthTest::Less,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
@@ -135,6 +135,7 @@ impl<M: Material> Water<M> {
         self.patches.iter_mut().for_each(|m| m.animate(time));
     }
 
+    #[allow(clippy::arc_with_non_send_sync)]
     fn indices(context: &Context) -> Arc<ElementBuffer> {
         let mut indices: Vec<u32> = Vec::new();
         let stride = VERTICES_PER_SIDE as u32;
@@ -152,6 +153,7 @@ impl<M: Material> Water<M> {
         Arc::new(ElementBuffer::new_with_data(context, &indices))
     }
 
+    #[allow(clippy::arc_with_non_send_sync)]
     fn positions(context: &Context, vertex_distance: f32) -> Arc<VertexBuffer> {
         let mut data = vec![vec3(0.0, 0.0, 0.0); VERTICES_PER_SIDE * VERTICES_PER_SIDE];
         for r in 0..VERTICES_PER_SIDE {
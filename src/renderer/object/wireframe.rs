@@ -0,0 +1,239 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The edges of the triangles of a mesh drawn as lines with a width given in pixels, usually on top of the mesh, for example to inspect the triangulation of imported geometry.
+///
+/// The lines are drawn in the fragment shader using the distance to the edges of each triangle instead of rendering lines,
+/// so they are antialiased and have the same width on all graphics drivers.
+/// The wireframe is rendered after the opaque objects and lines hidden behind the mesh or other objects are not shown unless [Wireframe::show_hidden_lines] is true.
+/// See also [NormalGlyphs] for visualizing the normals of a mesh.
+///
+pub struct Wireframe {
+    context: Context,
+    positions: VertexBuffer,
+    barycentrics: VertexBuffer,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    /// The color of the lines.
+    pub color: Srgba,
+    /// The width of the lines in physical pixels.
+    pub width: f32,
+    /// Whether the lines hidden behind the mesh or other objects are also shown.
+    pub show_hidden_lines: bool,
+}
+
+impl Wireframe {
+    ///
+    /// Creates a black wireframe of the triangles of the given mesh.
+    /// Use the same transformation as the mesh, see [Wireframe::set_transformation], to draw the wireframe on top of it.
+    ///
+    pub fn new(context: &Context, cpu_mesh: &CpuMesh) -> Self {
+        Self::new_with_meshes(context, [(Mat4::identity(), cpu_mesh)])
+    }
+
+    ///
+    /// Creates a black wireframe of the triangles of all the meshes of the given model, where the transformation of each part of the model is applied.
+    ///
+    pub fn new_from_model(context: &Context, cpu_model: &CpuModel) -> Self {
+        Self::new_with_meshes(
+            context,
+            cpu_model
+                .geometries
+                .iter()
+                .filter_map(|primitive| match &primitive.geometry {
+                    CpuGeometry::Triangles(mesh) => Some((primitive.transformation, mesh)),
+                    _ => None,
+                }),
+        )
+    }
+
+    fn new_with_meshes<'a>(
+        context: &Context,
+        meshes: impl IntoIterator<Item = (Mat4, &'a CpuMesh)>,
+    ) -> Self {
+        // Each triangle needs its own vertices, since the barycentric coordinates are different for each triangle
+        let mut positions = Vec::new();
+        let mut barycentrics = Vec::new();
+        for (transformation, mesh) in meshes {
+            let mesh_positions = mesh.positions.to_f32();
+            mesh.for_each_triangle(|i0, i1, i2| {
+                for (index, barycentric) in [
+                    (i0, vec3(1.0, 0.0, 0.0)),
+                    (i1, vec3(0.0, 1.0, 0.0)),
+                    (i2, vec3(0.0, 0.0, 1.0)),
+                ] {
+                    positions.push((transformation * mesh_positions[index].extend(1.0)).truncate());
+                    barycentrics.push(barycentric);
+                }
+            });
+        }
+        Self {
+            context: context.clone(),
+            aabb: AxisAlignedBoundingBox::new_with_positions(&positions),
+            positions: VertexBuffer::new_with_data(context, &positions),
+            barycentrics: VertexBuffer::new_with_data(context, &barycentrics),
+            transformation: Mat4::identity(),
+            color: Srgba::BLACK,
+            width: 1.0,
+            show_hidden_lines: false,
+        }
+    }
+
+    ///
+    /// Returns the local to world transformation applied to the wireframe.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to the wireframe.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+}
+
+impl<'a> IntoIterator for &'a Wireframe {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Wireframe {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if attributes.normal || attributes.tangents || attributes.uv || attributes.uv2 {
+            panic!("the material requires normal, tangent or uv attributes but a wireframe does not provide it")
+        }
+        if self.positions.vertex_count() == 0 {
+            return;
+        }
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("modelMatrix", self.transformation);
+        program.use_vertex_attribute("position", &self.positions);
+        if program.requires_attribute("barycentric") {
+            program.use_vertex_attribute("barycentric", &self.barycentrics);
+        }
+        program.draw_arrays(
+            render_states,
+            camera.viewport(),
+            self.positions.vertex_count(),
+        );
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/wireframe.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b1100u16
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = self.aabb;
+        if !aabb.is_empty() {
+            aabb.transform(&self.transformation);
+        }
+        aabb
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl Object for Wireframe {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            WireframeMaterial { wireframe: self },
+            lights,
+        )
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct WireframeMaterial<'a> {
+    wireframe: &'a Wireframe,
+}
+
+impl Material for WireframeMaterial<'_> {
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1u32 << 13 | 0b10u32
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}",
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/wireframe.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera.color_mapping.use_uniforms(program);
+        program.use_uniform("surfaceColor", self.wireframe.color.to_linear_srgb());
+        program.use_uniform("halfWidth", 0.5 * self.wireframe.width);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: if self.wireframe.show_hidden_lines {
+                DepthTest::Always
+            } else {
+                DepthTest::LessOrEqual
+            },
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
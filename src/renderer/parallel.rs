@@ -0,0 +1,56 @@
+//!
+//! Helpers for the CPU side preparation of the rendering, which run on the [rayon](https://crates.io/crates/rayon) thread pool when the `rayon` feature is enabled and otherwise on the calling thread.
+//! They are used for the work that does not touch the graphics context or the objects themselves, ie. frustum culling and sorting of the render keys,
+//! skinning matrices and the per instance data of instanced meshes. Everything that issues graphics calls runs on the calling thread.
+//!
+
+///
+/// Returns the result of the given function applied to each of the given items in the same order as the items.
+///
+pub(crate) fn map<T: Sync, U: Send>(items: &[T], f: impl Fn(&T) -> U + Sync + Send) -> Vec<U> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+///
+/// Returns the items where the given predicate is true in the same order as the items.
+///
+pub(crate) fn filter<T: Send + Sync + Copy>(
+    items: &[T],
+    predicate: impl Fn(&T) -> bool + Sync + Send,
+) -> Vec<T> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().copied().filter(predicate).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().copied().filter(predicate).collect()
+    }
+}
+
+///
+/// Sorts the given items using the given compare function, keeping the order of equal items.
+///
+pub(crate) fn sort_by<T: Send>(
+    items: &mut [T],
+    compare: impl Fn(&T, &T) -> std::cmp::Ordering + Sync,
+) {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.par_sort_by(compare)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.sort_by(compare)
+    }
+}
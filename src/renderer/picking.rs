@@ -0,0 +1,288 @@
+//!
+//! Picking objects on the GPU by rendering the id of each object into a texture.
+//!
+
+use crate::renderer::*;
+use std::task::Poll;
+
+///
+/// Identifies an object rendered by an [ObjectPicker].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(pub u32);
+
+///
+/// The result of picking with an [ObjectPicker].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickResult {
+    /// The id of the picked object.
+    pub object: ObjectId,
+    /// The position in world space of the picked surface.
+    pub position: Vec3,
+    /// The depth of the picked surface in the range `[0, 1]`, where zero is at the near plane and one is at the far plane of the camera.
+    pub depth: f32,
+}
+
+///
+/// Picks objects by rendering the id of each object and its depth into an offscreen texture using [ObjectPicker::render],
+/// so afterwards finding the object in a pixel and the position of the surface in that pixel only requires reading back a single pixel.
+/// This is much faster than intersecting a ray with the geometries on the CPU (see [pick]) when the scene contains many or detailed objects,
+/// and the same rendered ids can be used for picking many times, for example when the mouse moves, until the scene or camera changes.
+///
+/// The ids are stored as 32 bit integers, split into the channels of an 8 bit RGBA texture, and the depth is stored with 24 bits of precision in a second layer.
+///
+/// Use [ObjectPicker::pick] to read back the result immediately, which waits for the GPU to finish rendering,
+/// or [ObjectPicker::pick_async] followed by [ObjectPicker::poll_pick] to get the result a frame later without stalling.
+///
+pub struct ObjectPicker {
+    context: Context,
+    textures: Option<(Texture2DArray, DepthTexture2D)>,
+    camera: Option<Camera>,
+    pending: Option<PendingPick>,
+}
+
+struct PendingPick {
+    camera: Camera,
+    pixel: (u32, u32),
+    object: PixelReadback<[u8; 4]>,
+    depth: PixelReadback<[u8; 4]>,
+}
+
+impl ObjectPicker {
+    ///
+    /// Creates a new object picker. Nothing is picked until the objects are rendered using [ObjectPicker::render].
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            textures: None,
+            camera: None,
+            pending: None,
+        }
+    }
+
+    ///
+    /// Renders the id of the given objects seen from the given camera, which replaces the ids rendered previously.
+    /// Objects outside the camera frustum are not rendered.
+    ///
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = (ObjectId, impl Geometry)>,
+    ) {
+        let viewport = camera.viewport();
+        let (width, height) = (viewport.width.max(1), viewport.height.max(1));
+        if self
+            .textures
+            .as_ref()
+            .is_none_or(|(t, _)| t.width() != width || t.height() != height)
+        {
+            self.textures = Some((
+                Texture2DArray::new_empty::<[u8; 4]>(
+                    &self.context,
+                    width,
+                    height,
+                    2,
+                    Interpolation::Nearest,
+                    Interpolation::Nearest,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+                DepthTexture2D::new::<f32>(
+                    &self.context,
+                    width,
+                    height,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+            ));
+        }
+        let (texture, depth_texture) = self.textures.as_mut().unwrap();
+
+        let mut id_camera = camera.clone();
+        id_camera.set_viewport(Viewport::new_at_origo(width, height));
+        let frustum = id_camera.frustum();
+        RenderTarget::new(
+            texture.as_color_target(&[0, 1], None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .write::<RendererError>(|| {
+            for (id, geometry) in objects
+                .into_iter()
+                .filter(|(_, g)| frustum.intersects_aabb(&g.aabb()))
+            {
+                render_with_material(
+                    &self.context,
+                    &id_camera,
+                    geometry,
+                    ObjectIdMaterial { id },
+                    &[],
+                );
+            }
+            Ok(())
+        })
+        .unwrap();
+        self.camera = Some(camera.clone());
+        self.pending = None;
+    }
+
+    ///
+    /// Returns the object and the position of the surface in the given pixel, given in physical pixels where (viewport.x, viewport.y) is the bottom left corner of the viewport,
+    /// or `None` if no object is rendered in that pixel. The pixel refers to the camera given in the last call to [ObjectPicker::render].
+    ///
+    /// **Note:** This waits for the GPU to finish rendering, see [ObjectPicker::pick_async] to avoid that.
+    ///
+    pub fn pick(&mut self, pixel: impl Into<PhysicalPoint>) -> Option<PickResult> {
+        let camera = self.camera.as_ref()?;
+        let texel = texel(camera, pixel)?;
+        let (texture, _) = self.textures.as_mut().unwrap();
+        let mut read = |layer: u32| {
+            texture
+                .as_color_target(&[layer], None)
+                .read_partially::<[u8; 4]>(scissor_box(texel))[0]
+        };
+        decode(camera, texel, read(0), read(1))
+    }
+
+    ///
+    /// Starts picking in the given pixel like [ObjectPicker::pick] without waiting for the GPU.
+    /// The result is returned by [ObjectPicker::poll_pick] when it is available, which replaces any pick in progress.
+    ///
+    pub fn pick_async(&mut self, pixel: impl Into<PhysicalPoint>) {
+        self.pending = self.camera.as_ref().and_then(|camera| {
+            let texel = texel(camera, pixel)?;
+            let (texture, _) = self.textures.as_mut().unwrap();
+            let mut read = |layer: u32| {
+                texture
+                    .as_color_target(&[layer], None)
+                    .read_partially_async::<[u8; 4]>(scissor_box(texel))
+            };
+            Some(PendingPick {
+                camera: camera.clone(),
+                pixel: texel,
+                object: read(0),
+                depth: read(1),
+            })
+        });
+    }
+
+    ///
+    /// Returns the result of the pick started by [ObjectPicker::pick_async] if it is available, otherwise [Poll::Pending].
+    /// If no pick is in progress, `Poll::Ready(None)` is returned.
+    ///
+    pub fn poll_pick(&mut self) -> Poll<Option<PickResult>> {
+        let Some(pending) = &self.pending else {
+            return Poll::Ready(None);
+        };
+        if !pending.object.is_ready() || !pending.depth.is_ready() {
+            return Poll::Pending;
+        }
+        let pending = self.pending.take().unwrap();
+        Poll::Ready(decode(
+            &pending.camera,
+            pending.pixel,
+            pending.object.read()[0],
+            pending.depth.read()[0],
+        ))
+    }
+}
+
+fn texel(camera: &Camera, pixel: impl Into<PhysicalPoint>) -> Option<(u32, u32)> {
+    let pixel = pixel.into();
+    let viewport = camera.viewport();
+    let x = (pixel.x - viewport.x as f32).floor();
+    let y = (pixel.y - viewport.y as f32).floor();
+    (x >= 0.0 && y >= 0.0 && x < viewport.width as f32 && y < viewport.height as f32)
+        .then_some((x as u32, y as u32))
+}
+
+fn scissor_box((x, y): (u32, u32)) -> ScissorBox {
+    ScissorBox {
+        x: x as i32,
+        y: y as i32,
+        width: 1,
+        height: 1,
+    }
+}
+
+fn decode(
+    camera: &Camera,
+    (x, y): (u32, u32),
+    object: [u8; 4],
+    depth: [u8; 4],
+) -> Option<PickResult> {
+    // The alpha channel of the depth layer is only set where an object is rendered
+    if depth[3] == 0 {
+        return None;
+    }
+    let id = u32::from_le_bytes(object);
+    let depth = u32::from_le_bytes([depth[0], depth[1], depth[2], 0]) as f32 / 16777215.0;
+    let viewport = camera.viewport();
+    let ndc = vec4(
+        2.0 * (x as f32 + 0.5) / viewport.width as f32 - 1.0,
+        2.0 * (y as f32 + 0.5) / viewport.height as f32 - 1.0,
+        2.0 * depth - 1.0,
+        1.0,
+    );
+    let position = (camera.projection() * camera.view())
+        .invert()
+        .map(|m| m * ndc)
+        .map(|p| p.truncate() / p.w)?;
+    Some(PickResult {
+        object: ObjectId(id),
+        position,
+        depth,
+    })
+}
+
+///
+/// Writes the id of the object into the first layer and the depth into the second layer.
+///
+struct ObjectIdMaterial {
+    id: ObjectId,
+}
+
+impl Material for ObjectIdMaterial {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        "
+        uniform uint objectId;
+
+        layout (location = 0) out vec4 outObject;
+        layout (location = 1) out vec4 outDepth;
+
+        vec4 bytes(uint value) {
+            return vec4(float(value & 255u), float((value >> 8) & 255u), float((value >> 16) & 255u), float((value >> 24) & 255u)) / 255.0;
+        }
+
+        void main()
+        {
+            outObject = bytes(objectId);
+            outDepth = vec4(bytes(uint(gl_FragCoord.z * 16777215.0)).xyz, 1.0);
+        }
+        "
+        .to_owned()
+    }
+
+    fn id(&self) -> u32 {
+        0b1u32 << 15 | 0b1u32 << 8
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes::NONE
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("objectId", self.id.0);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,410 @@
+//!
+//! Intersecting rays with triangle meshes on the CPU, accelerated by a bounding volume hierarchy (see [Bvh]).
+//!
+
+use crate::renderer::*;
+
+///
+/// A ray starting at an origin and going in a direction.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    /// The start of the ray.
+    pub origin: Vec3,
+    /// The direction of the ray, which is normalized when the ray is constructed using [Ray::new].
+    pub direction: Vec3,
+}
+
+///
+/// The closest intersection between a [Ray] and the triangles in a [Bvh].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// The position of the hit.
+    pub position: Vec3,
+    /// The normalized normal at the hit, interpolated from the vertex normals if the mesh has normals, otherwise the normal of the triangle.
+    pub normal: Vec3,
+    /// The uv coordinates at the hit interpolated from the vertex uv coordinates, or `None` if the mesh has no uv coordinates.
+    pub uv: Option<Vec2>,
+    /// The index of the hit triangle in the mesh.
+    pub triangle: usize,
+    /// The distance from the origin of the ray to the hit.
+    pub distance: f32,
+}
+
+impl Ray {
+    ///
+    /// Constructs a new ray starting at the given origin and going in the given direction.
+    ///
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    ///
+    /// Constructs a ray from the given camera through the given pixel, starting at the near plane of the camera.
+    /// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport.
+    ///
+    pub fn from_camera(camera: &Camera, pixel: impl Into<PhysicalPoint> + Copy) -> Self {
+        let origin = camera.unproject(pixel, 0.0);
+        Self::new(origin, camera.unproject(pixel, 1.0) - origin)
+    }
+
+    ///
+    /// Returns the position along the ray at the given distance from the origin.
+    ///
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    ///
+    /// Returns the closest intersection between this ray and the triangles in the given bounding volume hierarchy,
+    /// or `None` if the ray does not hit any triangles.
+    ///
+    pub fn intersect(&self, bvh: &Bvh) -> Option<RayHit> {
+        bvh.intersect(self, f32::INFINITY)
+    }
+
+    ///
+    /// Returns the closest intersection between this ray and the triangles in the given bounding volume hierarchy placed using the given transformation,
+    /// or `None` if the ray does not hit any triangles.
+    /// The returned position, normal and distance are in the same space as the ray.
+    ///
+    pub fn intersect_transformed(&self, bvh: &Bvh, transformation: Mat4) -> Option<RayHit> {
+        self.intersect_transformed_closer_than(bvh, transformation, f32::INFINITY)
+    }
+
+    fn intersect_transformed_closer_than(
+        &self,
+        bvh: &Bvh,
+        transformation: Mat4,
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        let inverse = transformation.invert()?;
+        // The direction is not normalized, so the distance along the ray in the local space is the same as in this space
+        let local = Ray {
+            origin: (inverse * self.origin.extend(1.0)).truncate(),
+            direction: (inverse * self.direction.extend(0.0)).truncate(),
+        };
+        let scale = self.direction.magnitude();
+        let hit = bvh.intersect(&local, max_distance * scale)?;
+        let normal_transformation = inverse.transpose();
+        Some(RayHit {
+            position: (transformation * hit.position.extend(1.0)).truncate(),
+            normal: (normal_transformation * hit.normal.extend(0.0))
+                .truncate()
+                .normalize(),
+            distance: hit.distance / scale,
+            ..hit
+        })
+    }
+}
+
+///
+/// Returns the closest intersection between the given ray and the given models, each given by a bounding volume hierarchy and a transformation,
+/// together with the index of the hit model, or `None` if the ray does not hit any of the models.
+/// The returned position, normal and distance are in the same space as the ray.
+///
+pub fn raycast<'a>(
+    ray: &Ray,
+    models: impl IntoIterator<Item = (&'a Bvh, Mat4)>,
+) -> Option<(usize, RayHit)> {
+    let mut closest: Option<(usize, RayHit)> = None;
+    for (index, (bvh, transformation)) in models.into_iter().enumerate() {
+        let max_distance = closest.map_or(f32::INFINITY, |(_, hit)| hit.distance);
+        let aabb = bvh.aabb();
+        if aabb.is_empty() {
+            continue;
+        }
+        let mut world_aabb = aabb;
+        world_aabb.transform(&transformation);
+        if intersect_aabb(ray, world_aabb.min(), world_aabb.max(), max_distance).is_none() {
+            continue;
+        }
+        if let Some(hit) = ray.intersect_transformed_closer_than(bvh, transformation, max_distance)
+        {
+            closest = Some((index, hit));
+        }
+    }
+    closest
+}
+
+///
+/// Builds a [Bvh] from the triangles of a mesh, see [BuildBvh::build_bvh].
+///
+pub trait BuildBvh {
+    ///
+    /// Returns a bounding volume hierarchy of the triangles, which can be used for intersecting rays with the triangles (see [Ray::intersect]).
+    ///
+    fn build_bvh(&self) -> Bvh;
+}
+
+impl BuildBvh for CpuMesh {
+    fn build_bvh(&self) -> Bvh {
+        Bvh::new(self)
+    }
+}
+
+const MAX_TRIANGLES_PER_LEAF: usize = 4;
+
+///
+/// A bounding volume hierarchy of the triangles of a [CpuMesh], which makes it fast to find the intersection between a [Ray] and the triangles.
+/// Construct it using [Bvh::new] or [BuildBvh::build_bvh] and use it with [Ray::intersect] or [raycast].
+///
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    positions: Vec<Vec3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<Vec2>>,
+    indices: Vec<[u32; 3]>,
+    triangles: Vec<usize>,
+    nodes: Vec<BvhNode>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    // The index of the first triangle for a leaf, otherwise the index of the second child (the first child is the next node)
+    offset: usize,
+    // The number of triangles for a leaf, otherwise zero
+    count: usize,
+}
+
+impl Bvh {
+    ///
+    /// Constructs a new bounding volume hierarchy of the triangles of the given mesh.
+    ///
+    pub fn new(cpu_mesh: &CpuMesh) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        let indices = cpu_mesh
+            .indices
+            .to_u32()
+            .unwrap_or_else(|| (0..positions.len() as u32).collect::<Vec<_>>())
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect::<Vec<_>>();
+        let mut bvh = Self {
+            normals: cpu_mesh.normals.clone(),
+            uvs: cpu_mesh.uvs.clone(),
+            triangles: (0..indices.len()).collect(),
+            indices,
+            positions,
+            nodes: Vec::new(),
+        };
+        let centroids = bvh
+            .indices
+            .iter()
+            .map(|t| {
+                (bvh.positions[t[0] as usize]
+                    + bvh.positions[t[1] as usize]
+                    + bvh.positions[t[2] as usize])
+                    / 3.0
+            })
+            .collect::<Vec<_>>();
+        if !bvh.triangles.is_empty() {
+            bvh.build(&centroids, 0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    ///
+    /// Returns the number of triangles.
+    ///
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len()
+    }
+
+    ///
+    /// Returns the axis aligned bounding box of the triangles.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.nodes
+            .first()
+            .map(|n| AxisAlignedBoundingBox::new_with_positions(&[n.min, n.max]))
+            .unwrap_or(AxisAlignedBoundingBox::EMPTY)
+    }
+
+    fn build(&mut self, centroids: &[Vec3], start: usize, end: usize) -> usize {
+        let node_index = self.nodes.len();
+        let (min, max) = self.triangles[start..end].iter().fold(
+            (
+                vec3(f32::MAX, f32::MAX, f32::MAX),
+                vec3(f32::MIN, f32::MIN, f32::MIN),
+            ),
+            |(min, max), &t| {
+                self.indices[t].iter().fold((min, max), |(min, max), &i| {
+                    let p = self.positions[i as usize];
+                    (
+                        vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                        vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                    )
+                })
+            },
+        );
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            offset: start,
+            count: end - start,
+        });
+        if end - start <= MAX_TRIANGLES_PER_LEAF {
+            return node_index;
+        }
+
+        // Split at the median centroid along the longest axis of the centroid bounds
+        let (centroid_min, centroid_max) = self.triangles[start..end].iter().fold(
+            (
+                vec3(f32::MAX, f32::MAX, f32::MAX),
+                vec3(f32::MIN, f32::MIN, f32::MIN),
+            ),
+            |(min, max), &t| {
+                let c = centroids[t];
+                (
+                    vec3(min.x.min(c.x), min.y.min(c.y), min.z.min(c.z)),
+                    vec3(max.x.max(c.x), max.y.max(c.y), max.z.max(c.z)),
+                )
+            },
+        );
+        let extent = centroid_max - centroid_min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let middle = (start + end) / 2;
+        self.triangles[start..end].select_nth_unstable_by(middle - start, |a, b| {
+            centroids[*a][axis].total_cmp(&centroids[*b][axis])
+        });
+
+        self.build(centroids, start, middle);
+        let second = self.build(centroids, middle, end);
+        self.nodes[node_index].offset = second;
+        self.nodes[node_index].count = 0;
+        node_index
+    }
+
+    fn intersect(&self, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut closest: Option<(usize, f32, f32, f32)> = None;
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            let max_distance = closest.map_or(max_distance, |(_, t, _, _)| t);
+            if intersect_aabb(ray, node.min, node.max, max_distance).is_none() {
+                continue;
+            }
+            if node.count > 0 {
+                for &triangle in &self.triangles[node.offset..node.offset + node.count] {
+                    let max_distance = closest.map_or(max_distance, |(_, t, _, _)| t);
+                    let [a, b, c] = self.indices[triangle].map(|i| self.positions[i as usize]);
+                    if let Some((t, u, v)) = intersect_triangle(ray, a, b, c) {
+                        if t < max_distance {
+                            closest = Some((triangle, t, u, v));
+                        }
+                    }
+                }
+            } else {
+                // Visit the closest child first to find a close hit early
+                let first = node_index + 1;
+                let second = node.offset;
+                let distance = |i: usize| {
+                    intersect_aabb(ray, self.nodes[i].min, self.nodes[i].max, max_distance)
+                        .unwrap_or(f32::INFINITY)
+                };
+                if distance(first) <= distance(second) {
+                    stack.push(second);
+                    stack.push(first);
+                } else {
+                    stack.push(first);
+                    stack.push(second);
+                }
+            }
+        }
+
+        closest.map(|(triangle, t, u, v)| {
+            let [i0, i1, i2] = self.indices[triangle].map(|i| i as usize);
+            let w = 1.0 - u - v;
+            let normal = self
+                .normals
+                .as_ref()
+                .map(|n| w * n[i0] + u * n[i1] + v * n[i2])
+                .filter(|n| n.magnitude2() > 0.0)
+                .unwrap_or_else(|| {
+                    let [a, b, c] = [i0, i1, i2].map(|i| self.positions[i]);
+                    (b - a).cross(c - a)
+                });
+            RayHit {
+                position: ray.origin + ray.direction * t,
+                normal: normal.normalize(),
+                uv: self
+                    .uvs
+                    .as_ref()
+                    .map(|uvs| w * uvs[i0] + u * uvs[i1] + v * uvs[i2]),
+                triangle,
+                distance: t,
+            }
+        })
+    }
+}
+
+///
+/// Returns the distance along the ray to the entry of the box, or zero if the origin is inside the box,
+/// or `None` if the ray misses the box or enters it after the given maximum distance.
+///
+fn intersect_aabb(ray: &Ray, min: Vec3, max: Vec3, max_distance: f32) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = max_distance;
+    for axis in 0..3 {
+        let inverse = 1.0 / ray.direction[axis];
+        let t0 = (min[axis] - ray.origin[axis]) * inverse;
+        let t1 = (max[axis] - ray.origin[axis]) * inverse;
+        let (t0, t1) = if inverse < 0.0 { (t1, t0) } else { (t0, t1) };
+        // NaN appears when the origin is on a slab boundary and the direction is parallel to it, which is treated as inside
+        if t0 > t_min {
+            t_min = t0;
+        }
+        if t1 < t_max {
+            t_max = t1;
+        }
+        if t_max < t_min {
+            return None;
+        }
+    }
+    Some(t_min)
+}
+
+///
+/// Möller–Trumbore intersection, which returns the distance along the ray and the barycentric coordinates of the second and third vertex.
+/// Both sides of the triangle are hit.
+///
+fn intersect_triangle(ray: &Ray, a: Vec3, b: Vec3, c: Vec3) -> Option<(f32, f32, f32)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs()
+        < f32::EPSILON * edge1.magnitude() * edge2.magnitude() * ray.direction.magnitude()
+    {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = ray.origin - a;
+    let u = s.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = ray.direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(q) * inverse;
+    (t >= 0.0).then_some((t, u, v))
+}
@@ -0,0 +1,473 @@
+//!
+//! A render graph, which schedules render passes and allocates the intermediate textures used between the passes.
+//!
+
+use crate::renderer::*;
+
+///
+/// The format of a texture in a [RenderGraph].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RenderGraphFormat {
+    /// Four 8 bit channels, for example a low dynamic range color.
+    Rgba8,
+    /// Four 16 bit floating point channels, for example a high dynamic range color.
+    Rgba16F,
+    /// Four 32 bit floating point channels.
+    Rgba32F,
+    /// One 32 bit floating point channel.
+    R32F,
+    /// A 32 bit floating point depth texture.
+    Depth32F,
+}
+
+///
+/// Describes a texture in a [RenderGraph], which is allocated when executing the graph.
+/// Color textures are sampled with linear interpolation and clamped to the edge.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderGraphTexture {
+    /// The width of the texture in texels.
+    pub width: u32,
+    /// The height of the texture in texels.
+    pub height: u32,
+    /// The format of the texture.
+    pub format: RenderGraphFormat,
+}
+
+///
+/// Refers to a texture in a [RenderGraph].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderGraphResource(usize);
+
+enum Texture {
+    Color(Texture2D),
+    Depth(DepthTexture2D),
+}
+
+impl Texture {
+    fn new(context: &Context, description: RenderGraphTexture) -> Self {
+        let RenderGraphTexture {
+            width,
+            height,
+            format,
+        } = description;
+        macro_rules! new_color {
+            ($t:ty) => {
+                Self::Color(Texture2D::new_empty::<$t>(
+                    context,
+                    width,
+                    height,
+                    Interpolation::Linear,
+                    Interpolation::Linear,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ))
+            };
+        }
+        match format {
+            RenderGraphFormat::Rgba8 => new_color!([u8; 4]),
+            RenderGraphFormat::Rgba16F => new_color!([f16; 4]),
+            RenderGraphFormat::Rgba32F => new_color!([f32; 4]),
+            RenderGraphFormat::R32F => new_color!(f32),
+            RenderGraphFormat::Depth32F => Self::Depth(DepthTexture2D::new::<f32>(
+                context,
+                width,
+                height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )),
+        }
+    }
+}
+
+///
+/// Keeps the textures allocated by a [RenderGraph] between frames, so they are reused instead of allocated each frame.
+/// Textures which are not used when executing a graph are released.
+///
+pub struct RenderGraphTextures {
+    context: Context,
+    textures: Vec<(RenderGraphTexture, Texture)>,
+}
+
+impl RenderGraphTextures {
+    ///
+    /// Creates a new empty collection of textures.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            textures: Vec::new(),
+        }
+    }
+
+    ///
+    /// Returns the number of allocated textures.
+    ///
+    pub fn count(&self) -> usize {
+        self.textures.len()
+    }
+}
+
+struct Pass<'a> {
+    name: String,
+    reads: Vec<RenderGraphResource>,
+    writes: Vec<RenderGraphResource>,
+    output: bool,
+    execute: Box<dyn FnOnce(&mut RenderGraphPassResources) + 'a>,
+}
+
+///
+/// Schedules a number of render passes, for example a shadow pass, a scene pass, an ambient occlusion pass, a bloom pass and a final composition pass,
+/// where each pass declares the textures it reads and writes.
+///
+/// When executing the graph, a pass is executed after all the passes writing the textures it reads and passes which do not contribute to an output pass are skipped.
+/// The textures are transient, ie. they only exist while executing the graph, and a texture is reused by later passes when no remaining pass uses it,
+/// which keeps the memory usage down. Since a texture might contain the data from an earlier pass or frame, the first pass writing a texture must clear it.
+///
+/// The graph is built each frame, while the textures are kept between frames in [RenderGraphTextures].
+///
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    textures: Vec<RenderGraphTexture>,
+    passes: Vec<Pass<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    ///
+    /// Creates a new empty render graph.
+    ///
+    pub fn new() -> Self {
+        Self {
+            textures: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    ///
+    /// Declares a transient texture with the given description, which can be written and read by the passes.
+    ///
+    pub fn texture(&mut self, description: RenderGraphTexture) -> RenderGraphResource {
+        self.textures.push(description);
+        RenderGraphResource(self.textures.len() - 1)
+    }
+
+    ///
+    /// Adds a pass which reads and writes the given textures, where the textures are accessed in the callback using the given [RenderGraphPassResources].
+    /// The pass is skipped if nothing it writes is read by a pass contributing to an output pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pass reads a texture it also writes or lists the same texture more than once in `writes`.
+    ///
+    pub fn add_pass(
+        &mut self,
+        name: &str,
+        reads: &[RenderGraphResource],
+        writes: &[RenderGraphResource],
+        callback: impl FnOnce(&mut RenderGraphPassResources) + 'a,
+    ) {
+        self.push_pass(name, reads, writes, false, callback);
+    }
+
+    ///
+    /// Adds an output pass, for example a pass rendering into the screen, which reads the given textures.
+    /// Output passes are always executed.
+    ///
+    pub fn add_output_pass(
+        &mut self,
+        name: &str,
+        reads: &[RenderGraphResource],
+        callback: impl FnOnce(&mut RenderGraphPassResources) + 'a,
+    ) {
+        self.push_pass(name, reads, &[], true, callback);
+    }
+
+    fn push_pass(
+        &mut self,
+        name: &str,
+        reads: &[RenderGraphResource],
+        writes: &[RenderGraphResource],
+        output: bool,
+        callback: impl FnOnce(&mut RenderGraphPassResources) + 'a,
+    ) {
+        assert!(
+            reads.iter().all(|r| !writes.contains(r)),
+            "The render pass {} cannot read a texture it writes",
+            name
+        );
+        assert!(
+            writes
+                .iter()
+                .enumerate()
+                .all(|(i, w)| !writes[..i].contains(w)),
+            "The render pass {} cannot write the same texture more than once",
+            name
+        );
+        self.passes.push(Pass {
+            name: name.to_owned(),
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            output,
+            execute: Box::new(callback),
+        });
+    }
+
+    ///
+    /// Returns the names of the passes in the order they are executed, leaving out the passes that are skipped.
+    ///
+    pub fn schedule(&self) -> Result<Vec<&str>, RendererError> {
+        Ok(self
+            .order()?
+            .into_iter()
+            .map(|i| self.passes[i].name.as_str())
+            .collect())
+    }
+
+    // Returns the indices of the passes contributing to an output pass, ordered so each pass is after the passes writing the textures it reads.
+    // Passes writing the same texture are executed in the order they were added.
+    fn order(&self) -> Result<Vec<usize>, RendererError> {
+        let dependencies = |pass: usize| {
+            let p = &self.passes[pass];
+            (0..self.passes.len()).filter(move |&other| {
+                let o = &self.passes[other];
+                other != pass
+                    && (p.reads.iter().any(|r| o.writes.contains(r))
+                        || (other < pass && p.writes.iter().any(|w| o.writes.contains(w))))
+            })
+        };
+        // Depth first traversal from the output passes, where the passes are added after their dependencies
+        const UNVISITED: u8 = 0;
+        const VISITING: u8 = 1;
+        const VISITED: u8 = 2;
+        let mut state = vec![UNVISITED; self.passes.len()];
+        let mut order = Vec::new();
+        for output in (0..self.passes.len()).filter(|&i| self.passes[i].output) {
+            let mut stack = vec![(output, false)];
+            while let Some((pass, dependencies_added)) = stack.pop() {
+                if dependencies_added {
+                    state[pass] = VISITED;
+                    order.push(pass);
+                    continue;
+                }
+                match state[pass] {
+                    VISITED => continue,
+                    VISITING => {
+                        return Err(RendererError::RenderGraphCycle(
+                            self.passes[pass].name.clone(),
+                        ))
+                    }
+                    _ => {}
+                }
+                state[pass] = VISITING;
+                stack.push((pass, true));
+                for dependency in dependencies(pass).collect::<Vec<_>>().into_iter().rev() {
+                    match state[dependency] {
+                        VISITING => {
+                            return Err(RendererError::RenderGraphCycle(
+                                self.passes[dependency].name.clone(),
+                            ))
+                        }
+                        UNVISITED => stack.push((dependency, false)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    ///
+    /// Executes the passes contributing to an output pass in the order given by [RenderGraph::schedule], using and updating the given textures.
+    ///
+    pub fn execute(self, textures: &mut RenderGraphTextures) -> Result<(), RendererError> {
+        let order = self.order()?;
+        let mut first_use = vec![usize::MAX; self.textures.len()];
+        let mut last_use = vec![0; self.textures.len()];
+        for (step, pass) in order.iter().enumerate() {
+            let pass = &self.passes[*pass];
+            for r in pass.reads.iter().chain(pass.writes.iter()) {
+                first_use[r.0] = first_use[r.0].min(step);
+                last_use[r.0] = last_use[r.0].max(step);
+            }
+        }
+
+        let mut available = std::mem::take(&mut textures.textures)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut used = Vec::new();
+        let mut slots: Vec<Option<Texture>> = (0..self.textures.len()).map(|_| None).collect();
+        let mut passes = self.passes.into_iter().map(Some).collect::<Vec<_>>();
+        for (step, pass) in order.iter().enumerate() {
+            // Allocate the textures first used in this pass
+            for (i, description) in self.textures.iter().enumerate() {
+                if first_use[i] == step {
+                    let reused = available
+                        .iter_mut()
+                        .find(|t| t.as_ref().is_some_and(|(d, _)| d == description))
+                        .and_then(|t| t.take());
+                    slots[i] = Some(match reused {
+                        Some((_, texture)) => texture,
+                        None => Texture::new(&textures.context, *description),
+                    });
+                }
+            }
+
+            let pass = passes[*pass].take().unwrap();
+            let mut written = pass
+                .writes
+                .iter()
+                .map(|r| (*r, slots[r.0].take().unwrap()))
+                .collect::<Vec<_>>();
+            (pass.execute)(&mut RenderGraphPassResources {
+                name: &pass.name,
+                reads: &pass.reads,
+                slots: &slots,
+                written: &mut written,
+            });
+            for (r, texture) in written {
+                slots[r.0] = Some(texture);
+            }
+
+            // Release the textures last used in this pass, so they can be reused by the following passes
+            for (i, description) in self.textures.iter().enumerate() {
+                if last_use[i] == step && first_use[i] != usize::MAX {
+                    let texture = slots[i].take().unwrap();
+                    available.push(Some((*description, texture)));
+                    used.push(available.len() - 1);
+                }
+            }
+        }
+        textures.textures = available
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| used.contains(i))
+            .filter_map(|(_, t)| t)
+            .collect();
+        Ok(())
+    }
+}
+
+///
+/// Gives access to the textures read and written by a pass in a [RenderGraph].
+///
+pub struct RenderGraphPassResources<'a, 'b> {
+    name: &'a str,
+    reads: &'a [RenderGraphResource],
+    slots: &'a [Option<Texture>],
+    written: &'b mut Vec<(RenderGraphResource, Texture)>,
+}
+
+impl RenderGraphPassResources<'_, '_> {
+    ///
+    /// Returns the color texture which is read by this pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the texture is not read by this pass or is a depth texture.
+    ///
+    pub fn texture(&self, resource: RenderGraphResource) -> &Texture2D {
+        match self.read(resource) {
+            Texture::Color(texture) => texture,
+            Texture::Depth(_) => panic!("The texture read by {} is a depth texture", self.name),
+        }
+    }
+
+    ///
+    /// Returns the depth texture which is read by this pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the texture is not read by this pass or is a color texture.
+    ///
+    pub fn depth_texture(&self, resource: RenderGraphResource) -> &DepthTexture2D {
+        match self.read(resource) {
+            Texture::Depth(texture) => texture,
+            Texture::Color(_) => panic!("The texture read by {} is a color texture", self.name),
+        }
+    }
+
+    fn read(&self, resource: RenderGraphResource) -> &Texture {
+        assert!(
+            self.reads.contains(&resource),
+            "The texture is not read by the render pass {}",
+            self.name
+        );
+        self.slots[resource.0].as_ref().unwrap()
+    }
+
+    ///
+    /// Returns the color target of the color texture which is written by this pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the texture is not written by this pass or is a depth texture.
+    ///
+    pub fn color_target(&mut self, resource: RenderGraphResource) -> ColorTarget<'_> {
+        let name = self.name;
+        match self.write(resource) {
+            Texture::Color(texture) => texture.as_color_target(None),
+            Texture::Depth(_) => panic!("The texture written by {} is a depth texture", name),
+        }
+    }
+
+    ///
+    /// Returns the depth target of the depth texture which is written by this pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the texture is not written by this pass or is a color texture.
+    ///
+    pub fn depth_target(&mut self, resource: RenderGraphResource) -> DepthTarget<'_> {
+        let name = self.name;
+        match self.write(resource) {
+            Texture::Depth(texture) => texture.as_depth_target(),
+            Texture::Color(_) => panic!("The texture written by {} is a color texture", name),
+        }
+    }
+
+    ///
+    /// Returns a render target with the given color and depth textures which are written by this pass.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the textures are not written by this pass or if the color texture is a depth texture or the depth texture is a color texture.
+    ///
+    pub fn render_target(
+        &mut self,
+        color: RenderGraphResource,
+        depth: RenderGraphResource,
+    ) -> RenderTarget<'_> {
+        let name = self.name;
+        let mut color_texture = None;
+        let mut depth_texture = None;
+        for (r, texture) in self.written.iter_mut() {
+            if *r == color {
+                color_texture = Some(texture);
+            } else if *r == depth {
+                depth_texture = Some(texture);
+            }
+        }
+        match (color_texture, depth_texture) {
+            (Some(Texture::Color(color)), Some(Texture::Depth(depth))) => {
+                RenderTarget::new(color.as_color_target(None), depth.as_depth_target())
+            }
+            _ => panic!(
+                "The render pass {} must write a color texture and a depth texture to use them as a render target",
+                name
+            ),
+        }
+    }
+
+    fn write(&mut self, resource: RenderGraphResource) -> &mut Texture {
+        let name = self.name;
+        self.written
+            .iter_mut()
+            .find(|(r, _)| *r == resource)
+            .map(|(_, t)| t)
+            .unwrap_or_else(|| panic!("The texture is not written by the render pass {}", name))
+    }
+}
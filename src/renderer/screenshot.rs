@@ -0,0 +1,121 @@
+//!
+//! Rendering screenshots offscreen, independent of the window size.
+//!
+
+use crate::renderer::*;
+
+///
+/// Renders a screenshot of the given width and height into an offscreen render target instead of reading back the screen,
+/// so the screenshot can have a higher resolution than the window and a transparent background.
+///
+/// The `render` closure is called once with the offscreen render target, which is cleared using the given `clear_state`, and a copy of the camera with its viewport set to the size of the render target.
+/// Use for example `ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0)` and do not render a [Skybox] to get a transparent background.
+/// Note that objects rendered with [Blend::TRANSPARENCY] do not change the alpha value of the background, so semi-transparent objects are only visible in front of opaque objects.
+///
+/// The scene is rendered at `supersampling` times the width and height of the screenshot and downsampled by averaging the samples of each pixel, which smooths the edges of the objects.
+/// The size of the render target is limited by the maximum texture size of the graphics card, often 8192 or 16384 pixels.
+///
+pub fn render_screenshot(
+    context: &Context,
+    camera: &Camera,
+    width: u32,
+    height: u32,
+    supersampling: u32,
+    clear_state: ClearState,
+    render: impl FnOnce(&RenderTarget, &Camera),
+) -> CpuTexture {
+    let supersampling = supersampling.max(1);
+    let (width, height) = (width.max(1), height.max(1));
+    let (render_width, render_height) = (width * supersampling, height * supersampling);
+
+    let mut camera = camera.clone();
+    camera.set_viewport(Viewport::new_at_origo(render_width, render_height));
+
+    let mut color_texture = Texture2D::new_empty::<[u8; 4]>(
+        context,
+        render_width,
+        render_height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth_texture = DepthTexture2D::new::<f32>(
+        context,
+        render_width,
+        render_height,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let target = RenderTarget::new(
+        color_texture.as_color_target(None),
+        depth_texture.as_depth_target(),
+    );
+    target.clear(clear_state);
+    render(&target, &camera);
+    let pixels = target.read_color::<[u8; 4]>();
+
+    CpuTexture {
+        name: "screenshot".to_string(),
+        data: TextureData::RgbaU8(downsample(
+            &pixels,
+            render_width as usize,
+            width as usize,
+            height as usize,
+            supersampling as usize,
+        )),
+        width,
+        height,
+        ..Default::default()
+    }
+}
+
+///
+/// Averages each block of `factor` x `factor` pixels, weighted by alpha so the color of transparent pixels does not bleed into the edges of the objects.
+/// The colors are averaged in linear color space and converted back to sRGB, since averaging the sRGB values directly darkens the edges.
+///
+fn downsample(
+    pixels: &[[u8; 4]],
+    source_width: usize,
+    width: usize,
+    height: usize,
+    factor: usize,
+) -> Vec<[u8; 4]> {
+    let sample_count = (factor * factor) as f32;
+    let mut result = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let mut color = Vec3::zero();
+            let mut alpha = 0.0f32;
+            for sy in y * factor..(y + 1) * factor {
+                for sx in x * factor..(x + 1) * factor {
+                    let pixel = Srgba::from(pixels[sy * source_width + sx]).to_linear_srgb();
+                    color += pixel.truncate() * pixel.w;
+                    alpha += pixel.w;
+                }
+            }
+            result.push(if alpha > 0.0 {
+                let color = color / alpha;
+                [
+                    linear_to_srgb(color.x),
+                    linear_to_srgb(color.y),
+                    linear_to_srgb(color.z),
+                    (alpha / sample_count * 255.0).round() as u8,
+                ]
+            } else {
+                [0, 0, 0, 0]
+            });
+        }
+    }
+    result
+}
+
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = if c < 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,211 @@
+use crate::core::*;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::thread::JoinHandle;
+use thiserror::Error;
+
+///
+/// Error associated with capturing frames, see [FrameCapture].
+///
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum CaptureError {
+    #[error("failed writing the captured frames: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "image")]
+    #[error("failed encoding a captured frame: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("the size of the captured frames changed from {0}x{1} to {2}x{3}, which is not supported when capturing a video")]
+    SizeChanged(u32, u32, u32, u32),
+    #[error("ffmpeg failed encoding the video: {0}")]
+    Ffmpeg(String),
+    #[error("the thread writing the captured frames stopped unexpectedly")]
+    WriterStopped,
+}
+
+///
+/// Where the frames captured by a [FrameCapture] are written.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum CaptureOutput {
+    /// The frames are written as numbered PNG files, `frame_00000.png`, `frame_00001.png` etc., into the given directory, which is created if it does not exist.
+    #[cfg(feature = "image")]
+    PngSequence {
+        /// The directory the PNG files are written to.
+        directory: PathBuf,
+    },
+    /// The frames are encoded to a video file by [ffmpeg](https://ffmpeg.org/), which must be installed and available on the path.
+    /// The container and codec are chosen by ffmpeg from the file extension, for example `.mp4` or `.webm`.
+    Video {
+        /// The path of the video file.
+        path: PathBuf,
+        /// The number of frames per second in the video.
+        /// Every captured frame is a frame in the video, so to get a video in real time, the frame rate should match the rate the frames are rendered at.
+        frame_rate: u32,
+    },
+}
+
+struct Frame {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+///
+/// Captures rendered frames, for example to produce a video of a simulation, see [CaptureOutput] for the supported outputs.
+/// The pixels are read from the GPU without waiting for it to finish rendering (see [PixelReadback]) and are written to disk on a separate thread,
+/// so capturing only has a small impact on the frame rate.
+///
+/// When using the default [Window](crate::window::Window), capturing can be started and stopped using `FrameOutput::capture` (available with the `window` feature).
+/// Otherwise, call [FrameCapture::capture] after rendering each frame that should be captured and [FrameCapture::finish] when done.
+///
+pub struct FrameCapture {
+    pending: VecDeque<(PixelReadback<[u8; 4]>, u32, u32)>,
+    sender: Option<Sender<Frame>>,
+    writer: Option<JoinHandle<Result<(), CaptureError>>>,
+}
+
+impl FrameCapture {
+    ///
+    /// Creates a new frame capture which writes the captured frames to the given output.
+    ///
+    pub fn new(output: CaptureOutput) -> Self {
+        let (sender, receiver) = channel::<Frame>();
+        let writer = std::thread::spawn(move || {
+            match output {
+                #[cfg(feature = "image")]
+                CaptureOutput::PngSequence { directory } => {
+                    std::fs::create_dir_all(&directory)?;
+                    for (index, frame) in receiver.into_iter().enumerate() {
+                        image::RgbaImage::from_raw(
+                            frame.width,
+                            frame.height,
+                            frame.pixels.concat(),
+                        )
+                        .unwrap()
+                        .save(directory.join(format!("frame_{:05}.png", index)))?;
+                    }
+                }
+                CaptureOutput::Video { path, frame_rate } => {
+                    use std::io::Write;
+                    let mut ffmpeg: Option<(std::process::Child, u32, u32)> = None;
+                    for frame in receiver {
+                        let (child, width, height) = match &mut ffmpeg {
+                            Some(ffmpeg) => ffmpeg,
+                            None => ffmpeg.insert((
+                                std::process::Command::new("ffmpeg")
+                                    .args(["-y", "-loglevel", "error", "-f", "rawvideo"])
+                                    .args(["-pixel_format", "rgba", "-video_size"])
+                                    .arg(format!("{}x{}", frame.width, frame.height))
+                                    .arg("-framerate")
+                                    .arg(frame_rate.to_string())
+                                    .args(["-i", "-", "-pix_fmt", "yuv420p"])
+                                    // Most codecs require an even width and height
+                                    .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+                                    .arg(&path)
+                                    .stdin(std::process::Stdio::piped())
+                                    .spawn()?,
+                                frame.width,
+                                frame.height,
+                            )),
+                        };
+                        if frame.width != *width || frame.height != *height {
+                            return Err(CaptureError::SizeChanged(
+                                *width,
+                                *height,
+                                frame.width,
+                                frame.height,
+                            ));
+                        }
+                        child
+                            .stdin
+                            .as_mut()
+                            .unwrap()
+                            .write_all(&frame.pixels.concat())?;
+                    }
+                    if let Some((mut child, _, _)) = ffmpeg {
+                        drop(child.stdin.take());
+                        let status = child.wait()?;
+                        if !status.success() {
+                            return Err(CaptureError::Ffmpeg(status.to_string()));
+                        }
+                    }
+                }
+            }
+            Ok(())
+        });
+        Self {
+            pending: VecDeque::new(),
+            sender: Some(sender),
+            writer: Some(writer),
+        }
+    }
+
+    ///
+    /// Captures the color of the given render target, for example the screen, which should be called after rendering a frame.
+    /// Returns an error if writing the previously captured frames failed, in which case no more frames are captured.
+    ///
+    pub fn capture(&mut self, render_target: &RenderTarget) -> Result<(), CaptureError> {
+        if self.sender.is_none() {
+            return Err(CaptureError::WriterStopped);
+        }
+        let (width, height) = (render_target.width(), render_target.height());
+        self.pending.push_back((
+            render_target.read_color_partially_async(ScissorBox::new_at_origo(width, height)),
+            width,
+            height,
+        ));
+        // Avoid that the captured frames pile up on the GPU if it is slower than the CPU
+        while self.pending.len() > 3
+            || self
+                .pending
+                .front()
+                .map(|(readback, _, _)| readback.is_ready())
+                .unwrap_or(false)
+        {
+            let (readback, width, height) = self.pending.pop_front().unwrap();
+            self.send(Frame {
+                width,
+                height,
+                pixels: readback.read(),
+            })?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Waits until all captured frames have been written, for example until the video is encoded.
+    ///
+    pub fn finish(mut self) -> Result<(), CaptureError> {
+        while let Some((readback, width, height)) = self.pending.pop_front() {
+            self.send(Frame {
+                width,
+                height,
+                pixels: readback.read(),
+            })?;
+        }
+        self.sender = None;
+        self.join_writer()
+    }
+
+    fn send(&mut self, frame: Frame) -> Result<(), CaptureError> {
+        let Some(sender) = &self.sender else {
+            return Err(CaptureError::WriterStopped);
+        };
+        if sender.send(frame).is_err() {
+            // The writer has stopped because of an error
+            self.sender = None;
+            self.pending.clear();
+            return self.join_writer().and(Err(CaptureError::WriterStopped));
+        }
+        Ok(())
+    }
+
+    fn join_writer(&mut self) -> Result<(), CaptureError> {
+        match self.writer.take().map(|writer| writer.join()) {
+            Some(Ok(result)) => result,
+            _ => Err(CaptureError::WriterStopped),
+        }
+    }
+}
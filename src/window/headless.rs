@@ -12,6 +12,8 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 #[allow(missing_docs)]
 pub enum HeadlessError {
+    #[error("glutin error")]
+    GlutinError(#[from] glutin::error::Error),
     #[error("glutin error")]
     GlutinCreationError(#[from] glutin_029::CreationError),
     #[error("glutin error")]
@@ -25,10 +27,25 @@ pub enum HeadlessError {
 /// For a graphics context associated with a window, see [WindowedContext](crate::WindowedContext).
 /// Can only be created on native, not on web.
 ///
+/// Everything is rendered into textures, see [RenderTarget](crate::RenderTarget), which can then be read back, for example to save images on a server or compare
+/// rendered images to reference images in automated tests.
+/// On Linux, the context is created without any display server if possible, using a surfaceless EGL context on one of the available GPUs or a software renderer like llvmpipe,
+/// so it also works in a continuous integration environment. Otherwise, the context is created using OSMesa or a hidden pbuffer surface, which requires a display server.
+///
 #[derive(Clone)]
 pub struct HeadlessContext {
     context: Context,
-    _glutin_context: Rc<glutin_029::Context<PossiblyCurrent>>,
+    _glutin_context: Rc<GlutinContext>,
+}
+
+#[allow(dead_code)]
+enum GlutinContext {
+    #[cfg(target_os = "linux")]
+    Egl(
+        glutin::api::egl::context::PossiblyCurrentContext,
+        glutin::api::egl::display::Display,
+    ),
+    Glutin029(glutin_029::Context<PossiblyCurrent>),
 }
 
 impl HeadlessContext {
@@ -36,7 +53,24 @@ impl HeadlessContext {
     /// Creates a new headless graphics context.
     ///
     #[allow(unsafe_code)]
+    #[allow(clippy::arc_with_non_send_sync)]
     pub fn new() -> Result<Self, HeadlessError> {
+        #[cfg(target_os = "linux")]
+        if let Ok((glutin_context, display)) = build_context_surfaceless_egl() {
+            use glutin::display::GlDisplay;
+            let context = Context::from_gl_context(std::sync::Arc::new(unsafe {
+                crate::context::Context::from_loader_function(|s| {
+                    let s = std::ffi::CString::new(s)
+                        .expect("failed to construct C string from string for gl proc address");
+                    display.get_proc_address(&s)
+                })
+            }))?;
+            return Ok(Self {
+                context,
+                _glutin_context: Rc::new(GlutinContext::Egl(glutin_context, display)),
+            });
+        }
+
         let cb = ContextBuilder::new();
         let glutin_context = build_context(cb)?;
         let glutin_context = unsafe { glutin_context.make_current().map_err(|(_, e)| e)? };
@@ -47,7 +81,7 @@ impl HeadlessContext {
         }))?;
         Ok(Self {
             context,
-            _glutin_context: Rc::new(glutin_context),
+            _glutin_context: Rc::new(GlutinContext::Glutin029(glutin_context)),
         })
     }
 }
@@ -59,21 +93,60 @@ impl std::ops::Deref for HeadlessContext {
     }
 }
 
-/*#[cfg(target_os = "linux")]
-fn build_context_surfaceless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-    el: &EventLoop<()>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    use glutin_029::platform::unix::HeadlessContextExt;
-    cb.build_surfaceless(&el)
-}*/
+#[cfg(target_os = "linux")]
+#[allow(unsafe_code)]
+fn build_context_surfaceless_egl() -> Result<
+    (
+        glutin::api::egl::context::PossiblyCurrentContext,
+        glutin::api::egl::display::Display,
+    ),
+    glutin::error::Error,
+> {
+    use glutin::api::egl::{device::Device, display::Display};
+    use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+    use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+    use glutin::prelude::*;
+
+    // Try each device, which includes software renderers, until a context with a supported version is created
+    let mut error = glutin::error::ErrorKind::NotFound.into();
+    for device in Device::query_devices()? {
+        let display = match unsafe { Display::with_device(&device, None) } {
+            Ok(display) => display,
+            Err(e) => {
+                error = e;
+                continue;
+            }
+        };
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let Some(config) = unsafe { display.find_configs(template)? }.next() else {
+            continue;
+        };
+        for api in [
+            ContextApi::OpenGl(Some(Version::new(3, 3))),
+            ContextApi::Gles(Some(Version::new(3, 0))),
+        ] {
+            let attributes = ContextAttributesBuilder::new()
+                .with_context_api(api)
+                .build(None);
+            match unsafe { display.create_context(&config, &attributes) }
+                .and_then(|context| context.make_current_surfaceless())
+            {
+                Ok(context) => return Ok((context, display)),
+                Err(e) => error = e,
+            }
+        }
+    }
+    Err(error)
+}
 
 fn build_context_headless<T1: ContextCurrentState>(
     cb: ContextBuilder<T1>,
     el: &EventLoop<()>,
 ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
     let size_one = PhysicalSize::new(1, 1);
-    cb.build_headless(&el, size_one)
+    cb.build_headless(el, size_one)
 }
 
 #[cfg(target_os = "linux")]
@@ -89,19 +162,8 @@ fn build_context_osmesa<T1: ContextCurrentState>(
 fn build_context<T1: ContextCurrentState>(
     cb: ContextBuilder<T1>,
 ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    // On unix operating systems, you should always try for surfaceless first,
-    // and if that does not work, headless (pbuffers), and if that too fails,
-    // finally osmesa.
-    //
-    // If willing, you could attempt to use hidden windows instead of os mesa,
-    // but note that you must handle events for the window that come on the
-    // events loop.
-
-    /*
-    let err1 = match build_context_surfaceless(cb.clone(), &el) {
-        Ok(ctx) => return Ok((ctx, el)),
-        Err(err) => err,
-    };*/
+    // Surfaceless EGL is tried before this, so fall back to osmesa
+    // and if that too fails, headless (pbuffers) which requires a display server.
 
     let _err3 = match build_context_osmesa(cb.clone()) {
         Ok(ctx) => return Ok(ctx),
@@ -1,7 +1,7 @@
 #![allow(unsafe_code)]
 use crate::core::{Context, CoreError, Viewport};
 use winit::event::{Event, WindowEvent};
-use winit::event_loop::{ControlFlow, EventLoop};
+use winit::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
 use winit::window::WindowBuilder;
 use winit::*;
 
@@ -17,6 +17,20 @@ pub use frame_input_generator::*;
 mod windowed_context;
 pub use windowed_context::*;
 
+mod fixed_timestep;
+pub use fixed_timestep::*;
+
+mod cursor;
+pub use cursor::*;
+
+mod fullscreen;
+pub use fullscreen::*;
+
+#[cfg(target_arch = "wasm32")]
+use instant::{Duration, Instant};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
 use thiserror::Error;
 ///
 /// Error associated with a window.
@@ -29,6 +43,10 @@ pub enum WindowError {
     GlutinError(#[from] glutin::error::Error),
     #[error("winit error")]
     WinitError(#[from] winit::error::OsError),
+    #[error("failed to change the cursor: {0}")]
+    CursorError(#[from] winit::error::ExternalError),
+    #[error("invalid window icon: {0}")]
+    InvalidIcon(#[from] winit::window::BadIcon),
     #[error("error in three-d")]
     ThreeDError(#[from] CoreError),
     #[error("the number of MSAA samples must be a power of two")]
@@ -60,6 +78,12 @@ pub enum WindowError {
     OESTextureFloatNotSupported(String),
     #[error("error in three-d")]
     ThreeDError(#[from] CoreError),
+    #[error("sharing resources between graphics contexts is not supported on web")]
+    SharedContextNotSupported,
+    #[error("failed to change the cursor: {0}")]
+    CursorError(#[from] winit::error::ExternalError),
+    #[error("invalid window icon: {0}")]
+    InvalidIcon(#[from] winit::window::BadIcon),
 }
 
 ///
@@ -70,13 +94,21 @@ pub enum WindowError {
 /// use [WindowedContext::from_winit_window] and [FrameInputGenerator].
 ///
 pub struct Window {
-    window: winit::window::Window,
+    window: std::rc::Rc<winit::window::Window>,
     event_loop: EventLoop<()>,
     #[cfg(target_arch = "wasm32")]
     closure: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+    #[cfg(target_arch = "wasm32")]
+    context_lost_closure: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+    #[cfg(target_arch = "wasm32")]
+    context_restored_closure: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+    #[cfg(target_arch = "wasm32")]
+    context_restored: std::rc::Rc<std::cell::Cell<bool>>,
     gl: WindowedContext,
     #[allow(dead_code)]
     maximized: bool,
+    max_fps: Option<u32>,
+    cursor_locked: std::rc::Rc<std::cell::Cell<bool>>,
 }
 
 impl Window {
@@ -103,7 +135,8 @@ impl Window {
                     window_settings.min_size.0,
                     window_settings.min_size.1,
                 ))
-                .with_decorations(!window_settings.borderless);
+                .with_decorations(!window_settings.borderless)
+                .with_window_icon(window_settings.icon.as_ref().map(icon).transpose()?);
 
             if let Some((width, height)) = window_settings.max_size {
                 window_builder
@@ -158,12 +191,14 @@ impl Window {
 
         let winit_window = window_builder.build(&event_loop)?;
         winit_window.focus_window();
-        Self::from_winit_window(
+        let mut window = Self::from_winit_window(
             winit_window,
             event_loop,
             window_settings.surface_settings,
             window_settings.max_size.is_none(),
-        )
+        )?;
+        window.set_max_fps(window_settings.max_fps);
+        Ok(window)
     }
 
     ///
@@ -177,6 +212,7 @@ impl Window {
         mut surface_settings: SurfaceSettings,
         maximized: bool,
     ) -> Result<Self, WindowError> {
+        winit_window.set_ime_allowed(true);
         let mut gl = WindowedContext::from_winit_window(&winit_window, surface_settings);
         if gl.is_err() {
             surface_settings.multisamples = 0;
@@ -198,41 +234,124 @@ impl Window {
             closure
         };
 
+        // Preventing the default behavior when the WebGL context is lost makes it possible to restore it.
+        // The render loop is paused while the context is lost and woken up when it is restored.
+        #[cfg(target_arch = "wasm32")]
+        let context_restored = std::rc::Rc::new(std::cell::Cell::new(false));
+        #[cfg(target_arch = "wasm32")]
+        let (context_lost_closure, context_restored_closure) = {
+            use wasm_bindgen::JsCast;
+            use winit::platform::web::WindowExtWebSys;
+            let context_lost_closure =
+                wasm_bindgen::closure::Closure::wrap(Box::new(move |event: web_sys::Event| {
+                    event.prevent_default();
+                }) as Box<dyn FnMut(_)>);
+            let proxy = event_loop.create_proxy();
+            let restored = context_restored.clone();
+            let context_restored_closure =
+                wasm_bindgen::closure::Closure::wrap(Box::new(move |_: web_sys::Event| {
+                    restored.set(true);
+                    proxy.send_event(()).ok();
+                }) as Box<dyn FnMut(_)>);
+            let canvas = winit_window.canvas();
+            canvas
+                .add_event_listener_with_callback(
+                    "webglcontextlost",
+                    context_lost_closure.as_ref().unchecked_ref(),
+                )
+                .expect("failed to listen to webgl context lost");
+            canvas
+                .add_event_listener_with_callback(
+                    "webglcontextrestored",
+                    context_restored_closure.as_ref().unchecked_ref(),
+                )
+                .expect("failed to listen to webgl context restored");
+            (context_lost_closure, context_restored_closure)
+        };
+
         Ok(Self {
-            window: winit_window,
+            window: std::rc::Rc::new(winit_window),
             event_loop,
             gl: gl?,
             #[cfg(target_arch = "wasm32")]
             closure,
+            #[cfg(target_arch = "wasm32")]
+            context_lost_closure,
+            #[cfg(target_arch = "wasm32")]
+            context_restored_closure,
+            #[cfg(target_arch = "wasm32")]
+            context_restored,
             maximized,
+            max_fps: None,
+            cursor_locked: Default::default(),
         })
     }
 
+    ///
+    /// Limits the number of frames rendered per second by the render loop, see [WindowSettings::max_fps].
+    ///
+    pub fn set_max_fps(&mut self, max_fps: Option<u32>) {
+        self.max_fps = max_fps.filter(|fps| *fps > 0);
+    }
+
     ///
     /// Start the main render loop which calls the `callback` closure each frame.
+    /// The frame rate is limited by vsync, see [SurfaceSettings::vsync], and optionally by [WindowSettings::max_fps].
     ///
     pub fn render_loop<F: 'static + FnMut(FrameInput) -> FrameOutput>(self, mut callback: F) {
         let mut frame_input_generator = FrameInputGenerator::from_winit_window(&self.window);
-        self.event_loop
-            .run(move |event, _, control_flow| match event {
+        let frame_duration = self
+            .max_fps
+            .map(|fps| Duration::from_secs_f64(1.0 / fps as f64));
+        let mut next_frame_time: Option<Instant> = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut frame_capture: Option<crate::window::FrameCapture> = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut capture_error: Option<crate::window::CaptureError> = None;
+        self.event_loop.run(move |event, _, control_flow| {
+            frame_input_generator.set_cursor_locked(self.cursor_locked.get());
+            match event {
                 Event::LoopDestroyed => {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Some(frame_capture) = frame_capture.take() {
+                        // There are no more frames to report the error in
+                        let _ = frame_capture.finish();
+                    }
                     #[cfg(target_arch = "wasm32")]
                     {
                         use wasm_bindgen::JsCast;
                         use winit::platform::web::WindowExtWebSys;
-                        self.window
-                            .canvas()
+                        let canvas = self.window.canvas();
+                        canvas
                             .remove_event_listener_with_callback(
                                 "contextmenu",
                                 self.closure.as_ref().unchecked_ref(),
                             )
                             .unwrap();
+                        canvas
+                            .remove_event_listener_with_callback(
+                                "webglcontextlost",
+                                self.context_lost_closure.as_ref().unchecked_ref(),
+                            )
+                            .unwrap();
+                        canvas
+                            .remove_event_listener_with_callback(
+                                "webglcontextrestored",
+                                self.context_restored_closure.as_ref().unchecked_ref(),
+                            )
+                            .unwrap();
                     }
                 }
-                Event::MainEventsCleared => {
-                    self.window.request_redraw();
-                }
+                Event::MainEventsCleared => match next_frame_time {
+                    Some(time) if Instant::now() < time => {
+                        *control_flow = ControlFlow::WaitUntil(time);
+                    }
+                    _ => self.window.request_redraw(),
+                },
                 Event::RedrawRequested(_) => {
+                    if let Some(frame_duration) = frame_duration {
+                        next_frame_time = Some(Instant::now() + frame_duration);
+                    }
                     #[cfg(target_arch = "wasm32")]
                     if self.maximized || option_env!("THREE_D_SCREENSHOT").is_some() {
                         use winit::platform::web::WindowExtWebSys;
@@ -250,8 +369,66 @@ impl Window {
                         });
                     }
 
-                    let frame_input = frame_input_generator.generate(&self.gl);
+                    #[cfg(target_arch = "wasm32")]
+                    if self.gl.is_context_lost() {
+                        *control_flow = ControlFlow::Wait;
+                        return;
+                    }
+                    #[cfg(target_arch = "wasm32")]
+                    let context_restored = self.context_restored.replace(false);
+                    #[cfg(target_arch = "wasm32")]
+                    if context_restored {
+                        self.gl.restore_after_context_loss().unwrap();
+                    }
+
+                    self.gl.end_frame();
+                    #[allow(unused_mut)]
+                    let mut frame_input = frame_input_generator.generate(&self.gl);
+                    #[cfg(target_arch = "wasm32")]
+                    {
+                        frame_input.context_restored = context_restored;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        frame_input.capture_error = capture_error.take().map(std::sync::Arc::new);
+                    }
                     let frame_output = callback(frame_input);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        let mut result = Ok(());
+                        match frame_output.capture {
+                            Some(CaptureCommand::Start(output)) => {
+                                if let Some(frame_capture) = frame_capture.take() {
+                                    result = frame_capture.finish();
+                                }
+                                frame_capture = Some(crate::window::FrameCapture::new(output));
+                            }
+                            Some(CaptureCommand::Stop) => {
+                                if let Some(frame_capture) = frame_capture.take() {
+                                    result = frame_capture.finish();
+                                }
+                            }
+                            None => {}
+                        }
+                        if let Some(capture) = &mut frame_capture {
+                            if frame_output.swap_buffers {
+                                let (width, height): (u32, u32) = self.window.inner_size().into();
+                                let render_target =
+                                    crate::core::RenderTarget::screen(&self.gl, width, height);
+                                if let Err(e) = capture.capture(&render_target) {
+                                    // Stop capturing, the error of the writer is more descriptive than the error of the capture
+                                    let finished = frame_capture.take().unwrap().finish();
+                                    result = result.and(finished).and(Err(e));
+                                }
+                            }
+                        }
+                        if let Err(e) = result {
+                            capture_error = Some(e);
+                        }
+                    }
+                    if let Some(fullscreen) = frame_output.fullscreen {
+                        self.window.set_fullscreen(fullscreen.into());
+                    }
                     if frame_output.exit {
                         *control_flow = ControlFlow::Exit;
                     } else {
@@ -261,6 +438,8 @@ impl Window {
                         }
                         if frame_output.wait_next_event {
                             *control_flow = ControlFlow::Wait;
+                        } else if let Some(time) = next_frame_time {
+                            *control_flow = ControlFlow::WaitUntil(time);
                         } else {
                             *control_flow = ControlFlow::Poll;
                             self.window.request_redraw();
@@ -280,8 +459,105 @@ impl Window {
                         _ => (),
                     }
                 }
+                Event::DeviceEvent { ref event, .. } => {
+                    frame_input_generator.handle_winit_device_event(event);
+                }
                 _ => (),
+            }
+        });
+    }
+
+    ///
+    /// Start the main render loop like [Window::render_loop], but with a separate `update` closure which is called with a fixed time step in milliseconds,
+    /// for example to run a physics simulation which should not depend on the frame rate.
+    /// Each frame, `update` is called zero or more times, see [FixedTimestep], before the `callback` closure renders the frame.
+    /// The `callback` closure is also given the time since the last update as a fraction of the time step, ie. a value between 0 and 1,
+    /// which can be used to interpolate between the previous and current state of the simulation.
+    ///
+    /// **Note:** The `update` closure is given the [FrameInput] of the current frame, so the same events are seen by all updates in one frame.
+    ///
+    pub fn render_loop_with_fixed_update<
+        U: 'static + FnMut(&FrameInput, f64),
+        F: 'static + FnMut(FrameInput, f64) -> FrameOutput,
+    >(
+        self,
+        time_step: f64,
+        mut update: U,
+        mut callback: F,
+    ) {
+        let mut fixed_timestep = FixedTimestep::new(time_step);
+        self.render_loop(move |frame_input| {
+            let alpha = fixed_timestep.update(frame_input.elapsed_time, |time_step| {
+                update(&frame_input, time_step)
             });
+            callback(frame_input, alpha)
+        });
+    }
+
+    ///
+    /// Returns a [RedrawRequester] which can be used to wake up the render loop and render a new frame
+    /// when it is waiting for the next event, see [FrameOutput::wait_next_event].
+    ///
+    pub fn redraw_requester(&self) -> RedrawRequester {
+        RedrawRequester {
+            proxy: self.event_loop.create_proxy(),
+        }
+    }
+
+    ///
+    /// Returns a [Cursor] which can be used to hide, lock or move the cursor or change its icon, also from within the render loop.
+    ///
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            window: self.window.clone(),
+            locked: self.cursor_locked.clone(),
+        }
+    }
+
+    ///
+    /// Returns the monitors connected to the computer, for example to choose a monitor or video mode for [Window::set_fullscreen].
+    /// On web this only returns the current monitor, if any.
+    ///
+    pub fn monitors(&self) -> Vec<Monitor> {
+        self.window
+            .available_monitors()
+            .map(|handle| handle.into())
+            .collect()
+    }
+
+    ///
+    /// Returns the monitor the window is on, if it can be determined.
+    ///
+    pub fn current_monitor(&self) -> Option<Monitor> {
+        self.window.current_monitor().map(|handle| handle.into())
+    }
+
+    ///
+    /// Sets the fullscreen mode of the window, see [Fullscreen].
+    /// To change the fullscreen mode from within the render loop, for example when pressing Alt+Enter, use [FrameOutput::fullscreen].
+    ///
+    pub fn set_fullscreen(&self, fullscreen: Fullscreen) {
+        self.window.set_fullscreen(fullscreen.into());
+    }
+
+    ///
+    /// Returns the current fullscreen mode of the window.
+    ///
+    pub fn fullscreen(&self) -> Fullscreen {
+        self.window.fullscreen().into()
+    }
+
+    ///
+    /// Sets the icon of the window, which is also shown in the taskbar, see [WindowSettings::icon].
+    /// On web this has no effect.
+    ///
+    pub fn set_icon(&self, texture: Option<&crate::CpuTexture>) -> Result<(), WindowError> {
+        self.window.set_window_icon(
+            texture
+                .map(|texture| icon(&WindowIcon::from(texture)))
+                .transpose()?,
+        );
+        Ok(())
     }
 
     ///
@@ -316,3 +592,31 @@ impl Window {
         (*self.gl).clone()
     }
 }
+
+fn icon(icon: &WindowIcon) -> Result<winit::window::Icon, WindowError> {
+    Ok(winit::window::Icon::from_rgba(
+        icon.rgba.clone(),
+        icon.width,
+        icon.height,
+    )?)
+}
+
+///
+/// Used to request a new frame from the render loop of a [Window], which is useful when rendering on demand, ie. when [FrameOutput::wait_next_event] is true,
+/// and something else than an event changes what should be rendered, for example when a model has finished loading or a simulation running in the background has progressed.
+/// Create it using [Window::redraw_requester]. On native, it can be cloned and sent to other threads.
+///
+#[derive(Clone, Debug)]
+pub struct RedrawRequester {
+    proxy: EventLoopProxy<()>,
+}
+
+impl RedrawRequester {
+    ///
+    /// Wakes up the render loop, if it is waiting for the next event, and renders a new frame.
+    /// Does nothing if the render loop has stopped.
+    ///
+    pub fn request_redraw(&self) {
+        self.proxy.send_event(()).ok();
+    }
+}
@@ -0,0 +1,88 @@
+use super::WindowError;
+use crate::control::PhysicalPoint;
+use std::cell::Cell;
+use std::rc::Rc;
+
+pub use winit::window::CursorIcon;
+
+///
+/// Defines whether and how the cursor is kept inside the window, see [Cursor::set_grab].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorGrab {
+    /// The cursor can move freely.
+    None,
+    /// The cursor is confined to the window area.
+    /// On web this is not supported.
+    Confined,
+    /// The cursor is locked at its position, but the movement of the mouse is still reported as [Event::MouseMotion](crate::Event::MouseMotion) events.
+    /// This is usually combined with hiding the cursor, for example to look around with a [FirstPersonControl](crate::FirstPersonControl) with [mouse_look](crate::FirstPersonControl::mouse_look) enabled.
+    /// On web this uses the pointer lock API, which only succeeds as a response to a user action, for example a mouse click.
+    /// On platforms where locking is not supported, for example Windows, the cursor is confined instead.
+    Locked,
+}
+
+///
+/// Controls the cursor of a [Window](super::Window), ie. its visibility, icon, position and whether it is kept inside the window.
+/// Create it using [Window::cursor](super::Window::cursor). It can be cloned and moved into the render loop.
+///
+#[derive(Clone, Debug)]
+pub struct Cursor {
+    pub(super) window: Rc<winit::window::Window>,
+    pub(super) locked: Rc<Cell<bool>>,
+}
+
+impl Cursor {
+    ///
+    /// Shows or hides the cursor when it is inside the window.
+    ///
+    pub fn set_visible(&self, visible: bool) {
+        self.window.set_cursor_visible(visible);
+    }
+
+    ///
+    /// Sets the icon of the cursor when it is inside the window.
+    ///
+    pub fn set_icon(&self, icon: CursorIcon) {
+        self.window.set_cursor_icon(icon);
+    }
+
+    ///
+    /// Sets whether and how the cursor is kept inside the window, see [CursorGrab].
+    ///
+    pub fn set_grab(&self, grab: CursorGrab) -> Result<(), WindowError> {
+        use winit::window::CursorGrabMode;
+        match grab {
+            CursorGrab::None => self.window.set_cursor_grab(CursorGrabMode::None)?,
+            CursorGrab::Confined => self.window.set_cursor_grab(CursorGrabMode::Confined)?,
+            CursorGrab::Locked => self
+                .window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined))?,
+        }
+        self.locked.set(grab == CursorGrab::Locked);
+        Ok(())
+    }
+
+    ///
+    /// Returns whether the cursor is locked, see [CursorGrab::Locked].
+    ///
+    pub fn is_locked(&self) -> bool {
+        self.locked.get()
+    }
+
+    ///
+    /// Moves the cursor to the given position in physical pixels, where (0, 0) is the bottom left corner of the window, the same as the positions in the [events](crate::Event).
+    /// On web this is not supported.
+    ///
+    pub fn set_position(&self, position: impl Into<PhysicalPoint>) -> Result<(), WindowError> {
+        let position = position.into();
+        let height = self.window.inner_size().height as f32;
+        self.window
+            .set_cursor_position(winit::dpi::PhysicalPosition::new(
+                position.x,
+                height - position.y,
+            ))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+///
+/// Runs updates, for example of a physics simulation, with a fixed time step independent of the frame rate.
+/// Each frame, the elapsed time is accumulated and the update is run as many times as the accumulated time allows.
+/// The remaining time, relative to the time step, can be used to interpolate between the previous and current state when rendering.
+///
+/// This is used by [Window::render_loop_with_fixed_update](crate::window::Window::render_loop_with_fixed_update),
+/// but can also be used directly, for example with a custom event loop.
+///
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    /// The time step of each update in milliseconds.
+    pub time_step: f64,
+    /// The maximum number of updates in one frame.
+    /// If the frame took longer than this number of time steps, for example if the application was paused, the remaining time is skipped
+    /// so the simulation does not fall further and further behind.
+    /// The default is 10.
+    pub max_updates_per_frame: u32,
+    accumulated_time: f64,
+}
+
+impl FixedTimestep {
+    ///
+    /// Creates a new fixed time step with the given time step in milliseconds.
+    ///
+    pub fn new(time_step: f64) -> Self {
+        Self {
+            time_step,
+            max_updates_per_frame: 10,
+            accumulated_time: 0.0,
+        }
+    }
+
+    ///
+    /// Adds the elapsed time in milliseconds, usually [FrameInput::elapsed_time](crate::window::FrameInput::elapsed_time), and calls the `update` closure with the time step
+    /// zero or more times until less than one time step of time remains.
+    /// Returns the remaining time as a fraction of the time step, ie. a value between 0 and 1, which can be used to interpolate between the previous and current state.
+    ///
+    pub fn update(&mut self, elapsed_time: f64, mut update: impl FnMut(f64)) -> f64 {
+        if self.time_step <= 0.0 {
+            return 0.0;
+        }
+        self.accumulated_time += elapsed_time;
+        let mut count = 0;
+        while self.accumulated_time >= self.time_step {
+            if count >= self.max_updates_per_frame {
+                self.accumulated_time %= self.time_step;
+                break;
+            }
+            update(self.time_step);
+            self.accumulated_time -= self.time_step;
+            count += 1;
+        }
+        self.accumulated_time / self.time_step
+    }
+}
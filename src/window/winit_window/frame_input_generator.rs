@@ -6,6 +6,7 @@ use instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 use winit::dpi::PhysicalSize;
+use winit::event::DeviceEvent;
 use winit::event::TouchPhase;
 use winit::event::WindowEvent;
 
@@ -13,6 +14,11 @@ use winit::event::WindowEvent;
 /// Use this to generate [FrameInput] for a new frame with a custom [winit](https://crates.io/crates/winit) window.
 /// [FrameInput] is automatically generated if using the default [Window](crate::window::Window).
 ///
+/// This makes it possible to drive three-d from an existing event loop and window, for example in a larger application,
+/// by passing the window events to [FrameInputGenerator::handle_winit_window_event] and calling [FrameInputGenerator::generate] each frame.
+/// The graphics context can be a [WindowedContext](crate::WindowedContext) or a [Context] created from the OpenGL context of the application using [Context::from_gl_context].
+/// To only render into a part of the window, use [FrameInputGenerator::generate_for_viewport].
+///
 pub struct FrameInputGenerator {
     last_time: Instant,
     first_frame: bool,
@@ -28,6 +34,12 @@ pub struct FrameInputGenerator {
     secondary_finger_id: Option<u64>,
     modifiers: Modifiers,
     mouse_pressed: Option<MouseButton>,
+    dragging_in_viewport: bool,
+    cursor_locked: bool,
+    ime_composing: bool,
+    gamepads: Vec<Gamepad>,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
 }
 
 impl FrameInputGenerator {
@@ -52,6 +64,12 @@ impl FrameInputGenerator {
             secondary_finger_id: None,
             modifiers: Modifiers::default(),
             mouse_pressed: None,
+            dragging_in_viewport: false,
+            cursor_locked: false,
+            ime_composing: false,
+            gamepads: Vec::new(),
+            #[cfg(feature = "gamepad")]
+            gilrs: gilrs::Gilrs::new().ok(),
         }
     }
 
@@ -62,8 +80,38 @@ impl FrameInputGenerator {
         Self::new(window.inner_size(), window.scale_factor())
     }
 
+    ///
+    /// Creates a new frame input generator for a surface with the given size in physical pixels and device pixel ratio, when there is no [winit](https://crates.io/crates/winit) window,
+    /// for example when rendering into an offscreen canvas in a web worker, see `WindowedContext::from_offscreen_canvas` which is available on web.
+    /// Then the events, for example sent from the main thread, are added using [FrameInputGenerator::push_event]
+    /// and changes to the size are given using [FrameInputGenerator::set_size].
+    ///
+    pub fn from_size(physical_width: u32, physical_height: u32, device_pixel_ratio: f32) -> Self {
+        Self::new(
+            PhysicalSize::new(physical_width, physical_height),
+            device_pixel_ratio as f64,
+        )
+    }
+
+    ///
+    /// Sets the size of the surface in physical pixels and the device pixel ratio, see [FrameInputGenerator::from_size].
+    /// When using a [winit](https://crates.io/crates/winit) window, this is handled by [FrameInputGenerator::handle_winit_window_event].
+    ///
+    pub fn set_size(&mut self, physical_width: u32, physical_height: u32, device_pixel_ratio: f32) {
+        self.device_pixel_ratio = device_pixel_ratio as f64;
+        self.resize(PhysicalSize::new(physical_width, physical_height));
+    }
+
+    ///
+    /// Adds an event to the [FrameInput] of the next frame, for example an event that is generated on another thread, see [FrameInputGenerator::from_size].
+    ///
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
     ///
     /// Generates [FrameInput] for a new frame. This should be called each frame and the generated data should only be used for one frame.
+    /// To collect render statistics in a custom event loop, also call [Context::end_frame] after rendering each frame.
     ///
     pub fn generate(&mut self, context: &Context) -> FrameInput {
         let now = Instant::now();
@@ -72,6 +120,8 @@ impl FrameInputGenerator {
             duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 * 1e-6;
         self.accumulated_time += elapsed_time;
         self.last_time = now;
+        #[cfg(feature = "gamepad")]
+        self.update_gamepads();
 
         let frame_input = FrameInput {
             events: self.events.drain(..).collect(),
@@ -80,8 +130,14 @@ impl FrameInputGenerator {
             viewport: self.viewport,
             window_width: self.window_width,
             window_height: self.window_height,
+            physical_window_width: self.viewport.width,
+            physical_window_height: self.viewport.height,
             device_pixel_ratio: self.device_pixel_ratio as f32,
             first_frame: self.first_frame,
+            context_restored: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            capture_error: None,
+            gamepads: self.gamepads.clone(),
             context: context.clone(),
         };
         self.first_frame = false;
@@ -114,26 +170,160 @@ impl FrameInputGenerator {
         frame_input
     }
 
+    ///
+    /// Generates [FrameInput] for a new frame like [FrameInputGenerator::generate], but for a viewport which only covers the given part of the window,
+    /// for example to embed a 3D view in a larger application which renders its own user interface into the rest of the window.
+    /// The viewport is given in physical pixels, where (0, 0) is the bottom left corner of the window, and is returned as [FrameInput::viewport].
+    /// Mouse and touch events outside the viewport are marked as handled, so they are ignored by the [controls](crate::renderer::control),
+    /// except when dragging with a mouse button that was pressed inside the viewport.
+    ///
+    pub fn generate_for_viewport(&mut self, context: &Context, viewport: Viewport) -> FrameInput {
+        let mut frame_input = self.generate(context);
+        frame_input.viewport = viewport;
+        let inside = |position: &PhysicalPoint| {
+            position.x >= viewport.x as f32
+                && position.x < viewport.x as f32 + viewport.width as f32
+                && position.y >= viewport.y as f32
+                && position.y < viewport.y as f32 + viewport.height as f32
+        };
+        for event in frame_input.events.iter_mut() {
+            match event {
+                Event::MousePress {
+                    position, handled, ..
+                } => {
+                    self.dragging_in_viewport = inside(position);
+                    *handled |= !self.dragging_in_viewport;
+                }
+                Event::MouseRelease {
+                    position, handled, ..
+                } => {
+                    *handled |= !self.dragging_in_viewport && !inside(position);
+                    self.dragging_in_viewport = false;
+                }
+                Event::MouseMotion {
+                    button,
+                    position,
+                    handled,
+                    ..
+                } => {
+                    *handled |=
+                        !(inside(position) || button.is_some() && self.dragging_in_viewport);
+                }
+                Event::MouseWheel {
+                    position, handled, ..
+                }
+                | Event::TouchGesture {
+                    position, handled, ..
+                } => {
+                    *handled |= !inside(position);
+                }
+                _ => {}
+            }
+        }
+        frame_input
+    }
+
+    ///
+    /// Turns the gamepad events since the last frame into [events](crate::Event) and updates the state of the connected gamepads.
+    ///
+    #[cfg(feature = "gamepad")]
+    fn update_gamepads(&mut self) {
+        let Some(gilrs) = &mut self.gilrs else {
+            return;
+        };
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            let id = GamepadId(id.into());
+            match event {
+                gilrs::EventType::Connected => {
+                    self.events.push(crate::Event::GamepadConnected { id });
+                }
+                gilrs::EventType::Disconnected => {
+                    self.events.push(crate::Event::GamepadDisconnected { id });
+                }
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    if let Some(button) = gamepad_button(button) {
+                        self.events.push(crate::Event::GamepadButtonPress {
+                            id,
+                            button,
+                            handled: false,
+                        });
+                    }
+                }
+                gilrs::EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = gamepad_button(button) {
+                        self.events.push(crate::Event::GamepadButtonRelease {
+                            id,
+                            button,
+                            handled: false,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.gamepads = gilrs
+            .gamepads()
+            .map(|(id, gamepad)| {
+                let mut state = Gamepad::new(GamepadId(id.into()), gamepad.name());
+                for (axis, gilrs_axis) in [
+                    (GamepadAxis::LeftStickX, gilrs::Axis::LeftStickX),
+                    (GamepadAxis::LeftStickY, gilrs::Axis::LeftStickY),
+                    (GamepadAxis::RightStickX, gilrs::Axis::RightStickX),
+                    (GamepadAxis::RightStickY, gilrs::Axis::RightStickY),
+                ] {
+                    state.set_axis(axis, gamepad.value(gilrs_axis));
+                }
+                // The triggers are analog buttons in gilrs
+                for (axis, gilrs_button) in [
+                    (GamepadAxis::LeftTrigger, gilrs::Button::LeftTrigger2),
+                    (GamepadAxis::RightTrigger, gilrs::Button::RightTrigger2),
+                ] {
+                    let value = gamepad.button_data(gilrs_button).map_or(0.0, |d| d.value());
+                    state.set_axis(axis, value);
+                }
+                for gilrs_button in GAMEPAD_BUTTONS {
+                    if let Some(button) = gamepad_button(gilrs_button) {
+                        state.set_pressed(button, gamepad.is_pressed(gilrs_button));
+                    }
+                }
+                state
+            })
+            .collect();
+    }
+
+    ///
+    /// Updates the size of the window and the stored cursor positions, which are in logical pixels, to the current size and device pixel ratio,
+    /// so the positions of the following events are converted to physical pixels correctly.
+    ///
+    fn resize(&mut self, physical_size: PhysicalSize<u32>) {
+        self.viewport = Viewport::new_at_origo(physical_size.width, physical_size.height);
+        let logical_size = physical_size.to_logical(self.device_pixel_ratio);
+        self.window_width = logical_size.width;
+        self.window_height = logical_size.height;
+        for position in [&mut self.cursor_pos, &mut self.secondary_cursor_pos]
+            .into_iter()
+            .flatten()
+        {
+            position.device_pixel_ratio = self.device_pixel_ratio as f32;
+            position.height = physical_size.height as f32;
+        }
+    }
+
     ///
     /// Handle the [WindowEvent] generated by a [winit](https://crates.io/crates/winit) event loop.
     ///
     pub fn handle_winit_window_event(&mut self, event: &WindowEvent) {
         match event {
             WindowEvent::Resized(physical_size) => {
-                self.viewport = Viewport::new_at_origo(physical_size.width, physical_size.height);
-                let logical_size = physical_size.to_logical(self.device_pixel_ratio);
-                self.window_width = logical_size.width;
-                self.window_height = logical_size.height;
+                self.resize(*physical_size);
             }
             WindowEvent::ScaleFactorChanged {
                 scale_factor,
                 new_inner_size,
             } => {
+                // For example when the window is moved to a monitor with another DPI or when zooming in the browser
                 self.device_pixel_ratio = *scale_factor;
-                self.viewport = Viewport::new_at_origo(new_inner_size.width, new_inner_size.height);
-                let logical_size = new_inner_size.to_logical(self.device_pixel_ratio);
-                self.window_width = logical_size.width;
-                self.window_height = logical_size.height;
+                self.resize(**new_inner_size);
             }
             WindowEvent::KeyboardInput { input, .. } => {
                 if let Some(keycode) = input.virtual_keycode {
@@ -239,7 +429,18 @@ impl FrameInputGenerator {
                 }
             }
             WindowEvent::CursorMoved { position, .. } => {
-                let p = position.to_logical(self.device_pixel_ratio);
+                let p: winit::dpi::LogicalPosition<f32> =
+                    position.to_logical(self.device_pixel_ratio);
+                if self.cursor_locked {
+                    // The movement is reported from the device events instead
+                    self.cursor_pos = Some(LogicalPoint {
+                        x: p.x,
+                        y: p.y,
+                        device_pixel_ratio: self.device_pixel_ratio as f32,
+                        height: self.viewport.height as f32,
+                    });
+                    return;
+                }
                 let delta = if let Some(last_pos) = self.cursor_pos {
                     (p.x - last_pos.x, p.y - last_pos.y)
                 } else {
@@ -260,11 +461,43 @@ impl FrameInputGenerator {
                 });
                 self.cursor_pos = Some(position);
             }
-            WindowEvent::ReceivedCharacter(ch) => {
-                if is_printable_char(*ch) && !self.modifiers.ctrl && !self.modifiers.command {
-                    self.events.push(crate::Event::Text(ch.to_string()));
-                }
+            WindowEvent::ReceivedCharacter(ch)
+                if is_printable_char(*ch) && !self.modifiers.ctrl && !self.modifiers.command =>
+            {
+                self.events.push(crate::Event::Text(ch.to_string()));
             }
+            WindowEvent::Ime(ime) => match ime {
+                winit::event::Ime::Preedit(text, cursor) => {
+                    if !self.ime_composing && !text.is_empty() {
+                        self.ime_composing = true;
+                        self.events.push(crate::Event::ImeCompositionStart);
+                    }
+                    if self.ime_composing {
+                        self.events.push(crate::Event::ImeCompositionUpdate {
+                            text: text.clone(),
+                            cursor: *cursor,
+                        });
+                    }
+                }
+                winit::event::Ime::Commit(text) => {
+                    if self.ime_composing {
+                        self.ime_composing = false;
+                        self.events
+                            .push(crate::Event::ImeCompositionEnd(text.clone()));
+                    }
+                    if !text.is_empty() {
+                        self.events.push(crate::Event::Text(text.clone()));
+                    }
+                }
+                winit::event::Ime::Disabled => {
+                    if self.ime_composing {
+                        self.ime_composing = false;
+                        self.events
+                            .push(crate::Event::ImeCompositionEnd(String::new()));
+                    }
+                }
+                winit::event::Ime::Enabled => {}
+            },
             WindowEvent::CursorEntered { .. } => {
                 self.events.push(crate::Event::MouseEnter);
             }
@@ -316,19 +549,22 @@ impl FrameInputGenerator {
                         }
                     }
                     TouchPhase::Moved => {
-                        if self.finger_id.map(|id| id == touch.id).unwrap_or(false) {
-                            let last_pos = self.cursor_pos.unwrap();
-                            if let Some(p) = self.secondary_cursor_pos {
-                                self.events.push(crate::Event::MouseWheel {
-                                    position: position.into(),
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                    delta: (
-                                        (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
-                                        (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
-                                    ),
-                                });
-                            } else {
+                        let is_primary = self.finger_id == Some(touch.id);
+                        let is_secondary = self.secondary_finger_id == Some(touch.id);
+                        match (self.cursor_pos, self.secondary_cursor_pos) {
+                            (Some(first), Some(second)) if is_primary || is_secondary => {
+                                let (new_first, new_second) = if is_primary {
+                                    (position, second)
+                                } else {
+                                    (first, position)
+                                };
+                                self.events.push(touch_gesture(
+                                    (first.into(), second.into()),
+                                    (new_first.into(), new_second.into()),
+                                    self.modifiers,
+                                ));
+                            }
+                            (Some(last_pos), _) if is_primary => {
                                 self.events.push(crate::Event::MouseMotion {
                                     button: Some(MouseButton::Left),
                                     position: position.into(),
@@ -337,24 +573,11 @@ impl FrameInputGenerator {
                                     delta: (position.x - last_pos.x, position.y - last_pos.y),
                                 });
                             }
+                            _ => {}
+                        }
+                        if is_primary {
                             self.cursor_pos = Some(position);
-                        } else if self
-                            .secondary_finger_id
-                            .map(|id| id == touch.id)
-                            .unwrap_or(false)
-                        {
-                            let last_pos = self.secondary_cursor_pos.unwrap();
-                            if let Some(p) = self.cursor_pos {
-                                self.events.push(crate::Event::MouseWheel {
-                                    position: p.into(),
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                    delta: (
-                                        (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
-                                        (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
-                                    ),
-                                });
-                            }
+                        } else if is_secondary {
                             self.secondary_cursor_pos = Some(position);
                         }
                     }
@@ -363,6 +586,117 @@ impl FrameInputGenerator {
             _ => (),
         }
     }
+
+    ///
+    /// Handle the [DeviceEvent] generated by a [winit](https://crates.io/crates/winit) event loop.
+    /// This is only needed when the cursor is locked, see [FrameInputGenerator::set_cursor_locked].
+    ///
+    pub fn handle_winit_device_event(&mut self, event: &DeviceEvent) {
+        if let DeviceEvent::MouseMotion { delta } = event {
+            if self.cursor_locked {
+                let position = self.cursor_pos.unwrap_or(LogicalPoint {
+                    x: 0.5 * self.window_width as f32,
+                    y: 0.5 * self.window_height as f32,
+                    device_pixel_ratio: self.device_pixel_ratio as f32,
+                    height: self.viewport.height as f32,
+                });
+                self.events.push(crate::Event::MouseMotion {
+                    button: self.mouse_pressed,
+                    delta: (
+                        (delta.0 / self.device_pixel_ratio) as f32,
+                        (delta.1 / self.device_pixel_ratio) as f32,
+                    ),
+                    position: position.into(),
+                    modifiers: self.modifiers,
+                    handled: false,
+                });
+            }
+        }
+    }
+
+    ///
+    /// Set whether or not the cursor is locked, for example using [Cursor::set_grab](crate::window::Cursor::set_grab) with [CursorGrab::Locked](crate::window::CursorGrab::Locked).
+    /// A locked cursor does not move, so while it is locked, [Event::MouseMotion] events are generated from the raw mouse movement
+    /// passed to [FrameInputGenerator::handle_winit_device_event] instead of from the cursor position.
+    ///
+    pub fn set_cursor_locked(&mut self, locked: bool) {
+        self.cursor_locked = locked;
+    }
+}
+
+///
+/// Returns the gesture given by the positions of two fingers before and after they moved.
+///
+fn touch_gesture(
+    (first, second): (PhysicalPoint, PhysicalPoint),
+    (new_first, new_second): (PhysicalPoint, PhysicalPoint),
+    modifiers: Modifiers,
+) -> crate::Event {
+    let center = |a: PhysicalPoint, b: PhysicalPoint| ((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+    let distance = |a: PhysicalPoint, b: PhysicalPoint| (b.x - a.x).hypot(b.y - a.y);
+    let angle = |a: PhysicalPoint, b: PhysicalPoint| (b.y - a.y).atan2(b.x - a.x);
+    let (x, y) = center(first, second);
+    let (new_x, new_y) = center(new_first, new_second);
+    let mut rotation = angle(new_first, new_second) - angle(first, second);
+    // Choose the smallest rotation when the angle wraps around
+    if rotation > std::f32::consts::PI {
+        rotation -= 2.0 * std::f32::consts::PI;
+    } else if rotation < -std::f32::consts::PI {
+        rotation += 2.0 * std::f32::consts::PI;
+    }
+    crate::Event::TouchGesture {
+        position: PhysicalPoint { x: new_x, y: new_y },
+        pan: (new_x - x, new_y - y),
+        pinch: distance(new_first, new_second) - distance(first, second),
+        rotation,
+        modifiers,
+        handled: false,
+    }
+}
+
+#[cfg(feature = "gamepad")]
+const GAMEPAD_BUTTONS: [gilrs::Button; 17] = [
+    gilrs::Button::South,
+    gilrs::Button::East,
+    gilrs::Button::North,
+    gilrs::Button::West,
+    gilrs::Button::LeftTrigger,
+    gilrs::Button::RightTrigger,
+    gilrs::Button::LeftTrigger2,
+    gilrs::Button::RightTrigger2,
+    gilrs::Button::Select,
+    gilrs::Button::Start,
+    gilrs::Button::Mode,
+    gilrs::Button::LeftThumb,
+    gilrs::Button::RightThumb,
+    gilrs::Button::DPadUp,
+    gilrs::Button::DPadDown,
+    gilrs::Button::DPadLeft,
+    gilrs::Button::DPadRight,
+];
+
+#[cfg(feature = "gamepad")]
+fn gamepad_button(button: gilrs::Button) -> Option<GamepadButton> {
+    Some(match button {
+        gilrs::Button::South => GamepadButton::South,
+        gilrs::Button::East => GamepadButton::East,
+        gilrs::Button::North => GamepadButton::North,
+        gilrs::Button::West => GamepadButton::West,
+        gilrs::Button::LeftTrigger => GamepadButton::LeftBumper,
+        gilrs::Button::RightTrigger => GamepadButton::RightBumper,
+        gilrs::Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        gilrs::Button::RightTrigger2 => GamepadButton::RightTrigger,
+        gilrs::Button::Select => GamepadButton::Select,
+        gilrs::Button::Start => GamepadButton::Start,
+        gilrs::Button::Mode => GamepadButton::Mode,
+        gilrs::Button::LeftThumb => GamepadButton::LeftThumb,
+        gilrs::Button::RightThumb => GamepadButton::RightThumb,
+        gilrs::Button::DPadUp => GamepadButton::DPadUp,
+        gilrs::Button::DPadDown => GamepadButton::DPadDown,
+        gilrs::Button::DPadLeft => GamepadButton::DPadLeft,
+        gilrs::Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    })
 }
 
 fn is_printable_char(chr: char) -> bool {
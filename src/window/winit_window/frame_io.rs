@@ -1,4 +1,5 @@
-use crate::control::Event;
+use super::Fullscreen;
+use crate::control::{Event, Gamepad};
 use crate::core::{Context, RenderTarget, Viewport};
 
 ///
@@ -21,7 +22,8 @@ pub struct FrameInput {
     /// Milliseconds accumulated time since start.
     pub accumulated_time: f64,
 
-    /// Viewport of the window in physical pixels (the size of the screen [RenderTarget] which is returned from [FrameInput::screen]).
+    /// Viewport of the window in physical pixels (the size of the screen [RenderTarget] which is returned from [FrameInput::screen]),
+    /// or of the part of the window given to [FrameInputGenerator::generate_for_viewport](crate::window::FrameInputGenerator::generate_for_viewport).
     pub viewport: Viewport,
 
     /// Width of the window in logical pixels.
@@ -30,12 +32,41 @@ pub struct FrameInput {
     /// Height of the window in logical pixels.
     pub window_height: u32,
 
+    /// Width of the window in physical pixels.
+    /// This is the same as the width of the [viewport](FrameInput::viewport), unless it only covers a part of the window.
+    pub physical_window_width: u32,
+
+    /// Height of the window in physical pixels.
+    /// This is the same as the height of the [viewport](FrameInput::viewport), unless it only covers a part of the window.
+    pub physical_window_height: u32,
+
     /// Number of physical pixels for each logical pixel.
+    /// This can change from one frame to the next, for example when the window is moved to a monitor with another DPI or when zooming in the browser,
+    /// in which case the [viewport](FrameInput::viewport) changes as well.
+    /// The positions in the [events](crate::Event) are always in physical pixels, the same as the viewport, and can therefore be used directly for picking,
+    /// while the movement in [Event::MouseMotion](crate::Event::MouseMotion) and [Event::MouseWheel](crate::Event::MouseWheel) events is in logical pixels.
     pub device_pixel_ratio: f32,
 
     /// Whether or not this is the first frame.
     pub first_frame: bool,
 
+    /// Whether or not this is the first frame after the graphics context was lost and restored, which can happen on web, for example when the GPU is reset.
+    /// All GPU resources, like textures, buffers and programs, are invalid when this is true and must be recreated, for example from the CPU-side data they were originally created from.
+    /// The objects are not recreated automatically, see [Context::restore_after_context_loss](crate::core::Context::restore_after_context_loss),
+    /// only the state of the [Context] itself is restored before this frame.
+    /// While the context is lost, the render loop is paused.
+    pub context_restored: bool,
+
+    /// The error that stopped capturing the frames started using [FrameOutput::capture], which is set in the frame after the error occurred.
+    /// Capturing is stopped when an error occurs, for example if `ffmpeg` is not installed or the output cannot be written, and the render loop continues.
+    /// Not available on web.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub capture_error: Option<std::sync::Arc<crate::window::CaptureError>>,
+
+    /// The state of the connected gamepads, which is only available when the `gamepad` feature is enabled.
+    /// Connecting and disconnecting gamepads and pressing and releasing gamepad buttons are also reported as [events](crate::Event).
+    pub gamepads: Vec<Gamepad>,
+
     /// The graphics context for the window.
     pub context: Context,
 }
@@ -49,8 +80,15 @@ impl FrameInput {
     /// RenderTarget::screen(&frame_input.context, frame_input.viewport.width, frame_input.viewport.height)
     /// ```
     ///
-    pub fn screen(&self) -> RenderTarget {
-        RenderTarget::screen(&self.context, self.viewport.width, self.viewport.height)
+    /// when the viewport covers the whole window. If the viewport only covers a part of the window, see [FrameInputGenerator::generate_for_viewport](crate::window::FrameInputGenerator::generate_for_viewport),
+    /// the render target covers the window up to the top right corner of the viewport, so use [RenderTarget::clear_partially] with the viewport to only clear the viewport.
+    ///
+    pub fn screen(&self) -> RenderTarget<'_> {
+        RenderTarget::screen(
+            &self.context,
+            (self.viewport.x.max(0) as u32) + self.viewport.width,
+            (self.viewport.y.max(0) as u32) + self.viewport.height,
+        )
     }
 }
 
@@ -77,8 +115,41 @@ pub struct FrameOutput {
 
     ///
     /// Whether to stop the render loop until next event.
+    /// Set this to true to only render when something has changed, for example in a viewer where the camera is only moved by user input,
+    /// instead of rendering the same frame continuously and wasting CPU, GPU and battery.
+    /// Use [Window::redraw_requester](crate::window::Window::redraw_requester) to render a new frame when something else than an event requires it.
     ///
     pub wait_next_event: bool,
+
+    ///
+    /// Changes the fullscreen mode of the window after this frame if specified, for example to toggle fullscreen when pressing Alt+Enter.
+    /// See also [Window::set_fullscreen](crate::window::Window::set_fullscreen).
+    ///
+    pub fullscreen: Option<Fullscreen>,
+
+    ///
+    /// Starts or stops capturing the rendered frames after this frame if specified, see [CaptureCommand].
+    /// Only frames where [FrameOutput::swap_buffers] is true are captured.
+    /// If writing the captured frames fails, capturing is stopped and the error is reported in [FrameInput::capture_error] of the next frame.
+    /// Errors when finishing the capture as the render loop stops cannot be reported, so stop capturing in an earlier frame to check that the capture succeeded.
+    /// Not available on web.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub capture: Option<CaptureCommand>,
+}
+
+///
+/// Starts or stops capturing the rendered frames of the default [Window](crate::window::Window), see [FrameOutput::capture].
+///
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum CaptureCommand {
+    /// Starts capturing the frames, beginning with the current frame, to the given output, see [FrameCapture](crate::window::FrameCapture).
+    /// If already capturing, the previous capture is finished first.
+    Start(crate::window::CaptureOutput),
+    /// Stops capturing and waits until the captured frames are written.
+    /// Capturing is also stopped when the render loop stops.
+    Stop,
 }
 
 impl Default for FrameOutput {
@@ -87,6 +158,9 @@ impl Default for FrameOutput {
             exit: false,
             swap_buffers: true,
             wait_next_event: false,
+            fullscreen: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            capture: None,
         }
     }
 }
@@ -0,0 +1,104 @@
+use winit::monitor::{MonitorHandle, VideoMode as WinitVideoMode};
+
+///
+/// A monitor connected to the computer, see [Window::monitors](super::Window::monitors).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    /// The name of the monitor, if available.
+    pub name: Option<String>,
+    /// The width of the monitor in physical pixels.
+    pub width: u32,
+    /// The height of the monitor in physical pixels.
+    pub height: u32,
+    /// Number of physical pixels for each logical pixel on this monitor.
+    pub device_pixel_ratio: f32,
+    /// The video modes supported by the monitor, which can be used for [Fullscreen::Exclusive].
+    pub video_modes: Vec<VideoMode>,
+    handle: MonitorHandle,
+}
+
+impl From<MonitorHandle> for Monitor {
+    fn from(handle: MonitorHandle) -> Self {
+        let size = handle.size();
+        Self {
+            name: handle.name(),
+            width: size.width,
+            height: size.height,
+            device_pixel_ratio: handle.scale_factor() as f32,
+            video_modes: handle.video_modes().map(|mode| mode.into()).collect(),
+            handle,
+        }
+    }
+}
+
+///
+/// A video mode, ie. a resolution, bit depth and refresh rate, supported by a [Monitor].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoMode {
+    /// The width in physical pixels.
+    pub width: u32,
+    /// The height in physical pixels.
+    pub height: u32,
+    /// The number of bits per pixel.
+    pub bit_depth: u16,
+    /// The refresh rate in millihertz.
+    pub refresh_rate_millihertz: u32,
+    mode: WinitVideoMode,
+}
+
+impl From<WinitVideoMode> for VideoMode {
+    fn from(mode: WinitVideoMode) -> Self {
+        let size = mode.size();
+        Self {
+            width: size.width,
+            height: size.height,
+            bit_depth: mode.bit_depth(),
+            refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+            mode,
+        }
+    }
+}
+
+///
+/// The fullscreen mode of a [Window](super::Window), see [Window::set_fullscreen](super::Window::set_fullscreen) and [FrameOutput::fullscreen](super::FrameOutput::fullscreen).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fullscreen {
+    /// Not fullscreen, ie. a normal window.
+    Off,
+    /// A borderless window covering the given monitor, or the current monitor if `None`, without changing the video mode.
+    /// This is usually the preferred fullscreen mode, since switching to and from it is fast.
+    /// On web this uses the fullscreen API, which only succeeds as a response to a user action, for example a key press.
+    Borderless(Option<Monitor>),
+    /// Exclusive fullscreen with the given video mode of one of the monitors.
+    /// On web this is not supported.
+    Exclusive(VideoMode),
+}
+
+impl From<Fullscreen> for Option<winit::window::Fullscreen> {
+    fn from(fullscreen: Fullscreen) -> Self {
+        match fullscreen {
+            Fullscreen::Off => None,
+            Fullscreen::Borderless(monitor) => Some(winit::window::Fullscreen::Borderless(
+                monitor.map(|monitor| monitor.handle),
+            )),
+            Fullscreen::Exclusive(video_mode) => {
+                Some(winit::window::Fullscreen::Exclusive(video_mode.mode))
+            }
+        }
+    }
+}
+
+impl From<Option<winit::window::Fullscreen>> for Fullscreen {
+    fn from(fullscreen: Option<winit::window::Fullscreen>) -> Self {
+        match fullscreen {
+            None => Fullscreen::Off,
+            Some(winit::window::Fullscreen::Borderless(handle)) => {
+                Fullscreen::Borderless(handle.map(|handle| handle.into()))
+            }
+            Some(winit::window::Fullscreen::Exclusive(mode)) => Fullscreen::Exclusive(mode.into()),
+        }
+    }
+}
@@ -20,6 +20,12 @@ pub struct SurfaceSettings {
     /// The default is true.
     /// On web this has no effect since vsync is always on.
     pub vsync: bool,
+    /// The number of display refreshes to wait before presenting a new frame when [SurfaceSettings::vsync] is on,
+    /// for example 2 limits the FPS to half the display refresh rate.
+    /// A value of 0 is treated as 1.
+    /// The default is 1.
+    /// On web this has no effect.
+    pub swap_interval: u32,
     /// Sets the number of bits in the depth buffer.
     /// A value of 0 means no depth buffer.
     /// The default value is 24.
@@ -46,6 +52,7 @@ impl Default for SurfaceSettings {
     fn default() -> Self {
         Self {
             vsync: true,
+            swap_interval: 1,
             depth_buffer: 24,
             stencil_buffer: 0,
             multisamples: 4,
@@ -78,6 +85,16 @@ pub struct WindowSettings {
     ///
     /// On web this has no effect.
     pub borderless: bool,
+    /// Limits the number of frames rendered per second by the [render loop](super::Window::render_loop), if specified.
+    /// This is useful to reduce the power consumption when vsync is off or not supported, or to render at a lower rate than the display refresh rate.
+    /// The default is `None`, ie. the frame rate is only limited by vsync, see [SurfaceSettings::vsync].
+    pub max_fps: Option<u32>,
+    /// The icon of the window, which is also shown in the taskbar, if specified.
+    /// Use [WindowIcon::from] to create the icon from a [CpuTexture](crate::CpuTexture).
+    /// The icon can also be changed later using [Window::set_icon](super::Window::set_icon).
+    ///
+    /// On web this has no effect.
+    pub icon: Option<WindowIcon>,
     /// An optional [canvas element][web_sys::HtmlCanvasElement] for using as winit window.
     /// If this is `None`, the DOM (`index.html`) must contain a canvas element
     #[cfg(target_arch = "wasm32")]
@@ -86,6 +103,71 @@ pub struct WindowSettings {
     /// Settings related to the surface on where to draw.
     pub surface_settings: SurfaceSettings,
 }
+
+///
+/// The icon of a window, see [WindowSettings::icon].
+/// The icon is stored as 8-bit sRGBA values, which are converted from the data of a [CpuTexture](crate::CpuTexture) assuming values between 0 and 1 for other formats.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowIcon {
+    pub(super) rgba: Vec<u8>,
+    pub(super) width: u32,
+    pub(super) height: u32,
+}
+
+impl From<&crate::CpuTexture> for WindowIcon {
+    fn from(texture: &crate::CpuTexture) -> Self {
+        use crate::TextureData;
+        fn to_u8(value: f32) -> u8 {
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        }
+        let rgba: Vec<u8> = match &texture.data {
+            TextureData::RU8(data) => data.iter().flat_map(|&r| [r, r, r, 255]).collect(),
+            TextureData::RgU8(data) => data.iter().flat_map(|&[r, a]| [r, r, r, a]).collect(),
+            TextureData::RgbU8(data) => data.iter().flat_map(|&[r, g, b]| [r, g, b, 255]).collect(),
+            TextureData::RgbaU8(data) => data.iter().flatten().copied().collect(),
+            TextureData::RF16(data) => data
+                .iter()
+                .flat_map(|r| [to_u8(r.to_f32()); 3].into_iter().chain([255]))
+                .collect(),
+            TextureData::RgF16(data) => data
+                .iter()
+                .flat_map(|[r, a]| {
+                    [to_u8(r.to_f32()); 3]
+                        .into_iter()
+                        .chain([to_u8(a.to_f32())])
+                })
+                .collect(),
+            TextureData::RgbF16(data) => data
+                .iter()
+                .flat_map(|c| c.map(|v| to_u8(v.to_f32())).into_iter().chain([255]))
+                .collect(),
+            TextureData::RgbaF16(data) => data
+                .iter()
+                .flat_map(|c| c.map(|v| to_u8(v.to_f32())))
+                .collect(),
+            TextureData::RF32(data) => data
+                .iter()
+                .flat_map(|&r| [to_u8(r); 3].into_iter().chain([255]))
+                .collect(),
+            TextureData::RgF32(data) => data
+                .iter()
+                .flat_map(|&[r, a]| [to_u8(r); 3].into_iter().chain([to_u8(a)]))
+                .collect(),
+            TextureData::RgbF32(data) => data
+                .iter()
+                .flat_map(|c| c.map(to_u8).into_iter().chain([255]))
+                .collect(),
+            TextureData::RgbaF32(data) => data.iter().flat_map(|c| c.map(to_u8)).collect(),
+        };
+        Self {
+            rgba,
+            width: texture.width,
+            height: texture.height,
+        }
+    }
+}
+
 impl Default for WindowSettings {
     fn default() -> Self {
         Self {
@@ -93,6 +175,8 @@ impl Default for WindowSettings {
             min_size: (2, 2),
             max_size: None,
             borderless: false,
+            max_fps: None,
+            icon: None,
             #[cfg(target_arch = "wasm32")]
             canvas: None,
             surface_settings: SurfaceSettings::default(),
@@ -25,6 +25,37 @@ mod inner {
     /// A context used for rendering
     pub struct WindowedContext {
         pub(super) context: Context,
+        webgl_context: web_sys::WebGl2RenderingContext,
+    }
+
+    fn context_options(settings: SurfaceSettings) -> wasm_bindgen::JsValue {
+        serde_wasm_bindgen::to_value(&ContextOpt {
+            antialias: settings.multisamples > 0,
+            depth: settings.depth_buffer > 0,
+            stencil: settings.stencil_buffer > 0,
+            willReadFrequently: match settings.hardware_acceleration {
+                HardwareAcceleration::Required => false,
+                HardwareAcceleration::Preferred => false,
+                HardwareAcceleration::Off => true,
+            },
+            alpha: false,
+        })
+        .unwrap()
+    }
+
+    fn enable_extensions(
+        webgl_context: &web_sys::WebGl2RenderingContext,
+    ) -> Result<(), WindowError> {
+        webgl_context
+            .get_extension("EXT_color_buffer_float")
+            .map_err(|e| WindowError::ColorBufferFloatNotSupported(format!("{:?}", e)))?;
+        webgl_context
+            .get_extension("OES_texture_float_linear")
+            .map_err(|e| WindowError::OESTextureFloatNotSupported(format!(": {:?}", e)))?;
+        webgl_context
+            .get_extension("OES_texture_half_float_linear")
+            .map_err(|e| WindowError::OESTextureFloatNotSupported(format!(": {:?}", e)))?;
+        Ok(())
     }
 
     impl WindowedContext {
@@ -34,48 +65,74 @@ mod inner {
             settings: SurfaceSettings,
         ) -> Result<Self, WindowError> {
             let canvas = window.canvas();
+            let webgl_context = canvas
+                .get_context_with_context_options("webgl2", &context_options(settings))
+                .map_err(|e| WindowError::WebGL2NotSupported(format!(": {:?}", e)))?;
+            Self::from_webgl_context(webgl_context)
+        }
 
-            // get webgl context and verify extensions
+        ///
+        /// Creates a new context from an [OffscreenCanvas](web_sys::OffscreenCanvas), for example to render in a web worker so rendering does not block the main thread.
+        /// The offscreen canvas is usually created on the main thread using `HtmlCanvasElement::transfer_control_to_offscreen` and sent to the worker,
+        /// which then renders each frame, for example when `requestAnimationFrame` is called in the worker, and the result is shown in the canvas on the main thread automatically.
+        /// Use [FrameInputGenerator::from_size](crate::window::FrameInputGenerator::from_size) to generate the frame input in the worker
+        /// from the events sent from the main thread.
+        ///
+        /// **Note:** The size of the offscreen canvas must be set using `OffscreenCanvas::set_width` and `OffscreenCanvas::set_height`, [WindowedContext::resize] has no effect.
+        ///
+        pub fn from_offscreen_canvas(
+            canvas: &web_sys::OffscreenCanvas,
+            settings: SurfaceSettings,
+        ) -> Result<Self, WindowError> {
             let webgl_context = canvas
-                .get_context_with_context_options(
-                    "webgl2",
-                    &serde_wasm_bindgen::to_value(&ContextOpt {
-                        antialias: settings.multisamples > 0,
-                        depth: settings.depth_buffer > 0,
-                        stencil: settings.stencil_buffer > 0,
-                        willReadFrequently: match settings.hardware_acceleration {
-                            HardwareAcceleration::Required => false,
-                            HardwareAcceleration::Preferred => false,
-                            HardwareAcceleration::Off => true,
-                        },
-                        alpha: false,
-                    })
-                    .unwrap(),
-                )
-                .map_err(|e| WindowError::WebGL2NotSupported(format!(": {:?}", e)))?
+                .get_context_with_context_options("webgl2", &context_options(settings))
+                .map_err(|e| WindowError::WebGL2NotSupported(format!(": {:?}", e)))?;
+            Self::from_webgl_context(webgl_context)
+        }
+
+        fn from_webgl_context(webgl_context: Option<impl JsCast>) -> Result<Self, WindowError> {
+            // get webgl context and verify extensions
+            let webgl_context = webgl_context
                 .ok_or(WindowError::WebGL2NotSupported("".to_string()))?
                 .dyn_into::<web_sys::WebGl2RenderingContext>()
                 .map_err(|e| WindowError::WebGL2NotSupported(format!(": {:?}", e)))?;
-            webgl_context
-                .get_extension("EXT_color_buffer_float")
-                .map_err(|e| WindowError::ColorBufferFloatNotSupported(format!("{:?}", e)))?;
-            webgl_context
-                .get_extension("OES_texture_float_linear")
-                .map_err(|e| WindowError::OESTextureFloatNotSupported(format!(": {:?}", e)))?;
-            webgl_context
-                .get_extension("OES_texture_half_float_linear")
-                .map_err(|e| WindowError::OESTextureFloatNotSupported(format!(": {:?}", e)))?;
+            enable_extensions(&webgl_context)?;
 
             Ok(Self {
                 context: Context::from_gl_context(Arc::new(
-                    crate::context::Context::from_webgl2_context(webgl_context),
+                    crate::context::Context::from_webgl2_context(webgl_context.clone()),
                 ))?,
+                webgl_context,
             })
         }
 
+        /// Returns whether the WebGL context is lost, in which case nothing can be rendered until it is restored.
+        pub fn is_context_lost(&self) -> bool {
+            self.webgl_context.is_context_lost()
+        }
+
+        ///
+        /// Prepares the context for rendering again after the WebGL context has been restored, see [Context::restore_after_context_loss](crate::core::Context::restore_after_context_loss).
+        /// This is called automatically when using the default [Window](crate::window::Window).
+        ///
+        pub fn restore_after_context_loss(&self) -> Result<(), WindowError> {
+            enable_extensions(&self.webgl_context)?;
+            self.context.restore_after_context_loss()?;
+            Ok(())
+        }
+
         /// Resizes the context
         pub fn resize(&self, _physical_size: winit::dpi::PhysicalSize<u32>) {}
 
+        /// Sharing resources between contexts is not supported on web, so this always returns [WindowError::SharedContextNotSupported].
+        pub fn from_winit_window_with_sharing(
+            _window: &Window,
+            _settings: SurfaceSettings,
+            _share_with: &WindowedContext,
+        ) -> Result<Self, WindowError> {
+            Err(WindowError::SharedContextNotSupported)
+        }
+
         /// Make this context current. Needed when using multiple windows (contexts) on native.
         pub fn make_current(&self) -> Result<(), WindowError> {
             Ok(())
@@ -101,14 +158,41 @@ mod inner {
         pub(super) context: Context,
         surface: Surface<WindowSurface>,
         glutin_context: glutin::context::PossiblyCurrentContext,
+        gl_display: glutin::display::Display,
+        vao: crate::context::VertexArray,
     }
 
     impl WindowedContext {
         /// Creates a new windowed context from a [winit](https://crates.io/crates/winit) window.
-        #[allow(unsafe_code)]
         pub fn from_winit_window(
             window: &Window,
             settings: SurfaceSettings,
+        ) -> Result<Self, WindowError> {
+            Self::new(window, settings, None)
+        }
+
+        ///
+        /// Creates a new windowed context from a [winit](https://crates.io/crates/winit) window which shares GPU resources with the given context,
+        /// so for example the same [Mesh](crate::Mesh) or [Texture2D](crate::Texture2D) can be rendered in several windows, for example a main viewport and preview windows in an editor.
+        /// The compiled shader programs are also shared, see [Context::programs](crate::core::Context::programs).
+        /// Use [WindowedContext::make_current] before rendering to a window.
+        ///
+        /// **Note:** Sharing resources is not supported on web, where an error is returned.
+        ///
+        pub fn from_winit_window_with_sharing(
+            window: &Window,
+            settings: SurfaceSettings,
+            share_with: &WindowedContext,
+        ) -> Result<Self, WindowError> {
+            Self::new(window, settings, Some(share_with))
+        }
+
+        #[allow(unsafe_code)]
+        #[allow(clippy::arc_with_non_send_sync)]
+        fn new(
+            window: &Window,
+            settings: SurfaceSettings,
+            share_with: Option<&WindowedContext>,
         ) -> Result<Self, WindowError> {
             if settings.multisamples > 0 && !settings.multisamples.is_power_of_two() {
                 Err(WindowError::InvalidNumberOfMSAASamples)?;
@@ -138,10 +222,16 @@ mod inner {
             #[cfg(target_os = "android")]
             let preference = glutin::display::DisplayApiPreference::Egl;
 
-            let gl_display =
-                unsafe { glutin::display::Display::new(raw_display_handle, preference)? };
+            // Resources can only be shared between contexts on the same display
+            let gl_display = if let Some(share_with) = share_with {
+                share_with.gl_display.clone()
+            } else {
+                unsafe { glutin::display::Display::new(raw_display_handle, preference)? }
+            };
             let swap_interval = if settings.vsync {
-                glutin::surface::SwapInterval::Wait(std::num::NonZeroU32::new(1).unwrap())
+                glutin::surface::SwapInterval::Wait(
+                    std::num::NonZeroU32::new(settings.swap_interval.max(1)).unwrap(),
+                )
             } else {
                 glutin::surface::SwapInterval::DontWait
             };
@@ -175,8 +265,13 @@ mod inner {
                     .ok_or(WindowError::SurfaceCreationError)?
             };
 
-            let context_attributes =
-                glutin::context::ContextAttributesBuilder::new().build(Some(raw_window_handle));
+            let context_attributes = glutin::context::ContextAttributesBuilder::new();
+            let context_attributes = if let Some(share_with) = share_with {
+                context_attributes.with_sharing(&share_with.glutin_context)
+            } else {
+                context_attributes
+            }
+            .build(Some(raw_window_handle));
             // for surface creation.
             let (width, height): (u32, u32) = window.inner_size().into();
             let width = std::num::NonZeroU32::new(width.max(1)).unwrap();
@@ -192,17 +287,32 @@ mod inner {
             let gl_context = gl_context.make_current(&gl_surface)?;
             gl_surface.set_swap_interval(&gl_context, swap_interval)?;
 
-            Ok(Self {
-                context: Context::from_gl_context(Arc::new(unsafe {
+            let (context, vao) = if let Some(share_with) = share_with {
+                // Vertex array objects are not shared, so the shared context needs its own
+                use crate::context::HasContext;
+                let vao = unsafe { share_with.context.create_vertex_array() }
+                    .map_err(|_| WindowError::SurfaceCreationError)?;
+                share_with.context.set_vertex_array(vao);
+                (share_with.context.clone(), vao)
+            } else {
+                let context = Context::from_gl_context(Arc::new(unsafe {
                     crate::context::Context::from_loader_function(|s| {
                         let s = std::ffi::CString::new(s)
                             .expect("failed to construct C string from string for gl proc address");
 
                         gl_display.get_proc_address(&s)
                     })
-                }))?,
+                }))?;
+                let vao = context.vertex_array();
+                (context, vao)
+            };
+
+            Ok(Self {
+                context,
                 glutin_context: gl_context,
                 surface: gl_surface,
+                gl_display,
+                vao,
             })
         }
 
@@ -215,7 +325,9 @@ mod inner {
 
         /// Make this context current. Needed when using multiple windows (contexts) on native.
         pub fn make_current(&self) -> Result<(), WindowError> {
-            Ok(self.glutin_context.make_current(&self.surface)?)
+            self.glutin_context.make_current(&self.surface)?;
+            self.context.set_vertex_array(self.vao);
+            Ok(())
         }
 
         /// Swap buffers - should always be called after rendering.